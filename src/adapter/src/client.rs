@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use std::collections::BTreeMap;
-use std::fmt::{Debug, Display, Formatter};
+use std::fmt::Debug;
 use std::future::Future;
 use std::pin::{self};
 use std::sync::Arc;
@@ -27,7 +27,6 @@ use mz_ore::collections::CollectionExt;
 use mz_ore::id_gen::{IdAllocator, IdAllocatorInnerBitSet};
 use mz_ore::now::{to_datetime, EpochMillis, NowFn};
 use mz_ore::result::ResultExt;
-use mz_ore::task::AbortOnDropHandle;
 use mz_ore::thread::JoinOnDropHandle;
 use mz_ore::tracing::OpenTelemetryContext;
 use mz_repr::{GlobalId, Row, ScalarType};
@@ -53,7 +52,7 @@ use crate::coord::{Coordinator, ExecuteContextExtra};
 use crate::error::AdapterError;
 use crate::metrics::Metrics;
 use crate::optimize::{self, Optimize};
-use crate::session::{EndTransactionAction, PreparedStatement, Session, TransactionId};
+use crate::session::{EndTransactionAction, PreparedStatement, Session, TransactionStatus};
 use crate::statement_logging::StatementEndedExecutionReason;
 use crate::telemetry::{self, SegmentClientExt, StatementFailureType};
 use crate::webhook::AppendWebhookResponse;
@@ -195,7 +194,7 @@ impl Client {
             session: Some(session),
             cancel_tx,
             cancel_rx,
-            timeouts: Timeout::new(),
+            idle_in_transaction: false,
             environment_id: self.environment_id.clone(),
             segment_client: self.segment_client.clone(),
         };
@@ -204,6 +203,7 @@ impl Client {
             role_id,
             write_notify,
             session_defaults,
+            prepared_statement_limits,
             catalog,
         } = response;
 
@@ -225,6 +225,7 @@ impl Client {
         session
             .vars_mut()
             .end_transaction(EndTransactionAction::Commit);
+        session.set_prepared_statement_limits(prepared_statement_limits, &self.metrics);
 
         let catalog = catalog.for_session(session);
         if catalog.active_database().is_none() {
@@ -365,7 +366,9 @@ pub struct SessionClient {
     session: Option<Session>,
     cancel_tx: Arc<watch::Sender<Canceled>>,
     cancel_rx: watch::Receiver<Canceled>,
-    timeouts: Timeout,
+    /// Whether the coordinator was told that the session is waiting for its
+    /// client while in a transaction.
+    idle_in_transaction: bool,
     segment_client: Option<mz_segment::Client>,
     environment_id: EnvironmentId,
 }
@@ -489,8 +492,7 @@ impl SessionClient {
             desc,
             catalog.transient_revision(),
             now,
-        );
-        Ok(())
+        )
     }
 
     /// Binds a statement to a portal.
@@ -650,6 +652,45 @@ impl SessionClient {
         }
     }
 
+    /// Starts the clock of `idle_in_transaction_session_timeout` if the
+    /// session is in an explicit transaction.
+    ///
+    /// Protocol layers call this once they have told their client that they're
+    /// ready for its next message (e.g. pgwire's `ReadyForQuery`), and not
+    /// before, so that a client still consuming the results of a statement,
+    /// like those of a `SUBSCRIBE`, isn't considered idle.
+    pub fn add_idle_in_transaction_session_timeout(&mut self) {
+        let session = self.session();
+        let timeout = *session.vars().idle_in_transaction_session_timeout();
+        if timeout.is_zero() {
+            return;
+        }
+        let txn_id = match session.transaction() {
+            TransactionStatus::InTransaction(txn) | TransactionStatus::Failed(txn) => txn.id,
+            _ => return,
+        };
+        let conn_id = session.conn_id().clone();
+        self.inner().send(Command::IdleInTransaction {
+            conn_id,
+            idle: Some((txn_id, Instant::now() + timeout)),
+        });
+        self.idle_in_transaction = true;
+    }
+
+    /// Stops the clock started by
+    /// [`SessionClient::add_idle_in_transaction_session_timeout`], now that
+    /// the client has sent its next message.
+    pub fn remove_idle_in_transaction_session_timeout(&mut self) {
+        if !std::mem::take(&mut self.idle_in_transaction) {
+            return;
+        }
+        let conn_id = self.session().conn_id().clone();
+        self.inner().send(Command::IdleInTransaction {
+            conn_id,
+            idle: None,
+        });
+    }
+
     /// Inserts a set of rows into the given table.
     ///
     /// The rows only contain the columns positions in `columns`, so they
@@ -703,6 +744,20 @@ impl SessionClient {
             .await
     }
 
+    /// Checks whether the specified system variables could be updated to the
+    /// specified values, without updating any of them.
+    ///
+    /// Unlike [`SessionClient::set_system_vars`], which fails on the first
+    /// invalid variable, this reports the outcome for each variable.
+    pub async fn validate_system_vars(
+        &mut self,
+        vars: BTreeMap<String, String>,
+    ) -> BTreeMap<String, Result<(), AdapterError>> {
+        let conn_id = self.session().conn_id().clone();
+        self.send_without_session(|tx| Command::ValidateSystemVars { vars, conn_id, tx })
+            .await
+    }
+
     /// Terminates the client session.
     pub async fn terminate(&mut self) {
         let conn_id = self.session().conn_id().clone();
@@ -777,9 +832,11 @@ impl SessionClient {
                 | Command::PrivilegedCancelRequest { .. }
                 | Command::GetSystemVars { .. }
                 | Command::SetSystemVars { .. }
+                | Command::ValidateSystemVars { .. }
                 | Command::Terminate { .. }
                 | Command::RetireExecute { .. }
-                | Command::CheckConsistency { .. } => {}
+                | Command::CheckConsistency { .. }
+                | Command::IdleInTransaction { .. } => {}
             };
             cmd
         });
@@ -814,38 +871,6 @@ impl SessionClient {
             };
         }
     }
-
-    pub fn add_idle_in_transaction_session_timeout(&mut self) {
-        let session = self.session();
-        let timeout_dur = session.vars().idle_in_transaction_session_timeout();
-        if !timeout_dur.is_zero() {
-            let timeout_dur = timeout_dur.clone();
-            if let Some(txn) = session.transaction().inner() {
-                let txn_id = txn.id.clone();
-                let timeout = TimeoutType::IdleInTransactionSession(txn_id);
-                self.timeouts.add_timeout(timeout, timeout_dur);
-            }
-        }
-    }
-
-    pub fn remove_idle_in_transaction_session_timeout(&mut self) {
-        let session = self.session();
-        if let Some(txn) = session.transaction().inner() {
-            let txn_id = txn.id.clone();
-            self.timeouts
-                .remove_timeout(&TimeoutType::IdleInTransactionSession(txn_id));
-        }
-    }
-
-    /// # Cancel safety
-    ///
-    /// This method is cancel safe. If `recv` is used as the event in a
-    /// `tokio::select!` statement and some other branch
-    /// completes first, it is guaranteed that no messages were received on this
-    /// channel.
-    pub async fn recv_timeout(&mut self) -> Option<TimeoutType> {
-        self.timeouts.recv().await
-    }
 }
 
 impl Drop for SessionClient {
@@ -866,86 +891,6 @@ impl Drop for SessionClient {
     }
 }
 
-#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
-pub enum TimeoutType {
-    IdleInTransactionSession(TransactionId),
-}
-
-impl Display for TimeoutType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            TimeoutType::IdleInTransactionSession(txn_id) => {
-                writeln!(f, "Idle in transaction session for transaction '{txn_id}'")
-            }
-        }
-    }
-}
-
-impl From<TimeoutType> for AdapterError {
-    fn from(timeout: TimeoutType) -> Self {
-        match timeout {
-            TimeoutType::IdleInTransactionSession(_) => {
-                AdapterError::IdleInTransactionSessionTimeout
-            }
-        }
-    }
-}
-
-struct Timeout {
-    tx: mpsc::UnboundedSender<TimeoutType>,
-    rx: mpsc::UnboundedReceiver<TimeoutType>,
-    active_timeouts: BTreeMap<TimeoutType, AbortOnDropHandle<()>>,
-}
-
-impl Timeout {
-    fn new() -> Self {
-        let (tx, rx) = mpsc::unbounded_channel();
-        Timeout {
-            tx,
-            rx,
-            active_timeouts: BTreeMap::new(),
-        }
-    }
-
-    /// # Cancel safety
-    ///
-    /// This method is cancel safe. If `recv` is used as the event in a
-    /// `tokio::select!` statement and some other branch
-    /// completes first, it is guaranteed that no messages were received on this
-    /// channel.
-    ///
-    /// <https://docs.rs/tokio/latest/tokio/sync/mpsc/struct.UnboundedReceiver.html#cancel-safety>
-    async fn recv(&mut self) -> Option<TimeoutType> {
-        self.rx.recv().await
-    }
-
-    fn add_timeout(&mut self, timeout: TimeoutType, duration: Duration) {
-        let tx = self.tx.clone();
-        let timeout_key = timeout.clone();
-        let handle = mz_ore::task::spawn(|| format!("{timeout_key}"), async move {
-            tokio::time::sleep(duration).await;
-            let _ = tx.send(timeout);
-        })
-        .abort_on_drop();
-        self.active_timeouts.insert(timeout_key, handle);
-    }
-
-    fn remove_timeout(&mut self, timeout: &TimeoutType) {
-        self.active_timeouts.remove(timeout);
-
-        // Remove the timeout from the rx queue if it exists.
-        let mut timeouts = Vec::new();
-        while let Ok(pending_timeout) = self.rx.try_recv() {
-            if timeout != &pending_timeout {
-                timeouts.push(pending_timeout);
-            }
-        }
-        for pending_timeout in timeouts {
-            self.tx.send(pending_timeout).expect("rx is in this struct");
-        }
-    }
-}
-
 /// A wrapper around an UnboundedReceiver of PeekResponseUnary that records when it sees the
 /// first row data in the given histogram
 #[derive(Derivative)]
@@ -11,6 +11,7 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
 use derivative::Derivative;
 use enum_kinds::EnumKind;
@@ -36,7 +37,9 @@ use crate::coord::consistency::CoordinatorInconsistencies;
 use crate::coord::peek::PeekResponseUnary;
 use crate::coord::ExecuteContextExtra;
 use crate::error::AdapterError;
-use crate::session::{EndTransactionAction, RowBatchStream, Session};
+use crate::session::{
+    EndTransactionAction, PreparedStatementLimits, RowBatchStream, Session, TransactionId,
+};
 use crate::statement_logging::StatementEndedExecutionReason;
 use crate::util::Transmittable;
 use crate::webhook::AppendWebhookResponse;
@@ -104,6 +107,14 @@ pub enum Command {
         tx: oneshot::Sender<Result<(), AdapterError>>,
     },
 
+    /// Runs the checks of [`Command::SetSystemVars`] against each of `vars`
+    /// without applying any of them, reporting the outcome per var.
+    ValidateSystemVars {
+        vars: BTreeMap<String, String>,
+        conn_id: ConnectionId,
+        tx: oneshot::Sender<BTreeMap<String, Result<(), AdapterError>>>,
+    },
+
     Terminate {
         conn_id: ConnectionId,
         tx: Option<oneshot::Sender<Result<(), AdapterError>>>,
@@ -123,6 +134,17 @@ pub enum Command {
     CheckConsistency {
         tx: oneshot::Sender<Result<(), CoordinatorInconsistencies>>,
     },
+
+    /// Starts or stops the clock of `idle_in_transaction_session_timeout` for
+    /// a session.
+    ///
+    /// `idle` is the transaction the session is waiting for its client in and
+    /// the time at which that transaction times out, or `None` once the
+    /// client has sent its next message.
+    IdleInTransaction {
+        conn_id: ConnectionId,
+        idle: Option<(TransactionId, Instant)>,
+    },
 }
 
 impl Command {
@@ -137,8 +159,10 @@ impl Command {
             | Command::Terminate { .. }
             | Command::GetSystemVars { .. }
             | Command::SetSystemVars { .. }
+            | Command::ValidateSystemVars { .. }
             | Command::RetireExecute { .. }
-            | Command::CheckConsistency { .. } => None,
+            | Command::CheckConsistency { .. }
+            | Command::IdleInTransaction { .. } => None,
         }
     }
 
@@ -153,8 +177,10 @@ impl Command {
             | Command::Terminate { .. }
             | Command::GetSystemVars { .. }
             | Command::SetSystemVars { .. }
+            | Command::ValidateSystemVars { .. }
             | Command::RetireExecute { .. }
-            | Command::CheckConsistency { .. } => None,
+            | Command::CheckConsistency { .. }
+            | Command::IdleInTransaction { .. } => None,
         }
     }
 }
@@ -179,6 +205,8 @@ pub struct StartupResponse {
     pub write_notify: BoxFuture<'static, ()>,
     /// Map of (name, VarInput::Flat) tuples of session default variables that should be set.
     pub session_defaults: BTreeMap<String, OwnedVarInput>,
+    /// Limits on the number of prepared statements and portals in the session.
+    pub prepared_statement_limits: PreparedStatementLimits,
     pub catalog: Arc<Catalog>,
 }
 
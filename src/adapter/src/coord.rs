@@ -92,6 +92,7 @@ use mz_catalog::config::{AwsPrincipalContext, ClusterReplicaSizeMap};
 use mz_catalog::memory::objects::{CatalogEntry, CatalogItem, Connection, DataSourceDesc, Source};
 use mz_cloud_resources::{CloudResourceController, VpcEndpointConfig, VpcEndpointEvent};
 use mz_compute_client::controller::error::InstanceMissing;
+use mz_compute_client::controller::DataflowActivation;
 use mz_compute_types::dataflows::DataflowDescription;
 use mz_compute_types::plan::Plan;
 use mz_compute_types::ComputeInstanceId;
@@ -155,7 +156,7 @@ use crate::optimize::dataflows::{
     dataflow_import_id_bundle, ComputeInstanceSnapshot, DataflowBuilder,
 };
 use crate::optimize::{self, Optimize, OptimizerConfig};
-use crate::session::{EndTransactionAction, Session};
+use crate::session::{EndTransactionAction, Session, TransactionId};
 use crate::statement_logging::StatementEndedExecutionReason;
 use crate::subscribe::ActiveSubscribe;
 use crate::util::{ClientTransmitter, CompletedClientTransmitter, ComputeSinkId, ResultExt};
@@ -216,6 +217,11 @@ pub enum Message<T = mz_repr::Timestamp> {
     RemovePendingPeeks {
         conn_id: ConnectionId,
     },
+    /// Stops tracking the DDL that `conn_id` has been optimizing off thread,
+    /// because its optimization failed.
+    RemoveOptimizingDdl {
+        conn_id: ConnectionId,
+    },
     LinearizeReads(Vec<PendingReadTxn>),
     StorageUsageFetch,
     StorageUsageUpdate(ShardsUsageReferenced),
@@ -265,6 +271,9 @@ pub enum Message<T = mz_repr::Timestamp> {
     },
     DrainStatementLog,
     PrivateLinkVpcEndpointEvents(Vec<VpcEndpointEvent>),
+    /// Rolls back the transactions that have been idle for longer than their
+    /// session's `idle_in_transaction_session_timeout`.
+    SweepIdleInTransaction,
 }
 
 impl Message {
@@ -281,9 +290,11 @@ impl Message {
                 Command::GetWebhook { .. } => "command-get_webhook",
                 Command::GetSystemVars { .. } => "command-get_system_vars",
                 Command::SetSystemVars { .. } => "command-set_system_vars",
+                Command::ValidateSystemVars { .. } => "command-validate_system_vars",
                 Command::Terminate { .. } => "command-terminate",
                 Command::RetireExecute { .. } => "command-retire_execute",
                 Command::CheckConsistency { .. } => "command-check_consistency",
+                Command::IdleInTransaction { .. } => "command-idle_in_transaction",
             },
             Message::ControllerReady => "controller_ready",
             Message::PurifiedStatementReady(_) => "purified_statement_ready",
@@ -294,6 +305,7 @@ impl Message {
             Message::AdvanceTimelines => "advance_timelines",
             Message::ClusterEvent(_) => "cluster_event",
             Message::RemovePendingPeeks { .. } => "remove_pending_peeks",
+            Message::RemoveOptimizingDdl { .. } => "remove_optimizing_ddl",
             Message::LinearizeReads(_) => "linearize_reads",
             Message::StorageUsageFetch => "storage_usage_fetch",
             Message::StorageUsageUpdate(_) => "storage_usage_update",
@@ -312,6 +324,7 @@ impl Message {
             Message::DrainStatementLog => "drain_statement_log",
             Message::AlterConnectionValidationReady(..) => "alter_connection_validation_ready",
             Message::PrivateLinkVpcEndpointEvents(_) => "private_link_vpc_endpoint_events",
+            Message::SweepIdleInTransaction => "sweep_idle_in_transaction",
         }
     }
 }
@@ -868,6 +881,13 @@ pub struct ConnMeta {
     /// WARNING: This role reference is not updated when the role is dropped.
     /// Consumers should not assume that this role exist.
     authenticated_role: RoleId,
+
+    /// The explicit transaction, if any, that the session has left idle, and
+    /// the time at which it times out if it stays idle.
+    idle_in_transaction: Option<(TransactionId, Instant)>,
+    /// The transaction, if any, that was rolled back for being idle for too
+    /// long, and whose rollback the session has yet to observe.
+    timed_out_transaction: Option<TransactionId>,
 }
 
 impl ConnMeta {
@@ -1266,6 +1286,14 @@ pub struct Coordinator {
     /// A map from client connection ids to a pending real time recency timestamps.
     pending_real_time_recency_timestamp: BTreeMap<ConnectionId, RealTimeRecencyContext>,
 
+    /// A map from client connection ids to whether the `CREATE INDEX` or
+    /// `CREATE MATERIALIZED VIEW` being optimized off thread for that client
+    /// has been canceled. There is at most one such DDL per session.
+    ///
+    /// A canceled DDL is retired once its optimization finishes, before
+    /// anything is written to the catalog or the controllers.
+    optimizing_ddls: BTreeMap<ConnectionId, bool>,
+
     /// A map from active subscribes to the subscribe description.
     active_subscribes: BTreeMap<GlobalId, ActiveSubscribe>,
     /// A map from active webhooks to their invalidation handle.
@@ -1612,7 +1640,7 @@ impl Coordinator {
 
                         self.controller
                             .active_compute()
-                            .create_dataflow(idx.cluster_id, df_desc)
+                            .create_dataflow(idx.cluster_id, df_desc, DataflowActivation::Immediate)
                             .unwrap_or_terminate("cannot fail to create dataflows");
                     }
                 }
@@ -2389,6 +2417,7 @@ impl Coordinator {
             self.schedule_storage_usage_collection().await;
             self.spawn_privatelink_vpc_endpoints_watch_task();
             self.spawn_statement_logging_task();
+            self.spawn_idle_in_transaction_sweep_task();
             flags::tracing_config(self.catalog.system_config()).apply(&self.tracing_handle);
 
             // Report if the handling of a single message takes longer than this threshold.
@@ -2621,7 +2650,7 @@ impl Coordinator {
 
         self.controller
             .active_compute()
-            .create_dataflow(instance, dataflow)
+            .create_dataflow(instance, dataflow, DataflowActivation::Immediate)
             .unwrap_or_terminate("dataflow creation cannot fail");
 
         self.initialize_compute_read_policies(export_ids, instance, CompactionWindow::Default)
@@ -2867,6 +2896,7 @@ pub fn serve(
                     pending_peeks: BTreeMap::new(),
                     client_pending_peeks: BTreeMap::new(),
                     pending_real_time_recency_timestamp: BTreeMap::new(),
+                    optimizing_ddls: BTreeMap::new(),
                     active_subscribes: BTreeMap::new(),
                     active_webhooks: BTreeMap::new(),
                     write_lock: Arc::new(tokio::sync::Mutex::new(())),
@@ -34,11 +34,10 @@ use mz_sql::plan::{
 use mz_sql::pure::{
     materialized_view_option_contains_temporal, purify_create_materialized_view_options,
 };
-use mz_sql::rbac;
-use mz_sql::rbac::CREATE_ITEM_USAGE;
+use mz_sql::rbac::{self, RbacTrace, CREATE_ITEM_USAGE};
 use mz_sql::session::user::User;
 use mz_sql::session::vars::{
-    EndTransactionAction, OwnedVarInput, Value, Var, STATEMENT_LOGGING_SAMPLE_RATE,
+    EndTransactionAction, OwnedVarInput, Value, Var, VarInput, STATEMENT_LOGGING_SAMPLE_RATE,
 };
 use mz_sql_parser::ast::{CreateMaterializedViewStatement, ExplainPlanStatement, Explainee};
 use mz_storage_types::sources::Timeline;
@@ -55,7 +54,7 @@ use crate::coord::peek::PendingPeek;
 use crate::coord::{ConnMeta, Coordinator, Message, PendingTxn, PurifiedStatementReady};
 use crate::error::AdapterError;
 use crate::notice::AdapterNotice;
-use crate::session::{Session, TransactionOps, TransactionStatus};
+use crate::session::{PreparedStatementLimits, Session, TransactionOps, TransactionStatus};
 use crate::util::{ClientTransmitter, ResultExt};
 use crate::webhook::{
     AppendWebhookResponse, AppendWebhookValidator, WebhookAppender, WebhookAppenderInvalidator,
@@ -101,18 +100,33 @@ impl Coordinator {
 
                 Command::Execute {
                     portal_name,
-                    session,
+                    mut session,
                     tx,
                     outer_ctx_extra,
                 } => {
                     let tx = ClientTransmitter::new(tx, self.internal_cmd_tx.clone());
 
+                    if let Err(err) = self.end_idle_in_transaction(&mut session) {
+                        let extra = outer_ctx_extra.unwrap_or_else(Default::default);
+                        let ctx = ExecuteContext::from_parts(
+                            tx,
+                            self.internal_cmd_tx.clone(),
+                            session,
+                            extra,
+                        );
+                        return ctx.retire(Err(err));
+                    }
+
                     self.handle_execute(portal_name, session, tx, outer_ctx_extra)
                         .await;
                 }
 
                 Command::RetireExecute { data, reason } => self.retire_execution(reason, data),
 
+                Command::IdleInTransaction { conn_id, idle } => {
+                    self.idle_in_transaction(&conn_id, idle);
+                }
+
                 Command::CancelRequest {
                     conn_id,
                     secret_key,
@@ -166,6 +180,25 @@ impl Coordinator {
                     let _ = tx.send(result);
                 }
 
+                Command::ValidateSystemVars { vars, conn_id, tx } => {
+                    let conn = &self.active_conns[&conn_id];
+                    let system_config = self.catalog().system_config();
+                    let results = vars
+                        .into_iter()
+                        .map(|(name, value)| {
+                            let result = system_config
+                                .get(&name)
+                                .and_then(|var| var.visible(conn.user(), Some(system_config)))
+                                .and_then(|()| {
+                                    system_config.validate(&name, VarInput::Flat(&value))
+                                })
+                                .map_err(AdapterError::from);
+                            (name, result)
+                        })
+                        .collect();
+                    let _ = tx.send(results);
+                }
+
                 Command::Terminate { conn_id, tx } => {
                     self.handle_terminate(conn_id).await;
                     // Note: We purposefully do not use a ClientTransmitter here because we're already
@@ -177,10 +210,13 @@ impl Coordinator {
 
                 Command::Commit {
                     action,
-                    session,
+                    mut session,
                     tx,
                 } => {
                     let tx = ClientTransmitter::new(tx, self.internal_cmd_tx.clone());
+                    if let Err(err) = self.end_idle_in_transaction(&mut session) {
+                        return tx.send(Err(err), session);
+                    }
                     // We reach here not through a statement execution, but from the
                     // "commit" pgwire command. Thus, we just generate a default statement
                     // execution context (once statement logging is implemented, this will cause nothing to be logged
@@ -257,6 +293,11 @@ impl Coordinator {
                     OwnedVarInput::Flat(statement_logging_default),
                 );
 
+                let prepared_statement_limits = PreparedStatementLimits {
+                    max: system_config.max_prepared_statements_per_session(),
+                    evict: system_config.enable_prepared_statement_eviction(),
+                };
+
                 // Override system defaults with role defaults.
                 session_defaults.extend(
                     self.catalog()
@@ -281,6 +322,8 @@ impl Coordinator {
                     uuid,
                     conn_id: conn_id.clone(),
                     authenticated_role: role_id,
+                    idle_in_transaction: None,
+                    timed_out_transaction: None,
                 };
                 let update = self.catalog().state().pack_session_update(&conn, 1);
                 self.begin_session_for_statement_logging(&conn);
@@ -294,6 +337,7 @@ impl Coordinator {
                     role_id,
                     write_notify: Box::pin(notify),
                     session_defaults,
+                    prepared_statement_limits,
                     catalog: self.owned_catalog(),
                 });
                 if tx.send(resp).is_err() {
@@ -650,14 +694,22 @@ impl Coordinator {
                     // Checks if the session is authorized to purify a statement. Usually
                     // authorization is checked after planning, however purification happens before
                     // planning, which may require the use of some connections and secrets.
+                    let mut rbac_trace =
+                        ctx.session().vars().rbac_debug().then(RbacTrace::default);
                     if let Err(e) = rbac::check_usage(
                         &catalog,
                         ctx.session().role_metadata(),
                         ctx.session().vars(),
                         &resolved_ids,
                         &CREATE_ITEM_USAGE,
+                        rbac_trace.as_mut(),
                     ) {
-                        return ctx.retire(Err(e.into()));
+                        if let Some(trace) = &rbac_trace {
+                            ctx.session_mut().add_notice(AdapterNotice::RbacTrace {
+                                trace: trace.clone(),
+                            });
+                        }
+                        return ctx.retire(Err(AdapterError::unauthorized(e, rbac_trace)));
                     }
 
                     let result = mz_sql::pure::purify_statement(
@@ -895,6 +947,14 @@ impl Coordinator {
                 ctx.retire(Ok(ExecuteResponse::Canceled));
             }
 
+            // Cancel DDL that is still being optimized. Its context is owned by
+            // the optimization task, so it's retired when that task hands the
+            // DDL back to us.
+            if let Some(canceled) = self.optimizing_ddls.get_mut(&conn_id) {
+                *canceled = true;
+                fail::fail_point!("cancel_optimizing_ddl");
+            }
+
             // Inform the target session (if it asks) about the cancellation.
             let _ = conn_meta.cancel_tx.send(Canceled::Canceled);
 
@@ -946,6 +1006,7 @@ impl Coordinator {
             .with_label_values(&[session_type])
             .dec();
         self.cancel_pending_peeks(conn.conn_id());
+        self.optimizing_ddls.remove(conn.conn_id());
         self.end_session_for_statement_logging(conn.uuid());
 
         // Queue the builtin table update, but do not wait for it to complete. We explicitly do
@@ -110,6 +110,9 @@ impl Coordinator {
                 Message::RemovePendingPeeks { conn_id } => {
                     self.cancel_pending_peeks(&conn_id);
                 }
+                Message::RemoveOptimizingDdl { conn_id } => {
+                    self.optimizing_ddls.remove(&conn_id);
+                }
                 Message::LinearizeReads(pending_read_txns) => {
                     self.message_linearize_reads(pending_read_txns).await;
                 }
@@ -193,6 +196,9 @@ impl Coordinator {
                 Message::DrainStatementLog => {
                     self.drain_statement_log().await;
                 }
+                Message::SweepIdleInTransaction => {
+                    self.sweep_idle_in_transaction();
+                }
                 Message::PrivateLinkVpcEndpointEvents(events) => {
                     self.controller
                         .storage
@@ -356,7 +362,17 @@ impl Coordinator {
                 // We can also potentially receive multiple `Complete` responses, followed by
                 // a `Dropped` response.
                 if let Some(active_subscribe) = self.active_subscribes.get_mut(&sink_id) {
+                    let cluster_id = active_subscribe.cluster_id;
                     let remove = active_subscribe.process_response(response);
+                    // Let the controller know how much output the client has drained since the
+                    // last response, so it doesn't consider the subscribe too slow.
+                    let bytes = active_subscribe.channel.take_drained_bytes();
+                    if !remove && bytes > 0 {
+                        let _ = self
+                            .controller
+                            .compute
+                            .ack_subscribe_output(cluster_id, sink_id, bytes);
+                    }
                     if remove {
                         let csid = ComputeSinkId {
                             cluster_id: active_subscribe.cluster_id,
@@ -26,7 +26,7 @@ use mz_sql::plan::{
     self, AbortTransactionPlan, CommitTransactionPlan, CreateRolePlan, CreateSourcePlans,
     FetchPlan, MutationKind, Params, Plan, PlanKind, QueryWhen, RaisePlan,
 };
-use mz_sql::rbac;
+use mz_sql::rbac::{self, RbacTrace};
 use mz_sql_parser::ast::{Raw, Statement};
 use mz_storage_types::connections::inline::IntoInlineConnection;
 use std::sync::Arc;
@@ -112,6 +112,7 @@ impl Coordinator {
                 return ctx.retire(Err(e));
             }
 
+            let mut rbac_trace = ctx.session().vars().rbac_debug().then(RbacTrace::default);
             if let Err(e) = rbac::check_plan(
                 &session_catalog,
                 &self
@@ -126,8 +127,14 @@ impl Coordinator {
                 &plan,
                 target_cluster_id,
                 &resolved_ids,
+                rbac_trace.as_mut(),
             ) {
-                return ctx.retire(Err(e.into()));
+                if let Some(trace) = &rbac_trace {
+                    ctx.session_mut().add_notice(AdapterNotice::RbacTrace {
+                        trace: trace.clone(),
+                    });
+                }
+                return ctx.retire(Err(AdapterError::unauthorized(e, rbac_trace)));
             }
 
             match plan {
@@ -488,7 +495,7 @@ impl Coordinator {
                     {
                         ctx.retire(Err(AdapterError::PreparedStatementExists(plan.name)));
                     } else {
-                        ctx.session_mut().set_prepared_statement(
+                        let result = ctx.session_mut().set_prepared_statement(
                             plan.name,
                             Some(plan.stmt),
                             plan.sql,
@@ -496,7 +503,7 @@ impl Coordinator {
                             self.catalog().transient_revision(),
                             self.now(),
                         );
-                        ctx.retire(Ok(ExecuteResponse::Prepare));
+                        ctx.retire(result.map(|()| ExecuteResponse::Prepare));
                     }
                 }
                 Plan::Execute(plan) => {
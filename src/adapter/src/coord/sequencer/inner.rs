@@ -140,6 +140,12 @@ struct CreateSourceInner {
 }
 
 impl Coordinator {
+    /// Stops tracking the DDL that `conn_id` has been optimizing off thread,
+    /// returning whether it was canceled in the meantime.
+    pub(super) fn take_optimizing_ddl_canceled(&mut self, conn_id: &ConnectionId) -> bool {
+        self.optimizing_ddls.remove(conn_id).unwrap_or(false)
+    }
+
     async fn create_source_inner(
         &mut self,
         session: &Session,
@@ -108,6 +108,11 @@ impl Coordinator {
     ) {
         use CreateIndexStage::*;
 
+        // A DDL that is back from being optimized off thread can't be canceled
+        // anymore, so stop tracking it, whatever happens to it next.
+        let canceled = matches!(stage, Finish(_))
+            && self.take_optimizing_ddl_canceled(ctx.session().conn_id());
+
         // Process the current stage and allow for processing the next.
         loop {
             // Always verify plan validity. This is cheap, and prevents programming errors
@@ -127,6 +132,18 @@ impl Coordinator {
                     return;
                 }
                 Finish(stage) => {
+                    // The DDL might have been canceled while it was being
+                    // optimized. Nothing has been committed yet, so there is
+                    // nothing to roll back.
+                    if canceled {
+                        ctx.session()
+                            .add_notice(AdapterNotice::DdlCanceledBeforeCommit {
+                                name: stage.plan.name.item.clone(),
+                                ty: "index",
+                            });
+                        ctx.retire(Ok(ExecuteResponse::Canceled));
+                        return;
+                    }
                     let result = self.create_index_finish(&mut ctx, stage).await;
                     ctx.retire(result);
                     return;
@@ -213,9 +230,17 @@ impl Coordinator {
             optimizer_config,
         );
 
+        // Track the DDL while it's out of our hands, so that it can be canceled.
+        if explain_ctx.is_none() {
+            self.optimizing_ddls
+                .insert(ctx.session().conn_id().clone(), false);
+        }
+
         mz_ore::task::spawn_blocking(
             || "optimize create index",
             move || {
+                fail::fail_point!("optimize_create_index");
+
                 let mut pipeline = || -> Result<(
                     optimize::index::GlobalMirPlan,
                     optimize::index::GlobalLirPlan,
@@ -289,7 +314,11 @@ impl Coordinator {
                     Err(err) => {
                         let Some(explain_ctx) = explain_ctx else {
                             // In `sequence_~` contexts, immediately retire the
-                            // execution with the error.
+                            // execution with the error, after making sure the
+                            // DDL isn't tracked for cancellation anymore.
+                            let _ = internal_cmd_tx.send(Message::RemoveOptimizingDdl {
+                                conn_id: ctx.session().conn_id().clone(),
+                            });
                             return ctx.retire(Err(err.into()));
                         };
 
@@ -114,6 +114,11 @@ impl Coordinator {
     ) {
         use CreateMaterializedViewStage::*;
 
+        // A DDL that is back from being optimized off thread can't be canceled
+        // anymore, so stop tracking it, whatever happens to it next.
+        let canceled = matches!(stage, Finish(_))
+            && self.take_optimizing_ddl_canceled(ctx.session().conn_id());
+
         // Process the current stage and allow for processing the next.
         loop {
             // Always verify plan validity. This is cheap, and prevents programming errors
@@ -136,6 +141,18 @@ impl Coordinator {
                     return;
                 }
                 Finish(stage) => {
+                    // The DDL might have been canceled while it was being
+                    // optimized. Nothing has been committed yet, so there is
+                    // nothing to roll back.
+                    if canceled {
+                        ctx.session()
+                            .add_notice(AdapterNotice::DdlCanceledBeforeCommit {
+                                name: stage.plan.name.item.clone(),
+                                ty: "materialized view",
+                            });
+                        ctx.retire(Ok(ExecuteResponse::Canceled));
+                        return;
+                    }
                     let result = self.create_materialized_view_finish(&mut ctx, stage).await;
                     ctx.retire(result);
                     return;
@@ -264,9 +281,17 @@ impl Coordinator {
             optimizer_config,
         );
 
+        // Track the DDL while it's out of our hands, so that it can be canceled.
+        if explain_ctx.is_none() {
+            self.optimizing_ddls
+                .insert(ctx.session().conn_id().clone(), false);
+        }
+
         mz_ore::task::spawn_blocking(
             || "optimize create materialized view",
             move || {
+                fail::fail_point!("optimize_create_materialized_view");
+
                 let mut pipeline = || -> Result<(
                     optimize::materialized_view::LocalMirPlan,
                     optimize::materialized_view::GlobalMirPlan,
@@ -356,7 +381,11 @@ impl Coordinator {
                     Err(err) => {
                         let Some(explain_ctx) = explain_ctx else {
                             // In `sequence_~` contexts, immediately retire the
-                            // execution with the error.
+                            // execution with the error, after making sure the
+                            // DDL isn't tracked for cancellation anymore.
+                            let _ = internal_cmd_tx.send(Message::RemoveOptimizingDdl {
+                                conn_id: ctx.session().conn_id().clone(),
+                            });
                             return ctx.retire(Err(err.into()));
                         };
 
@@ -10,7 +10,6 @@
 use mz_ore::tracing::OpenTelemetryContext;
 use mz_sql::plan::{self, QueryWhen};
 use timely::progress::Antichain;
-use tokio::sync::mpsc;
 
 use crate::command::ExecuteResponse;
 use crate::coord::sequencer::inner::{check_log_reads, return_if_err};
@@ -20,7 +19,7 @@ use crate::coord::{
 };
 use crate::error::AdapterError;
 use crate::optimize::Optimize;
-use crate::session::{Session, TransactionOps};
+use crate::session::{row_batch_channel, Session, TransactionOps};
 use crate::subscribe::ActiveSubscribe;
 use crate::util::{ComputeSinkId, ResultExt};
 use crate::{optimize, AdapterNotice, ExecuteContext, TimelineContext};
@@ -350,7 +349,7 @@ impl Coordinator {
     ) -> Result<ExecuteResponse, AdapterError> {
         let sink_id = global_lir_plan.sink_id();
 
-        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx, rx) = row_batch_channel();
         let active_subscribe = ActiveSubscribe {
             user: ctx.session().user().clone(),
             conn_id: ctx.session().conn_id().clone(),
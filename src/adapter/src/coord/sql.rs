@@ -10,21 +10,30 @@
 //! Various utility methods used by the [`Coordinator`]. Ideally these are all
 //! put in more meaningfully named modules.
 
+use std::time::{Duration, Instant};
+
 use mz_adapter_types::connection::ConnectionId;
 use mz_ore::now::EpochMillis;
+use mz_ore::task::spawn;
 use mz_repr::{GlobalId, ScalarType};
 use mz_sql::names::{Aug, ResolvedIds};
 use mz_sql::plan::{Params, StatementDesc};
 use mz_sql_parser::ast::display::AstDisplay;
 use mz_sql_parser::ast::{Raw, Statement, StatementKind};
+use tokio::time::MissedTickBehavior;
+use tracing::info;
 
 use crate::catalog::Catalog;
 use crate::coord::appends::BuiltinTableAppendNotify;
-use crate::coord::Coordinator;
-use crate::session::{Session, TransactionStatus};
+use crate::coord::{Coordinator, Message};
+use crate::session::{Session, TransactionId, TransactionStatus};
 use crate::subscribe::ActiveSubscribe;
 use crate::util::describe;
-use crate::{metrics, AdapterError, ExecuteContext, ExecuteResponse};
+use crate::{metrics, AdapterError, AdapterNotice, ExecuteContext, ExecuteResponse};
+
+/// How often the coordinator checks for transactions that have been idle for
+/// longer than their session's `idle_in_transaction_session_timeout`.
+const IDLE_IN_TRANSACTION_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
 
 impl Coordinator {
     pub(crate) fn plan_statement(
@@ -128,6 +137,7 @@ impl Coordinator {
                 .expect("known to exist");
             ps.catalog_revision = revision;
         }
+        session.touch_prepared_statement(name);
 
         Ok(())
     }
@@ -154,6 +164,7 @@ impl Coordinator {
                 .expect("known to exist");
             portal.catalog_revision = revision;
         }
+        session.touch_portal(name);
         Ok(())
     }
 
@@ -213,6 +224,104 @@ impl Coordinator {
         }
     }
 
+    /// Spawns a task that periodically asks the coordinator to roll back the
+    /// transactions that have been idle for too long.
+    pub(crate) fn spawn_idle_in_transaction_sweep_task(&self) {
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        spawn(|| "idle_in_transaction_sweep", async move {
+            // `idle_in_transaction_session_timeout` is only enforced at this
+            // granularity, which is plenty for timeouts measured in minutes.
+            let mut interval = tokio::time::interval(IDLE_IN_TRANSACTION_SWEEP_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                if internal_cmd_tx
+                    .send(Message::SweepIdleInTransaction)
+                    .is_err()
+                {
+                    // The coordinator is shutting down.
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Records that the session of `conn_id` was left idle in a transaction,
+    /// which times out at the given deadline unless the session does
+    /// something first, or, if `idle` is `None`, that it's no longer idle.
+    pub(crate) fn idle_in_transaction(
+        &mut self,
+        conn_id: &ConnectionId,
+        idle: Option<(TransactionId, Instant)>,
+    ) {
+        // The connection might have been terminated in the meantime.
+        if let Some(conn_meta) = self.active_conns.get_mut(conn_id) {
+            conn_meta.idle_in_transaction = idle;
+        }
+    }
+
+    /// Rolls back the transactions that have been idle for longer than their
+    /// session's `idle_in_transaction_session_timeout`.
+    ///
+    /// The coordinator state of each transaction, like its read holds, is
+    /// released immediately, and the session is sent a notice. The session
+    /// itself is owned by the client while it's idle, so it's only cleared,
+    /// and the session told about it with an error, on its next command. See
+    /// [`Coordinator::end_idle_in_transaction`].
+    pub(crate) fn sweep_idle_in_transaction(&mut self) {
+        let now = Instant::now();
+        let timed_out: Vec<_> = self
+            .active_conns
+            .iter()
+            .filter(|(_, conn_meta)| {
+                matches!(conn_meta.idle_in_transaction, Some((_, deadline)) if deadline <= now)
+            })
+            .map(|(conn_id, _)| conn_id.clone())
+            .collect();
+        for conn_id in timed_out {
+            let conn_meta = self
+                .active_conns
+                .get_mut(&conn_id)
+                .expect("must exist for active session");
+            let (txn_id, _) = conn_meta
+                .idle_in_transaction
+                .take()
+                .expect("filtered above");
+            conn_meta.timed_out_transaction = Some(txn_id);
+            let _ = conn_meta
+                .notice_tx
+                .send(AdapterNotice::IdleInTransactionSessionTimeout);
+            info!(%conn_id, "rolling back transaction due to idle-in-transaction timeout");
+            self.clear_connection(&conn_id);
+        }
+    }
+
+    /// Marks the transaction of `session`, if any, as no longer idle, now
+    /// that the session issued another command.
+    ///
+    /// Returns an error if the transaction was rolled back by
+    /// [`Coordinator::sweep_idle_in_transaction`] while the session was
+    /// idle, after clearing it from the session.
+    pub(crate) fn end_idle_in_transaction(
+        &mut self,
+        session: &mut Session,
+    ) -> Result<(), AdapterError> {
+        let Some(conn_meta) = self.active_conns.get_mut(session.conn_id()) else {
+            return Ok(());
+        };
+        conn_meta.idle_in_transaction = None;
+        let Some(txn_id) = conn_meta.timed_out_transaction.take() else {
+            return Ok(());
+        };
+        // The session might have moved on from the transaction on its own,
+        // without involving the coordinator.
+        if session.transaction().inner().map(|txn| txn.id) != Some(txn_id) {
+            return Ok(());
+        }
+        let _ = self.clear_transaction(session);
+        Err(AdapterError::IdleInTransactionSessionTimeout)
+    }
+
     /// Handle adding metadata associated with a SUBSCRIBE query, returning a notify that resolves
     /// when our builtin table updates are complete.
     pub(crate) async fn add_active_subscribe(
@@ -149,6 +149,12 @@ pub enum AdapterError {
     },
     /// A user tried to perform an action that they were unauthorized to do.
     Unauthorized(rbac::UnauthorizedError),
+    /// Like [`AdapterError::Unauthorized`], but with the trail of RBAC checks that were
+    /// performed before the action was denied, collected because `rbac_debug` is on.
+    UnauthorizedWithTrace {
+        error: rbac::UnauthorizedError,
+        trace: rbac::RbacTrace,
+    },
     /// The named cursor does not exist.
     UnknownCursor(String),
     /// The named role does not exist.
@@ -222,6 +228,18 @@ impl AdapterError {
         }
     }
 
+    /// Converts an RBAC error into an [`AdapterError`], attaching the trail of RBAC checks if one
+    /// was collected.
+    pub fn unauthorized(
+        error: rbac::UnauthorizedError,
+        trace: Option<rbac::RbacTrace>,
+    ) -> AdapterError {
+        match trace {
+            Some(trace) => AdapterError::UnauthorizedWithTrace { error, trace },
+            None => AdapterError::Unauthorized(error),
+        }
+    }
+
     pub fn position(&self) -> Option<usize> {
         match self {
             AdapterError::ParseError(err) => Some(err.error.pos),
@@ -278,6 +296,13 @@ impl AdapterError {
             )),
             AdapterError::PlanError(e) => e.detail(),
             AdapterError::Unauthorized(unauthorized) => unauthorized.detail(),
+            AdapterError::UnauthorizedWithTrace { error, trace } => {
+                let trace = format!("RBAC checks performed:\n{trace}");
+                match error.detail() {
+                    Some(detail) => Some(format!("{detail}\n{trace}")),
+                    None => Some(trace),
+                }
+            }
             AdapterError::DependentObject(dependent_objects) => {
                 Some(dependent_objects
                     .iter()
@@ -451,7 +476,9 @@ impl AdapterError {
             AdapterError::UnallowedOnCluster { .. } => {
                 SqlState::S_R_E_PROHIBITED_SQL_STATEMENT_ATTEMPTED
             }
-            AdapterError::Unauthorized(_) => SqlState::INSUFFICIENT_PRIVILEGE,
+            AdapterError::Unauthorized(_) | AdapterError::UnauthorizedWithTrace { .. } => {
+                SqlState::INSUFFICIENT_PRIVILEGE
+            }
             AdapterError::UnknownCursor(_) => SqlState::INVALID_CURSOR_NAME,
             AdapterError::UnknownPreparedStatement(_) => SqlState::UNDEFINED_PSTATEMENT,
             AdapterError::UnknownLoginRole(_) => SqlState::INVALID_AUTHORIZATION_SPECIFICATION,
@@ -578,7 +605,7 @@ impl fmt::Display for AdapterError {
             AdapterError::IdleInTransactionSessionTimeout => {
                 write!(
                     f,
-                    "terminating connection due to idle-in-transaction timeout"
+                    "current transaction was rolled back due to idle-in-transaction timeout"
                 )
             }
             AdapterError::RecursionLimit(e) => e.fmt(f),
@@ -620,7 +647,11 @@ impl fmt::Display for AdapterError {
                     cluster.quoted()
                 )
             }
-            AdapterError::Unauthorized(unauthorized) => {
+            AdapterError::Unauthorized(unauthorized)
+            | AdapterError::UnauthorizedWithTrace {
+                error: unauthorized,
+                ..
+            } => {
                 write!(f, "{unauthorized}")
             }
             AdapterError::UnknownCursor(name) => {
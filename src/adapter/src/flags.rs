@@ -40,6 +40,35 @@ pub fn compute_config(config: &SystemVars) -> ComputeParameters {
         enable_jemalloc_profiling: Some(config.enable_jemalloc_profiling()),
         enable_specialized_arrangements: Some(config.enable_specialized_arrangements()),
         enable_columnation_lgalloc: Some(config.enable_columnation_lgalloc()),
+        replica_restart_backoff_base: Some(config.compute_replica_restart_backoff_base()),
+        replica_restart_backoff_cap: Some(config.compute_replica_restart_backoff_cap()),
+        subscribe_max_buffered_bytes: Some(
+            config
+                .compute_subscribe_max_buffered_bytes()
+                .map(u64::cast_from),
+        ),
+        subscribe_max_lag: Some(config.compute_subscribe_max_lag()),
+        subscribe_max_batch_rows: Some(
+            config
+                .compute_subscribe_max_batch_rows()
+                .map(u64::cast_from),
+        ),
+        subscribe_max_batch_bytes: Some(
+            config
+                .compute_subscribe_max_batch_bytes()
+                .map(u64::cast_from),
+        ),
+        introspection_shedding_backlog: Some(
+            config
+                .compute_introspection_shedding_backlog()
+                .map(u64::cast_from),
+        ),
+        introspection_shedding_drop: Some(config.compute_introspection_shedding_drop()),
+        history_reduce_threshold: Some(
+            config
+                .compute_history_reduce_threshold()
+                .map(u64::cast_from),
+        ),
         persist: persist_config(config),
         tracing: tracing_config(config),
         grpc_client: grpc_client_config(config),
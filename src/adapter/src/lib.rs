@@ -74,4 +74,5 @@ pub use crate::error::AdapterError;
 pub use crate::notice::AdapterNotice;
 pub use crate::webhook::{
     AppendWebhookError, AppendWebhookResponse, AppendWebhookValidator, WebhookAppenderCache,
+    WebhookValidationConjunct, WebhookValidationExplanation, WebhookValidationInput,
 };
@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use mz_ore::metric;
-use mz_ore::metrics::MetricsRegistry;
+use mz_ore::metrics::{raw, MetricsRegistry};
 use mz_ore::stats::{histogram_milliseconds_buckets, histogram_seconds_buckets};
 use mz_sql::ast::{AstInfo, Statement, StatementKind, SubscribeOutput};
 use mz_sql::session::user::User;
@@ -36,6 +36,8 @@ pub struct Metrics {
     pub append_table_duration_seconds: HistogramVec,
     pub webhook_validation_reduce_failures: IntCounterVec,
     pub webhook_get_appender: IntCounter,
+    pub prepared_statements: raw::UIntGaugeVec,
+    pub prepared_statement_evictions: IntCounterVec,
 }
 
 impl Metrics {
@@ -137,6 +139,16 @@ impl Metrics {
                 name: "mz_webhook_get_appender_count",
                 help: "Count of getting a webhook appender from the Coordinator.",
             )),
+            prepared_statements: registry.register(metric!(
+                name: "mz_prepared_statements",
+                help: "The number of prepared statements and portals held by active sessions.",
+                var_labels: ["session_type", "kind"],
+            )),
+            prepared_statement_evictions: registry.register(metric!(
+                name: "mz_prepared_statement_evictions_total",
+                help: "The total number of prepared statements and portals evicted from sessions that reached their limit.",
+                var_labels: ["session_type", "kind"],
+            )),
         }
     }
 }
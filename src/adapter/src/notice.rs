@@ -20,6 +20,7 @@ use mz_repr::strconv;
 use mz_sql::ast::NoticeSeverity;
 use mz_sql::catalog::ErrorMessageObjectDescription;
 use mz_sql::plan::PlanNotice;
+use mz_sql::rbac::RbacTrace;
 use mz_sql::session::vars::IsolationLevel;
 use tokio_postgres::error::SqlState;
 
@@ -88,6 +89,10 @@ pub enum AdapterNotice {
     DroppedSubscribe {
         dropped_name: String,
     },
+    DdlCanceledBeforeCommit {
+        name: String,
+        ty: &'static str,
+    },
     BadStartupSetting {
         name: String,
         reason: String,
@@ -130,6 +135,10 @@ pub enum AdapterNotice {
         var_name: Option<String>,
     },
     Welcome(String),
+    RbacTrace {
+        trace: RbacTrace,
+    },
+    IdleInTransactionSessionTimeout,
 }
 
 impl AdapterNotice {
@@ -172,6 +181,7 @@ impl AdapterNotice {
             AdapterNotice::QueryTrace { .. } => Severity::Notice,
             AdapterNotice::UnimplementedIsolationLevel { .. } => Severity::Notice,
             AdapterNotice::DroppedSubscribe { .. } => Severity::Notice,
+            AdapterNotice::DdlCanceledBeforeCommit { .. } => Severity::Notice,
             AdapterNotice::BadStartupSetting { .. } => Severity::Notice,
             AdapterNotice::RbacUserDisabled => Severity::Notice,
             AdapterNotice::RoleMembershipAlreadyExists { .. } => Severity::Notice,
@@ -191,6 +201,8 @@ impl AdapterNotice {
             AdapterNotice::PerReplicaLogRead { .. } => Severity::Notice,
             AdapterNotice::VarDefaultUpdated { .. } => Severity::Notice,
             AdapterNotice::Welcome(_) => Severity::Notice,
+            AdapterNotice::RbacTrace { .. } => Severity::Notice,
+            AdapterNotice::IdleInTransactionSessionTimeout => Severity::Warning,
         }
     }
 
@@ -205,6 +217,7 @@ impl AdapterNotice {
                     .map(|obj_info| format!("drop cascades to {}", obj_info))
                     .join("\n"),
             ),
+            AdapterNotice::RbacTrace { trace } => Some(trace.to_string()),
             _ => None,
         }
     }
@@ -262,6 +275,7 @@ impl AdapterNotice {
             AdapterNotice::QueryTrace { .. } => SqlState::WARNING,
             AdapterNotice::UnimplementedIsolationLevel { .. } => SqlState::WARNING,
             AdapterNotice::DroppedSubscribe { .. } => SqlState::WARNING,
+            AdapterNotice::DdlCanceledBeforeCommit { .. } => SqlState::WARNING,
             AdapterNotice::BadStartupSetting { .. } => SqlState::WARNING,
             AdapterNotice::RbacUserDisabled => SqlState::WARNING,
             AdapterNotice::RoleMembershipAlreadyExists { .. } => SqlState::WARNING,
@@ -281,6 +295,10 @@ impl AdapterNotice {
             AdapterNotice::PerReplicaLogRead { .. } => SqlState::WARNING,
             AdapterNotice::VarDefaultUpdated { .. } => SqlState::SUCCESSFUL_COMPLETION,
             AdapterNotice::Welcome(_) => SqlState::SUCCESSFUL_COMPLETION,
+            AdapterNotice::RbacTrace { .. } => SqlState::WARNING,
+            AdapterNotice::IdleInTransactionSessionTimeout => {
+                SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT
+            }
         }
     }
 }
@@ -363,6 +381,14 @@ impl fmt::Display for AdapterNotice {
                     IsolationLevel::Serializable.as_str()
                 )
             }
+            AdapterNotice::DdlCanceledBeforeCommit { name, ty } => {
+                write!(
+                    f,
+                    "{} {} was not created, because it was canceled before being committed",
+                    ty,
+                    name.quoted()
+                )
+            }
             AdapterNotice::DroppedSubscribe { dropped_name } => {
                 write!(
                     f,
@@ -446,6 +472,19 @@ impl fmt::Display for AdapterNotice {
                 )
             }
             AdapterNotice::Welcome(message) => message.fmt(f),
+            AdapterNotice::RbacTrace { trace } => {
+                write!(
+                    f,
+                    "performed {} RBAC checks before denying statement",
+                    trace.checks.len()
+                )
+            }
+            AdapterNotice::IdleInTransactionSessionTimeout => {
+                write!(
+                    f,
+                    "current transaction has been rolled back due to idle-in-transaction timeout"
+                )
+            }
         }
     }
 }
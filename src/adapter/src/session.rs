@@ -14,13 +14,19 @@
 use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, BTreeSet};
 use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{self, AtomicU64};
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use chrono::{DateTime, Utc};
 use derivative::Derivative;
+use futures::Stream;
 use mz_adapter_types::connection::ConnectionId;
 use mz_build_info::{BuildInfo, DUMMY_BUILD_INFO};
 use mz_controller_types::ClusterId;
+use mz_ore::cast::CastFrom;
+use mz_ore::metrics::UIntGauge;
 use mz_ore::now::EpochMillis;
 use mz_pgwire_common::Format;
 use mz_repr::role_id::RoleId;
@@ -35,10 +41,11 @@ pub use mz_sql::session::vars::{
     EndTransactionAction, SessionVars, DEFAULT_DATABASE_NAME, SERVER_MAJOR_VERSION,
     SERVER_MINOR_VERSION, SERVER_PATCH_VERSION,
 };
-use mz_sql::session::vars::{IsolationLevel, VarInput};
+use mz_sql::session::vars::{IsolationLevel, Var, VarInput, MAX_PREPARED_STATEMENTS_PER_SESSION};
 use mz_sql_parser::ast::display::AstDisplay;
 use mz_sql_parser::ast::{StatementKind, TransactionIsolationLevel};
 use mz_storage_types::sources::Timeline;
+use prometheus::IntCounter;
 use qcell::{QCell, QCellOwner};
 use rand::Rng;
 use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
@@ -52,6 +59,7 @@ use crate::coord::peek::PeekResponseUnary;
 use crate::coord::statement_logging::PreparedStatementLoggingInfo;
 use crate::coord::timestamp_selection::{TimestampContext, TimestampDetermination};
 use crate::error::AdapterError;
+use crate::metrics::{session_type_label_value, Metrics};
 use crate::AdapterNotice;
 
 const DUMMY_CONNECTION_ID: ConnectionId = ConnectionId::Static(0);
@@ -100,6 +108,15 @@ pub struct Session<T = mz_repr::Timestamp> {
     // on. We express this by gating access with this token.
     #[derivative(Debug = "ignore")]
     qcell_owner: QCellOwner,
+    /// Limits on the number of prepared statements and portals.
+    prepared_statement_limits: PreparedStatementLimits,
+    /// Reports the number of prepared statements and portals, once configured
+    /// by [`Session::set_prepared_statement_limits`].
+    #[derivative(Debug = "ignore")]
+    prepared_statement_metrics: Option<PreparedStatementMetrics>,
+    /// A logical clock, ticked whenever a prepared statement or portal is
+    /// created or used, that orders them for LRU eviction.
+    usage_clock: u64,
 }
 
 impl<T: TimestampManipulation> Session<T> {
@@ -190,6 +207,9 @@ impl<T: TimestampManipulation> Session<T> {
             secret_key: rand::thread_rng().gen(),
             external_metadata_rx: None,
             qcell_owner: QCellOwner::new(),
+            prepared_statement_limits: PreparedStatementLimits::default(),
+            prepared_statement_metrics: None,
+            usage_clock: 0,
         }
     }
 
@@ -318,6 +338,7 @@ impl<T: TimestampManipulation> Session<T> {
     #[must_use]
     pub fn clear_transaction(&mut self) -> TransactionStatus<T> {
         self.portals.clear();
+        self.update_prepared_statement_metrics();
         self.pcx = None;
         mem::take(&mut self.transaction)
     }
@@ -496,7 +517,20 @@ impl<T: TimestampManipulation> Session<T> {
         desc: StatementDesc,
         catalog_revision: u64,
         now: EpochMillis,
-    ) {
+    ) -> Result<(), AdapterError> {
+        // The unnamed statement is used for every simple query, so it never counts against the
+        // limit.
+        let exempt = name.is_empty() || self.prepared_statements.contains_key(&name);
+        let evicted = make_room(
+            &mut self.prepared_statements,
+            exempt,
+            &self.prepared_statement_limits,
+            "prepared statement",
+            |ps| ps.last_used,
+        )?;
+        if let Some(metrics) = &self.prepared_statement_metrics {
+            metrics.statement_evictions.inc_by(evicted);
+        }
         let redacted_sql = stmt
             .as_ref()
             .map(|stmt| stmt.to_ast_string_redacted())
@@ -518,20 +552,26 @@ impl<T: TimestampManipulation> Session<T> {
                     kind,
                 },
             )),
+            last_used: self.tick_usage_clock(),
         };
         self.prepared_statements.insert(name, statement);
+        self.update_prepared_statement_metrics();
+        Ok(())
     }
 
     /// Removes the prepared statement associated with `name`.
     ///
     /// Returns whether a statement previously existed.
     pub fn remove_prepared_statement(&mut self, name: &str) -> bool {
-        self.prepared_statements.remove(name).is_some()
+        let removed = self.prepared_statements.remove(name).is_some();
+        self.update_prepared_statement_metrics();
+        removed
     }
 
     /// Removes all prepared statements.
     pub fn remove_all_prepared_statements(&mut self) {
         self.prepared_statements.clear();
+        self.update_prepared_statement_metrics();
     }
 
     /// Retrieves the prepared statement associated with `name`.
@@ -558,6 +598,15 @@ impl<T: TimestampManipulation> Session<T> {
         &self.prepared_statements
     }
 
+    /// Marks the prepared statement associated with `name` as used, making it
+    /// the last to be evicted.
+    pub fn touch_prepared_statement(&mut self, name: &str) {
+        let now = self.tick_usage_clock();
+        if let Some(ps) = self.prepared_statements.get_mut(name) {
+            ps.last_used = now;
+        }
+    }
+
     /// Binds the specified portal to the specified prepared statement.
     ///
     /// If the prepared statement contains parameters, the values and types of
@@ -581,6 +630,11 @@ impl<T: TimestampManipulation> Session<T> {
         if !portal_name.is_empty() && self.portals.contains_key(&portal_name) {
             return Err(AdapterError::DuplicateCursor(portal_name));
         }
+        // The unnamed portal is used for every simple query, so it never counts against the
+        // limit.
+        let exempt = portal_name.is_empty() || self.portals.contains_key(&portal_name);
+        self.make_room_for_portal(exempt)?;
+        let last_used = self.tick_usage_clock();
         self.portals.insert(
             portal_name,
             Portal {
@@ -594,8 +648,10 @@ impl<T: TimestampManipulation> Session<T> {
                 result_formats: result_formats.into_iter().map(Into::into).collect(),
                 state: PortalState::NotStarted,
                 logging,
+                last_used,
             },
         );
+        self.update_prepared_statement_metrics();
         Ok(())
     }
 
@@ -603,7 +659,9 @@ impl<T: TimestampManipulation> Session<T> {
     ///
     /// If there is no such portal, this method does nothing. Returns whether that portal existed.
     pub fn remove_portal(&mut self, portal_name: &str) -> bool {
-        self.portals.remove(portal_name).is_some()
+        let removed = self.portals.remove(portal_name).is_some();
+        self.update_prepared_statement_metrics();
+        removed
     }
 
     /// Retrieves a reference to the specified portal.
@@ -620,6 +678,14 @@ impl<T: TimestampManipulation> Session<T> {
         self.portals.get_mut(portal_name)
     }
 
+    /// Marks the specified portal as used, making it the last to be evicted.
+    pub fn touch_portal(&mut self, portal_name: &str) {
+        let now = self.tick_usage_clock();
+        if let Some(portal) = self.portals.get_mut(portal_name) {
+            portal.last_used = now;
+        }
+    }
+
     /// Creates and installs a new portal.
     pub fn create_new_portal(
         &mut self,
@@ -632,6 +698,8 @@ impl<T: TimestampManipulation> Session<T> {
     ) -> Result<String, AdapterError> {
         // See: https://github.com/postgres/postgres/blob/84f5c2908dad81e8622b0406beea580e40bb03ac/src/backend/utils/mmgr/portalmem.c#L234
 
+        self.make_room_for_portal(false)?;
+        let last_used = self.tick_usage_clock();
         for i in 0usize.. {
             let name = format!("<unnamed portal {}>", i);
             match self.portals.entry(name.clone()) {
@@ -645,7 +713,9 @@ impl<T: TimestampManipulation> Session<T> {
                         result_formats,
                         state: PortalState::NotStarted,
                         logging,
+                        last_used,
                     });
+                    self.update_prepared_statement_metrics();
                     return Ok(name);
                 }
             }
@@ -654,11 +724,59 @@ impl<T: TimestampManipulation> Session<T> {
         coord_bail!("unable to create a new portal");
     }
 
+    /// Configures the limits on the number of prepared statements and portals
+    /// in the session, and starts reporting their counts to `metrics`.
+    pub(crate) fn set_prepared_statement_limits(
+        &mut self,
+        limits: PreparedStatementLimits,
+        metrics: &Metrics,
+    ) {
+        let session_type = session_type_label_value(self.user());
+        self.prepared_statement_limits = limits;
+        self.prepared_statement_metrics =
+            Some(PreparedStatementMetrics::new(metrics, session_type));
+        self.update_prepared_statement_metrics();
+    }
+
+    /// Returns the limits on the number of prepared statements and portals in
+    /// the session.
+    pub fn prepared_statement_limits(&self) -> &PreparedStatementLimits {
+        &self.prepared_statement_limits
+    }
+
+    /// Ensures there is room for a new portal, evicting other portals if
+    /// configured to do so, unless the portal is `exempt` from the limit.
+    fn make_room_for_portal(&mut self, exempt: bool) -> Result<(), AdapterError> {
+        let evicted = make_room(
+            &mut self.portals,
+            exempt,
+            &self.prepared_statement_limits,
+            "portal",
+            |portal| portal.last_used,
+        )?;
+        if let Some(metrics) = &self.prepared_statement_metrics {
+            metrics.portal_evictions.inc_by(evicted);
+        }
+        Ok(())
+    }
+
+    fn tick_usage_clock(&mut self) -> u64 {
+        self.usage_clock += 1;
+        self.usage_clock
+    }
+
+    fn update_prepared_statement_metrics(&mut self) {
+        if let Some(metrics) = &mut self.prepared_statement_metrics {
+            metrics.update(self.prepared_statements.len(), self.portals.len());
+        }
+    }
+
     /// Resets the session to its initial state. Returns sinks that need to be
     /// dropped.
     pub fn reset(&mut self) {
         let _ = self.clear_transaction();
         self.prepared_statements.clear();
+        self.update_prepared_statement_metrics();
         self.vars = SessionVars::new(self.vars.build_info(), self.vars.user().clone());
     }
 
@@ -794,6 +912,8 @@ pub struct PreparedStatement {
     pub catalog_revision: u64,
     #[derivative(Debug = "ignore")]
     logging: Arc<QCell<PreparedStatementLoggingInfo>>,
+    /// The value of the session's usage clock when this statement was last used.
+    last_used: u64,
 }
 
 impl PreparedStatement {
@@ -834,6 +954,126 @@ pub struct Portal {
     /// The execution state of the portal.
     #[derivative(Debug = "ignore")]
     pub state: PortalState,
+    /// The value of the session's usage clock when this portal was last used.
+    last_used: u64,
+}
+
+/// Limits on the number of prepared statements and portals a session may hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedStatementLimits {
+    /// The maximum number of prepared statements, and separately of portals.
+    pub max: usize,
+    /// Whether reaching `max` evicts the least recently used entry, rather
+    /// than returning an error.
+    pub evict: bool,
+}
+
+impl Default for PreparedStatementLimits {
+    fn default() -> Self {
+        PreparedStatementLimits {
+            max: usize::MAX,
+            evict: false,
+        }
+    }
+}
+
+/// Ensures `entries` has room for a new entry under `limits`.
+///
+/// Entries that are `exempt`, i.e. replacements of existing entries and the
+/// unnamed entry, always succeed. Otherwise, if `entries` is full, the least
+/// recently used entries are evicted, as if they had been unnamed, or an error
+/// is returned if eviction is disabled. The unnamed entry neither counts
+/// against the limit nor is ever evicted, as it's usually the one the client is
+/// in the middle of using. Returns the number of evicted entries.
+fn make_room<V>(
+    entries: &mut BTreeMap<String, V>,
+    exempt: bool,
+    limits: &PreparedStatementLimits,
+    resource_type: &str,
+    last_used: impl Fn(&V) -> u64,
+) -> Result<u64, AdapterError> {
+    if exempt {
+        return Ok(0);
+    }
+    let named =
+        |entries: &BTreeMap<String, V>| entries.len() - usize::from(entries.contains_key(""));
+    let mut evicted = 0;
+    while named(entries) >= limits.max {
+        let lru = if limits.evict {
+            entries
+                .iter()
+                .filter(|(name, _)| !name.is_empty())
+                .min_by_key(|(_, entry)| last_used(entry))
+                .map(|(name, _)| name.clone())
+        } else {
+            None
+        };
+        let Some(lru) = lru else {
+            return Err(AdapterError::ResourceExhaustion {
+                resource_type: resource_type.into(),
+                limit_name: MAX_PREPARED_STATEMENTS_PER_SESSION.name().into(),
+                desired: (named(entries) + 1).to_string(),
+                limit: limits.max.to_string(),
+                current: named(entries).to_string(),
+            });
+        };
+        entries.remove(&lru);
+        evicted += 1;
+    }
+    Ok(evicted)
+}
+
+/// Metrics for the prepared statements and portals of a session.
+struct PreparedStatementMetrics {
+    statements: UIntGauge,
+    portals: UIntGauge,
+    statement_evictions: IntCounter,
+    portal_evictions: IntCounter,
+    /// The number of prepared statements and portals last added to the gauges,
+    /// which are shared by all sessions of the same type.
+    reported_statements: u64,
+    reported_portals: u64,
+}
+
+impl PreparedStatementMetrics {
+    fn new(metrics: &Metrics, session_type: &str) -> Self {
+        PreparedStatementMetrics {
+            statements: metrics
+                .prepared_statements
+                .with_label_values(&[session_type, "prepared_statement"]),
+            portals: metrics
+                .prepared_statements
+                .with_label_values(&[session_type, "portal"]),
+            statement_evictions: metrics
+                .prepared_statement_evictions
+                .with_label_values(&[session_type, "prepared_statement"]),
+            portal_evictions: metrics
+                .prepared_statement_evictions
+                .with_label_values(&[session_type, "portal"]),
+            reported_statements: 0,
+            reported_portals: 0,
+        }
+    }
+
+    fn update(&mut self, statements: usize, portals: usize) {
+        fn report(gauge: &UIntGauge, reported: &mut u64, count: usize) {
+            let count = u64::cast_from(count);
+            if count > *reported {
+                gauge.add(count - *reported);
+            } else {
+                gauge.sub(*reported - count);
+            }
+            *reported = count;
+        }
+        report(&self.statements, &mut self.reported_statements, statements);
+        report(&self.portals, &mut self.reported_portals, portals);
+    }
+}
+
+impl Drop for PreparedStatementMetrics {
+    fn drop(&mut self) {
+        self.update(0, 0);
+    }
 }
 
 /// Execution states of a portal.
@@ -867,8 +1107,78 @@ impl InProgressRows {
     }
 }
 
+/// Returns a new channel of batched rows.
+pub fn row_batch_channel() -> (RowBatchSender, RowBatchStream) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let drained_bytes = Arc::new(AtomicU64::new(0));
+    let sender = RowBatchSender {
+        tx,
+        drained_bytes: Arc::clone(&drained_bytes),
+    };
+    let stream = RowBatchStream {
+        rx,
+        drained_bytes,
+        pending_bytes: 0,
+    };
+    (sender, stream)
+}
+
+/// The sending half of a channel of batched rows.
+///
+/// Each batch is sent along with the number of bytes it accounts for, which are reported back
+/// to the sender once the consumer has drained the batch.
+#[derive(Debug)]
+pub struct RowBatchSender {
+    tx: UnboundedSender<(PeekResponseUnary, u64)>,
+    drained_bytes: Arc<AtomicU64>,
+}
+
+impl RowBatchSender {
+    /// Sends a batch that accounts for `bytes` bytes.
+    ///
+    /// Returns the batch back if the receiving half has been dropped.
+    pub fn send(&self, batch: PeekResponseUnary, bytes: u64) -> Result<(), PeekResponseUnary> {
+        self.tx
+            .send((batch, bytes))
+            .map_err(|mpsc::error::SendError((batch, _))| batch)
+    }
+
+    /// Returns the number of bytes of batches that the consumer has drained since the last call.
+    pub fn take_drained_bytes(&self) -> u64 {
+        self.drained_bytes.swap(0, atomic::Ordering::Relaxed)
+    }
+}
+
 /// A channel of batched rows.
-pub type RowBatchStream = UnboundedReceiver<PeekResponseUnary>;
+///
+/// A batch counts as drained once the consumer asks for the next one, i.e. after it has finished
+/// processing the batch, not merely received it.
+#[derive(Debug)]
+pub struct RowBatchStream {
+    rx: UnboundedReceiver<(PeekResponseUnary, u64)>,
+    drained_bytes: Arc<AtomicU64>,
+    /// The bytes of the last batch handed to the consumer, not yet reported as drained.
+    pending_bytes: u64,
+}
+
+impl Stream for RowBatchStream {
+    type Item = PeekResponseUnary;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.pending_bytes > 0 {
+            this.drained_bytes
+                .fetch_add(this.pending_bytes, atomic::Ordering::Relaxed);
+            this.pending_bytes = 0;
+        }
+        this.rx.poll_recv(cx).map(|batch| {
+            batch.map(|(batch, bytes)| {
+                this.pending_bytes = bytes;
+                batch
+            })
+        })
+    }
+}
 
 /// The transaction status of a session.
 ///
@@ -1327,3 +1637,156 @@ pub struct WriteOp {
     /// The data rows.
     pub rows: Vec<(Row, Diff)>,
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use mz_ore::metrics::MetricsRegistry;
+
+    use super::*;
+
+    fn prepare(session: &mut Session, name: &str) -> Result<(), AdapterError> {
+        session.set_prepared_statement(
+            name.into(),
+            None,
+            String::new(),
+            StatementDesc::new(None),
+            0,
+            0,
+        )
+    }
+
+    fn bind(session: &mut Session, portal_name: &str) -> Result<(), AdapterError> {
+        let logging = Arc::clone(
+            session
+                .get_prepared_statement_unverified("")
+                .expect("unnamed statement exists")
+                .logging(),
+        );
+        session.set_portal(
+            portal_name.into(),
+            StatementDesc::new(None),
+            None,
+            logging,
+            vec![],
+            vec![],
+            0,
+        )
+    }
+
+    fn names<V>(entries: &BTreeMap<String, V>) -> Vec<&str> {
+        entries.keys().map(|name| name.as_str()).collect()
+    }
+
+    #[mz_ore::test]
+    fn prepared_statement_limit_error() {
+        let metrics = Metrics::register_into(&MetricsRegistry::new());
+        let statements = metrics
+            .prepared_statements
+            .with_label_values(&["system", "prepared_statement"]);
+        let mut session = Session::dummy();
+        session.set_prepared_statement_limits(
+            PreparedStatementLimits {
+                max: 2,
+                evict: false,
+            },
+            &metrics,
+        );
+
+        prepare(&mut session, "a").unwrap();
+        prepare(&mut session, "b").unwrap();
+        match prepare(&mut session, "c") {
+            Err(AdapterError::ResourceExhaustion {
+                resource_type,
+                limit_name,
+                ..
+            }) => {
+                assert_eq!(resource_type, "prepared statement");
+                assert_eq!(limit_name, "max_prepared_statements_per_session");
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert_eq!(names(session.prepared_statements()), ["a", "b"]);
+        assert_eq!(statements.get(), 2);
+
+        // The unnamed statement and portal don't count against the limit, so simple queries
+        // keep working in a full session.
+        prepare(&mut session, "").unwrap();
+        bind(&mut session, "p1").unwrap();
+        bind(&mut session, "p2").unwrap();
+        assert!(bind(&mut session, "p3").is_err());
+        bind(&mut session, "").unwrap();
+        assert_eq!(names(&session.portals), ["", "p1", "p2"]);
+        assert!(session.remove_prepared_statement(""));
+
+        // Replacing a statement doesn't require any room.
+        prepare(&mut session, "a").unwrap();
+        // Removing a statement makes room for another.
+        assert!(session.remove_prepared_statement("b"));
+        prepare(&mut session, "c").unwrap();
+        assert_eq!(names(session.prepared_statements()), ["a", "c"]);
+
+        drop(session);
+        assert_eq!(statements.get(), 0);
+    }
+
+    #[mz_ore::test]
+    fn prepared_statement_limit_evict() {
+        let metrics = Metrics::register_into(&MetricsRegistry::new());
+        let evictions = |kind| {
+            metrics
+                .prepared_statement_evictions
+                .with_label_values(&["system", kind])
+                .get()
+        };
+        let mut session = Session::dummy();
+        session.set_prepared_statement_limits(
+            PreparedStatementLimits {
+                max: 2,
+                evict: true,
+            },
+            &metrics,
+        );
+
+        prepare(&mut session, "").unwrap();
+        prepare(&mut session, "a").unwrap();
+        prepare(&mut session, "b").unwrap();
+        session.touch_prepared_statement("a");
+        // `b` is the least recently used, and the unnamed statement is never
+        // evicted.
+        prepare(&mut session, "c").unwrap();
+        assert_eq!(names(session.prepared_statements()), ["", "a", "c"]);
+        prepare(&mut session, "d").unwrap();
+        assert_eq!(names(session.prepared_statements()), ["", "c", "d"]);
+        assert_eq!(evictions("prepared_statement"), 2);
+
+        bind(&mut session, "").unwrap();
+        bind(&mut session, "p1").unwrap();
+        bind(&mut session, "p2").unwrap();
+        session.touch_portal("p1");
+        bind(&mut session, "p3").unwrap();
+        assert_eq!(names(&session.portals), ["", "p1", "p3"]);
+        assert_eq!(evictions("portal"), 1);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    async fn row_batch_drained_after_processing() {
+        let (tx, mut rx) = row_batch_channel();
+        tx.send(PeekResponseUnary::Rows(vec![]), 10).unwrap();
+        tx.send(PeekResponseUnary::Rows(vec![]), 0).unwrap();
+        tx.send(PeekResponseUnary::Error("boom".into()), 5).unwrap();
+        assert_eq!(tx.take_drained_bytes(), 0);
+
+        // A received batch isn't drained until the consumer asks for the next one.
+        assert!(rx.next().await.is_some());
+        assert_eq!(tx.take_drained_bytes(), 0);
+        assert!(rx.next().await.is_some());
+        assert_eq!(tx.take_drained_bytes(), 10);
+        assert!(rx.next().await.is_some());
+        assert_eq!(tx.take_drained_bytes(), 0);
+
+        // Sending fails once the consumer has gone away.
+        drop(rx);
+        assert!(tx.send(PeekResponseUnary::Canceled, 1).is_err());
+    }
+}
@@ -18,15 +18,16 @@ use mz_adapter_types::connection::ConnectionId;
 use mz_compute_client::protocol::response::{SubscribeBatch, SubscribeResponse};
 use mz_controller_types::ClusterId;
 use mz_expr::compare_columns;
+use mz_ore::cast::CastFrom;
 use mz_ore::now::EpochMillis;
 use mz_repr::adt::numeric;
 use mz_repr::{Datum, GlobalId, Row, Timestamp};
 use mz_sql::plan::SubscribeOutput;
 use mz_sql::session::user::User;
 use timely::progress::Antichain;
-use tokio::sync::mpsc;
 
 use crate::coord::peek::PeekResponseUnary;
+use crate::session::RowBatchSender;
 
 /// A description of an active subscribe from coord's perspective
 #[derive(Debug)]
@@ -38,7 +39,9 @@ pub struct ActiveSubscribe {
     /// Channel to send responses to the client.
     ///
     /// The responses have the form `PeekResponseUnary` but should perhaps become `TailResponse`.
-    pub channel: mpsc::UnboundedSender<PeekResponseUnary>,
+    /// Each response carries the size of the compute output it was made from, so the client's
+    /// progress in draining the channel can be acknowledged to the compute controller.
+    pub channel: RowBatchSender,
     /// Whether progress information should be emitted.
     pub emit_progress: bool,
     /// As of of subscribe
@@ -87,7 +90,7 @@ impl ActiveSubscribe {
                 }
             }
 
-            let result = self.channel.send(PeekResponseUnary::Rows(vec![row_buf]));
+            let result = self.channel.send(PeekResponseUnary::Rows(vec![row_buf]), 0);
             if result.is_err() {
                 // TODO(benesch): we should actually drop the sink if the
                 // receiver has gone away. E.g. form a DROP SINK command?
@@ -100,9 +103,10 @@ impl ActiveSubscribe {
     /// Returns `true` if the sink should be removed.
     pub(crate) fn process_response(&mut self, response: SubscribeResponse) -> bool {
         let mut row_buf = Row::default();
+        let bytes = u64::cast_from(response.updates_byte_len());
         match response {
             SubscribeResponse::Batch(SubscribeBatch {
-                lower: _,
+                lower,
                 upper,
                 updates,
             }) => {
@@ -295,14 +299,14 @@ impl ActiveSubscribe {
                             .collect();
                         // TODO(benesch): the lack of backpressure here can result in
                         // unbounded memory usage.
-                        let result = self.channel.send(PeekResponseUnary::Rows(rows));
+                        let result = self.channel.send(PeekResponseUnary::Rows(rows), bytes);
                         if result.is_err() {
                             // TODO(benesch): we should actually drop the sink if the
                             // receiver has gone away. E.g. form a DROP SINK command?
                         }
                     }
                     Err(text) => {
-                        let result = self.channel.send(PeekResponseUnary::Error(text));
+                        let result = self.channel.send(PeekResponseUnary::Error(text), bytes);
                         if result.is_err() {
                             // TODO(benesch): we should actually drop the sink if the
                             // receiver has gone away. E.g. form a DROP SINK command?
@@ -311,8 +315,10 @@ impl ActiveSubscribe {
                 }
                 // Emit progress message if requested. Don't emit progress for the first batch if the upper
                 // is exactly `as_of` (we're guaranteed it is not less than `as_of`, but it might be exactly
-                // `as_of`) as we've already emitted that progress message in `initialize`.
-                if !upper.less_equal(&self.as_of) {
+                // `as_of`) as we've already emitted that progress message in `initialize`. Batches with
+                // `lower == upper` are parts of a batch split by the controller and don't advance the
+                // frontier, so their progress has already been emitted as well.
+                if lower != upper && !upper.less_equal(&self.as_of) {
                     self.send_progress_message(&upper);
                 }
                 upper.is_empty()
@@ -353,7 +353,8 @@ impl ShouldHalt for DataflowCreationError {
             DataflowCreationError::SinceViolation(_)
             | DataflowCreationError::InstanceMissing(_)
             | DataflowCreationError::CollectionMissing(_)
-            | DataflowCreationError::MissingAsOf => false,
+            | DataflowCreationError::MissingAsOf
+            | DataflowCreationError::ReplicaAsOfRegression(_) => false,
         }
     }
 }
@@ -373,7 +374,8 @@ impl ShouldHalt for PeekError {
             PeekError::SinceViolation(_)
             | PeekError::InstanceMissing(_)
             | PeekError::CollectionMissing(_)
-            | PeekError::ReplicaMissing(_) => false,
+            | PeekError::ReplicaMissing(_)
+            | PeekError::ReplicaAsOfBeyond(_) => false,
         }
     }
 }
@@ -384,7 +386,8 @@ impl ShouldHalt for SubscribeTargetError {
             SubscribeTargetError::InstanceMissing(_)
             | SubscribeTargetError::SubscribeMissing(_)
             | SubscribeTargetError::ReplicaMissing(_)
-            | SubscribeTargetError::SubscribeAlreadyStarted => false,
+            | SubscribeTargetError::SubscribeAlreadyStarted
+            | SubscribeTargetError::ReplicaAsOfBeyond(_) => false,
         }
     }
 }
@@ -14,12 +14,15 @@ use std::sync::Arc;
 use anyhow::Context;
 use chrono::{DateTime, Utc};
 use derivative::Derivative;
+use mz_expr::explain::{HumanizedExplain, HumanizerMode};
+use mz_expr::{EvalError, MirScalarExpr, VariadicFunc};
 use mz_repr::{ColumnType, Datum, Diff, Row, RowArena};
 use mz_secrets::cache::CachingSecretsReader;
 use mz_secrets::SecretsReader;
 use mz_sql::plan::{WebhookHeaders, WebhookValidation, WebhookValidationSecret};
 use mz_storage_client::controller::MonotonicAppender;
 use mz_storage_types::controller::StorageError;
+use serde::Serialize;
 use tokio::sync::Semaphore;
 
 use crate::optimize::dataflows::{prep_scalar_expr, ExprPrepStyle};
@@ -79,6 +82,130 @@ impl AppendWebhookValidator {
         headers: Arc<BTreeMap<String, String>>,
         received_at: DateTime<Utc>,
     ) -> Result<bool, AppendWebhookError> {
+        let PreparedValidation {
+            expression,
+            column_names: _,
+            body_columns,
+            header_columns,
+            secret_contents,
+        } = self.prepare(received_at).await?;
+
+        // Create a closure to run our validation, this allows lifetimes and unwind boundaries to
+        // work.
+        let validate = move || {
+            // TODO(parkmycar): Re-use the RowArena when we implement rate limiting.
+            let temp_storage = RowArena::default();
+            with_datums(
+                &body,
+                &headers,
+                body_columns,
+                header_columns,
+                &secret_contents,
+                |datums| {
+                    // Run our validation
+                    let valid = expression
+                        .eval(datums, &temp_storage)
+                        .map_err(|_| AppendWebhookError::ValidationError)?;
+                    match valid {
+                        Datum::True => Ok::<_, AppendWebhookError>(true),
+                        Datum::False | Datum::Null => Ok(false),
+                        _ => unreachable!("Creating a webhook source asserts we return a boolean"),
+                    }
+                },
+            )?
+        };
+
+        run_guarded("webhook-validator-expr", validate).await?
+    }
+
+    /// Evaluates the validation expression against a sample request like [`Self::eval`], but
+    /// instead of only returning whether the request is valid, returns the values the expression
+    /// was evaluated against and the result of each of its top-level conjuncts.
+    ///
+    /// The values of any secrets are replaced with a placeholder, and errors that occur while
+    /// evaluating the expression are not included, since they might contain secrets.
+    pub async fn explain(
+        self,
+        body: bytes::Bytes,
+        headers: Arc<BTreeMap<String, String>>,
+        received_at: DateTime<Utc>,
+    ) -> Result<WebhookValidationExplanation, AppendWebhookError> {
+        let PreparedValidation {
+            expression,
+            column_names,
+            body_columns,
+            header_columns,
+            secret_contents,
+        } = self.prepare(received_at).await?;
+
+        let explain = move || {
+            let temp_storage = RowArena::default();
+            with_datums(
+                &body,
+                &headers,
+                body_columns,
+                header_columns,
+                &secret_contents,
+                |datums| {
+                    let inputs = datums
+                        .iter()
+                        .zip(column_names.iter())
+                        .enumerate()
+                        .map(|(column_idx, (datum, column))| {
+                            let value = if secret_contents.contains_key(&column_idx) {
+                                REDACTED_SECRET.to_string()
+                            } else {
+                                datum.to_string()
+                            };
+                            WebhookValidationInput {
+                                column: column.clone(),
+                                value,
+                            }
+                        })
+                        .collect();
+
+                    let mode = HumanizedExplain::new(false);
+                    let conjuncts: Vec<_> = expression
+                        .and_or_args(VariadicFunc::And)
+                        .iter()
+                        .map(|conjunct| {
+                            let result = conjunct.eval(datums, &temp_storage);
+                            WebhookValidationConjunct {
+                                expression: mode.expr(conjunct, Some(&column_names)).to_string(),
+                                passed: result == Ok(Datum::True),
+                                result: explain_result(result),
+                            }
+                        })
+                        .collect();
+
+                    let result = expression.eval(datums, &temp_storage);
+                    let valid = result == Ok(Datum::True);
+                    let failing_conjunct = if valid {
+                        None
+                    } else {
+                        conjuncts.iter().position(|conjunct| !conjunct.passed)
+                    };
+
+                    WebhookValidationExplanation {
+                        valid,
+                        result: explain_result(result),
+                        inputs,
+                        conjuncts,
+                        failing_conjunct,
+                    }
+                },
+            )
+        };
+
+        run_guarded("webhook-validator-explain", explain).await?
+    }
+
+    /// Reads any secrets required for validation and prepares the validation expression for
+    /// evaluation.
+    async fn prepare(
+        self,
+        received_at: DateTime<Utc>,
+    ) -> Result<PreparedValidation, AppendWebhookError> {
         let AppendWebhookValidator {
             validation,
             secrets_reader,
@@ -86,7 +213,7 @@ impl AppendWebhookValidator {
 
         let WebhookValidation {
             mut expression,
-            relation_desc: _,
+            relation_desc,
             secrets,
             bodies: body_columns,
             headers: header_columns,
@@ -120,111 +247,182 @@ impl AppendWebhookValidator {
             AppendWebhookError::ValidationError
         })?;
 
-        // Create a closure to run our validation, this allows lifetimes and unwind boundaries to
-        // work.
-        let validate = move || {
-            // Gather our Datums for evaluation
-            //
-            // TODO(parkmycar): Re-use the RowArena when we implement rate limiting.
-            let temp_storage = RowArena::default();
-            let mut datums = Vec::with_capacity(
-                body_columns.len() + header_columns.len() + secret_contents.len(),
-            );
-
-            // Append all of our body columns.
-            for (column_idx, use_bytes) in body_columns {
-                assert_eq!(column_idx, datums.len(), "body index and datums mismatch!");
-
-                let datum = if use_bytes {
-                    Datum::Bytes(&body[..])
-                } else {
-                    let s = std::str::from_utf8(&body[..])
-                        .map_err(|m| AppendWebhookError::InvalidUtf8Body { msg: m.to_string() })?;
-                    Datum::String(s)
-                };
-                datums.push(datum);
-            }
-
-            // Append all of our header columns, re-using Row packings.
-            //
-            let headers_byte = std::cell::OnceCell::new();
-            let headers_text = std::cell::OnceCell::new();
-            for (column_idx, use_bytes) in header_columns {
-                assert_eq!(column_idx, datums.len(), "index and datums mismatch!");
-
-                let row = if use_bytes {
-                    headers_byte.get_or_init(|| {
-                        let mut row = Row::with_capacity(1);
-                        let mut packer = row.packer();
-                        packer.push_dict(
-                            headers
-                                .iter()
-                                .map(|(name, val)| (name.as_str(), Datum::Bytes(val.as_bytes()))),
-                        );
-                        row
-                    })
-                } else {
-                    headers_text.get_or_init(|| {
-                        let mut row = Row::with_capacity(1);
-                        let mut packer = row.packer();
-                        packer.push_dict(
-                            headers
-                                .iter()
-                                .map(|(name, val)| (name.as_str(), Datum::String(val))),
-                        );
-                        row
-                    })
-                };
-                datums.push(row.unpack_first());
-            }
-
-            // Append all of our secrets to our datums, in the correct column order.
-            for column_idx in datums.len()..datums.len() + secret_contents.len() {
-                // Get the secret that corresponds with what is the next "column";
-                let (secret, use_bytes) = secret_contents
-                    .get(&column_idx)
-                    .expect("more secrets to provide, but none for the next column");
-
-                if *use_bytes {
-                    datums.push(Datum::Bytes(secret));
-                } else {
-                    let secret_str = std::str::from_utf8(&secret[..]).expect("valid UTF-8");
-                    datums.push(Datum::String(secret_str));
-                }
-            }
-
-            // Run our validation
-            let valid = expression
-                .eval(&datums[..], &temp_storage)
-                .map_err(|_| AppendWebhookError::ValidationError)?;
-            match valid {
-                Datum::True => Ok::<_, AppendWebhookError>(true),
-                Datum::False | Datum::Null => Ok(false),
-                _ => unreachable!("Creating a webhook source asserts we return a boolean"),
-            }
+        let column_names = relation_desc
+            .iter_names()
+            .map(|name| name.as_str().to_string())
+            .collect();
+
+        Ok(PreparedValidation {
+            expression,
+            column_names,
+            body_columns,
+            header_columns,
+            secret_contents,
+        })
+    }
+}
+
+/// The placeholder we report instead of the value of a secret.
+const REDACTED_SECRET: &str = "<redacted>";
+
+/// A validation expression that is ready to be evaluated, along with everything needed to
+/// evaluate it.
+struct PreparedValidation {
+    expression: MirScalarExpr,
+    column_names: Vec<String>,
+    body_columns: Vec<(usize, bool)>,
+    header_columns: Vec<(usize, bool)>,
+    secret_contents: BTreeMap<usize, (Vec<u8>, bool)>,
+}
+
+/// Calls `f` with the [`Datum`]s for the columns that a validation expression is evaluated
+/// against.
+fn with_datums<R>(
+    body: &[u8],
+    headers: &BTreeMap<String, String>,
+    body_columns: Vec<(usize, bool)>,
+    header_columns: Vec<(usize, bool)>,
+    secret_contents: &BTreeMap<usize, (Vec<u8>, bool)>,
+    f: impl FnOnce(&[Datum]) -> R,
+) -> Result<R, AppendWebhookError> {
+    // Gather our Datums for evaluation
+    let mut datums =
+        Vec::with_capacity(body_columns.len() + header_columns.len() + secret_contents.len());
+
+    // Append all of our body columns.
+    for (column_idx, use_bytes) in body_columns {
+        assert_eq!(column_idx, datums.len(), "body index and datums mismatch!");
+
+        let datum = if use_bytes {
+            Datum::Bytes(body)
+        } else {
+            let s = std::str::from_utf8(body)
+                .map_err(|m| AppendWebhookError::InvalidUtf8Body { msg: m.to_string() })?;
+            Datum::String(s)
         };
+        datums.push(datum);
+    }
 
-        // Then run the validation itself.
-        let valid = mz_ore::task::spawn_blocking(
-            || "webhook-validator-expr",
-            move || {
-                // Since the validation expression is technically a user defined function, we want to
-                // be extra careful and guard against issues taking down the entire process.
-                mz_ore::panic::catch_unwind(validate).map_err(|_| {
-                    tracing::error!("panic while validating webhook request!");
-                    AppendWebhookError::ValidationError
-                })
-            },
-        )
-        .await
-        .context("joining on validation")
-        .map_err(|e| {
-            tracing::error!("Failed to run validation for webhook, {e}");
-            AppendWebhookError::ValidationError
-        })??;
+    // Append all of our header columns, re-using Row packings.
+    //
+    let headers_byte = std::cell::OnceCell::new();
+    let headers_text = std::cell::OnceCell::new();
+    for (column_idx, use_bytes) in header_columns {
+        assert_eq!(column_idx, datums.len(), "index and datums mismatch!");
+
+        let row = if use_bytes {
+            headers_byte.get_or_init(|| {
+                let mut row = Row::with_capacity(1);
+                let mut packer = row.packer();
+                packer.push_dict(
+                    headers
+                        .iter()
+                        .map(|(name, val)| (name.as_str(), Datum::Bytes(val.as_bytes()))),
+                );
+                row
+            })
+        } else {
+            headers_text.get_or_init(|| {
+                let mut row = Row::with_capacity(1);
+                let mut packer = row.packer();
+                packer.push_dict(
+                    headers
+                        .iter()
+                        .map(|(name, val)| (name.as_str(), Datum::String(val))),
+                );
+                row
+            })
+        };
+        datums.push(row.unpack_first());
+    }
 
-        valid
+    // Append all of our secrets to our datums, in the correct column order.
+    for column_idx in datums.len()..datums.len() + secret_contents.len() {
+        // Get the secret that corresponds with what is the next "column";
+        let (secret, use_bytes) = secret_contents
+            .get(&column_idx)
+            .expect("more secrets to provide, but none for the next column");
+
+        if *use_bytes {
+            datums.push(Datum::Bytes(secret));
+        } else {
+            let secret_str = std::str::from_utf8(&secret[..]).expect("valid UTF-8");
+            datums.push(Datum::String(secret_str));
+        }
     }
+
+    Ok(f(&datums))
+}
+
+/// Runs `f` on a blocking thread, guarding against panics.
+async fn run_guarded<R: Send + 'static>(
+    name: &'static str,
+    f: impl FnOnce() -> R + Send + 'static,
+) -> Result<R, AppendWebhookError> {
+    mz_ore::task::spawn_blocking(
+        || name,
+        move || {
+            // Since the validation expression is technically a user defined function, we want to
+            // be extra careful and guard against issues taking down the entire process.
+            mz_ore::panic::catch_unwind(f).map_err(|_| {
+                tracing::error!("panic while validating webhook request!");
+                AppendWebhookError::ValidationError
+            })
+        },
+    )
+    .await
+    .context("joining on validation")
+    .map_err(|e| {
+        tracing::error!("Failed to run validation for webhook, {e}");
+        AppendWebhookError::ValidationError
+    })?
+}
+
+/// Renders the result of evaluating (part of) a validation expression.
+///
+/// Note: like [`AppendWebhookError::ValidationError`], we never include the details of an
+/// evaluation error, since they might contain secrets.
+fn explain_result(result: Result<Datum, EvalError>) -> String {
+    match result {
+        Ok(datum) => datum.to_string(),
+        Err(_) => "error".to_string(),
+    }
+}
+
+/// The result of evaluating a webhook validation expression against a sample request, see
+/// [`AppendWebhookValidator::explain`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookValidationExplanation {
+    /// Whether the request would have been accepted.
+    pub valid: bool,
+    /// The result of evaluating the whole expression.
+    pub result: String,
+    /// The values of the columns the expression was evaluated against.
+    pub inputs: Vec<WebhookValidationInput>,
+    /// The top-level conjuncts of the expression, and what each evaluated to.
+    pub conjuncts: Vec<WebhookValidationConjunct>,
+    /// The index of the first conjunct that caused the request to be rejected, if any.
+    pub failing_conjunct: Option<usize>,
+}
+
+/// A column that a webhook validation expression is evaluated against.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookValidationInput {
+    /// The name of the column, e.g. `body` or the name of a secret.
+    pub column: String,
+    /// The value of the column, or a placeholder for secrets.
+    pub value: String,
+}
+
+/// A top-level conjunct of a webhook validation expression.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookValidationConjunct {
+    /// The conjunct, rendered in terms of the validation columns.
+    pub expression: String,
+    /// What the conjunct evaluated to.
+    pub result: String,
+    /// Whether the conjunct evaluated to true.
+    pub passed: bool,
 }
 
 #[derive(Derivative, Clone)]
@@ -9,7 +9,6 @@
 
 //! Debug utility for Catalog storage.
 
-use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{self, Write};
@@ -26,11 +25,11 @@ use mz_build_info::{build_info, BuildInfo};
 use mz_catalog::config::{ClusterReplicaSizeMap, StateConfig};
 use mz_catalog::durable::debug::{
     AuditLogCollection, ClusterCollection, ClusterIntrospectionSourceIndexCollection,
-    ClusterReplicaCollection, Collection, CollectionTrace, CollectionType, CommentCollection,
-    ConfigCollection, DatabaseCollection, DebugCatalogState, DefaultPrivilegeCollection,
-    IdAllocatorCollection, ItemCollection, RoleCollection, SchemaCollection, SettingCollection,
-    StorageUsageCollection, SystemConfigurationCollection, SystemItemMappingCollection,
-    SystemPrivilegeCollection, TimestampCollection, Trace,
+    ClusterReplicaCollection, Collection, CollectionType, CommentCollection, ConfigCollection,
+    DatabaseCollection, DebugCatalogState, DefaultPrivilegeCollection, IdAllocatorCollection,
+    ItemCollection, RoleCollection, SchemaCollection, SettingCollection, StorageUsageCollection,
+    SystemConfigurationCollection, SystemItemMappingCollection, SystemPrivilegeCollection,
+    TimestampCollection,
 };
 use mz_catalog::durable::{
     persist_backed_catalog_state, stash_backed_catalog_state, BootstrapArgs,
@@ -39,11 +38,11 @@ use mz_catalog::durable::{
 use mz_ore::cli::{self, CliConfig};
 use mz_ore::error::ErrorExt;
 use mz_ore::metrics::MetricsRegistry;
-use mz_ore::now::SYSTEM_TIME;
+use mz_ore::now::{EpochMillis, SYSTEM_TIME};
 use mz_persist_client::cache::PersistClientCache;
 use mz_persist_client::cfg::PersistConfig;
 use mz_persist_client::rpc::PubSubClientConnection;
-use mz_persist_client::PersistLocation;
+use mz_persist_client::{PersistClient, PersistLocation, ShardId};
 use mz_secrets::InMemorySecretsController;
 use mz_sql::catalog::EnvironmentId;
 use mz_sql::session::vars::{CatalogKind, ConnectionCounter};
@@ -95,12 +94,31 @@ enum Action {
     Dump {
         /// Write output to specified path. Default stdout.
         target: Option<PathBuf>,
+        /// Only dump entries of event collections, like `storage_usage` and `audit_log`, that
+        /// were recorded at or after this time, in milliseconds since the Unix epoch. Other
+        /// collections are always dumped in full.
+        #[clap(long)]
+        since: Option<EpochMillis>,
+        /// Only dump entries of event collections, like `storage_usage` and `audit_log`, that
+        /// were recorded before this time, in milliseconds since the Unix epoch. Other
+        /// collections are always dumped in full.
+        #[clap(long)]
+        upper: Option<EpochMillis>,
     },
     /// Prints the current epoch.
     Epoch {
         /// Write output to specified path. Default stdout.
         target: Option<PathBuf>,
     },
+    /// Prints the critical readers of a persist shard, e.g. one holding a
+    /// storage collection, with their sinces and opaques as JSON. Requires
+    /// the persist store.
+    CriticalReaders {
+        /// The id of the shard.
+        shard_id: String,
+        /// Write output to specified path. Default stdout.
+        target: Option<PathBuf>,
+    },
     /// Edits a single item in a collection in the catalog.
     Edit {
         /// The name of the catalog collection to edit.
@@ -147,6 +165,7 @@ async fn main() {
 async fn run(args: Args) -> Result<(), anyhow::Error> {
     let metrics_registry = MetricsRegistry::new();
     let start = Instant::now();
+    let mut persist_client = None;
     let openable_state: Box<dyn OpenableDurableCatalogState> = match args.store {
         CatalogKind::Stash => {
             let postgres_url = args.postgres_url.expect("required for stash");
@@ -179,10 +198,11 @@ async fn run(args: Args) -> Result<(), anyhow::Error> {
                     .expect("required for persist")
                     .to_string(),
             };
-            let persist_client = persist_clients.open(persist_location).await?;
+            let client = persist_clients.open(persist_location).await?;
+            persist_client = Some(client.clone());
             let organization_id = args.organization_id.expect("required for persist");
             let metrics = Arc::new(mz_catalog::durable::Metrics::new(&metrics_registry));
-            Box::new(persist_backed_catalog_state(persist_client, organization_id, metrics).await)
+            Box::new(persist_backed_catalog_state(client, organization_id, metrics).await)
         }
         CatalogKind::Shadow => panic!("cannot use shadow catalog with catalog-debug tool"),
         CatalogKind::EmergencyStash => {
@@ -191,13 +211,17 @@ async fn run(args: Args) -> Result<(), anyhow::Error> {
     };
 
     match args.action {
-        Action::Dump { target } => {
+        Action::Dump {
+            target,
+            since,
+            upper,
+        } => {
             let target: Box<dyn Write> = if let Some(path) = target {
                 Box::new(File::create(path)?)
             } else {
                 Box::new(io::stdout().lock())
             };
-            dump(openable_state, target).await
+            dump(openable_state, target, TimeBounds { since, upper }).await
         }
         Action::Epoch { target } => {
             let target: Box<dyn Write> = if let Some(path) = target {
@@ -207,6 +231,17 @@ async fn run(args: Args) -> Result<(), anyhow::Error> {
             };
             epoch(openable_state, target).await
         }
+        Action::CriticalReaders { shard_id, target } => {
+            let Some(persist_client) = persist_client else {
+                anyhow::bail!("critical-readers requires --store=persist");
+            };
+            let target: Box<dyn Write> = if let Some(path) = target {
+                Box::new(File::create(path)?)
+            } else {
+                Box::new(io::stdout().lock())
+            };
+            critical_readers(persist_client, shard_id, target).await
+        }
         Action::Edit {
             collection,
             key,
@@ -304,76 +339,119 @@ async fn delete(
     Ok(())
 }
 
+/// A `[since, upper)` range of wall-clock times used to filter the entries of event
+/// collections. See [`Collection::event_time`].
+#[derive(Debug, Clone, Copy)]
+struct TimeBounds {
+    since: Option<EpochMillis>,
+    upper: Option<EpochMillis>,
+}
+
+impl TimeBounds {
+    /// Returns whether the entry with `key` and `value` of collection `T` should be kept.
+    ///
+    /// Entries without an event time, which includes all entries of collections that aren't
+    /// event collections, are always kept.
+    fn contains<T: Collection>(&self, key: &T::Key, value: &T::Value) -> bool {
+        match T::event_time(key, value) {
+            Some(time) => {
+                self.since.map_or(true, |since| since <= time)
+                    && self.upper.map_or(true, |upper| time < upper)
+            }
+            None => true,
+        }
+    }
+}
+
 async fn dump(
     mut openable_state: Box<dyn OpenableDurableCatalogState>,
-    mut target: impl Write,
+    target: impl Write,
+    bounds: TimeBounds,
 ) -> Result<(), anyhow::Error> {
-    fn dump_col<T: Collection>(data: &mut BTreeMap<String, Vec<Dumped>>, trace: CollectionTrace<T>)
+    /// Fetches the entries of collection `T` and writes them to `target` one at a time, instead
+    /// of first collecting the dumped form of the whole collection.
+    ///
+    /// The output matches the alternate `Debug` format of a map from collection name to entries.
+    async fn dump_col<T: Collection>(
+        openable_state: &mut Box<dyn OpenableDurableCatalogState>,
+        target: &mut dyn Write,
+        bounds: TimeBounds,
+    ) -> Result<(), anyhow::Error>
     where
         T::Key: Serialize + Debug + 'static,
         T::Value: Serialize + Debug + 'static,
     {
-        let dumped = trace
+        let trace = openable_state
+            .trace_collection(T::collection_type())
+            .await?;
+        let name = T::name();
+        let mut entries = T::collection_trace(trace)
             .values
             .into_iter()
-            .map(|((k, v), timestamp, diff)| {
-                let key_json = serde_json::to_string(&k).expect("must serialize");
-                let value_json = serde_json::to_string(&v).expect("must serialize");
-                Dumped {
-                    key: Box::new(k),
-                    value: Box::new(v),
-                    key_json: UnescapedDebug(key_json),
-                    value_json: UnescapedDebug(value_json),
-                    timestamp,
-                    diff,
-                }
-            })
-            .collect();
-        data.insert(T::name(), dumped);
+            .filter(|((k, v), _, _)| bounds.contains::<T>(k, v))
+            .peekable();
+        if entries.peek().is_none() {
+            writeln!(target, "    {name:?}: [],")?;
+            return Ok(());
+        }
+
+        writeln!(target, "    {name:?}: [")?;
+        for ((k, v), timestamp, diff) in entries {
+            let key_json = serde_json::to_string(&k).expect("must serialize");
+            let value_json = serde_json::to_string(&v).expect("must serialize");
+            let dumped = Dumped {
+                key: Box::new(k),
+                value: Box::new(v),
+                key_json: UnescapedDebug(key_json),
+                value_json: UnescapedDebug(value_json),
+                timestamp,
+                diff,
+            };
+            for line in format!("{dumped:#?},").lines() {
+                writeln!(target, "        {line}")?;
+            }
+        }
+        writeln!(target, "    ],")?;
+        Ok(())
     }
 
-    let mut data = BTreeMap::new();
-    let Trace {
-        audit_log,
-        clusters,
-        introspection_sources,
-        cluster_replicas,
-        comments,
-        configs,
-        databases,
-        default_privileges,
-        id_allocator,
-        items,
-        roles,
-        schemas,
-        settings,
-        storage_usage,
-        system_object_mappings,
-        system_configurations,
-        system_privileges,
-        timestamps,
-    } = openable_state.trace().await?;
-
-    dump_col(&mut data, audit_log);
-    dump_col(&mut data, clusters);
-    dump_col(&mut data, introspection_sources);
-    dump_col(&mut data, cluster_replicas);
-    dump_col(&mut data, comments);
-    dump_col(&mut data, configs);
-    dump_col(&mut data, databases);
-    dump_col(&mut data, default_privileges);
-    dump_col(&mut data, id_allocator);
-    dump_col(&mut data, items);
-    dump_col(&mut data, roles);
-    dump_col(&mut data, schemas);
-    dump_col(&mut data, settings);
-    dump_col(&mut data, storage_usage);
-    dump_col(&mut data, system_configurations);
-    dump_col(&mut data, system_object_mappings);
-    dump_col(&mut data, system_privileges);
-    dump_col(&mut data, timestamps);
-
-    writeln!(&mut target, "{data:#?}")?;
+    // Collections are fetched and written one at a time, so at most one collection of the
+    // catalog is held in memory at once. They are written in the order of their names.
+    let mut collection_types = [
+        CollectionType::AuditLog,
+        CollectionType::ComputeInstance,
+        CollectionType::ComputeIntrospectionSourceIndex,
+        CollectionType::ComputeReplicas,
+        CollectionType::Comments,
+        CollectionType::Config,
+        CollectionType::Database,
+        CollectionType::DefaultPrivileges,
+        CollectionType::IdAlloc,
+        CollectionType::Item,
+        CollectionType::Role,
+        CollectionType::Schema,
+        CollectionType::Setting,
+        CollectionType::StorageUsage,
+        CollectionType::SystemConfiguration,
+        CollectionType::SystemGidMapping,
+        CollectionType::SystemPrivileges,
+        CollectionType::Timestamp,
+    ];
+    collection_types.sort_by_key(|collection_type| collection_type.to_string());
+
+    let mut target = io::BufWriter::new(target);
+    writeln!(&mut target, "{{")?;
+    for collection_type in collection_types {
+        for_collection!(
+            collection_type,
+            dump_col,
+            &mut openable_state,
+            &mut target,
+            bounds
+        );
+    }
+    writeln!(&mut target, "}}")?;
+    target.flush()?;
     Ok(())
 }
 
@@ -386,6 +464,19 @@ async fn epoch(
     Ok(())
 }
 
+async fn critical_readers(
+    persist_client: PersistClient,
+    shard_id: String,
+    mut target: impl Write,
+) -> Result<(), anyhow::Error> {
+    let shard_id = ShardId::from_str(&shard_id).map_err(anyhow::Error::msg)?;
+    // The timestamps of all of Materialize's shards use the u64 codec.
+    let critical_readers = persist_client.critical_readers::<u64>(shard_id).await?;
+    serde_json::to_writer_pretty(&mut target, &critical_readers)?;
+    writeln!(&mut target)?;
+    Ok(())
+}
+
 async fn upgrade_check(
     openable_state: Box<dyn OpenableDurableCatalogState>,
     cluster_replica_sizes: ClusterReplicaSizeMap,
@@ -1946,6 +1946,28 @@ pub static MZ_COMPUTE_HYDRATION_STATUSES: Lazy<BuiltinSource> = Lazy::new(|| Bui
     access: vec![PUBLIC_SELECT],
 });
 
+pub static MZ_SUBSCRIPTION_LAGS: Lazy<BuiltinSource> = Lazy::new(|| BuiltinSource {
+    name: "mz_subscription_lags",
+    schema: MZ_INTERNAL_SCHEMA,
+    data_source: Some(IntrospectionType::ComputeSubscribeLag),
+    desc: RelationDesc::empty()
+        .with_column("subscription_id", ScalarType::String.nullable(false))
+        .with_column("lag", ScalarType::Interval.nullable(false)),
+    is_retained_metrics_object: false,
+    access: vec![PUBLIC_SELECT],
+});
+
+pub static MZ_COMPUTE_INTROSPECTION_SHEDDING: Lazy<BuiltinSource> = Lazy::new(|| BuiltinSource {
+    name: "mz_compute_introspection_shedding",
+    schema: MZ_INTERNAL_SCHEMA,
+    data_source: Some(IntrospectionType::ComputeIntrospectionShedding),
+    desc: RelationDesc::empty()
+        .with_column("introspection_type", ScalarType::String.nullable(false))
+        .with_column("dropped_updates", ScalarType::UInt64.nullable(false)),
+    is_retained_metrics_object: false,
+    access: vec![PUBLIC_SELECT],
+});
+
 pub static MZ_DATABASES: Lazy<BuiltinTable> = Lazy::new(|| BuiltinTable {
     name: "mz_databases",
     schema: MZ_CATALOG_SCHEMA,
@@ -6669,6 +6691,8 @@ pub static BUILTINS_STATIC: Lazy<Vec<Builtin<NameReference>>> = Lazy::new(|| {
         Builtin::View(&MZ_GLOBAL_FRONTIERS),
         Builtin::Source(&MZ_COMPUTE_DEPENDENCIES),
         Builtin::Source(&MZ_COMPUTE_HYDRATION_STATUSES),
+        Builtin::Source(&MZ_SUBSCRIPTION_LAGS),
+        Builtin::Source(&MZ_COMPUTE_INTROSPECTION_SHEDDING),
         Builtin::View(&MZ_HYDRATION_STATUSES),
         Builtin::View(&MZ_MATERIALIZATION_LAG),
         Builtin::View(&MZ_COMPUTE_ERROR_COUNTS_PER_WORKER),
@@ -28,7 +28,7 @@ use mz_storage_types::controller::PersistTxnTablesImpl;
 use mz_storage_types::sources::Timeline;
 use uuid::Uuid;
 
-use crate::durable::debug::{DebugCatalogState, Trace};
+use crate::durable::debug::{CollectionType, DebugCatalogState, Trace};
 pub use crate::durable::error::{CatalogError, DurableCatalogError};
 use crate::durable::impls::migrate::{CatalogMigrator, Direction};
 pub use crate::durable::impls::persist::metrics::Metrics;
@@ -171,6 +171,13 @@ pub trait OpenableDurableCatalogState: Debug + Send {
     /// Generate an unconsolidated [`Trace`] of catalog contents.
     async fn trace(&mut self) -> Result<Trace, CatalogError>;
 
+    /// Generate an unconsolidated [`Trace`] of the contents of the catalog collection of type
+    /// `collection_type`. All other collections of the returned [`Trace`] are empty.
+    async fn trace_collection(
+        &mut self,
+        collection_type: CollectionType,
+    ) -> Result<Trace, CatalogError>;
+
     /// Sets the kind of catalog opened to `catalog_kind` iff this `OpenableDurableCatalogState`
     /// knows how to open a catalog of kind `catalog_kind`, otherwise does nothing.
     fn set_catalog_kind(&mut self, catalog_kind: CatalogKind);
@@ -10,6 +10,7 @@
 //! Functionality for manually modifying and displaying the catalog contents. This is helpful for
 //! fixing a corrupt catalog.
 
+use mz_ore::now::EpochMillis;
 use mz_repr::Diff;
 use mz_stash::{Stash, TypedCollection};
 use serde::{Deserialize, Serialize};
@@ -41,6 +42,10 @@ pub trait Collection {
     /// Extract the [`CollectionTrace`] from a [`Trace`] that corresponds to [`Collection`].
     fn collection_trace(trace: Trace) -> CollectionTrace<Self>;
 
+    /// Return a mutable reference to the [`CollectionTrace`] within a [`Trace`] that corresponds
+    /// to [`Collection`].
+    fn collection_trace_mut(trace: &mut Trace) -> &mut CollectionTrace<Self>;
+
     /// Return [`TypedCollection`] that corresponds to [`Collection`].
     fn stash_collection() -> TypedCollection<Self::Key, Self::Value>;
 
@@ -51,6 +56,15 @@ pub trait Collection {
     fn name() -> String {
         Self::collection_type().to_string()
     }
+
+    /// The wall-clock time, in milliseconds since the Unix epoch, at which the entry with `key`
+    /// and `value` was recorded.
+    ///
+    /// Only collections that are logs of timestamped events, like [`StorageUsageCollection`],
+    /// have a meaningful time. All other collections return `None`.
+    fn event_time(_key: &Self::Key, _value: &Self::Value) -> Option<EpochMillis> {
+        None
+    }
 }
 
 /// The type of a [`Collection`].
@@ -59,7 +73,7 @@ pub trait Collection {
 ///
 /// The names of each variant are used to determine the labels of each [`CollectionTrace`] when
 /// dumping a [`Trace`].
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CollectionType {
     AuditLog,
@@ -95,6 +109,7 @@ derive_fromstr_from_deserialize!(CollectionType);
 /// - `$trace_field`, the corresponding field name within a [`Trace`].
 /// - `$stash_collection`, the corresponding [`TypedCollection`].
 /// - `$persist_update`, the corresponding [`StateUpdateKind`] constructor.
+/// - `$event_time`, optionally, the implementation of [`Collection::event_time`].
 macro_rules! collection_impl {
     ({
     name: $name:ident,
@@ -104,6 +119,7 @@ macro_rules! collection_impl {
     trace_field: $trace_field:ident,
     stash_collection: $stash_collection:expr,
     persist_update: $persist_update:expr,
+    $(event_time: $event_time:expr,)?
 }) => {
         #[derive(Debug, Clone, PartialEq, Eq)]
         pub struct $name {}
@@ -120,6 +136,10 @@ macro_rules! collection_impl {
                 trace.$trace_field
             }
 
+            fn collection_trace_mut(trace: &mut Trace) -> &mut CollectionTrace<Self> {
+                &mut trace.$trace_field
+            }
+
             fn stash_collection() -> TypedCollection<Self::Key, Self::Value> {
                 $stash_collection
             }
@@ -127,6 +147,12 @@ macro_rules! collection_impl {
             fn persist_update(key: Self::Key, value: Self::Value) -> StateUpdateKind {
                 $persist_update(key, value)
             }
+
+            $(
+                fn event_time(key: &Self::Key, value: &Self::Value) -> Option<EpochMillis> {
+                    $event_time(key, value)
+                }
+            )?
         }
     };
 }
@@ -139,6 +165,7 @@ collection_impl!({
     trace_field: audit_log,
     stash_collection: AUDIT_LOG_COLLECTION,
     persist_update: StateUpdateKind::AuditLog,
+    event_time: audit_log_event_time,
 });
 collection_impl!({
     name: ClusterCollection,
@@ -256,6 +283,7 @@ collection_impl!({
     trace_field: storage_usage,
     stash_collection: STORAGE_USAGE_COLLECTION,
     persist_update: StateUpdateKind::StorageUsage,
+    event_time: storage_usage_event_time,
 });
 collection_impl!({
     name: SystemConfigurationCollection,
@@ -294,6 +322,26 @@ collection_impl!({
     persist_update: StateUpdateKind::Timestamp,
 });
 
+fn audit_log_event_time(key: &proto::AuditLogKey, _value: &()) -> Option<EpochMillis> {
+    match &key.event {
+        Some(proto::audit_log_key::Event::V1(event)) => event
+            .occurred_at
+            .as_ref()
+            .map(|occurred_at| occurred_at.millis),
+        None => None,
+    }
+}
+
+fn storage_usage_event_time(key: &proto::StorageUsageKey, _value: &()) -> Option<EpochMillis> {
+    match &key.usage {
+        Some(proto::storage_usage_key::Usage::V1(usage)) => usage
+            .collection_timestamp
+            .as_ref()
+            .map(|collection_timestamp| collection_timestamp.millis),
+        None => None,
+    }
+}
+
 /// A trace of timestamped diffs for a particular [`Collection`].
 ///
 /// The timestamps are represented as strings since different implementations use non-compatible
@@ -12,7 +12,7 @@ use mz_ore::now::EpochMillis;
 use mz_sql::session::vars::CatalogKind;
 use tracing::{error, info};
 
-use crate::durable::debug::{DebugCatalogState, Trace};
+use crate::durable::debug::{CollectionType, DebugCatalogState, Trace};
 use crate::durable::impls::persist::UnopenedPersistCatalogState;
 use crate::durable::impls::stash::OpenableConnection;
 use crate::durable::{
@@ -273,6 +273,13 @@ impl OpenableDurableCatalogState for CatalogMigrator {
         panic!("cannot get a trace with the migrate implementation")
     }
 
+    async fn trace_collection(
+        &mut self,
+        _collection_type: CollectionType,
+    ) -> Result<Trace, CatalogError> {
+        panic!("cannot get a trace with the migrate implementation")
+    }
+
     fn set_catalog_kind(&mut self, catalog_kind: CatalogKind) {
         info!("Switching to {} backed catalog", catalog_kind.as_str());
         let direction = match catalog_kind.try_into() {
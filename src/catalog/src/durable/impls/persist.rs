@@ -47,7 +47,7 @@ use timely::progress::{Antichain, Timestamp as TimelyTimestamp};
 use tracing::{debug, error, info};
 use uuid::Uuid;
 
-use crate::durable::debug::{Collection, DebugCatalogState, Trace};
+use crate::durable::debug::{Collection, CollectionType, DebugCatalogState, Trace};
 use crate::durable::impls::persist::metrics::Metrics;
 use crate::durable::impls::persist::state_update::{IntoStateUpdateKindRaw, StateUpdateKindRaw};
 pub use crate::durable::impls::persist::state_update::{StateUpdate, StateUpdateKind};
@@ -407,6 +407,34 @@ impl UnopenedPersistCatalogState {
             .map(|state_update| state_update.try_into().expect("kind decoding error"))
     }
 
+    /// Like [`Self::snapshot_unconsolidated`], but only retains the updates of the collection of
+    /// type `collection_type`. All other updates are discarded as they are streamed in, so they
+    /// are never held in memory at once.
+    async fn snapshot_unconsolidated_collection(
+        &mut self,
+        as_of: Timestamp,
+        collection_type: CollectionType,
+    ) -> Vec<StateUpdate<StateUpdateKind>> {
+        let mut snapshot = Vec::new();
+        let mut stream = Box::pin(
+            // We use `snapshot_and_stream` because it guarantees unconsolidated output.
+            self.read_handle
+                .snapshot_and_stream(Antichain::from_elem(as_of))
+                .await
+                .expect("we have advanced the restart_as_of by the since"),
+        );
+        while let Some(update) = stream.next().await {
+            let update: StateUpdate<StateUpdateKind> =
+                Into::<StateUpdate<StateUpdateKindRaw>>::into(update)
+                    .try_into()
+                    .expect("kind decoding error");
+            if update.kind.collection_type() == Some(collection_type) {
+                snapshot.push(update);
+            }
+        }
+        snapshot
+    }
+
     /// Get the current value of config `key`.
     ///
     /// Some configs need to be read before the catalog is opened for bootstrapping.
@@ -639,6 +667,23 @@ impl OpenableDurableCatalogState for UnopenedPersistCatalogState {
         }
     }
 
+    #[tracing::instrument(level = "info", skip(self))]
+    async fn trace_collection(
+        &mut self,
+        collection_type: CollectionType,
+    ) -> Result<Trace, CatalogError> {
+        let (persist_shard_readable, current_upper) = self.is_persist_shard_readable().await;
+        if persist_shard_readable {
+            let as_of = self.as_of(current_upper);
+            let snapshot = self
+                .snapshot_unconsolidated_collection(as_of, collection_type)
+                .await;
+            Ok(Trace::from_snapshot(snapshot))
+        } else {
+            Err(CatalogError::Durable(DurableCatalogError::Uninitialized))
+        }
+    }
+
     fn set_catalog_kind(&mut self, catalog_kind: CatalogKind) {
         error!("unable to set catalog kind to {catalog_kind:?}");
     }
@@ -1366,7 +1411,7 @@ async fn snapshot_binary_inner(
     let snapshot = read_handle
         .snapshot_and_fetch(Antichain::from_elem(as_of))
         .await
-        .expect("we have advanced the restart_as_of by the since");
+        .unwrap_or_else(|err| panic!("unable to snapshot catalog at {as_of}: {err}"));
     soft_assert_no_log!(
         snapshot.iter().all(|(_, _, diff)| *diff == 1),
         "snapshot_and_fetch guarantees a consolidated result: {snapshot:?}"
@@ -1486,6 +1531,36 @@ impl Trace {
     }
 }
 
+impl StateUpdateKind {
+    /// The type of the [`Trace`] collection that this update belongs to, or `None` if it isn't
+    /// included in a [`Trace`].
+    fn collection_type(&self) -> Option<CollectionType> {
+        match self {
+            StateUpdateKind::AuditLog(_, _) => Some(CollectionType::AuditLog),
+            StateUpdateKind::Cluster(_, _) => Some(CollectionType::ComputeInstance),
+            StateUpdateKind::ClusterReplica(_, _) => Some(CollectionType::ComputeReplicas),
+            StateUpdateKind::Comment(_, _) => Some(CollectionType::Comments),
+            StateUpdateKind::Config(_, _) => Some(CollectionType::Config),
+            StateUpdateKind::Database(_, _) => Some(CollectionType::Database),
+            StateUpdateKind::DefaultPrivilege(_, _) => Some(CollectionType::DefaultPrivileges),
+            StateUpdateKind::Epoch(_) => None,
+            StateUpdateKind::IdAllocator(_, _) => Some(CollectionType::IdAlloc),
+            StateUpdateKind::IntrospectionSourceIndex(_, _) => {
+                Some(CollectionType::ComputeIntrospectionSourceIndex)
+            }
+            StateUpdateKind::Item(_, _) => Some(CollectionType::Item),
+            StateUpdateKind::Role(_, _) => Some(CollectionType::Role),
+            StateUpdateKind::Schema(_, _) => Some(CollectionType::Schema),
+            StateUpdateKind::Setting(_, _) => Some(CollectionType::Setting),
+            StateUpdateKind::StorageUsage(_, _) => Some(CollectionType::StorageUsage),
+            StateUpdateKind::SystemConfiguration(_, _) => Some(CollectionType::SystemConfiguration),
+            StateUpdateKind::SystemObjectMapping(_, _) => Some(CollectionType::SystemGidMapping),
+            StateUpdateKind::SystemPrivilege(_, _) => Some(CollectionType::SystemPrivileges),
+            StateUpdateKind::Timestamp(_, _) => Some(CollectionType::Timestamp),
+        }
+    }
+}
+
 impl UnopenedPersistCatalogState {
     /// Manually update value of `key` in collection `T` to `value`.
     #[tracing::instrument(level = "info", skip(self))]
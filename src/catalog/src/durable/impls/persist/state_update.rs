@@ -9,6 +9,7 @@
 
 use std::fmt::Debug;
 
+use mz_persist_client::fetch::DecodeResult;
 use mz_proto::{RustType, TryFromProtoError};
 use mz_repr::adt::jsonb::Jsonb;
 use mz_repr::Diff;
@@ -149,20 +150,10 @@ impl StateUpdate {
 
 /// Decodes a [`StateUpdate<StateUpdateKindRaw>`] from the `(key, value, ts,
 /// diff)` tuple/update we store in persist.
-impl
-    From<(
-        (Result<SourceData, String>, Result<(), String>),
-        Timestamp,
-        i64,
-    )> for StateUpdate<StateUpdateKindRaw>
+impl From<((DecodeResult<SourceData>, DecodeResult<()>), Timestamp, i64)>
+    for StateUpdate<StateUpdateKindRaw>
 {
-    fn from(
-        kvtd: (
-            (Result<SourceData, String>, Result<(), String>),
-            Timestamp,
-            i64,
-        ),
-    ) -> Self {
+    fn from(kvtd: ((DecodeResult<SourceData>, DecodeResult<()>), Timestamp, i64)) -> Self {
         let ((key, val), ts, diff) = kvtd;
         let (key, ()) = (
             key.expect("persist decoding error"),
@@ -25,7 +25,7 @@ use mz_repr::Timestamp;
 use mz_sql::session::vars::CatalogKind;
 use mz_storage_types::sources::Timeline;
 
-use crate::durable::debug::{DebugCatalogState, Trace};
+use crate::durable::debug::{CollectionType, DebugCatalogState, Trace};
 use crate::durable::objects::serialization::proto;
 use crate::durable::objects::{
     DurableType, Snapshot, TimelineTimestamp, TimestampKey, TimestampValue,
@@ -188,6 +188,13 @@ where
         panic!("ShadowCatalog is not used for catalog-debug tool");
     }
 
+    async fn trace_collection(
+        &mut self,
+        _collection_type: CollectionType,
+    ) -> Result<Trace, CatalogError> {
+        panic!("ShadowCatalog is not used for catalog-debug tool");
+    }
+
     fn set_catalog_kind(&mut self, catalog_kind: CatalogKind) {
         compare_and_return!(self, set_catalog_kind, catalog_kind)
     }
@@ -34,7 +34,14 @@ use mz_stash::{AppendBatch, DebugStashFactory, Diff, Stash, StashFactory, TypedC
 use mz_stash_types::StashError;
 use mz_storage_types::sources::Timeline;
 
-use crate::durable::debug::{Collection, CollectionTrace, Trace};
+use crate::durable::debug::{
+    AuditLogCollection, ClusterCollection, ClusterIntrospectionSourceIndexCollection,
+    ClusterReplicaCollection, Collection, CollectionTrace, CollectionType, CommentCollection,
+    ConfigCollection, DatabaseCollection, DefaultPrivilegeCollection, IdAllocatorCollection,
+    ItemCollection, RoleCollection, SchemaCollection, SettingCollection, StorageUsageCollection,
+    SystemConfigurationCollection, SystemItemMappingCollection, SystemPrivilegeCollection,
+    TimestampCollection, Trace,
+};
 use crate::durable::initialize::{
     CATALOG_KIND_KEY, DEPLOY_GENERATION, PERSIST_TXN_TABLES, SYSTEM_CONFIG_SYNCED_KEY,
     TOMBSTONE_KEY, USER_VERSION_KEY,
@@ -435,6 +442,62 @@ impl OpenableDurableCatalogState for OpenableConnection {
         })
     }
 
+    #[tracing::instrument(level = "info", skip(self))]
+    async fn trace_collection(
+        &mut self,
+        collection_type: CollectionType,
+    ) -> Result<Trace, CatalogError> {
+        async fn trace_col<T: Collection>(stash: &mut Stash) -> Result<Trace, CatalogError>
+        where
+            T::Key: mz_stash::Data,
+            T::Value: mz_stash::Data,
+        {
+            let values = T::stash_collection().iter(stash).await?;
+            let mut trace = Trace::new();
+            T::collection_trace_mut(&mut trace).values = values
+                .into_iter()
+                .map(|((k, v), ts, diff)| ((k, v), ts.to_string(), diff))
+                .collect();
+            Ok(trace)
+        }
+
+        let stash = match self.open_stash_read_only().await {
+            Err(e) if e.can_recover_with_write_mode() => {
+                return Err(CatalogError::Durable(DurableCatalogError::Uninitialized))
+            }
+            res => res?,
+        };
+
+        match collection_type {
+            CollectionType::AuditLog => trace_col::<AuditLogCollection>(stash).await,
+            CollectionType::ComputeInstance => trace_col::<ClusterCollection>(stash).await,
+            CollectionType::ComputeIntrospectionSourceIndex => {
+                trace_col::<ClusterIntrospectionSourceIndexCollection>(stash).await
+            }
+            CollectionType::ComputeReplicas => trace_col::<ClusterReplicaCollection>(stash).await,
+            CollectionType::Comments => trace_col::<CommentCollection>(stash).await,
+            CollectionType::Config => trace_col::<ConfigCollection>(stash).await,
+            CollectionType::Database => trace_col::<DatabaseCollection>(stash).await,
+            CollectionType::DefaultPrivileges => {
+                trace_col::<DefaultPrivilegeCollection>(stash).await
+            }
+            CollectionType::IdAlloc => trace_col::<IdAllocatorCollection>(stash).await,
+            CollectionType::Item => trace_col::<ItemCollection>(stash).await,
+            CollectionType::Role => trace_col::<RoleCollection>(stash).await,
+            CollectionType::Schema => trace_col::<SchemaCollection>(stash).await,
+            CollectionType::Setting => trace_col::<SettingCollection>(stash).await,
+            CollectionType::StorageUsage => trace_col::<StorageUsageCollection>(stash).await,
+            CollectionType::SystemConfiguration => {
+                trace_col::<SystemConfigurationCollection>(stash).await
+            }
+            CollectionType::SystemGidMapping => {
+                trace_col::<SystemItemMappingCollection>(stash).await
+            }
+            CollectionType::SystemPrivileges => trace_col::<SystemPrivilegeCollection>(stash).await,
+            CollectionType::Timestamp => trace_col::<TimestampCollection>(stash).await,
+        }
+    }
+
     fn set_catalog_kind(&mut self, catalog_kind: CatalogKind) {
         error!("unable to set catalog kind to {catalog_kind:?}");
     }
@@ -1389,6 +1452,15 @@ impl OpenableDurableCatalogState for TestOpenableConnection<'_> {
         self.openable_connection.trace().await
     }
 
+    async fn trace_collection(
+        &mut self,
+        collection_type: CollectionType,
+    ) -> Result<Trace, CatalogError> {
+        self.openable_connection
+            .trace_collection(collection_type)
+            .await
+    }
+
     fn set_catalog_kind(&mut self, catalog_kind: CatalogKind) {
         self.openable_connection.set_catalog_kind(catalog_kind);
     }
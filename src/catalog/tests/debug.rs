@@ -7,7 +7,9 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use mz_catalog::durable::debug::SettingCollection;
+use mz_catalog::durable::debug::{
+    Collection, CollectionType, SettingCollection, StorageUsageCollection,
+};
 use mz_catalog::durable::objects::serialization::proto;
 use mz_catalog::durable::{
     test_bootstrap_args, test_persist_backed_catalog_state, test_stash_backed_catalog_state,
@@ -57,6 +59,33 @@ async fn test_persist_debug() {
     .await;
 }
 
+#[mz_ore::test]
+fn test_event_time() {
+    let usage = proto::StorageUsageKey {
+        usage: Some(proto::storage_usage_key::Usage::V1(
+            proto::storage_usage_key::StorageUsageV1 {
+                id: 1,
+                shard_id: None,
+                size_bytes: 42,
+                collection_timestamp: Some(proto::EpochMillis { millis: 1000 }),
+            },
+        )),
+    };
+    assert_eq!(StorageUsageCollection::event_time(&usage, &()), Some(1000));
+
+    // Collections that aren't logs of events don't have an event time.
+    let setting_key = proto::SettingKey {
+        name: "a".to_string(),
+    };
+    let setting_value = proto::SettingValue {
+        value: "b".to_string(),
+    };
+    assert_eq!(
+        SettingCollection::event_time(&setting_key, &setting_value),
+        None
+    );
+}
+
 async fn test_debug(
     catalog_kind: &str,
     mut openable_state1: impl OpenableDurableCatalogState,
@@ -70,6 +99,15 @@ async fn test_debug(
         CatalogError::Durable(DurableCatalogError::Uninitialized).to_string()
     );
 
+    let err = openable_state1
+        .trace_collection(CollectionType::Setting)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        CatalogError::Durable(DurableCatalogError::Uninitialized).to_string()
+    );
+
     // Check initial epoch.
     let err = openable_state1.epoch().await.unwrap_err();
     assert_eq!(
@@ -99,6 +137,23 @@ async fn test_debug(
         "opening a debug catalog should not modify the contents"
     );
 
+    // Check tracing a single collection.
+    let roles = openable_state3
+        .trace_collection(CollectionType::Role)
+        .await
+        .unwrap();
+    assert!(!roles.roles.values.is_empty());
+    assert_eq!(roles.roles, trace.roles);
+    assert!(roles.clusters.values.is_empty());
+    assert!(roles.settings.values.is_empty());
+    let clusters = openable_state3
+        .trace_collection(CollectionType::ComputeInstance)
+        .await
+        .unwrap();
+    assert!(!clusters.clusters.values.is_empty());
+    assert_eq!(clusters.clusters, trace.clusters);
+    assert!(clusters.roles.values.is_empty());
+
     // Check adding a new value via `edit`.
     let prev = debug_state
         .edit::<SettingCollection>(
@@ -98,6 +98,10 @@ struct Args {
         parse(try_from_str)
     )]
     persist_txn_tables: PersistTxnTablesImpl,
+    /// The number of worker threads of the runtime persist uses for CPU-heavy
+    /// work. Defaults to the number of cores, clamped to a reasonable range.
+    #[clap(long, env = "PERSIST_ISOLATED_RUNTIME_THREADS")]
+    persist_isolated_runtime_threads: Option<usize>,
 
     // === Cloud options. ===
     /// An external ID to be supplied to all AWS AssumeRole operations.
@@ -253,8 +257,12 @@ async fn run(args: Args) -> Result<(), anyhow::Error> {
         .ok()
         .or_else(|| args.tracing.log_prefix.clone())
         .unwrap_or_default();
+    let mut persist_config = PersistConfig::new(&BUILD_INFO, SYSTEM_TIME.clone());
+    if let Some(threads) = args.persist_isolated_runtime_threads {
+        persist_config.isolated_runtime.worker_threads = threads;
+    }
     let persist_clients = Arc::new(PersistClientCache::new(
-        PersistConfig::new(&BUILD_INFO, SYSTEM_TIME.clone()),
+        persist_config,
         &metrics_registry,
         |persist_cfg, metrics| {
             let cfg = PersistPubSubClientConfig {
@@ -29,10 +29,11 @@
 //! from compacting beyond the allowed compaction of each of its outputs, ensuring that we can
 //! recover each dataflow to its current state in case of failure or other reconfiguration.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroI64;
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
 use differential_dataflow::consolidation::consolidate;
 use differential_dataflow::lattice::Lattice;
 use futures::{future, FutureExt};
@@ -42,14 +43,17 @@ use mz_cluster_client::ReplicaId;
 use mz_compute_types::dataflows::DataflowDescription;
 use mz_compute_types::ComputeInstanceId;
 use mz_expr::RowSetFinishing;
+use mz_ore::cast::CastFrom;
 use mz_ore::metrics::MetricsRegistry;
 use mz_ore::tracing::OpenTelemetryContext;
-use mz_repr::{Diff, GlobalId, Row};
+use mz_repr::{Datum, Diff, GlobalId, Row};
 use mz_storage_client::controller::{IntrospectionType, StorageController};
 use mz_storage_types::read_policy::ReadPolicy;
 use serde::{Deserialize, Serialize};
 use timely::progress::frontier::{AntichainRef, MutableAntichain};
-use timely::progress::{Antichain, Timestamp};
+use timely::progress::{Antichain, ChangeBatch, Timestamp};
+use timely::PartialOrder;
+use tokio::sync::watch;
 use tracing::warn;
 use uuid::Uuid;
 
@@ -61,7 +65,7 @@ use crate::controller::error::{
 use crate::controller::instance::{ActiveInstance, Instance};
 use crate::controller::replica::ReplicaConfig;
 use crate::logging::{LogVariant, LoggingConfig};
-use crate::metrics::ComputeControllerMetrics;
+use crate::metrics::{ComputeControllerMetrics, IntCounter};
 use crate::protocol::command::{ComputeParameters, PeekTarget};
 use crate::protocol::response::{ComputeResponse, PeekResponse, SubscribeResponse};
 use crate::service::{ComputeClient, ComputeGrpcClient};
@@ -79,6 +83,12 @@ pub enum ComputeControllerResponse<T> {
     /// See [`ComputeResponse::PeekResponse`].
     PeekResponse(Uuid, PeekResponse, OpenTelemetryContext),
     /// See [`ComputeResponse::SubscribeResponse`].
+    ///
+    /// Unlike the batches reported by replicas, batches emitted by the controller can have an
+    /// empty time interval (`lower == upper`), if the controller split a large batch into
+    /// several. Such batches carry updates but don't advance the subscribe's frontier, which
+    /// happens with the last batch of the split. The updates of all the batches together are the
+    /// updates of the original batch.
     SubscribeResponse(GlobalId, SubscribeResponse<T>),
     /// See [`ComputeResponse::FrontierUpper`]
     FrontierUpper { id: GlobalId, upper: Antichain<T> },
@@ -124,6 +134,8 @@ pub struct ComputeController<T> {
     default_idle_arrangement_merge_effort: u32,
     /// Default value for `arrangement_exert_proportionality`.
     default_arrangement_exert_proportionality: u32,
+    /// How long instances remember dropped collections when classifying replica responses.
+    dropped_collection_retention: Duration,
     /// A replica response to be handled by the corresponding `Instance` on a subsequent call to
     /// `ActiveComputeController::process`.
     stashed_replica_response: Option<(ComputeInstanceId, ReplicaId, ComputeResponse<T>)>,
@@ -149,6 +161,7 @@ impl<T> ComputeController<T> {
         metrics_registry: MetricsRegistry,
     ) -> Self {
         let (response_tx, response_rx) = crossbeam_channel::unbounded();
+        let metrics = ComputeControllerMetrics::new(metrics_registry);
 
         Self {
             instances: BTreeMap::new(),
@@ -157,10 +170,11 @@ impl<T> ComputeController<T> {
             config: Default::default(),
             default_idle_arrangement_merge_effort: 1000,
             default_arrangement_exert_proportionality: 16,
+            dropped_collection_retention: Duration::from_secs(5 * 60),
             stashed_replica_response: None,
             envd_epoch,
-            metrics: ComputeControllerMetrics::new(metrics_registry),
-            introspection: Introspection::new(),
+            introspection: Introspection::new(metrics.clone()),
+            metrics,
             response_rx,
             response_tx,
         }
@@ -241,6 +255,17 @@ impl<T> ComputeController<T> {
     pub fn set_default_arrangement_exert_proportionality(&mut self, value: u32) {
         self.default_arrangement_exert_proportionality = value;
     }
+
+    /// Set how long instances remember dropped collections.
+    ///
+    /// Replica responses for collections dropped within this duration are assumed to have raced
+    /// with the drop and are only counted, rather than reported as errors.
+    pub fn set_dropped_collection_retention(&mut self, value: Duration) {
+        self.dropped_collection_retention = value;
+        for instance in self.instances.values_mut() {
+            instance.set_dropped_collection_retention(value);
+        }
+    }
 }
 
 impl<T> ComputeController<T>
@@ -296,6 +321,7 @@ where
                 self.metrics.for_instance(id),
                 self.response_tx.clone(),
                 self.introspection.tx.clone(),
+                self.dropped_collection_retention,
             ),
         );
 
@@ -327,6 +353,7 @@ where
             instance.update_configuration(config_params.clone());
         }
 
+        self.introspection.update_configuration(&config_params);
         self.config.update(config_params);
     }
 
@@ -363,6 +390,20 @@ where
             return;
         }
 
+        // Failed replicas waiting for their restart backoff to elapse require processing once it
+        // has.
+        let next_rehydration = self
+            .instances
+            .values()
+            .filter_map(|i| i.next_rehydration())
+            .min();
+        let rehydration = async {
+            match next_rehydration {
+                Some(time) => tokio::time::sleep_until(time.into()).await,
+                None => future::pending().await,
+            }
+        };
+
         if self.instances.is_empty() {
             // If there are no clients, block forever. This signals that there may be more work to
             // do (e.g., if a compute instance is created). Calling `select_all` with an empty list
@@ -385,12 +426,13 @@ where
                     }
                     Err(_) => {
                         // There is nothing to do here. `recv` has already added the failed replica to
-                        // `instance.failed_replicas`, so it will be rehydrated in the next call to
-                        // `ActiveComputeController::process`.
+                        // `instance.failed_replicas`, so it will be rehydrated by
+                        // `ActiveComputeController::process` once its restart backoff has elapsed.
                     }
                 }
             },
             () = self.introspection.sleep() => (),
+            () = rehydration => (),
         }
     }
 
@@ -408,6 +450,23 @@ where
             .set_subscribe_target_replica(subscribe_id, target_replica)?;
         Ok(())
     }
+
+    /// Acknowledge that `bytes` bytes of output of the identified subscribe have been drained by
+    /// its consumer.
+    ///
+    /// The controller terminates subscribes whose emitted but unacknowledged output exceeds the
+    /// `subscribe_max_buffered_bytes` limit, so consumers of subscribe responses are expected to
+    /// acknowledge the size of the updates in each response they process.
+    pub fn ack_subscribe_output(
+        &mut self,
+        instance_id: ComputeInstanceId,
+        subscribe_id: GlobalId,
+        bytes: u64,
+    ) -> Result<(), InstanceMissing> {
+        self.instance_mut(instance_id)?
+            .ack_subscribe_output(subscribe_id, bytes);
+        Ok(())
+    }
 }
 
 /// A wrapper around a [`ComputeController`] with a live connection to a storage controller.
@@ -440,7 +499,7 @@ impl<T> ActiveComputeController<'_, T> {
 
 impl<T> ActiveComputeController<'_, T>
 where
-    T: Timestamp + Lattice,
+    T: Timestamp + Lattice + Into<mz_repr::Timestamp>,
     ComputeGrpcClient: ComputeClient<T>,
 {
     /// Adds replicas of an instance.
@@ -499,12 +558,81 @@ where
     /// It installs read dependencies from the outputs to the inputs, so that the input read
     /// capabilities will be held back to the output read capabilities, ensuring that we are
     /// always able to return to a state that can serve the output read capabilities.
+    ///
+    /// A [`DataflowActivation::Suspended`] dataflow is not installed on the replicas until it is
+    /// activated through [`ActiveComputeController::activate_collection`], or by a peek or another
+    /// dataflow reading from one of its outputs. Until then, the outputs' frontiers are tracked and their
+    /// inputs are held readable as usual.
     pub fn create_dataflow(
         &mut self,
         instance_id: ComputeInstanceId,
         dataflow: DataflowDescription<mz_compute_types::plan::Plan<T>, (), T>,
+        activation: DataflowActivation,
     ) -> Result<(), DataflowCreationError> {
-        self.instance(instance_id)?.create_dataflow(dataflow)?;
+        self.instance(instance_id)?.create_dataflow(
+            dataflow,
+            activation,
+            BTreeMap::new(),
+            BTreeMap::new(),
+        )?;
+        Ok(())
+    }
+
+    /// Like [`ActiveComputeController::create_dataflow`], but installs the exports in
+    /// `read_policies` with the given read policies.
+    ///
+    /// Otherwise, exports hold back their inputs to the dataflow's `as_of` until
+    /// [`ActiveComputeController::set_read_policy`] is called, which on busy systems can take long
+    /// enough to cause noticeable extra retention.
+    pub fn create_dataflow_with_read_policies(
+        &mut self,
+        instance_id: ComputeInstanceId,
+        dataflow: DataflowDescription<mz_compute_types::plan::Plan<T>, (), T>,
+        activation: DataflowActivation,
+        read_policies: BTreeMap<GlobalId, ReadPolicy<T>>,
+    ) -> Result<(), DataflowCreationError> {
+        self.instance(instance_id)?.create_dataflow(
+            dataflow,
+            activation,
+            BTreeMap::new(),
+            read_policies,
+        )?;
+        Ok(())
+    }
+
+    /// Like [`ActiveComputeController::create_dataflow`], but installs the dataflow on the
+    /// replicas in `replica_as_ofs` at the given `as_of`s instead of the dataflow's `as_of`.
+    ///
+    /// This allows replicas to skip computing history they won't be asked to serve, for example
+    /// when a replica is added to backfill an existing dataflow. The overrides must be beyond the
+    /// dataflow's `as_of`, and also apply to replicas that are added later. The remaining replicas
+    /// install the dataflow at its `as_of` as usual.
+    pub fn create_dataflow_with_replica_as_ofs(
+        &mut self,
+        instance_id: ComputeInstanceId,
+        dataflow: DataflowDescription<mz_compute_types::plan::Plan<T>, (), T>,
+        replica_as_ofs: BTreeMap<ReplicaId, Antichain<T>>,
+    ) -> Result<(), DataflowCreationError> {
+        self.instance(instance_id)?.create_dataflow(
+            dataflow,
+            DataflowActivation::Immediate,
+            replica_as_ofs,
+            BTreeMap::new(),
+        )?;
+        Ok(())
+    }
+
+    /// Install the suspended dataflow exporting the given collection on the replicas.
+    ///
+    /// The dataflow's `as_of` is advanced to the current `since`s of its inputs. Activating a
+    /// collection that is not suspended has no effect.
+    pub fn activate_collection(
+        &mut self,
+        instance_id: ComputeInstanceId,
+        collection_id: GlobalId,
+    ) -> Result<(), CollectionUpdateError> {
+        self.instance(instance_id)?
+            .activate_collection(collection_id)?;
         Ok(())
     }
 
@@ -658,6 +786,114 @@ impl<T> ComputeInstanceRef<'_, T> {
     pub fn collections(&self) -> impl Iterator<Item = (&GlobalId, &CollectionState<T>)> {
         self.instance.collections_iter()
     }
+
+    /// Return information about the identified replica, or `None` if the replica does not exist.
+    pub fn replica_info(&self, id: ReplicaId) -> Option<ReplicaInfo> {
+        self.instance.replica_info(id)
+    }
+}
+
+impl<T: Clone> ComputeInstanceRef<'_, T> {
+    /// Return a description of the identified pending peek, or `None` if the peek is not
+    /// tracked by this instance.
+    pub fn describe_peek(&self, uuid: Uuid) -> Option<PeekDescription<T>> {
+        self.instance.describe_peek(uuid)
+    }
+
+    /// Return descriptions of all peeks pending on this instance.
+    pub fn list_pending_peeks(&self) -> Vec<(Uuid, PeekDescription<T>)> {
+        self.instance.list_pending_peeks()
+    }
+
+    /// Return the write frontier the identified replica has reported for the identified
+    /// collection, or `None` if either doesn't exist or the replica hasn't reported a frontier
+    /// for the collection yet.
+    pub fn replica_write_frontier(
+        &self,
+        replica_id: ReplicaId,
+        id: GlobalId,
+    ) -> Option<Antichain<T>> {
+        self.instance.replica_write_frontier(replica_id, id)
+    }
+
+    /// Return the write frontiers all replicas have reported for the identified collection.
+    pub fn replica_write_frontiers(
+        &self,
+        id: GlobalId,
+    ) -> Result<BTreeMap<ReplicaId, Antichain<T>>, CollectionMissing> {
+        self.instance.replica_write_frontiers(id)
+    }
+}
+
+impl<T: Timestamp> ComputeInstanceRef<'_, T> {
+    /// Return the total number of outstanding read capabilities on the identified collection.
+    pub fn total_read_hold_count(&self, id: GlobalId) -> Result<i64, CollectionMissing> {
+        self.instance.total_read_hold_count(id)
+    }
+
+    /// Return the read capabilities on collections of this instance whose holders don't exist
+    /// anymore.
+    pub fn leaked_read_holds(&self) -> BTreeMap<GlobalId, BTreeMap<ReadHoldSource, i64>> {
+        self.instance.leaked_read_holds()
+    }
+
+    /// Return, for each replica, the collections it has not finished hydrating yet.
+    pub fn hydration_status(&self) -> BTreeMap<ReplicaId, BTreeSet<GlobalId>> {
+        self.instance.hydration_status()
+    }
+}
+
+/// Whether a newly created dataflow is installed on the replicas right away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataflowActivation {
+    /// Install the dataflow on the replicas immediately.
+    Immediate,
+    /// Withhold the dataflow from the replicas until one of its exports is activated.
+    Suspended,
+}
+
+/// A holder of read capabilities on a compute collection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReadHoldSource {
+    /// The read policy of the collection itself.
+    Policy,
+    /// The identified pending peek.
+    Peek(Uuid),
+    /// The identified compute collection reading from the collection, e.g. an index,
+    /// materialized view, or subscribe exported by a dependent dataflow.
+    Dependent(GlobalId),
+}
+
+/// A read-only description of a peek pending on a compute instance, for debugging peeks that
+/// don't complete.
+#[derive(Clone, Debug)]
+pub struct PeekDescription<T> {
+    /// Information about the collection targeted by the peek.
+    pub target: PeekTarget,
+    /// The peek time.
+    pub time: T,
+    /// For replica-targeted peeks, the replica whose response we are waiting on.
+    ///
+    /// If this value is `None`, the first response from any replica is passed on.
+    pub target_replica: Option<ReplicaId>,
+    /// How long the peek has been outstanding.
+    pub outstanding: Duration,
+}
+
+/// Read-only information about a replica of a compute instance.
+#[derive(Clone, Debug)]
+pub struct ReplicaInfo {
+    /// The time of the last heartbeat reported by the replica.
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    /// Collections the replica has reported on that the controller has never tracked.
+    ///
+    /// A non-empty set indicates a bug in the compute protocol.
+    pub quarantined_collections: BTreeSet<GlobalId>,
+    /// The number of times the replica has failed since it was last considered healthy.
+    pub consecutive_failures: u32,
+    /// If the replica has failed, the time at which its restart backoff elapses and it is due to
+    /// be rehydrated.
+    pub next_restart: Option<Instant>,
 }
 
 /// State maintained about individual compute collections.
@@ -670,6 +906,10 @@ pub struct CollectionState<T> {
     ///
     /// Log collections are special in that they are only maintained by a subset of all replicas.
     log_collection: bool,
+    /// The `as_of` the collection's dataflow is installed at.
+    ///
+    /// For suspended dataflows, this is advanced on activation.
+    as_of: Antichain<T>,
 
     /// Accumulation of read capabilities for the collection.
     ///
@@ -680,6 +920,10 @@ pub struct CollectionState<T> {
     implied_capability: Antichain<T>,
     /// The policy to use to downgrade `self.implied_capability`.
     read_policy: ReadPolicy<T>,
+    /// The read capabilities in `self.read_capabilities`, by the holder contributing them.
+    ///
+    /// This is only used for debugging, to find out who holds back the read frontier.
+    read_hold_sources: BTreeMap<ReadHoldSource, ChangeBatch<T>>,
 
     /// Storage identifiers on which this collection depends.
     storage_dependencies: Vec<GlobalId>,
@@ -690,6 +934,12 @@ pub struct CollectionState<T> {
     write_frontier: Antichain<T>,
     /// The write frontiers reported by individual replicas.
     replica_write_frontiers: BTreeMap<ReplicaId, Antichain<T>>,
+    /// The write frontiers individual replicas started out with, before they reported any.
+    replica_initial_frontiers: BTreeMap<ReplicaId, Antichain<T>>,
+    /// Notifies subscribers of advances of `write_frontier`.
+    ///
+    /// Dropped along with the collection state, which closes the channel.
+    write_frontier_tx: watch::Sender<Antichain<T>>,
 }
 
 impl<T> CollectionState<T> {
@@ -698,6 +948,11 @@ impl<T> CollectionState<T> {
         &self.implied_capability
     }
 
+    /// Reports the `as_of` the collection's dataflow is installed at.
+    pub fn as_of(&self) -> AntichainRef<T> {
+        self.as_of.borrow()
+    }
+
     /// Reports the current read frontier.
     pub fn read_frontier(&self) -> AntichainRef<T> {
         self.read_capabilities.frontier()
@@ -708,6 +963,13 @@ impl<T> CollectionState<T> {
         self.write_frontier.borrow()
     }
 
+    /// Returns a receiver that observes every advance of the write frontier.
+    ///
+    /// The receiver's `changed` future resolves with an error once the collection is dropped.
+    pub fn subscribe_write_frontier(&self) -> watch::Receiver<Antichain<T>> {
+        self.write_frontier_tx.subscribe()
+    }
+
     /// Reports the IDs of the dependencies of this collection.
     fn dependency_ids(&self) -> impl Iterator<Item = GlobalId> + '_ {
         let compute = self.compute_dependencies.iter().copied();
@@ -731,16 +993,107 @@ impl<T: Timestamp> CollectionState<T> {
         let mut read_capabilities = MutableAntichain::new();
         read_capabilities.update_iter(since.iter().map(|time| (time.clone(), 1)));
 
+        let mut policy_holds = ChangeBatch::new();
+        policy_holds.extend(since.iter().map(|time| (time.clone(), 1)));
+        let read_hold_sources = BTreeMap::from([(ReadHoldSource::Policy, policy_holds)]);
+        let (write_frontier_tx, _) = watch::channel(upper.clone());
+
         Self {
             log_collection: false,
+            as_of,
             read_capabilities,
+            read_hold_sources,
             implied_capability: since.clone(),
             read_policy: ReadPolicy::ValidFrom(since),
             storage_dependencies,
             compute_dependencies,
             write_frontier: upper,
             replica_write_frontiers: BTreeMap::new(),
+            replica_initial_frontiers: BTreeMap::new(),
+            write_frontier_tx,
+        }
+    }
+
+    /// Downgrades the implied capability to the frontier the read policy derives from the write
+    /// frontier, and returns the resulting changes to the read capabilities.
+    ///
+    /// The implied capability never regresses, so this has no effect if the policy frontier is
+    /// not beyond it.
+    fn apply_read_policy(&mut self) -> ChangeBatch<T> {
+        let mut new_read_capability = self.read_policy.frontier(self.write_frontier.borrow());
+        let mut update = ChangeBatch::new();
+        if PartialOrder::less_equal(&self.implied_capability, &new_read_capability) {
+            update.extend(new_read_capability.iter().map(|time| (time.clone(), 1)));
+            std::mem::swap(&mut self.implied_capability, &mut new_read_capability);
+            update.extend(new_read_capability.iter().map(|time| (time.clone(), -1)));
+        }
+        update
+    }
+
+    /// Reports whether the identified replica has hydrated the collection, i.e. reported a write
+    /// frontier beyond the `as_of` it installed the collection at and beyond the frontier its
+    /// tracking started out with.
+    ///
+    /// An empty write frontier always counts as hydrated. Replicas added after the collection's
+    /// read frontier became empty start out at the empty frontier and can't advance beyond it.
+    fn hydrated_on(&self, replica_id: ReplicaId, as_of: &Antichain<T>) -> bool {
+        let frontier = self.replica_write_frontiers.get(&replica_id);
+        let initial = self.replica_initial_frontiers.get(&replica_id);
+        match (frontier, initial) {
+            (Some(frontier), Some(initial)) => {
+                frontier.is_empty()
+                    || (!PartialOrder::less_equal(frontier, as_of)
+                        && !PartialOrder::less_equal(frontier, initial))
+            }
+            _ => false,
+        }
+    }
+
+    /// Advances the write frontier to `new_upper`, notifying subscribers, and reports whether
+    /// it advanced.
+    fn advance_write_frontier(&mut self, new_upper: &Antichain<T>) -> bool {
+        if PartialOrder::less_than(&self.write_frontier, new_upper) {
+            self.write_frontier = new_upper.clone();
+            self.write_frontier_tx.send_replace(new_upper.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Records that `source` changed its read capabilities on this collection by `changes`.
+    ///
+    /// This only updates the debugging information in `self.read_hold_sources`, it is the
+    /// caller's responsibility to apply `changes` to `self.read_capabilities`.
+    fn record_read_hold_changes(
+        &mut self,
+        source: ReadHoldSource,
+        changes: impl IntoIterator<Item = (T, i64)>,
+    ) {
+        let counts = self
+            .read_hold_sources
+            .entry(source)
+            .or_insert_with(ChangeBatch::new);
+        counts.extend(changes);
+        if counts.is_empty() {
+            self.read_hold_sources.remove(&source);
+        }
+    }
+
+    /// Reports the number of outstanding read capabilities at each time, by the holder
+    /// contributing them.
+    pub fn read_hold_counts(&self) -> BTreeMap<T, BTreeMap<ReadHoldSource, i64>> {
+        let mut counts: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for (source, changes) in &self.read_hold_sources {
+            for (time, diff) in changes.clone().iter() {
+                *counts
+                    .entry(time.clone())
+                    .or_default()
+                    .entry(*source)
+                    .or_default() += diff;
+            }
         }
+        counts
     }
 
     pub fn new_log_collection() -> Self {
@@ -751,7 +1104,33 @@ impl<T: Timestamp> CollectionState<T> {
     }
 }
 
+/// The priority of an introspection type.
+///
+/// Updates of low-priority types may be shed while the controller is overloaded, updates of
+/// high-priority types are always delivered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IntrospectionPriority {
+    High,
+    Low,
+}
+
+impl IntrospectionPriority {
+    fn of(type_: IntrospectionType) -> Self {
+        match type_ {
+            IntrospectionType::ComputeReplicaHeartbeats
+            | IntrospectionType::ComputeSubscribeLag => Self::Low,
+            _ => Self::High,
+        }
+    }
+}
+
 /// Compute controller introspection support.
+///
+/// When the number of introspection updates received between two recordings exceeds the
+/// configured shedding backlog, updates of low-priority introspection types are shed: They are
+/// either deferred, to be recorded in consolidated form once the backlog has cleared, or dropped
+/// entirely. Dropped updates are counted in a metric, and their running totals are periodically
+/// recorded in the `ComputeIntrospectionShedding` introspection collection.
 struct Introspection {
     /// Receiver for introspection updates produced by `Instance`s.
     rx: crossbeam_channel::Receiver<IntrospectionUpdates>,
@@ -761,22 +1140,66 @@ struct Introspection {
     last_recording: Instant,
     /// Amount of time to sleep between recordings.
     sleep_time: Duration,
+    /// The number of received updates above which low-priority updates are shed, if any.
+    shedding_backlog: Option<usize>,
+    /// Whether shed updates are dropped, rather than deferred.
+    shedding_drop: bool,
+    /// Consolidated low-priority updates deferred until the backlog has cleared.
+    deferred: BTreeMap<IntrospectionType, Vec<(Row, Diff)>>,
+    /// The net counts of low-priority rows whose insertions were dropped and not yet retracted.
+    ///
+    /// Used to suppress retractions of rows whose insertions were dropped. This is only populated
+    /// while shedding drops updates.
+    dropped: BTreeMap<IntrospectionType, BTreeMap<Row, Diff>>,
+    /// The number of dropped updates since the last shedding summary, per type.
+    shed_since_summary: BTreeMap<IntrospectionType, u64>,
+    /// The total number of dropped updates, per type, as of the last shedding summary.
+    shed_totals: BTreeMap<IntrospectionType, u64>,
+    /// Time when the last shedding summary was recorded.
+    last_summary: Instant,
+    /// Counters of dropped updates, per type.
+    shed_counters: BTreeMap<IntrospectionType, IntCounter>,
+    /// The compute controller metrics.
+    metrics: ComputeControllerMetrics,
 }
 
 impl Introspection {
-    fn new() -> Self {
+    /// Minimum amount of time between two shedding summaries.
+    const SHEDDING_SUMMARY_INTERVAL: Duration = Duration::from_secs(60);
+
+    fn new(metrics: ComputeControllerMetrics) -> Self {
         let (tx, rx) = crossbeam_channel::unbounded();
         Self {
             tx,
             rx,
             last_recording: Instant::now(),
             sleep_time: Duration::from_secs(1),
+            shedding_backlog: None,
+            shedding_drop: false,
+            deferred: BTreeMap::new(),
+            dropped: BTreeMap::new(),
+            shed_since_summary: BTreeMap::new(),
+            shed_totals: BTreeMap::new(),
+            last_summary: Instant::now(),
+            shed_counters: BTreeMap::new(),
+            metrics,
+        }
+    }
+
+    /// Apply the shedding policy contained in the given configuration.
+    fn update_configuration(&mut self, config_params: &ComputeParameters) {
+        if let Some(backlog) = config_params.introspection_shedding_backlog {
+            self.shedding_backlog = backlog.map(usize::cast_from);
+        }
+        if let Some(drop) = config_params.introspection_shedding_drop {
+            self.shedding_drop = drop;
         }
     }
 
     /// Whether we are ready to record introspection updates.
     fn ready_for_recording(&self) -> bool {
-        let updates_available = !self.rx.is_empty();
+        let updates_available =
+            !self.rx.is_empty() || !self.deferred.is_empty() || self.shedding_summary_due();
         let time_elapsed = self.last_recording.elapsed() > self.sleep_time;
         updates_available && time_elapsed
     }
@@ -795,7 +1218,15 @@ impl Introspection {
         let mut updates_by_type = BTreeMap::new();
 
         if self.ready_for_recording() {
+            let mut backlog = 0;
             for (type_, updates) in self.rx.try_iter() {
+                backlog += updates.len();
+                updates_by_type
+                    .entry(type_)
+                    .or_insert_with(Vec::new)
+                    .extend(updates);
+            }
+            for (type_, updates) in std::mem::take(&mut self.deferred) {
                 updates_by_type
                     .entry(type_)
                     .or_insert_with(Vec::new)
@@ -804,8 +1235,259 @@ impl Introspection {
             for updates in updates_by_type.values_mut() {
                 consolidate(updates);
             }
+
+            let overloaded = self.shedding_backlog.map_or(false, |max| backlog > max);
+            if overloaded {
+                self.shed(&mut updates_by_type);
+            }
+
+            for (type_, updates) in updates_by_type.iter_mut() {
+                self.suppress_dropped_retractions(*type_, updates);
+            }
+
+            if self.shedding_summary_due() {
+                let summary = self.shedding_summary();
+                updates_by_type
+                    .entry(IntrospectionType::ComputeIntrospectionShedding)
+                    .or_insert_with(Vec::new)
+                    .extend(summary);
+            }
+            updates_by_type.retain(|_, updates| !updates.is_empty());
         }
 
         updates_by_type.into_iter()
     }
+
+    /// Shed the low-priority updates in `updates_by_type`, according to the shedding policy.
+    ///
+    /// Retractions are not dropped, as they might undo previously recorded insertions.
+    fn shed(&mut self, updates_by_type: &mut BTreeMap<IntrospectionType, Vec<(Row, Diff)>>) {
+        for (type_, updates) in updates_by_type.iter_mut() {
+            if IntrospectionPriority::of(*type_) != IntrospectionPriority::Low {
+                continue;
+            }
+
+            if self.shedding_drop {
+                let dropped_rows = self.dropped.entry(*type_).or_default();
+                let mut dropped = 0;
+                updates.retain(|(row, diff)| {
+                    if *diff > 0 {
+                        dropped += diff.unsigned_abs();
+                        *dropped_rows.entry(row.clone()).or_default() += *diff;
+                        false
+                    } else {
+                        true
+                    }
+                });
+                if dropped > 0 {
+                    let metrics = &self.metrics;
+                    self.shed_counters
+                        .entry(*type_)
+                        .or_insert_with(|| metrics.introspection_shed_rows_total(*type_))
+                        .inc_by(dropped);
+                    *self.shed_since_summary.entry(*type_).or_default() += dropped;
+                }
+            } else if !updates.is_empty() {
+                self.deferred.insert(*type_, std::mem::take(updates));
+            }
+        }
+    }
+
+    /// Suppress retractions in the given updates about to be recorded that undo insertions that
+    /// were dropped, and were thus never recorded.
+    fn suppress_dropped_retractions(
+        &mut self,
+        type_: IntrospectionType,
+        updates: &mut Vec<(Row, Diff)>,
+    ) {
+        let Some(dropped) = self.dropped.get_mut(&type_) else {
+            return;
+        };
+        updates.retain_mut(|(row, diff)| {
+            if *diff > 0 {
+                return true;
+            }
+            let Some(count) = dropped.get_mut(row) else {
+                return true;
+            };
+
+            let suppressed = std::cmp::min(-*diff, *count);
+            *diff += suppressed;
+            *count -= suppressed;
+            if *count == 0 {
+                dropped.remove(row);
+            }
+            *diff != 0
+        });
+        if dropped.is_empty() {
+            self.dropped.remove(&type_);
+        }
+    }
+
+    /// Whether updates were dropped since the last shedding summary, and enough time has passed
+    /// to record another one.
+    fn shedding_summary_due(&self) -> bool {
+        !self.shed_since_summary.is_empty()
+            && self.last_summary.elapsed() >= Self::SHEDDING_SUMMARY_INTERVAL
+    }
+
+    /// Return the updates to the `ComputeIntrospectionShedding` collection that bring the
+    /// recorded totals of dropped updates up to date, and start a new summary period.
+    fn shedding_summary(&mut self) -> Vec<(Row, Diff)> {
+        let shed = std::mem::take(&mut self.shed_since_summary);
+        warn!(
+            ?shed,
+            "dropped low-priority introspection updates due to backlog"
+        );
+        self.last_summary = Instant::now();
+
+        let mut updates = Vec::new();
+        for (type_, count) in shed {
+            let type_name = format!("{type_:?}");
+            let total = self.shed_totals.entry(type_).or_default();
+            if *total > 0 {
+                let row = Row::pack_slice(&[Datum::String(&type_name), Datum::UInt64(*total)]);
+                updates.push((row, -1));
+            }
+            *total += count;
+            let row = Row::pack_slice(&[Datum::String(&type_name), Datum::UInt64(*total)]);
+            updates.push((row, 1));
+        }
+        updates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an `Introspection` that is ready for recording and applies the given shedding
+    /// policy.
+    fn test_introspection(backlog: u64, drop: bool) -> Introspection {
+        let metrics = ComputeControllerMetrics::new(MetricsRegistry::new());
+        let mut introspection = Introspection::new(metrics);
+        introspection.last_recording = Instant::now()
+            .checked_sub(Duration::from_secs(2))
+            .expect("instant in range");
+        introspection.update_configuration(&ComputeParameters {
+            introspection_shedding_backlog: Some(Some(backlog)),
+            introspection_shedding_drop: Some(drop),
+            ..Default::default()
+        });
+        introspection
+    }
+
+    fn row(name: &str, i: i64) -> Row {
+        Row::pack_slice(&[Datum::String(name), Datum::Int64(i)])
+    }
+
+    fn send(introspection: &Introspection, type_: IntrospectionType, updates: Vec<(Row, Diff)>) {
+        introspection
+            .tx
+            .send((type_, updates))
+            .expect("receiver alive");
+    }
+
+    fn record(introspection: &mut Introspection) -> BTreeMap<IntrospectionType, Vec<(Row, Diff)>> {
+        introspection.updates_for_recording().collect()
+    }
+
+    #[mz_ore::test]
+    fn introspection_shedding_drop() {
+        let mut introspection = test_introspection(10, true);
+        let heartbeats = IntrospectionType::ComputeReplicaHeartbeats;
+        let dependencies = IntrospectionType::ComputeDependencies;
+
+        // Below the backlog threshold, low-priority updates are delivered.
+        send(&introspection, heartbeats, vec![(row("a", 0), 1)]);
+        let recorded = record(&mut introspection);
+        assert_eq!(recorded[&heartbeats], vec![(row("a", 0), 1)]);
+        // Nothing was dropped, so nothing needs to be tracked.
+        assert!(introspection.dropped.is_empty());
+
+        // Saturate the pipeline with low-priority updates.
+        let mut updates: Vec<_> = (0..100).map(|i| (row("b", i), 1)).collect();
+        updates.push((row("a", 0), -1));
+        send(&introspection, heartbeats, updates);
+        send(&introspection, dependencies, vec![(row("d", 0), 1)]);
+
+        let recorded = record(&mut introspection);
+        assert_eq!(recorded[&dependencies], vec![(row("d", 0), 1)]);
+        assert_eq!(recorded[&heartbeats], vec![(row("a", 0), -1)]);
+        assert_eq!(introspection.shed_counters[&heartbeats].get(), 100);
+        assert_eq!(introspection.shed_since_summary[&heartbeats], 100);
+
+        // Retractions of dropped rows are suppressed.
+        send(&introspection, heartbeats, vec![(row("b", 0), -1)]);
+        let recorded = record(&mut introspection);
+        assert!(recorded.is_empty());
+    }
+
+    #[mz_ore::test]
+    fn introspection_shedding_defer() {
+        let mut introspection = test_introspection(10, false);
+        let lag = IntrospectionType::ComputeSubscribeLag;
+        let hydration = IntrospectionType::ComputeHydrationStatus;
+
+        // Saturate the pipeline with low-priority updates, some of which cancel out.
+        let mut updates: Vec<_> = (0..20).map(|i| (row("l", i), 1)).collect();
+        updates.extend((10..20).map(|i| (row("l", i), -1)));
+        send(&introspection, lag, updates);
+        send(&introspection, hydration, vec![(row("h", 0), 1)]);
+
+        let recorded = record(&mut introspection);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[&hydration], vec![(row("h", 0), 1)]);
+        assert!(introspection.shed_counters.is_empty());
+        assert!(introspection.dropped.is_empty());
+
+        // Once the backlog has cleared, the deferred updates are delivered in consolidated form.
+        assert!(introspection.ready_for_recording());
+        let mut recorded = record(&mut introspection);
+        let mut expected: Vec<_> = (0..10).map(|i| (row("l", i), 1)).collect();
+        expected.sort();
+        assert_eq!(recorded.remove(&lag), Some(expected));
+        assert!(!introspection.ready_for_recording());
+    }
+
+    #[mz_ore::test]
+    fn introspection_shedding_summary() {
+        let mut introspection = test_introspection(10, true);
+        let heartbeats = IntrospectionType::ComputeReplicaHeartbeats;
+        let shedding = IntrospectionType::ComputeIntrospectionShedding;
+        let summary_row = |total| {
+            Row::pack_slice(&[
+                Datum::String("ComputeReplicaHeartbeats"),
+                Datum::UInt64(total),
+            ])
+        };
+        let summary_interval_elapsed = || {
+            Instant::now()
+                .checked_sub(Introspection::SHEDDING_SUMMARY_INTERVAL)
+                .expect("instant in range")
+        };
+
+        // Dropped updates are not summarized before the summary interval has passed.
+        let updates: Vec<_> = (0..20).map(|i| (row("b", i), 1)).collect();
+        send(&introspection, heartbeats, updates);
+        let recorded = record(&mut introspection);
+        assert!(recorded.is_empty());
+        assert!(!introspection.ready_for_recording());
+
+        // Once it has, a summary is due even without new updates.
+        introspection.last_summary = summary_interval_elapsed();
+        assert!(introspection.ready_for_recording());
+        let recorded = record(&mut introspection);
+        assert_eq!(recorded[&shedding], vec![(summary_row(20), 1)]);
+        assert!(!introspection.ready_for_recording());
+
+        // The next summary replaces the previous total.
+        let updates: Vec<_> = (20..35).map(|i| (row("b", i), 1)).collect();
+        send(&introspection, heartbeats, updates);
+        introspection.last_summary = summary_interval_elapsed();
+        let mut recorded = record(&mut introspection);
+        let summary = recorded.remove(&shedding).expect("summary recorded");
+        assert_eq!(summary, vec![(summary_row(20), -1), (summary_row(35), 1)]);
+        assert!(recorded.is_empty());
+    }
 }
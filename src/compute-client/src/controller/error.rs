@@ -120,6 +120,8 @@ pub enum DataflowCreationError {
     MissingAsOf,
     #[error("dataflow has an as_of not beyond the since of collection: {0}")]
     SinceViolation(GlobalId),
+    #[error("dataflow has an as_of override for replica {0} not beyond its as_of")]
+    ReplicaAsOfRegression(ReplicaId),
 }
 
 impl From<InstanceMissing> for DataflowCreationError {
@@ -135,6 +137,7 @@ impl From<instance::DataflowCreationError> for DataflowCreationError {
             CollectionMissing(id) => Self::CollectionMissing(id),
             MissingAsOf => Self::MissingAsOf,
             SinceViolation(id) => Self::SinceViolation(id),
+            ReplicaAsOfRegression(id) => Self::ReplicaAsOfRegression(id),
         }
     }
 }
@@ -150,6 +153,8 @@ pub enum PeekError {
     ReplicaMissing(ReplicaId),
     #[error("peek timestamp is not beyond the since of collection: {0}")]
     SinceViolation(GlobalId),
+    #[error("replica installed the collection at an as_of beyond the peek timestamp: {0}")]
+    ReplicaAsOfBeyond(ReplicaId),
 }
 
 impl From<InstanceMissing> for PeekError {
@@ -165,6 +170,7 @@ impl From<instance::PeekError> for PeekError {
             CollectionMissing(id) => Self::CollectionMissing(id),
             ReplicaMissing(id) => Self::ReplicaMissing(id),
             SinceViolation(id) => Self::SinceViolation(id),
+            ReplicaAsOfBeyond(id) => Self::ReplicaAsOfBeyond(id),
         }
     }
 }
@@ -201,6 +207,8 @@ pub enum SubscribeTargetError {
     ReplicaMissing(ReplicaId),
     #[error("subscribe has already produced output")]
     SubscribeAlreadyStarted,
+    #[error("replica installed the subscribe at a later as_of: {0}")]
+    ReplicaAsOfBeyond(ReplicaId),
 }
 
 impl From<InstanceMissing> for SubscribeTargetError {
@@ -216,6 +224,7 @@ impl From<instance::SubscribeTargetError> for SubscribeTargetError {
             SubscribeMissing(id) => Self::SubscribeMissing(id),
             ReplicaMissing(id) => Self::ReplicaMissing(id),
             SubscribeAlreadyStarted => Self::SubscribeAlreadyStarted,
+            ReplicaAsOfBeyond(id) => Self::ReplicaAsOfBeyond(id),
         }
     }
 }
@@ -9,7 +9,8 @@
 
 //! A controller for a compute instance.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Debug;
 use std::num::NonZeroI64;
 use std::time::Instant;
 
@@ -25,8 +26,12 @@ use mz_compute_types::sources::SourceInstanceDesc;
 use mz_expr::RowSetFinishing;
 use mz_ore::cast::CastFrom;
 use mz_ore::tracing::OpenTelemetryContext;
+use mz_repr::adt::interval::Interval;
 use mz_repr::{Datum, Diff, GlobalId, Row};
-use mz_storage_client::controller::{IntrospectionType, StorageController};
+use mz_storage_client::controller::{
+    CollectionState as StorageCollectionState, IntrospectionType, StorageController,
+};
+use mz_storage_types::controller::{CollectionMetadata, StorageError};
 use mz_storage_types::read_policy::ReadPolicy;
 use thiserror::Error;
 use timely::progress::{Antichain, ChangeBatch, Timestamp};
@@ -36,11 +41,12 @@ use uuid::Uuid;
 use crate::controller::error::CollectionMissing;
 use crate::controller::replica::{Replica, ReplicaConfig};
 use crate::controller::{
-    CollectionState, ComputeControllerResponse, IntrospectionUpdates, ReplicaId,
+    CollectionState, ComputeControllerResponse, DataflowActivation, IntrospectionUpdates,
+    PeekDescription, ReadHoldSource, ReplicaId, ReplicaInfo,
 };
 use crate::logging::LogVariant;
-use crate::metrics::InstanceMetrics;
 use crate::metrics::UIntGauge;
+use crate::metrics::{InstanceMetrics, ReplicaBackoffMetrics};
 use crate::protocol::command::{
     ComputeCommand, ComputeParameters, InstanceConfig, Peek, PeekTarget,
 };
@@ -64,6 +70,8 @@ pub(super) enum DataflowCreationError {
     MissingAsOf,
     #[error("dataflow has an as_of not beyond the since of collection: {0}")]
     SinceViolation(GlobalId),
+    #[error("dataflow has an as_of override for replica {0} not beyond its as_of")]
+    ReplicaAsOfRegression(ReplicaId),
 }
 
 impl From<CollectionMissing> for DataflowCreationError {
@@ -80,6 +88,8 @@ pub(super) enum PeekError {
     ReplicaMissing(ReplicaId),
     #[error("peek timestamp is not beyond the since of collection: {0}")]
     SinceViolation(GlobalId),
+    #[error("replica installed the collection at an as_of beyond the peek timestamp: {0}")]
+    ReplicaAsOfBeyond(ReplicaId),
 }
 
 impl From<CollectionMissing> for PeekError {
@@ -96,6 +106,8 @@ pub(super) enum SubscribeTargetError {
     ReplicaMissing(ReplicaId),
     #[error("subscribe has already produced output")]
     SubscribeAlreadyStarted,
+    #[error("replica installed the subscribe at a later as_of: {0}")]
+    ReplicaAsOfBeyond(ReplicaId),
 }
 
 /// The state we keep for a compute instance.
@@ -122,6 +134,17 @@ pub(super) struct Instance<T> {
     /// Only if both these conditions hold is dropping a collection's state, and the associated
     /// read holds on its inputs, sound.
     collections: BTreeMap<GlobalId, CollectionState<T>>,
+    /// Dataflows created in a suspended state.
+    ///
+    /// The `CreateDataflow` commands of these dataflows are withheld until they are activated,
+    /// either explicitly through [`ActiveInstance::activate_collection`] or by a peek or another
+    /// dataflow reading from one of their exports. Because the commands have not been sent, they
+    /// are also absent from the command history, so replicas added in the meantime don't install
+    /// the dataflows either.
+    ///
+    /// The exports of suspended dataflows are tracked in `collections` like any other collection.
+    suspended_dataflows:
+        Vec<DataflowDescription<mz_compute_types::plan::Plan<T>, CollectionMetadata, T>>,
     /// IDs of log sources maintained by this compute instance.
     log_sources: BTreeMap<LogVariant, GlobalId>,
     /// Currently outstanding peeks.
@@ -147,10 +170,32 @@ pub(super) struct Instance<T> {
     /// on the subscribe's input. `subscribes` is only used to track which updates have been
     /// emitted, to decide if new ones should be emitted or suppressed.
     subscribes: BTreeMap<GlobalId, ActiveSubscribe<T>>,
+    /// Recently dropped collections and never-tracked collections reported by replicas.
+    ///
+    /// Used to differentiate between responses that race with collection drops and responses
+    /// that indicate protocol bugs.
+    untracked: UntrackedCollections,
     /// The command history, used when introducing new replicas or restarting existing replicas.
     history: ComputeCommandHistory<UIntGauge, T>,
     /// IDs of replicas that have failed and require rehydration.
     failed_replicas: BTreeSet<ReplicaId>,
+    /// Restart backoff state of replicas.
+    ///
+    /// Entries outlive individual incarnations of a replica and are only removed when the replica
+    /// is dropped.
+    replica_backoffs: BTreeMap<ReplicaId, ReplicaBackoff>,
+    /// Per-replica overrides of the `as_of`s of dataflows, keyed by the dataflows' export IDs.
+    ///
+    /// A replica with an override installs the dataflow at the join of the override and the
+    /// dataflow's `as_of`, rather than at the dataflow's `as_of`. Entries outlive individual
+    /// incarnations of a replica and are only removed when the replica is dropped.
+    replica_as_ofs: BTreeMap<ReplicaId, BTreeMap<GlobalId, Antichain<T>>>,
+    /// The backoff applied to restarts of failed replicas.
+    restart_backoff: RestartBackoffConfig,
+    /// The limits beyond which subscribes are terminated for being too slow.
+    subscribe_limits: SubscribeLimits,
+    /// The limits beyond which subscribe batches are split before being emitted.
+    subscribe_batch_limits: SubscribeBatchLimits,
     /// Sender for responses to be delivered.
     response_tx: crossbeam_channel::Sender<ComputeControllerResponse<T>>,
     /// Sender for introspection updates to be recorded.
@@ -188,7 +233,122 @@ impl<T> Instance<T> {
 
     fn remove_collection(&mut self, id: GlobalId) {
         self.report_dependency_updates(id, -1);
+        // This also closes the collection's write frontier channel.
         self.collections.remove(&id);
+        self.untracked.record_drop(id, Instant::now());
+
+        // Suspended dataflows that lost all their exports don't need to be activated anymore.
+        for dataflow in &mut self.suspended_dataflows {
+            dataflow.index_exports.remove(&id);
+            dataflow.sink_exports.remove(&id);
+        }
+        self.suspended_dataflows
+            .retain(|dataflow| dataflow.export_ids().next().is_some());
+
+        for as_ofs in self.replica_as_ofs.values_mut() {
+            as_ofs.remove(&id);
+        }
+        self.replica_as_ofs.retain(|_, as_ofs| !as_ofs.is_empty());
+    }
+
+    /// Returns whether the identified collection is exported by a suspended dataflow.
+    pub fn collection_suspended(&self, id: GlobalId) -> bool {
+        self.suspended_dataflow(id).is_some()
+    }
+
+    /// Returns the suspended dataflow exporting the identified collection, if any.
+    fn suspended_dataflow(
+        &self,
+        id: GlobalId,
+    ) -> Option<&DataflowDescription<mz_compute_types::plan::Plan<T>, CollectionMetadata, T>> {
+        self.suspended_dataflows
+            .iter()
+            .find(|dataflow| dataflow.export_ids().any(|export_id| export_id == id))
+    }
+
+    /// Removes and returns the suspended dataflow exporting the identified collection, if any.
+    fn take_suspended_dataflow(
+        &mut self,
+        id: GlobalId,
+    ) -> Option<DataflowDescription<mz_compute_types::plan::Plan<T>, CollectionMetadata, T>> {
+        let idx = self
+            .suspended_dataflows
+            .iter()
+            .position(|dataflow| dataflow.export_ids().any(|export_id| export_id == id))?;
+        Some(self.suspended_dataflows.remove(idx))
+    }
+
+    /// Set how long dropped collections are remembered for the purpose of classifying replica
+    /// responses about them.
+    pub fn set_dropped_collection_retention(&mut self, retention: std::time::Duration) {
+        self.untracked.retention = retention;
+    }
+
+    /// Return information about the identified replica, or `None` if the replica does not exist.
+    pub fn replica_info(&self, id: ReplicaId) -> Option<ReplicaInfo> {
+        let replica = self.replicas.get(&id)?;
+        let backoff = self.replica_backoffs.get(&id);
+        Some(ReplicaInfo {
+            last_heartbeat: replica.last_heartbeat,
+            quarantined_collections: self.untracked.quarantined(id).clone(),
+            consecutive_failures: backoff.map_or(0, |b| b.consecutive_failures),
+            next_restart: self.next_restart(id),
+        })
+    }
+
+    /// Return the time at which the identified replica is due to be rehydrated, or `None` if the
+    /// replica has not failed.
+    fn next_restart(&self, id: ReplicaId) -> Option<Instant> {
+        if !self.failed_replicas.contains(&id) {
+            return None;
+        }
+        let next = self
+            .replica_backoffs
+            .get(&id)
+            .map(|backoff| backoff.next_attempt(&self.restart_backoff));
+        // Replicas without backoff state can be rehydrated immediately.
+        Some(next.unwrap_or_else(Instant::now))
+    }
+
+    /// Return the earliest time at which a failed replica is due to be rehydrated, or `None` if
+    /// there are no failed replicas.
+    pub fn next_rehydration(&self) -> Option<Instant> {
+        self.failed_replicas
+            .iter()
+            .filter_map(|id| self.next_restart(*id))
+            .min()
+    }
+
+    /// Mark the identified replica as failed and in need of rehydration.
+    fn mark_replica_failed(&mut self, id: ReplicaId) {
+        if self.failed_replicas.insert(id) {
+            if let Some(backoff) = self.replica_backoffs.get_mut(&id) {
+                backoff.record_failure(&self.restart_backoff);
+            }
+        }
+    }
+
+    /// Set the backoff applied to restarts of failed replicas.
+    fn set_restart_backoff(&mut self, config: RestartBackoffConfig) {
+        self.restart_backoff = config;
+        for backoff in self.replica_backoffs.values() {
+            backoff.update_metrics(&self.restart_backoff);
+        }
+    }
+
+    /// Classify a replica response for the untracked collection `id`, logging and recording it
+    /// accordingly.
+    fn report_untracked_collection(&mut self, id: GlobalId, replica_id: ReplicaId, what: &str) {
+        match self.untracked.classify(id, replica_id, Instant::now()) {
+            UntrackedKind::RecentlyDropped => {
+                self.metrics.dropped_collection_responses_total.inc();
+                tracing::debug!(?replica_id, "{what} for recently dropped collection {id}");
+            }
+            UntrackedKind::Unknown => {
+                tracing::warn!(?replica_id, "{what} for unknown collection {id}");
+                tracing::error!("Replica reported an untracked collection");
+            }
+        }
     }
 
     /// Enqueue the given response for delivery to the controller clients.
@@ -210,10 +370,10 @@ impl<T> Instance<T> {
     }
 
     /// Acquire an [`ActiveInstance`] by providing a storage controller.
-    pub fn activate<'a>(
-        &'a mut self,
-        storage_controller: &'a mut dyn StorageController<Timestamp = T>,
-    ) -> ActiveInstance<'a, T> {
+    pub fn activate<'a, S>(&'a mut self, storage_controller: &'a mut S) -> ActiveInstance<'a, T, S>
+    where
+        S: InstanceStorage<T> + ?Sized,
+    {
         ActiveInstance {
             compute: self,
             storage_controller,
@@ -222,8 +382,9 @@ impl<T> Instance<T> {
 
     /// Return whether this instance has any processing work scheduled.
     pub fn wants_processing(&self) -> bool {
-        // Do we need to rehydrate failed replicas?
-        !self.failed_replicas.is_empty()
+        // Do we need to rehydrate failed replicas whose backoff has elapsed?
+        self.next_rehydration()
+            .map_or(false, |next| next <= Instant::now())
     }
 
     /// Returns whether the identified replica exists.
@@ -250,6 +411,26 @@ impl<T> Instance<T> {
         })
     }
 
+    /// Return a description of the identified pending peek, or `None` if the peek is not
+    /// tracked.
+    pub fn describe_peek(&self, uuid: Uuid) -> Option<PeekDescription<T>>
+    where
+        T: Clone,
+    {
+        self.peeks.get(&uuid).map(PendingPeek::describe)
+    }
+
+    /// Return descriptions of all pending peeks.
+    pub fn list_pending_peeks(&self) -> Vec<(Uuid, PeekDescription<T>)>
+    where
+        T: Clone,
+    {
+        self.peeks
+            .iter()
+            .map(|(uuid, peek)| (*uuid, peek.describe()))
+            .collect()
+    }
+
     /// Return the IDs of in-progress subscribes targeting the specified replica.
     fn subscribes_targeting(&self, replica_id: ReplicaId) -> impl Iterator<Item = GlobalId> + '_ {
         self.subscribes.iter().filter_map(move |(id, subscribe)| {
@@ -258,6 +439,114 @@ impl<T> Instance<T> {
         })
     }
 
+    /// Stop tracking the identified subscribe, retracting its reported lag.
+    fn remove_subscribe(&mut self, id: GlobalId) -> Option<ActiveSubscribe<T>> {
+        let subscribe = self.subscribes.remove(&id)?;
+        if let Some(lag) = subscribe.lag {
+            self.deliver_introspection_updates(
+                IntrospectionType::ComputeSubscribeLag,
+                vec![(subscribe_lag_row(id, lag), -1)],
+            );
+        }
+        Some(subscribe)
+    }
+
+    /// Emit a batch of output of the identified subscribe, updating its tracking state.
+    ///
+    /// If the subscribe's consumer has fallen too far behind, as judged by the configured
+    /// [`SubscribeLimits`], the subscribe is terminated instead, by emitting an error batch in
+    /// place of the given one.
+    fn emit_subscribe_batch(
+        &mut self,
+        id: GlobalId,
+        mut subscribe: ActiveSubscribe<T>,
+        batch: SubscribeBatch<T>,
+    ) -> ComputeControllerResponse<T> {
+        if batch.upper.is_empty() {
+            // This subscribe cannot produce more data. Stop tracking it.
+            self.remove_subscribe(id);
+            return ComputeControllerResponse::SubscribeResponse(
+                id,
+                SubscribeResponse::Batch(batch),
+            );
+        }
+
+        subscribe.buffered_bytes += u64::cast_from(batch.updates_byte_len());
+        let too_slow = self.subscribe_limits.exceeded_by(&subscribe);
+        // This subscribe can produce more data. Update our tracking of it.
+        self.subscribes.insert(id, subscribe);
+        if !too_slow {
+            return ComputeControllerResponse::SubscribeResponse(
+                id,
+                SubscribeResponse::Batch(batch),
+            );
+        }
+
+        let subscribe = self.remove_subscribe(id).expect("just inserted");
+        tracing::info!(
+            %id,
+            buffered_bytes = subscribe.buffered_bytes,
+            lag_ms = ?subscribe.lag,
+            "terminating slow subscribe",
+        );
+        self.metrics.subscribes_terminated_too_slow.inc();
+        // The empty upper signals the consumer that the subscribe won't produce any more output,
+        // so it can clean up its resources.
+        ComputeControllerResponse::SubscribeResponse(
+            id,
+            SubscribeResponse::Batch(SubscribeBatch {
+                lower: batch.lower,
+                upper: Antichain::new(),
+                updates: Err("subscriber too slow".into()),
+            }),
+        )
+    }
+
+    /// Emit the given batch of output for the identified subscribe, split into several batches if
+    /// it exceeds the [`SubscribeBatchLimits`].
+    ///
+    /// A batch that doesn't need splitting is returned as a single response, like
+    /// [`Instance::emit_subscribe_batch`] does. The parts of a split batch are instead all
+    /// delivered through the response channel, so that they are processed in order and the
+    /// consumer can interleave them with other work. In that case, `None` is returned.
+    fn emit_subscribe_batches(
+        &mut self,
+        id: GlobalId,
+        mut subscribe: ActiveSubscribe<T>,
+        batch: SubscribeBatch<T>,
+    ) -> Option<ComputeControllerResponse<T>>
+    where
+        T: Timestamp,
+    {
+        let mut batches = self.subscribe_batch_limits.split(batch);
+        if batches.len() == 1 {
+            let batch = batches.pop().expect("one batch");
+            return Some(self.emit_subscribe_batch(id, subscribe, batch));
+        }
+
+        for batch in batches {
+            let response = self.emit_subscribe_batch(id, subscribe, batch);
+            self.deliver_response(response);
+            // The subscribe might have been terminated for being too slow, in which case no
+            // further output must be emitted.
+            match self.subscribes.get(&id) {
+                Some(active) => subscribe = active.clone(),
+                None => break,
+            }
+        }
+        None
+    }
+
+    /// Acknowledge that the consumer of the identified subscribe's output has drained `bytes`
+    /// bytes of updates.
+    ///
+    /// Acknowledgements for subscribes that are not tracked anymore are ignored.
+    pub fn ack_subscribe_output(&mut self, id: GlobalId, bytes: u64) {
+        if let Some(subscribe) = self.subscribes.get_mut(&id) {
+            subscribe.buffered_bytes = subscribe.buffered_bytes.saturating_sub(bytes);
+        }
+    }
+
     /// Refresh the controller state metrics for this instance.
     ///
     /// We could also do state metric updates directly in response to state changes, but that would
@@ -303,6 +592,107 @@ impl<T> Instance<T> {
         self.deliver_introspection_updates(IntrospectionType::ComputeDependencies, updates);
     }
 
+    /// Return the total number of outstanding read capabilities on the identified collection,
+    /// across all times and holders.
+    ///
+    /// Use [`CollectionState::read_hold_counts`] for a breakdown by time and holder.
+    pub fn total_read_hold_count(&self, id: GlobalId) -> Result<i64, CollectionMissing>
+    where
+        T: Timestamp,
+    {
+        let counts = self.collection(id)?.read_hold_counts();
+        Ok(counts.values().flat_map(|sources| sources.values()).sum())
+    }
+
+    /// Return the read capabilities on compute collections whose holders don't exist anymore,
+    /// i.e. peeks that are no longer pending and dependent collections that have been dropped,
+    /// along with the number of capabilities each of them still holds.
+    ///
+    /// Such leaked read capabilities prevent the read frontier of the collection from ever
+    /// advancing past them.
+    pub fn leaked_read_holds(&self) -> BTreeMap<GlobalId, BTreeMap<ReadHoldSource, i64>>
+    where
+        T: Timestamp,
+    {
+        let mut leaked = BTreeMap::new();
+        for (id, collection) in &self.collections {
+            for sources in collection.read_hold_counts().into_values() {
+                for (source, count) in sources {
+                    let holder_exists = match source {
+                        ReadHoldSource::Policy => true,
+                        ReadHoldSource::Peek(uuid) => self.peeks.contains_key(&uuid),
+                        ReadHoldSource::Dependent(id) => self.collections.contains_key(&id),
+                    };
+                    if !holder_exists {
+                        let counts: &mut BTreeMap<_, _> = leaked.entry(*id).or_default();
+                        *counts.entry(source).or_default() += count;
+                    }
+                }
+            }
+        }
+        leaked
+    }
+
+    /// Return the write frontier the identified replica has reported for the identified
+    /// collection, or `None` if either doesn't exist or the replica hasn't reported a frontier
+    /// for the collection yet.
+    pub fn replica_write_frontier(
+        &self,
+        replica_id: ReplicaId,
+        id: GlobalId,
+    ) -> Option<Antichain<T>>
+    where
+        T: Clone,
+    {
+        let collection = self.collection(id).ok()?;
+        collection.replica_write_frontiers.get(&replica_id).cloned()
+    }
+
+    /// Return the write frontiers all replicas have reported for the identified collection.
+    ///
+    /// Replicas that haven't reported a frontier for the collection yet are not included.
+    pub fn replica_write_frontiers(
+        &self,
+        id: GlobalId,
+    ) -> Result<BTreeMap<ReplicaId, Antichain<T>>, CollectionMissing>
+    where
+        T: Clone,
+    {
+        let collection = self.collection(id)?;
+        Ok(collection.replica_write_frontiers.clone())
+    }
+
+    /// Return, for each replica, the collections it has not finished hydrating yet.
+    ///
+    /// A replica has hydrated a collection once it reports a write frontier beyond the `as_of` it
+    /// installed the collection's dataflow at, as well as beyond the frontier its tracking started
+    /// out with, which is the collection's read frontier for replicas added later, or once it
+    /// reports the empty frontier. Log collections and collections of suspended dataflows are not
+    /// considered, as replicas don't hydrate them.
+    pub fn hydration_status(&self) -> BTreeMap<ReplicaId, BTreeSet<GlobalId>>
+    where
+        T: Timestamp,
+    {
+        let mut status: BTreeMap<_, BTreeSet<_>> =
+            self.replica_ids().map(|id| (id, BTreeSet::new())).collect();
+        for (id, collection) in &self.collections {
+            if collection.log_collection || self.collection_suspended(*id) {
+                continue;
+            }
+            for (replica_id, hydrating) in status.iter_mut() {
+                let as_of = self
+                    .replica_as_ofs
+                    .get(replica_id)
+                    .and_then(|as_ofs| as_ofs.get(id))
+                    .unwrap_or(&collection.as_of);
+                if !collection.hydrated_on(*replica_id, as_of) {
+                    hydrating.insert(*id);
+                }
+            }
+        }
+        status
+    }
+
     /// List compute collections that depend on the given collection.
     pub fn collection_reverse_dependencies(&self, id: GlobalId) -> impl Iterator<Item = &GlobalId> {
         self.collections_iter().filter_map(move |(id2, state)| {
@@ -313,6 +703,96 @@ impl<T> Instance<T> {
             }
         })
     }
+
+    /// Returns the compute collections the identified collection depends on, directly or
+    /// transitively.
+    ///
+    /// Dependencies on collections that are not installed are skipped with a warning, as are
+    /// their own dependencies.
+    pub fn transitive_compute_dependencies(
+        &self,
+        id: GlobalId,
+    ) -> Result<BTreeSet<GlobalId>, CollectionMissing> {
+        let mut dependencies = BTreeSet::new();
+        let mut todo = self.collection(id)?.compute_dependencies.clone();
+        while let Some(dep_id) = todo.pop() {
+            if dependencies.contains(&dep_id) {
+                continue;
+            }
+            let Some(dep) = self.collections.get(&dep_id) else {
+                tracing::warn!(%id, "skipping dependency on unknown collection {dep_id}");
+                continue;
+            };
+            dependencies.insert(dep_id);
+            todo.extend(dep.compute_dependencies.iter().copied());
+        }
+        Ok(dependencies)
+    }
+
+    /// Returns the storage collections the identified collection depends on, directly or through
+    /// its transitive compute dependencies.
+    ///
+    /// Dependencies on compute collections that are not installed are skipped with a warning, see
+    /// [`Instance::transitive_compute_dependencies`].
+    pub fn transitive_storage_dependencies(
+        &self,
+        id: GlobalId,
+    ) -> Result<BTreeSet<GlobalId>, CollectionMissing> {
+        let mut dependencies: BTreeSet<_> = self
+            .collection(id)?
+            .storage_dependencies
+            .iter()
+            .copied()
+            .collect();
+        for compute_id in self.transitive_compute_dependencies(id)? {
+            if let Some(collection) = self.collections.get(&compute_id) {
+                dependencies.extend(collection.storage_dependencies.iter().copied());
+            }
+        }
+        Ok(dependencies)
+    }
+
+    /// Returns a cycle in the compute dependencies between collections, if there is one.
+    ///
+    /// In the returned path, each collection depends on the next one and the last one depends on
+    /// the first. Dependencies on collections that are not installed are ignored.
+    pub fn find_dependency_cycle(&self) -> Option<Vec<GlobalId>> {
+        // Collections whose dependencies have been fully explored without finding a cycle.
+        let mut done = BTreeSet::new();
+        for (root_id, root) in &self.collections {
+            if done.contains(root_id) {
+                continue;
+            }
+
+            // Depth-first search, tracking the collections on the current path along with the
+            // dependencies that are left to visit for each of them.
+            let mut path = vec![(*root_id, root.compute_dependencies.iter())];
+            let mut on_path = BTreeSet::from([*root_id]);
+            while let Some((id, dependencies)) = path.last_mut() {
+                let Some(dep_id) = dependencies.next() else {
+                    on_path.remove(id);
+                    done.insert(*id);
+                    path.pop();
+                    continue;
+                };
+                if on_path.contains(dep_id) {
+                    let start = path
+                        .iter()
+                        .position(|(id, _)| id == dep_id)
+                        .expect("collection is on the path");
+                    return Some(path[start..].iter().map(|(id, _)| *id).collect());
+                }
+                if done.contains(dep_id) {
+                    continue;
+                }
+                if let Some(dep) = self.collections.get(dep_id) {
+                    on_path.insert(*dep_id);
+                    path.push((*dep_id, dep.compute_dependencies.iter()));
+                }
+            }
+        }
+        None
+    }
 }
 
 impl<T> Instance<T>
@@ -327,6 +807,7 @@ where
         metrics: InstanceMetrics,
         response_tx: crossbeam_channel::Sender<ComputeControllerResponse<T>>,
         introspection_tx: crossbeam_channel::Sender<IntrospectionUpdates>,
+        dropped_collection_retention: std::time::Duration,
     ) -> Self {
         let collections = arranged_logs
             .iter()
@@ -342,11 +823,18 @@ where
             initialized: false,
             replicas: Default::default(),
             collections,
+            suspended_dataflows: Default::default(),
             log_sources: arranged_logs,
             peeks: Default::default(),
             subscribes: Default::default(),
+            untracked: UntrackedCollections::new(dropped_collection_retention),
             history,
             failed_replicas: Default::default(),
+            replica_backoffs: Default::default(),
+            replica_as_ofs: Default::default(),
+            restart_backoff: Default::default(),
+            subscribe_limits: Default::default(),
+            subscribe_batch_limits: Default::default(),
             response_tx,
             introspection_tx,
             envd_epoch,
@@ -369,6 +857,32 @@ where
 
     /// Update instance configuration.
     pub fn update_configuration(&mut self, config_params: ComputeParameters) {
+        let mut restart_backoff = self.restart_backoff;
+        if let Some(base) = config_params.replica_restart_backoff_base {
+            restart_backoff.base = base;
+        }
+        if let Some(cap) = config_params.replica_restart_backoff_cap {
+            restart_backoff.cap = cap;
+        }
+        self.set_restart_backoff(restart_backoff);
+
+        if let Some(max_buffered_bytes) = config_params.subscribe_max_buffered_bytes {
+            self.subscribe_limits.max_buffered_bytes = max_buffered_bytes;
+        }
+        if let Some(max_lag) = config_params.subscribe_max_lag {
+            self.subscribe_limits.max_lag = max_lag;
+        }
+        if let Some(max_rows) = config_params.subscribe_max_batch_rows {
+            self.subscribe_batch_limits.max_rows = max_rows;
+        }
+        if let Some(max_bytes) = config_params.subscribe_max_batch_bytes {
+            self.subscribe_batch_limits.max_bytes = max_bytes;
+        }
+        if let Some(threshold) = config_params.history_reduce_threshold {
+            self.history
+                .set_reduce_threshold(threshold.map(usize::cast_from));
+        }
+
         self.send(ComputeCommand::UpdateConfiguration(config_params));
     }
 
@@ -408,12 +922,17 @@ where
         self.history.push(cmd.clone());
 
         // Clone the command for each active replica.
+        let mut failed = Vec::new();
         for (id, replica) in self.replicas.iter_mut() {
+            let cmd = command_for_replica(&self.replica_as_ofs, *id, &cmd);
             // If sending the command fails, the replica requires rehydration.
-            if replica.send(cmd.clone()).is_err() {
-                self.failed_replicas.insert(*id);
+            if replica.send(cmd).is_err() {
+                failed.push(*id);
             }
         }
+        for id in failed {
+            self.mark_replica_failed(id);
+        }
     }
 
     /// Receives the next response from any replica of this instance.
@@ -424,10 +943,13 @@ where
     /// This method is cancellation safe.
     pub async fn recv(&mut self) -> Result<(ReplicaId, ComputeResponse<T>), ReplicaId> {
         // Receive responses from any of the replicas, and take appropriate
-        // action.
+        // action. Failed replicas are skipped, as they would report their
+        // failure again while waiting for their restart backoff to elapse.
+        let failed_replicas = &self.failed_replicas;
         let response = self
             .replicas
             .iter_mut()
+            .filter(|(id, _)| !failed_replicas.contains(id))
             .map(|(id, replica)| async { (*id, replica.recv().await) })
             .collect::<FuturesUnordered<_>>()
             .next()
@@ -441,12 +963,15 @@ where
             }
             Some((replica_id, None)) => {
                 // A replica has failed and requires rehydration.
-                self.failed_replicas.insert(replica_id);
+                self.mark_replica_failed(replica_id);
                 Err(replica_id)
             }
             Some((replica_id, Some(response))) => {
                 // A replica has produced a response. Return it.
                 self.register_replica_heartbeat(replica_id);
+                if let Some(backoff) = self.replica_backoffs.get_mut(&replica_id) {
+                    backoff.record_healthy(&self.restart_backoff, Instant::now());
+                }
                 Ok((replica_id, response))
             }
         }
@@ -491,6 +1016,24 @@ where
         self.deliver_introspection_updates(IntrospectionType::ComputeReplicaHeartbeats, updates);
     }
 
+    /// Returns whether the identified replica can serve reads of the identified collection at
+    /// times beyond `frontier`.
+    ///
+    /// This is not the case if the replica installed the collection at an `as_of` override that
+    /// is beyond `frontier`, since the replica's output doesn't distinguish the times before that
+    /// `as_of`.
+    fn replica_can_serve(
+        &self,
+        replica_id: ReplicaId,
+        id: GlobalId,
+        frontier: &Antichain<T>,
+    ) -> bool {
+        self.replica_as_ofs
+            .get(&replica_id)
+            .and_then(|as_ofs| as_ofs.get(&id))
+            .map_or(true, |as_of| PartialOrder::less_equal(as_of, frontier))
+    }
+
     /// Assign a target replica to the identified subscribe.
     ///
     /// If a subscribe has a target replica assigned, only subscribe responses
@@ -504,7 +1047,7 @@ where
             return Err(SubscribeTargetError::ReplicaMissing(target_replica));
         }
 
-        let Some(subscribe) = self.subscribes.get_mut(&id) else {
+        let Some(subscribe) = self.subscribes.get(&id) else {
             return Err(SubscribeTargetError::SubscribeMissing(id));
         };
 
@@ -514,21 +1057,59 @@ where
             return Err(SubscribeTargetError::SubscribeAlreadyStarted);
         }
 
+        // A replica that installed the subscribe at a later `as_of` can't produce its output
+        // from the start.
+        if !self.replica_can_serve(target_replica, id, &subscribe.frontier) {
+            return Err(SubscribeTargetError::ReplicaAsOfBeyond(target_replica));
+        }
+
+        let subscribe = self.subscribes.get_mut(&id).expect("subscribe exists");
+
         subscribe.target_replica = Some(target_replica);
         Ok(())
     }
 }
 
+/// The parts of a [`StorageController`] that an [`ActiveInstance`] uses.
+pub(super) trait InstanceStorage<T>: Debug {
+    /// See [`StorageController::collection`].
+    fn collection(&self, id: GlobalId) -> Result<&StorageCollectionState<T>, StorageError>;
+
+    /// See [`StorageController::update_write_frontiers`].
+    fn update_write_frontiers(&mut self, updates: &[(GlobalId, Antichain<T>)]);
+
+    /// See [`StorageController::update_read_capabilities`].
+    fn update_read_capabilities(&mut self, updates: &mut BTreeMap<GlobalId, ChangeBatch<T>>);
+}
+
+impl<T, S> InstanceStorage<T> for S
+where
+    S: StorageController<Timestamp = T> + ?Sized,
+{
+    fn collection(&self, id: GlobalId) -> Result<&StorageCollectionState<T>, StorageError> {
+        StorageController::collection(self, id)
+    }
+
+    fn update_write_frontiers(&mut self, updates: &[(GlobalId, Antichain<T>)]) {
+        StorageController::update_write_frontiers(self, updates)
+    }
+
+    fn update_read_capabilities(&mut self, updates: &mut BTreeMap<GlobalId, ChangeBatch<T>>) {
+        StorageController::update_read_capabilities(self, updates)
+    }
+}
+
 /// A wrapper around [`Instance`] with a live storage controller.
 #[derive(Debug)]
-pub(super) struct ActiveInstance<'a, T> {
+pub(super) struct ActiveInstance<'a, T, S: ?Sized = dyn StorageController<Timestamp = T> + 'a> {
     compute: &'a mut Instance<T>,
-    storage_controller: &'a mut dyn StorageController<Timestamp = T>,
+    storage_controller: &'a mut S,
 }
 
-impl<'a, T> ActiveInstance<'a, T>
+impl<'a, T, S> ActiveInstance<'a, T, S>
 where
-    T: Timestamp + Lattice,
+    T: Timestamp + Lattice + Into<mz_repr::Timestamp>,
+    S: InstanceStorage<T> + ?Sized,
     ComputeGrpcClient: ComputeClient<T>,
 {
     /// Add a new instance replica, by ID.
@@ -547,11 +1128,16 @@ where
         // Initialize frontier tracking for the new replica
         // and clean up any dropped collections that we can
         let mut updates = Vec::new();
-        for (compute_id, collection) in &mut self.compute.collections {
+        for (compute_id, collection) in &self.compute.collections {
             // Skip log collections not maintained by this replica.
             if collection.log_collection && !log_ids.contains(compute_id) {
                 continue;
             }
+            // Skip collections of suspended dataflows, which the replica won't install until
+            // they are activated.
+            if self.compute.collection_suspended(*compute_id) {
+                continue;
+            }
 
             let read_frontier = collection.read_frontier();
             updates.push((*compute_id, read_frontier.to_owned()));
@@ -570,11 +1156,20 @@ where
         );
 
         // Take this opportunity to clean up the history we should present.
-        self.compute.history.reduce();
+        let stats = self.compute.history.reduce();
+        self.compute.metrics.history_reduce.observe(&stats);
+        tracing::debug!(
+            replica_id = %id,
+            commands_before = stats.commands_before,
+            commands_after = stats.commands_after,
+            bytes_after = stats.bytes_after,
+            "reduced compute command history",
+        );
 
         // Replay the commands at the client, creating new dataflow identifiers.
         for command in self.compute.history.iter() {
-            if replica.send(command.clone()).is_err() {
+            let command = command_for_replica(&self.compute.replica_as_ofs, id, command);
+            if replica.send(command).is_err() {
                 // We swallow the error here. On the next send, we will fail again, and
                 // restart the connection as well as this rehydration.
                 tracing::warn!("Replica {:?} connection terminated during hydration", id);
@@ -584,6 +1179,11 @@ where
 
         // Add replica to tracked state.
         self.compute.replicas.insert(id, replica);
+        if !self.compute.replica_backoffs.contains_key(&id) {
+            let metrics = self.compute.metrics.for_replica_backoff(id);
+            let backoff = ReplicaBackoff::new(Instant::now(), metrics);
+            self.compute.replica_backoffs.insert(id, backoff);
+        }
 
         Ok(())
     }
@@ -597,6 +1197,9 @@ where
             .ok_or(ReplicaMissing(id))?;
 
         self.compute.failed_replicas.remove(&id);
+        self.compute.replica_backoffs.remove(&id);
+        self.compute.replica_as_ofs.remove(&id);
+        self.compute.untracked.remove_replica(id);
 
         // Remove frontier tracking for this replica.
         self.remove_write_frontiers(id);
@@ -618,7 +1221,7 @@ where
         // introspection index). We produce an error to inform upstream.
         let to_drop: Vec<_> = self.compute.subscribes_targeting(id).collect();
         for subscribe_id in to_drop {
-            let subscribe = self.compute.subscribes.remove(&subscribe_id).unwrap();
+            let subscribe = self.compute.remove_subscribe(subscribe_id).unwrap();
             let response = ComputeControllerResponse::SubscribeResponse(
                 subscribe_id,
                 SubscribeResponse::Batch(SubscribeBatch {
@@ -659,7 +1262,16 @@ where
     /// Panics if the specified replica does not exist.
     fn rehydrate_replica(&mut self, id: ReplicaId) {
         let config = self.compute.replicas[&id].config.clone();
+        // The restart backoff state and `as_of` overrides must survive the replica's removal.
+        let backoff = self.compute.replica_backoffs.remove(&id);
+        let as_ofs = self.compute.replica_as_ofs.remove(&id);
         self.remove_replica(id).expect("replica must exist");
+        if let Some(backoff) = backoff {
+            self.compute.replica_backoffs.insert(id, backoff);
+        }
+        if let Some(as_ofs) = as_ofs {
+            self.compute.replica_as_ofs.insert(id, as_ofs);
+        }
         let result = self.add_replica(id, config);
 
         match result {
@@ -668,26 +1280,69 @@ where
         }
     }
 
-    /// Rehydrate any failed replicas of this instance.
+    /// Rehydrate any failed replicas of this instance whose restart backoff has elapsed.
     pub fn rehydrate_failed_replicas(&mut self) {
+        let now = Instant::now();
         let failed_replicas = self.compute.failed_replicas.clone();
         for replica_id in failed_replicas {
+            if self
+                .compute
+                .next_restart(replica_id)
+                .map_or(false, |next| next > now)
+            {
+                continue;
+            }
+
+            if let Some(backoff) = self.compute.replica_backoffs.get_mut(&replica_id) {
+                tracing::info!(
+                    ?replica_id,
+                    consecutive_failures = backoff.consecutive_failures,
+                    "rehydrating failed replica",
+                );
+                backoff.record_attempt(now);
+            }
             self.rehydrate_replica(replica_id);
             self.compute.failed_replicas.remove(&replica_id);
         }
     }
 
     /// Create the described dataflows and initializes state for their output.
+    ///
+    /// A [`DataflowActivation::Suspended`] dataflow is not sent to the replicas until it is
+    /// activated through [`ActiveInstance::activate_collection`], or by a peek or another dataflow
+    /// reading from one of its exports.
+    ///
+    /// Replicas in `replica_as_ofs` install the dataflow at the given `as_of` instead, which must
+    /// be beyond the dataflow's `as_of`. This includes replicas that are added later.
+    ///
+    /// Exports in `read_policies` are installed with the given read policy instead of one that
+    /// holds them readable at the `as_of` until [`ActiveInstance::set_read_policy`] is called.
     pub fn create_dataflow(
         &mut self,
         dataflow: DataflowDescription<mz_compute_types::plan::Plan<T>, (), T>,
+        activation: DataflowActivation,
+        replica_as_ofs: BTreeMap<ReplicaId, Antichain<T>>,
+        read_policies: BTreeMap<GlobalId, ReadPolicy<T>>,
     ) -> Result<(), DataflowCreationError> {
+        let suspended = activation == DataflowActivation::Suspended;
+        if let Some(id) = read_policies
+            .keys()
+            .find(|id| !dataflow.export_ids().any(|export_id| export_id == **id))
+        {
+            return Err(DataflowCreationError::CollectionMissing(*id));
+        }
+
         // Validate the dataflow as having inputs whose `since` is less or equal to the dataflow's `as_of`.
         // Start tracking frontiers for each dataflow, using its `as_of` for each index and sink.
         let as_of = dataflow
             .as_of
             .as_ref()
             .ok_or(DataflowCreationError::MissingAsOf)?;
+        for (replica_id, replica_as_of) in &replica_as_ofs {
+            if !PartialOrder::less_equal(as_of, replica_as_of) {
+                return Err(DataflowCreationError::ReplicaAsOfRegression(*replica_id));
+            }
+        }
 
         // When we initialize per-replica write frontiers (and thereby the per-replica read
         // capabilities), we cannot use the `as_of` because of reconciliation: Existing
@@ -701,6 +1356,11 @@ where
         let mut storage_dependencies = Vec::new();
         let mut compute_dependencies = Vec::new();
 
+        // The `as_of`s the dataflow will be installed at on the various replicas.
+        let as_ofs: Vec<_> = std::iter::once(as_of)
+            .chain(replica_as_ofs.values())
+            .collect();
+
         // Validate sources have `since.less_equal(as_of)`.
         for source_id in dataflow.source_imports.keys() {
             let since = &self
@@ -709,7 +1369,10 @@ where
                 .map_err(|_| DataflowCreationError::CollectionMissing(*source_id))?
                 .read_capabilities
                 .frontier();
-            if !(timely::order::PartialOrder::less_equal(since, &as_of.borrow())) {
+            if as_ofs
+                .iter()
+                .any(|as_of| !PartialOrder::less_equal(since, &as_of.borrow()))
+            {
                 Err(DataflowCreationError::SinceViolation(*source_id))?;
             }
 
@@ -722,7 +1385,10 @@ where
         for index_id in dataflow.index_imports.keys() {
             let collection = self.compute.collection(*index_id)?;
             let since = collection.read_capabilities.frontier();
-            if !(timely::order::PartialOrder::less_equal(&since, &as_of.borrow())) {
+            if as_ofs
+                .iter()
+                .any(|as_of| !PartialOrder::less_equal(&since, &as_of.borrow()))
+            {
                 Err(DataflowCreationError::SinceViolation(*index_id))?;
             }
 
@@ -730,6 +1396,14 @@ where
             replica_write_frontier.join_assign(&since.to_owned());
         }
 
+        // Dataflows sent to the replicas require the dataflows they read from to be installed too.
+        // Only do so once the dataflow is known to be valid, so a rejected dataflow has no effect.
+        if !suspended {
+            for index_id in dataflow.index_imports.keys() {
+                self.activate_collection(*index_id)?;
+            }
+        }
+
         // Canonicalize dependencies.
         // Probably redundant based on key structure, but doing for sanity.
         storage_dependencies.sort();
@@ -752,12 +1426,22 @@ where
             .collect();
         self.storage_controller
             .update_read_capabilities(&mut storage_read_updates);
-        // Update compute read capabilities for inputs.
-        let mut compute_read_updates = compute_dependencies
+        // Update compute read capabilities for inputs, on behalf of each of the outputs.
+        let compute_read_updates = compute_dependencies
             .iter()
-            .map(|id| (*id, changes.clone()))
+            .map(|id| {
+                let holds = dataflow
+                    .export_ids()
+                    .map(|export_id| {
+                        let mut changes = ChangeBatch::new();
+                        changes.extend(as_of.iter().map(|time| (time.clone(), 1)));
+                        (ReadHoldSource::Dependent(export_id), changes)
+                    })
+                    .collect();
+                (*id, holds)
+            })
             .collect();
-        self.update_read_capabilities(&mut compute_read_updates);
+        self.update_sourced_read_capabilities(compute_read_updates);
 
         // Install collection state for each of the exports.
         let mut updates = Vec::new();
@@ -772,10 +1456,35 @@ where
             );
             updates.push((export_id, replica_write_frontier.clone()));
         }
-        // Initialize tracking of replica frontiers.
-        let replica_ids: Vec<_> = self.compute.replica_ids().collect();
-        for replica_id in replica_ids {
-            self.update_write_frontiers(replica_id, &updates);
+        debug_assert_eq!(
+            self.compute.find_dependency_cycle(),
+            None,
+            "dataflow {} introduced a dependency cycle",
+            dataflow.debug_name,
+        );
+
+        // Apply the initial read policies before any write frontiers are reported, so the exports
+        // never hold back their inputs further than their policies allow.
+        if !read_policies.is_empty() {
+            self.set_read_policy(read_policies.into_iter().collect())?;
+        }
+
+        // Initialize tracking of replica frontiers. For suspended dataflows this happens on
+        // activation instead, as the replicas don't know about them until then.
+        if !suspended {
+            let replica_ids: Vec<_> = self.compute.replica_ids().collect();
+            for replica_id in replica_ids {
+                self.update_write_frontiers(replica_id, &updates);
+            }
+        }
+
+        // Record the `as_of` overrides, to be applied whenever the dataflow is sent to the
+        // respective replicas.
+        for (replica_id, replica_as_of) in replica_as_ofs {
+            let overrides = self.compute.replica_as_ofs.entry(replica_id).or_default();
+            for export_id in dataflow.export_ids() {
+                overrides.insert(export_id, replica_as_of.clone());
+            }
         }
 
         // Initialize tracking of subscribes.
@@ -843,6 +1552,19 @@ where
             debug_name: dataflow.debug_name,
         };
 
+        if suspended {
+            tracing::info!(
+                name = %augmented_dataflow.debug_name,
+                import_ids = %augmented_dataflow.display_import_ids(),
+                export_ids = %augmented_dataflow.display_export_ids(),
+                as_of = ?augmented_dataflow.as_of.as_ref().unwrap().elements(),
+                until = ?augmented_dataflow.until.elements(),
+                "creating suspended dataflow",
+            );
+            self.compute.suspended_dataflows.push(augmented_dataflow);
+            return Ok(());
+        }
+
         if augmented_dataflow.is_transient() {
             tracing::debug!(
                 name = %augmented_dataflow.debug_name,
@@ -869,6 +1591,94 @@ where
         Ok(())
     }
 
+    /// Activates the suspended dataflow exporting the identified collection, if any.
+    ///
+    /// This sends the withheld `CreateDataflow` command to the replicas, with the dataflow's
+    /// `as_of` advanced to the current `since`s of its inputs. Suspended dataflows it reads from
+    /// are activated first. Activating a collection that is not suspended has no effect.
+    pub fn activate_collection(&mut self, id: GlobalId) -> Result<(), CollectionMissing> {
+        self.compute.collection(id)?;
+        let Some(dataflow) = self.compute.suspended_dataflow(id) else {
+            return Ok(());
+        };
+        let source_ids: Vec<_> = dataflow.source_imports.keys().copied().collect();
+        let index_ids: Vec<_> = dataflow.index_imports.keys().copied().collect();
+
+        for index_id in &index_ids {
+            self.activate_collection(*index_id)?;
+        }
+
+        let mut input_sinces = Vec::new();
+        for source_id in source_ids {
+            let since = self
+                .storage_controller
+                .collection(source_id)
+                .map_err(|_| CollectionMissing(source_id))?
+                .read_capabilities
+                .frontier()
+                .to_owned();
+            input_sinces.push(since);
+        }
+        for index_id in index_ids {
+            let since = self
+                .compute
+                .collection(index_id)?
+                .read_frontier()
+                .to_owned();
+            input_sinces.push(since);
+        }
+
+        let mut dataflow = self
+            .compute
+            .take_suspended_dataflow(id)
+            .expect("checked above");
+        let as_of = activation_as_of(
+            dataflow.as_of.as_ref().expect("validated on creation"),
+            input_sinces,
+        );
+        dataflow.as_of = Some(as_of.clone());
+
+        // Initialize tracking of replica frontiers, which was skipped while the dataflow was
+        // suspended.
+        let export_ids: Vec<_> = dataflow.export_ids().collect();
+        for export_id in &export_ids {
+            let collection = self.compute.collection_mut(*export_id)?;
+            collection.as_of = as_of.clone();
+        }
+        let updates: Vec<_> = export_ids.iter().map(|id| (*id, as_of.clone())).collect();
+        let replica_ids: Vec<_> = self.compute.replica_ids().collect();
+        for replica_id in replica_ids {
+            self.update_write_frontiers(replica_id, &updates);
+        }
+
+        tracing::info!(
+            name = %dataflow.debug_name,
+            export_ids = %dataflow.display_export_ids(),
+            as_of = ?as_of.elements(),
+            "activating suspended dataflow",
+        );
+        self.compute.send(ComputeCommand::CreateDataflow(dataflow));
+
+        // Compaction of the exports was not communicated to the replicas while the dataflow was
+        // suspended, so catch up on it now.
+        for export_id in export_ids {
+            let frontier = self
+                .compute
+                .collection(export_id)
+                .expect("export of suspended dataflow must exist")
+                .read_frontier()
+                .to_owned();
+            if frontier != as_of {
+                self.compute.send(ComputeCommand::AllowCompaction {
+                    id: export_id,
+                    frontier,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     /// Drops the read capability for the given collections and allows their resources to be
     /// reclaimed.
     pub fn drop_collections(&mut self, ids: Vec<GlobalId>) -> Result<(), CollectionMissing> {
@@ -911,13 +1721,26 @@ where
             if !self.compute.replica_exists(target) {
                 return Err(PeekError::ReplicaMissing(target));
             }
+            if let PeekTarget::Index { .. } = &peek_target {
+                let time = Antichain::from_elem(timestamp.clone());
+                if !self.compute.replica_can_serve(target, id, &time) {
+                    return Err(PeekError::ReplicaAsOfBeyond(target));
+                }
+            }
+        }
+
+        // Peeked indexes must be installed on the replicas.
+        if let PeekTarget::Index { .. } = &peek_target {
+            self.activate_collection(id)?;
         }
 
         // Install a compaction hold on `id` at `timestamp`.
         let mut updates = BTreeMap::new();
         updates.insert(id, ChangeBatch::new_from(timestamp.clone(), 1));
         match &peek_target {
-            PeekTarget::Index { .. } => self.update_read_capabilities(&mut updates),
+            PeekTarget::Index { .. } => {
+                self.update_read_capabilities(ReadHoldSource::Peek(uuid), &mut updates)
+            }
             PeekTarget::Persist { .. } => self
                 .storage_controller
                 .update_read_capabilities(&mut updates),
@@ -992,25 +1815,14 @@ where
         let mut read_capability_changes = BTreeMap::default();
         for (id, policy) in policies.into_iter() {
             let collection = self.compute.collection_mut(id)?;
-            let mut new_read_capability = policy.frontier(collection.write_frontier.borrow());
-
-            if timely::order::PartialOrder::less_equal(
-                &collection.implied_capability,
-                &new_read_capability,
-            ) {
-                let mut update = ChangeBatch::new();
-                update.extend(new_read_capability.iter().map(|time| (time.clone(), 1)));
-                std::mem::swap(&mut collection.implied_capability, &mut new_read_capability);
-                update.extend(new_read_capability.iter().map(|time| (time.clone(), -1)));
-                if !update.is_empty() {
-                    read_capability_changes.insert(id, update);
-                }
-            }
-
             collection.read_policy = policy;
+            let mut update = collection.apply_read_policy();
+            if !update.is_empty() {
+                read_capability_changes.insert(id, update);
+            }
         }
         if !read_capability_changes.is_empty() {
-            self.update_read_capabilities(&mut read_capability_changes);
+            self.update_read_capabilities(ReadHoldSource::Policy, &mut read_capability_changes);
         }
         Ok(())
     }
@@ -1045,14 +1857,18 @@ where
                 .collection_mut(*id)
                 .expect("reference to absent collection");
 
-            if PartialOrder::less_than(&collection.write_frontier, new_upper) {
+            if collection.advance_write_frontier(new_upper) {
                 advanced_collections.push(*id);
-                collection.write_frontier = new_upper.clone();
             }
 
             let old_upper = collection
                 .replica_write_frontiers
                 .insert(replica_id, new_upper.clone());
+            if old_upper.is_none() {
+                collection
+                    .replica_initial_frontiers
+                    .insert(replica_id, new_upper.clone());
+            }
 
             // Safety check against frontier regressions.
             if let Some(old) = &old_upper {
@@ -1067,20 +1883,9 @@ where
                 dropped_collection_ids.push(*id);
             }
 
-            let mut new_read_capability = collection
-                .read_policy
-                .frontier(collection.write_frontier.borrow());
-            if timely::order::PartialOrder::less_equal(
-                &collection.implied_capability,
-                &new_read_capability,
-            ) {
-                let mut update = ChangeBatch::new();
-                update.extend(new_read_capability.iter().map(|time| (time.clone(), 1)));
-                std::mem::swap(&mut collection.implied_capability, &mut new_read_capability);
-                update.extend(new_read_capability.iter().map(|time| (time.clone(), -1)));
-                if !update.is_empty() {
-                    compute_read_capability_changes.insert(*id, update);
-                }
+            let mut update = collection.apply_read_policy();
+            if !update.is_empty() {
+                compute_read_capability_changes.insert(*id, update);
             }
 
             // Update read holds on storage dependencies.
@@ -1095,7 +1900,10 @@ where
             }
         }
         if !compute_read_capability_changes.is_empty() {
-            self.update_read_capabilities(&mut compute_read_capability_changes);
+            self.update_read_capabilities(
+                ReadHoldSource::Policy,
+                &mut compute_read_capability_changes,
+            );
         }
         if !storage_read_capability_changes.is_empty() {
             self.storage_controller
@@ -1130,6 +1938,7 @@ where
         let mut dropped_collection_ids = Vec::new();
         for (id, collection) in self.compute.collections.iter_mut() {
             let last_upper = collection.replica_write_frontiers.remove(&replica_id);
+            collection.replica_initial_frontiers.remove(&replica_id);
 
             if let Some(frontier) = last_upper {
                 dropped_collection_ids.push(*id);
@@ -1152,16 +1961,42 @@ where
         }
     }
 
-    /// Applies `updates`, propagates consequences through other read capabilities, and sends an appropriate compaction command.
+    /// Applies `updates` on behalf of `source`, propagates consequences through other read
+    /// capabilities, and sends an appropriate compaction command.
     #[tracing::instrument(level = "debug", skip(self))]
-    fn update_read_capabilities(&mut self, updates: &mut BTreeMap<GlobalId, ChangeBatch<T>>) {
+    fn update_read_capabilities(
+        &mut self,
+        source: ReadHoldSource,
+        updates: &mut BTreeMap<GlobalId, ChangeBatch<T>>,
+    ) {
+        let updates = updates
+            .iter_mut()
+            .map(|(id, update)| {
+                let mut changes = ChangeBatch::new();
+                changes.extend(update.drain());
+                (*id, BTreeMap::from([(source, changes)]))
+            })
+            .collect();
+        self.update_sourced_read_capabilities(updates);
+    }
+
+    /// Like [`ActiveInstance::update_read_capabilities`], but for updates by several sources.
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn update_sourced_read_capabilities(
+        &mut self,
+        mut updates: BTreeMap<GlobalId, BTreeMap<ReadHoldSource, ChangeBatch<T>>>,
+    ) {
         // Locations to record consequences that we need to act on.
         let mut storage_todo = BTreeMap::default();
         let mut compute_net = Vec::default();
         // Repeatedly extract the maximum id, and updates for it.
-        while let Some(key) = updates.keys().rev().next().cloned() {
-            let mut update = updates.remove(&key).unwrap();
+        while let Some((key, sourced_updates)) = updates.pop_last() {
             if let Ok(collection) = self.compute.collection_mut(key) {
+                let mut update = ChangeBatch::new();
+                for (source, mut changes) in sourced_updates {
+                    collection.record_read_hold_changes(source, changes.iter().cloned());
+                    update.extend(changes.drain());
+                }
                 let changes = collection.read_capabilities.update_iter(update.drain());
                 update.extend(changes);
                 for id in collection.storage_dependencies.iter() {
@@ -1173,6 +2008,8 @@ where
                 for id in collection.compute_dependencies.iter() {
                     updates
                         .entry(*id)
+                        .or_default()
+                        .entry(ReadHoldSource::Dependent(key))
                         .or_insert_with(ChangeBatch::new)
                         .extend(update.iter().cloned());
                 }
@@ -1180,10 +2017,10 @@ where
             } else {
                 // Storage presumably, but verify.
                 if self.storage_controller.collection(key).is_ok() {
-                    storage_todo
-                        .entry(key)
-                        .or_insert_with(ChangeBatch::new)
-                        .extend(update.drain())
+                    let update = storage_todo.entry(key).or_insert_with(ChangeBatch::new);
+                    for (_source, mut changes) in sourced_updates {
+                        update.extend(changes.drain());
+                    }
                 } else {
                     tracing::error!(
                         "found neither compute nor storage collection with id {}",
@@ -1205,7 +2042,9 @@ where
             if frontier.is_empty() {
                 dropped_collection_ids.push(*id);
             }
-            if !change.is_empty() {
+            // Replicas don't know about the collections of suspended dataflows. Their compaction
+            // is communicated when they are activated instead.
+            if !change.is_empty() && !self.compute.collection_suspended(*id) {
                 let frontier = frontier.to_owned();
                 self.compute
                     .send(ComputeCommand::AllowCompaction { id: *id, frontier });
@@ -1241,7 +2080,9 @@ where
         let update = (peek.target.id(), ChangeBatch::new_from(peek.time, -1));
         let mut updates = [update].into();
         match &peek.target {
-            PeekTarget::Index { .. } => self.update_read_capabilities(&mut updates),
+            PeekTarget::Index { .. } => {
+                self.update_read_capabilities(ReadHoldSource::Peek(uuid), &mut updates)
+            }
             PeekTarget::Persist { .. } => self
                 .storage_controller
                 .update_read_capabilities(&mut updates),
@@ -1315,12 +2156,9 @@ where
         // that regress frontiers they have reported previously. We still perform a check here,
         // rather than risking the controller becoming confused trying to handle regressions.
         let Ok(coll) = self.compute.collection(id) else {
-            tracing::warn!(
-                ?replica_id,
-                "Frontier update for unknown collection {id}: {:?}",
-                new_frontier.elements(),
-            );
-            tracing::error!("Replica reported an untracked collection frontier");
+            let what = format!("Frontier update {:?}", new_frontier.elements());
+            self.compute
+                .report_untracked_collection(id, replica_id, &what);
             return;
         };
 
@@ -1357,6 +2195,15 @@ where
             return None;
         }
 
+        // Ignore responses from replicas that installed the peeked index at an `as_of` beyond
+        // the peek time. They can't answer the peek correctly.
+        if let PeekTarget::Index { id } = &peek.target {
+            let time = Antichain::from_elem(peek.time.clone());
+            if !self.compute.replica_can_serve(replica_id, *id, &time) {
+                return None;
+            }
+        }
+
         let duration = peek.requested_at.elapsed();
         self.compute
             .metrics
@@ -1378,8 +2225,11 @@ where
         replica_id: ReplicaId,
     ) -> Option<ComputeControllerResponse<T>> {
         if !self.compute.collections.contains_key(&subscribe_id) {
-            tracing::warn!(?replica_id, "Response for unknown subscribe {subscribe_id}",);
-            tracing::error!("Replica sent a response for an unknown subscibe");
+            self.compute.report_untracked_collection(
+                subscribe_id,
+                replica_id,
+                "Subscribe response",
+            );
             return None;
         }
 
@@ -1401,6 +2251,16 @@ where
 
         match response {
             SubscribeResponse::Batch(batch) => {
+                // Ignore batches from replicas that installed the subscribe at an `as_of` beyond
+                // its current frontier, until the subscribe has caught up to that `as_of`. Their
+                // batches would be missing the updates at the times in between.
+                if !self
+                    .compute
+                    .replica_can_serve(replica_id, subscribe_id, &subscribe.frontier)
+                {
+                    return None;
+                }
+
                 let upper = batch.upper;
                 let mut updates = batch.updates;
 
@@ -1409,32 +2269,27 @@ where
                 if PartialOrder::less_than(&subscribe.frontier, &upper) {
                     let lower = std::mem::replace(&mut subscribe.frontier, upper.clone());
 
-                    if upper.is_empty() {
-                        // This subscribe cannot produce more data. Stop tracking it.
-                        self.compute.subscribes.remove(&subscribe_id);
-                    } else {
-                        // This subscribe can produce more data. Update our tracking of it.
-                        self.compute.subscribes.insert(subscribe_id, subscribe);
+                    if !upper.is_empty() {
+                        self.update_subscribe_lag(subscribe_id, &mut subscribe);
                     }
 
                     if let Ok(updates) = updates.as_mut() {
                         updates.retain(|(time, _data, _diff)| lower.less_equal(time));
                     }
-                    Some(ComputeControllerResponse::SubscribeResponse(
-                        subscribe_id,
-                        SubscribeResponse::Batch(SubscribeBatch {
-                            lower,
-                            upper,
-                            updates,
-                        }),
-                    ))
+                    let batch = SubscribeBatch {
+                        lower,
+                        upper,
+                        updates,
+                    };
+                    self.compute
+                        .emit_subscribe_batches(subscribe_id, subscribe, batch)
                 } else {
                     None
                 }
             }
             SubscribeResponse::DroppedAt(_) => {
                 // This subscribe cannot produce more data. Stop tracking it.
-                self.compute.subscribes.remove(&subscribe_id);
+                self.compute.remove_subscribe(subscribe_id);
 
                 Some(ComputeControllerResponse::SubscribeResponse(
                     subscribe_id,
@@ -1443,6 +2298,107 @@ where
             }
         }
     }
+
+    /// Update the reported lag of the given subscribe, after its frontier has advanced.
+    ///
+    /// The lag is the distance between the write frontier of the subscribe's inputs and the
+    /// subscribe's own frontier. If the lag changed, the previously reported value is retracted.
+    fn update_subscribe_lag(&mut self, subscribe_id: GlobalId, subscribe: &mut ActiveSubscribe<T>) {
+        let to_ts = |t: &T| -> mz_repr::Timestamp { t.clone().into() };
+        let Some(upper) = subscribe.frontier.iter().map(to_ts).min() else {
+            return;
+        };
+        let Ok(collection) = self.compute.collection(subscribe_id) else {
+            return;
+        };
+
+        // The input frontier is the meet of the write frontiers of all inputs.
+        let mut input_upper = None;
+        for id in &collection.compute_dependencies {
+            if let Ok(dep) = self.compute.collection(*id) {
+                input_upper = dep
+                    .write_frontier()
+                    .iter()
+                    .map(to_ts)
+                    .chain(input_upper)
+                    .min();
+            }
+        }
+        for id in &collection.storage_dependencies {
+            if let Ok(dep) = self.storage_controller.collection(*id) {
+                input_upper = dep
+                    .write_frontier
+                    .iter()
+                    .map(to_ts)
+                    .chain(input_upper)
+                    .min();
+            }
+        }
+        let Some(input_upper) = input_upper else {
+            return;
+        };
+
+        let lag = u64::from(input_upper.saturating_sub(upper));
+        if subscribe.lag == Some(lag) {
+            return;
+        }
+
+        let mut updates = Vec::new();
+        if let Some(old) = subscribe.lag.replace(lag) {
+            updates.push((subscribe_lag_row(subscribe_id, old), -1));
+        }
+        updates.push((subscribe_lag_row(subscribe_id, lag), 1));
+        self.compute
+            .deliver_introspection_updates(IntrospectionType::ComputeSubscribeLag, updates);
+    }
+}
+
+/// Returns the given command as it should be sent to the identified replica.
+///
+/// `CreateDataflow` commands have their `as_of` advanced to the replica's override for the
+/// dataflow, if there is one. Other commands are sent unchanged.
+fn command_for_replica<T: Timestamp + Lattice>(
+    replica_as_ofs: &BTreeMap<ReplicaId, BTreeMap<GlobalId, Antichain<T>>>,
+    replica_id: ReplicaId,
+    command: &ComputeCommand<T>,
+) -> ComputeCommand<T> {
+    let mut command = command.clone();
+    if let ComputeCommand::CreateDataflow(dataflow) = &mut command {
+        let replica_as_of = replica_as_ofs
+            .get(&replica_id)
+            .and_then(|as_ofs| dataflow.export_ids().find_map(|id| as_ofs.get(&id)));
+        if let (Some(as_of), Some(replica_as_of)) = (dataflow.as_of.as_mut(), replica_as_of) {
+            as_of.join_assign(replica_as_of);
+        }
+    }
+    command
+}
+
+/// Returns the `as_of` of a suspended dataflow on activation.
+///
+/// While the dataflow was suspended, the read policies of its exports might have allowed its
+/// inputs to compact beyond its original `as_of`. The dataflow is then advanced to the join of
+/// the input `since`s, the earliest time it can still read all its inputs at. This is never
+/// beyond the read frontiers of the exports, as those hold back the inputs.
+fn activation_as_of<T: Timestamp + Lattice>(
+    as_of: &Antichain<T>,
+    input_sinces: impl IntoIterator<Item = Antichain<T>>,
+) -> Antichain<T> {
+    let mut as_of = as_of.clone();
+    for since in input_sinces {
+        as_of.join_assign(&since);
+    }
+    as_of
+}
+
+/// Returns the `ComputeSubscribeLag` introspection row for the given subscribe and lag, in
+/// milliseconds.
+fn subscribe_lag_row(subscribe_id: GlobalId, lag: u64) -> Row {
+    let lag_micros = i64::try_from(lag.saturating_mul(1000)).unwrap_or(i64::MAX);
+    Row::pack_slice(&[
+        Datum::String(&subscribe_id.to_string()),
+        Datum::Interval(Interval::new(0, 0, lag_micros)),
+    ])
 }
 
 #[derive(Debug)]
@@ -1463,6 +2419,17 @@ struct PendingPeek<T> {
     requested_at: Instant,
 }
 
+impl<T: Clone> PendingPeek<T> {
+    fn describe(&self) -> PeekDescription<T> {
+        PeekDescription {
+            target: self.target.clone(),
+            time: self.time.clone(),
+            target_replica: self.target_replica,
+            outstanding: self.requested_at.elapsed(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ActiveSubscribe<T> {
     /// Current upper frontier of this subscribe.
@@ -1471,6 +2438,10 @@ struct ActiveSubscribe<T> {
     ///
     /// If this value is `None`, we pass on the first response for each time slice.
     target_replica: Option<ReplicaId>,
+    /// The lag, in milliseconds, last reported to the `ComputeSubscribeLag` introspection.
+    lag: Option<u64>,
+    /// The number of bytes of updates emitted but not yet acknowledged by the consumer.
+    buffered_bytes: u64,
 }
 
 impl<T: Timestamp> ActiveSubscribe<T> {
@@ -1478,6 +2449,1512 @@ impl<T: Timestamp> ActiveSubscribe<T> {
         Self {
             frontier: Antichain::from_elem(Timestamp::minimum()),
             target_replica: None,
+            lag: None,
+            buffered_bytes: 0,
+        }
+    }
+}
+
+/// Limits beyond which a subscribe is considered too slow and gets terminated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SubscribeLimits {
+    /// The maximum number of bytes of emitted but unacknowledged output.
+    max_buffered_bytes: Option<u64>,
+    /// The maximum lag of the subscribe's frontier behind the frontier of its inputs.
+    max_lag: Option<std::time::Duration>,
+}
+
+impl SubscribeLimits {
+    /// Reports whether the given subscribe exceeds any of the limits.
+    fn exceeded_by<T>(&self, subscribe: &ActiveSubscribe<T>) -> bool {
+        let bytes_exceeded = self
+            .max_buffered_bytes
+            .map_or(false, |max| subscribe.buffered_bytes > max);
+        let lag_exceeded = match (self.max_lag, subscribe.lag) {
+            (Some(max), Some(lag)) => u128::from(lag) > max.as_millis(),
+            _ => false,
+        };
+        bytes_exceeded || lag_exceeded
+    }
+}
+
+/// Limits beyond which a batch of subscribe output is split into several batches.
+///
+/// Replicas can report arbitrarily large batches of subscribe output, e.g., for the initial
+/// snapshot of a large collection. Forwarding those as a single response leads to memory spikes
+/// and long pauses in the consumer, so the controller splits them.
+///
+/// Splitting a batch `[lower, upper)` produces a sequence of batches with the following
+/// properties:
+///
+///   * Concatenating their updates yields the updates of the original batch, sorted by time.
+///   * All updates at the same time are contained in the same batch, so a batch only exceeds the
+///     limits if the updates at a single time exceed them on their own.
+///   * All but the last batch have `lower == upper == lower` of the original batch. They carry
+///     updates but don't advance the subscribe's frontier.
+///   * The last batch has the original `lower` and `upper`, so it carries the frontier advancement.
+///
+/// Consumers that only act on frontier advancements thus observe the same progress as without
+/// splitting, and consumers that process updates per time see all updates at a time at once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SubscribeBatchLimits {
+    /// The maximum number of updates in a batch.
+    max_rows: Option<u64>,
+    /// The maximum size in bytes of the updates in a batch.
+    max_bytes: Option<u64>,
+}
+
+impl SubscribeBatchLimits {
+    /// Reports whether a batch with the given number of updates and bytes exceeds the limits.
+    fn exceeded_by(&self, rows: usize, bytes: usize) -> bool {
+        let rows_exceeded = self
+            .max_rows
+            .map_or(false, |max| u64::cast_from(rows) > max);
+        let bytes_exceeded = self
+            .max_bytes
+            .map_or(false, |max| u64::cast_from(bytes) > max);
+        rows_exceeded || bytes_exceeded
+    }
+
+    /// Splits the given batch into batches that don't exceed the limits, as described on
+    /// [`SubscribeBatchLimits`].
+    ///
+    /// Batches that don't exceed the limits, or that carry an error, are returned unchanged.
+    fn split<T: Timestamp>(&self, batch: SubscribeBatch<T>) -> Vec<SubscribeBatch<T>> {
+        let (lower, upper, mut updates) = match batch {
+            SubscribeBatch {
+                lower,
+                upper,
+                updates: Ok(updates),
+            } => (lower, upper, updates),
+            batch => return vec![batch],
+        };
+        let bytes = updates.iter().map(|(_, row, _)| row.byte_len()).sum();
+        if !self.exceeded_by(updates.len(), bytes) {
+            return vec![SubscribeBatch {
+                lower,
+                upper,
+                updates: Ok(updates),
+            }];
+        }
+
+        // Sort by time, so updates at the same time are adjacent. The sort is stable to retain the
+        // order of updates within a time.
+        updates.sort_by(|(x, _, _), (y, _, _)| x.cmp(y));
+
+        let mut parts = Vec::new();
+        let mut part: Vec<(T, Row, Diff)> = Vec::new();
+        let mut part_bytes = 0;
+        let mut updates = updates.into_iter().peekable();
+        while let Some(update) = updates.next() {
+            // Collect all updates at the time of `update`.
+            let time = update.0.clone();
+            let mut group_bytes = update.1.byte_len();
+            let mut group = vec![update];
+            while let Some(next) = updates.next_if(|(t, _, _)| *t == time) {
+                group_bytes += next.1.byte_len();
+                group.push(next);
+            }
+
+            let exceeded = self.exceeded_by(part.len() + group.len(), part_bytes + group_bytes);
+            if exceeded && !part.is_empty() {
+                parts.push(std::mem::take(&mut part));
+                part_bytes = 0;
+            }
+            part.extend(group);
+            part_bytes += group_bytes;
+        }
+        parts.push(part);
+
+        let last = parts.len() - 1;
+        parts
+            .into_iter()
+            .enumerate()
+            .map(|(i, updates)| SubscribeBatch {
+                lower: lower.clone(),
+                upper: if i == last {
+                    upper.clone()
+                } else {
+                    lower.clone()
+                },
+                updates: Ok(updates),
+            })
+            .collect()
+    }
+}
+
+/// The backoff applied to restarts of failed replicas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RestartBackoffConfig {
+    /// The delay before the first restart after a failure.
+    base: std::time::Duration,
+    /// The maximum delay before a restart.
+    cap: std::time::Duration,
+}
+
+impl Default for RestartBackoffConfig {
+    fn default() -> Self {
+        Self {
+            base: std::time::Duration::from_secs(1),
+            cap: std::time::Duration::from_secs(60),
+        }
+    }
+}
+
+/// Restart backoff state of a single replica.
+///
+/// Each rehydration attempt of a replica that keeps failing waits twice as long as the previous
+/// one, up to the configured cap. Once a replica has been responding for at least the cap since
+/// its last rehydration, its failures are forgotten.
+#[derive(Debug)]
+struct ReplicaBackoff {
+    /// The number of failures since the replica was last considered healthy.
+    consecutive_failures: u32,
+    /// The time of the last (re)hydration of the replica.
+    last_attempt: Instant,
+    /// Metrics reflecting the backoff state.
+    metrics: ReplicaBackoffMetrics,
+}
+
+impl ReplicaBackoff {
+    fn new(now: Instant, metrics: ReplicaBackoffMetrics) -> Self {
+        Self {
+            consecutive_failures: 0,
+            last_attempt: now,
+            metrics,
+        }
+    }
+
+    /// Return the delay between the last (re)hydration and the next rehydration.
+    fn delay(&self, config: &RestartBackoffConfig) -> std::time::Duration {
+        let Some(exponent) = self.consecutive_failures.checked_sub(1) else {
+            return std::time::Duration::ZERO;
+        };
+        2_u32
+            .checked_pow(exponent)
+            .and_then(|factor| config.base.checked_mul(factor))
+            .map_or(config.cap, |delay| delay.min(config.cap))
+    }
+
+    /// Return the earliest time at which the replica may be rehydrated.
+    fn next_attempt(&self, config: &RestartBackoffConfig) -> Instant {
+        self.last_attempt + self.delay(config)
+    }
+
+    /// Record a failure of the replica.
+    fn record_failure(&mut self, config: &RestartBackoffConfig) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.update_metrics(config);
+    }
+
+    /// Record a rehydration of the replica at time `now`.
+    fn record_attempt(&mut self, now: Instant) {
+        self.last_attempt = now;
+        self.metrics.restarts_total.inc();
+    }
+
+    /// Record a response from the replica at time `now`.
+    ///
+    /// Forgets previous failures if the replica has been healthy for long enough.
+    fn record_healthy(&mut self, config: &RestartBackoffConfig, now: Instant) {
+        if self.consecutive_failures > 0
+            && now.saturating_duration_since(self.last_attempt) >= config.cap
+        {
+            self.consecutive_failures = 0;
+            self.update_metrics(config);
+        }
+    }
+
+    fn update_metrics(&self, config: &RestartBackoffConfig) {
+        self.metrics
+            .consecutive_failures
+            .set(u64::from(self.consecutive_failures));
+        self.metrics
+            .backoff_seconds
+            .set(self.delay(config).as_secs_f64());
+    }
+}
+
+/// The maximum number of recently dropped collections remembered by [`UntrackedCollections`].
+const MAX_RECENTLY_DROPPED: usize = 1024;
+
+/// Bookkeeping for replica responses about collections the controller does not track.
+///
+/// Replicas can legitimately report on collections that were dropped concurrently, so responses
+/// for recently dropped collections are expected. Responses for collections that were never
+/// tracked (or dropped long ago) indicate a protocol bug and are quarantined per replica.
+#[derive(Debug)]
+struct UntrackedCollections {
+    /// Recently dropped collections, in drop order.
+    ///
+    /// Bounded in size by [`MAX_RECENTLY_DROPPED`]; entries expire after `retention`.
+    recently_dropped: VecDeque<(Instant, GlobalId)>,
+    /// The IDs contained in `recently_dropped`, for fast lookup.
+    recently_dropped_ids: BTreeSet<GlobalId>,
+    /// How long dropped collections are remembered.
+    retention: std::time::Duration,
+    /// Per replica, the IDs of never-tracked collections the replica has reported on.
+    quarantine: BTreeMap<ReplicaId, BTreeSet<GlobalId>>,
+}
+
+/// The kind of a replica response for an untracked collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UntrackedKind {
+    /// The collection was dropped recently. The response likely raced with the drop.
+    RecentlyDropped,
+    /// The collection is unknown.
+    Unknown,
+}
+
+impl UntrackedCollections {
+    fn new(retention: std::time::Duration) -> Self {
+        Self {
+            recently_dropped: Default::default(),
+            recently_dropped_ids: Default::default(),
+            retention,
+            quarantine: Default::default(),
+        }
+    }
+
+    /// Remember that the collection `id` was dropped at time `now`.
+    fn record_drop(&mut self, id: GlobalId, now: Instant) {
+        self.expire(now);
+        if self.recently_dropped_ids.insert(id) {
+            self.recently_dropped.push_back((now, id));
+        }
+        while self.recently_dropped.len() > MAX_RECENTLY_DROPPED {
+            if let Some((_, id)) = self.recently_dropped.pop_front() {
+                self.recently_dropped_ids.remove(&id);
+            }
+        }
+    }
+
+    /// Forget dropped collections that are older than the retention.
+    fn expire(&mut self, now: Instant) {
+        while let Some((dropped_at, id)) = self.recently_dropped.front() {
+            if now.saturating_duration_since(*dropped_at) <= self.retention {
+                break;
+            }
+            self.recently_dropped_ids.remove(id);
+            self.recently_dropped.pop_front();
+        }
+    }
+
+    /// Classify a response from `replica_id` about the untracked collection `id`.
+    ///
+    /// Unknown collections are added to the replica's quarantine.
+    fn classify(&mut self, id: GlobalId, replica_id: ReplicaId, now: Instant) -> UntrackedKind {
+        self.expire(now);
+        if self.recently_dropped_ids.contains(&id) {
+            UntrackedKind::RecentlyDropped
+        } else {
+            self.quarantine.entry(replica_id).or_default().insert(id);
+            UntrackedKind::Unknown
+        }
+    }
+
+    /// Return the IDs quarantined for the given replica.
+    fn quarantined(&self, replica_id: ReplicaId) -> &BTreeSet<GlobalId> {
+        static EMPTY: BTreeSet<GlobalId> = BTreeSet::new();
+        self.quarantine.get(&replica_id).unwrap_or(&EMPTY)
+    }
+
+    /// Forget the quarantine of the given replica.
+    fn remove_replica(&mut self, replica_id: ReplicaId) {
+        self.quarantine.remove(&replica_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mz_build_info::DUMMY_BUILD_INFO;
+    use mz_cluster_client::client::ClusterReplicaLocation;
+    use mz_compute_types::dataflows::{IndexDesc, IndexImport};
+    use mz_compute_types::plan::Plan;
+    use mz_compute_types::sinks::SubscribeSinkConnection;
+    use mz_compute_types::ComputeInstanceId;
+    use mz_expr::MapFilterProject;
+    use mz_ore::metrics::MetricsRegistry;
+    use mz_repr::{RelationDesc, RelationType, Timestamp};
+
+    use crate::logging::TimelyLog;
+    use crate::metrics::ComputeControllerMetrics;
+
+    use super::*;
+
+    /// Returns an instance without replicas, along with the receivers of its responses and
+    /// introspection updates.
+    fn test_instance() -> (
+        Instance<Timestamp>,
+        crossbeam_channel::Receiver<ComputeControllerResponse<Timestamp>>,
+        crossbeam_channel::Receiver<IntrospectionUpdates>,
+    ) {
+        test_instance_with_logs(BTreeMap::new())
+    }
+
+    /// Like [`test_instance`], but with the given arranged log collections.
+    fn test_instance_with_logs(
+        arranged_logs: BTreeMap<LogVariant, GlobalId>,
+    ) -> (
+        Instance<Timestamp>,
+        crossbeam_channel::Receiver<ComputeControllerResponse<Timestamp>>,
+        crossbeam_channel::Receiver<IntrospectionUpdates>,
+    ) {
+        let metrics = ComputeControllerMetrics::new(MetricsRegistry::new())
+            .for_instance(ComputeInstanceId::User(1));
+        let (response_tx, response_rx) = crossbeam_channel::unbounded();
+        let (introspection_tx, introspection_rx) = crossbeam_channel::unbounded();
+        let instance = Instance::new(
+            &DUMMY_BUILD_INFO,
+            arranged_logs,
+            NonZeroI64::new(1).unwrap(),
+            metrics,
+            response_tx,
+            introspection_tx,
+            Duration::from_secs(60),
+        );
+        (instance, response_rx, introspection_rx)
+    }
+
+    /// Returns the `as_of`s of the dataflows a newly added replica would be sent.
+    fn replayed_dataflows(instance: &Instance<Timestamp>) -> Vec<Antichain<Timestamp>> {
+        instance
+            .history
+            .iter()
+            .filter_map(|command| match command {
+                ComputeCommand::CreateDataflow(dataflow) => dataflow.as_of.clone(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns a dataflow that exports indexes and subscribes with the given IDs.
+    fn test_dataflow(
+        index_ids: &[GlobalId],
+        subscribe_ids: &[GlobalId],
+        as_of: u64,
+    ) -> DataflowDescription<Plan<Timestamp>, (), Timestamp> {
+        let index_desc = IndexDesc {
+            on_id: GlobalId::User(0),
+            key: Vec::new(),
+        };
+        let sink_desc = ComputeSinkDesc {
+            from: GlobalId::User(0),
+            from_desc: RelationDesc::empty(),
+            connection: ComputeSinkConnection::Subscribe(SubscribeSinkConnection::default()),
+            with_snapshot: true,
+            up_to: Antichain::new(),
+            non_null_assertions: Vec::new(),
+            refresh_schedule: None,
+        };
+        DataflowDescription {
+            source_imports: BTreeMap::new(),
+            index_imports: BTreeMap::new(),
+            objects_to_build: Vec::new(),
+            index_exports: index_ids
+                .iter()
+                .map(|id| (*id, (index_desc.clone(), RelationType::empty())))
+                .collect(),
+            sink_exports: subscribe_ids
+                .iter()
+                .map(|id| (*id, sink_desc.clone()))
+                .collect(),
+            as_of: Some(Antichain::from_elem(as_of.into())),
+            until: Antichain::new(),
+            debug_name: "test".into(),
+        }
+    }
+
+    /// Returns an import of an index, for dataflows reading from other dataflows.
+    fn index_import() -> IndexImport {
+        IndexImport {
+            desc: IndexDesc {
+                on_id: GlobalId::User(0),
+                key: Vec::new(),
+            },
+            typ: RelationType::empty(),
+            monotonic: false,
+        }
+    }
+
+    /// Adds a replica that never manages to connect, so it only ever sees the commands sent to it
+    /// and only ever responds with what a test passes to `handle_response`.
+    fn add_test_replica(instance: &mut ActiveInstance<Timestamp, NoStorage>, id: ReplicaId) {
+        let config = ReplicaConfig {
+            location: ClusterReplicaLocation {
+                ctl_addrs: vec!["localhost:0".into()],
+                dataflow_addrs: vec!["localhost:0".into()],
+                workers: 1,
+            },
+            logging: Default::default(),
+            idle_arrangement_merge_effort: 0,
+            arrangement_exert_proportionality: 0,
+            grpc_client: Default::default(),
+        };
+        instance.add_replica(id, config).unwrap();
+    }
+
+    /// Peeks the index `id` at `time`, returning the UUID of the peek.
+    fn peek_index(
+        active: &mut ActiveInstance<Timestamp, NoStorage>,
+        id: GlobalId,
+        time: u64,
+        target_replica: Option<ReplicaId>,
+    ) -> Result<Uuid, PeekError> {
+        let uuid = Uuid::new_v4();
+        let mfp = MapFilterProject::new(0)
+            .into_plan()
+            .unwrap()
+            .into_nontemporal()
+            .unwrap();
+        active.peek(
+            id,
+            None,
+            uuid,
+            time.into(),
+            RowSetFinishing::trivial(0),
+            mfp,
+            target_replica,
+            PeekTarget::Index { id },
+        )?;
+        Ok(uuid)
+    }
+
+    /// Storage without any collections, for driving dataflows that don't read from storage
+    /// through an [`ActiveInstance`].
+    #[derive(Debug)]
+    struct NoStorage;
+
+    impl InstanceStorage<Timestamp> for NoStorage {
+        fn collection(
+            &self,
+            id: GlobalId,
+        ) -> Result<&StorageCollectionState<Timestamp>, StorageError> {
+            Err(StorageError::IdentifierMissing(id))
+        }
+
+        fn update_write_frontiers(&mut self, updates: &[(GlobalId, Antichain<Timestamp>)]) {
+            if let Some((id, _)) = updates.first() {
+                panic!("write frontier update for unknown collection {id}");
+            }
+        }
+
+        fn update_read_capabilities(
+            &mut self,
+            updates: &mut BTreeMap<GlobalId, ChangeBatch<Timestamp>>,
+        ) {
+            if let Some(id) = updates.keys().next() {
+                panic!("read capability update for unknown collection {id}");
+            }
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn suspended_dataflow_tracking() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (a, b) = (GlobalId::User(1), GlobalId::User(2));
+        add_test_replica(&mut active, ReplicaId::User(1));
+        active
+            .create_dataflow(
+                test_dataflow(&[a, b], &[], 5),
+                DataflowActivation::Suspended,
+                BTreeMap::new(),
+                BTreeMap::new(),
+            )
+            .unwrap();
+
+        // The exports are tracked like any other collection, but nothing is sent to replicas,
+        // including replicas added while the dataflow is suspended.
+        add_test_replica(&mut active, ReplicaId::User(2));
+        assert!(active.compute.collection_suspended(a));
+        assert!(active.compute.collection_suspended(b));
+        assert!(!active.compute.collection_suspended(GlobalId::User(3)));
+        let collection = active.compute.collection(a).unwrap();
+        assert_eq!(
+            collection.write_frontier(),
+            Antichain::from_elem(5.into()).borrow()
+        );
+        assert!(collection.replica_write_frontiers.is_empty());
+        assert!(replayed_dataflows(active.compute).is_empty());
+
+        // Dropping an export removes it from the suspended dataflow, dropping all of them
+        // discards the dataflow.
+        active.drop_collections(vec![a]).unwrap();
+        assert!(active.compute.collection(a).is_err());
+        let exports: Vec<_> = active
+            .compute
+            .suspended_dataflow(b)
+            .unwrap()
+            .export_ids()
+            .collect();
+        assert_eq!(exports, [b]);
+        active.drop_collections(vec![b]).unwrap();
+        assert!(active.compute.suspended_dataflows.is_empty());
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn suspended_dataflow_activation() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (a, b, c) = (GlobalId::User(1), GlobalId::User(2), GlobalId::User(3));
+        let replica = ReplicaId::User(1);
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        add_test_replica(&mut active, replica);
+
+        // `b` reads from `a`, `c` is unrelated.
+        let mut dataflow_b = test_dataflow(&[b], &[], 5);
+        dataflow_b.index_imports.insert(a, index_import());
+        for dataflow in [
+            test_dataflow(&[a], &[], 5),
+            dataflow_b,
+            test_dataflow(&[c], &[], 5),
+        ] {
+            active
+                .create_dataflow(
+                    dataflow,
+                    DataflowActivation::Suspended,
+                    BTreeMap::new(),
+                    BTreeMap::new(),
+                )
+                .unwrap();
+        }
+
+        // Creating a dataflow that fails validation doesn't activate the dataflows it reads from.
+        let mut invalid = test_dataflow(&[GlobalId::User(4)], &[], 3);
+        invalid.index_imports.insert(a, index_import());
+        let res = active.create_dataflow(
+            invalid,
+            DataflowActivation::Immediate,
+            BTreeMap::new(),
+            BTreeMap::new(),
+        );
+        assert!(matches!(res, Err(DataflowCreationError::SinceViolation(id)) if id == a));
+        assert!(active.compute.collection_suspended(a));
+
+        // Activating a collection activates its whole dataflow, after the suspended dataflows it
+        // reads from, and starts tracking the replica frontiers of their exports.
+        active.activate_collection(b).unwrap();
+        assert!(!active.compute.collection_suspended(a));
+        assert!(!active.compute.collection_suspended(b));
+        assert!(active.compute.collection_suspended(c));
+        assert_eq!(replayed_dataflows(active.compute), [as_of(5), as_of(5)]);
+        let collection = active.compute.collection(b).unwrap();
+        assert_eq!(collection.replica_write_frontiers[&replica], as_of(5));
+
+        // Activating an active collection has no effect, peeking a suspended one activates it.
+        active.activate_collection(a).unwrap();
+        assert_eq!(replayed_dataflows(active.compute).len(), 2);
+        peek_index(&mut active, c, 5, None).unwrap();
+        assert!(!active.compute.collection_suspended(c));
+        assert_eq!(replayed_dataflows(active.compute).len(), 3);
+
+        // Replicas added later install the activated dataflows, also after the history has been
+        // reduced.
+        active.compute.history.reduce();
+        assert_eq!(replayed_dataflows(active.compute).len(), 3);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn initial_read_policy() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (default, lagging) = (GlobalId::User(1), GlobalId::User(2));
+        let replica = ReplicaId::User(1);
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        let read_capability = |active: &ActiveInstance<Timestamp, NoStorage>, id| {
+            active
+                .compute
+                .collection(id)
+                .unwrap()
+                .read_capability()
+                .clone()
+        };
+        add_test_replica(&mut active, replica);
+
+        // Policies can only be given for the dataflow's exports, and are validated before
+        // anything is installed.
+        let policies = BTreeMap::from([(lagging, ReadPolicy::lag_writes_by(3.into(), 1.into()))]);
+        let result = active.create_dataflow(
+            test_dataflow(&[default], &[], 5),
+            DataflowActivation::Immediate,
+            BTreeMap::new(),
+            policies.clone(),
+        );
+        assert!(matches!(
+            result,
+            Err(DataflowCreationError::CollectionMissing(id)) if id == lagging
+        ));
+        assert!(active.compute.collection(default).is_err());
+
+        // Before any writes are known, the policy can't move the capability.
+        active
+            .create_dataflow(
+                test_dataflow(&[default, lagging], &[], 5),
+                DataflowActivation::Immediate,
+                BTreeMap::new(),
+                policies,
+            )
+            .unwrap();
+        assert_eq!(read_capability(&active, default), as_of(5));
+        assert_eq!(read_capability(&active, lagging), as_of(5));
+
+        // Once the first write frontier is reported, the capability follows the policy right
+        // away, whereas the default policy holds it at the `as_of`.
+        for id in [default, lagging] {
+            let response = ComputeResponse::FrontierUpper {
+                id,
+                upper: as_of(20),
+            };
+            active.handle_response(response, replica);
+        }
+        assert_eq!(read_capability(&active, default), as_of(5));
+        assert_eq!(read_capability(&active, lagging), as_of(17));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn replica_hydration() {
+        let log_id = GlobalId::System(1);
+        let logs = BTreeMap::from([(LogVariant::Timely(TimelyLog::Operates), log_id)]);
+        let (mut instance, _response_rx, _introspection_rx) = test_instance_with_logs(logs);
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (a, b, suspended) = (GlobalId::User(1), GlobalId::User(2), GlobalId::User(3));
+        let (early, late) = (ReplicaId::User(1), ReplicaId::User(2));
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        let report = |active: &mut ActiveInstance<Timestamp, NoStorage>, replica_id, id, upper| {
+            let response = ComputeResponse::FrontierUpper { id, upper };
+            active.handle_response(response, replica_id);
+        };
+
+        // Log collections and collections of suspended dataflows are never hydrating, and
+        // collections installed at an `as_of` override must advance beyond that.
+        add_test_replica(&mut active, early);
+        for (id, replica_as_ofs) in [
+            (a, BTreeMap::new()),
+            (b, BTreeMap::from([(early, as_of(8))])),
+        ] {
+            let dataflow = test_dataflow(&[id], &[], 5);
+            active
+                .create_dataflow(
+                    dataflow,
+                    DataflowActivation::Immediate,
+                    replica_as_ofs,
+                    BTreeMap::new(),
+                )
+                .unwrap();
         }
+        let dataflow = test_dataflow(&[suspended], &[], 5);
+        active
+            .create_dataflow(
+                dataflow,
+                DataflowActivation::Suspended,
+                BTreeMap::new(),
+                BTreeMap::new(),
+            )
+            .unwrap();
+        let status =
+            |active: &ActiveInstance<Timestamp, NoStorage>| active.compute.hydration_status();
+        assert_eq!(
+            status(&active),
+            BTreeMap::from([(early, BTreeSet::from([a, b]))])
+        );
+
+        // Reaching the `as_of` is not enough, replicas must produce output beyond it.
+        report(&mut active, early, a, as_of(5));
+        report(&mut active, early, b, as_of(7));
+        assert_eq!(
+            status(&active),
+            BTreeMap::from([(early, BTreeSet::from([a, b]))])
+        );
+        report(&mut active, early, a, as_of(6));
+        report(&mut active, early, b, as_of(9));
+        assert_eq!(status(&active), BTreeMap::from([(early, BTreeSet::new())]));
+
+        // Replicas added later start out at the read frontier and must advance beyond it. Once the
+        // read frontier is empty, as for a dropped collection, there is nothing left to hydrate.
+        active
+            .set_read_policy(vec![
+                (a, ReadPolicy::ValidFrom(Antichain::new())),
+                (b, ReadPolicy::ValidFrom(as_of(9))),
+            ])
+            .unwrap();
+        add_test_replica(&mut active, late);
+        assert_eq!(
+            status(&active),
+            BTreeMap::from([(early, BTreeSet::new()), (late, BTreeSet::from([b]))])
+        );
+        report(&mut active, late, b, as_of(9));
+        assert_eq!(status(&active)[&late], BTreeSet::from([b]));
+        report(&mut active, late, b, as_of(10));
+        assert_eq!(status(&active)[&late], BTreeSet::new());
+    }
+
+    #[mz_ore::test]
+    fn replica_as_of_overrides() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let (a, b) = (GlobalId::User(1), GlobalId::User(2));
+        let (replica, other_replica) = (ReplicaId::User(1), ReplicaId::User(2));
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+
+        // Overrides must not move the `as_of` backwards.
+        let mut active = instance.activate(&mut storage);
+        let result = active.create_dataflow(
+            test_dataflow(&[a, b], &[], 5),
+            DataflowActivation::Immediate,
+            BTreeMap::from([(replica, as_of(3))]),
+            BTreeMap::new(),
+        );
+        assert!(matches!(
+            result,
+            Err(DataflowCreationError::ReplicaAsOfRegression(id)) if id == replica
+        ));
+        active
+            .create_dataflow(
+                test_dataflow(&[a, b], &[], 5),
+                DataflowActivation::Immediate,
+                BTreeMap::from([(replica, as_of(8))]),
+                BTreeMap::new(),
+            )
+            .unwrap();
+
+        // Only the replica with an override installs the dataflow at the later `as_of`.
+        let create_as_of = |instance: &Instance<Timestamp>, replica_id| {
+            let as_ofs: Vec<_> = instance
+                .history
+                .iter()
+                .filter_map(|command| {
+                    match command_for_replica(&instance.replica_as_ofs, replica_id, command) {
+                        ComputeCommand::CreateDataflow(dataflow) => dataflow.as_of,
+                        _ => None,
+                    }
+                })
+                .collect();
+            as_ofs
+        };
+        assert_eq!(create_as_of(&instance, replica), [as_of(8)]);
+        assert_eq!(create_as_of(&instance, other_replica), [as_of(5)]);
+
+        // The overrides are discarded along with the dataflow's exports.
+        instance.remove_collection(a);
+        assert_eq!(instance.replica_as_ofs[&replica].len(), 1);
+        instance.remove_collection(b);
+        assert!(instance.replica_as_ofs.is_empty());
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn replica_as_of_overrides_exclude_earlier_reads() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (index, subscribe) = (GlobalId::User(1), GlobalId::User(2));
+        let (replica, late_replica) = (ReplicaId::User(1), ReplicaId::User(2));
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        add_test_replica(&mut active, replica);
+        add_test_replica(&mut active, late_replica);
+        active
+            .create_dataflow(
+                test_dataflow(&[index], &[subscribe], 5),
+                DataflowActivation::Immediate,
+                BTreeMap::from([(late_replica, as_of(8))]),
+                BTreeMap::new(),
+            )
+            .unwrap();
+
+        // Peeks below the later `as_of` can't target the replica, and its responses to them are
+        // ignored.
+        let peek = |active: &mut ActiveInstance<Timestamp, NoStorage>, time, target| {
+            peek_index(active, index, time, target)
+        };
+        let respond = |active: &mut ActiveInstance<Timestamp, NoStorage>, uuid, replica_id| {
+            let response = ComputeResponse::PeekResponse(
+                uuid,
+                PeekResponse::Rows(Vec::new()),
+                OpenTelemetryContext::empty(),
+            );
+            active.handle_response(response, replica_id).is_some()
+        };
+        assert!(matches!(
+            peek(&mut active, 6, Some(late_replica)),
+            Err(PeekError::ReplicaAsOfBeyond(id)) if id == late_replica
+        ));
+        let uuid = peek(&mut active, 6, None).unwrap();
+        assert!(!respond(&mut active, uuid, late_replica));
+        assert!(respond(&mut active, uuid, replica));
+        let uuid = peek(&mut active, 8, Some(late_replica)).unwrap();
+        assert!(respond(&mut active, uuid, late_replica));
+
+        // The same goes for the subscribe, until it has advanced to the later `as_of`.
+        assert!(matches!(
+            active
+                .compute
+                .set_subscribe_target_replica(subscribe, late_replica),
+            Err(SubscribeTargetError::ReplicaAsOfBeyond(id)) if id == late_replica
+        ));
+        let respond =
+            |active: &mut ActiveInstance<Timestamp, NoStorage>, replica_id, lower, upper| {
+                let batch = SubscribeBatch {
+                    lower: as_of(lower),
+                    upper: as_of(upper),
+                    updates: Ok(Vec::new()),
+                };
+                let response =
+                    ComputeResponse::SubscribeResponse(subscribe, SubscribeResponse::Batch(batch));
+                active.handle_response(response, replica_id).is_some()
+            };
+        assert!(!respond(&mut active, late_replica, 8, 9));
+        assert!(respond(&mut active, replica, 5, 7));
+        assert!(!respond(&mut active, late_replica, 8, 10));
+        assert!(respond(&mut active, replica, 7, 8));
+        assert!(respond(&mut active, late_replica, 8, 10));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn subscribe_lag() {
+        let (mut instance, _response_rx, introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (index, subscribe) = (GlobalId::User(1), GlobalId::User(2));
+        let replica = ReplicaId::User(1);
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        add_test_replica(&mut active, replica);
+
+        // The subscribe reads from the index.
+        let mut dataflow = test_dataflow(&[], &[subscribe], 5);
+        dataflow.index_imports.insert(index, index_import());
+        for dataflow in [test_dataflow(&[index], &[], 5), dataflow] {
+            active
+                .create_dataflow(
+                    dataflow,
+                    DataflowActivation::Immediate,
+                    BTreeMap::new(),
+                    BTreeMap::new(),
+                )
+                .unwrap();
+        }
+
+        let advance =
+            |active: &mut ActiveInstance<Timestamp, NoStorage>, index_upper, lower, upper| {
+                let response = ComputeResponse::FrontierUpper {
+                    id: index,
+                    upper: as_of(index_upper),
+                };
+                active.handle_response(response, replica);
+                let batch = SubscribeBatch {
+                    lower: as_of(lower),
+                    upper: as_of(upper),
+                    updates: Ok(Vec::new()),
+                };
+                let response =
+                    ComputeResponse::SubscribeResponse(subscribe, SubscribeResponse::Batch(batch));
+                active.handle_response(response, replica);
+            };
+        let lag_updates = || {
+            let mut updates = Vec::new();
+            for (type_, mut batch) in introspection_rx.try_iter() {
+                if type_ == IntrospectionType::ComputeSubscribeLag {
+                    updates.append(&mut batch);
+                }
+            }
+            updates
+        };
+
+        // The lag is the distance of the subscribe's frontier to that of its input.
+        advance(&mut active, 20, 5, 12);
+        assert_eq!(lag_updates(), [(subscribe_lag_row(subscribe, 8), 1)]);
+
+        // A changed lag retracts the previously reported one.
+        advance(&mut active, 20, 12, 18);
+        assert_eq!(
+            lag_updates(),
+            [
+                (subscribe_lag_row(subscribe, 8), -1),
+                (subscribe_lag_row(subscribe, 2), 1),
+            ]
+        );
+
+        // An unchanged lag is not reported again.
+        advance(&mut active, 25, 18, 23);
+        assert!(lag_updates().is_empty());
+    }
+
+    #[mz_ore::test]
+    fn transitive_dependencies() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let (a, b, c, d) = (
+            GlobalId::User(1),
+            GlobalId::User(2),
+            GlobalId::User(3),
+            GlobalId::User(4),
+        );
+        let (s1, s2, s3) = (GlobalId::User(11), GlobalId::User(12), GlobalId::User(13));
+        let as_of = Antichain::from_elem(Timestamp::from(1));
+        // `d` is never installed, so `b`'s dependency on it is dangling.
+        instance.add_collection(b, CollectionState::new(as_of.clone(), vec![s2], vec![d]));
+        instance.add_collection(c, CollectionState::new(as_of.clone(), vec![s3], vec![b]));
+        instance.add_collection(a, CollectionState::new(as_of, vec![s1], vec![b, c]));
+
+        assert_eq!(
+            instance.transitive_compute_dependencies(a).unwrap(),
+            BTreeSet::from([b, c])
+        );
+        assert_eq!(
+            instance.transitive_compute_dependencies(b).unwrap(),
+            BTreeSet::new()
+        );
+        assert_eq!(
+            instance.transitive_storage_dependencies(a).unwrap(),
+            BTreeSet::from([s1, s2, s3])
+        );
+        assert_eq!(
+            instance.transitive_storage_dependencies(c).unwrap(),
+            BTreeSet::from([s2, s3])
+        );
+        assert_eq!(
+            instance.transitive_compute_dependencies(d).unwrap_err().0,
+            d
+        );
+    }
+
+    #[mz_ore::test]
+    fn dependency_cycle_detection() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let (a, b, c, d) = (
+            GlobalId::User(1),
+            GlobalId::User(2),
+            GlobalId::User(3),
+            GlobalId::User(4),
+        );
+        let as_of = Antichain::from_elem(Timestamp::from(1));
+        instance.add_collection(
+            a,
+            CollectionState::new(as_of.clone(), Vec::new(), vec![b, d]),
+        );
+        instance.add_collection(b, CollectionState::new(as_of.clone(), Vec::new(), vec![c]));
+        instance.add_collection(c, CollectionState::new(as_of, Vec::new(), Vec::new()));
+        assert_eq!(instance.find_dependency_cycle(), None);
+
+        // Close the loop, and make sure the transitive closure still terminates.
+        instance
+            .collection_mut(c)
+            .unwrap()
+            .compute_dependencies
+            .push(a);
+        assert_eq!(instance.find_dependency_cycle(), Some(vec![a, b, c]));
+        assert_eq!(
+            instance.transitive_compute_dependencies(b).unwrap(),
+            BTreeSet::from([a, b, c])
+        );
+    }
+
+    #[mz_ore::test]
+    fn activation_as_of_never_regresses() {
+        let as_of = Antichain::from_elem(Timestamp::from(5));
+        assert_eq!(activation_as_of(&as_of, []), as_of);
+        let sinces = [
+            Antichain::from_elem(1.into()),
+            Antichain::from_elem(3.into()),
+        ];
+        assert_eq!(activation_as_of(&as_of, sinces), as_of);
+
+        // Inputs that compacted while the dataflow was suspended advance the `as_of`.
+        let sinces = [
+            Antichain::from_elem(3.into()),
+            Antichain::from_elem(7.into()),
+        ];
+        assert_eq!(
+            activation_as_of(&as_of, sinces),
+            Antichain::from_elem(7.into())
+        );
+    }
+
+    #[mz_ore::test]
+    fn read_hold_tracking() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let (a, b) = (GlobalId::User(1), GlobalId::User(2));
+        let peek = Uuid::new_v4();
+        let as_of = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        instance.add_collection(a, CollectionState::new(as_of(1), Vec::new(), Vec::new()));
+        instance.add_collection(b, CollectionState::new(as_of(3), Vec::new(), vec![a]));
+
+        let collection = instance.collection_mut(a).unwrap();
+        collection.record_read_hold_changes(ReadHoldSource::Dependent(b), [(3.into(), 1)]);
+        collection.record_read_hold_changes(ReadHoldSource::Peek(peek), [(2.into(), 1)]);
+        assert_eq!(
+            instance.collection(a).unwrap().read_hold_counts(),
+            BTreeMap::from([
+                (1.into(), BTreeMap::from([(ReadHoldSource::Policy, 1)])),
+                (2.into(), BTreeMap::from([(ReadHoldSource::Peek(peek), 1)])),
+                (
+                    3.into(),
+                    BTreeMap::from([(ReadHoldSource::Dependent(b), 1)])
+                ),
+            ])
+        );
+        assert_eq!(instance.total_read_hold_count(a).unwrap(), 3);
+
+        // The peek isn't pending, so its read hold is leaked.
+        let leaked = |peek_count, dependent_count| {
+            let mut sources = BTreeMap::new();
+            if peek_count != 0 {
+                sources.insert(ReadHoldSource::Peek(peek), peek_count);
+            }
+            if dependent_count != 0 {
+                sources.insert(ReadHoldSource::Dependent(b), dependent_count);
+            }
+            BTreeMap::from([(a, sources)])
+        };
+        assert_eq!(instance.leaked_read_holds(), leaked(1, 0));
+
+        // Dropping the dependent without releasing its read hold leaks it as well.
+        instance.remove_collection(b);
+        assert_eq!(instance.leaked_read_holds(), leaked(1, 1));
+
+        // Released read holds are forgotten.
+        let collection = instance.collection_mut(a).unwrap();
+        collection.record_read_hold_changes(ReadHoldSource::Dependent(b), [(3.into(), -1)]);
+        collection.record_read_hold_changes(ReadHoldSource::Peek(peek), [(2.into(), -1)]);
+        assert_eq!(instance.leaked_read_holds(), BTreeMap::new());
+        assert_eq!(instance.total_read_hold_count(a).unwrap(), 1);
+    }
+
+    #[mz_ore::test]
+    fn write_frontier_subscription() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let id = GlobalId::User(1);
+        let frontier = |t: u64| Antichain::from_elem(Timestamp::from(t));
+        instance.add_collection(
+            id,
+            CollectionState::new(frontier(1), Vec::new(), Vec::new()),
+        );
+
+        let mut rx = instance.collection(id).unwrap().subscribe_write_frontier();
+        assert_eq!(*rx.borrow_and_update(), frontier(1));
+
+        let collection = instance.collection_mut(id).unwrap();
+        assert!(collection.advance_write_frontier(&frontier(3)));
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), frontier(3));
+
+        // Updates that don't advance the frontier don't notify subscribers.
+        let collection = instance.collection_mut(id).unwrap();
+        assert!(!collection.advance_write_frontier(&frontier(2)));
+        assert!(!collection.advance_write_frontier(&frontier(3)));
+        assert!(!rx.has_changed().unwrap());
+
+        // Subscribers learn when the collection goes away.
+        instance.remove_collection(id);
+        assert!(rx.has_changed().is_err());
+    }
+
+    #[mz_ore::test]
+    fn replica_backoff_schedule() {
+        let config = RestartBackoffConfig {
+            base: Duration::from_secs(1),
+            cap: Duration::from_secs(10),
+        };
+        let metrics = ComputeControllerMetrics::new(MetricsRegistry::new())
+            .for_instance(ComputeInstanceId::User(1))
+            .for_replica_backoff(ReplicaId::User(1));
+        let start = Instant::now();
+        let mut backoff = ReplicaBackoff::new(start, metrics);
+
+        // A replica that fails right after each rehydration is rehydrated with exponentially
+        // increasing delays, up to the cap.
+        let mut attempts = vec![start];
+        for _ in 0..6 {
+            backoff.record_failure(&config);
+            let next = backoff.next_attempt(&config);
+            backoff.record_attempt(next);
+            attempts.push(next);
+        }
+        let delays: Vec<_> = attempts.windows(2).map(|w| w[1] - w[0]).collect();
+        let expected: Vec<_> = [1, 2, 4, 8, 10, 10]
+            .into_iter()
+            .map(Duration::from_secs)
+            .collect();
+        assert_eq!(delays, expected);
+        assert_eq!(backoff.metrics.consecutive_failures.get(), 6);
+        assert_eq!(backoff.metrics.backoff_seconds.get(), 10.0);
+        assert_eq!(backoff.metrics.restarts_total.get(), 6);
+
+        // Responding for a short while doesn't reset the backoff.
+        let last = *attempts.last().unwrap();
+        backoff.record_healthy(&config, last + Duration::from_secs(1));
+        assert_eq!(backoff.consecutive_failures, 6);
+
+        // Responding for long enough does, so the next failure is handled immediately.
+        let healthy = last + config.cap;
+        backoff.record_healthy(&config, healthy);
+        assert_eq!(backoff.consecutive_failures, 0);
+        assert_eq!(backoff.metrics.consecutive_failures.get(), 0);
+        backoff.record_failure(&config);
+        assert!(backoff.next_attempt(&config) <= healthy);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn failed_replica_restart_backoff() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let base = Duration::from_millis(200);
+        instance.set_restart_backoff(RestartBackoffConfig {
+            base,
+            cap: Duration::from_secs(3600),
+        });
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let replica = ReplicaId::User(1);
+        let epoch =
+            |active: &ActiveInstance<Timestamp, NoStorage>| active.compute.replica_epochs[&replica];
+        add_test_replica(&mut active, replica);
+        assert_eq!(epoch(&active), 1);
+
+        // The first failure is handled right away.
+        active.compute.mark_replica_failed(replica);
+        let info = active.compute.replica_info(replica).unwrap();
+        assert_eq!(info.consecutive_failures, 1);
+        assert!(info.next_restart.unwrap() <= Instant::now());
+        active.rehydrate_failed_replicas();
+        assert_eq!(epoch(&active), 2);
+        let info = active.compute.replica_info(replica).unwrap();
+        assert_eq!(info.next_restart, None);
+
+        // Each further failure doubles the delay before the replica is restarted, during which
+        // the replica is left alone.
+        for (failures, factor) in [(2, 1), (3, 2)] {
+            active.compute.mark_replica_failed(replica);
+            let info = active.compute.replica_info(replica).unwrap();
+            assert_eq!(info.consecutive_failures, failures);
+            let next_restart = info.next_restart.unwrap();
+            let last_attempt = active.compute.replica_backoffs[&replica].last_attempt;
+            assert_eq!(next_restart - last_attempt, base * factor);
+
+            let restarts = epoch(&active);
+            active.rehydrate_failed_replicas();
+            assert_eq!(epoch(&active), restarts);
+            tokio::time::sleep_until(next_restart.into()).await;
+            active.rehydrate_failed_replicas();
+            assert_eq!(epoch(&active), restarts + 1);
+        }
+        let metrics = &active.compute.replica_backoffs[&replica].metrics;
+        assert_eq!(metrics.restarts_total.get(), 3);
+    }
+
+    /// Returns a batch of subscribe output for the interval `[lower, upper)`, with a single update
+    /// of a row of the given size.
+    fn subscribe_batch(lower: u64, upper: u64, row_bytes: usize) -> SubscribeBatch<Timestamp> {
+        let row = Row::pack_slice(&[Datum::String(&"x".repeat(row_bytes))]);
+        SubscribeBatch {
+            lower: Antichain::from_elem(lower.into()),
+            upper: Antichain::from_elem(upper.into()),
+            updates: Ok(vec![(lower.into(), row, 1)]),
+        }
+    }
+
+    /// Emits the given batch for the identified subscribe, returning the emitted response.
+    fn emit(
+        instance: &mut Instance<Timestamp>,
+        id: GlobalId,
+        batch: SubscribeBatch<Timestamp>,
+    ) -> SubscribeResponse<Timestamp> {
+        let subscribe = instance.subscribes[&id].clone();
+        match instance.emit_subscribe_batch(id, subscribe, batch) {
+            ComputeControllerResponse::SubscribeResponse(_, response) => response,
+            response => panic!("unexpected response: {response:?}"),
+        }
+    }
+
+    #[mz_ore::test]
+    fn slow_subscribe_terminated_by_buffered_bytes() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        instance.subscribe_limits.max_buffered_bytes = Some(1024);
+        let (draining, stuck) = (GlobalId::User(1), GlobalId::User(2));
+        instance.subscribes.insert(draining, ActiveSubscribe::new());
+        instance.subscribes.insert(stuck, ActiveSubscribe::new());
+
+        // A consumer that drains its output can receive any amount of it.
+        for t in 0..10 {
+            let response = emit(&mut instance, draining, subscribe_batch(t, t + 1, 500));
+            let bytes = u64::cast_from(response.updates_byte_len());
+            assert!(bytes > 0);
+            instance.ack_subscribe_output(draining, bytes);
+        }
+        assert_eq!(instance.subscribes[&draining].buffered_bytes, 0);
+
+        // A consumer that doesn't drain its output is terminated once its unacknowledged output
+        // exceeds the budget.
+        let response = emit(&mut instance, stuck, subscribe_batch(0, 1, 500));
+        assert!(matches!(
+            response,
+            SubscribeResponse::Batch(SubscribeBatch { updates: Ok(_), .. })
+        ));
+        let response = emit(&mut instance, stuck, subscribe_batch(1, 2, 600));
+        match response {
+            SubscribeResponse::Batch(batch) => {
+                assert_eq!(batch.updates, Err("subscriber too slow".into()));
+                assert_eq!(batch.lower, Antichain::from_elem(1.into()));
+                assert!(batch.upper.is_empty());
+            }
+            response => panic!("unexpected response: {response:?}"),
+        }
+        assert!(!instance.subscribes.contains_key(&stuck));
+        assert!(instance.subscribes.contains_key(&draining));
+        assert_eq!(instance.metrics.subscribes_terminated_too_slow.get(), 1);
+
+        // Late acknowledgements for the terminated subscribe are ignored.
+        instance.ack_subscribe_output(stuck, 500);
+    }
+
+    #[mz_ore::test]
+    fn slow_subscribe_terminated_by_lag() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        instance.subscribe_limits.max_lag = Some(Duration::from_secs(1));
+        let id = GlobalId::User(1);
+        instance.subscribes.insert(id, ActiveSubscribe::new());
+
+        instance.subscribes.get_mut(&id).unwrap().lag = Some(1000);
+        let response = emit(&mut instance, id, subscribe_batch(0, 1, 10));
+        assert!(matches!(
+            response,
+            SubscribeResponse::Batch(SubscribeBatch { updates: Ok(_), .. })
+        ));
+
+        instance.subscribes.get_mut(&id).unwrap().lag = Some(1001);
+        let response = emit(&mut instance, id, subscribe_batch(1, 2, 10));
+        assert!(matches!(
+            response,
+            SubscribeResponse::Batch(SubscribeBatch { updates: Err(e), .. }) if e == "subscriber too slow"
+        ));
+        assert!(!instance.subscribes.contains_key(&id));
+    }
+
+    /// Returns a batch of subscribe output for the interval `[lower, upper)`, containing an update
+    /// for each of the given times.
+    fn subscribe_batch_at(lower: u64, upper: u64, times: &[u64]) -> SubscribeBatch<Timestamp> {
+        let updates = times
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let row = Row::pack_slice(&[Datum::String(&format!("row {i}"))]);
+                (Timestamp::from(*t), row, 1)
+            })
+            .collect();
+        SubscribeBatch {
+            lower: Antichain::from_elem(lower.into()),
+            upper: Antichain::from_elem(upper.into()),
+            updates: Ok(updates),
+        }
+    }
+
+    #[mz_ore::test]
+    fn subscribe_batch_split() {
+        let limits = SubscribeBatchLimits {
+            max_rows: Some(2),
+            max_bytes: None,
+        };
+        let batch = subscribe_batch_at(1, 5, &[3, 1, 2, 1, 3, 4, 4, 4]);
+        let parts = limits.split(batch.clone());
+
+        // Concatenating the parts yields the original updates, (stably) sorted by time.
+        let mut expected = batch.updates.clone().unwrap();
+        expected.sort_by_key(|(time, _, _)| *time);
+        let concatenated: Vec<_> = parts
+            .iter()
+            .flat_map(|part| part.updates.clone().unwrap())
+            .collect();
+        assert_eq!(concatenated, expected);
+
+        // Updates at the same time are never split, even if they exceed the limits on their own.
+        let times: Vec<Vec<u64>> = parts
+            .iter()
+            .map(|part| {
+                let updates = part.updates.as_ref().unwrap();
+                updates
+                    .iter()
+                    .map(|(time, _, _)| u64::from(*time))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(times, [vec![1, 1], vec![2], vec![3, 3], vec![4, 4, 4]]);
+
+        // Only the last part advances the frontier.
+        let (last, init) = parts.split_last().unwrap();
+        for part in init {
+            assert_eq!(part.lower, batch.lower);
+            assert_eq!(part.upper, batch.lower);
+        }
+        assert_eq!(last.lower, batch.lower);
+        assert_eq!(last.upper, batch.upper);
+
+        // Batches within the limits, and errors, are left alone.
+        let small = subscribe_batch_at(1, 5, &[3, 1]);
+        assert_eq!(limits.split(small.clone()), [small.clone()]);
+        assert_eq!(
+            SubscribeBatchLimits::default().split(batch.clone()),
+            [batch.clone()]
+        );
+        let error = SubscribeBatch {
+            updates: Err("error".into()),
+            ..batch
+        };
+        assert_eq!(limits.split(error.clone()), [error]);
+
+        // The byte limit is applied the same way.
+        let row_bytes = u64::cast_from(small.updates_byte_len() / 2);
+        let limits = SubscribeBatchLimits {
+            max_rows: None,
+            max_bytes: Some(row_bytes),
+        };
+        assert_eq!(limits.split(small).len(), 2);
+    }
+
+    #[mz_ore::test]
+    fn subscribe_batch_split_emission() {
+        let (mut instance, response_rx, _introspection_rx) = test_instance();
+        instance.subscribe_batch_limits.max_rows = Some(1);
+        let id = GlobalId::User(1);
+        instance.subscribes.insert(id, ActiveSubscribe::new());
+
+        // A batch that doesn't need splitting is returned directly.
+        let first = subscribe_batch_at(0, 1, &[0]);
+        let subscribe = instance.subscribes[&id].clone();
+        let response = instance.emit_subscribe_batches(id, subscribe, first.clone());
+        assert!(response.is_some());
+        assert!(response_rx.is_empty());
+
+        // The parts of a split batch are delivered through the response channel, in order.
+        let batch = subscribe_batch_at(1, 4, &[1, 2, 3]);
+        let subscribe = instance.subscribes[&id].clone();
+        let response = instance.emit_subscribe_batches(id, subscribe, batch.clone());
+        assert!(response.is_none());
+        let parts: Vec<_> = response_rx
+            .try_iter()
+            .map(|response| match response {
+                ComputeControllerResponse::SubscribeResponse(_, SubscribeResponse::Batch(b)) => b,
+                response => panic!("unexpected response: {response:?}"),
+            })
+            .collect();
+        assert_eq!(parts, instance.subscribe_batch_limits.split(batch.clone()));
+        assert_eq!(
+            instance.subscribes[&id].buffered_bytes,
+            u64::cast_from(first.updates_byte_len() + batch.updates_byte_len())
+        );
+
+        // A subscribe terminated for being too slow doesn't emit the remaining parts.
+        instance.subscribe_limits.max_buffered_bytes =
+            Some(instance.subscribes[&id].buffered_bytes);
+        let batch = subscribe_batch_at(4, 7, &[4, 5, 6]);
+        let subscribe = instance.subscribes[&id].clone();
+        let response = instance.emit_subscribe_batches(id, subscribe, batch);
+        assert!(response.is_none());
+        let responses: Vec<_> = response_rx.try_iter().collect();
+        assert_eq!(responses.len(), 1);
+        assert!(matches!(
+            &responses[0],
+            ComputeControllerResponse::SubscribeResponse(
+                _,
+                SubscribeResponse::Batch(SubscribeBatch {
+                    updates: Err(_),
+                    ..
+                })
+            )
+        ));
+        assert!(!instance.subscribes.contains_key(&id));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `socket`
+    async fn untracked_collection_responses() {
+        let (mut instance, _response_rx, _introspection_rx) = test_instance();
+        let mut storage = NoStorage;
+        let mut active = instance.activate(&mut storage);
+        let (dropped, unknown) = (GlobalId::User(1), GlobalId::User(2));
+        let replica = ReplicaId::User(1);
+        add_test_replica(&mut active, replica);
+        active
+            .create_dataflow(
+                test_dataflow(&[dropped], &[], 5),
+                DataflowActivation::Immediate,
+                BTreeMap::new(),
+                BTreeMap::new(),
+            )
+            .unwrap();
+        let report = |active: &mut ActiveInstance<Timestamp, NoStorage>, id, upper| {
+            let response = ComputeResponse::FrontierUpper { id, upper };
+            active.handle_response(response, replica);
+        };
+
+        // The collection is forgotten once the replica has reported the empty frontier for it.
+        active.drop_collections(vec![dropped]).unwrap();
+        report(&mut active, dropped, Antichain::new());
+        assert!(active.compute.collection(dropped).is_err());
+
+        // Late reports for the dropped collection are only counted.
+        report(&mut active, dropped, Antichain::from_elem(7.into()));
+        let metrics = &active.compute.metrics;
+        assert_eq!(metrics.dropped_collection_responses_total.get(), 1);
+        let info = active.compute.replica_info(replica).unwrap();
+        assert!(info.quarantined_collections.is_empty());
+
+        // Reports for collections that were never tracked quarantine them on the replica.
+        let response = ComputeResponse::SubscribeResponse(
+            unknown,
+            SubscribeResponse::DroppedAt(Antichain::new()),
+        );
+        assert!(active.handle_response(response, replica).is_none());
+        report(&mut active, unknown, Antichain::new());
+        let metrics = &active.compute.metrics;
+        assert_eq!(metrics.dropped_collection_responses_total.get(), 1);
+        let info = active.compute.replica_info(replica).unwrap();
+        assert_eq!(info.quarantined_collections, BTreeSet::from([unknown]));
+    }
+
+    #[mz_ore::test]
+    fn untracked_collections_dropped_vs_unknown() {
+        let retention = Duration::from_secs(60);
+        let mut untracked = UntrackedCollections::new(retention);
+        let replica = ReplicaId::User(1);
+        let other_replica = ReplicaId::User(2);
+        let dropped = GlobalId::User(1);
+        let unknown = GlobalId::User(2);
+        let start = Instant::now();
+
+        untracked.record_drop(dropped, start);
+
+        // Responses for recently dropped collections are not quarantined.
+        let kind = untracked.classify(dropped, replica, start + Duration::from_secs(1));
+        assert_eq!(kind, UntrackedKind::RecentlyDropped);
+        assert!(untracked.quarantined(replica).is_empty());
+
+        // Responses for unknown collections are quarantined for the reporting replica only.
+        let kind = untracked.classify(unknown, replica, start + Duration::from_secs(1));
+        assert_eq!(kind, UntrackedKind::Unknown);
+        assert_eq!(untracked.quarantined(replica), &BTreeSet::from([unknown]));
+        assert!(untracked.quarantined(other_replica).is_empty());
+
+        // Once the retention has passed, the dropped collection is treated as unknown.
+        let late = start + retention + Duration::from_secs(1);
+        let kind = untracked.classify(dropped, replica, late);
+        assert_eq!(kind, UntrackedKind::Unknown);
+        assert_eq!(
+            untracked.quarantined(replica),
+            &BTreeSet::from([dropped, unknown])
+        );
+
+        // Removing the replica clears its quarantine.
+        untracked.remove_replica(replica);
+        assert!(untracked.quarantined(replica).is_empty());
     }
 }
@@ -24,12 +24,14 @@ use mz_ore::metrics::{
 use mz_ore::stats::histogram_seconds_buckets;
 use mz_repr::GlobalId;
 use mz_service::codec::StatsCollector;
+use mz_storage_client::controller::IntrospectionType;
 use prometheus::core::{AtomicF64, AtomicU64};
 
 use crate::protocol::command::{ComputeCommand, ProtoComputeCommand};
+use crate::protocol::history::ReduceStats;
 use crate::protocol::response::{PeekResponse, ProtoComputeResponse};
 
-type IntCounter = DeleteOnDropCounter<'static, AtomicU64, Vec<String>>;
+pub type IntCounter = DeleteOnDropCounter<'static, AtomicU64, Vec<String>>;
 type Gauge = DeleteOnDropGauge<'static, AtomicF64, Vec<String>>;
 pub type UIntGauge = DeleteOnDropGauge<'static, AtomicU64, Vec<String>>;
 type Histogram = DeleteOnDropHistogram<'static, Vec<String>>;
@@ -54,13 +56,29 @@ pub struct ComputeControllerMetrics {
     // command history
     history_command_count: UIntGaugeVec,
     history_dataflow_count: UIntGaugeVec,
+    history_reduce_commands: UIntGaugeVec,
+    history_reduce_bytes: UIntGaugeVec,
 
     // peeks
     peeks_total: IntCounterVec,
     peek_duration_seconds: HistogramVec,
 
+    // untracked collections
+    dropped_collection_responses_total: IntCounterVec,
+
+    // subscribes
+    subscribes_terminated_too_slow_total: IntCounterVec,
+
+    // replica restarts
+    replica_consecutive_failures: UIntGaugeVec,
+    replica_restart_backoff_seconds: GaugeVec,
+    replica_restarts_total: IntCounterVec,
+
     // dataflows
     dataflow_initial_output_duration_seconds: GaugeVec,
+
+    // introspection
+    introspection_shed_rows_total: IntCounterVec,
 }
 
 impl ComputeControllerMetrics {
@@ -126,6 +144,16 @@ impl ComputeControllerMetrics {
                 help: "The number of dataflows in the controller's command history.",
                 var_labels: ["instance_id"],
             )),
+            history_reduce_commands: metrics_registry.register(metric!(
+                name: "mz_compute_controller_history_reduce_commands",
+                help: "The number of commands in the controller's command history before and after its last reduction on replica rehydration.",
+                var_labels: ["instance_id", "stage"],
+            )),
+            history_reduce_bytes: metrics_registry.register(metric!(
+                name: "mz_compute_controller_history_reduce_bytes",
+                help: "An estimate of the size of the controller's command history before and after its last reduction on replica rehydration.",
+                var_labels: ["instance_id", "stage"],
+            )),
             peeks_total: metrics_registry.register(metric!(
                 name: "mz_compute_peeks_total",
                 help: "The total number of peeks served.",
@@ -137,14 +165,51 @@ impl ComputeControllerMetrics {
                 var_labels: ["instance_id", "result"],
                 buckets: histogram_seconds_buckets(0.000_500, 32.),
             )),
+            dropped_collection_responses_total: metrics_registry.register(metric!(
+                name: "mz_compute_controller_dropped_collection_responses_total",
+                help: "The number of replica responses received for recently dropped collections.",
+                var_labels: ["instance_id"],
+            )),
+            subscribes_terminated_too_slow_total: metrics_registry.register(metric!(
+                name: "mz_compute_controller_subscribes_terminated_too_slow_total",
+                help: "The number of subscribes terminated because their consumer was too slow.",
+                var_labels: ["instance_id"],
+            )),
+            replica_consecutive_failures: metrics_registry.register(metric!(
+                name: "mz_compute_controller_replica_consecutive_failures",
+                help: "The number of consecutive failures of a replica.",
+                var_labels: ["instance_id", "replica_id"],
+            )),
+            replica_restart_backoff_seconds: metrics_registry.register(metric!(
+                name: "mz_compute_controller_replica_restart_backoff_seconds",
+                help: "The delay applied before the next restart of a failed replica.",
+                var_labels: ["instance_id", "replica_id"],
+            )),
+            replica_restarts_total: metrics_registry.register(metric!(
+                name: "mz_compute_controller_replica_restarts_total",
+                help: "The total number of restarts of failed replicas.",
+                var_labels: ["instance_id", "replica_id"],
+            )),
             dataflow_initial_output_duration_seconds: metrics_registry.register(metric!(
                 name: "mz_dataflow_initial_output_duration_seconds",
                 help: "The time from dataflow creation up to when the first output was produced.",
                 var_labels: ["instance_id", "replica_id", "collection_id"],
             )),
+            introspection_shed_rows_total: metrics_registry.register(metric!(
+                name: "mz_compute_controller_introspection_shed_rows_total",
+                help: "The number of introspection updates dropped by the controller while overloaded.",
+                var_labels: ["introspection_type"],
+            )),
         }
     }
 
+    /// Returns the counter of shed introspection updates of the given type.
+    pub fn introspection_shed_rows_total(&self, type_: IntrospectionType) -> IntCounter {
+        let labels = vec![format!("{type_:?}")];
+        self.introspection_shed_rows_total
+            .get_delete_on_drop_counter(labels)
+    }
+
     pub fn for_instance(&self, instance_id: ComputeInstanceId) -> InstanceMetrics {
         let labels = vec![instance_id.to_string()];
         let replica_count = self.replica_count.get_delete_on_drop_gauge(labels.clone());
@@ -162,6 +227,22 @@ impl ComputeControllerMetrics {
         let history_dataflow_count = self
             .history_dataflow_count
             .get_delete_on_drop_gauge(labels.clone());
+        let stage_labels =
+            |stage: &str| -> Vec<String> { labels.iter().cloned().chain([stage.into()]).collect() };
+        let history_reduce = HistoryReduceMetrics {
+            commands_before: self
+                .history_reduce_commands
+                .get_delete_on_drop_gauge(stage_labels("before")),
+            commands_after: self
+                .history_reduce_commands
+                .get_delete_on_drop_gauge(stage_labels("after")),
+            bytes_before: self
+                .history_reduce_bytes
+                .get_delete_on_drop_gauge(stage_labels("before")),
+            bytes_after: self
+                .history_reduce_bytes
+                .get_delete_on_drop_gauge(stage_labels("after")),
+        };
         let peeks_total = PeekMetrics::build(|typ| {
             let labels = labels.iter().cloned().chain([typ.into()]).collect();
             self.peeks_total.get_delete_on_drop_counter(labels)
@@ -171,6 +252,12 @@ impl ComputeControllerMetrics {
             self.peek_duration_seconds
                 .get_delete_on_drop_histogram(labels)
         });
+        let dropped_collection_responses_total = self
+            .dropped_collection_responses_total
+            .get_delete_on_drop_counter(labels.clone());
+        let subscribes_terminated_too_slow = self
+            .subscribes_terminated_too_slow_total
+            .get_delete_on_drop_counter(labels);
 
         InstanceMetrics {
             instance_id,
@@ -181,8 +268,11 @@ impl ComputeControllerMetrics {
             subscribe_count,
             history_command_count,
             history_dataflow_count,
+            history_reduce,
             peeks_total,
             peek_duration_seconds,
+            dropped_collection_responses_total,
+            subscribes_terminated_too_slow,
         }
     }
 }
@@ -199,8 +289,11 @@ pub struct InstanceMetrics {
     pub subscribe_count: UIntGauge,
     pub history_command_count: CommandMetrics<UIntGauge>,
     pub history_dataflow_count: UIntGauge,
+    pub history_reduce: HistoryReduceMetrics,
     pub peeks_total: PeekMetrics<IntCounter>,
     pub peek_duration_seconds: PeekMetrics<Histogram>,
+    pub dropped_collection_responses_total: IntCounter,
+    pub subscribes_terminated_too_slow: IntCounter,
 }
 
 impl InstanceMetrics {
@@ -263,6 +356,28 @@ impl InstanceMetrics {
         }
     }
 
+    pub fn for_replica_backoff(&self, replica_id: ReplicaId) -> ReplicaBackoffMetrics {
+        let labels = vec![self.instance_id.to_string(), replica_id.to_string()];
+        let consecutive_failures = self
+            .metrics
+            .replica_consecutive_failures
+            .get_delete_on_drop_gauge(labels.clone());
+        let backoff_seconds = self
+            .metrics
+            .replica_restart_backoff_seconds
+            .get_delete_on_drop_gauge(labels.clone());
+        let restarts_total = self
+            .metrics
+            .replica_restarts_total
+            .get_delete_on_drop_counter(labels);
+
+        ReplicaBackoffMetrics {
+            consecutive_failures,
+            backoff_seconds,
+            restarts_total,
+        }
+    }
+
     pub fn for_history(&self) -> HistoryMetrics<UIntGauge> {
         let labels = vec![self.instance_id.to_string()];
         let command_counts = CommandMetrics::build(|typ| {
@@ -291,6 +406,16 @@ impl InstanceMetrics {
     }
 }
 
+/// Per-replica metrics about restarts of failed replicas.
+///
+/// Unlike [`ReplicaMetrics`], these outlive individual incarnations of a replica.
+#[derive(Debug)]
+pub struct ReplicaBackoffMetrics {
+    pub consecutive_failures: UIntGauge,
+    pub backoff_seconds: Gauge,
+    pub restarts_total: IntCounter,
+}
+
 /// Per-replica metrics.
 #[derive(Debug, Clone)]
 pub struct ReplicaMetrics {
@@ -492,6 +617,27 @@ where
     }
 }
 
+/// Metrics reflecting the last reduction of a controller's command history.
+#[derive(Debug)]
+pub struct HistoryReduceMetrics {
+    pub commands_before: UIntGauge,
+    pub commands_after: UIntGauge,
+    pub bytes_before: UIntGauge,
+    pub bytes_after: UIntGauge,
+}
+
+impl HistoryReduceMetrics {
+    /// Reflect the given reduction statistics in the metrics.
+    pub fn observe(&self, stats: &ReduceStats) {
+        self.commands_before
+            .set(u64::cast_from(stats.commands_before));
+        self.commands_after
+            .set(u64::cast_from(stats.commands_after));
+        self.bytes_before.set(u64::cast_from(stats.bytes_before));
+        self.bytes_after.set(u64::cast_from(stats.bytes_after));
+    }
+}
+
 /// Metrics for finished peeks, keyed by peek result.
 #[derive(Debug)]
 pub struct PeekMetrics<M> {
@@ -9,6 +9,8 @@
 
 //! Compute protocol commands.
 
+use std::time::Duration;
+
 use mz_cluster_client::client::{ClusterStartupEpoch, TimelyConfig, TryIntoTimelyConfig};
 use mz_compute_types::dataflows::{DataflowDescription, YieldSpec};
 use mz_expr::RowSetFinishing;
@@ -374,6 +376,56 @@ pub struct ComputeParameters {
     pub enable_specialized_arrangements: Option<bool>,
     /// Enable lgalloc for columnation.
     pub enable_columnation_lgalloc: Option<bool>,
+    /// The initial delay before the controller restarts a failed replica.
+    ///
+    /// The delay doubles with each consecutive failure of the replica, up to
+    /// `replica_restart_backoff_cap`. Only used by the controller.
+    pub replica_restart_backoff_base: Option<Duration>,
+    /// The maximum delay before the controller restarts a failed replica.
+    ///
+    /// Only used by the controller.
+    pub replica_restart_backoff_cap: Option<Duration>,
+    /// The maximum number of bytes of subscribe output the controller may have emitted without
+    /// the consumer acknowledging them, before it terminates the subscribe.
+    ///
+    /// NB: This value is optional, so the outer option indicates if this update includes an
+    /// override and the inner option is part of the config value. Only used by the controller.
+    pub subscribe_max_buffered_bytes: Option<Option<u64>>,
+    /// The maximum duration a subscribe's frontier may lag behind the frontier of its inputs,
+    /// before the controller terminates the subscribe.
+    ///
+    /// NB: This value is optional, so the outer option indicates if this update includes an
+    /// override and the inner option is part of the config value. Only used by the controller.
+    pub subscribe_max_lag: Option<Option<Duration>>,
+    /// The maximum number of updates in a subscribe batch emitted by the controller. Larger
+    /// batches received from replicas are split into several batches.
+    ///
+    /// NB: This value is optional, so the outer option indicates if this update includes an
+    /// override and the inner option is part of the config value. Only used by the controller.
+    pub subscribe_max_batch_rows: Option<Option<u64>>,
+    /// The maximum size in bytes of the updates in a subscribe batch emitted by the controller.
+    /// Larger batches received from replicas are split into several batches.
+    ///
+    /// NB: This value is optional, so the outer option indicates if this update includes an
+    /// override and the inner option is part of the config value. Only used by the controller.
+    pub subscribe_max_batch_bytes: Option<Option<u64>>,
+    /// The number of pending introspection updates above which the controller starts shedding
+    /// updates of low-priority introspection types. High-priority types are never shed.
+    ///
+    /// NB: This value is optional, so the outer option indicates if this update includes an
+    /// override and the inner option is part of the config value. Only used by the controller.
+    pub introspection_shedding_backlog: Option<Option<u64>>,
+    /// Whether the controller drops shed low-priority introspection updates, rather than
+    /// deferring and coalescing them until the backlog has cleared.
+    ///
+    /// Only used by the controller.
+    pub introspection_shedding_drop: Option<bool>,
+    /// The number of commands the controller's command history may grow by before it is
+    /// reduced, in addition to reducing it every time it doubles.
+    ///
+    /// NB: This value is optional, so the outer option indicates if this update includes an
+    /// override and the inner option is part of the config value. Only used by the controller.
+    pub history_reduce_threshold: Option<Option<u64>>,
     /// Persist client configuration.
     pub persist: PersistParameters,
     /// Tracing configuration.
@@ -393,6 +445,15 @@ impl ComputeParameters {
             enable_jemalloc_profiling,
             enable_specialized_arrangements,
             enable_columnation_lgalloc,
+            replica_restart_backoff_base,
+            replica_restart_backoff_cap,
+            subscribe_max_buffered_bytes,
+            subscribe_max_lag,
+            subscribe_max_batch_rows,
+            subscribe_max_batch_bytes,
+            introspection_shedding_backlog,
+            introspection_shedding_drop,
+            history_reduce_threshold,
             persist,
             tracing,
             grpc_client,
@@ -422,6 +483,42 @@ impl ComputeParameters {
             self.enable_columnation_lgalloc = enable_columnation_lgalloc;
         }
 
+        if replica_restart_backoff_base.is_some() {
+            self.replica_restart_backoff_base = replica_restart_backoff_base;
+        }
+
+        if replica_restart_backoff_cap.is_some() {
+            self.replica_restart_backoff_cap = replica_restart_backoff_cap;
+        }
+
+        if subscribe_max_buffered_bytes.is_some() {
+            self.subscribe_max_buffered_bytes = subscribe_max_buffered_bytes;
+        }
+
+        if subscribe_max_lag.is_some() {
+            self.subscribe_max_lag = subscribe_max_lag;
+        }
+
+        if subscribe_max_batch_rows.is_some() {
+            self.subscribe_max_batch_rows = subscribe_max_batch_rows;
+        }
+
+        if subscribe_max_batch_bytes.is_some() {
+            self.subscribe_max_batch_bytes = subscribe_max_batch_bytes;
+        }
+
+        if introspection_shedding_backlog.is_some() {
+            self.introspection_shedding_backlog = introspection_shedding_backlog;
+        }
+
+        if introspection_shedding_drop.is_some() {
+            self.introspection_shedding_drop = introspection_shedding_drop;
+        }
+
+        if history_reduce_threshold.is_some() {
+            self.history_reduce_threshold = history_reduce_threshold;
+        }
+
         self.persist.update(persist);
         self.tracing.update(tracing);
         self.grpc_client.update(grpc_client);
@@ -447,6 +544,37 @@ impl RustType<ProtoComputeParameters> for ComputeParameters {
             enable_jemalloc_profiling: self.enable_jemalloc_profiling.into_proto(),
             enable_specialized_arrangements: self.enable_specialized_arrangements.into_proto(),
             enable_columnation_lgalloc: self.enable_columnation_lgalloc.into_proto(),
+            replica_restart_backoff_base: self.replica_restart_backoff_base.into_proto(),
+            replica_restart_backoff_cap: self.replica_restart_backoff_cap.into_proto(),
+            subscribe_max_buffered_bytes: self.subscribe_max_buffered_bytes.map(|x| {
+                ProtoSubscribeMaxBufferedBytesConfig {
+                    subscribe_max_buffered_bytes: x.into_proto(),
+                }
+            }),
+            subscribe_max_lag: self.subscribe_max_lag.map(|x| ProtoSubscribeMaxLagConfig {
+                subscribe_max_lag: x.into_proto(),
+            }),
+            subscribe_max_batch_rows: self.subscribe_max_batch_rows.map(|x| {
+                ProtoSubscribeMaxBatchRowsConfig {
+                    subscribe_max_batch_rows: x.into_proto(),
+                }
+            }),
+            subscribe_max_batch_bytes: self.subscribe_max_batch_bytes.map(|x| {
+                ProtoSubscribeMaxBatchBytesConfig {
+                    subscribe_max_batch_bytes: x.into_proto(),
+                }
+            }),
+            introspection_shedding_backlog: self.introspection_shedding_backlog.map(|x| {
+                ProtoIntrospectionSheddingBacklogConfig {
+                    introspection_shedding_backlog: x.into_proto(),
+                }
+            }),
+            introspection_shedding_drop: self.introspection_shedding_drop.into_proto(),
+            history_reduce_threshold: self.history_reduce_threshold.map(|x| {
+                ProtoHistoryReduceThresholdConfig {
+                    history_reduce_threshold: x.into_proto(),
+                }
+            }),
             persist: Some(self.persist.into_proto()),
             tracing: Some(self.tracing.into_proto()),
             grpc_client: Some(self.grpc_client.into_proto()),
@@ -465,6 +593,33 @@ impl RustType<ProtoComputeParameters> for ComputeParameters {
             enable_jemalloc_profiling: proto.enable_jemalloc_profiling.into_rust()?,
             enable_specialized_arrangements: proto.enable_specialized_arrangements.into_rust()?,
             enable_columnation_lgalloc: proto.enable_columnation_lgalloc.into_rust()?,
+            replica_restart_backoff_base: proto.replica_restart_backoff_base.into_rust()?,
+            replica_restart_backoff_cap: proto.replica_restart_backoff_cap.into_rust()?,
+            subscribe_max_buffered_bytes: proto
+                .subscribe_max_buffered_bytes
+                .map(|x| x.subscribe_max_buffered_bytes.into_rust())
+                .transpose()?,
+            subscribe_max_lag: proto
+                .subscribe_max_lag
+                .map(|x| x.subscribe_max_lag.into_rust())
+                .transpose()?,
+            subscribe_max_batch_rows: proto
+                .subscribe_max_batch_rows
+                .map(|x| x.subscribe_max_batch_rows.into_rust())
+                .transpose()?,
+            subscribe_max_batch_bytes: proto
+                .subscribe_max_batch_bytes
+                .map(|x| x.subscribe_max_batch_bytes.into_rust())
+                .transpose()?,
+            introspection_shedding_backlog: proto
+                .introspection_shedding_backlog
+                .map(|x| x.introspection_shedding_backlog.into_rust())
+                .transpose()?,
+            introspection_shedding_drop: proto.introspection_shedding_drop.into_rust()?,
+            history_reduce_threshold: proto
+                .history_reduce_threshold
+                .map(|x| x.history_reduce_threshold.into_rust())
+                .transpose()?,
             persist: proto
                 .persist
                 .into_rust_if_some("ProtoComputeParameters::persist")?,
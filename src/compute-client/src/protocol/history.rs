@@ -11,8 +11,11 @@
 
 use std::borrow::Borrow;
 use std::collections::BTreeMap;
+use std::mem;
 
-use mz_ore::cast::CastFrom;
+use mz_compute_types::dataflows::BuildDesc;
+use mz_compute_types::plan::Plan;
+use mz_ore::cast::{CastFrom, CastLossy};
 use mz_ore::metrics::UIntGauge;
 use timely::progress::Antichain;
 
@@ -23,6 +26,9 @@ use crate::protocol::command::{ComputeCommand, ComputeParameters, Peek};
 pub struct ComputeCommandHistory<M, T = mz_repr::Timestamp> {
     /// The number of commands at the last time we compacted the history.
     reduced_count: usize,
+    /// The number of commands the history may grow by before it is reduced, in addition to
+    /// reducing it every time it doubles.
+    reduce_threshold: Option<usize>,
     /// The sequence of commands that should be applied.
     ///
     /// This list may not be "compact" in that there can be commands that could be optimized
@@ -33,6 +39,31 @@ pub struct ComputeCommandHistory<M, T = mz_repr::Timestamp> {
     metrics: HistoryMetrics<M>,
 }
 
+/// Statistics about a single reduction of a [`ComputeCommandHistory`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReduceStats {
+    /// The number of commands in the history before the reduction.
+    pub commands_before: usize,
+    /// The number of commands in the history after the reduction.
+    pub commands_after: usize,
+    /// A rough estimate of the in-memory size of the history before the reduction, in bytes.
+    pub bytes_before: usize,
+    /// A rough estimate of the in-memory size of the history after the reduction, in bytes.
+    pub bytes_after: usize,
+}
+
+impl ReduceStats {
+    /// The fraction of commands that survived the reduction.
+    ///
+    /// Returns `1.0` for an empty history.
+    pub fn command_ratio(&self) -> f64 {
+        if self.commands_before == 0 {
+            return 1.0;
+        }
+        f64::cast_lossy(self.commands_after) / f64::cast_lossy(self.commands_before)
+    }
+}
+
 impl<M, T> ComputeCommandHistory<M, T>
 where
     M: Borrow<UIntGauge>,
@@ -43,6 +74,7 @@ where
 
         Self {
             reduced_count: 0,
+            reduce_threshold: None,
             commands: Vec::new(),
             metrics,
         }
@@ -50,11 +82,15 @@ where
 
     /// Add a command to the history.
     ///
-    /// This action will reduce the history every time it doubles.
+    /// This action will reduce the history every time it doubles, and every time it grows by
+    /// more than the configured reduce threshold.
     pub fn push(&mut self, command: ComputeCommand<T>) {
         self.commands.push(command);
 
-        if self.commands.len() > 2 * self.reduced_count {
+        let threshold_exceeded = self.reduce_threshold.map_or(false, |threshold| {
+            self.commands.len() > self.reduced_count.saturating_add(threshold)
+        });
+        if self.commands.len() > 2 * self.reduced_count || threshold_exceeded {
             self.reduce();
         } else {
             // Refresh reported metrics. `reduce` already refreshes metrics, so we only need to do
@@ -77,7 +113,12 @@ where
     /// to only reference inputs from times that are still certain to be valid. Commands that allow
     /// compaction of a collection also remove certainty that the inputs will be available for times
     /// not greater or equal to that compaction frontier.
-    pub fn reduce(&mut self) {
+    ///
+    /// Returns statistics about how much the history shrank.
+    pub fn reduce(&mut self) -> ReduceStats {
+        let commands_before = self.commands.len();
+        let bytes_before = self.estimated_bytes();
+
         // First determine what the final compacted frontiers will be for each collection.
         // These will determine for each collection whether the command that creates it is required,
         // and if required what `as_of` frontier should be used for its updated command.
@@ -229,6 +270,41 @@ where
         }
 
         self.reduced_count = self.commands.len();
+
+        ReduceStats {
+            commands_before,
+            commands_after: self.commands.len(),
+            bytes_before,
+            bytes_after: self.estimated_bytes(),
+        }
+    }
+
+    /// Returns a rough estimate of the in-memory size of the contained commands, in bytes.
+    ///
+    /// Only the dominant parts of the commands are taken into account: the commands themselves
+    /// and, for dataflows, the objects they build and import or export.
+    fn estimated_bytes(&self) -> usize {
+        let mut bytes = mem::size_of_val(self.commands.as_slice());
+        for command in &self.commands {
+            if let ComputeCommand::CreateDataflow(dataflow) = command {
+                bytes += dataflow.objects_to_build.len() * mem::size_of::<BuildDesc<Plan<T>>>();
+                let ids = dataflow.source_imports.len()
+                    + dataflow.index_imports.len()
+                    + dataflow.index_exports.len()
+                    + dataflow.sink_exports.len();
+                bytes += ids * mem::size_of::<mz_repr::GlobalId>();
+                bytes += dataflow.debug_name.len();
+            }
+        }
+        bytes
+    }
+
+    /// Set the number of commands the history may grow by before it is reduced.
+    ///
+    /// `None` disables threshold-based reduction, leaving only the reduction every time the
+    /// history doubles.
+    pub fn set_reduce_threshold(&mut self, threshold: Option<usize>) {
+        self.reduce_threshold = threshold;
     }
 
     /// Discard all peek commands.
@@ -252,3 +328,89 @@ where
         self.commands.iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mz_compute_types::ComputeInstanceId;
+    use mz_ore::metrics::MetricsRegistry;
+    use mz_repr::GlobalId;
+
+    use crate::metrics::ComputeControllerMetrics;
+    use crate::protocol::command::ComputeParameters;
+
+    use super::*;
+
+    #[mz_ore::test]
+    fn reduce_stats() {
+        let metrics = ComputeControllerMetrics::new(MetricsRegistry::new())
+            .for_instance(ComputeInstanceId::User(1))
+            .for_history();
+        let mut history = ComputeCommandHistory::<_, mz_repr::Timestamp>::new(metrics);
+
+        // Pushing commands reduces the history whenever it doubles, so reduce once upfront to
+        // observe only the commands pushed below.
+        assert_eq!(history.reduce(), ReduceStats::default());
+        assert_eq!(history.reduce().command_ratio(), 1.0);
+
+        history.push(ComputeCommand::InitializationComplete);
+        for _ in 0..3 {
+            history.push(ComputeCommand::UpdateConfiguration(ComputeParameters {
+                max_result_size: Some(1),
+                ..Default::default()
+            }));
+        }
+        history.push(ComputeCommand::AllowCompaction {
+            id: GlobalId::User(1),
+            frontier: Antichain::new(),
+        });
+
+        let before = history.iter().count();
+        let stats = history.reduce();
+        assert_eq!(stats.commands_before, before);
+        assert_eq!(stats.commands_after, history.iter().count());
+        assert!(stats.commands_after < stats.commands_before);
+        assert!(stats.bytes_after < stats.bytes_before);
+        assert!(stats.command_ratio() < 1.0);
+
+        // Reducing a reduced history is a no-op.
+        let stats = history.reduce();
+        assert_eq!(stats.commands_before, stats.commands_after);
+        assert_eq!(stats.bytes_before, stats.bytes_after);
+    }
+
+    #[mz_ore::test]
+    fn reduce_threshold() {
+        let metrics = ComputeControllerMetrics::new(MetricsRegistry::new())
+            .for_instance(ComputeInstanceId::User(1))
+            .for_history();
+        let mut history = ComputeCommandHistory::<_, mz_repr::Timestamp>::new(metrics);
+        let update_configuration = || {
+            ComputeCommand::UpdateConfiguration(ComputeParameters {
+                max_result_size: Some(1),
+                ..Default::default()
+            })
+        };
+
+        history.push(ComputeCommand::InitializationComplete);
+        for _ in 0..10 {
+            history.push(update_configuration());
+        }
+        // Without a threshold, the history is only reduced when it doubles, so it still contains
+        // redundant configuration updates.
+        assert_eq!(history.iter().count(), 4);
+
+        // With a threshold, the history is reduced as soon as it grows by more than the
+        // threshold, well before it doubles.
+        history.set_reduce_threshold(Some(0));
+        for _ in 0..10 {
+            history.push(update_configuration());
+            assert_eq!(history.iter().count(), 2);
+        }
+
+        // Removing the threshold restores the reduction on doubling only.
+        history.set_reduce_threshold(None);
+        history.push(update_configuration());
+        history.push(update_configuration());
+        assert_eq!(history.iter().count(), 4);
+    }
+}
@@ -306,6 +306,14 @@ impl<T> SubscribeResponse<T> {
             batch.to_error_if_exceeds(max_result_size);
         }
     }
+
+    /// The total size in bytes of the updates carried by `self`.
+    pub fn updates_byte_len(&self) -> usize {
+        match self {
+            SubscribeResponse::Batch(batch) => batch.updates_byte_len(),
+            SubscribeResponse::DroppedAt(_) => 0,
+        }
+    }
 }
 
 impl RustType<ProtoSubscribeResponse> for SubscribeResponse<mz_repr::Timestamp> {
@@ -363,14 +371,22 @@ pub struct SubscribeBatch<T> {
 }
 
 impl<T> SubscribeBatch<T> {
+    /// The total size in bytes of the updates in `self`, or zero if `self` carries an error.
+    pub fn updates_byte_len(&self) -> usize {
+        match &self.updates {
+            Ok(updates) => updates
+                .iter()
+                .map(|(_time, row, _diff)| row.byte_len())
+                .sum(),
+            Err(_) => 0,
+        }
+    }
+
     /// Converts `self` to an error if a maximum size is exceeded.
     fn to_error_if_exceeds(&mut self, max_result_size: usize) {
         use bytesize::ByteSize;
-        if let Ok(updates) = &self.updates {
-            let total_size: usize = updates
-                .iter()
-                .map(|(_time, row, _diff)| row.byte_len())
-                .sum();
+        if self.updates.is_ok() {
+            let total_size = self.updates_byte_len();
             if total_size > max_result_size {
                 use mz_ore::cast::CastFrom;
                 self.updates = Err(format!(
@@ -217,6 +217,15 @@ impl<'a, A: Allocate + 'static> ActiveComputeState<'a, A> {
             enable_jemalloc_profiling,
             enable_specialized_arrangements,
             enable_columnation_lgalloc,
+            replica_restart_backoff_base: _,
+            replica_restart_backoff_cap: _,
+            subscribe_max_buffered_bytes: _,
+            subscribe_max_lag: _,
+            subscribe_max_batch_rows: _,
+            subscribe_max_batch_bytes: _,
+            introspection_shedding_backlog: _,
+            introspection_shedding_drop: _,
+            history_reduce_threshold: _,
             persist,
             tracing,
             grpc_client: _grpc_client,
@@ -308,7 +308,7 @@ pub struct CreateReplicaConfig {
 
 impl<T> Controller<T>
 where
-    T: Timestamp + Lattice,
+    T: Timestamp + Lattice + Into<mz_repr::Timestamp>,
     ComputeGrpcClient: ComputeClient<T>,
 {
     /// Creates a cluster with the specified identifier and configuration.
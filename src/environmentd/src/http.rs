@@ -904,6 +904,10 @@ fn base_router(BaseRouterConfig { profiling }: BaseRouterConfig) -> Router {
             routing::get(move || async move { root::handle_home(profiling).await }),
         )
         .route("/api/sql", routing::post(sql::handle_sql))
+        .route(
+            "/api/webhook/:database/:schema/:id/explain",
+            routing::post(webhook::handle_webhook_explain),
+        )
         .route("/memory", routing::get(memory::handle_memory))
         .route(
             "/hierarchical-memory",
@@ -39,9 +39,8 @@ use mz_sql::ast::{Raw, Statement, StatementKind};
 use mz_sql::parse::StatementParseResult;
 use mz_sql::plan::Plan;
 use serde::{Deserialize, Serialize};
-use tokio::{select, time};
+use tokio::time;
 use tokio_postgres::error::SqlState;
-use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::debug;
 use tungstenite::protocol::frame::coding::CloseCode;
 
@@ -201,23 +200,9 @@ async fn run_ws(state: &WsState, user: Option<AuthedUser>, mut ws: WebSocket) {
     }
 
     loop {
-        // Handle timeouts first so we don't execute any statements when there's a pending timeout.
-        let msg = select! {
-            biased;
-
-            // `recv_timeout()` is cancel-safe as per it's docs.
-            Some(timeout) = client.client.recv_timeout() => {
-                client.client.terminate().await;
-                // We must wait for the client to send a request before we can send the error
-                // response. Although this isn't the PG wire protocol, we choose to mirror it by
-                // only sending errors as responses to requests.
-                let _ = ws.recv().await;
-                let err = Error::from(AdapterError::from(timeout));
-                let _ = send_ws_response(&mut ws, WebSocketResponse::Error(err.into())).await;
-                return;
-            },
-            message = ws.recv() => message,
-        };
+        // The session is idle until the client sends its next request.
+        client.client.add_idle_in_transaction_session_timeout();
+        let msg = ws.recv().await;
 
         client.client.remove_idle_in_transaction_session_timeout();
         client.client.reset_canceled();
@@ -1273,7 +1258,7 @@ async fn execute_stmt<S: ResultSender>(
             tag: "SUBSCRIBE".into(),
             desc: desc.relation_desc.unwrap(),
             rx: RecordFirstRowStream::new(
-                Box::new(UnboundedReceiverStream::new(rx)),
+                Box::new(rx),
                 execute_started,
                 client,
             ),
@@ -10,23 +10,30 @@
 //! Helpers for handling events from a Webhook source.
 
 use std::collections::BTreeMap;
+use std::iter;
 use std::sync::Arc;
 
-use mz_adapter::{AppendWebhookError, AppendWebhookResponse, WebhookAppenderCache};
+use mz_adapter::{
+    AppendWebhookError, AppendWebhookResponse, WebhookAppenderCache, WebhookValidationExplanation,
+};
 use mz_ore::retry::{Retry, RetryResult};
 use mz_ore::str::StrExt;
 use mz_repr::adt::jsonb::JsonbPacker;
 use mz_repr::{ColumnType, Datum, Row, ScalarType};
+use mz_sql::catalog::{CatalogItem, SessionCatalog};
+use mz_sql::names::PartialItemName;
 use mz_sql::plan::{WebhookHeaderFilters, WebhookHeaders};
+use mz_sql::rbac;
 use mz_storage_types::controller::StorageError;
 
 use axum::extract::{Path, State};
 use axum::response::IntoResponse;
+use axum::Json;
 use bytes::Bytes;
 use http::StatusCode;
 use thiserror::Error;
 
-use crate::http::WebhookState;
+use crate::http::{AuthedClient, WebhookState};
 
 pub async fn handle_webhook(
     State(WebhookState {
@@ -37,19 +44,7 @@ pub async fn handle_webhook(
     headers: http::HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
-    // Collect headers into a map, while converting them into strings.
-    let mut headers_s = BTreeMap::new();
-    for (name, val) in headers.iter() {
-        if let Ok(val_s) = val.to_str().map(|s| s.to_string()) {
-            // If a header is included more than once, bail returning an error to the user.
-            let existing = headers_s.insert(name.as_str().to_string(), val_s);
-            if existing.is_some() {
-                let msg = format!("{} provided more than once", name.as_str());
-                return Err(WebhookError::InvalidHeaders(msg));
-            }
-        }
-    }
-    let headers = Arc::new(headers_s);
+    let headers = Arc::new(collect_headers(&headers)?);
 
     // Append to the webhook source, retrying if we race with a concurrent `ALTER SOURCE` op.
     Retry::default()
@@ -79,6 +74,84 @@ pub async fn handle_webhook(
     Ok::<_, WebhookError>(())
 }
 
+/// Evaluates the validation expression of the webhook source identified via `database`,
+/// `schema`, and `name` against the provided sample request, without appending it.
+///
+/// Unlike [`handle_webhook`] this requires authentication, since the response describes how the
+/// request was validated.
+pub async fn handle_webhook_explain(
+    mut client: AuthedClient,
+    Path((database, schema, name)): Path<(String, String, String)>,
+    headers: http::HeaderMap,
+    body: Bytes,
+) -> Result<Json<WebhookValidationExplanation>, WebhookError> {
+    let headers = Arc::new(collect_headers(&headers)?);
+
+    // The explanation reveals the inputs to the validation expression, including any secrets it
+    // references, so require the same privileges as reading from the source.
+    {
+        let catalog = client.client.catalog_snapshot().await;
+        let session = client.client.session();
+        let conn_catalog = catalog.for_session(session);
+        let item_name = PartialItemName {
+            database: Some(database.clone()),
+            schema: Some(schema.clone()),
+            item: name.clone(),
+        };
+        // If the source doesn't exist we report that below, when fetching the validator.
+        if let Ok(item) = conn_catalog.resolve_item(&item_name) {
+            let ids = iter::once(item.id()).chain(item.references().0.iter().copied());
+            rbac::check_read(
+                &conn_catalog,
+                session.role_metadata(),
+                session.vars(),
+                ids,
+                None,
+            )
+            .map_err(|e| WebhookError::Unauthorized(e.to_string()))?;
+        }
+    }
+
+    let adapter_client = client.client.inner();
+
+    // Record the time we receive the request, for use if validation checks the current timestamp.
+    let received_at = adapter_client.now();
+
+    // Note: we only use the validator, we never append with the returned appender.
+    let AppendWebhookResponse { validator, .. } = adapter_client
+        .get_webhook_appender(database, schema, name)
+        .await?;
+
+    let explanation = match validator {
+        Some(validator) => validator.explain(body, headers, received_at).await?,
+        // A webhook source without a CHECK expression accepts every request.
+        None => WebhookValidationExplanation {
+            valid: true,
+            result: Datum::True.to_string(),
+            inputs: vec![],
+            conjuncts: vec![],
+            failing_conjunct: None,
+        },
+    };
+    Ok(Json(explanation))
+}
+
+/// Collect headers into a map, while converting them into strings.
+fn collect_headers(headers: &http::HeaderMap) -> Result<BTreeMap<String, String>, WebhookError> {
+    let mut headers_s = BTreeMap::new();
+    for (name, val) in headers.iter() {
+        if let Ok(val_s) = val.to_str().map(|s| s.to_string()) {
+            // If a header is included more than once, bail returning an error to the user.
+            let existing = headers_s.insert(name.as_str().to_string(), val_s);
+            if existing.is_some() {
+                let msg = format!("{} provided more than once", name.as_str());
+                return Err(WebhookError::InvalidHeaders(msg));
+            }
+        }
+    }
+    Ok(headers_s)
+}
+
 /// Append the provided `body` and `headers` to the webhook source identified via `database`,
 /// `schema`, and `name`.
 async fn append_webhook(
@@ -243,6 +316,8 @@ pub enum WebhookError {
     SecretMissing,
     #[error("headers of request were invalid: {0}")]
     InvalidHeaders(String),
+    #[error("permission denied: {0}")]
+    Unauthorized(String),
     #[error("failed to deserialize body as {ty:?}: {msg}")]
     InvalidBody { ty: ScalarType, msg: String },
     #[error("failed to validate the request")]
@@ -308,6 +383,9 @@ impl IntoResponse for WebhookError {
             e @ WebhookError::InvalidHeaders(_) => {
                 (StatusCode::UNAUTHORIZED, e.to_string()).into_response()
             }
+            e @ WebhookError::Unauthorized(_) => {
+                (StatusCode::FORBIDDEN, e.to_string()).into_response()
+            }
             e @ WebhookError::Unavailable => {
                 (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response()
             }
@@ -335,7 +413,10 @@ mod tests {
     use http::StatusCode;
     use mz_adapter::AppendWebhookError;
     use mz_repr::{ColumnType, GlobalId, ScalarType};
+    use mz_sql::catalog::{CatalogItem, SessionCatalog};
+    use mz_sql::names::PartialItemName;
     use mz_sql::plan::{WebhookHeaderFilters, WebhookHeaders};
+    use mz_sql::rbac;
     use mz_storage_types::controller::StorageError;
     use proptest::prelude::*;
 
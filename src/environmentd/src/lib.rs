@@ -715,7 +715,7 @@ impl Listeners {
                 || "system_parameter_sync",
                 AssertUnwindSafe(system_parameter_sync(
                     system_parameter_sync_config,
-                    adapter_client,
+                    adapter_client.clone(),
                     config.config_sync_loop_interval,
                 ))
                 .ore_catch_unwind(),
@@ -729,6 +729,7 @@ impl Listeners {
             balancer_http_listener,
             internal_sql_listener,
             internal_http_listener,
+            adapter_client,
             _adapter_handle: adapter_handle,
         })
     }
@@ -906,6 +907,7 @@ pub struct Server {
     balancer_http_listener: ListenerHandle,
     internal_sql_listener: ListenerHandle,
     internal_http_listener: ListenerHandle,
+    adapter_client: mz_adapter::Client,
     _adapter_handle: mz_adapter::Handle,
 }
 
@@ -933,4 +935,9 @@ impl Server {
     pub fn internal_http_local_addr(&self) -> SocketAddr {
         self.internal_http_listener.local_addr()
     }
+
+    /// Returns a client for issuing commands directly to the coordinator.
+    pub fn adapter_client(&self) -> &mz_adapter::Client {
+        &self.adapter_client
+    }
 }
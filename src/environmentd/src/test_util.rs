@@ -36,7 +36,9 @@ use mz_ore::tracing::{
     TracingHandle,
 };
 use mz_persist_client::cache::PersistClientCache;
-use mz_persist_client::cfg::{PersistConfig, PersistParameters};
+use mz_persist_client::cfg::{
+    PersistConfig, PersistParameters, CONSENSUS_CONNECTION_POOL_MAX_SIZE,
+};
 use mz_persist_client::rpc::PersistGrpcPubSubServer;
 use mz_persist_client::PersistLocation;
 use mz_secrets::SecretsController;
@@ -337,10 +339,10 @@ impl Listeners {
         // Messing with the clock causes persist to expire leases, causing hangs and
         // panics. Is it possible/desirable to put this back somehow?
         let persist_now = SYSTEM_TIME.clone();
-        let mut persist_cfg = PersistConfig::new(&crate::BUILD_INFO, persist_now);
+        let persist_cfg = PersistConfig::new(&crate::BUILD_INFO, persist_now);
         // Tune down the number of connections to make this all work a little easier
         // with local postgres.
-        persist_cfg.consensus_connection_pool_max_size = 1;
+        persist_cfg.set_config(&CONSENSUS_CONNECTION_POOL_MAX_SIZE, 1);
         // Stress persist more by writing rollups frequently
         let mut persist_parameters = PersistParameters::default();
         persist_parameters.rollup_threshold = Some(5);
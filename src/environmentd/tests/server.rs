@@ -15,7 +15,7 @@ use std::io::Write as _;
 use std::net::Ipv4Addr;
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Barrier, Mutex};
 use std::time::{Duration, Instant};
 use std::{iter, thread};
 
@@ -37,7 +37,7 @@ use mz_ore::now::{to_datetime, NowFn};
 use mz_ore::retry::Retry;
 use mz_ore::{assert_contains, task::RuntimeExt};
 use mz_pgrepr::UInt8;
-use mz_sql::session::user::{HTTP_DEFAULT_USER, SYSTEM_USER};
+use mz_sql::session::user::{User, HTTP_DEFAULT_USER, SYSTEM_USER};
 use mz_sql_parser::ast::display::AstDisplay;
 
 use postgres_array::Array;
@@ -833,6 +833,83 @@ fn test_cancel_long_running_query() {
         .expect("simple query succeeds after cancellation");
 }
 
+// Test that canceling a CREATE MATERIALIZED VIEW or CREATE INDEX while it's
+// being optimized, i.e. before anything is committed to the catalog, doesn't
+// create the object.
+#[mz_ore::test]
+fn test_cancel_ddl_during_optimization() {
+    let server = test_util::TestHarness::default()
+        .unsafe_mode()
+        .start_blocking();
+
+    let (notice_tx, notice_rx) = std::sync::mpsc::channel();
+    let mut client = server
+        .pg_config()
+        .notice_callback(move |notice| notice_tx.send(notice).expect("send notice"))
+        .connect(postgres::NoTls)
+        .unwrap();
+    client.batch_execute("CREATE TABLE t (i INT)").unwrap();
+
+    for (failpoint, ddl, object_query, notice) in [
+        (
+            "optimize_create_materialized_view",
+            "CREATE MATERIALIZED VIEW mv AS SELECT * FROM t",
+            "SELECT count(*) FROM mz_materialized_views WHERE name = 'mv'",
+            "materialized view \"mv\" was not created, because it was canceled before being committed",
+        ),
+        (
+            "optimize_create_index",
+            "CREATE INDEX i ON t (i)",
+            "SELECT count(*) FROM mz_indexes WHERE name = 'i'",
+            "index \"i\" was not created, because it was canceled before being committed",
+        ),
+    ] {
+        // Hold the DDL in optimization until the coordinator has seen the
+        // cancellation.
+        let optimizing = Arc::new(Barrier::new(2));
+        let canceled = Arc::new(Barrier::new(2));
+        fail::cfg_callback(failpoint, {
+            let optimizing = Arc::clone(&optimizing);
+            let canceled = Arc::clone(&canceled);
+            move || {
+                optimizing.wait();
+                canceled.wait();
+            }
+        })
+        .unwrap();
+        fail::cfg_callback("cancel_optimizing_ddl", move || {
+            canceled.wait();
+        })
+        .unwrap();
+
+        let cancel_token = client.cancel_token();
+        let handle = thread::spawn(move || {
+            optimizing.wait();
+            cancel_token.cancel_query(postgres::NoTls).unwrap();
+        });
+
+        match client.simple_query(ddl) {
+            Err(e) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
+            Err(e) => panic!("expected error SqlState::QUERY_CANCELED, but got {:?}", e),
+            Ok(_) => panic!("expected error SqlState::QUERY_CANCELED, but {ddl} succeeded"),
+        }
+        handle.join().unwrap();
+        fail::remove(failpoint);
+        fail::remove("cancel_optimizing_ddl");
+
+        let notices: Vec<_> = notice_rx.try_iter().map(|n| n.message().to_string()).collect();
+        assert_eq!(notices, [notice]);
+
+        let count: i64 = client.query_one(object_query, &[]).unwrap().get(0);
+        assert_eq!(count, 0, "{ddl} created an object after being canceled");
+
+        // The DDL didn't leave anything behind, so running it again succeeds.
+        client.batch_execute(ddl).unwrap();
+        let count: i64 = client.query_one(object_query, &[]).unwrap().get(0);
+        assert_eq!(count, 1);
+    }
+}
+
 fn test_cancellation_cancels_dataflows(query: &str) {
     let server = test_util::TestHarness::default()
         .unsafe_mode()
@@ -2560,6 +2637,114 @@ fn test_webhook_duplicate_headers() {
     assert_eq!(resp.status().as_u16(), 401);
 }
 
+#[mz_ore::test]
+#[cfg_attr(miri, ignore)] // too slow
+fn test_webhook_explain_validation() {
+    let server = test_util::TestHarness::default().start_blocking();
+
+    let mut client = server.connect(postgres::NoTls).unwrap();
+    let http_client = Client::new();
+
+    client
+        .batch_execute(
+            "CREATE CLUSTER webhook_cluster REPLICAS (r1 (SIZE '1'));
+            CREATE SECRET webhook_token AS 'hunter2';
+            CREATE SOURCE webhook_text IN CLUSTER webhook_cluster FROM WEBHOOK BODY FORMAT TEXT
+            CHECK (
+                WITH (HEADERS, BODY, SECRET webhook_token)
+                body = 'hello' AND headers->'x-token' = webhook_token
+            );",
+        )
+        .expect("failed to create source");
+    let explain_url = format!(
+        "http://{}/api/webhook/materialize/public/webhook_text/explain",
+        server.inner().http_local_addr()
+    );
+
+    // The default HTTP user has no privileges on the source, so it can't see how requests are
+    // validated.
+    server
+        .pg_config_internal()
+        .user(&SYSTEM_USER.name)
+        .connect(postgres::NoTls)
+        .unwrap()
+        .batch_execute(&format!("CREATE ROLE {}", &HTTP_DEFAULT_USER.name))
+        .unwrap();
+    let resp = http_client
+        .post(&explain_url)
+        .body("hello")
+        .header("x-token", "hunter2")
+        .send()
+        .expect("failed to POST explain");
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert!(!resp.text().expect("valid text").contains("hunter2"));
+
+    // Reading from the source and its secret is enough to explain requests.
+    client
+        .batch_execute(&format!(
+            "GRANT SELECT ON webhook_text TO {user};
+            GRANT USAGE ON SECRET webhook_token TO {user};",
+            user = &HTTP_DEFAULT_USER.name,
+        ))
+        .expect("failed to grant privileges");
+
+    // A request that passes validation.
+    let explanation: serde_json::Value = http_client
+        .post(&explain_url)
+        .body("hello")
+        .header("x-token", "hunter2")
+        .send()
+        .expect("failed to POST explain")
+        .error_for_status()
+        .expect("explain failed")
+        .json()
+        .expect("valid json");
+    assert_eq!(explanation["valid"], true);
+    assert_eq!(explanation["failing_conjunct"], serde_json::Value::Null);
+    let conjuncts = explanation["conjuncts"].as_array().expect("array");
+    assert_eq!(conjuncts.len(), 2);
+    assert!(conjuncts.iter().all(|c| c["passed"] == true));
+
+    // A request that fails validation because of the token.
+    let explanation: serde_json::Value = http_client
+        .post(&explain_url)
+        .body("hello")
+        .header("x-token", "wrong")
+        .send()
+        .expect("failed to POST explain")
+        .error_for_status()
+        .expect("explain failed")
+        .json()
+        .expect("valid json");
+    assert_eq!(explanation["valid"], false);
+    let failing = explanation["failing_conjunct"]
+        .as_u64()
+        .expect("a failing conjunct");
+    let failing = &explanation["conjuncts"][usize::cast_from(failing)];
+    assert_eq!(failing["passed"], false);
+    assert_contains!(failing["expression"].as_str().expect("string"), "x-token");
+
+    // The secret is redacted, but the other inputs are not.
+    let inputs = explanation["inputs"].as_array().expect("array");
+    let input = |column: &str| {
+        inputs
+            .iter()
+            .find(|input| input["column"] == column)
+            .map(|input| input["value"].as_str().expect("string").to_string())
+            .expect("input exists")
+    };
+    assert_eq!(input("webhook_token"), "<redacted>");
+    assert_contains!(input("body"), "hello");
+    assert!(!explanation.to_string().contains("hunter2"));
+
+    // Explaining never appends to the source.
+    let count: i64 = client
+        .query_one("SELECT COUNT(*) FROM webhook_text", &[])
+        .expect("success")
+        .get(0);
+    assert_eq!(count, 0);
+}
+
 // Test that websockets observe cancellation and leave the transaction in an idle state.
 #[mz_ore::test]
 #[cfg_attr(miri, ignore)] // unsupported operation: can't call foreign function `epoll_wait` on OS `linux`
@@ -3381,3 +3566,60 @@ async fn test_connection_id() {
     let pid_envid = pid >> 19;
     assert_eq!(envid_lower, pid_envid);
 }
+
+// Test that validating system variables through the coordinator reports the
+// outcome for each variable, without applying any of them.
+#[mz_ore::test(tokio::test(flavor = "multi_thread", worker_threads = 1))]
+#[cfg_attr(miri, ignore)] // too slow
+async fn test_validate_system_vars() {
+    let server = test_util::TestHarness::default().start().await;
+    let adapter_client = server.inner.adapter_client();
+
+    let validate = |user: User, vars: &[(&str, &str)]| {
+        let vars: BTreeMap<_, _> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        async move {
+            let conn_id = adapter_client.new_conn_id().unwrap();
+            let session = adapter_client.new_session(conn_id, user);
+            let mut session_client = adapter_client.startup(session).await.unwrap();
+            let results = session_client.validate_system_vars(vars).await;
+            session_client.terminate().await;
+            results
+        }
+    };
+
+    let results = validate(
+        SYSTEM_USER.clone(),
+        &[
+            ("max_tables", "7"),
+            ("max_clusters", "seven"),
+            ("no_such_var", "7"),
+            ("persist_blob_target_size", "1048576"),
+        ],
+    )
+    .await;
+    assert_eq!(results.len(), 4);
+    assert!(results["max_tables"].is_ok());
+    assert!(results["max_clusters"].is_err());
+    assert!(results["no_such_var"].is_err());
+    assert!(results["persist_blob_target_size"].is_ok());
+
+    // Internal variables aren't visible to regular users.
+    let user = User {
+        name: "materialize".into(),
+        external_metadata: None,
+    };
+    let results = validate(user, &[("persist_blob_target_size", "1048576")]).await;
+    assert!(results["persist_blob_target_size"].is_err());
+
+    // Nothing was applied.
+    let client = server.connect().internal().await.unwrap();
+    let max_tables: String = client
+        .query_one("SHOW max_tables", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(max_tables, "25");
+}
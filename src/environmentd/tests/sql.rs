@@ -21,6 +21,7 @@ use std::time::{Duration, Instant};
 use axum::response::{IntoResponse, Response};
 use axum::{routing, Json, Router};
 use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use http::StatusCode;
 use itertools::Itertools;
 use mz_adapter::{TimestampContext, TimestampExplanation};
@@ -2191,86 +2192,144 @@ fn test_introspection_user_permissions() {
         .is_ok());
 }
 
-#[mz_ore::test]
-fn test_idle_in_transaction_session_timeout() {
-    let server = test_util::TestHarness::default().start_blocking();
-    server.enable_feature_flags(&["enable_unsafe_functions"]);
+#[mz_ore::test(tokio::test(flavor = "multi_thread", worker_threads = 1))]
+async fn test_idle_in_transaction_session_timeout() {
+    let server = test_util::TestHarness::default().start().await;
+    server
+        .enable_feature_flags(&["enable_unsafe_functions"])
+        .await;
 
-    let mut client = server.connect(postgres::NoTls).unwrap();
+    async fn expect_timeout_notice(rx: &mut futures::channel::mpsc::UnboundedReceiver<DbError>) {
+        loop {
+            let notice = rx.next().await.expect("notice stream closed");
+            if notice.code() == &SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT {
+                return;
+            }
+        }
+    }
+
+    let (tx, mut rx) = futures::channel::mpsc::unbounded();
+    let client = server
+        .connect()
+        .notice_callback(move |notice| tx.unbounded_send(notice).unwrap())
+        .await
+        .unwrap();
     client
-        .batch_execute("SET idle_in_transaction_session_timeout TO '4ms'")
+        .batch_execute("CREATE TABLE t (a int)")
+        .await
         .unwrap();
-    client.batch_execute("BEGIN").unwrap();
-    std::thread::sleep(Duration::from_millis(5));
-    // Retry because sleep might be woken up early.
-    Retry::default()
-        .max_duration(Duration::from_secs(1))
-        .retry(|_| {
-            let res = client.query("SELECT 1", &[]);
-            if let Err(error) = res {
-                if error.is_closed() {
-                    Ok(())
-                } else {
-                    Err(format!(
-                        "error should indicates that the connection is closed: {error:?}"
-                    ))
-                }
-            } else {
-                Err(format!("query should return error: {res:?}"))
-            }
-        })
+    client
+        .batch_execute("SET idle_in_transaction_session_timeout TO '50ms'")
+        .await
         .unwrap();
 
-    // session should be timed out even if transaction has failed.
-    let mut client = server.connect(postgres::NoTls).unwrap();
+    // An idle transaction is rolled back, and the session is told about it
+    // right away with a notice, without it having to issue another command.
+    client.batch_execute("BEGIN").await.unwrap();
     client
-        .batch_execute("SET idle_in_transaction_session_timeout TO '50ms'")
+        .batch_execute("INSERT INTO t VALUES (1)")
+        .await
         .unwrap();
-    client.batch_execute("BEGIN").unwrap();
-    let error = client.batch_execute("SELECT 1/0").unwrap_err();
-    assert!(
-        !error.is_closed(),
-        "failing a transaction should not close the connection: {error:?}"
+    expect_timeout_notice(&mut rx).await;
+    // The next command fails, but doesn't close the connection.
+    let error = client.batch_execute("SELECT 1").await.unwrap_err();
+    assert_eq!(
+        error.code(),
+        Some(&SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT)
     );
-    std::thread::sleep(Duration::from_millis(51));
-    // Retry because sleep might be woken up early.
-    Retry::default()
-        .max_duration(Duration::from_secs(1))
-        .retry(|_| {
-            let res = client.query("SELECT 1", &[]);
-            if let Err(error) = res {
-                if error.is_closed() {
-                    Ok(())
-                } else {
-                    Err(format!(
-                        "error should indicates that the connection is closed: {error:?}"
-                    ))
-                }
-            } else {
-                Err(format!("query should return error: {res:?}"))
-            }
-        })
+    // The session is no longer in a transaction, and its writes are gone.
+    let count: i64 = client
+        .query_one("SELECT count(*) FROM t", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(count, 0);
+
+    // Failed transactions are rolled back too.
+    client.batch_execute("BEGIN").await.unwrap();
+    let error = client.batch_execute("SELECT 1/0").await.unwrap_err();
+    assert_ne!(
+        error.code(),
+        Some(&SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT)
+    );
+    expect_timeout_notice(&mut rx).await;
+    let error = client.batch_execute("ROLLBACK").await.unwrap_err();
+    assert_eq!(
+        error.code(),
+        Some(&SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT)
+    );
+    client.batch_execute("SELECT 1").await.unwrap();
+
+    // A transaction isn't rolled back while it's running a statement.
+    client.batch_execute("BEGIN").await.unwrap();
+    client
+        .batch_execute("SELECT mz_unsafe.mz_sleep(2)")
+        .await
         .unwrap();
+    client
+        .batch_execute("INSERT INTO t VALUES (1)")
+        .await
+        .unwrap();
+    client.batch_execute("COMMIT").await.unwrap();
 
-    // session should not be timed out if it's not idle.
-    let mut client = server.connect(postgres::NoTls).unwrap();
+    // Nor while the client is still consuming the output of a long-running
+    // statement, like a SUBSCRIBE.
+    client
+        .batch_execute("SET idle_in_transaction_session_timeout TO '1s'")
+        .await
+        .unwrap();
+    client
+        .batch_execute("CREATE TABLE s (a int)")
+        .await
+        .unwrap();
+    let writer = server.connect().await.unwrap();
+    client.batch_execute("BEGIN").await.unwrap();
+    let out = client
+        .copy_out("COPY (SUBSCRIBE s) TO STDOUT")
+        .await
+        .unwrap();
+    let mut out = std::pin::pin!(out);
+    for i in 0..6 {
+        writer
+            .batch_execute(&format!("INSERT INTO s VALUES ({i})"))
+            .await
+            .unwrap();
+        out.next().await.expect("subscribe ended").unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    client
+        .cancel_token()
+        .cancel_query(tokio_postgres::NoTls)
+        .await
+        .unwrap();
+    while let Some(Ok(_)) = out.next().await {}
+    while let Ok(Some(notice)) = rx.try_next() {
+        assert_ne!(
+            notice.code(),
+            &SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT
+        );
+    }
+    client.batch_execute("ROLLBACK").await.unwrap();
     client
         .batch_execute("SET idle_in_transaction_session_timeout TO '50ms'")
+        .await
         .unwrap();
-    client.batch_execute("BEGIN").unwrap();
-    client.query("SELECT mz_unsafe.mz_sleep(1)", &[]).unwrap();
-    client.query("SELECT 1", &[]).unwrap();
-    client.batch_execute("COMMIT").unwrap();
 
-    // 0 timeout indicated no timeout.
-    let mut client = server.connect(postgres::NoTls).unwrap();
+    // A timeout of 0 disables it.
     client
         .batch_execute("SET idle_in_transaction_session_timeout TO 0")
+        .await
         .unwrap();
-    client.batch_execute("BEGIN").unwrap();
-    std::thread::sleep(Duration::from_millis(5));
-    client.query("SELECT 1", &[]).unwrap();
-    client.batch_execute("COMMIT").unwrap();
+    client.batch_execute("BEGIN").await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    client.batch_execute("SELECT 1").await.unwrap();
+    client.batch_execute("COMMIT").await.unwrap();
+    let count: i64 = client
+        .query_one("SELECT count(*) FROM t", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(count, 1);
 }
 
 #[mz_ore::test]
@@ -3783,3 +3842,103 @@ async fn test_temporal_static_queries() {
         timestamp
     )
 }
+
+/// Runs `SELECT * FROM t`, which must be denied, and returns the error detail and the notices
+/// received, if any.
+fn select_denied(
+    client: &mut postgres::Client,
+    rx: &mut futures::channel::mpsc::UnboundedReceiver<DbError>,
+) -> (Option<String>, Vec<DbError>) {
+    let err = client.batch_execute("SELECT * FROM t").unwrap_err();
+    let err = err.as_db_error().unwrap();
+    assert_eq!(err.code(), &SqlState::INSUFFICIENT_PRIVILEGE);
+    let detail = err.detail().map(|detail| detail.to_string());
+    // Notices are only processed by the client during the next statement.
+    client.batch_execute("SHOW rbac_debug").unwrap();
+    let mut notices = Vec::new();
+    while let Ok(Some(notice)) = rx.try_next() {
+        notices.push(notice);
+    }
+    (detail, notices)
+}
+
+#[mz_ore::test]
+fn test_rbac_debug_trace() {
+    let server = test_util::TestHarness::default().start_blocking();
+
+    let mut sys_client = server
+        .pg_config_internal()
+        .user(&SYSTEM_USER.name)
+        .connect(postgres::NoTls)
+        .unwrap();
+    sys_client
+        .batch_execute("ALTER SYSTEM SET enable_rbac_checks TO true")
+        .unwrap();
+
+    let (tx, mut rx) = futures::channel::mpsc::unbounded();
+    let mut client = server
+        .pg_config()
+        .user("joe")
+        .notice_callback(move |notice| tx.unbounded_send(notice).unwrap())
+        .connect(postgres::NoTls)
+        .unwrap();
+
+    sys_client
+        .batch_execute(
+            "CREATE TABLE materialize.public.t (a int);
+            CREATE CLUSTER c REPLICAS ();",
+        )
+        .unwrap();
+    client.batch_execute("SET cluster = c").unwrap();
+    // Discard the welcome message.
+    while let Ok(Some(_)) = rx.try_next() {}
+
+    // Without `rbac_debug`, nothing beyond the usual error is reported.
+    sys_client
+        .batch_execute("GRANT SELECT ON materialize.public.t TO joe")
+        .unwrap();
+    let (detail, notices) = select_denied(&mut client, &mut rx);
+    assert_eq!(detail, None);
+    assert!(notices.is_empty(), "{notices:?}");
+
+    client.batch_execute("SET rbac_debug = on").unwrap();
+
+    // Denied due to missing USAGE on the cluster.
+    let expected = "\
+        passed: USAGE on SCHEMA \"materialize.public\" for role \"joe\"\n\
+        passed: SELECT on TABLE \"materialize.public.t\" for role \"joe\"\n\
+        denied: USAGE on CLUSTER \"c\" for role \"joe\"";
+    let (detail, notices) = select_denied(&mut client, &mut rx);
+    assert_eq!(
+        detail.as_deref(),
+        Some(format!("RBAC checks performed:\n{expected}").as_str())
+    );
+    let notice = notices.into_element();
+    assert_eq!(
+        notice.message(),
+        "performed 3 RBAC checks before denying statement"
+    );
+    assert_eq!(notice.detail(), Some(expected));
+
+    // Denied due to missing SELECT on the table.
+    sys_client
+        .batch_execute(
+            "REVOKE SELECT ON materialize.public.t FROM joe;
+            GRANT USAGE ON CLUSTER c TO joe;",
+        )
+        .unwrap();
+    let expected = "\
+        passed: USAGE on SCHEMA \"materialize.public\" for role \"joe\"\n\
+        denied: SELECT on TABLE \"materialize.public.t\" for role \"joe\"";
+    let (detail, notices) = select_denied(&mut client, &mut rx);
+    assert_eq!(
+        detail.as_deref(),
+        Some(format!("RBAC checks performed:\n{expected}").as_str())
+    );
+    let notice = notices.into_element();
+    assert_eq!(
+        notice.message(),
+        "performed 2 RBAC checks before denying statement"
+    );
+    assert_eq!(notice.detail(), Some(expected));
+}
@@ -100,8 +100,9 @@ impl Consensus for MaelstromConsensus {
             .await
             .map_err(anyhow::Error::new)?
         {
+            // Deleted keys are tombstoned with a null value, see `delete`.
+            Some(Value::Null) | None => return Ok(None),
             Some(x) => x,
-            None => return Ok(None),
         };
         let value = VersionedData::from(MaelstromVersionedData::try_from(&value)?);
         self.cache
@@ -167,6 +168,33 @@ impl Consensus for MaelstromConsensus {
     async fn truncate(&self, _key: &str, _seqno: SeqNo) -> Result<usize, ExternalError> {
         unimplemented!("TODO")
     }
+
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        // lin-kv has no way to remove a key, so instead CaS it to null, which
+        // `head` treats as absent and `compare_and_set` with an expectation
+        // of None happily replaces.
+        let from = match self.hydrate_seqno(key, expected).await? {
+            Ok(x) => Value::from(&MaelstromVersionedData::from(x)),
+            Err(_) => return Ok(CaSResult::ExpectationMismatch),
+        };
+        let cas_res = self
+            .handle
+            .lin_kv_compare_and_set(
+                Value::from(format!("consensus/{}", key)),
+                from,
+                Value::Null,
+                Some(false),
+            )
+            .await;
+        match cas_res {
+            Ok(()) => Ok(CaSResult::Committed),
+            Err(MaelstromError {
+                code: ErrorCode::PreconditionFailed | ErrorCode::KeyDoesNotExist,
+                ..
+            }) => Ok(CaSResult::ExpectationMismatch),
+            Err(err) => Err(ExternalError::from(anyhow::Error::new(err))),
+        }
+    }
 }
 
 /// Implementation of [Blob] backed by the Maelstrom lin-kv service.
@@ -392,7 +420,7 @@ impl MaelstromOracleKey {
         match res {
             Ok(()) => Ok(CaSResult::Committed),
             Err(MaelstromError {
-                code: ErrorCode::PreconditionFailed,
+                code: ErrorCode::PreconditionFailed | ErrorCode::KeyDoesNotExist,
                 ..
             }) => Ok(CaSResult::ExpectationMismatch),
             Err(err) => Err(anyhow::Error::new(err).into()),
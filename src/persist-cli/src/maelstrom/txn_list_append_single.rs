@@ -26,6 +26,8 @@ use mz_persist_client::async_runtime::IsolatedRuntime;
 use mz_persist_client::cache::StateCache;
 use mz_persist_client::cfg::PersistConfig;
 use mz_persist_client::critical::SinceHandle;
+use mz_persist_client::error::SnapshotError;
+use mz_persist_client::fetch::DecodeResult;
 use mz_persist_client::metrics::Metrics;
 use mz_persist_client::read::{Listen, ListenEvent};
 use mz_persist_client::rpc::PubSubClientConnection;
@@ -112,7 +114,7 @@ pub struct Transactor {
     // assert that it has the same data as the short-lived snapshot+listen in
     // `read`. This hopefully stresses slightly different parts of the system.
     long_lived_updates: Vec<(
-        (Result<MaelstromKey, String>, Result<MaelstromVal, String>),
+        (DecodeResult<MaelstromKey>, DecodeResult<MaelstromVal>),
         u64,
         i64,
     )>,
@@ -155,7 +157,10 @@ impl Transactor {
         let mut updates = read
             .snapshot_and_fetch(as_of.clone())
             .await
-            .expect("as_of unexpectedly unavailable");
+            .map_err(|err| MaelstromError {
+                code: ErrorCode::Crash,
+                text: format!("unable to snapshot at {:?}: {}", as_of.elements(), err),
+            })?;
         long_lived_updates.append(&mut updates);
         let long_lived_listen = read
             .listen(as_of.clone())
@@ -240,7 +245,7 @@ impl Transactor {
     ) -> Result<
         (
             Vec<(
-                (Result<MaelstromKey, String>, Result<MaelstromVal, String>),
+                (DecodeResult<MaelstromKey>, DecodeResult<MaelstromVal>),
                 u64,
                 i64,
             )>,
@@ -274,7 +279,26 @@ impl Transactor {
             let updates_res = read.snapshot_and_fetch(snap_as_of.clone()).await;
             let mut updates = match updates_res {
                 Ok(x) => x,
-                Err(since) => {
+                Err(SnapshotError::InternalPanic(err)) => {
+                    return Err(MaelstromError {
+                        code: ErrorCode::Crash,
+                        text: err.to_string(),
+                    });
+                }
+                Err(SnapshotError::Fetch(err)) => {
+                    // Nothing was written, so this definitely didn't happen.
+                    return Err(MaelstromError {
+                        code: ErrorCode::Abort,
+                        text: err.to_string(),
+                    });
+                }
+                Err(SnapshotError::Compacted) => {
+                    // The as_of is still readable, so try again at the same
+                    // read_ts.
+                    info!("snapshot data compacted away during read, trying again");
+                    continue;
+                }
+                Err(SnapshotError::Since(since) | SnapshotError::SinceAdvanced(since)) => {
                     let recent_upper = self.write.fetch_recent_upper().await;
                     // Because we artificially share the same CriticalReaderId
                     // between nodes, it doesn't quite act like a capability.
@@ -376,7 +400,7 @@ impl Transactor {
         frontier: &Antichain<u64>,
     ) -> Result<
         Vec<(
-            (Result<MaelstromKey, String>, Result<MaelstromVal, String>),
+            (DecodeResult<MaelstromKey>, DecodeResult<MaelstromVal>),
             u64,
             i64,
         )>,
@@ -408,7 +432,7 @@ impl Transactor {
         &mut self,
         as_of: &Antichain<u64>,
     ) -> Vec<(
-        (Result<MaelstromKey, String>, Result<MaelstromVal, String>),
+        (DecodeResult<MaelstromKey>, DecodeResult<MaelstromVal>),
         u64,
         i64,
     )> {
@@ -443,7 +467,7 @@ impl Transactor {
     fn extract_state_map(
         read_ts: u64,
         updates: Vec<(
-            (Result<MaelstromKey, String>, Result<MaelstromVal, String>),
+            (DecodeResult<MaelstromKey>, DecodeResult<MaelstromVal>),
             u64,
             i64,
         )>,
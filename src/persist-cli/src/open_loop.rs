@@ -608,10 +608,8 @@ mod raw_persist_benchmark {
                 || format!("appender-{}", idx),
                 async move {
                     while let Some(batch) = batch_rx.recv().await {
-                        let lower = batch.lower().clone();
-                        let upper = batch.upper().clone();
                         write
-                            .append_batch(batch, lower, upper)
+                            .append_batch_owned(batch)
                             .await
                             .expect("invalid usage")
                             .expect("unexpected upper");
@@ -99,6 +99,10 @@ pub fn bench_persist(c: &mut Criterion) {
     }
     plumbing::bench_encode_batch("plumbing/encode_batch", throughput, c, &data);
     plumbing::bench_trace_push_batch(c);
+    plumbing::bench_state_apply_diffs(c);
+    plumbing::bench_state_compare_and_append(c);
+    plumbing::bench_state_encode_decode(c);
+    plumbing::bench_batch_builder_add(c, &runtime);
 }
 
 fn create_mem_mem_client() -> Result<PersistClient, ExternalError> {
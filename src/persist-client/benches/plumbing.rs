@@ -14,22 +14,27 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use bytes::Bytes;
-use criterion::{Bencher, BenchmarkId, Criterion, Throughput};
+use criterion::{BatchSize, Bencher, BenchmarkId, Criterion, Throughput};
 use differential_dataflow::trace::Description;
 use futures::stream::{FuturesUnordered, StreamExt};
+use mz_ore::bytes::SegmentedBytes;
+use mz_ore::cast::CastFrom;
 use mz_ore::task::RuntimeExt;
 use mz_persist::indexed::encoding::BlobTraceBatchPart;
 use mz_persist::location::{
     Atomicity, Blob, CaSResult, Consensus, ExternalError, SeqNo, VersionedData,
 };
 use mz_persist::workload::{self, DataGenerator};
-use mz_persist_client::internals_bench::trace_push_batch_one_iter;
+use mz_persist_client::internals_bench::{
+    batch_builder_add_one_iter, trace_push_batch_one_iter, trace_push_batch_pattern_one_iter,
+    SpinePattern, StateFixture, StateParams,
+};
 use mz_persist_client::ShardId;
 use timely::progress::Antichain;
 use tokio::runtime::Runtime;
 use uuid::Uuid;
 
-use crate::{bench_all_blob, bench_all_consensus};
+use crate::{bench_all_blob, bench_all_consensus, create_mem_mem_client};
 
 pub fn bench_consensus_compare_and_set(
     name: &str,
@@ -209,6 +214,18 @@ pub fn bench_encode_batch(name: &str, throughput: bool, c: &mut Criterion, data:
             trace.encode(&mut buf);
         })
     });
+
+    // Fetching a part verifies its checksum before decoding it, so compare
+    // the two.
+    let mut buf = Vec::new();
+    trace.encode(&mut buf);
+    g.bench_function(BenchmarkId::new("checksum", data.goodput_pretty()), |b| {
+        b.iter(|| crc32fast::hash(&buf))
+    });
+    let buf = SegmentedBytes::from(buf);
+    g.bench_function(BenchmarkId::new("decode", data.goodput_pretty()), |b| {
+        b.iter(|| BlobTraceBatchPart::<u64>::decode(&buf).expect("decodable part"))
+    });
 }
 
 pub fn bench_trace_push_batch(c: &mut Criterion) {
@@ -224,4 +241,108 @@ pub fn bench_trace_push_batch(c: &mut Criterion) {
     g.bench_function(BenchmarkId::new("push_batch", num_batches), |b| {
         b.iter(|| trace_push_batch_one_iter(num_batches));
     });
+    for pattern in SpinePattern::ALL {
+        g.bench_function(
+            BenchmarkId::new(format!("push_batch_{:?}", pattern), num_batches),
+            |b| b.iter(|| trace_push_batch_pattern_one_iter(*pattern, num_batches)),
+        );
+    }
+}
+
+pub fn bench_state_apply_diffs(c: &mut Criterion) {
+    let mut g = c.benchmark_group("state/apply_diffs");
+    // As above, the larger numbers are the interesting ones, but they take too
+    // long with cargo test --all-targets.
+    let (num_batches, num_diffs) = if cfg!(debug_assertions) {
+        (&[10, 100][..], &[1, 10][..])
+    } else {
+        (&[10, 1_000, 10_000][..], &[1, 10, 100][..])
+    };
+    for num_batches in num_batches {
+        let fixture = StateFixture::new(StateParams {
+            num_batches: *num_batches,
+            num_parts_per_batch: 1,
+            num_readers: 1,
+            num_writers: 1,
+        });
+        for num_diffs in num_diffs {
+            let diffs = fixture.diffs(*num_diffs, 1);
+            g.throughput(Throughput::Elements(u64::cast_from(*num_diffs)));
+            g.bench_function(
+                BenchmarkId::new(format!("batches={}", num_batches), num_diffs),
+                |b| {
+                    b.iter_batched(
+                        || fixture.state(),
+                        |state| fixture.apply_diffs_one_iter(state, &diffs),
+                        BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+}
+
+pub fn bench_state_compare_and_append(c: &mut Criterion) {
+    let mut g = c.benchmark_group("state/compare_and_append");
+    let num_handles = if cfg!(debug_assertions) {
+        &[1, 10][..]
+    } else {
+        &[1, 10, 100, 1_000][..]
+    };
+    for num_handles in num_handles {
+        let fixture = StateFixture::new(StateParams {
+            num_batches: 100,
+            num_parts_per_batch: 1,
+            num_readers: *num_handles,
+            num_writers: *num_handles,
+        });
+        g.bench_function(BenchmarkId::new("handles", num_handles), |b| {
+            b.iter(|| fixture.compare_and_append_one_iter())
+        });
+    }
+}
+
+pub fn bench_state_encode_decode(c: &mut Criterion) {
+    let mut g = c.benchmark_group("state");
+    let num_batches = if cfg!(debug_assertions) {
+        &[10, 100][..]
+    } else {
+        &[10, 1_000, 10_000][..]
+    };
+    for num_batches in num_batches {
+        let fixture = StateFixture::new(StateParams {
+            num_batches: *num_batches,
+            num_parts_per_batch: 10,
+            num_readers: 10,
+            num_writers: 10,
+        });
+        g.bench_function(BenchmarkId::new("encode", num_batches), |b| {
+            b.iter(|| fixture.encode_one_iter())
+        });
+        let buf = fixture.encode_one_iter();
+        g.bench_function(BenchmarkId::new("decode", num_batches), |b| {
+            b.iter(|| fixture.decode_one_iter(&buf))
+        });
+    }
+}
+
+pub fn bench_batch_builder_add(c: &mut Criterion, runtime: &Runtime) {
+    let mut g = c.benchmark_group("batch_builder");
+    // As above, the larger number is the interesting one, but it takes too
+    // long with cargo test --all-targets.
+    let num_updates: u64 = if cfg!(debug_assertions) {
+        1_000
+    } else {
+        1_000_000
+    };
+    let updates = (0..num_updates)
+        .map(|i| ((i.to_le_bytes().to_vec(), vec![]), i, 1i64))
+        .collect::<Vec<_>>();
+    g.throughput(Throughput::Elements(num_updates));
+    for (name, add_many) in [("add", false), ("add_many", true)] {
+        g.bench_function(BenchmarkId::new(name, num_updates), |b| {
+            let client = create_mem_mem_client().expect("failed to create mem_mem client");
+            b.iter(|| runtime.block_on(batch_builder_add_one_iter(&client, &updates, add_many)));
+        });
+    }
 }
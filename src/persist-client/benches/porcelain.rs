@@ -246,7 +246,7 @@ async fn bench_snapshot_one_iter(
     let x = read
         .snapshot_and_fetch(as_of.clone())
         .await
-        .expect("cannot serve requested as_of");
+        .expect("snapshot_and_fetch failed");
     black_box(x);
 
     // Gracefully expire the ReadHandle.
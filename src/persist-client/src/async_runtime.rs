@@ -10,11 +10,62 @@
 //! Async runtime extensions.
 
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
+use mz_ore::cast::CastFrom;
+use mz_ore::future::OreFutureExt;
+use mz_ore::metrics::{MetricsRegistry, UIntGauge};
 use mz_ore::task::{JoinHandle, RuntimeExt};
 use tokio::runtime::{Builder, Runtime};
 
+use crate::error::InternalPanic;
+use crate::internal::compact::CompactionQueue;
+use crate::internal::metrics::IsolatedRuntimeMetrics;
+
+/// Configuration for an [IsolatedRuntime].
+#[derive(Debug, Clone)]
+pub struct IsolatedRuntimeConfig {
+    /// The number of worker threads.
+    pub worker_threads: usize,
+    /// The prefix of the names of the runtime's threads.
+    ///
+    /// Thread names are truncated to 15 bytes on Linux, so this should be
+    /// kept short.
+    pub thread_name_prefix: String,
+}
+
+impl IsolatedRuntimeConfig {
+    /// The minimum default number of worker threads.
+    pub const MIN_DEFAULT_WORKER_THREADS: usize = 1;
+    /// The maximum default number of worker threads.
+    pub const MAX_DEFAULT_WORKER_THREADS: usize = 32;
+
+    /// Returns the default number of worker threads: the number of cores
+    /// available to this process, clamped to
+    /// [Self::MIN_DEFAULT_WORKER_THREADS] and
+    /// [Self::MAX_DEFAULT_WORKER_THREADS].
+    pub fn default_worker_threads() -> usize {
+        std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .clamp(
+                Self::MIN_DEFAULT_WORKER_THREADS,
+                Self::MAX_DEFAULT_WORKER_THREADS,
+            )
+    }
+}
+
+impl Default for IsolatedRuntimeConfig {
+    fn default() -> Self {
+        IsolatedRuntimeConfig {
+            worker_threads: Self::default_worker_threads(),
+            thread_name_prefix: "persist".into(),
+        }
+    }
+}
+
 /// An isolated runtime for asynchronous tasks, particularly work
 /// that may be CPU intensive such as encoding/decoding and shard
 /// maintenance.
@@ -28,29 +79,58 @@ use tokio::runtime::{Builder, Runtime};
 #[derive(Debug)]
 pub struct IsolatedRuntime {
     inner: Option<Runtime>,
+    metrics: IsolatedRuntimeMetrics,
+    /// Admission control for the compactions run on this runtime, shared by
+    /// every shard that uses it.
+    pub(crate) compaction_queue: Arc<CompactionQueue>,
 }
 
 impl IsolatedRuntime {
-    /// Creates a new isolated runtime.
+    /// Creates a new isolated runtime with the default configuration and
+    /// metrics that are not connected to any registry.
     pub fn new() -> IsolatedRuntime {
-        // TODO: choose a more principled `worker_limit`. Right now we use the
-        // Tokio default, which is presently the number of cores on the machine.
+        Self::new_with_config(
+            &IsolatedRuntimeConfig::default(),
+            IsolatedRuntimeMetrics::new(&MetricsRegistry::new()),
+        )
+    }
+
+    /// Creates a new isolated runtime with the given configuration.
+    pub fn new_with_config(
+        cfg: &IsolatedRuntimeConfig,
+        metrics: IsolatedRuntimeMetrics,
+    ) -> IsolatedRuntime {
+        let worker_threads = std::cmp::max(cfg.worker_threads, 1);
+        let thread_name_prefix = cfg.thread_name_prefix.clone();
+        let next_id = AtomicUsize::new(0);
         let runtime = Builder::new_multi_thread()
-            .thread_name_fn(|| {
-                static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
-                let id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
+            .worker_threads(worker_threads)
+            .thread_name_fn(move || {
+                let id = next_id.fetch_add(1, Ordering::SeqCst);
                 // This will wrap around eventually, which is not ideal, but it's important that
                 // it stays small to fit within OS limits.
-                format!("persist:{:04x}", id % 0x10000)
+                format!("{}:{:04x}", thread_name_prefix, id % 0x10000)
             })
             .enable_all()
             .build()
             .expect("known to be valid");
+        metrics.workers.set(u64::cast_from(worker_threads));
         IsolatedRuntime {
             inner: Some(runtime),
+            metrics,
+            compaction_queue: Arc::new(CompactionQueue::default()),
         }
     }
 
+    /// Returns the number of worker threads of this runtime.
+    pub fn worker_threads(&self) -> usize {
+        self.inner
+            .as_ref()
+            .expect("exists until drop")
+            .metrics()
+            .num_workers()
+    }
+
     /// Spawns a task onto this runtime.
     pub fn spawn_named<N, S, F>(&self, name: N, fut: F) -> JoinHandle<F::Output>
     where
@@ -59,11 +139,85 @@ impl IsolatedRuntime {
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
+        self.metrics.queue_depth.inc();
+        let queued = DecOnDrop(self.metrics.queue_depth.clone());
+        let busy_seconds = self.metrics.busy_seconds.clone();
+        let fut = async move {
+            let _queued = queued;
+            let mut fut = std::pin::pin!(fut);
+            std::future::poll_fn(|cx| {
+                let start = Instant::now();
+                let ret = fut.as_mut().poll(cx);
+                busy_seconds.inc_by(start.elapsed().as_secs_f64());
+                ret
+            })
+            .await
+        };
         self.inner
             .as_ref()
             .expect("exists until drop")
             .spawn_named(name, fut)
     }
+
+    /// Runs `fut` as a task on this runtime, converting a panic in the task
+    /// into an [InternalPanic].
+    ///
+    /// Callers decide whether the error can be returned to the user (e.g. for
+    /// reads) or whether continuing is unsafe and the panic must be re-raised
+    /// with [InternalPanic::abort] (e.g. for operations that mutate durable
+    /// state).
+    ///
+    /// The panic is caught with [OreFutureExt::ore_catch_unwind], so this also
+    /// works in processes that abort on panic (see
+    /// [mz_ore::panic::set_abort_on_panic]), which our binaries do. A plain
+    /// [tokio::task::JoinError] would never be observed there. Builds with
+    /// `panic = "abort"` can't unwind at all, so there the process still aborts
+    /// instead of returning an [InternalPanic].
+    pub(crate) fn spawn_catching<F>(
+        &self,
+        operation: impl Into<String>,
+        fut: F,
+    ) -> impl Future<Output = Result<F::Output, InternalPanic>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let operation = operation.into();
+        let handle = self.spawn_named(
+            || operation.clone(),
+            AssertUnwindSafe(fut).ore_catch_unwind(),
+        );
+        let panics = self.metrics.panics.clone();
+        async move {
+            let payload = match handle.await {
+                Ok(Ok(output)) => return Ok(output),
+                Ok(Err(payload)) => payload,
+                // Panics that escape ore_catch_unwind, e.g. from dropping the
+                // future.
+                Err(err) if err.is_panic() => err.into_panic(),
+                // The only way a task on the isolated runtime gets cancelled
+                // is the runtime shutting down.
+                Err(_) => {
+                    return Err(InternalPanic {
+                        operation,
+                        message: "task cancelled by runtime shutdown".into(),
+                    })
+                }
+            };
+            panics.inc();
+            Err(InternalPanic::from_payload(operation, payload))
+        }
+    }
+}
+
+/// Decrements the queue depth of an [IsolatedRuntime] when a task completes or
+/// is dropped.
+struct DecOnDrop(UIntGauge);
+
+impl Drop for DecOnDrop {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
 }
 
 impl Drop for IsolatedRuntime {
@@ -77,3 +231,51 @@ impl Drop for IsolatedRuntime {
             .shutdown_background()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::{Barrier, Mutex};
+
+    use super::*;
+
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    fn isolated_runtime_worker_threads() {
+        let cfg = IsolatedRuntimeConfig {
+            worker_threads: 3,
+            thread_name_prefix: "test".into(),
+        };
+        let metrics = IsolatedRuntimeMetrics::new(&MetricsRegistry::new());
+        let runtime = IsolatedRuntime::new_with_config(&cfg, metrics.clone());
+        assert_eq!(runtime.worker_threads(), 3);
+        assert_eq!(metrics.workers.get(), 3);
+
+        // Block every worker on a barrier, which can only be passed if the
+        // runtime has (at least) the configured number of threads.
+        let barrier = Arc::new(Barrier::new(cfg.worker_threads));
+        let names = Arc::new(Mutex::new(BTreeSet::new()));
+        let handles = (0..cfg.worker_threads)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                let names = Arc::clone(&names);
+                runtime.spawn_named(|| "barrier", async move {
+                    let name = std::thread::current().name().map(String::from);
+                    names.lock().expect("lock poisoned").insert(name);
+                    barrier.wait();
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            futures::executor::block_on(handle).expect("task succeeded");
+        }
+
+        let names = names.lock().expect("lock poisoned");
+        assert_eq!(names.len(), cfg.worker_threads);
+        for name in names.iter() {
+            let name = name.as_deref().expect("worker threads are named");
+            assert!(name.starts_with("test:"), "unexpected thread name {}", name);
+        }
+        assert_eq!(metrics.queue_depth.get(), 0);
+    }
+}
@@ -26,7 +26,7 @@ use futures_util::stream::{FuturesUnordered, StreamExt};
 use mz_ore::cast::CastFrom;
 use mz_ore::task::{JoinHandle, JoinHandleExt};
 use mz_persist::indexed::columnar::{ColumnarRecords, ColumnarRecordsBuilder};
-use mz_persist::indexed::encoding::BlobTraceBatchPart;
+use mz_persist::indexed::encoding::{BlobCompression, BlobTraceBatchPart};
 use mz_persist::location::{Atomicity, Blob};
 use mz_persist_types::stats::{trim_to_budget, truncate_bytes, TruncateBound, TRUNCATE_LEN};
 use mz_persist_types::{Codec, Codec64};
@@ -49,7 +49,7 @@ use crate::internal::paths::{PartId, PartialBatchKey, WriterKey};
 use crate::internal::state::{HollowBatch, HollowBatchPart};
 use crate::stats::PartStats;
 use crate::write::WriterId;
-use crate::{PersistConfig, ShardId};
+use crate::{PersistConfig, SchemaId, ShardId, ShardTuning};
 
 include!(concat!(env!("OUT_DIR"), "/mz_persist_client.batch.rs"));
 
@@ -141,6 +141,72 @@ where
         self.batch.desc.lower()
     }
 
+    /// Merges this [Batch] with `other`, which must immediately follow it, into
+    /// a single batch spanning `[self.lower(), other.upper())`.
+    ///
+    /// The merged batch references the already uploaded parts of both batches,
+    /// so nothing is re-uploaded. Because the two batches cover disjoint
+    /// intervals of time, their updates can't consolidate with each other, and
+    /// the merged batch is exactly as consolidated as its inputs.
+    ///
+    /// Returns an error if the batches are from different shards or if
+    /// `self.upper()` is not equal to `other.lower()`. In that case both
+    /// batches are handed back untouched, so that the caller can still append
+    /// or [Self::delete] them.
+    #[allow(clippy::result_large_err)]
+    pub fn merge(mut self, mut other: Self) -> Result<Self, (InvalidUsage<T>, Self, Self)> {
+        if self.shard_id != other.shard_id {
+            let err = InvalidUsage::BatchNotFromThisShard {
+                batch_shard: other.shard_id,
+                handle_shard: self.shard_id,
+            };
+            return Err((err, self, other));
+        }
+        if self.upper() != other.lower() {
+            let err = InvalidUsage::NonAdjacentBatches {
+                upper: self.upper().clone(),
+                lower: other.lower().clone(),
+            };
+            return Err((err, self, other));
+        }
+
+        let desc = Description::new(
+            self.lower().clone(),
+            other.upper().clone(),
+            self.batch.desc.since().join(other.batch.desc.since()),
+        );
+        let (mut parts, mut runs) = (vec![], vec![]);
+        for batch in [&self.batch, &other.batch] {
+            for run in batch.runs() {
+                // Mark the boundary if this is not the first run in the batch.
+                let start_index = parts.len();
+                if start_index != 0 {
+                    runs.push(start_index);
+                }
+                parts.extend_from_slice(run);
+            }
+        }
+        let batch = HollowBatch {
+            desc,
+            parts,
+            len: self.batch.len + other.batch.len,
+            runs,
+        };
+        // Keep the newer of the two versions, so that appending the merged
+        // batch still notices data from the future.
+        let version = std::cmp::max(&self.version, &other.version).clone();
+        self.mark_consumed();
+        other.mark_consumed();
+        Ok(Self::new(
+            self.batch_delete_enabled,
+            Arc::clone(&self.metrics),
+            Arc::clone(&self.blob),
+            self.shard_id,
+            version,
+            batch,
+        ))
+    }
+
     /// Marks the blobs that this batch handle points to as consumed, likely
     /// because they were appended to a shard.
     ///
@@ -186,10 +252,17 @@ where
     /// transfer this batch across process boundaries, for example when
     /// exchanging data between timely workers.
     ///
+    /// The returned `ProtoBatch` only references the already uploaded parts of
+    /// the batch, so it's cheap to send over the network. It can be appended by
+    /// any [`WriteHandle`](crate::write::WriteHandle) of the same shard,
+    /// including one in a different process.
+    ///
     /// **NOTE**: If this batch is not eventually appended to a shard or
     /// dropped, the data that it represents will have leaked. The caller is
     /// responsible for turning this back into a [`Batch`] using
     /// [`WriteHandle::batch_from_transmittable_batch`](crate::write::WriteHandle::batch_from_transmittable_batch).
+    /// Until then, the parts are safe from GC, which only deletes blobs that
+    /// were once referenced by the shard's state.
     pub fn into_transmittable_batch(mut self) -> ProtoBatch {
         let ret = ProtoBatch {
             shard_id: self.shard_id.into_proto(),
@@ -217,11 +290,14 @@ pub enum Added {
 pub struct BatchBuilderConfig {
     writer_key: WriterKey,
     pub(crate) blob_target_size: usize,
+    pub(crate) consolidation_buffer: usize,
     pub(crate) batch_delete_enabled: bool,
     pub(crate) batch_builder_max_outstanding_parts: usize,
     pub(crate) stats_collection_enabled: bool,
     pub(crate) stats_budget: usize,
     pub(crate) stats_untrimmable_columns: Arc<UntrimmableColumns>,
+    pub(crate) schema_id: Option<SchemaId>,
+    pub(crate) blob_compression: BlobCompression,
 }
 
 // TODO: Remove this once we're comfortable that there aren't any bugs.
@@ -231,13 +307,39 @@ pub(crate) const BATCH_DELETE_ENABLED: Config<bool> = Config::new(
     "Whether to actually delete blobs when batch delete is called (Materialize).",
 );
 
+pub(crate) const BATCH_BUILDER_CONSOLIDATION_BUFFER: Config<usize> = Config::new(
+    "persist_batch_builder_consolidation_buffer",
+    0,
+    "The number of updates a batch builder buffers between intermediate \
+    consolidations, or 0 to only consolidate (if at all) when writing a part.",
+);
+
+pub(crate) const BLOB_COMPRESSION: Config<String> = Config::new(
+    "persist_blob_compression",
+    "none",
+    "The compression codec for newly written batch parts: `none`, `zstd`, or \
+    `zstd:<level>` with a level between 1 and 22. Binaries from before zstd \
+    support can't read zstd parts, so don't enable it until every reader of \
+    the shard has been upgraded.",
+);
+
 impl BatchBuilderConfig {
     /// Initialize a batch builder config based on a snapshot of the Persist config.
     pub fn new(value: &PersistConfig, _writer_id: &WriterId) -> Self {
         let writer_key = WriterKey::for_version(&value.build_version);
+        let blob_compression = BLOB_COMPRESSION.get(&value.configs);
+        let blob_compression = blob_compression.parse().unwrap_or_else(|err| {
+            warn!(
+                "invalid {}, writing uncompressed parts: {}",
+                BLOB_COMPRESSION.name(),
+                err
+            );
+            BlobCompression::None
+        });
         BatchBuilderConfig {
             writer_key,
             blob_target_size: value.dynamic.blob_target_size(),
+            consolidation_buffer: BATCH_BUILDER_CONSOLIDATION_BUFFER.get(&value.configs),
             batch_delete_enabled: BATCH_DELETE_ENABLED.get(&value.configs),
             batch_builder_max_outstanding_parts: value
                 .dynamic
@@ -245,7 +347,20 @@ impl BatchBuilderConfig {
             stats_collection_enabled: value.dynamic.stats_collection_enabled(),
             stats_budget: value.dynamic.stats_budget_bytes(),
             stats_untrimmable_columns: Arc::new(value.dynamic.stats_untrimmable_columns()),
+            schema_id: None,
+            blob_compression,
+        }
+    }
+
+    /// Applies the per-shard overrides in `tuning` on top of this config.
+    pub(crate) fn with_tuning(mut self, tuning: &ShardTuning) -> Self {
+        if let Some(blob_target_size) = tuning.blob_target_size {
+            self.blob_target_size = blob_target_size;
+        }
+        if let Some(max_outstanding_parts) = tuning.batch_builder_max_outstanding_parts {
+            self.batch_builder_max_outstanding_parts = max_outstanding_parts;
         }
+        self
     }
 }
 
@@ -365,6 +480,42 @@ where
             .add(&self.stats_schemas, key, val, ts, diff)
             .await
     }
+
+    /// Adds the given updates to the batch.
+    ///
+    /// This is equivalent to calling [Self::add] for each update in order, but
+    /// only awaits when a part needs to be flushed to blob storage. If any
+    /// update timestamp is not greater or equal to `lower`, an error is
+    /// returned and none of the updates are added.
+    pub async fn add_many(&mut self, updates: &[((K, V), T, D)]) -> Result<Added, InvalidUsage<T>> {
+        self.builder.add_many(&self.stats_schemas, updates).await
+    }
+
+    /// Adds the given already-encoded updates to the batch.
+    ///
+    /// The keys and vals must have been encoded with the [Codec] impls of `K`
+    /// and `V`. Otherwise, this behaves like [Self::add_many].
+    pub async fn add_columnar(
+        &mut self,
+        updates: &ColumnarRecords,
+    ) -> Result<Added, InvalidUsage<T>> {
+        self.builder
+            .add_columnar(&self.stats_schemas, updates)
+            .await
+    }
+
+    /// Opts this builder into consolidating updates by key, value, and
+    /// timestamp before they're written to blob storage.
+    ///
+    /// This is intended for callers that add many updates that could be
+    /// consolidated with each other, which would otherwise pay to write them
+    /// out and then again to compact them away. Up to `budget_bytes` of updates
+    /// (or the target part size, if that's larger) are buffered in memory.
+    /// Once the buffer is full, it's consolidated in place and, if that didn't
+    /// free up at least half of the budget, spilled to blob storage as normal.
+    pub fn consolidate_before_write(&mut self, budget_bytes: usize) {
+        self.builder.buffer.consolidate_before_write(budget_bytes);
+    }
 }
 
 #[derive(Debug)]
@@ -441,6 +592,7 @@ where
                 Arc::clone(&metrics),
                 batch_write_metrics,
                 cfg.blob_target_size,
+                cfg.consolidation_buffer,
                 consolidate,
             ),
             metrics,
@@ -496,8 +648,9 @@ where
             }
         }
 
-        let (key_lower, remainder) = self.buffer.drain();
-        self.flush_part(stats_schemas, key_lower, remainder).await;
+        for (key_lower, remainder) in self.buffer.drain() {
+            self.flush_part(stats_schemas, key_lower, remainder).await;
+        }
 
         let batch_delete_enabled = self.parts.cfg.batch_delete_enabled;
         let parts = self.parts.finish().await;
@@ -520,6 +673,13 @@ where
         Ok(batch)
     }
 
+    /// Abandons this batch, deleting any parts of it that were already written.
+    ///
+    /// Returns the number of parts deleted.
+    pub async fn abort(self) -> usize {
+        self.parts.abort().await
+    }
+
     /// Adds the given update to the batch.
     ///
     /// The update timestamp must be greater or equal to `lower` that was given
@@ -532,23 +692,84 @@ where
         ts: &T,
         diff: &D,
     ) -> Result<Added, InvalidUsage<T>> {
-        if !self.lower.less_equal(ts) {
-            return Err(InvalidUsage::UpdateNotBeyondLower {
-                ts: ts.clone(),
-                lower: self.lower.clone(),
-            });
-        }
+        self.validate_lower(ts)?;
 
         self.inclusive_upper.insert(Reverse(ts.clone()));
 
-        match self.buffer.push(key, val, ts.clone(), diff.clone()) {
-            Some((key_lower, part_to_flush)) => {
+        let parts_to_flush = self.buffer.push(key, val, ts.clone(), diff.clone());
+        if parts_to_flush.is_empty() {
+            return Ok(Added::Record);
+        }
+        for (key_lower, part_to_flush) in parts_to_flush {
+            self.flush_part(stats_schemas, key_lower, part_to_flush)
+                .await;
+        }
+        Ok(Added::RecordAndParts)
+    }
+
+    /// Adds the given updates to the batch.
+    ///
+    /// This is equivalent to calling [Self::add] for each update in order, but
+    /// only awaits when a part needs to be flushed. Every update timestamp is
+    /// validated against `lower` before any of them are buffered, so an error
+    /// leaves the batch unchanged.
+    pub async fn add_many<StatsK: Codec, StatsV: Codec>(
+        &mut self,
+        stats_schemas: &Schemas<StatsK, StatsV>,
+        updates: &[((K, V), T, D)],
+    ) -> Result<Added, InvalidUsage<T>> {
+        for (_, ts, _) in updates {
+            self.validate_lower(ts)?;
+        }
+
+        let mut added = Added::Record;
+        for ((key, val), ts, diff) in updates {
+            self.inclusive_upper.insert(Reverse(ts.clone()));
+            for (key_lower, part_to_flush) in self.buffer.push(key, val, ts.clone(), diff.clone()) {
                 self.flush_part(stats_schemas, key_lower, part_to_flush)
                     .await;
-                Ok(Added::RecordAndParts)
+                added = Added::RecordAndParts;
             }
-            None => Ok(Added::Record),
         }
+        Ok(added)
+    }
+
+    /// Adds the given already-encoded updates to the batch.
+    ///
+    /// The keys and vals must have been encoded with the [Codec] impls of `K`
+    /// and `V`. Otherwise, this behaves like [Self::add_many].
+    pub async fn add_columnar<StatsK: Codec, StatsV: Codec>(
+        &mut self,
+        stats_schemas: &Schemas<StatsK, StatsV>,
+        updates: &ColumnarRecords,
+    ) -> Result<Added, InvalidUsage<T>> {
+        for (_, ts, _) in updates.iter() {
+            self.validate_lower(&T::decode(ts))?;
+        }
+
+        let mut added = Added::Record;
+        for ((key, val), ts, diff) in updates.iter() {
+            let ts = T::decode(ts);
+            self.inclusive_upper.insert(Reverse(ts.clone()));
+            for (key_lower, part_to_flush) in
+                self.buffer.push_encoded(key, val, ts, D::decode(diff))
+            {
+                self.flush_part(stats_schemas, key_lower, part_to_flush)
+                    .await;
+                added = Added::RecordAndParts;
+            }
+        }
+        Ok(added)
+    }
+
+    fn validate_lower(&self, ts: &T) -> Result<(), InvalidUsage<T>> {
+        if !self.lower.less_equal(ts) {
+            return Err(InvalidUsage::UpdateNotBeyondLower {
+                ts: ts.clone(),
+                lower: self.lower.clone(),
+            });
+        }
+        Ok(())
     }
 
     /// Flushes the current part to Blob storage, first consolidating and then
@@ -629,6 +850,10 @@ struct BatchBuffer<T, D> {
     metrics: Arc<Metrics>,
     batch_write_metrics: BatchWriteMetrics,
     blob_target_size: usize,
+    consolidation_buffer: usize,
+    /// The memory budget for consolidating updates before they're written, if
+    /// the builder has opted into it.
+    consolidation_budget: Option<usize>,
     consolidate: bool,
 
     key_buf: Vec<u8>,
@@ -638,6 +863,8 @@ struct BatchBuffer<T, D> {
     current_part_total_bytes: usize,
     current_part_key_bytes: usize,
     current_part_value_bytes: usize,
+    updates_since_consolidation: usize,
+    updates_since_drain: usize,
 }
 
 impl<T, D> BatchBuffer<T, D>
@@ -649,12 +876,15 @@ where
         metrics: Arc<Metrics>,
         batch_write_metrics: BatchWriteMetrics,
         blob_target_size: usize,
+        consolidation_buffer: usize,
         should_consolidate: bool,
     ) -> Self {
         BatchBuffer {
             metrics,
             batch_write_metrics,
             blob_target_size,
+            consolidation_buffer,
+            consolidation_budget: None,
             consolidate: should_consolidate,
             key_buf: Default::default(),
             val_buf: Default::default(),
@@ -662,16 +892,23 @@ where
             current_part_total_bytes: Default::default(),
             current_part_key_bytes: Default::default(),
             current_part_value_bytes: Default::default(),
+            updates_since_consolidation: Default::default(),
+            updates_since_drain: Default::default(),
         }
     }
 
+    /// See [BatchBuilder::consolidate_before_write].
+    fn consolidate_before_write(&mut self, budget_bytes: usize) {
+        self.consolidation_budget = Some(std::cmp::max(budget_bytes, self.blob_target_size));
+    }
+
     fn push<K: Codec, V: Codec>(
         &mut self,
         key: &K,
         val: &V,
         ts: T,
         diff: D,
-    ) -> Option<(Vec<u8>, ColumnarRecords)> {
+    ) -> Vec<(Vec<u8>, ColumnarRecords)> {
         let initial_key_buf_len = self.key_buf.len();
         let initial_val_buf_len = self.val_buf.len();
         self.metrics
@@ -684,28 +921,120 @@ where
             .encode(|| V::encode(val, &mut self.val_buf));
         let k_range = initial_key_buf_len..self.key_buf.len();
         let v_range = initial_val_buf_len..self.val_buf.len();
+        self.push_ranges(k_range, v_range, ts, diff)
+    }
+
+    /// Like [Self::push], but for a key and val that have already been
+    /// encoded.
+    fn push_encoded(
+        &mut self,
+        key: &[u8],
+        val: &[u8],
+        ts: T,
+        diff: D,
+    ) -> Vec<(Vec<u8>, ColumnarRecords)> {
+        let initial_key_buf_len = self.key_buf.len();
+        let initial_val_buf_len = self.val_buf.len();
+        self.key_buf.extend_from_slice(key);
+        self.val_buf.extend_from_slice(val);
+        let k_range = initial_key_buf_len..self.key_buf.len();
+        let v_range = initial_val_buf_len..self.val_buf.len();
+        self.push_ranges(k_range, v_range, ts, diff)
+    }
+
+    /// Records an update whose key and val have already been written to the
+    /// given ranges of the key and val buffers, returning any parts to flush
+    /// if the buffer is full.
+    fn push_ranges(
+        &mut self,
+        k_range: Range<usize>,
+        v_range: Range<usize>,
+        ts: T,
+        diff: D,
+    ) -> Vec<(Vec<u8>, ColumnarRecords)> {
         let size = ColumnarRecordsBuilder::columnar_record_size(k_range.len(), v_range.len());
 
         self.current_part_total_bytes += size;
         self.current_part_key_bytes += k_range.len();
         self.current_part_value_bytes += v_range.len();
         self.current_part.push(((k_range, v_range), ts, diff));
+        self.updates_since_consolidation += 1;
+        self.updates_since_drain += 1;
 
-        // if we've filled up a batch part, flush out to blob to keep our memory usage capped.
-        if self.current_part_total_bytes >= self.blob_target_size {
-            Some(self.drain())
-        } else {
-            None
+        if self.consolidation_buffer > 0
+            && self.updates_since_consolidation >= self.consolidation_buffer
+        {
+            self.consolidate_current_part();
+        }
+
+        match self.consolidation_budget {
+            // if we've filled up a batch part, flush out to blob to keep our memory usage capped.
+            None if self.current_part_total_bytes >= self.blob_target_size => self.drain(),
+            Some(budget) if self.current_part_total_bytes >= budget => {
+                self.consolidate_current_part();
+                // Keep buffering only if consolidating freed up a good chunk
+                // of the budget, otherwise we'd spend most of our time
+                // re-consolidating the same updates.
+                if self.current_part_total_bytes >= budget / 2 {
+                    self.drain()
+                } else {
+                    Vec::new()
+                }
+            }
+            None | Some(_) => Vec::new(),
         }
     }
 
-    fn drain(&mut self) -> (Vec<u8>, ColumnarRecords) {
+    /// Consolidates the updates buffered so far, dropping any that cancel out,
+    /// and reclaims the space they took up in the key and val buffers.
+    fn consolidate_current_part(&mut self) {
+        let start = Instant::now();
         let mut updates = Vec::with_capacity(self.current_part.len());
         for ((k_range, v_range), t, d) in self.current_part.drain(..) {
             updates.push(((&self.key_buf[k_range], &self.val_buf[v_range]), t, d));
         }
+        consolidate_updates(&mut updates);
 
-        if self.consolidate {
+        let mut key_buf = Vec::with_capacity(updates.iter().map(|((k, _), _, _)| k.len()).sum());
+        let mut val_buf = Vec::with_capacity(updates.iter().map(|((_, v), _, _)| v.len()).sum());
+        self.current_part_total_bytes = 0;
+        for ((k, v), t, d) in updates {
+            let k_range = key_buf.len()..key_buf.len() + k.len();
+            let v_range = val_buf.len()..val_buf.len() + v.len();
+            key_buf.extend_from_slice(k);
+            val_buf.extend_from_slice(v);
+            self.current_part_total_bytes +=
+                ColumnarRecordsBuilder::columnar_record_size(k_range.len(), v_range.len());
+            self.current_part.push(((k_range, v_range), t, d));
+        }
+        self.current_part_key_bytes = key_buf.len();
+        self.current_part_value_bytes = val_buf.len();
+        self.key_buf = key_buf;
+        self.val_buf = val_buf;
+        self.updates_since_consolidation = 0;
+
+        self.batch_write_metrics
+            .step_consolidation
+            .inc_by(start.elapsed().as_secs_f64());
+    }
+
+    /// Drains the buffered updates into columnar parts, each paired with a
+    /// lower bound on its keys.
+    ///
+    /// When the builder has been buffering more than a part's worth of updates
+    /// for consolidation, they're split into parts of the target size.
+    fn drain(&mut self) -> Vec<(Vec<u8>, ColumnarRecords)> {
+        self.updates_since_consolidation = 0;
+        let mut updates = Vec::with_capacity(self.current_part.len());
+        for ((k_range, v_range), t, d) in self.current_part.drain(..) {
+            updates.push(((&self.key_buf[k_range], &self.val_buf[v_range]), t, d));
+        }
+
+        // Consolidate if the caller asked for consolidated parts or if we've
+        // been asked to consolidate along the way, so that any updates that
+        // cancel out since the last intermediate consolidation are dropped.
+        if self.consolidate || self.consolidation_buffer > 0 || self.consolidation_budget.is_some()
+        {
             let start = Instant::now();
             consolidate_updates(&mut updates);
             self.batch_write_metrics
@@ -713,50 +1042,81 @@ where
                 .inc_by(start.elapsed().as_secs_f64());
         }
 
-        if updates.is_empty() {
-            self.key_buf.clear();
-            self.val_buf.clear();
-            return (vec![], ColumnarRecordsBuilder::default().finish());
+        self.batch_write_metrics
+            .updates_in
+            .inc_by(u64::cast_from(self.updates_since_drain));
+        self.batch_write_metrics
+            .updates_written
+            .inc_by(u64::cast_from(updates.len()));
+        self.updates_since_drain = 0;
+
+        // Without a consolidation budget, we never buffer more than a part's
+        // worth of updates, so there's no need to split them up.
+        let max_part_bytes = match self.consolidation_budget {
+            Some(_) => self.blob_target_size,
+            None => usize::MAX,
+        };
+        let start = Instant::now();
+        let mut parts = Vec::new();
+        let mut part_start = 0;
+        let mut part_bytes = 0;
+        for (idx, ((k, v), _, _)) in updates.iter().enumerate() {
+            part_bytes += ColumnarRecordsBuilder::columnar_record_size(k.len(), v.len());
+            if part_bytes >= max_part_bytes || idx + 1 == updates.len() {
+                parts.push(Self::encode_part(
+                    &updates[part_start..=idx],
+                    self.consolidate,
+                ));
+                part_start = idx + 1;
+                part_bytes = 0;
+            }
         }
+        if !parts.is_empty() {
+            self.batch_write_metrics
+                .step_columnar_encoding
+                .inc_by(start.elapsed().as_secs_f64());
+        }
+
+        self.key_buf.clear();
+        self.val_buf.clear();
+        self.current_part_total_bytes = 0;
+        self.current_part_key_bytes = 0;
+        self.current_part_value_bytes = 0;
+        assert_eq!(self.current_part.len(), 0);
 
+        parts
+    }
+
+    /// Columnar encodes a non-empty run of updates into a part, returning it
+    /// along with a lower bound on its keys.
+    fn encode_part(
+        updates: &[((&[u8], &[u8]), T, D)],
+        consolidated: bool,
+    ) -> (Vec<u8>, ColumnarRecords) {
         let ((mut key_lower, _), _, _) = &updates[0];
-        let start = Instant::now();
         let mut builder = ColumnarRecordsBuilder::default();
         builder.reserve_exact(
-            self.current_part.len(),
-            self.current_part_key_bytes,
-            self.current_part_value_bytes,
+            updates.len(),
+            updates.iter().map(|((k, _), _, _)| k.len()).sum(),
+            updates.iter().map(|((_, v), _, _)| v.len()).sum(),
         );
         for ((k, v), t, d) in updates {
-            if self.consolidate {
+            if consolidated {
                 debug_assert!(
-                    key_lower <= k,
+                    key_lower <= *k,
                     "consolidated data should be presented in order"
                 )
             } else {
-                key_lower = k.min(key_lower);
+                key_lower = (*k).min(key_lower);
             }
             // if this fails, the individual record is too big to fit in a ColumnarRecords by itself.
             // The limits are big, so this is a pretty extreme case that we intentionally don't handle
             // right now.
-            assert!(builder.push(((k, v), T::encode(&t), D::encode(&d))));
+            assert!(builder.push(((*k, *v), T::encode(t), D::encode(d))));
         }
         let key_lower = truncate_bytes(key_lower, TRUNCATE_LEN, TruncateBound::Lower)
             .expect("lower bound always exists");
-        let columnar = builder.finish();
-
-        self.batch_write_metrics
-            .step_columnar_encoding
-            .inc_by(start.elapsed().as_secs_f64());
-
-        self.key_buf.clear();
-        self.val_buf.clear();
-        self.current_part_total_bytes = 0;
-        self.current_part_key_bytes = 0;
-        self.current_part_value_bytes = 0;
-        assert_eq!(self.current_part.len(), 0);
-
-        (key_lower, columnar)
+        (key_lower, builder.finish())
     }
 }
 
@@ -822,6 +1182,8 @@ impl<T: Timestamp + Codec64> BatchParts<T> {
         let stats_budget = self.cfg.stats_budget;
         let schemas = schemas.clone();
         let untrimmable_columns = Arc::clone(&self.cfg.stats_untrimmable_columns);
+        let schema_id = self.cfg.schema_id;
+        let blob_compression = self.cfg.blob_compression;
 
         let write_span = debug_span!("batch::write_part", shard = %self.shard_id).or_current();
         let handle = mz_ore::task::spawn(
@@ -834,8 +1196,10 @@ impl<T: Timestamp + Codec64> BatchParts<T> {
                     index,
                 };
 
-                let (stats, (buf, encode_time)) = isolated_runtime
-                    .spawn_named(|| "batch::encode_part", async move {
+                // There's no error path out of batch building and continuing
+                // would silently drop this part, so a panic here is fatal.
+                let (stats, (buf, checksum, encode_time)) = isolated_runtime
+                    .spawn_catching("batch::encode_part", async move {
                         let stats = if stats_collection_enabled {
                             let stats_start = Instant::now();
                             match PartStats::legacy_part_format(&schemas, &batch.updates) {
@@ -859,15 +1223,16 @@ impl<T: Timestamp + Codec64> BatchParts<T> {
 
                         let encode_start = Instant::now();
                         let mut buf = Vec::new();
-                        batch.encode(&mut buf);
+                        batch.encode_with_compression(&mut buf, blob_compression);
+                        let checksum = crc32fast::hash(&buf);
 
                         // Drop batch as soon as we can to reclaim its memory.
                         drop(batch);
-                        (stats, (Bytes::from(buf), encode_start.elapsed()))
+                        (stats, (Bytes::from(buf), checksum, encode_start.elapsed()))
                     })
                     .instrument(debug_span!("batch::encode_part"))
                     .await
-                    .expect("part encode task failed");
+                    .unwrap_or_else(|err| err.abort());
                 // Can't use the `CodecMetrics::encode` helper because of async.
                 metrics.codecs.batch.encode_count.inc();
                 metrics
@@ -887,6 +1252,9 @@ impl<T: Timestamp + Codec64> BatchParts<T> {
                 .await;
                 batch_metrics.seconds.inc_by(start.elapsed().as_secs_f64());
                 batch_metrics.bytes.inc_by(u64::cast_from(payload_len));
+                shard_metrics
+                    .batch_bytes
+                    .inc_by(u64::cast_from(payload_len));
                 batch_metrics.goodbytes.inc_by(u64::cast_from(goodbytes));
                 let stats = stats.map(|(stats, stats_step_timing, trimmed_bytes)| {
                     batch_metrics
@@ -907,6 +1275,8 @@ impl<T: Timestamp + Codec64> BatchParts<T> {
                     encoded_size_bytes: payload_len,
                     key_lower,
                     stats,
+                    schema_id,
+                    checksum: Some(checksum),
                 }
             }
             .instrument(write_span),
@@ -936,6 +1306,22 @@ impl<T: Timestamp + Codec64> BatchParts<T> {
         }
         parts
     }
+
+    /// Waits for any outstanding part uploads, and then deletes every part
+    /// written so far.
+    ///
+    /// Returns the number of parts deleted.
+    pub(crate) async fn abort(self) -> usize {
+        let metrics = Arc::clone(&self.metrics);
+        let blob = Arc::clone(&self.blob);
+        let shard_id = self.shard_id;
+        let parts = self.finish().await;
+        for part in parts.iter() {
+            let key = part.key.complete(&shard_id);
+            retry_external(&metrics.retries.external.batch_delete, || blob.delete(&key)).await;
+        }
+        parts.len()
+    }
 }
 
 pub(crate) fn validate_truncate_batch<T: Timestamp>(
@@ -958,12 +1344,146 @@ pub(crate) fn validate_truncate_batch<T: Timestamp>(
 #[cfg(test)]
 mod tests {
     use crate::cache::PersistClientCache;
+    use crate::dyn_cfg::ConfigGuard;
+    use crate::error::SnapshotError;
+    use crate::fetch::{
+        fetch_batch_part, retry_leased_fetch, FetchBatchError, CHECKSUM_MISMATCH_MAX_REFETCHES,
+    };
     use crate::internal::paths::{BlobKey, PartialBlobKey};
-    use crate::tests::{all_ok, CodecProduct};
+    use crate::tests::{all_ok, new_test_client, new_test_shard, test_data, CodecProduct};
     use crate::PersistLocation;
 
     use super::*;
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_builder_consolidation_buffer() {
+        let updates = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("1".to_owned(), "one".to_owned()), 1, -1),
+            (("2".to_owned(), "two".to_owned()), 2, 3),
+            (("2".to_owned(), "two".to_owned()), 2, -1),
+            (("2".to_owned(), "two".to_owned()), 2, -2),
+            (("3".to_owned(), "three".to_owned()), 3, 2),
+            (("3".to_owned(), "three".to_owned()), 3, -2),
+        ];
+
+        let (client, mut write, _) = new_test_shard().await;
+
+        let _guard = ConfigGuard::new(&client.cfg.configs);
+        for consolidation_buffer in [1, 2, 3, 100] {
+            client
+                .cfg
+                .set_config(&BATCH_BUILDER_CONSOLIDATION_BUFFER, consolidation_buffer);
+            // Feed the updates in a variety of orders, including ones where
+            // updates that cancel are split across intermediate consolidations.
+            let mut orders = Vec::new();
+            for i in 0..updates.len() {
+                let mut order = updates.clone();
+                order.rotate_left(i);
+                orders.push(order.clone());
+                order.reverse();
+                orders.push(order);
+            }
+            for order in orders {
+                let batch = write.expect_batch(&order, 0, 4).await;
+                assert_eq!(batch.batch.len, 0, "{:?}", order);
+                assert_eq!(batch.batch.parts.len(), 0, "{:?}", order);
+            }
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_builder_blob_compression() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 0, 1),
+            (("2".to_owned(), "two".to_owned()), 1, 1),
+            (("3".to_owned(), "three".to_owned()), 2, 1),
+        ];
+
+        let (client, mut write, mut read) = new_test_shard().await;
+
+        let _guard = ConfigGuard::new(&client.cfg.configs);
+        let writer_id = WriterId::new();
+        for (compression, expected) in [
+            ("zstd:5", BlobCompression::Zstd { level: 5 }),
+            ("none", BlobCompression::None),
+            // Invalid values fall back to writing uncompressed parts.
+            ("zstd:100", BlobCompression::None),
+            ("lz4", BlobCompression::None),
+        ] {
+            client
+                .cfg
+                .set_config(&BLOB_COMPRESSION, compression.to_owned());
+            let cfg = BatchBuilderConfig::new(&client.cfg, &writer_id);
+            assert_eq!(cfg.blob_compression, expected, "{}", compression);
+        }
+
+        // Write each batch with a different codec. The shard then has a mix of
+        // them, all of which read back.
+        for (update, compression) in data.iter().zip(["none", "zstd", "zstd:19"]) {
+            client
+                .cfg
+                .set_config(&BLOB_COMPRESSION, compression.to_owned());
+            let ts = update.1;
+            write
+                .expect_append(&[update.clone()], vec![ts], vec![ts + 1])
+                .await;
+        }
+        assert_eq!(read.expect_snapshot_and_fetch(2).await, all_ok(&data, 2));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_builder_consolidate_before_write() {
+        let data = test_data(3);
+
+        let (client, mut write, mut read) = new_test_shard().await;
+
+        // Each update fed twice with diffs that cancel produces an empty batch.
+        let mut builder = write.builder(Antichain::from_elem(0));
+        builder.consolidate_before_write(1024 * 1024);
+        for ((k, v), t, d) in data.iter() {
+            builder.add(k, v, t, d).await.expect("invalid usage");
+            builder.add(k, v, t, &-d).await.expect("invalid usage");
+        }
+        let batch = builder
+            .finish(Antichain::from_elem(4))
+            .await
+            .expect("invalid usage");
+        assert_eq!(batch.batch.len, 0);
+        assert_eq!(batch.batch.parts.len(), 0);
+        assert_eq!(client.metrics.user.updates_in.get(), 6);
+        assert_eq!(client.metrics.user.updates_written.get(), 0);
+
+        // Each update fed twice with diffs that sum produces a single entry.
+        let mut builder = write.builder(Antichain::from_elem(0));
+        builder.consolidate_before_write(1024 * 1024);
+        for ((k, v), t, d) in data.iter() {
+            builder.add(k, v, t, d).await.expect("invalid usage");
+            builder.add(k, v, t, d).await.expect("invalid usage");
+        }
+        let mut batch = builder
+            .finish(Antichain::from_elem(4))
+            .await
+            .expect("invalid usage");
+        assert_eq!(batch.batch.len, data.len());
+        assert_eq!(client.metrics.user.updates_in.get(), 12);
+        assert_eq!(client.metrics.user.updates_written.get(), 3);
+        write
+            .expect_compare_and_append_batch(&mut [&mut batch], 0, 4)
+            .await;
+        let expected = data
+            .iter()
+            .map(|((k, v), t, d)| ((k.clone(), v.clone()), *t, d * 2))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            read.expect_snapshot_and_fetch(3).await,
+            all_ok(&expected, 3)
+        );
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn batch_builder_flushing() {
@@ -1054,6 +1574,108 @@ mod tests {
         assert_eq!(read.expect_snapshot_and_fetch(3).await, all_ok(&data, 3));
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_builder_add_many() {
+        let data = (0..100u64)
+            .map(|i| ((i.to_string(), format!("val{}", i)), i % 7, 1i64))
+            .collect::<Vec<_>>();
+        let mut records = ColumnarRecordsBuilder::default();
+        for ((k, v), t, d) in data.iter() {
+            let (mut k_buf, mut v_buf) = (Vec::new(), Vec::new());
+            k.encode(&mut k_buf);
+            v.encode(&mut v_buf);
+            assert!(records.push(((&k_buf, &v_buf), Codec64::encode(t), Codec64::encode(d))));
+        }
+        let records = records.finish();
+
+        let cache = PersistClientCache::new_no_metrics();
+        // Use a small blob_target_size so that the batches are split across
+        // several parts and the flushing paths get exercised.
+        cache.cfg.dynamic.set_blob_target_size(200);
+        cache.cfg.dynamic.set_batch_builder_max_outstanding_parts(2);
+        let client = cache
+            .open(PersistLocation::new_in_mem())
+            .await
+            .expect("client construction failed");
+
+        // Build the same batch one update at a time, with a single call to
+        // add_many, and from pre-encoded keys and vals.
+        let mut batches = Vec::new();
+        for method in ["add", "add_many", "add_columnar"] {
+            let (mut write, mut read) = client
+                .expect_open::<String, String, u64, i64>(ShardId::new())
+                .await;
+            let mut builder = write.builder(Antichain::from_elem(0));
+            match method {
+                "add" => {
+                    for ((k, v), t, d) in data.iter() {
+                        builder.add(k, v, t, d).await.expect("invalid usage");
+                    }
+                }
+                "add_many" => {
+                    builder.add_many(&data).await.expect("invalid usage");
+                }
+                "add_columnar" => {
+                    builder.add_columnar(&records).await.expect("invalid usage");
+                }
+                _ => unreachable!(),
+            }
+            let mut batch = builder
+                .finish(Antichain::from_elem(7))
+                .await
+                .expect("invalid usage");
+            let hollow = batch.batch.clone();
+            write
+                .compare_and_append_batch(
+                    &mut [&mut batch],
+                    Antichain::from_elem(0),
+                    Antichain::from_elem(7),
+                )
+                .await
+                .expect("invalid usage")
+                .expect("unexpected upper");
+            assert_eq!(
+                read.expect_snapshot_and_fetch(6).await,
+                all_ok(&data, 6),
+                "{}",
+                method
+            );
+            batches.push((method, hollow));
+        }
+
+        let (_, expected) = &batches[0];
+        assert!(expected.parts.len() > 1);
+        for (method, batch) in &batches[1..] {
+            assert_eq!(batch.len, expected.len, "{}", method);
+            assert_eq!(batch.runs, expected.runs, "{}", method);
+            assert_eq!(batch.parts.len(), expected.parts.len(), "{}", method);
+            for (part, expected) in batch.parts.iter().zip(expected.parts.iter()) {
+                assert_eq!(
+                    part.encoded_size_bytes, expected.encoded_size_bytes,
+                    "{}",
+                    method
+                );
+                assert_eq!(part.key_lower, expected.key_lower, "{}", method);
+            }
+        }
+
+        // An update before the lower is rejected without adding any of the
+        // others.
+        let (mut write, _) = client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        let mut builder = write.builder(Antichain::from_elem(1));
+        assert!(builder.add_many(&data).await.is_err());
+        assert!(builder.add_columnar(&records).await.is_err());
+        let batch = builder
+            .finish(Antichain::from_elem(7))
+            .await
+            .expect("invalid usage");
+        assert_eq!(batch.batch.len, 0);
+        batch.delete().await;
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn batch_builder_keys() {
@@ -1140,6 +1762,208 @@ mod tests {
         }
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_part_checksum() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let batch = write.expect_batch(&data, 0, 3).await;
+        assert_eq!(batch.batch.parts.len(), 1);
+        let part = batch.batch.parts[0].clone();
+        let checksum = part.checksum.expect("new parts have a checksum");
+
+        let fetch = |checksum| {
+            fetch_batch_part(
+                &shard_id,
+                write.blob.as_ref(),
+                &write.metrics,
+                &write.machine.applier.shard_metrics,
+                &write.metrics.read.batch_fetcher,
+                &part.key,
+                &batch.batch.desc,
+                checksum,
+            )
+        };
+        assert!(fetch(Some(checksum)).await.is_ok());
+        // Parts without a checksum skip verification.
+        assert!(fetch(None).await.is_ok());
+
+        // Flip a byte of the stored blob.
+        let key = part.key.complete(&shard_id);
+        let mut value = write
+            .blob
+            .get(&key)
+            .await
+            .expect("blob available")
+            .expect("part exists")
+            .into_contiguous();
+        let idx = value.len() / 2;
+        value[idx] ^= 0xff;
+        write
+            .blob
+            .set(&key, Bytes::from(value), Atomicity::RequireAtomic)
+            .await
+            .expect("blob available");
+
+        match fetch(Some(checksum)).await {
+            Err(FetchBatchError::ChecksumMismatch {
+                key: mismatch_key,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(mismatch_key, key);
+                assert_eq!(expected, checksum);
+                assert_ne!(actual, checksum);
+            }
+            res => panic!("expected checksum mismatch: {:?}", res.map(|_| ())),
+        }
+        assert_eq!(
+            write.metrics.read.batch_fetcher.checksum_mismatches.get(),
+            1
+        );
+
+        // The blob is corrupted at rest, so refetching it gives up after a
+        // bounded number of attempts instead of retrying forever.
+        match retry_leased_fetch("test", || fetch(Some(checksum))).await {
+            Err(FetchBatchError::ChecksumMismatch { .. }) => {}
+            res => panic!("expected checksum mismatch: {:?}", res.map(|_| ())),
+        }
+        assert_eq!(
+            write.metrics.read.batch_fetcher.checksum_mismatches.get(),
+            2 + u64::cast_from(CHECKSUM_MISMATCH_MAX_REFETCHES)
+        );
+
+        // Readers get the mismatch back as an error, instead of the process
+        // going down with it.
+        let mut batch = batch;
+        write
+            .expect_compare_and_append_batch(&mut [&mut batch], 0, 3)
+            .await;
+        match read.snapshot_and_fetch(Antichain::from_elem(2)).await {
+            Err(SnapshotError::Fetch(FetchBatchError::ChecksumMismatch {
+                key: err_key, ..
+            })) => {
+                assert_eq!(err_key, key);
+            }
+            res => panic!("expected checksum mismatch: {:?}", res),
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_part_decode_error() {
+        let data = vec![(("1".to_owned(), "one".to_owned()), 1, 1)];
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, _) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let batch = write.expect_batch(&data, 0, 2).await;
+        let part = batch.batch.parts[0].clone();
+
+        // Replace the part with something that isn't a batch part at all.
+        let key = part.key.complete(&shard_id);
+        write
+            .blob
+            .set(
+                &key,
+                Bytes::from_static(b"not a batch part"),
+                Atomicity::RequireAtomic,
+            )
+            .await
+            .expect("blob available");
+
+        let res = fetch_batch_part(
+            &shard_id,
+            write.blob.as_ref(),
+            &write.metrics,
+            &write.machine.applier.shard_metrics,
+            &write.metrics.read.batch_fetcher,
+            &part.key,
+            &batch.batch.desc,
+            None,
+        )
+        .await;
+        match res {
+            Err(err @ FetchBatchError::Decode { .. }) => {
+                assert!(!err.is_transient());
+                assert!(err.to_string().contains(&key.to_string()), "{}", err);
+            }
+            res => panic!("expected decode error: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_merge() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("3".to_owned(), "three".to_owned()), 3, 1),
+            (("4".to_owned(), "four".to_owned()), 4, 1),
+        ];
+
+        let (client, mut write, mut read) = new_test_shard().await;
+
+        let first = write.expect_batch(&data[..2], 0, 3).await;
+        let second = write.expect_batch(&data[2..], 3, 5).await;
+        let (first_parts, second_parts) = (first.batch.parts.len(), second.batch.parts.len());
+        let mut merged = first.merge(second).expect("batches are adjacent");
+        assert_eq!(merged.lower(), &Antichain::from_elem(0));
+        assert_eq!(merged.upper(), &Antichain::from_elem(5));
+        assert_eq!(merged.batch.len, data.len());
+        assert_eq!(merged.batch.parts.len(), first_parts + second_parts);
+        write
+            .expect_compare_and_append_batch(&mut [&mut merged], 0, 5)
+            .await;
+        assert_eq!(read.expect_snapshot_and_fetch(4).await, all_ok(&data, 4));
+
+        // Batches that leave a gap, overlap, or come in the wrong order are
+        // rejected.
+        for (first, second) in [((5, 6), (7, 8)), ((5, 7), (6, 8)), ((6, 8), (5, 6))] {
+            let first = write.expect_batch(&[], first.0, first.1).await;
+            let second = write.expect_batch(&[], second.0, second.1).await;
+            let expected = InvalidUsage::NonAdjacentBatches {
+                upper: first.upper().clone(),
+                lower: second.lower().clone(),
+            };
+            let Err((err, first, second)) = first.merge(second) else {
+                panic!("non-adjacent batches were merged");
+            };
+            assert_eq!(err, expected);
+            first.delete().await;
+            second.delete().await;
+        }
+
+        // Batches from different shards are rejected and handed back, so their
+        // blobs can still be cleaned up.
+        let (mut other_write, _) = client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        let first = write.expect_batch(&data[..2], 0, 3).await;
+        let second = other_write.expect_batch(&data[2..], 3, 5).await;
+        let Err((err, first, second)) = first.merge(second) else {
+            panic!("batches from different shards were merged");
+        };
+        assert_eq!(
+            err,
+            InvalidUsage::BatchNotFromThisShard {
+                batch_shard: other_write.shard_id(),
+                handle_shard: write.shard_id(),
+            }
+        );
+        assert_eq!(first.batch.len, 2);
+        assert_eq!(second.batch.len, 2);
+        first.delete().await;
+        second.delete().await;
+    }
+
     #[mz_ore::test]
     fn untrimmable_columns() {
         let untrimmable = UntrimmableColumns {
@@ -11,12 +11,13 @@
 
 //! The tunable knobs for persist.
 
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use mz_build_info::BuildInfo;
-use mz_ore::now::NowFn;
+use mz_ore::cast::CastFrom;
+use mz_ore::now::{EpochMillis, NowFn};
 use mz_persist::cfg::BlobKnobs;
 use mz_persist::retry::Retry;
 use mz_postgres_client::PostgresClientKnobs;
@@ -26,8 +27,10 @@ use proptest_derive::Arbitrary;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
+use crate::async_runtime::IsolatedRuntimeConfig;
 use crate::batch::UntrimmableColumns;
 use crate::dyn_cfg::{Config, ConfigSet, ConfigType, ConfigUpdates};
+use crate::error::InvalidUsage;
 use crate::internal::compact::STREAMING_COMPACTION_ENABLED;
 use crate::read::STREAMING_SNAPSHOT_AND_FETCH_ENABLED;
 
@@ -101,20 +104,21 @@ pub struct PersistConfig {
     pub dynamic: Arc<DynamicConfig>,
     /// Whether to physically and logically compact batches in blob storage.
     pub compaction_enabled: bool,
+    /// Whether to reject blob and consensus URIs that can't be part of the
+    /// same location when opening a client. See [Self::validate].
+    pub location_validation_enabled: bool,
     /// In Compactor::compact_and_apply_background, the maximum number of concurrent
     /// compaction requests that can execute for a given shard.
     pub compaction_concurrency_limit: usize,
     /// In Compactor::compact_and_apply_background, the maximum number of pending
     /// compaction requests to queue.
     pub compaction_queue_size: usize,
+    /// In Compactor::compact_and_apply_background, how long a compaction request
+    /// may wait for a concurrency slot before it is dropped.
+    pub compaction_queue_timeout: Duration,
     /// In Compactor::compact_and_apply_background, how many updates to encode or
     /// decode before voluntarily yielding the task.
     pub compaction_yield_after_n_updates: usize,
-    /// The maximum size of the connection pool to Postgres/CRDB when performing
-    /// consensus reads and writes.
-    pub consensus_connection_pool_max_size: usize,
-    /// The maximum time to wait when attempting to obtain a connection from the pool.
-    pub consensus_connection_pool_max_wait: Option<Duration>,
     /// Length of time after a writer's last operation after which the writer
     /// may be expired.
     pub writer_lease_duration: Duration,
@@ -136,6 +140,9 @@ pub struct PersistConfig {
     pub pubsub_state_cache_shard_ref_channel_size: usize,
     /// Backoff after an established connection to Persist PubSub service fails.
     pub pubsub_reconnect_backoff: Duration,
+    /// Configuration of the runtime used for CPU-heavy work, such as
+    /// compaction and decoding.
+    pub isolated_runtime: IsolatedRuntimeConfig,
 }
 
 impl PersistConfig {
@@ -199,11 +206,11 @@ impl PersistConfig {
                 txns_data_shard_retryer: RwLock::new(Self::DEFAULT_TXNS_DATA_SHARD_RETRYER),
             }),
             compaction_enabled: !compaction_disabled,
+            location_validation_enabled: true,
             compaction_concurrency_limit: 5,
             compaction_queue_size: 20,
+            compaction_queue_timeout: Duration::from_secs(5 * 60),
             compaction_yield_after_n_updates: 100_000,
-            consensus_connection_pool_max_size: 50,
-            consensus_connection_pool_max_wait: Some(Duration::from_secs(60)),
             writer_lease_duration: 60 * Duration::from_secs(60),
             critical_downgrade_interval: Duration::from_secs(30),
             pubsub_connect_attempt_timeout: Duration::from_secs(5),
@@ -214,6 +221,7 @@ impl PersistConfig {
             pubsub_server_connection_channel_size: 25,
             pubsub_state_cache_shard_ref_channel_size: 25,
             pubsub_reconnect_backoff: Duration::from_secs(5),
+            isolated_runtime: IsolatedRuntimeConfig::default(),
             // TODO: This doesn't work with the process orchestrator. Instead,
             // separate --log-prefix into --service-name and --enable-log-prefix
             // options, where the first is always provided and the second is
@@ -222,7 +230,40 @@ impl PersistConfig {
         }
     }
 
-    pub(crate) fn set_config<T: ConfigType>(&self, cfg: &Config<T>, val: T) {
+    /// Returns an error if the given blob and consensus URIs can't belong to
+    /// the same persist location.
+    ///
+    /// At the moment, this only catches mixing in-mem and durable storage
+    /// (e.g. a `mem://` blob with a `postgres://` consensus), which silently
+    /// loses data on restart. Unknown schemes are left for opening the blob
+    /// or consensus to reject.
+    pub fn validate(&self, blob_uri: &str, consensus_uri: &str) -> Result<(), InvalidUsage<()>> {
+        fn scheme(uri: &str) -> &str {
+            uri.split_once(':').map_or(uri, |(scheme, _)| scheme)
+        }
+        let (blob_scheme, consensus_scheme) = (scheme(blob_uri), scheme(consensus_uri));
+        let blob_durable = match blob_scheme {
+            "mem" => false,
+            "file" | "s3" => true,
+            _ => return Ok(()),
+        };
+        let consensus_durable = match consensus_scheme {
+            "mem" => false,
+            "postgres" | "postgresql" => true,
+            _ => return Ok(()),
+        };
+        if blob_durable != consensus_durable {
+            // NB: Only report the schemes, the URIs may contain credentials.
+            return Err(InvalidUsage::IncompatibleLocation {
+                blob_scheme: blob_scheme.to_owned(),
+                consensus_scheme: consensus_scheme.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Sets the value of the given [Config] in [Self::configs].
+    pub fn set_config<T: ConfigType>(&self, cfg: &Config<T>, val: T) {
         let shared = cfg.shared(&self.configs);
         T::set(&shared, val)
     }
@@ -254,10 +295,20 @@ impl PersistConfig {
 
     /// Returns a new instance of [PersistConfig] for tests.
     pub fn new_for_tests() -> Self {
-        use mz_build_info::DUMMY_BUILD_INFO;
         use mz_ore::now::SYSTEM_TIME;
 
-        let mut cfg = Self::new(&DUMMY_BUILD_INFO, SYSTEM_TIME.clone());
+        Self::for_tests_with_now(SYSTEM_TIME.clone())
+    }
+
+    /// Returns a new instance of [PersistConfig] for tests that reads the
+    /// current time from `now`.
+    ///
+    /// Combined with a [ManualClock], this allows tests of lease expiry to
+    /// step time deterministically instead of sleeping.
+    pub fn for_tests_with_now(now: NowFn) -> Self {
+        use mz_build_info::DUMMY_BUILD_INFO;
+
+        let mut cfg = Self::new(&DUMMY_BUILD_INFO, now);
         cfg.hostname = "tests".into();
         cfg.set_config(&STREAMING_COMPACTION_ENABLED, true);
         cfg.set_config(&STREAMING_SNAPSHOT_AND_FETCH_ENABLED, true);
@@ -265,9 +316,71 @@ impl PersistConfig {
     }
 }
 
+/// A clock for tests that only moves when explicitly advanced.
+///
+/// Clones share the same underlying time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Returns a new clock starting at `now`.
+    pub fn new(now: EpochMillis) -> Self {
+        ManualClock {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    /// Returns the current time of this clock.
+    pub fn now(&self) -> EpochMillis {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    /// Moves this clock forward by `d`.
+    pub fn advance(&self, d: Duration) {
+        let d = u64::try_from(d.as_millis()).expect("duration millis should fit in u64");
+        self.now.fetch_add(d, Ordering::SeqCst);
+    }
+
+    /// Returns a [NowFn] that reads the current time of this clock.
+    pub fn now_fn(&self) -> NowFn {
+        let now = Arc::clone(&self.now);
+        NowFn::from(move || now.load(Ordering::SeqCst))
+    }
+}
+
 #[allow(non_upper_case_globals)]
 pub(crate) const MiB: usize = 1024 * 1024;
 
+pub const CONSENSUS_CONNECTION_POOL_MAX_SIZE: Config<usize> = Config::new(
+    "persist_consensus_connection_pool_max_size",
+    50,
+    "the maximum size of the connection pool to Postgres/CRDB when performing \
+    consensus reads and writes",
+);
+
+pub const CONSENSUS_CONNECTION_POOL_MAX_WAIT_MS: Config<usize> = Config::new(
+    "persist_consensus_connection_pool_max_wait_ms",
+    60_000,
+    "the maximum time in milliseconds to wait when attempting to obtain a \
+    connection from the consensus connection pool",
+);
+
+pub const CONSENSUS_STATEMENT_TIMEOUT_MS: Config<usize> = Config::new(
+    "persist_consensus_statement_timeout_ms",
+    0,
+    "the statement timeout in milliseconds of new consensus connections to \
+    Postgres/CRDB (0 means no timeout)",
+);
+
+pub const CONSENSUS_TCP_KEEPALIVE_MS: Config<usize> = Config::new(
+    "persist_consensus_tcp_keepalive_ms",
+    0,
+    "the idle time in milliseconds before TCP keepalives are sent on new \
+    consensus connections to Postgres/CRDB (0 means the driver default)",
+);
+
 /// Adds the full set of all persist [Config]s.
 ///
 /// TODO(cfg): Consider replacing this with a static global registry powered by
@@ -275,9 +388,35 @@ pub(crate) const MiB: usize = 1024 * 1024;
 /// the footgun of a Config being linked into one binary but not the other.
 pub fn all_dyn_configs(configs: ConfigSet) -> ConfigSet {
     configs
+        .add(&crate::batch::BATCH_BUILDER_CONSOLIDATION_BUFFER)
         .add(&crate::batch::BATCH_DELETE_ENABLED)
+        .add(&crate::batch::BLOB_COMPRESSION)
+        .add(&CONSENSUS_CONNECTION_POOL_MAX_SIZE)
+        .add(&CONSENSUS_CONNECTION_POOL_MAX_WAIT_MS)
+        .add(&CONSENSUS_STATEMENT_TIMEOUT_MS)
+        .add(&CONSENSUS_TCP_KEEPALIVE_MS)
+        .add(&crate::internal::compact::COMPACTION_CANCELLATION_CHECK_INTERVAL_PARTS)
+        .add(&crate::internal::compact::COMPACTION_FROZEN)
+        .add(&crate::internal::compact::COMPACTION_QUEUE_MAX_CONCURRENCY)
         .add(&crate::internal::compact::STREAMING_COMPACTION_ENABLED)
+        .add(&crate::internal::gc::GC_BLOB_DELETE_MAX_PER_RUN)
+        .add(&crate::internal::gc::GC_BLOB_DELETE_RATE_LIMIT_PER_SEC)
+        .add(&crate::internal::machine::CONSENSUS_RETRY_INITIAL_BACKOFF_MS)
+        .add(&crate::internal::machine::CONSENSUS_RETRY_MULTIPLIER)
+        .add(&crate::internal::machine::CONSENSUS_RETRY_CLAMP_MS)
+        .add(&crate::internal::machine::CONSENSUS_RETRY_MAX_ATTEMPTS)
+        .add(&crate::internal::machine::BLOB_READ_RETRY_INITIAL_BACKOFF_MS)
+        .add(&crate::internal::machine::BLOB_READ_RETRY_MULTIPLIER)
+        .add(&crate::internal::machine::BLOB_READ_RETRY_CLAMP_MS)
+        .add(&crate::internal::machine::BLOB_READ_RETRY_MAX_ATTEMPTS)
+        .add(&crate::internal::machine::BLOB_WRITE_RETRY_INITIAL_BACKOFF_MS)
+        .add(&crate::internal::machine::BLOB_WRITE_RETRY_MULTIPLIER)
+        .add(&crate::internal::machine::BLOB_WRITE_RETRY_CLAMP_MS)
+        .add(&crate::internal::machine::BLOB_WRITE_RETRY_MAX_ATTEMPTS)
+        .add(&crate::internal::watchdog::SLOW_OP_THRESHOLDS)
+        .add(&crate::internal::watchdog::SLOW_OP_WARN_INTERVAL_MS)
         .add(&crate::read::STREAMING_SNAPSHOT_AND_FETCH_ENABLED)
+        .add(&crate::write::WRITER_COALESCE_WINDOW_MS)
 }
 
 impl PersistConfig {
@@ -380,11 +519,12 @@ impl PersistConfig {
 
 impl PostgresClientKnobs for PersistConfig {
     fn connection_pool_max_size(&self) -> usize {
-        self.consensus_connection_pool_max_size
+        CONSENSUS_CONNECTION_POOL_MAX_SIZE.get(&self.configs)
     }
 
     fn connection_pool_max_wait(&self) -> Option<Duration> {
-        self.consensus_connection_pool_max_wait
+        let max_wait_ms = CONSENSUS_CONNECTION_POOL_MAX_WAIT_MS.get(&self.configs);
+        Some(Duration::from_millis(u64::cast_from(max_wait_ms)))
     }
 
     fn connection_pool_ttl(&self) -> Duration {
@@ -410,6 +550,18 @@ impl PostgresClientKnobs for PersistConfig {
             .read()
             .expect("lock poisoned")
     }
+
+    fn statement_timeout(&self) -> Duration {
+        let timeout_ms = CONSENSUS_STATEMENT_TIMEOUT_MS.get(&self.configs);
+        Duration::from_millis(u64::cast_from(timeout_ms))
+    }
+
+    fn tcp_keepalive(&self) -> Option<Duration> {
+        match CONSENSUS_TCP_KEEPALIVE_MS.get(&self.configs) {
+            0 => None,
+            keepalive_ms => Some(Duration::from_millis(u64::cast_from(keepalive_ms))),
+        }
+    }
 }
 
 /// Persist configurations that can be dynamically updated.
@@ -10,10 +10,11 @@
 //! CLI introspection tools for persist
 
 use std::any::Any;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail};
 use differential_dataflow::difference::Semigroup;
@@ -21,24 +22,29 @@ use differential_dataflow::lattice::Lattice;
 use futures_util::{stream, StreamExt, TryStreamExt};
 use mz_ore::metrics::MetricsRegistry;
 use mz_ore::now::SYSTEM_TIME;
-use mz_persist::location::{Blob, Consensus, ExternalError};
+use mz_persist::location::{Blob, Consensus, ExternalError, SeqNo};
 use mz_persist_types::codec_impls::TodoSchema;
 use mz_persist_types::{Codec, Codec64};
 use prometheus::proto::{MetricFamily, MetricType};
-use timely::progress::Timestamp;
+use timely::progress::{Antichain, Timestamp};
 use tracing::info;
 
 use crate::async_runtime::IsolatedRuntime;
 use crate::cache::StateCache;
 use crate::cli::args::{make_blob, make_consensus, StateArgs, StoreArgs};
-use crate::internal::compact::{CompactConfig, CompactReq, Compactor};
+use crate::internal::compact::{efficacy_stats, CompactConfig, CompactReq, Compactor};
 use crate::internal::encoding::Schemas;
-use crate::internal::gc::{GarbageCollector, GcReq};
+use crate::internal::gc::{GarbageCollector, GcReq, GcResults};
 use crate::internal::machine::Machine;
+use crate::internal::state::Upper;
 use crate::internal::trace::{ApplyMergeResult, FueledMergeRes};
+use crate::read::LeasedReaderId;
 use crate::rpc::NoopPubSubSender;
 use crate::write::WriterId;
-use crate::{Diagnostics, Metrics, PersistConfig, ShardId, StateVersions, BUILD_INFO};
+use crate::{
+    Diagnostics, FinalizeFrontiers, Metrics, PersistConfig, PurgeTombstoneResult, ShardId,
+    StateVersions, BUILD_INFO,
+};
 
 /// Commands for read-write administration of persist state
 #[derive(Debug, clap::Args)]
@@ -59,10 +65,15 @@ pub(crate) enum Command {
     /// Manually kick off a GC run for a shard.
     ForceGc(ForceGcArgs),
     /// Manually finalize an unfinalized shard.
-    Finalize(StateArgs),
+    Finalize(FinalizeArgs),
     /// Attempt to ensure that all the files referenced by consensus are available
     /// in Blob.
     RestoreBlob(RestoreBlobArgs),
+    /// Permanently delete the state of all shards that have been tombstones for
+    /// longer than a safety window.
+    PurgeTombstones(PurgeTombstonesArgs),
+    /// Roll a shard back to its contents as of an earlier version of its state.
+    RestoreShard(RestoreShardArgs),
 }
 
 /// Manually completes all fueled compactions in a shard.
@@ -76,11 +87,29 @@ pub(crate) struct ForceCompactionArgs {
     compaction_memory_bound_bytes: usize,
 }
 
-/// Manually completes all fueled compactions in a shard.
+/// Manually kick off a GC run for a shard.
 #[derive(Debug, clap::Parser)]
 pub(crate) struct ForceGcArgs {
     #[clap(flatten)]
     state: StateArgs,
+
+    /// The seqno to GC up to (defaults to the shard's seqno_since). Must not
+    /// be past the earliest state version still needed by a reader or rollup.
+    #[clap(long)]
+    up_to_seqno: Option<u64>,
+}
+
+/// Manually finalize an unfinalized shard.
+#[derive(Debug, clap::Parser)]
+pub(crate) struct FinalizeArgs {
+    #[clap(flatten)]
+    state: StateArgs,
+
+    /// Advance the since and upper of the shard to `[]` even if a reader or
+    /// writer is holding them back, e.g. because a critical since handle was
+    /// lost. Acknowledges that this breaks any live readers or writers.
+    #[clap(long)]
+    force_empty_frontiers: bool,
 }
 
 /// Attempt to restore all the blobs that are referenced by the current state of consensus.
@@ -94,6 +123,35 @@ pub(crate) struct RestoreBlobArgs {
     concurrency: usize,
 }
 
+/// Permanently delete the state of all shards that have been tombstones for
+/// longer than a safety window.
+#[derive(Debug, clap::Parser)]
+pub(crate) struct PurgeTombstonesArgs {
+    #[clap(flatten)]
+    state: StoreArgs,
+
+    /// Only purge shards that have been tombstones for at least this long.
+    #[clap(long, parse(try_from_str = humantime::parse_duration))]
+    older_than: Duration,
+
+    /// Acknowledge that purging is irreversible: opening a purged shard again
+    /// creates a brand-new, empty shard with the same id.
+    #[clap(long)]
+    force: bool,
+}
+
+/// Roll a shard back to its contents as of an earlier version of its state.
+#[derive(Debug, clap::Parser)]
+pub(crate) struct RestoreShardArgs {
+    #[clap(flatten)]
+    state: StateArgs,
+
+    /// The seqno of the state version to restore the shard to. Must still be
+    /// live, i.e. not yet truncated by GC.
+    #[clap(long)]
+    as_of_seqno: u64,
+}
+
 /// Runs the given read-write admin command.
 pub async fn run(command: AdminArgs) -> Result<(), anyhow::Error> {
     match command.command {
@@ -105,7 +163,7 @@ pub async fn run(command: AdminArgs) -> Result<(), anyhow::Error> {
                     .set_compaction_memory_bound_bytes(args.compaction_memory_bound_bytes);
             }
             let metrics_registry = MetricsRegistry::new();
-            let () = force_compaction::<crate::cli::inspect::K, crate::cli::inspect::V, u64, i64>(
+            let _ = force_compaction::<crate::cli::inspect::K, crate::cli::inspect::V, u64, i64>(
                 cfg,
                 &metrics_registry,
                 shard_id,
@@ -113,6 +171,8 @@ pub async fn run(command: AdminArgs) -> Result<(), anyhow::Error> {
                 &args.state.blob_uri,
                 Arc::new(TodoSchema::default()),
                 Arc::new(TodoSchema::default()),
+                None,
+                None,
                 command.commit,
             )
             .await?;
@@ -130,16 +190,21 @@ pub async fn run(command: AdminArgs) -> Result<(), anyhow::Error> {
                 shard_id,
                 &args.state.consensus_uri,
                 &args.state.blob_uri,
+                args.up_to_seqno.map(SeqNo),
                 command.commit,
             )
             .await?;
             info_log_non_zero_metrics(&metrics_registry.gather());
         }
         Command::Finalize(args) => {
-            let StateArgs {
-                shard_id,
-                consensus_uri,
-                blob_uri,
+            let FinalizeArgs {
+                state:
+                    StateArgs {
+                        shard_id,
+                        consensus_uri,
+                        blob_uri,
+                    },
+                force_empty_frontiers,
             } = args;
             let shard_id = ShardId::from_str(&shard_id).expect("invalid shard id");
             let commit = command.commit;
@@ -152,7 +217,13 @@ pub async fn run(command: AdminArgs) -> Result<(), anyhow::Error> {
             let blob = make_blob(&cfg, &blob_uri, commit, Arc::clone(&metrics)).await?;
             let mut machine =
                 make_machine(&cfg, consensus, blob, metrics, shard_id, commit).await?;
-            let maintenance = machine.become_tombstone().await?;
+            let maintenance = machine
+                .become_tombstone(if force_empty_frontiers {
+                    FinalizeFrontiers::ForceEmpty
+                } else {
+                    FinalizeFrontiers::RequireEmpty
+                })
+                .await?;
             if !maintenance.is_empty() {
                 info!("ignoring non-empty requested maintenance: {maintenance:?}")
             }
@@ -215,6 +286,72 @@ pub async fn run(command: AdminArgs) -> Result<(), anyhow::Error> {
                 bail!("referenced blobs were not restored: {not_restored:#?}")
             }
         }
+        Command::PurgeTombstones(args) => {
+            let PurgeTombstonesArgs {
+                state:
+                    StoreArgs {
+                        consensus_uri,
+                        blob_uri,
+                    },
+                older_than,
+                force,
+            } = args;
+            if !force {
+                bail!("purging tombstones is irreversible, pass --force to confirm");
+            }
+            let commit = command.commit;
+            let cfg = PersistConfig::new(&BUILD_INFO, SYSTEM_TIME.clone());
+            let metrics_registry = MetricsRegistry::new();
+            let metrics = Arc::new(Metrics::new(&cfg, &metrics_registry));
+            let consensus =
+                make_consensus(&cfg, &consensus_uri, commit, Arc::clone(&metrics)).await?;
+            let blob = make_blob(&cfg, &blob_uri, commit, Arc::clone(&metrics)).await?;
+            let versions = StateVersions::new(cfg, Arc::clone(&consensus), blob, metrics);
+
+            let shard_ids: Vec<_> = consensus.list_keys().try_collect().await?;
+            let mut purged = 0;
+            for shard_id in shard_ids {
+                let shard_id = ShardId::from_str(&shard_id).expect("invalid shard id");
+                let res = purge_tombstone(&versions, &shard_id, older_than).await;
+                info!("shard {shard_id}: {res:?}");
+                if let Some(PurgeTombstoneResult::Purged { .. }) = res {
+                    purged += 1;
+                }
+            }
+            info!("purged {purged} tombstones (commit={commit})");
+            info_log_non_zero_metrics(&metrics_registry.gather());
+        }
+        Command::RestoreShard(args) => {
+            let RestoreShardArgs {
+                state:
+                    StateArgs {
+                        shard_id,
+                        consensus_uri,
+                        blob_uri,
+                    },
+                as_of_seqno,
+            } = args;
+            let shard_id = ShardId::from_str(&shard_id).expect("invalid shard id");
+            let commit = command.commit;
+            let cfg = PersistConfig::new(&BUILD_INFO, SYSTEM_TIME.clone());
+            let metrics_registry = MetricsRegistry::new();
+            let metrics = Arc::new(Metrics::new(&cfg, &metrics_registry));
+            let consensus =
+                make_consensus(&cfg, &consensus_uri, commit, Arc::clone(&metrics)).await?;
+            let blob = make_blob(&cfg, &blob_uri, commit, Arc::clone(&metrics)).await?;
+            let seqno = restore_shard(
+                &cfg,
+                consensus,
+                blob,
+                metrics,
+                shard_id,
+                SeqNo(as_of_seqno),
+                commit,
+            )
+            .await?;
+            info!("restored shard {shard_id} to seqno {as_of_seqno} in seqno {seqno} (commit={commit})");
+            info_log_non_zero_metrics(&metrics_registry.gather());
+        }
     }
     Ok(())
 }
@@ -253,6 +390,12 @@ pub(crate) fn info_log_non_zero_metrics(metric_families: &[MetricFamily]) {
 }
 
 /// Manually completes all fueled compactions in a shard.
+///
+/// If set, `blob_target_size` and `max_outstanding_parts` override the
+/// corresponding configs for just these compactions.
+///
+/// Returns the number of batch parts in the shard once all compactions are
+/// done.
 pub async fn force_compaction<K, V, T, D>(
     cfg: PersistConfig,
     metrics_registry: &MetricsRegistry,
@@ -261,8 +404,10 @@ pub async fn force_compaction<K, V, T, D>(
     blob_uri: &str,
     key_schema: Arc<K::Schema>,
     val_schema: Arc<V::Schema>,
+    blob_target_size: Option<usize>,
+    max_outstanding_parts: Option<usize>,
     commit: bool,
-) -> Result<(), anyhow::Error>
+) -> Result<usize, anyhow::Error>
 where
     K: Debug + Codec,
     V: Debug + Codec,
@@ -302,6 +447,7 @@ where
                 .iter()
                 .flat_map(|x| x.parts.iter().map(|x| x.encoded_size_bytes))
                 .sum::<usize>();
+            let input_stats = req.input_stats();
             let start = Instant::now();
             info!(
                 "attempt {} req {}: compacting {} batches {} in parts {} totaling bytes: lower={:?} upper={:?} since={:?}",
@@ -323,16 +469,29 @@ where
                 val: Arc::clone(&val_schema),
             };
 
+            let mut compact_cfg = CompactConfig::new(&cfg, &writer_id);
+            if let Some(blob_target_size) = blob_target_size {
+                compact_cfg.batch.blob_target_size = blob_target_size;
+            }
+            if let Some(max_outstanding_parts) = max_outstanding_parts {
+                compact_cfg.batch.batch_builder_max_outstanding_parts = max_outstanding_parts;
+            }
             let res = Compactor::<K, V, T, D>::compact(
-                CompactConfig::new(&cfg, &writer_id),
+                compact_cfg,
                 Arc::clone(&blob),
                 Arc::clone(&metrics),
                 Arc::clone(&machine.applier.shard_metrics),
                 Arc::new(IsolatedRuntime::new()),
                 req,
                 schemas,
+                None,
             )
             .await?;
+            metrics.compaction.forced.observe(
+                input_stats,
+                efficacy_stats([&res.output]),
+                start.elapsed(),
+            );
             info!(
                 "attempt {} req {}: compacted into {} parts {} bytes in {:?}",
                 attempt,
@@ -370,7 +529,10 @@ where
         info!("attempt {}: did {} compactions", attempt, reqs.len());
         let _ = machine.expire_writer(&writer_id).await;
         info!("expired writer {}", writer_id);
-        return Ok(());
+        machine.applier.fetch_and_update_state(None).await;
+        let part_count = machine.applier.batch_part_count();
+        info!("shard has {} batch parts", part_count);
+        return Ok(part_count);
     }
 }
 
@@ -452,32 +614,472 @@ where
         Arc::new(NoopPubSubSender),
         Arc::new(IsolatedRuntime::new()),
         Diagnostics::from_purpose("admin"),
+        None,
     )
     .await?;
 
     Ok(machine)
 }
 
+/// Restores the given shard to its contents as of the state version
+/// `as_of_seqno`, returning the seqno of the state version that does so.
+///
+/// The since and upper of the shard don't regress: everything written after
+/// the target version is replaced by an empty batch that advances the upper
+/// by one, which fences out existing writers (their next write fails with an
+/// upper mismatch). Readers keep their leases and sinces. A seqno hold at the
+/// target version keeps GC from deleting any of its blobs until the restore
+/// is committed. Blobs written after the target version are not deleted here,
+/// but left for GC and the orphan cleanup tooling.
+///
+/// Processes using the shard may keep operating on cached state until they
+/// next fetch it, so this is best run while they're shut down.
+pub async fn restore_shard(
+    cfg: &PersistConfig,
+    consensus: Arc<dyn Consensus + Send + Sync>,
+    blob: Arc<dyn Blob + Send + Sync>,
+    metrics: Arc<Metrics>,
+    shard_id: ShardId,
+    as_of_seqno: SeqNo,
+    commit: bool,
+) -> anyhow::Result<SeqNo> {
+    let mut machine = make_machine(cfg, consensus, blob, metrics, shard_id, commit).await?;
+    if machine.is_finalized() {
+        bail!("shard {shard_id} is a tombstone and cannot be restored");
+    }
+
+    let hold_id = LeasedReaderId::new();
+    let (held, _maintenance) = machine
+        .register_seqno_hold(
+            &hold_id,
+            "persistcli admin restore-shard",
+            as_of_seqno,
+            cfg.dynamic.reader_lease_duration(),
+            (cfg.now)(),
+        )
+        .await;
+    if !held {
+        bail!("seqno {as_of_seqno} of shard {shard_id} may already have been garbage collected");
+    }
+    let res = restore_held_shard(&mut machine, shard_id, as_of_seqno).await;
+    let _ = machine.expire_leased_reader(&hold_id).await;
+    res
+}
+
+async fn restore_held_shard(
+    machine: &mut Machine<crate::cli::inspect::K, crate::cli::inspect::V, u64, i64>,
+    shard_id: ShardId,
+    as_of_seqno: SeqNo,
+) -> anyhow::Result<SeqNo> {
+    let versions = Arc::clone(&machine.applier.state_versions);
+    let target = crate::internal::restore::fetch_restorable_state::<u64>(
+        &versions,
+        versions.blob.as_ref(),
+        shard_id,
+        as_of_seqno,
+    )
+    .await?;
+
+    let upper = machine.applier.clone_upper();
+    let Some(new_upper) = upper.as_option().and_then(|ts| ts.checked_add(1)) else {
+        bail!("shard {shard_id} is closed and cannot be restored");
+    };
+    let new_upper = Antichain::from_elem(new_upper);
+    info!(
+        "restoring shard {shard_id} from seqno {} to seqno {as_of_seqno}: upper {:?} -> {:?}",
+        machine.seqno(),
+        upper.elements(),
+        new_upper.elements(),
+    );
+    let (seqno, res, maintenance) = machine.restore(&target, &new_upper).await;
+    if let Err(Upper(current)) = res {
+        bail!(
+            "shard {shard_id} upper advanced to {:?} concurrently with the restore",
+            current.elements()
+        );
+    }
+    if !maintenance.is_empty() {
+        info!("ignoring non-empty requested maintenance: {maintenance:?}")
+    }
+    // Write a rollup of the restored state, so that anything loading the shard
+    // from scratch starts from it rather than replaying the restore.
+    let maintenance = machine.add_rollup_for_current_seqno().await;
+    if !maintenance.is_empty() {
+        info!("ignoring non-empty requested maintenance: {maintenance:?}")
+    }
+    Ok(seqno)
+}
+
+/// Purges the given shard if it's an old enough tombstone, returning None if
+/// its codecs aren't ones this tool can decode.
+async fn purge_tombstone(
+    versions: &StateVersions,
+    shard_id: &ShardId,
+    older_than: Duration,
+) -> Option<PurgeTombstoneResult> {
+    loop {
+        let res = versions
+            .purge_tombstone::<crate::cli::inspect::K, crate::cli::inspect::V, u64, i64>(
+                shard_id, older_than,
+            )
+            .await;
+        match res {
+            Ok(res) => return Some(res),
+            Err(codec) => {
+                // Same codec magic as make_typed_machine, but we can only
+                // rebind the key and val codecs.
+                if codec.actual.2 != u64::codec_name() || codec.actual.3 != i64::codec_name() {
+                    return None;
+                }
+                let mut kvtd = crate::cli::inspect::KVTD_CODECS.lock().expect("lockable");
+                *kvtd = codec.actual;
+            }
+        }
+    }
+}
+
 async fn force_gc(
     cfg: PersistConfig,
     metrics_registry: &MetricsRegistry,
     shard_id: ShardId,
     consensus_uri: &str,
     blob_uri: &str,
+    up_to_seqno: Option<SeqNo>,
     commit: bool,
 ) -> anyhow::Result<Box<dyn Any>> {
     let metrics = Arc::new(Metrics::new(&cfg, metrics_registry));
     let consensus = make_consensus(&cfg, consensus_uri, commit, Arc::clone(&metrics)).await?;
     let blob = make_blob(&cfg, blob_uri, commit, Arc::clone(&metrics)).await?;
     let mut machine = make_machine(&cfg, consensus, blob, metrics, shard_id, commit).await?;
-    let gc_req = GcReq {
+    let live_diffs_before = machine
+        .applier
+        .state_versions
+        .fetch_all_live_diffs(&shard_id)
+        .await
+        .0
+        .len();
+    let results = gc_up_to(&mut machine, up_to_seqno, commit).await?;
+    let live_diffs_after = machine
+        .applier
+        .state_versions
+        .fetch_all_live_diffs(&shard_id)
+        .await
+        .0
+        .len();
+    info!(
+        "deleted {} batch parts and {} rollups from blob (commit={commit})",
+        results.batch_parts_deleted_from_blob, results.rollups_deleted_from_blob,
+    );
+    info!(
+        "truncated consensus to {:?}: {} live state versions -> {} (commit={commit})",
+        results.truncated_consensus_to.last(),
+        live_diffs_before,
+        live_diffs_after,
+    );
+
+    Ok(Box::new(machine))
+}
+
+/// Synchronously garbage collects the given shard up to `up_to_seqno`, or up
+/// to its seqno_since if none is given, returning the combined results.
+///
+/// This follows the usual GC safety rules: it refuses to run if `up_to_seqno`
+/// is past the seqno_since, which holds back the earliest state version still
+/// referenced by a reader or rollup. Runs stopped early by the per-run blob
+/// delete cap are resumed until GC completes, except in a dry run, where
+/// nothing is actually truncated.
+async fn gc_up_to<K, V, T, D>(
+    machine: &mut Machine<K, V, T, D>,
+    up_to_seqno: Option<SeqNo>,
+    commit: bool,
+) -> anyhow::Result<GcResults>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64,
+{
+    let shard_id = machine.shard_id();
+    let safe_seqno = machine.applier.seqno_since();
+    let new_seqno_since = match up_to_seqno {
+        Some(seqno) if seqno > safe_seqno => bail!(
+            "refusing to gc shard {shard_id} up to seqno {seqno}: state versions from seqno \
+            {safe_seqno} on are still needed by readers or rollups"
+        ),
+        Some(seqno) => seqno,
+        None => safe_seqno,
+    };
+    info!("running gc for shard {shard_id} up to seqno {new_seqno_since}");
+
+    let req = GcReq {
         shard_id,
-        new_seqno_since: machine.applier.seqno_since(),
+        new_seqno_since,
     };
-    let (maintenance, _stats) = GarbageCollector::gc_and_truncate(&mut machine, gc_req).await;
-    if !maintenance.is_empty() {
-        info!("ignoring non-empty requested maintenance: {maintenance:?}")
+    let mut results = GcResults::default();
+    let mut deleted_before_truncate = BTreeSet::new();
+    loop {
+        let (maintenance, run) = GarbageCollector::gc_and_truncate_resumable(
+            machine,
+            req.clone(),
+            &mut deleted_before_truncate,
+        )
+        .await;
+        info!("gc run finished: {run:?}");
+        results.batch_parts_deleted_from_blob += run.batch_parts_deleted_from_blob;
+        results.rollups_deleted_from_blob += run.rollups_deleted_from_blob;
+        results
+            .truncated_consensus_to
+            .extend(run.truncated_consensus_to);
+        results
+            .rollups_removed_from_state
+            .extend(run.rollups_removed_from_state);
+        results.blob_deletes_deferred = run.blob_deletes_deferred;
+        // A run that still has work left requests a follow-up GC. Anything
+        // else it requested is not ours to do.
+        if !commit || run.blob_deletes_deferred == 0 {
+            if !maintenance.is_empty() {
+                info!("ignoring non-empty requested maintenance: {maintenance:?}")
+            }
+            break;
+        }
     }
+    Ok(results)
+}
 
-    Ok(Box::new(machine))
+#[cfg(test)]
+mod tests {
+    use mz_ore::cast::CastFrom;
+
+    use crate::rpc::PubSubSender;
+    use crate::tests::{all_ok, new_test_client, new_test_client_cache};
+    use crate::{PersistClient, PersistLocation};
+
+    use super::*;
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn restore_shard_to_seqno() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("1".to_owned(), "one".to_owned()), 3, -1),
+            (("2".to_owned(), "two".to_owned()), 3, -1),
+        ];
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        // The reader's seqno lease keeps GC from progressing past the version
+        // we restore to.
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data[..2], 0, 3).await;
+        let as_of_seqno = write.machine.seqno();
+        // Oops, an accidental retraction of everything.
+        write.expect_compare_and_append(&data[2..], 3, 4).await;
+
+        let restore = |seqno| {
+            restore_shard(
+                &client.cfg,
+                Arc::clone(&client.consensus),
+                Arc::clone(&client.blob),
+                Arc::clone(&client.metrics),
+                shard_id,
+                seqno,
+                true,
+            )
+        };
+        // Versions that don't exist (yet) can't be restored.
+        assert!(restore(SeqNo(u64::MAX)).await.is_err());
+        let seqno = restore(as_of_seqno).await.expect("restore failed");
+        assert!(seqno > as_of_seqno);
+
+        // The existing writer is fenced out by the advanced upper.
+        let res = write
+            .compare_and_append(&data[2..], Antichain::from_elem(4), Antichain::from_elem(5))
+            .await
+            .expect("usage should be valid");
+        match res {
+            Err(mismatch) => assert_eq!(mismatch.current, Antichain::from_elem(5)),
+            Ok(()) => panic!("write to restored shard unexpectedly succeeded"),
+        }
+
+        // A client without cached state for the shard sees its earlier
+        // contents.
+        let pubsub_sender: Arc<dyn PubSubSender> = Arc::new(NoopPubSubSender);
+        let fresh_client = PersistClient::new(
+            client.cfg.clone(),
+            Arc::clone(&client.blob),
+            Arc::clone(&client.consensus),
+            Arc::clone(&client.metrics),
+            Arc::clone(&client.isolated_runtime),
+            Arc::new(StateCache::new(
+                &client.cfg,
+                Arc::clone(&client.metrics),
+                Arc::clone(&pubsub_sender),
+            )),
+            pubsub_sender,
+        )
+        .expect("client construction failed");
+        let (mut fresh_write, mut read) = fresh_client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        assert_eq!(
+            fresh_write.fetch_recent_upper().await,
+            &Antichain::from_elem(5)
+        );
+        assert_eq!(
+            read.expect_snapshot_and_fetch(4).await,
+            all_ok(&data[..2], 4)
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn restore_shard_across_compaction() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 0, 1),
+            (("2".to_owned(), "two".to_owned()), 1, 1),
+            (("3".to_owned(), "three".to_owned()), 2, 1),
+            (("1".to_owned(), "one".to_owned()), 3, -1),
+            (("2".to_owned(), "two".to_owned()), 3, -1),
+            (("3".to_owned(), "three".to_owned()), 3, -1),
+        ];
+
+        let mut cache = new_test_client_cache();
+        // Compact by hand below, so we know what the spine looks like.
+        cache.cfg.compaction_enabled = false;
+        let client = cache
+            .open(PersistLocation::new_in_mem())
+            .await
+            .expect("client construction failed");
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        for (idx, update) in data[..3].iter().enumerate() {
+            let ts = u64::cast_from(idx);
+            write
+                .expect_compare_and_append(&[update.clone()], ts, ts + 1)
+                .await;
+        }
+        let as_of_seqno = write.machine.seqno();
+        write.expect_compare_and_append(&data[3..], 3, 4).await;
+
+        let batches = || async {
+            let state = client
+                .inspect_shard::<u64>(&shard_id)
+                .await
+                .expect("shard exists");
+            let state = serde_json::to_value(state).expect("serializable state");
+            state["batches"].as_array().expect("batches").len()
+        };
+        assert_eq!(batches().await, 4);
+        loop {
+            let reqs = write.machine.applier.all_fueled_merge_reqs();
+            if reqs.is_empty() {
+                break;
+            }
+            for req in reqs {
+                let req = CompactReq {
+                    shard_id,
+                    desc: req.desc,
+                    inputs: req.inputs.iter().map(|b| b.batch.clone()).collect(),
+                };
+                let res = Compactor::<String, String, u64, i64>::compact(
+                    CompactConfig::new(&client.cfg, &write.writer_id),
+                    Arc::clone(&client.blob),
+                    Arc::clone(&client.metrics),
+                    Arc::clone(&write.machine.applier.shard_metrics),
+                    Arc::clone(&client.isolated_runtime),
+                    req,
+                    write.schemas.clone(),
+                    None,
+                )
+                .await
+                .expect("compaction failed");
+                let _ = write
+                    .machine
+                    .merge_res(&FueledMergeRes { output: res.output })
+                    .await;
+            }
+        }
+        assert!(batches().await < 4);
+
+        restore_shard(
+            &client.cfg,
+            Arc::clone(&client.consensus),
+            Arc::clone(&client.blob),
+            Arc::clone(&client.metrics),
+            shard_id,
+            as_of_seqno,
+            true,
+        )
+        .await
+        .expect("restore failed");
+
+        // The handles' cached state still has the compacted spine, which the
+        // restore applies to all the same.
+        assert_eq!(write.fetch_recent_upper().await, &Antichain::from_elem(5));
+        assert_eq!(
+            read.expect_snapshot_and_fetch(4).await,
+            all_ok(&data[..3], 4)
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn force_gc_up_to_seqno() {
+        let client = new_test_client().await;
+        // set a low rollup threshold so there are many truncation points
+        client.cfg.dynamic.set_rollup_threshold(5);
+        let shard_id = ShardId::new();
+        // The reader's seqno lease holds back GC while we generate garbage.
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        for idx in 0..20u64 {
+            let data = [((idx.to_string(), "".to_owned()), idx, 1)];
+            write.expect_compare_and_append(&data, idx, idx + 1).await;
+        }
+
+        let mut machine = make_machine(
+            &client.cfg,
+            Arc::clone(&client.consensus),
+            Arc::clone(&client.blob),
+            Arc::clone(&client.metrics),
+            shard_id,
+            true,
+        )
+        .await
+        .expect("valid machine");
+        let state_versions = Arc::clone(&machine.applier.state_versions);
+        let live_diffs = || async { state_versions.fetch_all_live_diffs(&shard_id).await.0.len() };
+        let live_diffs_before = live_diffs().await;
+
+        // GC can't go past what the reader still needs.
+        let seqno = machine.seqno();
+        let err = gc_up_to(&mut machine, Some(seqno), true).await.unwrap_err();
+        assert!(err.to_string().contains("refusing to gc"), "{err}");
+        assert_eq!(live_diffs().await, live_diffs_before);
+
+        // Release the seqno hold, without running the resulting maintenance,
+        // and GC by hand.
+        let (_, _, _maintenance) = read
+            .machine
+            .downgrade_since(
+                &read.reader_id,
+                None,
+                &Antichain::from_elem(0),
+                (client.cfg.now)(),
+            )
+            .await;
+        machine.applier.fetch_and_update_state(None).await;
+        let results = gc_up_to(&mut machine, None, true)
+            .await
+            .expect("gc succeeds");
+        assert!(!results.truncated_consensus_to.is_empty());
+        assert!(results.batch_parts_deleted_from_blob + results.rollups_deleted_from_blob > 0);
+        assert!(live_diffs().await < live_diffs_before);
+    }
 }
@@ -272,4 +272,10 @@ impl Consensus for ReadOnly<Arc<dyn Consensus + Sync + Send>> {
         self.ignoring_write();
         Ok(0)
     }
+
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        warn!("ignoring delete({key}) in read-only mode (at seqno {expected})");
+        self.ignoring_write();
+        Ok(CaSResult::Committed)
+    }
 }
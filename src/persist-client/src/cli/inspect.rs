@@ -12,6 +12,7 @@
 use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
@@ -19,26 +20,36 @@ use anyhow::anyhow;
 use bytes::BufMut;
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::trace::Description;
+use futures::io::AllowStdIo;
 use mz_ore::cast::CastFrom;
 use mz_ore::metrics::MetricsRegistry;
 use mz_ore::now::SYSTEM_TIME;
 use mz_persist::indexed::encoding::BlobTraceBatchPart;
+use mz_persist::location::{Consensus, SeqNo, VersionedData, SCAN_ALL};
 use mz_persist_types::codec_impls::TodoSchema;
 use mz_persist_types::{Codec, Codec64};
 use mz_proto::RustType;
 use prost::Message;
 use serde_json::json;
+use timely::progress::Antichain;
+use tracing::info;
 
 use crate::async_runtime::IsolatedRuntime;
 use crate::cache::StateCache;
-use crate::cli::args::{make_blob, make_consensus, StateArgs, NO_COMMIT, READ_ALL_BUILD_INFO};
+use crate::cli::args::{
+    make_blob, make_consensus, StateArgs, StoreArgs, NO_COMMIT, READ_ALL_BUILD_INFO,
+};
 use crate::error::CodecConcreteType;
+use crate::export::ExportFormat;
 use crate::fetch::{Cursor, EncodedPart};
+use crate::health::PersistHealth;
 use crate::internal::encoding::{Rollup, UntypedState};
 use crate::internal::paths::{
     BlobKey, BlobKeyPrefix, PartialBatchKey, PartialBlobKey, PartialRollupKey, WriterKey,
 };
 use crate::internal::state::{ProtoRollup, ProtoStateDiff, State};
+use crate::internal::state_diff::{StateDiff, StateFieldValDiff};
+use crate::internal::state_versions::StateVersions;
 use crate::rpc::NoopPubSubSender;
 use crate::usage::{HumanBytes, StorageUsageClient};
 use crate::{Metrics, PersistClient, PersistConfig, ShardId};
@@ -53,7 +64,8 @@ pub struct InspectArgs {
 /// Individual subcommands of inspect
 #[derive(Debug, clap::Subcommand)]
 pub(crate) enum Command {
-    /// Prints latest consensus state as JSON
+    /// Prints latest consensus state as JSON, along with a summary of its
+    /// critical readers
     State(StateArgs),
 
     /// Prints latest consensus rollup state as JSON
@@ -77,6 +89,9 @@ pub(crate) enum Command {
     /// Prints information about blob usage for a shard
     BlobUsage(StateArgs),
 
+    /// Prints the critical readers of a shard, with their sinces and opaques, as JSON
+    CriticalReaders(StateArgs),
+
     /// Prints each consensus state change as JSON. Output includes the full consensus state
     /// before and after each state transitions:
     ///
@@ -102,13 +117,25 @@ pub(crate) enum Command {
     ///
     #[clap(verbatim_doc_comment)]
     StateDiff(StateArgs),
+
+    /// Prints one line per live consensus diff of a shard, with a summary of what changed
+    StateHistory(StateHistoryArgs),
+
+    /// Probes blob and consensus and prints a summary of their health as JSON
+    Health(HealthArgs),
+
+    /// Writes a consolidated snapshot of a shard to a Parquet file
+    ExportParquet(ExportParquetArgs),
 }
 
 /// Runs the given read-only inspect command.
 pub async fn run(command: InspectArgs) -> Result<(), anyhow::Error> {
     match command.command {
         Command::State(args) => {
-            let state = fetch_latest_state(&args).await?;
+            let mut state = serde_json::to_value(fetch_latest_state(&args).await?)
+                .expect("unserializable state");
+            let critical_readers = fetch_critical_readers(&args).await?;
+            state["critical_reader_summaries"] = json!(critical_readers);
             println!(
                 "{}",
                 serde_json::to_string_pretty(&state).expect("unserializable state")
@@ -140,6 +167,23 @@ pub async fn run(command: InspectArgs) -> Result<(), anyhow::Error> {
                 );
             }
         }
+        Command::StateHistory(args) => {
+            let state_versions = args.state.open().await?;
+            let history = state_history(
+                state_versions.consensus.as_ref(),
+                &state_versions.cfg.build_version,
+                &args.state.shard_id(),
+                SeqNo(args.since_seqno.unwrap_or(0)),
+                args.limit.unwrap_or(SCAN_ALL),
+            )
+            .await?;
+            for entry in history {
+                match args.format {
+                    HistoryFormat::Text => println!("{}", entry),
+                    HistoryFormat::Json => println!("{}", json!(entry)),
+                }
+            }
+        }
         Command::BlobCount(args) => {
             let blob_counts = blob_counts(&args.blob_uri).await?;
             println!("{}", json!(blob_counts));
@@ -156,9 +200,28 @@ pub async fn run(command: InspectArgs) -> Result<(), anyhow::Error> {
         Command::BlobUsage(args) => {
             let () = blob_usage(&args).await?;
         }
+        Command::CriticalReaders(args) => {
+            let critical_readers = fetch_critical_readers(&args).await?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&critical_readers)
+                    .expect("unserializable critical readers")
+            );
+        }
         Command::ShardStats(args) => {
             shard_stats(&args.blob_uri).await?;
         }
+        Command::Health(args) => {
+            let health = health(&args).await?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&health).expect("unserializable health")
+            );
+        }
+        Command::ExportParquet(args) => {
+            let count = export_parquet(&args).await?;
+            info!("exported {} updates to {}", count, args.output.display());
+        }
     }
 
     Ok(())
@@ -175,6 +238,152 @@ pub struct StateRollupArgs {
     pub(crate) rollup_key: Option<String>,
 }
 
+/// Arguments for probing the health of a persist location
+#[derive(Debug, Clone, clap::Parser)]
+pub struct HealthArgs {
+    #[clap(flatten)]
+    pub(crate) store: StoreArgs,
+
+    /// The number of times to probe each of blob and consensus.
+    #[clap(long, default_value_t = 10)]
+    pub(crate) probes: usize,
+}
+
+/// Probes blob and consensus with read-only operations and summarizes their
+/// health.
+///
+/// A fresh process hasn't observed any operations, so unlike
+/// [PersistClient::health] this issues its own (a `get` of a blob key and a
+/// `head` of a consensus key, neither of which need exist) and reports on
+/// those.
+pub async fn health(args: &HealthArgs) -> Result<PersistHealth, anyhow::Error> {
+    let cfg = PersistConfig::new(&READ_ALL_BUILD_INFO, SYSTEM_TIME.clone());
+    let metrics = Arc::new(Metrics::new(&cfg, &MetricsRegistry::new()));
+    let consensus = make_consensus(
+        &cfg,
+        &args.store.consensus_uri,
+        NO_COMMIT,
+        Arc::clone(&metrics),
+    )
+    .await?;
+    let blob = make_blob(&cfg, &args.store.blob_uri, NO_COMMIT, Arc::clone(&metrics)).await?;
+
+    let key = format!("persistcli-health-probe-{}", ShardId::new());
+    for _ in 0..args.probes {
+        // Failures are recorded in the metrics, which is all we're after.
+        if let Err(err) = blob.get(&key).await {
+            info!("blob probe failed: {}", err);
+        }
+        if let Err(err) = consensus.head(&key).await {
+            info!("consensus probe failed: {}", err);
+        }
+    }
+    Ok(metrics.health())
+}
+
+/// Arguments for exporting a snapshot of a shard to Parquet
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportParquetArgs {
+    #[clap(flatten)]
+    pub(crate) state: StateArgs,
+
+    /// The time as of which to export the shard. Defaults to the latest
+    /// readable time, i.e. one less than the shard's upper.
+    #[clap(long)]
+    pub(crate) as_of: Option<u64>,
+
+    /// The file to write the Parquet output to.
+    #[clap(long)]
+    pub(crate) output: PathBuf,
+}
+
+/// Writes a consolidated snapshot of a shard to a Parquet file, returning the
+/// number of updates written.
+///
+/// The types of the shard's keys and values aren't known here, so they are
+/// exported as their encoded bytes. Timestamps and diffs are assumed to be
+/// u64 and i64, which is the case for all shards written by Materialize.
+///
+/// Like all inspect commands, this doesn't write anything, so the reader it
+/// opens doesn't hold back garbage collection of the shard. Exporting a shard
+/// that is concurrently being compacted may fail to fetch blobs deleted out
+/// from under it.
+pub async fn export_parquet(args: &ExportParquetArgs) -> Result<usize, anyhow::Error> {
+    let shard_id = args.state.shard_id();
+    let cfg = PersistConfig::new(&READ_ALL_BUILD_INFO, SYSTEM_TIME.clone());
+    let metrics = Arc::new(Metrics::new(&cfg, &MetricsRegistry::new()));
+    let consensus = make_consensus(
+        &cfg,
+        &args.state.consensus_uri,
+        NO_COMMIT,
+        Arc::clone(&metrics),
+    )
+    .await?;
+    let blob = make_blob(&cfg, &args.state.blob_uri, NO_COMMIT, Arc::clone(&metrics)).await?;
+
+    // Prime the K V codec magic
+    let state_versions = StateVersions::new(
+        cfg.clone(),
+        Arc::clone(&consensus),
+        Arc::clone(&blob),
+        Arc::clone(&metrics),
+    );
+    let versions = state_versions
+        .fetch_recent_live_diffs::<u64>(&shard_id)
+        .await;
+    let state = state_versions
+        .fetch_current_state::<u64>(&shard_id, versions.0.clone())
+        .await
+        .check_codecs::<K, V, i64>(&shard_id);
+    let state = match state {
+        Ok(state) => state,
+        Err(codec) => {
+            *KVTD_CODECS.lock().expect("lockable") = codec.actual;
+            state_versions
+                .fetch_current_state::<u64>(&shard_id, versions.0)
+                .await
+                .check_codecs::<K, V, i64>(&shard_id)
+                .map_err(|err| anyhow!("unsupported shard codecs: {}", err))?
+        }
+    };
+
+    let as_of = match args.as_of {
+        Some(as_of) => as_of,
+        None => match (state.since().as_option(), state.upper().as_option()) {
+            (Some(since), Some(upper)) if since < upper => upper - 1,
+            (Some(since), None) => *since,
+            _ => return Err(anyhow!("shard {} has no readable times", shard_id)),
+        },
+    };
+
+    let isolated_runtime = Arc::new(IsolatedRuntime::new());
+    let state_cache = Arc::new(StateCache::new(
+        &cfg,
+        Arc::clone(&metrics),
+        Arc::new(NoopPubSubSender),
+    ));
+    let client = PersistClient::new(
+        cfg,
+        blob,
+        consensus,
+        metrics,
+        isolated_runtime,
+        state_cache,
+        Arc::new(NoopPubSubSender),
+    )?;
+    let output = std::fs::File::create(&args.output)?;
+    client
+        .export_snapshot_parquet::<K, V, u64, i64, _>(
+            shard_id,
+            Antichain::from_elem(as_of),
+            Arc::new(TodoSchema::default()),
+            Arc::new(TodoSchema::default()),
+            ExportFormat::Encoded,
+            AllowStdIo::new(output),
+        )
+        .await
+}
+
 /// Fetches the current state of a given shard
 pub async fn fetch_latest_state(args: &StateArgs) -> Result<impl serde::Serialize, anyhow::Error> {
     let shard_id = args.shard_id();
@@ -188,6 +397,25 @@ pub async fn fetch_latest_state(args: &StateArgs) -> Result<impl serde::Serializ
     Ok(Rollup::from_untyped_state_without_diffs(state).into_proto())
 }
 
+/// Fetches the critical readers of a given shard
+pub async fn fetch_critical_readers(
+    args: &StateArgs,
+) -> Result<impl serde::Serialize, anyhow::Error> {
+    let shard_id = args.shard_id();
+    let state_versions = args.open().await?;
+    let versions = state_versions
+        .fetch_recent_live_diffs::<u64>(&shard_id)
+        .await;
+    if versions.0.is_empty() {
+        return Err(anyhow!("unknown shard"));
+    }
+    let state = state_versions
+        .fetch_current_state::<u64>(&shard_id, versions.0)
+        .await
+        .check_ts_codec(&shard_id)?;
+    Ok(state.critical_reader_infos())
+}
+
 /// Fetches a state rollup of a given shard. If the seqno is not provided, choose the latest;
 /// if the rollup id is not provided, discover it by inspecting state.
 pub async fn fetch_state_rollup(
@@ -270,6 +498,141 @@ pub async fn fetch_state_diffs(
     Ok(live_states)
 }
 
+/// Arguments for viewing the history of a shard's state
+#[derive(Debug, Clone, clap::Parser)]
+pub struct StateHistoryArgs {
+    #[clap(flatten)]
+    pub(crate) state: StateArgs,
+
+    /// Only print diffs with a seqno greater or equal to this one.
+    #[clap(long)]
+    pub(crate) since_seqno: Option<u64>,
+
+    /// Maximum number of diffs to print. Default is unbounded.
+    #[clap(long)]
+    pub(crate) limit: Option<usize>,
+
+    /// The format to print each diff in.
+    #[clap(arg_enum, long, default_value_t = HistoryFormat::Text)]
+    pub(crate) format: HistoryFormat,
+}
+
+/// Output formats of the `state-history` command.
+#[derive(clap::ArgEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HistoryFormat {
+    /// One human-readable line per diff.
+    Text,
+    /// One JSON object per line per diff.
+    Json,
+}
+
+/// A summary of a single live consensus diff of a shard.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct StateHistoryEntry {
+    /// The seqno of the state the diff produces.
+    pub seqno: SeqNo,
+    /// The wall clock time, in milliseconds since the epoch, at which the
+    /// diff was created.
+    ///
+    /// Consensus doesn't store when a diff was inserted, so this is the time
+    /// recorded in the diff itself by the process that wrote it.
+    pub walltime_ms: u64,
+    /// The encoded size of the diff in bytes.
+    pub diff_bytes: usize,
+    /// The upper the shard advanced to, if the diff advanced it.
+    pub upper: Option<Vec<u64>>,
+    /// The since the shard was downgraded to, if the diff downgraded it.
+    pub since: Option<Vec<u64>>,
+    /// The change in the number of batches in the shard's trace.
+    pub batch_count_delta: i64,
+}
+
+impl StateHistoryEntry {
+    fn new(build_version: &semver::Version, diff: VersionedData) -> Self {
+        let diff_bytes = diff.data.len();
+        let diff = StateDiff::<u64>::decode(build_version, diff.data);
+
+        // Compaction replaces batches with a batch covering the same times, so
+        // the upper only advances if an inserted batch ends at a time that no
+        // deleted one did.
+        let mut inserted_uppers = Vec::new();
+        let mut deleted_uppers = BTreeSet::new();
+        let mut batch_count_delta = 0i64;
+        for batch in diff.spine.iter() {
+            match batch.val {
+                StateFieldValDiff::Insert(()) => {
+                    inserted_uppers.push(batch.key.desc.upper().elements().to_vec());
+                    batch_count_delta += 1;
+                }
+                StateFieldValDiff::Delete(()) => {
+                    deleted_uppers.insert(batch.key.desc.upper().elements().to_vec());
+                    batch_count_delta -= 1;
+                }
+                StateFieldValDiff::Update((), ()) => {}
+            }
+        }
+        let upper = inserted_uppers
+            .into_iter()
+            .filter(|upper| !deleted_uppers.contains(upper))
+            // The empty antichain is the greatest upper of them all.
+            .max_by(|a, b| (a.is_empty(), a).cmp(&(b.is_empty(), b)));
+
+        let since = diff.since.iter().find_map(|since| match &since.val {
+            StateFieldValDiff::Insert(since) | StateFieldValDiff::Update(_, since) => {
+                Some(since.elements().to_vec())
+            }
+            StateFieldValDiff::Delete(_) => None,
+        });
+
+        StateHistoryEntry {
+            seqno: diff.seqno_to,
+            walltime_ms: diff.walltime_ms,
+            diff_bytes,
+            upper,
+            since,
+            batch_count_delta,
+        }
+    }
+}
+
+impl fmt::Display for StateHistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} walltime_ms={} bytes={}",
+            self.seqno, self.walltime_ms, self.diff_bytes
+        )?;
+        if let Some(upper) = &self.upper {
+            write!(f, " upper={:?}", upper)?;
+        }
+        if let Some(since) = &self.since {
+            write!(f, " since={:?}", since)?;
+        }
+        if self.batch_count_delta != 0 {
+            write!(f, " batches={:+}", self.batch_count_delta)?;
+        }
+        Ok(())
+    }
+}
+
+/// Summarizes the live consensus diffs of a shard, starting at `since_seqno`
+/// and returning at most `limit` of them.
+pub async fn state_history(
+    consensus: &(dyn Consensus + Send + Sync),
+    build_version: &semver::Version,
+    shard_id: &ShardId,
+    since_seqno: SeqNo,
+    limit: usize,
+) -> Result<Vec<StateHistoryEntry>, anyhow::Error> {
+    let diffs = consensus
+        .scan(&shard_id.to_string(), since_seqno, limit)
+        .await?;
+    Ok(diffs
+        .into_iter()
+        .map(|diff| StateHistoryEntry::new(build_version, diff))
+        .collect())
+}
+
 /// Arguments for viewing contents of a batch part
 #[derive(Debug, Clone, clap::Parser)]
 pub struct BlobBatchPartArgs {
@@ -613,9 +976,9 @@ pub async fn blob_usage(args: &StateArgs) -> Result<(), anyhow::Error> {
 /// return static Codec names, and rebind the names if/when we get a CodecMismatch, so we can convince
 /// the type system and our safety checks that we really can read the data.
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct K;
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct V;
 #[derive(Debug)]
 struct T;
@@ -706,3 +1069,75 @@ impl Semigroup for D {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{new_test_client, test_data};
+
+    use super::*;
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn state_history_summaries() {
+        let data = test_data(3);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data[..2], 0, 3).await;
+        read.downgrade_since(&Antichain::from_elem(2)).await;
+        write.expect_compare_and_append(&data[2..], 3, 5).await;
+
+        let history = |since_seqno, limit| {
+            state_history(
+                client.consensus.as_ref(),
+                &client.cfg.build_version,
+                &shard_id,
+                since_seqno,
+                limit,
+            )
+        };
+        let entries = history(SeqNo(0), SCAN_ALL).await.expect("valid scan");
+        assert!(entries
+            .windows(2)
+            .all(|window| window[0].seqno < window[1].seqno));
+        assert!(entries.iter().all(|entry| entry.diff_bytes > 0));
+
+        // Each write shows up as advancing the upper and adding a batch.
+        let appends: Vec<_> = entries
+            .iter()
+            .filter_map(|entry| Some((entry.upper.clone()?, entry.batch_count_delta)))
+            .collect();
+        assert_eq!(appends[0], (vec![3], 1));
+        assert_eq!(appends.last().map(|(upper, _)| upper), Some(&vec![5]));
+
+        // The reader's downgrade shows up as a since downgrade, after the first
+        // write and before the second.
+        let since = entries
+            .iter()
+            .position(|entry| entry.since == Some(vec![2]))
+            .expect("since downgrade");
+        let first_write = entries
+            .iter()
+            .position(|entry| entry.upper == Some(vec![3]))
+            .expect("first write");
+        let second_write = entries
+            .iter()
+            .position(|entry| entry.upper == Some(vec![5]))
+            .expect("second write");
+        assert!(first_write < since && since < second_write);
+
+        // The history can be restricted to a range of seqnos.
+        let restricted = history(entries[since].seqno, 1).await.expect("valid scan");
+        assert_eq!(restricted, vec![entries[since].clone()]);
+        let text = restricted[0].to_string();
+        assert!(
+            text.starts_with(&format!("{} ", entries[since].seqno)),
+            "{}",
+            text
+        );
+        assert!(text.contains(" since=[2]"), "{}", text);
+    }
+}
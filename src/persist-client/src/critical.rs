@@ -319,12 +319,11 @@ where
         let mut machine = self.machine.clone();
         async move {
             let batches = machine.snapshot(&as_of).await?;
-            let num_updates = batches.iter().map(|b| b.len).sum();
-            Ok(SnapshotStats {
-                shard_id: machine.shard_id(),
+            Ok(SnapshotStats::from_batches(
+                machine.shard_id(),
                 as_of,
-                num_updates,
-            })
+                &batches,
+            ))
         }
     }
 
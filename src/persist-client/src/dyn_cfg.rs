@@ -57,7 +57,7 @@
 
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU64};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 use tracing::error;
 
@@ -193,6 +193,12 @@ pub trait ConfigType: Sized {
 #[derive(Clone, Default)]
 pub struct ConfigSet {
     configs: BTreeMap<String, ConfigEntry>,
+    /// Serializes [Self::snapshot] with bulk updates of the values in this set
+    /// (see [ConfigUpdates::apply]), so that a snapshot never observes a
+    /// partially applied update.
+    ///
+    /// Shared by clones of this set, the same as the values themselves.
+    update_lock: Arc<Mutex<()>>,
 }
 
 impl ConfigSet {
@@ -217,6 +223,65 @@ impl ConfigSet {
     pub fn entries(&self) -> impl Iterator<Item = &ConfigEntry> {
         self.configs.values()
     }
+
+    /// Captures the current values of all configs in this set.
+    ///
+    /// The snapshot is consistent with respect to [Self::restore] and
+    /// [ConfigUpdates::apply]: it observes either all or none of the values
+    /// written by any one of them.
+    pub fn snapshot(&self) -> ConfigSnapshot {
+        let _guard = self.update_lock.lock().expect("lock poisoned");
+        let mut updates = ConfigUpdates::default();
+        for entry in self.entries() {
+            updates.add(entry);
+        }
+        ConfigSnapshot { updates }
+    }
+
+    /// Reverts the configs in this set to the values captured in `snapshot`.
+    ///
+    /// Configs that were registered to this set after the snapshot was taken
+    /// are left untouched. Like [Self::snapshot], this is atomic with respect
+    /// to other snapshots and bulk updates, but a reader using [Config::get]
+    /// to get several configs may still see some reverted and some not.
+    pub fn restore(&self, snapshot: &ConfigSnapshot) {
+        snapshot.updates.apply(self)
+    }
+}
+
+/// The values of all configs in a [ConfigSet] at some point in time, as
+/// returned by [ConfigSet::snapshot].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigSnapshot {
+    updates: ConfigUpdates,
+}
+
+/// Reverts the configs in a [ConfigSet] to their values at the time this guard
+/// was created when it is dropped.
+///
+/// This is intended for tests that tweak configs of a set that's shared with
+/// other tests (e.g. via a `PersistClientCache`).
+#[derive(Debug)]
+pub struct ConfigGuard {
+    set: ConfigSet,
+    snapshot: ConfigSnapshot,
+}
+
+impl ConfigGuard {
+    /// Snapshots the given set, to be restored when the returned guard is
+    /// dropped.
+    pub fn new(set: &ConfigSet) -> Self {
+        ConfigGuard {
+            set: set.clone(),
+            snapshot: set.snapshot(),
+        }
+    }
+}
+
+impl Drop for ConfigGuard {
+    fn drop(&mut self) {
+        self.set.restore(&self.snapshot)
+    }
 }
 
 /// An entry for a config in a [ConfigSet].
@@ -300,6 +365,7 @@ impl ConfigUpdates {
             T::set(dst, T::get(src))
         }
 
+        let _guard = set.update_lock.lock().expect("lock poisoned");
         for ProtoConfigVal { name, val } in self.updates.iter() {
             let Some(config) = set.configs.get(name) else {
                 error!("config update {} {:?} not known set: {:?}", name, val, set);
@@ -431,7 +497,10 @@ mod impls {
 
     impl std::fmt::Debug for ConfigSet {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            let ConfigSet { configs } = self;
+            let ConfigSet {
+                configs,
+                update_lock: _,
+            } = self;
             f.debug_map()
                 .entries(configs.iter().map(|(name, val)| (name, val.val())))
                 .finish()
@@ -441,6 +510,8 @@ mod impls {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::Ordering;
+
     use super::*;
 
     const BOOL: Config<bool> = Config::new("bool", true, "");
@@ -486,4 +557,68 @@ mod tests {
         updates.apply(&c1);
         assert_eq!(USIZE.get(&c1), 2);
     }
+
+    #[mz_ore::test]
+    fn snapshot_restore() {
+        let configs = ConfigSet::default().add(&BOOL).add(&USIZE);
+        let snapshot = configs.snapshot();
+
+        bool::set(bool::shared(&BOOL, &configs).unwrap(), false);
+        usize::set(usize::shared(&USIZE, &configs).unwrap(), 2);
+        configs.restore(&snapshot);
+        assert!(BOOL.get(&configs));
+        assert_eq!(USIZE.get(&configs), 1);
+
+        // Configs registered after the snapshot are left untouched.
+        let configs = configs.add(&STRING);
+        usize::set(usize::shared(&USIZE, &configs).unwrap(), 3);
+        String::set(String::shared(&STRING, &configs).unwrap(), "b".to_owned());
+        configs.restore(&snapshot);
+        assert_eq!(USIZE.get(&configs), 1);
+        assert_eq!(STRING.get(&configs), "b");
+
+        // The guard restores on drop, including for clones of the set.
+        {
+            let _guard = ConfigGuard::new(&configs);
+            let clone = configs.clone();
+            usize::set(usize::shared(&USIZE, &clone).unwrap(), 4);
+            assert_eq!(USIZE.get(&configs), 4);
+        }
+        assert_eq!(USIZE.get(&configs), 1);
+    }
+
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn snapshot_restore_concurrent() {
+        let configs = ConfigSet::default().add(&BOOL).add(&USIZE).add(&STRING);
+        let a = configs.snapshot();
+        bool::set(bool::shared(&BOOL, &configs).unwrap(), false);
+        usize::set(usize::shared(&USIZE, &configs).unwrap(), 2);
+        String::set(String::shared(&STRING, &configs).unwrap(), "b".to_owned());
+        let b = configs.snapshot();
+        assert_ne!(a, b);
+
+        // Readers only ever observe all of one snapshot or the other, never a
+        // mix of the two.
+        let done = Arc::new(AtomicBool::new(false));
+        let readers = (0..2)
+            .map(|_| {
+                let (configs, a, b) = (configs.clone(), a.clone(), b.clone());
+                let done = Arc::clone(&done);
+                std::thread::spawn(move || {
+                    while !done.load(Ordering::SeqCst) {
+                        let snapshot = configs.snapshot();
+                        assert!(snapshot == a || snapshot == b, "{:?}", snapshot);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for i in 0..1000 {
+            configs.restore(if i % 2 == 0 { &a } else { &b });
+        }
+        done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            reader.join().expect("reader panicked");
+        }
+    }
 }
@@ -9,12 +9,19 @@
 
 //! Errors for the crate
 
+use std::any::Any;
 use std::fmt::Debug;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
 
+use mz_ore::metrics::IntCounter;
 use timely::progress::{Antichain, Timestamp};
 
+use crate::fetch::FetchBatchError;
 use crate::internal::paths::PartialBatchKey;
-use crate::ShardId;
+use crate::internal::state::Since;
+use crate::write::WriterId;
+use crate::{Diagnostics, SchemaId, ShardId};
 
 /// An error resulting from invalid usage of the API.
 #[derive(Debug)]
@@ -61,7 +68,8 @@ pub enum InvalidUsage<T> {
         /// The expected upper of the batch
         expected_upper: Antichain<T>,
     },
-    /// A [crate::batch::Batch] or [crate::fetch::LeasedBatchPart] was
+    /// A [crate::batch::Batch] (possibly rehydrated from a
+    /// [crate::batch::ProtoBatch]) or [crate::fetch::LeasedBatchPart] was
     /// given to a [crate::write::WriteHandle] from a different shard
     BatchNotFromThisShard {
         /// The shard of the batch
@@ -69,6 +77,13 @@ pub enum InvalidUsage<T> {
         /// The shard of the handle
         handle_shard: ShardId,
     },
+    /// Two [crate::batch::Batch]es were merged whose bounds don't line up.
+    NonAdjacentBatches {
+        /// The upper of the first batch
+        upper: Antichain<T>,
+        /// The lower of the second batch
+        lower: Antichain<T>,
+    },
     /// Attempted to finalize a shard without advancing frontiers.
     FinalizationError {
         /// The current since of the shard.
@@ -78,6 +93,39 @@ pub enum InvalidUsage<T> {
     },
     /// The requested codecs don't match the actual ones in durable storage.
     CodecMismatch(Box<CodecMismatch>),
+    /// The blob and consensus of a location can't belong together, e.g. an
+    /// in-mem blob with a durable consensus.
+    IncompatibleLocation {
+        /// The scheme of the blob URI.
+        blob_scheme: String,
+        /// The scheme of the consensus URI.
+        consensus_scheme: String,
+    },
+    /// A tombstone purge was requested without acknowledging that it is
+    /// irreversible.
+    PurgeNotForced {
+        /// The shard that was to be purged.
+        shard_id: ShardId,
+    },
+    /// A handle was requested for a shard that has never been used, but
+    /// [crate::OpenOptions::create_if_missing] was false.
+    ShardNeverUsed {
+        /// The shard that was to be opened.
+        shard_id: ShardId,
+    },
+    /// A batch was to be written with a schema that isn't a compatible
+    /// evolution of the one the shard was opened with.
+    IncompatibleSchema {
+        /// The id of the incompatible schema.
+        schema_id: SchemaId,
+    },
+    /// The task merging [crate::write::WriteHandle::append_coalesced] appends
+    /// went away before reporting the outcome of an append, e.g. because the
+    /// runtime is shutting down. The append may or may not have been applied.
+    CoalescerExited {
+        /// The writer whose appends were being merged.
+        writer_id: WriterId,
+    },
 }
 
 impl<T: Debug> std::fmt::Display for InvalidUsage<T> {
@@ -117,6 +165,11 @@ impl<T: Debug> std::fmt::Display for InvalidUsage<T> {
                 batch_shard,
                 handle_shard,
             } => write!(f, "batch was from {} not {}", batch_shard, handle_shard),
+            InvalidUsage::NonAdjacentBatches { upper, lower } => write!(
+                f,
+                "cannot merge batch with upper {:?} and batch with lower {:?}",
+                upper, lower
+            ),
             InvalidUsage::FinalizationError { since, upper } => {
                 write!(
                     f,
@@ -125,6 +178,28 @@ impl<T: Debug> std::fmt::Display for InvalidUsage<T> {
             }
 
             InvalidUsage::CodecMismatch(err) => std::fmt::Display::fmt(err, f),
+            InvalidUsage::IncompatibleLocation {
+                blob_scheme,
+                consensus_scheme,
+            } => write!(
+                f,
+                "blob {blob_scheme}:// and consensus {consensus_scheme}:// cannot be used together"
+            ),
+            InvalidUsage::PurgeNotForced { shard_id } => write!(
+                f,
+                "purging tombstone {shard_id} is irreversible and must be forced"
+            ),
+            InvalidUsage::ShardNeverUsed { shard_id } => {
+                write!(f, "shard {shard_id} has never been used")
+            }
+            InvalidUsage::IncompatibleSchema { schema_id } => write!(
+                f,
+                "schema {schema_id:?} is incompatible with the shard's schema"
+            ),
+            InvalidUsage::CoalescerExited { writer_id } => write!(
+                f,
+                "coalescer for writer {writer_id} exited before applying the append"
+            ),
         }
     }
 }
@@ -145,6 +220,23 @@ pub struct CodecMismatch {
     /// The last element in the tuple is Some when the name of the codecs match,
     /// but the concrete types don't: e.g. mz_repr::Timestamp and u64.
     pub(crate) actual: (String, String, String, String, Option<CodecConcreteType>),
+    /// The diagnostics of the handle that initialized the shard and so
+    /// registered the actual codecs, if known.
+    pub(crate) registered_by: Option<Diagnostics>,
+}
+
+impl CodecMismatch {
+    /// The requested (K, V, T, D) codec names.
+    pub fn requested(&self) -> (&str, &str, &str, &str) {
+        let (k, v, t, d, _) = &self.requested;
+        (k, v, t, d)
+    }
+
+    /// The (K, V, T, D) codec names in durable storage.
+    pub fn actual(&self) -> (&str, &str, &str, &str) {
+        let (k, v, t, d, _) = &self.actual;
+        (k, v, t, d)
+    }
 }
 
 impl std::error::Error for CodecMismatch {}
@@ -155,7 +247,15 @@ impl std::fmt::Display for CodecMismatch {
             f,
             "requested codecs {:?} did not match ones in durable storage {:?}",
             self.requested, self.actual
-        )
+        )?;
+        if let Some(registered_by) = &self.registered_by {
+            write!(
+                f,
+                " registered by handle {:?} of shard {:?}",
+                registered_by.handle_purpose, registered_by.shard_name
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -177,6 +277,29 @@ impl<T> From<Box<CodecMismatch>> for InvalidUsage<T> {
     }
 }
 
+/// A caller-provided decision of whether a [CodecMismatch] against durable
+/// storage may be ignored, see [crate::PersistClient::open_with_codec_check].
+#[derive(Clone)]
+pub(crate) struct AllowCodecMismatch(Arc<dyn Fn(&CodecMismatch) -> bool + Send + Sync>);
+
+impl AllowCodecMismatch {
+    pub(crate) fn new(allow: impl Fn(&CodecMismatch) -> bool + Send + Sync + 'static) -> Self {
+        AllowCodecMismatch(Arc::new(allow))
+    }
+
+    /// Returns whether the given mismatch is allowed, which it never is
+    /// without a hook.
+    pub(crate) fn allows(this: Option<&Self>, mismatch: &CodecMismatch) -> bool {
+        this.map_or(false, |allow| (allow.0)(mismatch))
+    }
+}
+
+impl Debug for AllowCodecMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AllowCodecMismatch").finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct CodecMismatchT {
     /// The requested T codec.
@@ -217,3 +340,121 @@ impl<T: Timestamp> std::fmt::Display for UpperMismatch<T> {
         )
     }
 }
+
+/// A panic in persist-internal work (e.g. decoding or compaction) that was
+/// caught and converted into an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalPanic {
+    /// The name of the operation that panicked.
+    pub operation: String,
+    /// The panic message.
+    pub message: String,
+}
+
+impl InternalPanic {
+    /// Returns an [InternalPanic] for the given panic payload.
+    pub(crate) fn from_payload(operation: impl Into<String>, payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).to_owned(),
+                Err(_) => "<non-string panic payload>".to_owned(),
+            },
+        };
+        InternalPanic {
+            operation: operation.into(),
+            message,
+        }
+    }
+
+    /// Runs `f`, converting a panic into an [InternalPanic] and counting it in
+    /// `panics`.
+    ///
+    /// Like [crate::async_runtime::IsolatedRuntime::spawn_catching], this uses
+    /// [mz_ore::panic::catch_unwind], so the panic is caught even in processes
+    /// that abort on panic.
+    pub(crate) fn catch<R>(
+        operation: &str,
+        panics: &IntCounter,
+        f: impl FnOnce() -> R,
+    ) -> Result<R, InternalPanic> {
+        mz_ore::panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+            panics.inc();
+            InternalPanic::from_payload(operation, payload)
+        })
+    }
+
+    /// Re-raises this panic.
+    ///
+    /// Used for operations that mutate durable state, where continuing after
+    /// a panic is unsafe.
+    pub(crate) fn abort(self) -> ! {
+        panic!("{}", self)
+    }
+}
+
+impl std::error::Error for InternalPanic {}
+
+impl std::fmt::Display for InternalPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "internal panic in {}: {}", self.operation, self.message)
+    }
+}
+
+/// An error returned from [crate::read::ReadHandle::snapshot_and_fetch].
+#[derive(Debug)]
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+pub enum SnapshotError<T> {
+    /// The requested as_of was not beyond the since of the shard.
+    Since(Since<T>),
+    /// Decoding the snapshot panicked.
+    InternalPanic(InternalPanic),
+    /// A part of the snapshot could not be fetched from blob storage.
+    Fetch(FetchBatchError),
+    /// The data of the snapshot was garbage collected while it was being read,
+    /// and the since of the shard advanced past the `as_of` of the read.
+    ///
+    /// Only returned by [crate::read::ReadOnlyHandle], which doesn't hold back
+    /// the since or garbage collection of the shard. Includes the since of the
+    /// shard at the time of the failure: the read can be retried at an `as_of`
+    /// beyond it.
+    SinceAdvanced(Since<T>),
+    /// The data of the snapshot was garbage collected while it was being read,
+    /// after compaction replaced it with equivalent data.
+    ///
+    /// Only returned by [crate::read::ReadOnlyHandle]. Unlike
+    /// [SnapshotError::SinceAdvanced], the since of the shard hadn't passed
+    /// the `as_of` of the read at the time of the failure, so the read can be
+    /// retried at the same `as_of`.
+    Compacted,
+}
+
+impl<T: Debug> std::fmt::Display for SnapshotError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Since(since) => {
+                write!(f, "as_of not beyond since {:?}", since.0.elements())
+            }
+            SnapshotError::InternalPanic(err) => std::fmt::Display::fmt(err, f),
+            SnapshotError::Fetch(err) => write!(f, "could not fetch batch part: {}", err),
+            SnapshotError::SinceAdvanced(since) => {
+                write!(f, "since advanced to {:?} during read", since.0.elements())
+            }
+            SnapshotError::Compacted => write!(f, "data compacted away during read"),
+        }
+    }
+}
+
+impl<T: Debug> std::error::Error for SnapshotError<T> {}
+
+impl<T> From<Since<T>> for SnapshotError<T> {
+    fn from(x: Since<T>) -> Self {
+        SnapshotError::Since(x)
+    }
+}
+
+impl<T> From<InternalPanic> for SnapshotError<T> {
+    fn from(x: InternalPanic) -> Self {
+        SnapshotError::InternalPanic(x)
+    }
+}
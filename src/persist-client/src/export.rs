@@ -0,0 +1,379 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Export of shard contents to formats understood by tools outside of persist.
+
+use std::fmt::Debug;
+use std::io::Write;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use arrow2::array::{Array, BinaryArray, PrimitiveArray};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema as ArrowSchema};
+use arrow2::io::parquet::write::{
+    CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+};
+use differential_dataflow::difference::Semigroup;
+use differential_dataflow::lattice::Lattice;
+use futures::{AsyncWrite, AsyncWriteExt};
+use mz_persist_types::columnar::{PartEncoder, Schema};
+use mz_persist_types::part::{Part, PartBuilder};
+use mz_persist_types::{Codec, Codec64};
+use timely::progress::{Antichain, Timestamp};
+
+use crate::internal::encoding::Schemas;
+use crate::read::ReadHandle;
+
+/// How keys and values are represented in a Parquet export of a shard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// As the bytes produced by their [Codec], in binary columns.
+    Encoded,
+    /// As struct columns with the fields described by their [Schema].
+    ///
+    /// This requires the schemas of both keys and values to support columnar
+    /// encoding.
+    Columnar,
+}
+
+/// Streams a consolidated snapshot of the shard read by `read` as of `as_of`
+/// into `writer` as a Parquet file, returning the number of updates written.
+///
+/// The file has the following columns:
+/// - `k` and `v`: the key and value, either encoded or expanded into the
+///   columns of their schema, depending on `format`. In the latter case, a
+///   schema without any columns results in a missing column.
+/// - `t`: the timestamp, as the bytes produced by its [Codec64].
+/// - `t_u64`: the same bytes interpreted as a little-endian u64. This is the
+///   timestamp itself for u64 and `mz_repr::Timestamp`, but meaningless for
+///   other timestamp types.
+/// - `d`: the diff, as the bytes produced by its [Codec64] interpreted as a
+///   little-endian i64 (as in persist's own columnar encoding).
+///
+/// Updates are consolidated by their encoded keys and values. To bound
+/// memory usage, they are written out in row groups of roughly
+/// `row_group_bytes` of encoded keys and values.
+pub(crate) async fn export_snapshot_parquet<K, V, T, D, W>(
+    read: &mut ReadHandle<K, V, T, D>,
+    as_of: Antichain<T>,
+    format: ExportFormat,
+    row_group_bytes: usize,
+    mut writer: W,
+) -> Result<usize, anyhow::Error>
+where
+    K: Debug + Codec + Ord,
+    V: Debug + Codec + Ord,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+    W: AsyncWrite + Unpin,
+{
+    let schemas = read.schemas.clone();
+    let (schema, encodings) = arrow_schema(format, &schemas)?;
+    let options = WriteOptions {
+        write_statistics: false,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+        data_pagesize_limit: None, // use default limit
+    };
+
+    // The parquet writer is synchronous, so have it write into a buffer that
+    // we drain into `writer` after each row group.
+    let buf = SharedBuf::default();
+    let mut file = FileWriter::try_new(buf.clone(), schema.clone(), options)?;
+
+    let mut cursor = read
+        .snapshot_cursor(as_of, |_| true)
+        .await
+        .map_err(|since| anyhow!("as_of is not readable at since {:?}", since.0))?;
+    let mut updates = Vec::new();
+    let mut updates_bytes = 0;
+    let mut count = 0;
+    while let Some(iter) = cursor.next_encoded().await {
+        for (k, v, t, d) in iter {
+            updates_bytes += k.len() + v.len();
+            updates.push((k.to_vec(), v.to_vec(), t, d));
+            if updates_bytes >= row_group_bytes {
+                let chunk = row_group(format, &schemas, &updates)?;
+                count += updates.len();
+                updates.clear();
+                updates_bytes = 0;
+                let row_groups = RowGroupIterator::try_new(
+                    std::iter::once(Ok(chunk)),
+                    &schema,
+                    options,
+                    encodings.clone(),
+                )?;
+                for group in row_groups {
+                    file.write(group?)?;
+                }
+                writer.write_all(&buf.take()).await?;
+            }
+        }
+    }
+    if !updates.is_empty() {
+        let chunk = row_group(format, &schemas, &updates)?;
+        count += updates.len();
+        let row_groups =
+            RowGroupIterator::try_new(std::iter::once(Ok(chunk)), &schema, options, encodings)?;
+        for group in row_groups {
+            file.write(group?)?;
+        }
+    }
+    let _ = file.end(None)?;
+    writer.write_all(&buf.take()).await?;
+    writer.flush().await?;
+    Ok(count)
+}
+
+/// Returns the arrow schema of a Parquet export in the given format, along
+/// with the parquet encodings of its fields.
+fn arrow_schema<K: Codec, V: Codec>(
+    format: ExportFormat,
+    schemas: &Schemas<K, V>,
+) -> Result<(ArrowSchema, Vec<Vec<Encoding>>), anyhow::Error> {
+    let (mut fields, mut encodings) = (Vec::new(), Vec::new());
+    match format {
+        ExportFormat::Encoded => {
+            for name in ["k", "v"] {
+                fields.push(Field::new(name, DataType::Binary, false));
+                encodings.push(vec![Encoding::Plain]);
+            }
+        }
+        ExportFormat::Columnar => {
+            // The struct types only depend on the schemas, so derive them from
+            // an empty part.
+            let part = PartBuilder::new(schemas.key.as_ref(), schemas.val.as_ref())
+                .finish()
+                .map_err(anyhow::Error::msg)?;
+            let (key, val) = (part.key_arrow(), part.val_arrow());
+            for (name, col) in [("k", key), ("v", val)] {
+                if let Some((array, col_encodings)) = col {
+                    fields.push(Field::new(name, array.data_type().clone(), false));
+                    encodings.push(col_encodings);
+                }
+            }
+        }
+    }
+    fields.push(Field::new("t", DataType::Binary, false));
+    encodings.push(vec![Encoding::Plain]);
+    fields.push(Field::new("t_u64", DataType::UInt64, false));
+    encodings.push(vec![Encoding::Plain]);
+    fields.push(Field::new("d", DataType::Int64, false));
+    encodings.push(vec![Encoding::Plain]);
+    Ok((ArrowSchema::from(fields), encodings))
+}
+
+/// Returns the columns of a Parquet export in the given format for the given
+/// updates.
+fn row_group<K: Codec, V: Codec, T: Codec64 + Clone, D: Codec64 + Clone>(
+    format: ExportFormat,
+    schemas: &Schemas<K, V>,
+    updates: &[(Vec<u8>, Vec<u8>, T, D)],
+) -> Result<Chunk<Box<dyn Array>>, anyhow::Error> {
+    let mut arrays = Vec::<Box<dyn Array>>::new();
+    match format {
+        ExportFormat::Encoded => {
+            let keys = updates.iter().map(|(k, _, _, _)| k);
+            arrays.push(Box::new(BinaryArray::<i32>::from_iter_values(keys)));
+            let vals = updates.iter().map(|(_, v, _, _)| v);
+            arrays.push(Box::new(BinaryArray::<i32>::from_iter_values(vals)));
+        }
+        ExportFormat::Columnar => {
+            let part = columnar_part(schemas, updates)?;
+            if let Some((array, _)) = part.key_arrow() {
+                arrays.push(Box::new(array));
+            }
+            if let Some((array, _)) = part.val_arrow() {
+                arrays.push(Box::new(array));
+            }
+        }
+    }
+    let ts = updates.iter().map(|(_, _, t, _)| T::encode(t));
+    arrays.push(Box::new(BinaryArray::<i32>::from_iter_values(ts)));
+    let ts_u64 = updates
+        .iter()
+        .map(|(_, _, t, _)| u64::from_le_bytes(T::encode(t)));
+    arrays.push(Box::new(PrimitiveArray::<u64>::from_vec(ts_u64.collect())));
+    let diffs = updates
+        .iter()
+        .map(|(_, _, _, d)| i64::from_le_bytes(D::encode(d)));
+    arrays.push(Box::new(PrimitiveArray::<i64>::from_vec(diffs.collect())));
+    Ok(Chunk::new(arrays))
+}
+
+/// Decodes the given updates and re-encodes them into a [Part] with the given
+/// schemas.
+fn columnar_part<K: Codec, V: Codec, T: Codec64 + Clone, D: Codec64 + Clone>(
+    schemas: &Schemas<K, V>,
+    updates: &[(Vec<u8>, Vec<u8>, T, D)],
+) -> Result<Part, anyhow::Error> {
+    let mut part = PartBuilder::new(schemas.key.as_ref(), schemas.val.as_ref());
+    {
+        let part_mut = part.get_mut();
+        let mut key_encoder = schemas
+            .key
+            .encoder(part_mut.key)
+            .map_err(anyhow::Error::msg)?;
+        let mut val_encoder = schemas
+            .val
+            .encoder(part_mut.val)
+            .map_err(anyhow::Error::msg)?;
+        let (mut ts, mut diffs) = (part_mut.ts, part_mut.diff);
+        for (k, v, t, d) in updates {
+            let k = K::decode(k).map_err(|err| anyhow!("decoding key: {}", err))?;
+            let v = V::decode(v).map_err(|err| anyhow!("decoding val: {}", err))?;
+            key_encoder.encode(&k);
+            val_encoder.encode(&v);
+            // Only the key and val columns of the part are exported, but the
+            // part is only valid if all of its columns have the same length.
+            ts.push(t.clone());
+            diffs.push(d.clone());
+        }
+    }
+    part.finish().map_err(anyhow::Error::msg)
+}
+
+/// A [Write] implementation that appends to a shared, drainable buffer.
+#[derive(Debug, Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuf {
+    /// Removes and returns everything written so far.
+    fn take(&self) -> Vec<u8> {
+        mem::take(&mut *self.0.lock().expect("lock poisoned"))
+    }
+}
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().expect("lock poisoned").extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use arrow2::array::{StructArray, Utf8Array};
+    use arrow2::io::parquet::read::{infer_schema, read_metadata, FileReader};
+    use mz_persist_types::codec_impls::StringSchema;
+
+    use crate::tests::new_test_client;
+    use crate::ShardId;
+
+    use super::*;
+
+    /// Returns the strings in an exported key or val column.
+    fn strings(format: ExportFormat, array: &dyn Array) -> Vec<String> {
+        match format {
+            ExportFormat::Encoded => {
+                let array = array
+                    .as_any()
+                    .downcast_ref::<BinaryArray<i32>>()
+                    .expect("binary column");
+                array
+                    .values_iter()
+                    .map(|x| String::from_utf8(x.to_vec()).expect("valid utf8"))
+                    .collect()
+            }
+            ExportFormat::Columnar => {
+                let array = array
+                    .as_any()
+                    .downcast_ref::<StructArray>()
+                    .expect("struct column");
+                let array = array.values()[0]
+                    .as_any()
+                    .downcast_ref::<Utf8Array<i32>>()
+                    .expect("utf8 column");
+                array.values_iter().map(|x| x.to_owned()).collect()
+            }
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn export_snapshot_parquet() {
+        let data = [
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("3".to_owned(), "three".to_owned()), 3, 1),
+            (("2".to_owned(), "two".to_owned()), 3, -1),
+        ];
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 4).await;
+
+        for format in [ExportFormat::Encoded, ExportFormat::Columnar] {
+            let mut buf = Vec::new();
+            let count = client
+                .export_snapshot_parquet::<String, String, u64, i64, _>(
+                    shard_id,
+                    Antichain::from_elem(3),
+                    Arc::new(StringSchema),
+                    Arc::new(StringSchema),
+                    format,
+                    &mut buf,
+                )
+                .await
+                .expect("export succeeds");
+            assert_eq!(count, 2);
+
+            let mut buf = Cursor::new(buf);
+            let metadata = read_metadata(&mut buf).expect("valid parquet");
+            let schema = infer_schema(&metadata).expect("valid schema");
+            let names = schema
+                .fields
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(names, vec!["k", "v", "t", "t_u64", "d"]);
+
+            let reader = FileReader::new(buf, metadata.row_groups, schema, None, None, None);
+            let mut actual = Vec::new();
+            for chunk in reader {
+                let chunk = chunk.expect("valid row group");
+                let arrays = chunk.arrays();
+                let keys = strings(format, arrays[0].as_ref());
+                let vals = strings(format, arrays[1].as_ref());
+                let ts = arrays[2]
+                    .as_any()
+                    .downcast_ref::<BinaryArray<i32>>()
+                    .expect("binary column");
+                let ts_u64 = arrays[3]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<u64>>()
+                    .expect("u64 column");
+                let diffs = arrays[4]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i64>>()
+                    .expect("i64 column");
+                for (idx, (k, v)) in keys.into_iter().zip(vals).enumerate() {
+                    assert_eq!(ts.value(idx), 3u64.to_le_bytes());
+                    actual.push((k, v, ts_u64.value(idx), diffs.value(idx)));
+                }
+            }
+            let expected = vec![
+                ("1".to_owned(), "one".to_owned(), 3, 1),
+                ("3".to_owned(), "three".to_owned(), 3, 1),
+            ];
+            assert_eq!(actual, expected, "{:?}", format);
+        }
+    }
+}
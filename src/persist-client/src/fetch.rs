@@ -9,28 +9,29 @@
 
 //! Fetching batches of data from persist's backing store
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::future::Future;
 use std::marker::PhantomData;
 use std::sync::Arc;
 use std::time::Instant;
 
-use anyhow::anyhow;
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
 use differential_dataflow::trace::Description;
 use mz_ore::cast::CastFrom;
 use mz_persist::indexed::encoding::BlobTraceBatchPart;
-use mz_persist::location::{Blob, SeqNo};
+use mz_persist::location::{Blob, ExternalError, SeqNo};
 use mz_persist_types::{Codec, Codec64};
 use serde::{Deserialize, Serialize};
 use timely::progress::frontier::AntichainRef;
 use timely::progress::{Antichain, Timestamp};
 use timely::PartialOrder;
-use tracing::{debug_span, trace_span, Instrument};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug_span, trace_span, warn, Instrument};
 
 use crate::error::InvalidUsage;
 use crate::internal::encoding::{LazyPartStats, Schemas};
-use crate::internal::machine::retry_external;
+use crate::internal::machine::retry_external_bounded;
 use crate::internal::metrics::{Metrics, ReadMetrics, ShardMetrics};
 use crate::internal::paths::{BlobKey, PartialBatchKey};
 use crate::read::LeasedReaderId;
@@ -67,17 +68,23 @@ where
 {
     /// Takes a [`SerdeLeasedBatchPart`] into a [`LeasedBatchPart`].
     pub fn leased_part_from_exchangeable(&self, x: SerdeLeasedBatchPart) -> LeasedBatchPart<T> {
-        LeasedBatchPart::from(x, Arc::clone(&self.metrics))
+        // Parts fetched this way are not tied to a `ReadHandle` in this
+        // process, so there is nowhere to return their lease to on drop.
+        LeasedBatchPart::from(x, Arc::clone(&self.metrics), None)
     }
 
     /// Trade in an exchange-able [LeasedBatchPart] for the data it represents.
     ///
     /// Note to check the `LeasedBatchPart` documentation for how to handle the
     /// returned value.
+    ///
+    /// The inner error is returned if the part could not be fetched, see
+    /// [FetchBatchError]. The part's lease is unaffected, so the fetch may be
+    /// retried.
     pub async fn fetch_leased_part(
         &self,
         part: &LeasedBatchPart<T>,
-    ) -> Result<FetchedPart<K, V, T, D>, InvalidUsage<T>> {
+    ) -> Result<Result<FetchedPart<K, V, T, D>, FetchBatchError>, InvalidUsage<T>> {
         if &part.shard_id != &self.shard_id {
             let batch_shard = part.shard_id.clone();
             return Err(InvalidUsage::BatchNotFromThisShard {
@@ -179,10 +186,10 @@ pub(crate) async fn fetch_leased_part<K, V, T, D>(
     blob: &(dyn Blob + Send + Sync),
     metrics: Arc<Metrics>,
     read_metrics: &ReadMetrics,
-    shard_metrics: &ShardMetrics,
+    shard_metrics: &Arc<ShardMetrics>,
     reader_id: Option<&LeasedReaderId>,
     schemas: Schemas<K, V>,
-) -> FetchedPart<K, V, T, D>
+) -> Result<FetchedPart<K, V, T, D>, FetchBatchError>
 where
     K: Debug + Codec,
     V: Debug + Codec,
@@ -199,9 +206,11 @@ where
         read_metrics,
         &part.key,
         &part.desc,
+        part.checksum,
     )
-    .await
-    .unwrap_or_else(|blob_key| {
+    .await;
+    let encoded_part = match encoded_part {
+        Ok(encoded_part) => encoded_part,
         // Ideally, readers should never encounter a missing blob. They place a seqno
         // hold as they consume their snapshot/listen, preventing any blobs they need
         // from being deleted by garbage collection, and all blob implementations are
@@ -210,31 +219,189 @@ where
         // If we do have a bug and a reader does encounter a missing blob, the state
         // cannot be recovered, and our best option is to panic and retry the whole
         // process.
-        panic!(
-            "{} could not fetch batch part: {}",
-            reader_id
-                .map(|id| id.to_string())
-                .unwrap_or_else(|| "batch fetcher".to_string()),
-            blob_key
-        )
-    });
-    let fetched_part = FetchedPart {
+        Err(FetchBatchError::Missing(blob_key)) => {
+            panic!(
+                "{} could not fetch batch part: missing blob {}",
+                reader_name(reader_id),
+                blob_key
+            )
+        }
+        Err(err) => return Err(err),
+    };
+    let filter_pushdown_audit = if part.filter_pushdown_audit {
+        part.stats.clone()
+    } else {
+        None
+    };
+    Ok(FetchedPart::new(
         metrics,
+        Arc::clone(shard_metrics),
         ts_filter,
-        part: encoded_part,
+        encoded_part,
         schemas,
-        filter_pushdown_audit: if part.filter_pushdown_audit {
-            part.stats.clone()
-        } else {
-            None
-        },
-        part_cursor: Cursor::default(),
-        _phantom: PhantomData,
-    };
+        filter_pushdown_audit,
+    ))
+}
 
-    fetched_part
+/// Like [fetch_leased_part], but for readers that have no way to surface a
+/// [FetchBatchError] to their caller.
+///
+/// Transient errors are retried by [retry_leased_fetch]. Any other error, or
+/// a transient one that persists past its retries, panics.
+pub(crate) async fn fetch_leased_part_retrying<K, V, T, D>(
+    part: &LeasedBatchPart<T>,
+    blob: &(dyn Blob + Send + Sync),
+    metrics: Arc<Metrics>,
+    read_metrics: &ReadMetrics,
+    shard_metrics: &Arc<ShardMetrics>,
+    reader_id: Option<&LeasedReaderId>,
+    schemas: Schemas<K, V>,
+) -> FetchedPart<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    let reader_name = reader_name(reader_id);
+    let fetched_part = retry_leased_fetch(&reader_name, || {
+        fetch_leased_part(
+            part,
+            blob,
+            Arc::clone(&metrics),
+            read_metrics,
+            shard_metrics,
+            reader_id,
+            schemas.clone(),
+        )
+    })
+    .await;
+    match fetched_part {
+        Ok(fetched_part) => fetched_part,
+        Err(err) => panic!("{} could not fetch batch part: {}", reader_name, err),
+    }
+}
+
+/// The number of times [retry_leased_fetch] fetches a part again after a
+/// [FetchBatchError::ChecksumMismatch], before giving up on it.
+pub(crate) const CHECKSUM_MISMATCH_MAX_REFETCHES: usize = 2;
+
+/// Fetches a leased batch part with `fetch`, fetching it again if that might
+/// fix the error (see [FetchBatchError::is_transient]).
+///
+/// The part's lease keeps its blob from being garbage collected, so a refetch
+/// can succeed. Failing blob reads are already retried with backoff by
+/// [fetch_batch_part], according to the blob read retry policy, so they're
+/// returned as soon as that policy gives up. A checksum mismatch is refetched
+/// at most [CHECKSUM_MISMATCH_MAX_REFETCHES] times: past that, the blob is
+/// most likely corrupted at rest and the mismatch is returned.
+pub(crate) async fn retry_leased_fetch<R, F, FetchFn>(
+    reader_name: &str,
+    mut fetch: FetchFn,
+) -> Result<R, FetchBatchError>
+where
+    F: Future<Output = Result<R, FetchBatchError>>,
+    FetchFn: FnMut() -> F,
+{
+    let mut refetches = 0;
+    loop {
+        match fetch().await {
+            Err(err) if err.is_transient() && refetches < CHECKSUM_MISMATCH_MAX_REFETCHES => {
+                refetches += 1;
+                warn!(
+                    "{} could not fetch batch part, retrying: {}",
+                    reader_name, err
+                );
+            }
+            res => return res,
+        }
+    }
+}
+
+fn reader_name(reader_id: Option<&LeasedReaderId>) -> String {
+    reader_id
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "batch fetcher".to_string())
 }
 
+/// The reason a batch part could not be fetched.
+#[derive(Debug)]
+#[cfg_attr(any(test, debug_assertions), derive(PartialEq))]
+pub enum FetchBatchError {
+    /// The blob for the part does not exist.
+    Missing(BlobKey),
+    /// Fetching the blob failed more times than the blob read retry policy
+    /// allows.
+    External(BlobKey, ExternalError),
+    /// The checksum of the fetched blob doesn't match the one recorded for
+    /// the part in state.
+    ChecksumMismatch {
+        /// The blob of the part.
+        key: BlobKey,
+        /// The checksum recorded for the part in state.
+        expected: u32,
+        /// The checksum of the fetched blob.
+        actual: u32,
+    },
+    /// The fetched blob could not be decoded.
+    Decode {
+        /// The blob of the part.
+        key: BlobKey,
+        /// The reason the blob could not be decoded.
+        err: mz_persist::error::Error,
+    },
+}
+
+impl fmt::Display for FetchBatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchBatchError::Missing(blob_key) => write!(f, "missing blob {}", blob_key),
+            FetchBatchError::External(blob_key, err) => write!(f, "{}: {}", blob_key, err),
+            FetchBatchError::ChecksumMismatch {
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "checksum mismatch for blob {}: expected {:08x} got {:08x}",
+                key, expected, actual
+            ),
+            FetchBatchError::Decode { key, err } => {
+                write!(f, "couldn't decode batch at key {}: {}", key, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FetchBatchError {}
+
+impl FetchBatchError {
+    /// Whether fetching the part again might succeed.
+    ///
+    /// This includes a [FetchBatchError::ChecksumMismatch]: the blob may have
+    /// been corrupted on its way to us and not at rest. If it was corrupted at
+    /// rest, refetches keep failing, so callers should only refetch a bounded
+    /// number of times (see [retry_leased_fetch]).
+    ///
+    /// An [FetchBatchError::External] error is only returned once the blob
+    /// read retry policy has given up, so fetching again would just start the
+    /// same retries over.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchBatchError::Missing(_)
+            | FetchBatchError::External(..)
+            | FetchBatchError::Decode { .. } => false,
+            FetchBatchError::ChecksumMismatch { .. } => true,
+        }
+    }
+}
+
+/// Fetches and decodes the given batch part.
+///
+/// If the part has a `checksum`, it's verified against the fetched blob before
+/// decoding. This costs one pass of CRC-32 over the encoded bytes, which is
+/// small relative to the decode itself (see the `plumbing/encode_batch`
+/// benchmarks).
 pub(crate) async fn fetch_batch_part<T>(
     shard_id: &ShardId,
     blob: &(dyn Blob + Send + Sync),
@@ -243,37 +410,59 @@ pub(crate) async fn fetch_batch_part<T>(
     read_metrics: &ReadMetrics,
     key: &PartialBatchKey,
     registered_desc: &Description<T>,
-) -> Result<EncodedPart<T>, BlobKey>
+    checksum: Option<u32>,
+) -> Result<EncodedPart<T>, FetchBatchError>
 where
     T: Timestamp + Lattice + Codec64,
 {
     let now = Instant::now();
     let get_span = debug_span!("fetch_batch::get");
     let blob_key = key.complete(shard_id);
-    let value = retry_external(&metrics.retries.external.fetch_batch_get, || async {
+    let value = retry_external_bounded(&metrics.retries.external.fetch_batch_get, || async {
         shard_metrics.blob_gets.inc();
         blob.get(&blob_key).await
     })
     .instrument(get_span.clone())
     .await
-    .ok_or(blob_key)?;
+    .map_err(|err| FetchBatchError::External(blob_key.clone(), err))?
+    .ok_or_else(|| FetchBatchError::Missing(blob_key.clone()))?;
 
     drop(get_span);
 
     read_metrics.part_count.inc();
     read_metrics.part_bytes.inc_by(u64::cast_from(value.len()));
 
+    if let Some(expected) = checksum {
+        let actual = trace_span!("fetch_batch::checksum").in_scope(|| {
+            let mut hasher = crc32fast::Hasher::new();
+            for segment in value.clone().into_segments() {
+                hasher.update(&segment);
+            }
+            hasher.finalize()
+        });
+        if actual != expected {
+            read_metrics.checksum_mismatches.inc();
+            return Err(FetchBatchError::ChecksumMismatch {
+                key: blob_key,
+                expected,
+                actual,
+            });
+        }
+    }
+
     let part = trace_span!("fetch_batch::decode").in_scope(|| {
         let part = metrics
             .codecs
             .batch
             .decode(|| BlobTraceBatchPart::decode(&value))
-            .map_err(|err| anyhow!("couldn't decode batch at key {}: {}", key, err))
-            // We received a State that we couldn't decode. This could happen if
+            // We received a part that we couldn't decode. This could happen if
             // persist messes up backward/forward compatibility, if the durable
-            // data was corrupted, or if operations messes up deployment. In any
-            // case, fail loudly.
-            .expect("internal error: invalid encoded state");
+            // data was corrupted, or if it uses an essential part feature that
+            // this version doesn't know about. Let the caller decide what to do.
+            .map_err(|err| FetchBatchError::Decode {
+                key: blob_key.clone(),
+                err,
+            })?;
 
         // Drop the encoded representation as soon as we can to reclaim memory.
         drop(value);
@@ -281,8 +470,8 @@ where
             part.updates.iter().map(|x| x.goodbytes()).sum::<usize>(),
         ));
 
-        EncodedPart::new(key, registered_desc.clone(), part)
-    });
+        Ok(EncodedPart::new(&blob_key, registered_desc.clone(), part))
+    })?;
 
     read_metrics.seconds.inc_by(now.elapsed().as_secs_f64());
 
@@ -324,14 +513,16 @@ pub(crate) enum SerdeLeasedBatchPartMetadata {
 /// `SerdeLeasedBatchPart::from(self)`, but we want the additional warning message to
 /// be visible and sufficiently scary.
 ///
-/// # Panics
-/// `LeasedBatchPart` panics when dropped unless a very strict set of invariants are
-/// held:
+/// # Drop
+/// A `LeasedBatchPart` that still holds a leased `SeqNo` when dropped returns
+/// the lease to the issuing [crate::ReadHandle], which releases it on its next
+/// [crate::ReadHandle::downgrade_since]. Explicitly returning the part via
+/// [crate::ReadHandle::process_returned_leased_part] releases the lease
+/// immediately, but is not required.
 ///
-/// `LeasedBatchPart` may only be dropped if it:
-/// - Does not have a leased `SeqNo (i.e. `self.leased_seqno.is_none()`)
-///
-/// In any other circumstance, dropping `LeasedBatchPart` panics.
+/// If the issuing `ReadHandle` is gone, or the part was created from a
+/// [SerdeLeasedBatchPart] by a [BatchFetcher], the lease can't be returned and
+/// is only released once the reader's lease expires.
 #[derive(Debug)]
 pub struct LeasedBatchPart<T> {
     pub(crate) metrics: Arc<Metrics>,
@@ -341,10 +532,15 @@ pub struct LeasedBatchPart<T> {
     pub(crate) desc: Description<T>,
     pub(crate) key: PartialBatchKey,
     pub(crate) encoded_size_bytes: usize,
+    /// See [crate::internal::state::HollowBatchPart::checksum].
+    pub(crate) checksum: Option<u32>,
     /// The `SeqNo` from which this part originated; we track this value as
     /// long as necessary to ensure the `SeqNo` isn't garbage collected while a
     /// read still depends on it.
     pub(crate) leased_seqno: Option<SeqNo>,
+    /// Where to return `leased_seqno` to if this part is dropped while still
+    /// holding it, if anywhere.
+    pub(crate) lease_return_tx: Option<UnboundedSender<SeqNo>>,
     pub(crate) stats: Option<LazyPartStats>,
     pub(crate) filter_pushdown_audit: bool,
     /// A lower bound on the key. If a tight lower bound is not available, the
@@ -374,6 +570,7 @@ where
             since: self.desc.since().iter().map(T::encode).collect(),
             key: self.key.clone(),
             encoded_size_bytes: self.encoded_size_bytes,
+            checksum: self.checksum,
             leased_seqno: self.leased_seqno,
             reader_id: self.reader_id.clone(),
             stats: self.stats.clone(),
@@ -425,19 +622,96 @@ where
 impl<T> Drop for LeasedBatchPart<T> {
     /// For details, see [`LeasedBatchPart`].
     fn drop(&mut self) {
-        self.metrics.lease.dropped_part.inc()
+        let Some(lease) = self.leased_seqno.take() else {
+            return;
+        };
+        self.metrics.lease.dropped_part.inc();
+        // Sending on an unbounded channel never blocks and doesn't need a
+        // runtime, so this is safe even while the runtime is shutting down.
+        let returned = match &self.lease_return_tx {
+            Some(tx) => tx.send(lease).is_ok(),
+            None => false,
+        };
+        if !returned {
+            self.metrics.lease.leaked_part.inc();
+            warn!(
+                "{} reader {} dropped part without returning its lease on seqno {} to the \
+                issuing ReadHandle; the seqno stays leased until the reader expires",
+                self.shard_id, self.reader_id, lease
+            );
+        }
+    }
+}
+
+/// The result of decoding a key or value fetched from persist.
+pub type DecodeResult<X> = Result<X, DecodeError>;
+
+/// A key or value fetched from persist could not be decoded.
+///
+/// This is either data corruption or (more likely) a reader using a different
+/// codec than the one the data was written with: compare `codec_name` against
+/// the shard's registered codecs (see [crate::PersistClient::shard_codecs]) to
+/// tell the two apart.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DecodeError {
+    /// The key of the blob the update was read from.
+    ///
+    /// None if the update was produced by streaming consolidation (e.g.
+    /// [crate::read::Cursor]), which merges updates from several blobs.
+    pub blob_key: Option<String>,
+    /// The index of the update in the blob it was read from.
+    ///
+    /// None under the same conditions as `blob_key`.
+    pub offset: Option<usize>,
+    /// The [Codec::codec_name] of the codec that failed to decode the update.
+    pub codec_name: String,
+    /// The error returned by the codec.
+    pub reason: String,
+}
+
+impl DecodeError {
+    pub(crate) fn new<C: Codec>(
+        blob_key: Option<String>,
+        offset: Option<usize>,
+        reason: String,
+    ) -> Self {
+        DecodeError {
+            blob_key,
+            offset,
+            codec_name: C::codec_name(),
+            reason,
+        }
     }
 }
 
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to decode {}", self.codec_name)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at update {}", offset)?;
+        }
+        if let Some(blob_key) = &self.blob_key {
+            write!(f, " of blob {}", blob_key)?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 /// A [Blob] object that has been fetched, but not yet decoded.
 #[derive(Debug)]
 pub struct FetchedPart<K: Codec, V: Codec, T, D> {
     metrics: Arc<Metrics>,
+    shard_metrics: Arc<ShardMetrics>,
     ts_filter: FetchBatchFilter<T>,
     part: EncodedPart<T>,
     schemas: Schemas<K, V>,
     filter_pushdown_audit: Option<LazyPartStats>,
     part_cursor: Cursor,
+    /// Whether to opportunistically consolidate successive updates with the
+    /// same key, value, and time.
+    consolidate: bool,
 
     _phantom: PhantomData<fn() -> (K, V, D)>,
 }
@@ -446,17 +720,49 @@ impl<K: Codec, V: Codec, T: Clone, D> Clone for FetchedPart<K, V, T, D> {
     fn clone(&self) -> Self {
         Self {
             metrics: Arc::clone(&self.metrics),
+            shard_metrics: Arc::clone(&self.shard_metrics),
             ts_filter: self.ts_filter.clone(),
             part: self.part.clone(),
             schemas: self.schemas.clone(),
             filter_pushdown_audit: self.filter_pushdown_audit.clone(),
             part_cursor: self.part_cursor.clone(),
+            consolidate: self.consolidate,
             _phantom: self._phantom.clone(),
         }
     }
 }
 
 impl<K: Codec, V: Codec, T, D> FetchedPart<K, V, T, D> {
+    pub(crate) fn new(
+        metrics: Arc<Metrics>,
+        shard_metrics: Arc<ShardMetrics>,
+        ts_filter: FetchBatchFilter<T>,
+        part: EncodedPart<T>,
+        schemas: Schemas<K, V>,
+        filter_pushdown_audit: Option<LazyPartStats>,
+    ) -> Self {
+        FetchedPart {
+            metrics,
+            shard_metrics,
+            ts_filter,
+            part,
+            schemas,
+            filter_pushdown_audit,
+            part_cursor: Cursor::default(),
+            consolidate: true,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn decode_error<C: Codec>(&self, popped: &Cursor, reason: String) -> DecodeError {
+        self.shard_metrics.decode_failures.inc();
+        DecodeError::new::<C>(
+            Some(self.part.blob_key.clone()),
+            Some(popped.popped_offset(&self.part)),
+            reason,
+        )
+    }
+
     /// Returns Some if this part was only fetched as part of a filter pushdown
     /// audit. See [LeasedBatchPart::request_filter_pushdown_audit].
     ///
@@ -465,12 +771,19 @@ impl<K: Codec, V: Codec, T, D> FetchedPart<K, V, T, D> {
     pub fn is_filter_pushdown_audit(&self) -> Option<impl std::fmt::Debug> {
         self.filter_pushdown_audit.clone()
     }
+
+    /// Configures whether successive updates with the same key, value, and
+    /// time are consolidated together as they're read out of this part.
+    pub(crate) fn set_consolidate(&mut self, consolidate: bool) {
+        self.consolidate = consolidate;
+    }
 }
 
 /// A [Blob] object that has been fetched, but has no associated decoding
 /// logic.
 #[derive(Debug, Clone)]
 pub(crate) struct EncodedPart<T> {
+    blob_key: String,
     registered_desc: Description<T>,
     part: Arc<BlobTraceBatchPart<T>>,
     needs_truncation: bool,
@@ -483,21 +796,23 @@ where
     T: Timestamp + Lattice + Codec64,
     D: Semigroup + Codec64 + Send + Sync,
 {
-    type Item = ((Result<K, String>, Result<V, String>), T, D);
+    type Item = ((DecodeResult<K>, DecodeResult<V>), T, D);
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some((k, v, mut t, d)) = self.part_cursor.pop(&self.part) {
             if !self.ts_filter.filter_ts(&mut t) {
                 continue;
             }
+            // Remember where this update came from, in case it doesn't decode.
+            let popped = self.part_cursor.clone();
 
             let mut d = D::decode(d);
 
             // If `filter_ts` advances our timestamp, we may end up with the same K, V, T in successive
-            // records. If so, opportunistically consolidate those out.
+            // records. If so, opportunistically consolidate those out (unless we've been asked not to).
             while let Some((k_next, v_next, mut t_next, d_next)) = self.part_cursor.peek(&self.part)
             {
-                if (k, v) != (k_next, v_next) {
+                if !self.consolidate || (k, v) != (k_next, v_next) {
                     break;
                 }
 
@@ -520,6 +835,8 @@ where
 
             let k = self.metrics.codecs.key.decode(|| K::decode(k));
             let v = self.metrics.codecs.val.decode(|| V::decode(v));
+            let k = k.map_err(|reason| self.decode_error::<K>(&popped, reason));
+            let v = v.map_err(|reason| self.decode_error::<V>(&popped, reason));
             return Some(((k, v), t, d));
         }
         None
@@ -591,6 +908,7 @@ where
         }
 
         EncodedPart {
+            blob_key: key.to_owned(),
             registered_desc,
             part: Arc::new(part),
             needs_truncation,
@@ -663,6 +981,16 @@ impl Cursor {
         update
     }
 
+    /// The index, across all of the part's updates, of the update most recently
+    /// returned by [Self::pop].
+    fn popped_offset<T>(&self, encoded: &EncodedPart<T>) -> usize {
+        let preceding: usize = encoded.part.updates[..self.part_idx]
+            .iter()
+            .map(|x| x.len())
+            .sum();
+        preceding + self.idx.saturating_sub(1)
+    }
+
     /// Advance the cursor just past the end of the most recent update, if there is one.
     pub fn advance<'a, T: Timestamp + Codec64>(&mut self, part: &'a EncodedPart<T>) {
         if self.part_idx < part.part.updates.len() {
@@ -688,6 +1016,7 @@ pub struct SerdeLeasedBatchPart {
     since: Vec<[u8; 8]>,
     key: PartialBatchKey,
     encoded_size_bytes: usize,
+    checksum: Option<u32>,
     leased_seqno: Option<SeqNo>,
     reader_id: LeasedReaderId,
     stats: Option<LazyPartStats>,
@@ -712,7 +1041,11 @@ impl<T: Timestamp + Codec64> LeasedBatchPart<T> {
     /// `LeasedBatchPart`'s droppability.
     ///
     /// For more details, see [`LeasedBatchPart`]'s documentation.
-    pub(crate) fn from(x: SerdeLeasedBatchPart, metrics: Arc<Metrics>) -> Self {
+    pub(crate) fn from(
+        x: SerdeLeasedBatchPart,
+        metrics: Arc<Metrics>,
+        lease_return_tx: Option<UnboundedSender<SeqNo>>,
+    ) -> Self {
         LeasedBatchPart {
             metrics,
             shard_id: x.shard_id,
@@ -724,7 +1057,9 @@ impl<T: Timestamp + Codec64> LeasedBatchPart<T> {
             ),
             key: x.key,
             encoded_size_bytes: x.encoded_size_bytes,
+            checksum: x.checksum,
             leased_seqno: x.leased_seqno,
+            lease_return_tx,
             reader_id: x.reader_id,
             stats: x.stats,
             filter_pushdown_audit: x.filter_pushdown_audit,
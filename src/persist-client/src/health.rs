@@ -0,0 +1,423 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Summaries and checks of the health of a persist location.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use bytes::Bytes;
+use mz_ore::cast::{CastFrom, CastLossy};
+use mz_persist::location::{
+    Atomicity, Blob, Consensus, Determinate, ExternalError, BLOB_HEALTH_CHECK_KEY_PREFIX,
+    CONSENSUS_HEAD_LIVENESS_KEY,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The width of each bucket of a [HealthWindow].
+const BUCKET_SECS: u64 = 10;
+/// The number of buckets of a [HealthWindow], which together span the window.
+const NUM_BUCKETS: usize = 6;
+/// The number of latency buckets. Bucket `i` counts operations that took less
+/// than `2^i` microseconds, with the last bucket counting everything slower.
+const NUM_LATENCY_BUCKETS: usize = 32;
+
+/// A snapshot of the health of a persist location, as observed by this
+/// process over a recent sliding window.
+///
+/// Returned by [crate::PersistClient::health].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistHealth {
+    /// The length of the sliding window the operation stats cover.
+    pub window_secs: u64,
+    /// Stats for each class of blob and consensus operation (e.g. `blob_get`
+    /// or `consensus_cas`) that ran in the window.
+    pub ops: BTreeMap<String, ExternalOpHealth>,
+    /// Whether the PubSub client is currently connected.
+    ///
+    /// This is always false when PubSub is not in use.
+    pub pubsub_connected: bool,
+    /// The number of shards open in this process whose last compaction
+    /// attempt failed.
+    pub shards_with_failed_maintenance: usize,
+}
+
+impl PersistHealth {
+    /// The error rate of all operations in the window whose class has the
+    /// given prefix (e.g. `blob_` or `consensus_`), or None if no such
+    /// operation ran.
+    pub fn error_rate(&self, op_prefix: &str) -> Option<f64> {
+        let (mut ops, mut failures) = (0, 0);
+        for (op, health) in self.ops.iter() {
+            if op.starts_with(op_prefix) {
+                ops += health.count;
+                failures += health.failures;
+            }
+        }
+        (ops > 0).then(|| f64::cast_lossy(failures) / f64::cast_lossy(ops))
+    }
+}
+
+/// Stats for a single class of blob or consensus operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalOpHealth {
+    /// The number of operations that completed in the window.
+    pub count: u64,
+    /// The number of operations that failed in the window.
+    pub failures: u64,
+    /// `failures / count`.
+    pub error_rate: f64,
+    /// An upper bound on the 99th percentile latency of operations in the
+    /// window, precise to within a factor of two.
+    pub p99_latency_secs: f64,
+}
+
+/// The results of actively checking connectivity to a persist location.
+///
+/// Returned by [crate::PersistClient::health_check].
+#[derive(Debug)]
+pub struct HealthCheck {
+    /// How long a blob round-trip of a small probe took, or why it failed.
+    pub blob: Result<Duration, ExternalError>,
+    /// How long a consensus head read took, or why it failed.
+    pub consensus: Result<Duration, ExternalError>,
+}
+
+impl HealthCheck {
+    /// Whether both blob and consensus passed the check.
+    pub fn is_healthy(&self) -> bool {
+        self.blob.is_ok() && self.consensus.is_ok()
+    }
+
+    /// Returns the first failure, if any, as a determinate error naming the
+    /// subsystem that failed.
+    pub fn into_result(self) -> Result<(), ExternalError> {
+        if let Err(err) = self.blob {
+            return Err(Determinate::new(anyhow!("blob health check failed: {}", err)).into());
+        }
+        if let Err(err) = self.consensus {
+            return Err(Determinate::new(anyhow!("consensus health check failed: {}", err)).into());
+        }
+        Ok(())
+    }
+}
+
+/// Writes, reads back, and deletes a probe in `blob`, returning how long it
+/// took.
+pub(crate) async fn check_blob(blob: &(dyn Blob + Send + Sync)) -> Result<Duration, ExternalError> {
+    // A unique key, so that concurrent checks of the same location (e.g. from
+    // several processes) can't observe each other's probes.
+    let key = format!("{}{}", BLOB_HEALTH_CHECK_KEY_PREFIX, Uuid::new_v4());
+    let value = Bytes::from_static(b"persist health check");
+    let start = Instant::now();
+    blob.set(&key, value.clone(), Atomicity::RequireAtomic)
+        .await?;
+    let read = blob.get(&key).await;
+    // Clean up the probe even if reading it back failed.
+    let deleted = blob.delete(&key).await;
+    match read? {
+        Some(read) if read.into_contiguous()[..] == value[..] => {}
+        Some(_) => return Err(anyhow!("blob probe read back with unexpected contents").into()),
+        None => return Err(anyhow!("blob probe missing after write").into()),
+    }
+    deleted?;
+    Ok(start.elapsed())
+}
+
+/// Reads the head of a well-known key in `consensus`, returning how long it
+/// took.
+pub(crate) async fn check_consensus(
+    consensus: &(dyn Consensus + Send + Sync),
+) -> Result<Duration, ExternalError> {
+    let start = Instant::now();
+    let _ = consensus.head(CONSENSUS_HEAD_LIVENESS_KEY).await?;
+    Ok(start.elapsed())
+}
+
+/// Sliding window stats for a single class of external operation.
+///
+/// Recording is lock-free: the window is a ring of time buckets of atomic
+/// counters, and a bucket is lazily reset by the first recording that finds it
+/// stale. A recording racing with a reset may be lost or misattributed, which
+/// is fine for the purposes of a health summary.
+#[derive(Debug)]
+pub(crate) struct HealthWindow {
+    start: Instant,
+    buckets: [HealthBucket; NUM_BUCKETS],
+}
+
+#[derive(Debug, Default)]
+struct HealthBucket {
+    /// The epoch (see [HealthWindow::epoch]) this bucket holds stats for, or 0
+    /// if it was never used.
+    epoch: AtomicU64,
+    count: AtomicU64,
+    failures: AtomicU64,
+    latencies: [AtomicU64; NUM_LATENCY_BUCKETS],
+}
+
+impl HealthWindow {
+    fn new() -> Self {
+        HealthWindow {
+            start: Instant::now(),
+            buckets: Default::default(),
+        }
+    }
+
+    /// The index of the time bucket `now` falls into, starting at 1.
+    fn epoch(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.start).as_secs() / BUCKET_SECS + 1
+    }
+
+    /// Records a completed operation.
+    pub(crate) fn record(&self, elapsed: Duration, succeeded: bool) {
+        self.record_at(Instant::now(), elapsed, succeeded)
+    }
+
+    fn record_at(&self, now: Instant, elapsed: Duration, succeeded: bool) {
+        let epoch = self.epoch(now);
+        let bucket = &self.buckets[usize::cast_from(epoch) % NUM_BUCKETS];
+        let bucket_epoch = bucket.epoch.load(Ordering::Relaxed);
+        if bucket_epoch != epoch
+            && bucket
+                .epoch
+                .compare_exchange(bucket_epoch, epoch, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            bucket.count.store(0, Ordering::Relaxed);
+            bucket.failures.store(0, Ordering::Relaxed);
+            for latency in bucket.latencies.iter() {
+                latency.store(0, Ordering::Relaxed);
+            }
+        }
+
+        bucket.count.fetch_add(1, Ordering::Relaxed);
+        if !succeeded {
+            bucket.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        // The number of bits needed to represent `micros`, i.e. the smallest
+        // `i` such that `micros < 2^i`.
+        let latency_bucket = usize::cast_from(u64::BITS - micros.leading_zeros());
+        bucket.latencies[latency_bucket.min(NUM_LATENCY_BUCKETS - 1)]
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the stats of the window ending at `now`, or None if no
+    /// operations completed in it.
+    fn snapshot_at(&self, now: Instant) -> Option<ExternalOpHealth> {
+        let epoch = self.epoch(now);
+        let oldest_epoch = epoch.saturating_sub(u64::cast_from(NUM_BUCKETS) - 1);
+        let (mut count, mut failures) = (0, 0);
+        let mut latencies = [0u64; NUM_LATENCY_BUCKETS];
+        for bucket in self.buckets.iter() {
+            let bucket_epoch = bucket.epoch.load(Ordering::Relaxed);
+            if bucket_epoch < oldest_epoch || bucket_epoch > epoch || bucket_epoch == 0 {
+                continue;
+            }
+            count += bucket.count.load(Ordering::Relaxed);
+            failures += bucket.failures.load(Ordering::Relaxed);
+            for (sum, latency) in latencies.iter_mut().zip(bucket.latencies.iter()) {
+                *sum += latency.load(Ordering::Relaxed);
+            }
+        }
+        if count == 0 {
+            return None;
+        }
+
+        // The rank of the p99 operation, rounded up.
+        let p99_rank = (count * 99).div_ceil(100);
+        let mut seen = 0;
+        let mut p99_bucket = NUM_LATENCY_BUCKETS - 1;
+        for (idx, latency) in latencies.iter().enumerate() {
+            seen += *latency;
+            if seen >= p99_rank {
+                p99_bucket = idx;
+                break;
+            }
+        }
+        let p99_latency = Duration::from_micros(1u64 << p99_bucket);
+
+        Some(ExternalOpHealth {
+            count,
+            failures,
+            error_rate: f64::cast_lossy(failures) / f64::cast_lossy(count),
+            p99_latency_secs: p99_latency.as_secs_f64(),
+        })
+    }
+}
+
+/// The [HealthWindow]s of all external operation classes.
+#[derive(Debug, Default)]
+pub(crate) struct HealthWindows {
+    /// Only locked when registering a new operation class, which happens while
+    /// constructing [crate::Metrics], and when taking a snapshot.
+    windows: Mutex<BTreeMap<String, Arc<HealthWindow>>>,
+}
+
+impl HealthWindows {
+    /// Returns the window for the given operation class, creating it if
+    /// necessary.
+    pub(crate) fn window(&self, op: &str) -> Arc<HealthWindow> {
+        let mut windows = self.windows.lock().expect("mutex poisoned");
+        Arc::clone(
+            windows
+                .entry(op.to_owned())
+                .or_insert_with(|| Arc::new(HealthWindow::new())),
+        )
+    }
+
+    /// Returns the stats of all operation classes that completed operations
+    /// in the window ending now.
+    pub(crate) fn snapshot(&self) -> BTreeMap<String, ExternalOpHealth> {
+        let now = Instant::now();
+        let windows = self.windows.lock().expect("mutex poisoned");
+        windows
+            .iter()
+            .filter_map(|(op, window)| Some((op.clone(), window.snapshot_at(now)?)))
+            .collect()
+    }
+
+    /// The length of the window covered by [Self::snapshot].
+    pub(crate) fn window_secs() -> u64 {
+        BUCKET_SECS * u64::cast_from(NUM_BUCKETS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_ore::metrics::MetricsRegistry;
+    use mz_persist::location::{Blob, Consensus};
+    use mz_persist::mem::{MemBlob, MemBlobConfig, MemConsensus};
+    use mz_persist::unreliable::{UnreliableBlob, UnreliableConsensus, UnreliableHandle};
+
+    use crate::internal::metrics::{MetricsBlob, MetricsConsensus};
+    use crate::{Metrics, PersistConfig, ShardId};
+
+    use super::*;
+
+    #[mz_ore::test]
+    fn health_window() {
+        let window = HealthWindow::new();
+        let start = window.start;
+        let ms = Duration::from_millis;
+        assert_eq!(window.snapshot_at(start), None);
+
+        // 98 fast successes and 2 slow failures: the p99 falls in the slow
+        // bucket.
+        for _ in 0..98 {
+            window.record_at(start, ms(1), true);
+        }
+        window.record_at(start, ms(500), false);
+        window.record_at(start, ms(500), false);
+        let health = window.snapshot_at(start).unwrap();
+        assert_eq!(health.count, 100);
+        assert_eq!(health.failures, 2);
+        assert_eq!(health.error_rate, 0.02);
+        assert!(health.p99_latency_secs >= 0.5);
+        assert!(health.p99_latency_secs < 1.0);
+
+        // Stats age out of the window one bucket at a time.
+        let later = start + Duration::from_secs(BUCKET_SECS);
+        window.record_at(later, ms(1), true);
+        let health = window.snapshot_at(later).unwrap();
+        assert_eq!(health.count, 101);
+        let window_end = start + Duration::from_secs(HealthWindows::window_secs());
+        let health = window.snapshot_at(window_end).unwrap();
+        assert_eq!(health.count, 1);
+        assert!(health.p99_latency_secs < 0.002);
+        let after_window = window_end + Duration::from_secs(BUCKET_SECS);
+        assert_eq!(window.snapshot_at(after_window), None);
+
+        // Reusing a bucket discards its stale stats.
+        window.record_at(after_window, ms(1), false);
+        let health = window.snapshot_at(after_window).unwrap();
+        assert_eq!(health.count, 1);
+        assert_eq!(health.error_rate, 1.0);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn health_fault_injection() {
+        let cfg = PersistConfig::new_for_tests();
+        let metrics = Arc::new(Metrics::new(&cfg, &MetricsRegistry::new()));
+        let handle = UnreliableHandle::default();
+        let blob = MetricsBlob::new(
+            Arc::new(UnreliableBlob::new(
+                Arc::new(MemBlob::open(MemBlobConfig::default())),
+                handle.clone(),
+            )),
+            Arc::clone(&metrics),
+        );
+        let consensus = MetricsConsensus::new(
+            Arc::new(UnreliableConsensus::new(
+                Arc::new(MemConsensus::default()),
+                handle.clone(),
+            )),
+            Arc::clone(&metrics),
+        );
+
+        let health = metrics.health();
+        assert!(health.ops.is_empty());
+        assert_eq!(health.error_rate("blob_"), None);
+        assert!(!health.pubsub_connected);
+        assert_eq!(health.shards_with_failed_maintenance, 0);
+
+        // Every call fails while the location is unavailable.
+        handle.totally_unavailable();
+        for _ in 0..10 {
+            assert!(blob.get("key").await.is_err());
+            assert!(consensus.head("key").await.is_err());
+        }
+        let health = metrics.health();
+        assert_eq!(health.ops["blob_get"].count, 10);
+        assert_eq!(health.ops["blob_get"].failures, 10);
+        assert_eq!(health.ops["consensus_head"].error_rate, 1.0);
+        assert_eq!(health.error_rate("blob_"), Some(1.0));
+        assert_eq!(health.error_rate("consensus_"), Some(1.0));
+        assert!(!health.ops.contains_key("consensus_cas"));
+
+        // Once it recovers, the error rate starts to come back down.
+        handle.totally_available();
+        for _ in 0..30 {
+            assert!(blob.get("key").await.is_ok());
+        }
+        let health = metrics.health();
+        assert_eq!(health.ops["blob_get"].count, 40);
+        assert_eq!(health.error_rate("blob_"), Some(0.25));
+        assert_eq!(health.error_rate("consensus_"), Some(1.0));
+
+        // Slow calls are reflected in the latencies.
+        handle.set_latency(Duration::from_millis(100));
+        for _ in 0..10 {
+            assert!(consensus.head("key").await.is_ok());
+        }
+        let health = metrics.health();
+        assert_eq!(health.ops["consensus_head"].error_rate, 0.5);
+        assert!(health.ops["consensus_head"].p99_latency_secs >= 0.1);
+
+        // Shards whose last compaction failed are counted, for as long as
+        // they're open.
+        let shard = metrics.shards.shard(&ShardId::new(), "test");
+        shard.maintenance_failing.set(1);
+        assert_eq!(metrics.health().shards_with_failed_maintenance, 1);
+        shard.maintenance_failing.set(0);
+        assert_eq!(metrics.health().shards_with_failed_maintenance, 0);
+        shard.maintenance_failing.set(1);
+        drop(shard);
+        assert_eq!(metrics.health().shards_with_failed_maintenance, 0);
+
+        let json = serde_json::to_value(&health).unwrap();
+        assert_eq!(json["ops"]["blob_get"]["count"], 40);
+    }
+}
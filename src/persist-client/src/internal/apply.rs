@@ -11,7 +11,7 @@
 
 use std::fmt::Debug;
 use std::ops::ControlFlow::{self, Break, Continue};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use differential_dataflow::difference::Semigroup;
@@ -20,11 +20,13 @@ use mz_ore::cast::CastFrom;
 use mz_persist::location::{CaSResult, Indeterminate, SeqNo, VersionedData};
 use mz_persist_types::{Codec, Codec64};
 use timely::progress::{Antichain, Timestamp};
-use tracing::debug;
+use tracing::{debug, instrument};
 
 use crate::cache::{LockingTypedState, StateCache};
-use crate::error::{CodecMismatch, InvalidUsage};
+use crate::error::{AllowCodecMismatch, CodecMismatch, InvalidUsage};
+use crate::internal::compact::CompactionCancelReason;
 use crate::internal::gc::GcReq;
+use crate::internal::machine::ReaderHeartbeats;
 use crate::internal::maintenance::RoutineMaintenance;
 use crate::internal::metrics::{CmdMetrics, Metrics, ShardMetrics};
 use crate::internal::paths::{PartialRollupKey, RollupId};
@@ -36,7 +38,8 @@ use crate::internal::state_versions::{EncodedRollup, StateVersions};
 use crate::internal::trace::FueledMergeReq;
 use crate::internal::watch::StateWatch;
 use crate::rpc::PubSubSender;
-use crate::{Diagnostics, PersistConfig, ShardId};
+use crate::usage::ShardOp;
+use crate::{Diagnostics, PersistConfig, ShardId, ShardTuning};
 
 /// An applier of persist commands.
 ///
@@ -51,6 +54,8 @@ pub struct Applier<K, V, T, D> {
     shared_states: Arc<StateCache>,
     pubsub_sender: Arc<dyn PubSubSender>,
     pub(crate) shard_id: ShardId,
+    pub(crate) diagnostics: Arc<Diagnostics>,
+    allow_codec_mismatch: Option<AllowCodecMismatch>,
 
     // Access to the shard's state, shared across all handles created by the same
     // PersistClientCache. The state is wrapped in LockingTypedState, disallowing
@@ -74,6 +79,8 @@ impl<K, V, T: Clone, D> Clone for Applier<K, V, T, D> {
             shared_states: Arc::clone(&self.shared_states),
             pubsub_sender: Arc::clone(&self.pubsub_sender),
             shard_id: self.shard_id,
+            diagnostics: Arc::clone(&self.diagnostics),
+            allow_codec_mismatch: self.allow_codec_mismatch.clone(),
             state: Arc::clone(&self.state),
         }
     }
@@ -94,6 +101,7 @@ where
         shared_states: Arc<StateCache>,
         pubsub_sender: Arc<dyn PubSubSender>,
         diagnostics: Diagnostics,
+        allow_codec_mismatch: Option<AllowCodecMismatch>,
     ) -> Result<Self, Box<CodecMismatch>> {
         let shard_metrics = metrics.shards.shard(&shard_id, &diagnostics.shard_name);
         let state = shared_states
@@ -101,7 +109,11 @@ where
                 shard_id,
                 || {
                     metrics.cmds.init_state.run_cmd(&shard_metrics, || {
-                        state_versions.maybe_init_shard(&shard_metrics)
+                        state_versions.maybe_init_shard(
+                            &shard_metrics,
+                            &diagnostics,
+                            allow_codec_mismatch.as_ref(),
+                        )
                     })
                 },
                 &diagnostics,
@@ -115,6 +127,8 @@ where
             shared_states,
             pubsub_sender,
             shard_id,
+            diagnostics: Arc::new(diagnostics),
+            allow_codec_mismatch,
             state,
         };
         Ok(ret)
@@ -125,6 +139,12 @@ where
         StateWatch::new(Arc::clone(&self.state), Arc::clone(&self.metrics))
     }
 
+    /// Returns the leased readers kept alive by the shared heartbeat of this
+    /// Applier's shard.
+    pub fn reader_heartbeats(&self) -> &Arc<Mutex<ReaderHeartbeats>> {
+        self.state.reader_heartbeats()
+    }
+
     /// Fetches the latest state from Consensus and passes its `upper` to the provided closure.
     pub async fn fetch_upper<R, F: FnMut(&Antichain<T>) -> R>(&mut self, f: F) -> R {
         self.fetch_and_update_state(None).await;
@@ -175,6 +195,20 @@ where
             })
     }
 
+    /// A point-in-time read of `seqno`, `since`, and `upper` from the current
+    /// state, all from the same version of it.
+    ///
+    /// Due to sharing state with other handles, successive reads to this fn or any other may
+    /// see a different version of state, even if this Applier has not explicitly fetched and
+    /// updated to the latest state. Successive calls will always return values such that
+    /// `call1 <= call2` hold true.
+    pub fn seqno_and_frontiers(&self) -> (SeqNo, Antichain<T>, Antichain<T>) {
+        self.state
+            .read_lock(&self.metrics.locks.applier_read_cacheable, |state| {
+                (state.seqno, state.since().clone(), state.upper().clone())
+            })
+    }
+
     /// A point-in-time read of `seqno_since` from the current state.
     ///
     /// Due to sharing state with other handles, successive reads to this fn or any other may
@@ -188,6 +222,19 @@ where
             })
     }
 
+    /// A point-in-time read of the shard's tuning overrides from the current
+    /// state.
+    ///
+    /// Due to sharing state with other handles, successive reads to this fn or any other may
+    /// see a different version of state, even if this Applier has not explicitly fetched and
+    /// updated to the latest state.
+    pub fn tuning(&self) -> ShardTuning {
+        self.state
+            .read_lock(&self.metrics.locks.applier_read_cacheable, |state| {
+                state.collections.tuning.clone()
+            })
+    }
+
     /// A point-in-time read from the current state. (We declare a shard 'finalized' if it's
     /// both become an unreadable tombstone and the state itself is has been emptied out.)
     ///
@@ -201,6 +248,31 @@ where
             })
     }
 
+    /// Returns why a compaction of `inputs` is no longer useful, if it isn't,
+    /// as of a point-in-time read from the current state.
+    ///
+    /// Due to sharing state with other handles, successive reads to this fn or any other may
+    /// see a different version of state, even if this Applier has not explicitly fetched and
+    /// updated to the latest state.
+    pub fn compaction_cancel_reason(
+        &self,
+        inputs: &[HollowBatch<T>],
+    ) -> Option<CompactionCancelReason> {
+        self.state
+            .read_lock(&self.metrics.locks.applier_read_noncacheable, |state| {
+                if state.collections.is_tombstone() {
+                    return Some(CompactionCancelReason::Tombstone);
+                }
+                // The output can still be applied as long as any of the inputs
+                // remain, see [crate::internal::trace::Trace::apply_merge_res].
+                let mut any_input_present = false;
+                state.collections.trace.map_batches(|batch| {
+                    any_input_present |= inputs.contains(batch);
+                });
+                (!any_input_present).then_some(CompactionCancelReason::InputsReplaced)
+            })
+    }
+
     /// Returns whether the current's state `since` and `upper` are both empty.
     ///
     /// Due to sharing state with other handles, successive reads to this fn or any other may
@@ -244,6 +316,19 @@ where
             })
     }
 
+    pub fn batch_part_count(&self) -> usize {
+        self.state
+            .read_lock(&self.metrics.locks.applier_read_noncacheable, |state| {
+                state
+                    .collections
+                    .trace
+                    .batches()
+                    .into_iter()
+                    .map(|b| b.parts.len())
+                    .sum()
+            })
+    }
+
     pub fn snapshot(&self, as_of: &Antichain<T>) -> Result<Vec<HollowBatch<T>>, SnapshotErr<T>> {
         self.state
             .read_lock(&self.metrics.locks.applier_read_noncacheable, |state| {
@@ -273,10 +358,16 @@ where
             });
 
         self.state_versions
-            .write_rollup_for_state(self.shard_metrics.as_ref(), state, &RollupId::new())
+            .write_rollup_for_state(
+                &self.diagnostics,
+                self.shard_metrics.as_ref(),
+                state,
+                &RollupId::new(),
+            )
             .await
     }
 
+    #[instrument(level = "debug", skip_all, fields(shard = %self.shard_id, shard_name = %self.diagnostics.shard_name, handle_purpose = %self.diagnostics.handle_purpose, cmd = cmd.name))]
     pub async fn apply_unbatched_cmd<
         R,
         E,
@@ -286,7 +377,10 @@ where
         cmd: &CmdMetrics,
         mut work_fn: WorkFn,
     ) -> Result<(SeqNo, Result<R, E>, RoutineMaintenance), Indeterminate> {
+        let start = Instant::now();
+        let mut attempts = 0;
         loop {
+            attempts += 1;
             cmd.started.inc();
             let now = Instant::now();
             let ret = Self::apply_unbatched_cmd_locked(
@@ -297,10 +391,22 @@ where
                 &self.metrics,
                 &self.shard_metrics,
                 &self.state_versions,
+                &self.diagnostics,
             )
             .await;
             cmd.seconds.inc_by(now.elapsed().as_secs_f64());
 
+            if !matches!(ret, ApplyCmdResult::ExpectationMismatch(_)) {
+                self.shard_metrics.slow_ops.observe(
+                    &self.cfg,
+                    cmd,
+                    &self.shard_id,
+                    &self.diagnostics.handle_purpose,
+                    start.elapsed(),
+                    attempts,
+                );
+            }
+
             match ret {
                 ApplyCmdResult::Committed((diff, new_state, res, maintenance)) => {
                     cmd.succeeded.inc();
@@ -308,6 +414,7 @@ where
                     self.update_state(new_state);
                     if self.cfg.dynamic.pubsub_push_diff_enabled() {
                         self.pubsub_sender.push_diff(&self.shard_id, &diff);
+                        self.shard_metrics.record(ShardOp::PubSubPush);
                     }
                     return Ok((diff.seqno, Ok(res), maintenance));
                 }
@@ -342,6 +449,7 @@ where
         metrics: &Metrics,
         shard_metrics: &ShardMetrics,
         state_versions: &StateVersions,
+        diagnostics: &Diagnostics,
     ) -> ApplyCmdResult<K, V, T, D, R, E> {
         let computed_next_state = state
             .read_lock(&metrics.locks.applier_read_noncacheable, |state| {
@@ -375,7 +483,14 @@ where
         // retry even indeterminate errors. See
         // [Self::apply_unbatched_idempotent_cmd].
         let cas_res = state_versions
-            .try_compare_and_set_current(&cmd.name, shard_metrics, Some(expected), &state, &diff)
+            .try_compare_and_set_current(
+                &cmd.name,
+                diagnostics,
+                shard_metrics,
+                Some(expected),
+                &state,
+                &diff,
+            )
             .await;
 
         match cas_res {
@@ -536,7 +651,11 @@ where
 
         let diffs_to_current = self
             .state_versions
-            .fetch_all_live_diffs_gt_seqno::<K, V, T, D>(&self.shard_id, seqno_before)
+            .fetch_all_live_diffs_gt_seqno::<K, V, T, D>(
+                &self.diagnostics,
+                &self.shard_id,
+                seqno_before,
+            )
             .await;
 
         // no new diffs past our current seqno, nothing to do
@@ -573,7 +692,7 @@ where
             .state_versions
             .fetch_current_state(&self.shard_id, diffs_to_current)
             .await
-            .check_codecs::<K, V, D>(&self.shard_id)
+            .check_codecs_allowing::<K, V, D>(&self.shard_id, self.allow_codec_mismatch.as_ref())
             .expect("shard codecs should not change");
 
         let new_seqno = self
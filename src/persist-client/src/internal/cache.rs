@@ -15,7 +15,9 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use mz_ore::bytes::SegmentedBytes;
 use mz_ore::cast::CastFrom;
-use mz_persist::location::{Atomicity, Blob, BlobMetadata, ExternalError};
+use mz_persist::location::{
+    Atomicity, Blob, BlobMetadata, ExternalError, BLOB_HEALTH_CHECK_KEY_PREFIX,
+};
 
 use crate::cfg::{DynamicConfig, PersistConfig};
 use crate::internal::metrics::Metrics;
@@ -102,6 +104,11 @@ impl Blob for BlobMemCache {
 
     async fn set(&self, key: &str, value: Bytes, atomic: Atomicity) -> Result<(), ExternalError> {
         let () = self.blob.set(key, value.clone(), atomic).await?;
+        // Health check probes are read back to exercise the underlying blob,
+        // so they must not be served from the cache.
+        if key.starts_with(BLOB_HEALTH_CHECK_KEY_PREFIX) {
+            return Ok(());
+        }
         let weight = value.len();
         let mut cache = self.cache.lock().expect("lock poisoned");
         cache.insert(key.to_owned(), SegmentedBytes::from(value), weight);
@@ -11,7 +11,7 @@ use std::cmp::Reverse;
 use std::collections::{BinaryHeap, VecDeque};
 use std::fmt::Debug;
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
@@ -35,16 +35,15 @@ use crate::async_runtime::IsolatedRuntime;
 use crate::batch::{BatchBuilderConfig, BatchBuilderInternal};
 use crate::cfg::MiB;
 use crate::dyn_cfg::Config;
-use crate::fetch::{fetch_batch_part, Cursor, EncodedPart, FetchBatchFilter};
+use crate::fetch::{fetch_batch_part, Cursor, EncodedPart, FetchBatchError, FetchBatchFilter};
 use crate::internal::encoding::Schemas;
 use crate::internal::gc::GarbageCollector;
 use crate::internal::machine::{retry_external, Machine};
-use crate::internal::metrics::ShardMetrics;
-use crate::internal::paths::BlobKey;
+use crate::internal::metrics::{CompactionEfficacyStats, ShardMetrics};
 use crate::internal::state::{HollowBatch, HollowBatchPart};
 use crate::internal::trace::{ApplyMergeResult, FueledMergeRes};
 use crate::iter::Consolidator;
-use crate::{Metrics, PersistConfig, ShardId, WriterId};
+use crate::{Metrics, PersistConfig, ShardId, ShardTuning, WriterId};
 
 /// A request for compaction.
 ///
@@ -62,6 +61,39 @@ pub struct CompactReq<T> {
     pub inputs: Vec<HollowBatch<T>>,
 }
 
+impl<T> CompactReq<T> {
+    /// The total encoded size of the parts to be compacted.
+    pub(crate) fn input_bytes(&self) -> usize {
+        self.inputs
+            .iter()
+            .flat_map(|batch| batch.parts.iter())
+            .map(|part| part.encoded_size_bytes)
+            .sum()
+    }
+
+    /// The size of the inputs to be compacted, for efficacy metrics.
+    pub(crate) fn input_stats(&self) -> CompactionEfficacyStats {
+        efficacy_stats(&self.inputs)
+    }
+}
+
+/// Sums the encoded size, update count, and number of runs of `batches`.
+pub(crate) fn efficacy_stats<'a, T: 'a>(
+    batches: impl IntoIterator<Item = &'a HollowBatch<T>>,
+) -> CompactionEfficacyStats {
+    let mut stats = CompactionEfficacyStats::default();
+    for batch in batches {
+        stats.bytes += batch
+            .parts
+            .iter()
+            .map(|part| part.encoded_size_bytes)
+            .sum::<usize>();
+        stats.updates += batch.len;
+        stats.runs += batch.runs().count();
+    }
+    stats
+}
+
 /// A response from compaction.
 #[derive(Debug)]
 pub struct CompactRes<T> {
@@ -75,6 +107,148 @@ pub(crate) const STREAMING_COMPACTION_ENABLED: Config<bool> = Config::new(
     "use the new streaming consolidate during compaction",
 );
 
+pub(crate) const COMPACTION_CANCELLATION_CHECK_INTERVAL_PARTS: Config<usize> = Config::new(
+    "persist_compaction_cancellation_check_interval_parts",
+    16,
+    "the number of input parts a compaction fetches between checks of whether \
+    its output is still useful (0 disables the checks)",
+);
+
+pub(crate) const COMPACTION_QUEUE_MAX_CONCURRENCY: Config<usize> = Config::new(
+    "persist_compaction_queue_max_concurrency",
+    16,
+    "the maximum number of compactions that can run at once across every shard \
+    in the process",
+);
+
+pub(crate) const COMPACTION_FROZEN: Config<bool> = Config::new(
+    "persist_compaction_frozen",
+    false,
+    "skip new compactions and abandon in-flight ones",
+);
+
+/// Why an in-flight compaction was abandoned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompactionCancelReason {
+    /// None of the compaction's inputs are in the shard's spine anymore, e.g.
+    /// because a newer merge replaced them, so its output could not be
+    /// applied.
+    InputsReplaced,
+    /// The shard became a tombstone.
+    Tombstone,
+    /// Compaction was frozen via [COMPACTION_FROZEN].
+    Frozen,
+}
+
+/// A cheap check, run periodically while a compaction fetches its inputs, of
+/// whether its output is still useful.
+#[derive(Clone)]
+pub(crate) struct CompactionCancellation {
+    /// The number of input parts to fetch between checks, or 0 to never check.
+    interval_parts: usize,
+    check: Arc<dyn Fn() -> Option<CompactionCancelReason> + Send + Sync>,
+}
+
+impl Debug for CompactionCancellation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompactionCancellation")
+            .field("interval_parts", &self.interval_parts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CompactionCancellation {
+    /// Returns a cancellation that abandons a compaction of `inputs` once the
+    /// current state of `machine`, or the config, says it's pointless.
+    pub(crate) fn new<K, V, T, D>(
+        cfg: &PersistConfig,
+        machine: &Machine<K, V, T, D>,
+        inputs: Vec<HollowBatch<T>>,
+    ) -> Self
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let frozen = COMPACTION_FROZEN.shared(&cfg.configs);
+        let applier = machine.applier.clone();
+        CompactionCancellation {
+            interval_parts: COMPACTION_CANCELLATION_CHECK_INTERVAL_PARTS.get(&cfg.configs),
+            check: Arc::new(move || {
+                if COMPACTION_FROZEN.get_from_shared(&frozen) {
+                    return Some(CompactionCancelReason::Frozen);
+                }
+                applier.compaction_cancel_reason(&inputs)
+            }),
+        }
+    }
+}
+
+/// The error returned by a compaction abandoned by its
+/// [CompactionCancellation].
+#[derive(Debug)]
+pub(crate) struct CompactionCancelled {
+    pub(crate) reason: CompactionCancelReason,
+}
+
+impl std::fmt::Display for CompactionCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "compaction cancelled: {:?}", self.reason)
+    }
+}
+
+impl std::error::Error for CompactionCancelled {}
+
+/// Tracks a compaction's progress through its inputs, running its
+/// [CompactionCancellation] check every so often.
+#[derive(Debug)]
+struct CancellationTracker {
+    cancellation: Option<CompactionCancellation>,
+    parts_since_check: usize,
+    bytes_fetched: usize,
+}
+
+impl CancellationTracker {
+    fn new(cancellation: Option<CompactionCancellation>) -> Self {
+        CancellationTracker {
+            cancellation,
+            parts_since_check: 0,
+            bytes_fetched: 0,
+        }
+    }
+
+    /// Records that `parts` input parts totalling `bytes` were fetched,
+    /// returning an error if the compaction should be abandoned.
+    ///
+    /// If fetching `failed`, this checks regardless of the interval: the
+    /// inputs of a compaction that's no longer useful may have been deleted
+    /// out from under it, which is a cancellation rather than a failure.
+    fn fetched(
+        &mut self,
+        parts: usize,
+        bytes: usize,
+        failed: bool,
+    ) -> Result<(), CompactionCancelled> {
+        self.bytes_fetched += bytes;
+        let Some(cancellation) = self.cancellation.as_ref() else {
+            return Ok(());
+        };
+        if cancellation.interval_parts == 0 {
+            return Ok(());
+        }
+        self.parts_since_check += parts;
+        if self.parts_since_check < cancellation.interval_parts && !failed {
+            return Ok(());
+        }
+        self.parts_since_check = 0;
+        match (cancellation.check)() {
+            Some(reason) => Err(CompactionCancelled { reason }),
+            None => Ok(()),
+        }
+    }
+}
+
 /// A snapshot of dynamic configs to make it easier to reason about an
 /// individual run of compaction.
 #[derive(Debug, Clone)]
@@ -97,6 +271,173 @@ impl CompactConfig {
             streaming_compact: STREAMING_COMPACTION_ENABLED.get(&value.configs),
         }
     }
+
+    /// Applies the per-shard overrides in `tuning` on top of this config.
+    pub(crate) fn with_tuning(mut self, tuning: &ShardTuning) -> Self {
+        self.batch = self.batch.with_tuning(tuning);
+        if let Some(memory_bound) = tuning.compaction_memory_bound_bytes {
+            self.compaction_memory_bound_bytes = memory_bound;
+        }
+        // Compaction needs memory for at least 2 runs and 2 in-progress parts,
+        // which an override of either setting could otherwise violate.
+        self.compaction_memory_bound_bytes = std::cmp::max(
+            self.compaction_memory_bound_bytes,
+            4 * self.batch.blob_target_size,
+        );
+        self
+    }
+}
+
+/// Admission control for compaction across every shard in a process.
+///
+/// At most [COMPACTION_QUEUE_MAX_CONCURRENCY] compactions run at once. When the limit is reached, waiting requests are admitted in order of
+/// how many bytes they would compact, largest first, so that the shards most in
+/// need of compaction get it first. Ties go to whichever request has been
+/// waiting longest.
+#[derive(Debug, Default)]
+pub(crate) struct CompactionQueue {
+    state: Mutex<CompactionQueueState>,
+}
+
+#[derive(Debug, Default)]
+struct CompactionQueueState {
+    /// The most recently requested concurrency limit.
+    limit: usize,
+    running: usize,
+    next_id: u64,
+    waiting: BinaryHeap<CompactionWaiter>,
+}
+
+#[derive(Debug)]
+struct CompactionWaiter {
+    bytes: usize,
+    id: Reverse<u64>,
+    notify: oneshot::Sender<()>,
+}
+
+impl PartialEq for CompactionWaiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for CompactionWaiter {}
+
+impl PartialOrd for CompactionWaiter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CompactionWaiter {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.bytes, self.id).cmp(&(other.bytes, other.id))
+    }
+}
+
+impl CompactionQueueState {
+    /// Admits as many of the highest priority waiters as the limit allows.
+    fn admit(&mut self) {
+        while self.running < self.limit {
+            let Some(waiter) = self.waiting.pop() else {
+                break;
+            };
+            // A waiter that has given up will have closed its receiver.
+            if waiter.notify.send(()).is_ok() {
+                self.running += 1;
+            }
+        }
+    }
+}
+
+impl CompactionQueue {
+    /// Waits for a slot to run a compaction of `bytes` bytes, with at most
+    /// `limit` compactions running at once.
+    ///
+    /// Returns None if no slot became available within `timeout`.
+    pub(crate) async fn acquire(
+        self: &Arc<Self>,
+        limit: usize,
+        bytes: usize,
+        timeout: Duration,
+        metrics: &Metrics,
+    ) -> Option<CompactionPermit> {
+        let rx = {
+            let mut state = self.state.lock().expect("lock poisoned");
+            state.limit = std::cmp::max(limit, 1);
+            if state.waiting.is_empty() && state.running < state.limit {
+                state.running += 1;
+                return Some(CompactionPermit {
+                    queue: Arc::clone(self),
+                });
+            }
+            let (tx, rx) = oneshot::channel();
+            let id = Reverse(state.next_id);
+            state.next_id += 1;
+            state.waiting.push(CompactionWaiter {
+                bytes,
+                id,
+                notify: tx,
+            });
+            // The limit may have just been raised.
+            state.admit();
+            rx
+        };
+        metrics.compaction.concurrency_waits.inc();
+        let mut waiter = QueuedCompaction {
+            rx,
+            queue: Arc::clone(self),
+        };
+        match tokio::time::timeout(timeout, &mut waiter.rx).await {
+            Ok(Ok(())) => Some(CompactionPermit {
+                queue: Arc::clone(self),
+            }),
+            // If we were admitted at the last moment, dropping `waiter` hands
+            // the slot back.
+            Ok(Err(_)) | Err(_) => None,
+        }
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        state.running -= 1;
+        state.admit();
+    }
+
+    #[cfg(test)]
+    fn num_waiting(&self) -> usize {
+        self.state.lock().expect("lock poisoned").waiting.len()
+    }
+}
+
+/// A slot to run a compaction, handed back to its [CompactionQueue] on drop.
+#[derive(Debug)]
+pub(crate) struct CompactionPermit {
+    queue: Arc<CompactionQueue>,
+}
+
+impl Drop for CompactionPermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// A request waiting in a [CompactionQueue].
+#[derive(Debug)]
+struct QueuedCompaction {
+    rx: oneshot::Receiver<()>,
+    queue: Arc<CompactionQueue>,
+}
+
+impl Drop for QueuedCompaction {
+    fn drop(&mut self) {
+        // Stop any further admission and, if we were admitted but never
+        // noticed (e.g. we timed out at the same moment), give the slot back.
+        self.rx.close();
+        if self.rx.try_recv().is_ok() {
+            self.queue.release();
+        }
+    }
 }
 
 /// A service for performing physical and logical compaction.
@@ -152,6 +493,7 @@ where
         let concurrency_limit = Arc::new(tokio::sync::Semaphore::new(
             cfg.compaction_concurrency_limit,
         ));
+        let compaction_queue = Arc::clone(&isolated_runtime.compaction_queue);
 
         // spin off a single task responsible for executing compaction requests.
         // work is enqueued into the task through a channel
@@ -162,7 +504,7 @@ where
                 assert_eq!(req.shard_id, machine.shard_id());
                 let metrics = Arc::clone(&machine.applier.metrics);
 
-                let permit = {
+                let shard_permit = {
                     let inner = Arc::clone(&concurrency_limit);
                     // perform a non-blocking attempt to acquire a permit so we can
                     // record how often we're ever blocked on the concurrency limit
@@ -183,10 +525,38 @@ where
                         }
                     }
                 };
+
+                // On top of the per-shard limit, there's one shared by every
+                // shard in the process, so wait our turn, giving up (and
+                // dropping the request) if that takes too long.
+                let queue_timeout = machine
+                    .applier
+                    .cfg
+                    .compaction_queue_timeout
+                    .saturating_sub(enqueued.elapsed());
+                let permit = compaction_queue
+                    .acquire(
+                        COMPACTION_QUEUE_MAX_CONCURRENCY.get(&machine.applier.cfg.configs),
+                        req.input_bytes(),
+                        queue_timeout,
+                        &metrics,
+                    )
+                    .await;
                 metrics
                     .compaction
                     .queued_seconds
                     .inc_by(enqueued.elapsed().as_secs_f64());
+                let Some(permit) = permit else {
+                    metrics.compaction.queue_timed_out.inc();
+                    debug!(
+                        "compaction for {} timed out waiting on the concurrency limit",
+                        machine.shard_id()
+                    );
+                    let _ = completer.send(Err(anyhow!(
+                        "timed out waiting on the compaction concurrency limit"
+                    )));
+                    continue;
+                };
 
                 let cfg = machine.applier.cfg.clone();
                 let blob = Arc::clone(&machine.applier.state_versions.blob);
@@ -194,8 +564,13 @@ where
                 let writer_id = writer_id.clone();
                 let schemas = schemas.clone();
 
-                let compact_span =
-                    debug_span!(parent: None, "compact::apply", shard_id=%machine.shard_id());
+                let compact_span = debug_span!(
+                    parent: None,
+                    "compact::apply",
+                    shard_id=%machine.shard_id(),
+                    shard_name=machine.shard_name(),
+                    handle_purpose=machine.handle_purpose(),
+                );
                 compact_span.follows_from(&Span::current());
                 let gc = gc.clone();
                 mz_ore::task::spawn(|| "PersistCompactionWorker", async move {
@@ -217,8 +592,9 @@ where
                     // wasn't interested in waiting and dropped their receiver
                     let _ = completer.send(res);
 
-                    // moves `permit` into async scope so it can be dropped upon completion
+                    // moves the permits into async scope so they can be dropped upon completion
                     drop(permit);
+                    drop(shard_permit);
                 });
             }
         });
@@ -250,7 +626,7 @@ where
                 >= self.cfg.dynamic.compaction_heuristic_min_parts()
             || req.inputs.iter().map(|x| x.len).sum::<usize>()
                 >= self.cfg.dynamic.compaction_heuristic_min_updates();
-        if !should_compact {
+        if !should_compact || COMPACTION_FROZEN.get(&self.cfg.configs) {
             self.metrics.compaction.skipped.inc();
             return None;
         }
@@ -294,12 +670,8 @@ where
 
         // pick a timeout for our compaction request proportional to the amount
         // of data that must be read (with a minimum set by PersistConfig)
-        let total_input_bytes = req
-            .inputs
-            .iter()
-            .flat_map(|batch| batch.parts.iter())
-            .map(|parts| parts.encoded_size_bytes)
-            .sum::<usize>();
+        let total_input_bytes = req.input_bytes();
+        let input_stats = req.input_stats();
         let timeout = Duration::max(
             // either our minimum timeout
             cfg.dynamic.compaction_minimum_timeout(),
@@ -314,21 +686,30 @@ where
             timeout.as_secs_f64()
         );
 
+        // Abandon the compaction early if the shard moves on such that its
+        // output would be discarded anyway.
+        let cancellation = CompactionCancellation::new(&cfg, machine, req.inputs.clone());
+
         let compact_span = debug_span!("compact::consolidate");
         let res = tokio::time::timeout(
             timeout,
             // Compaction is cpu intensive, so be polite and spawn it on the isolated runtime.
+            //
+            // A panic here is returned as an error like any other compaction
+            // failure: the output is only applied to state if compaction
+            // succeeds, so dropping it is safe.
             isolated_runtime
-                .spawn_named(
-                    || "persist::compact::consolidate",
+                .spawn_catching(
+                    "persist::compact::consolidate",
                     Self::compact(
-                        CompactConfig::new(&cfg, &writer_id),
+                        CompactConfig::new(&cfg, &writer_id).with_tuning(&machine.applier.tuning()),
                         Arc::clone(&blob),
                         Arc::clone(&metrics),
                         Arc::clone(&machine.applier.shard_metrics),
                         Arc::clone(&isolated_runtime),
                         req,
                         schemas.clone(),
+                        Some(cancellation),
                     )
                     .instrument(compact_span),
                 )
@@ -344,13 +725,17 @@ where
             }
         };
 
-        metrics
-            .compaction
-            .seconds
-            .inc_by(start.elapsed().as_secs_f64());
+        let elapsed = start.elapsed();
+        metrics.compaction.seconds.inc_by(elapsed.as_secs_f64());
 
         match res {
             Ok(Ok(res)) => {
+                machine.applier.shard_metrics.maintenance_failing.set(0);
+                metrics.compaction.maintenance.observe(
+                    input_stats,
+                    efficacy_stats([&res.output]),
+                    elapsed,
+                );
                 let res = FueledMergeRes { output: res.output };
                 let (apply_merge_result, maintenance) = machine.merge_res(&res).await;
                 maintenance.start_performing(machine, gc);
@@ -386,8 +771,18 @@ where
                     }
                 }
             }
+            Ok(Err(err)) if err.is::<CompactionCancelled>() => {
+                // Not a failure: the shard moved on while we were compacting.
+                debug!(
+                    "compaction for {} cancelled: {}",
+                    machine.shard_id(),
+                    err.display_with_causes()
+                );
+                Err(err)
+            }
             Ok(Err(err)) | Err(err) => {
                 metrics.compaction.failed.inc();
+                machine.applier.shard_metrics.maintenance_failing.set(1);
                 debug!(
                     "compaction for {} failed: {}",
                     machine.shard_id(),
@@ -429,6 +824,7 @@ where
         isolated_runtime: Arc<IsolatedRuntime>,
         req: CompactReq<T>,
         schemas: Schemas<K, V>,
+        cancellation: Option<CompactionCancellation>,
     ) -> Result<CompactRes<T>, anyhow::Error> {
         let () = Self::validate_req(&req)?;
 
@@ -467,6 +863,7 @@ where
         let mut all_parts = vec![];
         let mut all_runs = vec![];
         let mut len = 0;
+        let mut tracker = CancellationTracker::new(cancellation);
 
         for (runs, run_chunk_max_memory_usage) in
             Self::chunk_runs(&req, &cfg, metrics.as_ref(), run_reserved_memory_bytes)
@@ -485,6 +882,15 @@ where
                 / cfg.batch.blob_target_size;
             let mut run_cfg = cfg.clone();
             run_cfg.batch.batch_builder_max_outstanding_parts = 1 + extra_outstanding_parts;
+            // Carry the schema the inputs were written with over to the
+            // outputs. Compaction works on the encoded updates, so mixed inputs
+            // are labeled with the latest schema, which is assumed to be an
+            // evolution of the earlier ones.
+            run_cfg.batch.schema_id = runs
+                .iter()
+                .flat_map(|(_, parts)| parts.iter())
+                .filter_map(|part| part.schema_id)
+                .max();
             let batch = Self::compact_runs(
                 &run_cfg,
                 &req.shard_id,
@@ -495,8 +901,34 @@ where
                 Arc::clone(&shard_metrics),
                 Arc::clone(&isolated_runtime),
                 schemas.clone(),
+                &mut tracker,
             )
-            .await?;
+            .await;
+            let batch = match batch {
+                Ok(batch) => batch,
+                Err(err) => {
+                    if err.is::<CompactionCancelled>() {
+                        metrics.compaction.cancelled.inc();
+                        metrics
+                            .compaction
+                            .cancelled_bytes_avoided
+                            .inc_by(u64::cast_from(
+                                req.input_bytes().saturating_sub(tracker.bytes_fetched),
+                            ));
+                        // The in-progress run cleaned up after itself, but the
+                        // output of any earlier ones is ours to delete.
+                        for part in all_parts.iter() {
+                            let key = part.key.complete(&req.shard_id);
+                            retry_external(
+                                &metrics.retries.external.compaction_noop_delete,
+                                || blob.delete(&key),
+                            )
+                            .await;
+                        }
+                    }
+                    return Err(err);
+                }
+            };
             let (parts, runs, updates) = (batch.parts, batch.runs, batch.len);
             assert!(
                 (updates == 0 && parts.len() == 0) || (updates > 0 && parts.len() > 0),
@@ -655,6 +1087,7 @@ where
         shard_metrics: Arc<ShardMetrics>,
         isolated_runtime: Arc<IsolatedRuntime>,
         real_schemas: Schemas<K, V>,
+        tracker: &mut CancellationTracker,
     ) -> Result<HollowBatch<T>, anyhow::Error> {
         // TODO: Figure out a more principled way to allocate our memory budget.
         // Currently, we give any excess budget to write parallelism. If we had
@@ -699,6 +1132,14 @@ where
             prefetch_budget_bytes,
         );
 
+        // The consolidator fetches parts internally, so we can only check for
+        // cancellation once it's done with all of them.
+        let parts_fetched = runs.iter().map(|(_, parts)| parts.len()).sum();
+        let bytes_fetched = runs
+            .iter()
+            .flat_map(|(_, parts)| parts.iter())
+            .map(|part| part.encoded_size_bytes)
+            .sum();
         for (desc, parts) in runs {
             consolidator.enqueue_run(
                 *shard_id,
@@ -721,7 +1162,12 @@ where
         let mut val_vec = vec![];
         loop {
             let fetch_start = Instant::now();
-            let Some(updates) = consolidator.next().await? else {
+            let updates = consolidator.next().await;
+            if let Err(cancelled) = tracker.fetched(0, 0, updates.is_err()) {
+                batch.abort().await;
+                return Err(cancelled.into());
+            }
+            let Some(updates) = updates? else {
                 break;
             };
             timings.part_fetching += fetch_start.elapsed();
@@ -734,6 +1180,10 @@ where
             }
             tokio::task::yield_now().await;
         }
+        if let Err(cancelled) = tracker.fetched(parts_fetched, bytes_fetched, false) {
+            batch.abort().await;
+            return Err(cancelled.into());
+        }
         let batch = batch.finish(&real_schemas, desc.upper().clone()).await?;
         let hollow_batch = batch.into_hollow_batch();
 
@@ -756,6 +1206,7 @@ where
         shard_metrics: Arc<ShardMetrics>,
         isolated_runtime: Arc<IsolatedRuntime>,
         real_schemas: Schemas<K, V>,
+        tracker: &mut CancellationTracker,
     ) -> Result<HollowBatch<T>, anyhow::Error> {
         if cfg.streaming_compact {
             return Self::compact_runs_streaming(
@@ -768,6 +1219,7 @@ where
                 shard_metrics,
                 isolated_runtime,
                 real_schemas,
+                tracker,
             )
             .await;
         }
@@ -842,9 +1294,15 @@ where
         for (index, (part_desc, parts)) in runs.iter_mut().enumerate() {
             if let Some(part) = parts.pop_front() {
                 let start = Instant::now();
+                let part_bytes = part.encoded_size_bytes();
                 let part = part
                     .join(shard_id, blob.as_ref(), &metrics, &shard_metrics, part_desc)
-                    .await?;
+                    .await;
+                if let Err(cancelled) = tracker.fetched(1, part_bytes, part.is_err()) {
+                    batch.abort().await;
+                    return Err(cancelled.into());
+                }
+                let part = part?;
                 // Ideally we'd hook into start_prefetches here, too, but runs
                 // is mutable borrowed. Not the end of the world. Instead do it
                 // once after this initial heap population.
@@ -890,9 +1348,15 @@ where
                 let (part_desc, parts) = &mut runs[index];
                 if let Some(part) = parts.pop_front() {
                     let start = Instant::now();
+                    let part_bytes = part.encoded_size_bytes();
                     let part = part
                         .join(shard_id, blob.as_ref(), &metrics, &shard_metrics, part_desc)
-                        .await?;
+                        .await;
+                    if let Err(cancelled) = tracker.fetched(1, part_bytes, part.is_err()) {
+                        batch.abort().await;
+                        return Err(cancelled.into());
+                    }
+                    let part = part?;
                     // start_prefetches is O(n) so calling it here is O(n^2). N
                     // is the number of things we're about to fetch over the
                     // network, so if it's big enough for N^2 to matter, we've
@@ -1005,7 +1469,7 @@ impl Timings {
 #[derive(Debug)]
 enum CompactionPart<'a, T> {
     Queued(&'a HollowBatchPart),
-    Prefetched(usize, JoinHandle<Result<EncodedPart<T>, BlobKey>>),
+    Prefetched(usize, JoinHandle<Result<EncodedPart<T>, FetchBatchError>>),
 }
 
 impl<'a, T: Timestamp + Lattice + Codec64> CompactionPart<'a, T> {
@@ -1016,6 +1480,13 @@ impl<'a, T: Timestamp + Lattice + Codec64> CompactionPart<'a, T> {
         }
     }
 
+    fn encoded_size_bytes(&self) -> usize {
+        match self {
+            CompactionPart::Queued(part) => part.encoded_size_bytes,
+            CompactionPart::Prefetched(cost_bytes, _) => *cost_bytes,
+        }
+    }
+
     async fn join(
         self,
         shard_id: &ShardId,
@@ -1043,11 +1514,12 @@ impl<'a, T: Timestamp + Lattice + Codec64> CompactionPart<'a, T> {
                     &metrics.read.compaction,
                     &part.key,
                     part_desc,
+                    part.checksum,
                 )
                 .await
             }
         };
-        result.map_err(|blob_key| anyhow!("failed to fetch part for shard: {blob_key}"))
+        result.map_err(|err| anyhow!("failed to fetch part for shard: {err}"))
     }
 }
 
@@ -1102,6 +1574,7 @@ fn start_prefetches<T: Timestamp + Lattice + Codec64>(
             let shard_metrics = Arc::clone(shard_metrics);
             let part_key = part.key.clone();
             let part_desc = part_desc.clone();
+            let part_checksum = part.checksum;
             let handle = spawn(
                 || "persist::compaction::prefetch",
                 async move {
@@ -1113,6 +1586,7 @@ fn start_prefetches<T: Timestamp + Lattice + Codec64>(
                         &metrics.read.compaction,
                         &part_key,
                         &part_desc,
+                        part_checksum,
                     )
                     .await
                 }
@@ -1125,14 +1599,22 @@ fn start_prefetches<T: Timestamp + Lattice + Codec64>(
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bytes::Bytes;
+    use mz_ore::metrics::MetricsRegistry;
+    use mz_persist::location::Atomicity;
+    use mz_persist::unreliable::{Fault, Op, UnreliableHandle};
     use mz_persist_types::codec_impls::{StringSchema, UnitSchema};
     use timely::progress::Antichain;
 
-    use crate::internal::paths::PartialBatchKey;
+    use crate::internal::paths::{BlobKey, PartialBatchKey, PartialBlobKey};
     use crate::tests::{
-        all_ok, expect_fetch_part, new_test_client, new_test_client_cache, CodecProduct,
+        all_ok, expect_fetch_part, new_fault_injected_test_client, new_test_client,
+        new_test_client_cache, new_test_shard, CodecProduct,
     };
-    use crate::PersistLocation;
+    use crate::{Diagnostics, FinalizeFrontiers, PersistLocation};
 
     use super::*;
 
@@ -1148,12 +1630,12 @@ mod tests {
             (("1".to_owned(), "one".to_owned()), 1, 1),
         ];
 
-        let cache = new_test_client_cache();
-        cache.cfg.dynamic.set_blob_target_size(100);
-        let (mut write, _) = cache
-            .open(PersistLocation::new_in_mem())
-            .await
-            .expect("client construction failed")
+        // Compaction must tolerate flaky blob reads and writes, including writes
+        // whose outcome is unknown to it.
+        let faults = UnreliableHandle::new(0, 1.0, 0.0);
+        let client = new_fault_injected_test_client(&faults);
+        client.cfg.dynamic.set_blob_target_size(100);
+        let (mut write, _) = client
             .expect_open::<String, String, u64, i64>(ShardId::new())
             .await;
         let b0 = write
@@ -1165,6 +1647,9 @@ mod tests {
             .await
             .into_hollow_batch();
 
+        faults.fail_next(Op::BlobGet, 2, Fault::Determinate);
+        faults.fail_next(Op::BlobSet, 1, Fault::IndeterminateAfterRun);
+        faults.fail_next(Op::BlobSet, 1, Fault::Indeterminate);
         let req = CompactReq {
             shard_id: write.machine.shard_id(),
             desc: Description::new(
@@ -1186,9 +1671,12 @@ mod tests {
             Arc::new(IsolatedRuntime::new()),
             req.clone(),
             schemas,
+            None,
         )
         .await
         .expect("compaction failed");
+        assert_eq!(faults.injected(Op::BlobGet), 2);
+        assert_eq!(faults.injected(Op::BlobSet), 2);
 
         assert_eq!(res.output.desc, req.desc);
         assert_eq!(res.output.len, 1);
@@ -1203,6 +1691,134 @@ mod tests {
         assert_eq!(updates, all_ok(&data, 10));
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn compaction_efficacy_metrics() {
+        let data = vec![
+            (("0".to_owned(), "zero".to_owned()), 0, 1),
+            (("0".to_owned(), "zero".to_owned()), 1, -1),
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+        ];
+
+        let (client, mut write, _) = new_test_shard().await;
+        let b0 = write
+            .expect_batch(&data[..1], 0, 1)
+            .await
+            .into_hollow_batch();
+        let b1 = write
+            .expect_batch(&data[1..], 1, 2)
+            .await
+            .into_hollow_batch();
+
+        let req = CompactReq {
+            shard_id: write.machine.shard_id(),
+            desc: Description::new(
+                b0.desc.lower().clone(),
+                b1.desc.upper().clone(),
+                Antichain::from_elem(10u64),
+            ),
+            inputs: vec![b0, b1],
+        };
+        let input_bytes = req.input_bytes();
+        let schemas = Schemas {
+            key: Arc::new(StringSchema),
+            val: Arc::new(StringSchema),
+        };
+        let mut machine = write.machine.clone();
+        let gc = GarbageCollector::new(machine.clone(), Arc::clone(&client.isolated_runtime));
+        Compactor::<String, String, u64, i64>::compact_and_apply(
+            write.cfg.clone(),
+            Arc::clone(&write.blob),
+            Arc::clone(&write.metrics),
+            Arc::clone(&client.isolated_runtime),
+            req,
+            write.writer_id.clone(),
+            schemas,
+            &mut machine,
+            &gc,
+        )
+        .await
+        .expect("compaction failed");
+
+        let efficacy = &write.metrics.compaction.maintenance;
+        assert_eq!(efficacy.count.get(), 1);
+        assert_eq!(efficacy.input_bytes.get(), u64::cast_from(input_bytes));
+        assert!(efficacy.output_bytes.get() > 0);
+        assert_eq!(efficacy.input_updates.get(), 3);
+        // The updates to "0" consolidate out.
+        assert_eq!(efficacy.output_updates.get(), 1);
+        assert_eq!(efficacy.runs_merged.get(), 2);
+        assert!(efficacy.write_amplification.get() > 0.0);
+        assert_eq!(write.metrics.compaction.forced.count.get(), 0);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn compaction_checksum_mismatch() {
+        let data = vec![
+            (("0".to_owned(), "zero".to_owned()), 0, 1),
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+        ];
+
+        let (_client, mut write, _) = new_test_shard().await;
+        let b0 = write
+            .expect_batch(&data[..1], 0, 1)
+            .await
+            .into_hollow_batch();
+        let b1 = write
+            .expect_batch(&data[1..], 1, 2)
+            .await
+            .into_hollow_batch();
+
+        // Flip a byte of one of the inputs.
+        let key = b0.parts[0].key.complete(&write.machine.shard_id());
+        let mut value = write
+            .blob
+            .get(&key)
+            .await
+            .expect("blob available")
+            .expect("part exists")
+            .into_contiguous();
+        value[0] ^= 0xff;
+        write
+            .blob
+            .set(&key, Bytes::from(value), Atomicity::RequireAtomic)
+            .await
+            .expect("blob available");
+
+        let req = CompactReq {
+            shard_id: write.machine.shard_id(),
+            desc: Description::new(
+                b0.desc.lower().clone(),
+                b1.desc.upper().clone(),
+                Antichain::from_elem(10u64),
+            ),
+            inputs: vec![b0, b1],
+        };
+        let schemas = Schemas {
+            key: Arc::new(StringSchema),
+            val: Arc::new(StringSchema),
+        };
+        let res = Compactor::<String, String, u64, i64>::compact(
+            CompactConfig::new(&write.cfg, &write.writer_id),
+            Arc::clone(&write.blob),
+            Arc::clone(&write.metrics),
+            write.metrics.shards.shard(&write.machine.shard_id(), ""),
+            Arc::new(IsolatedRuntime::new()),
+            req,
+            schemas,
+            None,
+        )
+        .await;
+        let err = res.expect_err("compaction input was corrupted");
+        assert!(
+            format!("{:#}", err).contains("checksum mismatch"),
+            "unexpected error: {:#}",
+            err
+        );
+        assert_eq!(write.metrics.read.compaction.checksum_mismatches.get(), 1);
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn compaction_partial_order() {
@@ -1268,6 +1884,7 @@ mod tests {
             Arc::new(IsolatedRuntime::new()),
             req.clone(),
             schemas,
+            None,
         )
         .await
         .expect("compaction failed");
@@ -1299,6 +1916,8 @@ mod tests {
                 encoded_size_bytes,
                 key_lower: vec![],
                 stats: None,
+                schema_id: None,
+                checksum: None,
             })
             .collect::<Vec<_>>();
         let parse = |x: &str| {
@@ -1383,4 +2002,188 @@ mod tests {
         start_prefetches(1, &mut runs, &shard_id, blob, metrics, shard_metrics);
         assert_eq!(print(&runs), " 1| 1|f9");
     }
+
+    #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn compaction_queue_priority() {
+        const TIMEOUT: Duration = Duration::from_secs(60);
+        let cfg = PersistConfig::new_for_tests();
+        let metrics = Arc::new(Metrics::new(&cfg, &MetricsRegistry::new()));
+        let queue = Arc::new(CompactionQueue::default());
+
+        // Hold the only slot while several shards queue up behind it.
+        let held = queue
+            .acquire(1, 0, TIMEOUT, &metrics)
+            .await
+            .expect("slot is free");
+        let shards = [
+            (ShardId::new(), 10),
+            (ShardId::new(), 1000),
+            (ShardId::new(), 100),
+        ];
+        let running = Arc::new(AtomicUsize::new(0));
+        let (order_tx, mut order_rx) = mpsc::unbounded_channel();
+        let mut tasks = Vec::new();
+        for (shard_id, bytes) in shards {
+            let (queue, metrics) = (Arc::clone(&queue), Arc::clone(&metrics));
+            let (running, order_tx) = (Arc::clone(&running), order_tx.clone());
+            tasks.push(spawn(|| "compaction_queue_priority", async move {
+                let _permit = queue
+                    .acquire(1, bytes, TIMEOUT, &metrics)
+                    .await
+                    .expect("admitted before timeout");
+                assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                assert_eq!(running.fetch_sub(1, Ordering::SeqCst), 1);
+                order_tx.send(shard_id).expect("receiver is alive");
+            }));
+        }
+        while queue.num_waiting() < shards.len() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        drop(held);
+        for task in tasks {
+            task.await.expect("task succeeded");
+        }
+
+        // The shards ran one at a time, biggest first.
+        let mut expected = shards.to_vec();
+        expected.sort_by_key(|(_, bytes)| Reverse(*bytes));
+        for (shard_id, _) in expected {
+            assert_eq!(order_rx.recv().await, Some(shard_id));
+        }
+
+        // A request that times out is dropped without leaking its slot.
+        let held = queue
+            .acquire(1, 0, TIMEOUT, &metrics)
+            .await
+            .expect("slot is free");
+        assert!(queue
+            .acquire(1, 0, Duration::from_millis(1), &metrics)
+            .await
+            .is_none());
+        drop(held);
+        assert!(queue.acquire(1, 0, TIMEOUT, &metrics).await.is_some());
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn compaction_cancelled_by_finalize() {
+        async fn batch_keys(blob: &(dyn Blob + Send + Sync)) -> BTreeSet<String> {
+            let mut keys = BTreeSet::new();
+            blob.list_keys_and_metadata("", &mut |x| {
+                if let Ok((_, PartialBlobKey::Batch(..))) = BlobKey::parse_ids(x.key) {
+                    keys.insert(x.key.to_owned());
+                }
+            })
+            .await
+            .expect("blob available");
+            keys
+        }
+
+        let data = (0..80u64)
+            .map(|i| ((format!("{:02}", i), "val".to_owned()), i / 40, 1i64))
+            .collect::<Vec<_>>();
+
+        let faults = UnreliableHandle::new(0, 1.0, 0.0);
+        let client = new_fault_injected_test_client(&faults);
+        client.cfg.dynamic.set_blob_target_size(100);
+        client
+            .cfg
+            .set_config(&COMPACTION_CANCELLATION_CHECK_INTERVAL_PARTS, 1);
+        let shard_id = ShardId::new();
+        let (mut write, read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data[..40], 0, 1).await;
+        write.expect_compare_and_append(&data[40..], 1, 2).await;
+        let (machine, blob, metrics) = (
+            write.machine.clone(),
+            Arc::clone(&write.blob),
+            Arc::clone(&write.metrics),
+        );
+        let inputs = machine
+            .applier
+            .snapshot(&Antichain::from_elem(1))
+            .expect("valid as_of");
+        let req = CompactReq {
+            shard_id,
+            desc: Description::new(
+                inputs[0].desc.lower().clone(),
+                inputs[inputs.len() - 1].desc.upper().clone(),
+                Antichain::from_elem(0u64),
+            ),
+            inputs,
+        };
+        let keys_before = batch_keys(blob.as_ref()).await;
+
+        // Slow the compaction down enough to finalize the shard while it's
+        // still fetching its inputs.
+        faults.set_op_latency(Op::BlobGet, Duration::from_millis(50));
+        let schemas = Schemas {
+            key: Arc::new(StringSchema),
+            val: Arc::new(StringSchema),
+        };
+        let compaction = spawn(
+            || "compaction",
+            Compactor::<String, String, u64, i64>::compact(
+                CompactConfig::new(&client.cfg, &write.writer_id),
+                Arc::clone(&blob),
+                Arc::clone(&metrics),
+                metrics.shards.shard(&shard_id, ""),
+                Arc::new(IsolatedRuntime::new()),
+                req.clone(),
+                schemas,
+                Some(CompactionCancellation::new(
+                    &client.cfg,
+                    &machine,
+                    req.inputs.clone(),
+                )),
+            ),
+        );
+        while metrics.compaction.batch.bytes.get() == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        const EMPTY: &[((String, String), u64, i64)] = &[];
+        let () = write
+            .compare_and_append(EMPTY, Antichain::from_elem(2), Antichain::new())
+            .await
+            .expect("usage should be valid")
+            .expect("upper should match");
+        read.expire().await;
+        write.expire().await;
+        client
+            .finalize_shard::<String, String, u64, i64>(
+                shard_id,
+                Diagnostics::for_tests(),
+                FinalizeFrontiers::ForceEmpty,
+            )
+            .await
+            .expect("invalid usage");
+
+        let err = compaction
+            .await
+            .expect("task succeeded")
+            .expect_err("compaction was cancelled");
+        let cancelled = err
+            .downcast_ref::<CompactionCancelled>()
+            .expect("compaction was cancelled");
+        assert_eq!(cancelled.reason, CompactionCancelReason::Tombstone);
+        assert_eq!(metrics.compaction.cancelled.get(), 1);
+        assert!(metrics.compaction.cancelled_bytes_avoided.get() > 0);
+
+        // The partial output was cleaned up.
+        faults.clear_op_faults();
+        let keys_after = batch_keys(blob.as_ref()).await;
+        assert!(
+            keys_after.is_subset(&keys_before),
+            "leaked: {:?}",
+            keys_after.difference(&keys_before).collect::<Vec<_>>()
+        );
+
+        // Compaction can also be frozen explicitly.
+        client.cfg.set_config(&COMPACTION_FROZEN, true);
+        let cancellation = CompactionCancellation::new(&client.cfg, &machine, req.inputs);
+        assert_eq!((cancellation.check)(), Some(CompactionCancelReason::Frozen));
+    }
 }
@@ -108,6 +108,8 @@ impl<'a> DirectiveArgs<'a> {
                     encoded_size_bytes: 0,
                     key_lower: vec![],
                     stats: None,
+                    schema_id: None,
+                    checksum: None,
                 })
                 .collect(),
             runs: vec![],
@@ -32,15 +32,15 @@ use tracing::debug;
 use uuid::Uuid;
 
 use crate::critical::CriticalReaderId;
-use crate::error::{CodecMismatch, CodecMismatchT};
+use crate::error::{AllowCodecMismatch, CodecMismatch, CodecMismatchT};
 use crate::internal::metrics::Metrics;
 use crate::internal::paths::{PartialBatchKey, PartialRollupKey};
 use crate::internal::state::{
     CriticalReaderState, HandleDebugState, HollowBatch, HollowBatchPart, HollowRollup,
-    IdempotencyToken, LeasedReaderState, OpaqueState, ProtoCriticalReaderState,
+    IdempotencyToken, LeasedReaderState, OpaqueState, ProtoCriticalReaderState, ProtoDiagnostics,
     ProtoHandleDebugState, ProtoHollowBatch, ProtoHollowBatchPart, ProtoHollowRollup,
-    ProtoInlinedDiffs, ProtoLeasedReaderState, ProtoRollup, ProtoStateDiff, ProtoStateField,
-    ProtoStateFieldDiffType, ProtoStateFieldDiffs, ProtoTrace, ProtoU64Antichain,
+    ProtoInlinedDiffs, ProtoLeasedReaderState, ProtoRollup, ProtoShardTuning, ProtoStateDiff,
+    ProtoStateField, ProtoStateFieldDiffType, ProtoStateFieldDiffs, ProtoTrace, ProtoU64Antichain,
     ProtoU64Description, ProtoVersionedData, ProtoWriterState, State, StateCollections, TypedState,
     WriterState,
 };
@@ -50,7 +50,7 @@ use crate::internal::state_diff::{
 use crate::internal::trace::Trace;
 use crate::read::LeasedReaderId;
 use crate::stats::PartStats;
-use crate::{PersistConfig, ShardId, WriterId};
+use crate::{Diagnostics, PersistConfig, SchemaId, ShardId, ShardStatus, ShardTuning, WriterId};
 
 #[derive(Debug)]
 pub struct Schemas<K: Codec, V: Codec> {
@@ -342,6 +342,7 @@ impl<T: Timestamp + Codec64> RustType<ProtoStateDiff> for StateDiff<T> {
             leased_readers,
             critical_readers,
             writers,
+            tuning,
             since,
             spine,
         } = self;
@@ -361,6 +362,7 @@ impl<T: Timestamp + Codec64> RustType<ProtoStateDiff> for StateDiff<T> {
             &mut writer,
         );
         field_diffs_into_proto(ProtoStateField::Writers, writers, &mut writer);
+        field_diffs_into_proto(ProtoStateField::Tuning, tuning, &mut writer);
         field_diffs_into_proto(ProtoStateField::Since, since, &mut writer);
         field_diffs_into_proto(ProtoStateField::Spine, spine, &mut writer);
 
@@ -464,6 +466,14 @@ impl<T: Timestamp + Codec64> RustType<ProtoStateDiff> for StateDiff<T> {
                             |v| v.into_rust(),
                         )?
                     }
+                    ProtoStateField::Tuning => {
+                        field_diff_into_rust::<(), ProtoShardTuning, _, _, _, _>(
+                            diff,
+                            &mut state_diff.tuning,
+                            |()| Ok(()),
+                            |v| v.into_rust(),
+                        )?
+                    }
                     ProtoStateField::Since => {
                         field_diff_into_rust::<(), ProtoU64Antichain, _, _, _, _>(
                             diff,
@@ -619,6 +629,16 @@ impl<T: Timestamp + Lattice + Codec64> UntypedState<T> {
     pub fn check_codecs<K: Codec, V: Codec, D: Codec64>(
         self,
         shard_id: &ShardId,
+    ) -> Result<TypedState<K, V, T, D>, Box<CodecMismatch>> {
+        self.check_codecs_allowing(shard_id, None)
+    }
+
+    /// [Self::check_codecs], but ignoring any mismatch that the given hook
+    /// allows.
+    pub(crate) fn check_codecs_allowing<K: Codec, V: Codec, D: Codec64>(
+        self,
+        shard_id: &ShardId,
+        allow: Option<&AllowCodecMismatch>,
     ) -> Result<TypedState<K, V, T, D>, Box<CodecMismatch>> {
         // Also defensively check that the shard_id on the state we fetched
         // matches the shard_id we were trying to fetch.
@@ -628,7 +648,7 @@ impl<T: Timestamp + Lattice + Codec64> UntypedState<T> {
             || T::codec_name() != self.ts_codec
             || D::codec_name() != self.diff_codec
         {
-            return Err(Box::new(CodecMismatch {
+            let mismatch = Box::new(CodecMismatch {
                 requested: (
                     K::codec_name(),
                     V::codec_name(),
@@ -643,7 +663,11 @@ impl<T: Timestamp + Lattice + Codec64> UntypedState<T> {
                     self.diff_codec,
                     None,
                 ),
-            }));
+                registered_by: self.state.registered_by.clone(),
+            });
+            if !AllowCodecMismatch::allows(allow, &mismatch) {
+                return Err(mismatch);
+            }
         }
         Ok(TypedState {
             state: self.state,
@@ -664,6 +688,33 @@ impl<T: Timestamp + Lattice + Codec64> UntypedState<T> {
         Ok(self.state)
     }
 
+    pub(crate) fn shard_status(self, shard_id: &ShardId) -> Result<ShardStatus, CodecMismatchT> {
+        let codecs = (
+            self.key_codec.clone(),
+            self.val_codec.clone(),
+            self.ts_codec.clone(),
+            self.diff_codec.clone(),
+        );
+        let state = self.check_ts_codec(shard_id)?;
+        Ok(ShardStatus {
+            exists: true,
+            is_finalized: state.collections.is_tombstone(),
+            upper_codec64_bytes: state
+                .upper()
+                .elements()
+                .iter()
+                .map(Codec64::encode)
+                .collect(),
+            since_codec64_bytes: state
+                .since()
+                .elements()
+                .iter()
+                .map(Codec64::encode)
+                .collect(),
+            codecs: Some(codecs),
+        })
+    }
+
     pub fn decode(build_version: &Version, buf: impl Buf) -> Self {
         let proto = ProtoRollup::decode(buf)
             // We received a State that we couldn't decode. This could happen if
@@ -807,11 +858,13 @@ impl<T: Timestamp + Lattice + Codec64> RustType<ProtoRollup> for Rollup<T> {
             seqno: self.state.state.seqno.into_proto(),
             walltime_ms: self.state.state.walltime_ms.into_proto(),
             hostname: self.state.state.hostname.into_proto(),
+            registered_by: self.state.state.registered_by.into_proto(),
             key_codec: self.state.key_codec.into_proto(),
             val_codec: self.state.val_codec.into_proto(),
             ts_codec: T::codec_name(),
             diff_codec: self.state.diff_codec.into_proto(),
             last_gc_req: self.state.state.collections.last_gc_req.into_proto(),
+            tuning: Some(self.state.state.collections.tuning.into_proto()),
             rollups: self
                 .state
                 .state
@@ -896,6 +949,9 @@ impl<T: Timestamp + Lattice + Codec64> RustType<ProtoRollup> for Rollup<T> {
             leased_readers,
             critical_readers,
             writers,
+            // MIGRATION: Rollups written before shard tuning existed don't
+            // have it, which is the same as not overriding anything.
+            tuning: x.tuning.into_rust()?.unwrap_or_default(),
             trace: x.trace.into_rust_if_some("trace")?,
         };
         let state = State {
@@ -904,6 +960,7 @@ impl<T: Timestamp + Lattice + Codec64> RustType<ProtoRollup> for Rollup<T> {
             seqno: x.seqno.into_rust()?,
             walltime_ms: x.walltime_ms,
             hostname: x.hostname,
+            registered_by: x.registered_by.into_rust()?,
             collections,
         };
 
@@ -1103,6 +1160,44 @@ impl<T: Timestamp + Codec64> RustType<ProtoWriterState> for WriterState<T> {
     }
 }
 
+impl RustType<ProtoDiagnostics> for Diagnostics {
+    fn into_proto(&self) -> ProtoDiagnostics {
+        ProtoDiagnostics {
+            shard_name: self.shard_name.into_proto(),
+            handle_purpose: self.handle_purpose.into_proto(),
+        }
+    }
+
+    fn from_proto(proto: ProtoDiagnostics) -> Result<Self, TryFromProtoError> {
+        Ok(Diagnostics {
+            shard_name: proto.shard_name,
+            handle_purpose: proto.handle_purpose,
+        })
+    }
+}
+
+impl RustType<ProtoShardTuning> for ShardTuning {
+    fn into_proto(&self) -> ProtoShardTuning {
+        ProtoShardTuning {
+            blob_target_size: self.blob_target_size.into_proto(),
+            batch_builder_max_outstanding_parts: self
+                .batch_builder_max_outstanding_parts
+                .into_proto(),
+            compaction_memory_bound_bytes: self.compaction_memory_bound_bytes.into_proto(),
+        }
+    }
+
+    fn from_proto(proto: ProtoShardTuning) -> Result<Self, TryFromProtoError> {
+        Ok(ShardTuning {
+            blob_target_size: proto.blob_target_size.into_rust()?,
+            batch_builder_max_outstanding_parts: proto
+                .batch_builder_max_outstanding_parts
+                .into_rust()?,
+            compaction_memory_bound_bytes: proto.compaction_memory_bound_bytes.into_rust()?,
+        })
+    }
+}
+
 impl RustType<ProtoHandleDebugState> for HandleDebugState {
     fn into_proto(&self) -> ProtoHandleDebugState {
         ProtoHandleDebugState {
@@ -1143,6 +1238,8 @@ impl<T: Timestamp + Codec64> RustType<ProtoHollowBatch> for HollowBatch<T> {
                     encoded_size_bytes: 0,
                     key_lower: vec![],
                     stats: None,
+                    schema_id: None,
+                    checksum: None,
                 }),
         );
         Ok(HollowBatch {
@@ -1161,6 +1258,8 @@ impl RustType<ProtoHollowBatchPart> for HollowBatchPart {
             encoded_size_bytes: self.encoded_size_bytes.into_proto(),
             key_lower: Bytes::copy_from_slice(&self.key_lower),
             key_stats: self.stats.into_proto(),
+            schema_id: self.schema_id.map(|x| x.0.into_proto()),
+            checksum: self.checksum,
         }
     }
 
@@ -1170,6 +1269,11 @@ impl RustType<ProtoHollowBatchPart> for HollowBatchPart {
             encoded_size_bytes: proto.encoded_size_bytes.into_rust()?,
             key_lower: proto.key_lower.into(),
             stats: proto.key_stats.into_rust()?,
+            schema_id: proto
+                .schema_id
+                .map(|x| x.into_rust().map(SchemaId))
+                .transpose()?,
+            checksum: proto.checksum,
         })
     }
 }
@@ -1385,6 +1489,8 @@ mod tests {
                 encoded_size_bytes: 5,
                 key_lower: vec![],
                 stats: None,
+                schema_id: None,
+                checksum: None,
             }],
             runs: vec![],
         };
@@ -1404,6 +1510,8 @@ mod tests {
             encoded_size_bytes: 0,
             key_lower: vec![],
             stats: None,
+            schema_id: None,
+            checksum: None,
         });
         assert_eq!(<HollowBatch<u64>>::from_proto(old).unwrap(), expected);
     }
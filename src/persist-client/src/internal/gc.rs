@@ -13,7 +13,7 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::mem;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
@@ -22,10 +22,13 @@ use futures_util::StreamExt;
 use prometheus::Counter;
 use timely::progress::Timestamp;
 use tokio::sync::mpsc::UnboundedSender;
-use tokio::sync::{mpsc, oneshot, Semaphore};
+use tokio::sync::{mpsc, oneshot, Mutex, Semaphore};
+use tokio::time::{Interval, MissedTickBehavior};
 use tracing::{debug, debug_span, error, warn, Instrument, Span};
 
 use crate::async_runtime::IsolatedRuntime;
+use crate::cfg::PersistConfig;
+use crate::dyn_cfg::Config;
 use mz_ore::cast::CastFrom;
 use mz_ore::collections::HashSet;
 use mz_persist::location::{Blob, SeqNo};
@@ -39,6 +42,20 @@ use crate::internal::state::HollowBlobRef;
 use crate::internal::state_versions::{InspectDiff, StateVersionsIter};
 use crate::ShardId;
 
+pub(crate) const GC_BLOB_DELETE_MAX_PER_RUN: Config<usize> = Config::new(
+    "persist_gc_blob_delete_max_per_run",
+    0,
+    "the maximum number of blobs deleted by a single run of GC, with any \
+    remaining deletes carried forward to a follow-up run (0 means unlimited)",
+);
+
+pub(crate) const GC_BLOB_DELETE_RATE_LIMIT_PER_SEC: Config<usize> = Config::new(
+    "persist_gc_blob_delete_rate_limit_per_sec",
+    0,
+    "the maximum number of blobs deleted per second by a single run of GC \
+    (0 means unlimited)",
+);
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct GcReq {
     pub shard_id: ShardId,
@@ -108,6 +125,14 @@ impl<K, V, T, D> Clone for GarbageCollector<K, V, T, D> {
 ///   leaked. We anyway always have the possibility of a write process being
 ///   killed between when it writes a blob and links it into state, so this is
 ///   fine; it'll be caught and fixed by the same mechanism.)
+/// - A large advancement of the seqno_since can make a single request eligible
+///   to delete a huge number of blobs. To avoid overwhelming Blob, each run
+///   may be capped ([GC_BLOB_DELETE_MAX_PER_RUN]) and paced
+///   ([GC_BLOB_DELETE_RATE_LIMIT_PER_SEC]). A run that hits the cap stops
+///   before truncating past the blobs it couldn't delete and returns a
+///   follow-up request for the same seqno_since as maintenance. The blobs it
+///   did delete are remembered by the GarbageCollector task so the follow-up
+///   run doesn't spend its budget deleting them again.
 impl<K, V, T, D> GarbageCollector<K, V, T, D>
 where
     K: Debug + Codec,
@@ -122,6 +147,9 @@ where
         // spin off a single task responsible for executing GC requests.
         // work is enqueued into the task through a channel
         let _worker_handle = mz_ore::task::spawn(|| "PersistGcWorker", async move {
+            // Blobs deleted by a previous run that was stopped by the per-run
+            // cap before it could truncate Consensus past them.
+            let mut deleted_before_truncate = BTreeSet::new();
             while let Some((req, completer)) = gc_req_recv.recv().await {
                 let mut consolidated_req = req;
                 let mut gc_completed_senders = vec![completer];
@@ -149,7 +177,13 @@ where
                     );
                 }
 
-                let gc_span = debug_span!(parent: None, "gc_and_truncate", shard_id=%consolidated_req.shard_id);
+                let gc_span = debug_span!(
+                    parent: None,
+                    "gc_and_truncate",
+                    shard_id=%consolidated_req.shard_id,
+                    shard_name=machine.shard_name(),
+                    handle_purpose=machine.handle_purpose(),
+                );
                 gc_span.follows_from(&Span::current());
 
                 let start = Instant::now();
@@ -157,14 +191,24 @@ where
                 let (mut maintenance, _stats) = {
                     let name = format!("gc_and_truncate ({})", &consolidated_req.shard_id);
                     let mut machine = machine.clone();
-                    isolated_runtime
-                        .spawn_named(|| name, async move {
-                            Self::gc_and_truncate(&mut machine, consolidated_req)
-                                .instrument(gc_span)
-                                .await
+                    let mut deleted = mem::take(&mut deleted_before_truncate);
+                    // GC deletes blobs and truncates consensus, so continuing
+                    // after a panic partway through is unsafe.
+                    let (maintenance, stats, deleted) = isolated_runtime
+                        .spawn_catching(name, async move {
+                            let (maintenance, stats) = Self::gc_and_truncate_resumable(
+                                &mut machine,
+                                consolidated_req,
+                                &mut deleted,
+                            )
+                            .instrument(gc_span)
+                            .await;
+                            (maintenance, stats, deleted)
                         })
                         .await
-                        .expect("gc_and_truncate failed")
+                        .unwrap_or_else(|err| err.abort());
+                    deleted_before_truncate = deleted;
+                    (maintenance, stats)
                 };
                 machine.applier.metrics.gc.finished.inc();
                 machine.applier.shard_metrics.gc_finished.inc();
@@ -215,9 +259,21 @@ where
         Some(gc_completed_receiver)
     }
 
+    #[cfg(test)]
     pub(crate) async fn gc_and_truncate(
         machine: &mut Machine<K, V, T, D>,
         req: GcReq,
+    ) -> (RoutineMaintenance, GcResults) {
+        Self::gc_and_truncate_resumable(machine, req, &mut BTreeSet::new()).await
+    }
+
+    /// Like `gc_and_truncate`, but skips deleting any blobs in
+    /// `deleted_before_truncate` and adds to it any blobs deleted by a run that
+    /// is stopped early by [GC_BLOB_DELETE_MAX_PER_RUN].
+    pub(crate) async fn gc_and_truncate_resumable(
+        machine: &mut Machine<K, V, T, D>,
+        req: GcReq,
+        deleted_before_truncate: &mut BTreeSet<BlobKey>,
     ) -> (RoutineMaintenance, GcResults) {
         let mut step_start = Instant::now();
         let mut report_step_timing = |counter: &Counter| {
@@ -258,6 +314,12 @@ where
             // been done, or the there aren't enough rollups <= seqno_since to have any
             // to delete), we can safely exit.
             machine.applier.metrics.gc.noop.inc();
+            machine
+                .applier
+                .shard_metrics
+                .gc_deferred_blob_deletes
+                .set(0);
+            deleted_before_truncate.clear();
             return (RoutineMaintenance::default(), gc_results);
         }
 
@@ -291,14 +353,41 @@ where
             states.len()
         );
 
-        Self::incrementally_delete_and_truncate(
+        let mut budget = GcDeleteBudget::new(&machine.applier.cfg);
+        let completed = Self::incrementally_delete_and_truncate(
             &mut states,
             &gc_rollups,
             machine,
+            &mut budget,
+            deleted_before_truncate,
             &mut report_step_timing,
             &mut gc_results,
         )
         .await;
+        machine
+            .applier
+            .shard_metrics
+            .gc_deferred_blob_deletes
+            .set(u64::cast_from(gc_results.blob_deletes_deferred));
+
+        let rollups_to_remove_from_state = if completed {
+            deleted_before_truncate.clear();
+            rollups_to_remove_from_state
+        } else {
+            // We ran out of budget before truncating through every rollup,
+            // so we may only remove the rollups for states that are no
+            // longer in Consensus. The rollup we stopped on is still needed
+            // to maintain the invariant below.
+            machine.applier.metrics.gc.capped.inc();
+            let truncated_lt = gc_results
+                .truncated_consensus_to
+                .last()
+                .copied()
+                .unwrap_or(initial_seqno);
+            let truncated_rollups = rollups_to_remove_from_state
+                .partition_point(|(seqno, _rollup)| *seqno < truncated_lt);
+            &rollups_to_remove_from_state[..truncated_rollups]
+        };
 
         // Now that the blobs are deleted / Consensus is truncated, remove
         // the rollups from state. Doing this at the end ensures that our
@@ -316,12 +405,22 @@ where
         // In short, while this step is not incremental, it does not need
         // to be for GC to efficiently resume. And in fact, making it
         // incremental could be quite expensive (e.g. more CaS operations).
-        let (removed_rollups, maintenance) =
-            machine.remove_rollups(rollups_to_remove_from_state).await;
+        let (removed_rollups, mut maintenance) = if rollups_to_remove_from_state.is_empty() {
+            (Vec::new(), RoutineMaintenance::default())
+        } else {
+            machine.remove_rollups(rollups_to_remove_from_state).await
+        };
         report_step_timing(&machine.applier.metrics.gc.steps.remove_rollups_from_state);
         debug!("CaS removed rollups from state: {:?}", removed_rollups);
         gc_results.rollups_removed_from_state = removed_rollups;
 
+        // Carry forward whatever work the cap didn't let us get to. A GC
+        // request generated by removing rollups is at least as recent as ours,
+        // so it covers the remaining work just as well.
+        if !completed && maintenance.garbage_collection.is_none() {
+            maintenance.garbage_collection = Some(req.clone());
+        }
+
         // Everything here and below is not strictly needed for GC to complete,
         // but it's a good opportunity, while we have all live states in hand,
         // to run some metrics and assertions.
@@ -396,13 +495,19 @@ where
     /// Internally, performs deletions for each rollup encountered, ensuring that
     /// incremental progress is made even if the process is interrupted before
     /// completing all gc work.
+    ///
+    /// Returns false if `budget` ran out before Consensus could be truncated to
+    /// every rollup `<= seqno_since`.
     async fn incrementally_delete_and_truncate<F>(
         states: &mut StateVersionsIter<T>,
         gc_rollups: &GcRollups,
         machine: &Machine<K, V, T, D>,
+        budget: &mut GcDeleteBudget,
+        deleted_before_truncate: &mut BTreeSet<BlobKey>,
         timer: &mut F,
         gc_results: &mut GcResults,
-    ) where
+    ) -> bool
+    where
         F: FnMut(&Counter),
     {
         assert_eq!(states.state().shard_id, machine.shard_id());
@@ -484,19 +589,22 @@ where
                 }
             });
 
-            gc_results.truncated_consensus_to.push(truncate_lt);
-            gc_results.batch_parts_deleted_from_blob += batch_parts_to_delete.len();
-            gc_results.rollups_deleted_from_blob += rollups_to_delete.len();
-
-            Self::delete_and_truncate(
+            let truncated = Self::delete_and_truncate(
                 truncate_lt,
                 &mut batch_parts_to_delete,
                 &mut rollups_to_delete,
                 machine,
+                budget,
+                deleted_before_truncate,
                 timer,
+                gc_results,
             )
             .await;
+            if !truncated {
+                return false;
+            }
         }
+        true
     }
 
     /// Iterates through `states`, accumulating all deleted blobs (both batch parts
@@ -531,6 +639,17 @@ where
                         assert!(rollups_to_delete.insert(rollup.key.to_owned()));
                     }
                 });
+                // Restoring a shard to an earlier state (see
+                // [crate::internal::restore::restore_state]) re-inserts
+                // batches that were deleted since, so those must be kept.
+                diff.map_blob_inserts(|blob| match blob {
+                    HollowBlobRef::Batch(batch) => {
+                        for part in &batch.parts {
+                            batch_parts_to_delete.remove(&part.key);
+                        }
+                    }
+                    HollowBlobRef::Rollup(_) => {}
+                });
             }
         }) {
             if state.seqno == truncate_lt {
@@ -540,15 +659,23 @@ where
         timer(&metrics.find_deletable_blobs_seconds);
     }
 
-    /// Deletes `batch_parts` and `rollups` from Blob.
-    /// Truncates Consensus to `truncate_lt`.
+    /// Deletes `batch_parts` and `rollups` from Blob, skipping any already in
+    /// `deleted_before_truncate`. Truncates Consensus to `truncate_lt`.
+    ///
+    /// If `budget` runs out before every blob is deleted, Consensus is left
+    /// untruncated, the blobs that were deleted are added to
+    /// `deleted_before_truncate`, and false is returned.
     async fn delete_and_truncate<F>(
         truncate_lt: SeqNo,
         batch_parts: &mut BTreeSet<PartialBatchKey>,
         rollups: &mut BTreeSet<PartialRollupKey>,
         machine: &Machine<K, V, T, D>,
+        budget: &mut GcDeleteBudget,
+        deleted_before_truncate: &mut BTreeSet<BlobKey>,
         timer: &mut F,
-    ) where
+        gc_results: &mut GcResults,
+    ) -> bool
+    where
         F: FnMut(&Counter),
     {
         let shard_id = machine.shard_id();
@@ -560,34 +687,78 @@ where
                 .gc_blob_delete_concurrency_limit(),
         );
 
+        let mut batch_part_keys: Vec<BlobKey> = batch_parts
+            .iter()
+            .map(|k| k.complete(&shard_id))
+            .filter(|k| !deleted_before_truncate.contains(k))
+            .collect();
+        batch_parts.clear();
+        let mut rollup_keys: Vec<BlobKey> = rollups
+            .iter()
+            .map(|k| k.complete(&shard_id))
+            .filter(|k| !deleted_before_truncate.contains(k))
+            .collect();
+        rollups.clear();
+
+        // Batch parts are deleted first, so they're also the first to be
+        // granted out of the budget.
+        let pending = batch_part_keys.len() + rollup_keys.len();
+        let granted = budget.claim(pending);
+        batch_part_keys.truncate(granted);
+        rollup_keys.truncate(granted - batch_part_keys.len());
+
         Self::delete_all(
             machine.applier.state_versions.blob.borrow(),
-            batch_parts.iter().map(|k| k.complete(&shard_id)),
+            batch_part_keys.iter().cloned(),
             &machine.applier.metrics.retries.external.rollup_delete,
             debug_span!("rollup::delete"),
             &delete_semaphore,
+            budget.rate_limit.as_ref(),
         )
         .await;
-        batch_parts.clear();
+        gc_results.batch_parts_deleted_from_blob += batch_part_keys.len();
         timer(&machine.applier.metrics.gc.steps.delete_rollup_seconds);
 
         Self::delete_all(
             machine.applier.state_versions.blob.borrow(),
-            rollups.iter().map(|k| k.complete(&shard_id)),
+            rollup_keys.iter().cloned(),
             &machine.applier.metrics.retries.external.batch_delete,
             debug_span!("batch::delete"),
             &delete_semaphore,
+            budget.rate_limit.as_ref(),
         )
         .await;
-        rollups.clear();
+        gc_results.rollups_deleted_from_blob += rollup_keys.len();
         timer(&machine.applier.metrics.gc.steps.delete_batch_part_seconds);
 
+        let deferred = pending - granted;
+        if deferred > 0 {
+            debug!(
+                "gc deferred {} blob deletes before truncating to {}",
+                deferred, truncate_lt
+            );
+            deleted_before_truncate.extend(batch_part_keys);
+            deleted_before_truncate.extend(rollup_keys);
+            gc_results.blob_deletes_deferred += deferred;
+            machine
+                .applier
+                .metrics
+                .gc
+                .deferred_blob_deletes
+                .inc_by(u64::cast_from(deferred));
+            return false;
+        }
+
         machine
             .applier
             .state_versions
             .truncate_diffs(&shard_id, truncate_lt)
             .await;
         timer(&machine.applier.metrics.gc.steps.truncate_diff_seconds);
+        gc_results.truncated_consensus_to.push(truncate_lt);
+        // Everything deleted before this truncation is now unreachable.
+        deleted_before_truncate.clear();
+        true
     }
 
     // There's also a bulk delete API in s3 if the performance of this
@@ -600,6 +771,7 @@ where
         metrics: &RetryMetrics,
         span: Span,
         semaphore: &Semaphore,
+        rate_limit: Option<&Mutex<Interval>>,
     ) {
         let futures = FuturesUnordered::new();
         for key in keys {
@@ -607,6 +779,9 @@ where
                 retry_external(metrics, move || {
                     let key = key.clone();
                     async move {
+                        if let Some(rate_limit) = rate_limit {
+                            rate_limit.lock().await.tick().await;
+                        }
                         let _permit = semaphore
                             .acquire()
                             .await
@@ -628,6 +803,48 @@ pub(crate) struct GcResults {
     pub(crate) rollups_deleted_from_blob: usize,
     pub(crate) truncated_consensus_to: Vec<SeqNo>,
     pub(crate) rollups_removed_from_state: Vec<SeqNo>,
+    pub(crate) blob_deletes_deferred: usize,
+}
+
+/// Limits on the blob deletes issued by a single run of GC.
+///
+/// See [GC_BLOB_DELETE_MAX_PER_RUN] and [GC_BLOB_DELETE_RATE_LIMIT_PER_SEC].
+#[derive(Debug)]
+struct GcDeleteBudget {
+    /// The number of deletes this run may still issue, if capped.
+    remaining: Option<usize>,
+    /// Paces deletes to the configured rate, if limited.
+    rate_limit: Option<Mutex<Interval>>,
+}
+
+impl GcDeleteBudget {
+    fn new(cfg: &PersistConfig) -> Self {
+        let max_per_run = GC_BLOB_DELETE_MAX_PER_RUN.get(&cfg.configs);
+        let per_sec = GC_BLOB_DELETE_RATE_LIMIT_PER_SEC.get(&cfg.configs);
+        let rate_limit = (per_sec > 0).then(|| {
+            let period = Duration::from_secs(1) / u32::try_from(per_sec).unwrap_or(u32::MAX);
+            let mut interval =
+                tokio::time::interval(std::cmp::max(period, Duration::from_nanos(1)));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            Mutex::new(interval)
+        });
+        GcDeleteBudget {
+            remaining: (max_per_run > 0).then_some(max_per_run),
+            rate_limit,
+        }
+    }
+
+    /// Claims up to `n` deletes, returning the number granted.
+    fn claim(&mut self, n: usize) -> usize {
+        match &mut self.remaining {
+            None => n,
+            Some(remaining) => {
+                let granted = std::cmp::min(*remaining, n);
+                *remaining -= granted;
+                granted
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -9,32 +9,35 @@
 
 //! Implementation of the persist state machine.
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 use std::future::Future;
 use std::ops::ControlFlow::{self, Continue};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime};
 
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
 use futures::stream::{FuturesUnordered, StreamExt};
 use futures::FutureExt;
+use mz_ore::cast::CastFrom;
 use mz_ore::error::ErrorExt;
 #[allow(unused_imports)] // False positive.
 use mz_ore::fmt::FormatBuffer;
-use mz_ore::task::JoinHandle;
+use mz_ore::task::{AbortOnDropHandle, JoinHandle};
 use mz_persist::location::{ExternalError, Indeterminate, SeqNo};
 use mz_persist::retry::Retry;
 use mz_persist_types::{Codec, Codec64, Opaque};
 use timely::progress::{Antichain, Timestamp};
 use timely::PartialOrder;
-use tracing::{debug, info, trace_span, warn, Instrument};
+use tracing::{debug, info, instrument, trace_span, warn, Instrument};
 
 use crate::async_runtime::IsolatedRuntime;
 use crate::cache::StateCache;
 use crate::cfg::RetryParameters;
 use crate::critical::CriticalReaderId;
-use crate::error::{CodecMismatch, InvalidUsage};
+use crate::dyn_cfg::{Config, ConfigSet};
+use crate::error::{AllowCodecMismatch, CodecMismatch, InvalidUsage};
 use crate::internal::apply::Applier;
 use crate::internal::compact::CompactReq;
 use crate::internal::gc::GarbageCollector;
@@ -51,8 +54,9 @@ use crate::internal::trace::{ApplyMergeResult, FueledMergeRes};
 use crate::internal::watch::StateWatch;
 use crate::read::LeasedReaderId;
 use crate::rpc::PubSubSender;
+use crate::usage::ShardOp;
 use crate::write::WriterId;
-use crate::{Diagnostics, PersistConfig, ShardId};
+use crate::{Diagnostics, FinalizeFrontiers, PersistConfig, ShardId, ShardTuning};
 
 #[derive(Debug)]
 pub struct Machine<K, V, T, D> {
@@ -86,6 +90,7 @@ where
         pubsub_sender: Arc<dyn PubSubSender>,
         isolated_runtime: Arc<IsolatedRuntime>,
         diagnostics: Diagnostics,
+        allow_codec_mismatch: Option<AllowCodecMismatch>,
     ) -> Result<Self, Box<CodecMismatch>> {
         let applier = Applier::new(
             cfg,
@@ -95,6 +100,7 @@ where
             shared_states,
             pubsub_sender,
             diagnostics,
+            allow_codec_mismatch,
         )
         .await?;
         Ok(Machine {
@@ -107,6 +113,17 @@ where
         self.applier.shard_id
     }
 
+    /// The user-friendly name of the shard, as given by the [Diagnostics] of
+    /// the handle that created this machine.
+    pub fn shard_name(&self) -> &str {
+        &self.applier.diagnostics.shard_name
+    }
+
+    /// The purpose of the handle that created this machine.
+    pub fn handle_purpose(&self) -> &str {
+        &self.applier.diagnostics.handle_purpose
+    }
+
     pub fn seqno(&self) -> SeqNo {
         self.applier.seqno()
     }
@@ -213,6 +230,7 @@ where
         (state, maintenance)
     }
 
+    #[instrument(level = "debug", name = "machine::compare_and_append", skip_all, fields(shard = %self.shard_id(), shard_name = self.shard_name(), handle_purpose = self.handle_purpose()))]
     pub async fn compare_and_append(
         &mut self,
         batch: &HollowBatch<T>,
@@ -419,6 +437,7 @@ where
                     if !writer_was_present {
                         metrics.state.writer_added.inc();
                     }
+                    self.applier.shard_metrics.record(ShardOp::Append);
                     return Ok(Ok((seqno, writer_maintenance)));
                 }
                 Err(CompareAndAppendBreak::AlreadyCommitted) => {
@@ -430,6 +449,7 @@ where
                     if !writer_was_present {
                         metrics.state.writer_added.inc();
                     }
+                    self.applier.shard_metrics.record(ShardOp::Append);
                     return Ok(Ok((seqno, WriterMaintenance::default())));
                 }
                 Err(CompareAndAppendBreak::InvalidUsage(err)) => {
@@ -487,6 +507,7 @@ where
         }
     }
 
+    #[instrument(level = "debug", name = "machine::merge_res", skip_all, fields(shard = %self.shard_id(), shard_name = self.shard_name(), handle_purpose = self.handle_purpose()))]
     pub async fn merge_res(
         &mut self,
         res: &FueledMergeRes<T>,
@@ -603,6 +624,23 @@ where
         (seqno, existed, maintenance)
     }
 
+    /// Heartbeats the given leased readers in a single state transition.
+    ///
+    /// Returns the readers that no longer exist.
+    pub async fn heartbeat_leased_readers(
+        &mut self,
+        reader_ids: &[LeasedReaderId],
+        heartbeat_timestamp_ms: u64,
+    ) -> (SeqNo, Vec<LeasedReaderId>, RoutineMaintenance) {
+        let metrics = Arc::clone(&self.applier.metrics);
+        let (seqno, missing, maintenance) = self
+            .apply_unbatched_idempotent_cmd(&metrics.cmds.heartbeat_reader, |_, _, state| {
+                state.heartbeat_leased_readers(reader_ids, heartbeat_timestamp_ms)
+            })
+            .await;
+        (seqno, missing, maintenance)
+    }
+
     pub async fn expire_leased_reader(
         &mut self,
         reader_id: &LeasedReaderId,
@@ -644,6 +682,54 @@ where
         self.applier.is_finalized()
     }
 
+    pub async fn set_tuning(&mut self, tuning: ShardTuning) -> (SeqNo, RoutineMaintenance) {
+        let metrics = Arc::clone(&self.applier.metrics);
+        let (seqno, (), maintenance) = self
+            .apply_unbatched_idempotent_cmd(&metrics.cmds.set_tuning, |_, _, state| {
+                state.set_tuning(&tuning)
+            })
+            .await;
+        (seqno, maintenance)
+    }
+
+    /// Registers a leased reader holding back GC at `hold_seqno`, returning
+    /// false if GC may already have progressed past it.
+    pub async fn register_seqno_hold(
+        &mut self,
+        reader_id: &LeasedReaderId,
+        purpose: &str,
+        hold_seqno: SeqNo,
+        lease_duration: Duration,
+        heartbeat_timestamp_ms: u64,
+    ) -> (bool, RoutineMaintenance) {
+        let metrics = Arc::clone(&self.applier.metrics);
+        let (_seqno, held, maintenance) = self
+            .apply_unbatched_idempotent_cmd(&metrics.cmds.register, |_, cfg, state| {
+                state.register_seqno_hold(
+                    &cfg.hostname,
+                    reader_id,
+                    purpose,
+                    hold_seqno,
+                    lease_duration,
+                    heartbeat_timestamp_ms,
+                )
+            })
+            .await;
+        (held, maintenance)
+    }
+
+    pub async fn restore(
+        &mut self,
+        target: &StateCollections<T>,
+        new_upper: &Antichain<T>,
+    ) -> (SeqNo, Result<(), Upper<T>>, RoutineMaintenance) {
+        let metrics = Arc::clone(&self.applier.metrics);
+        self.apply_unbatched_idempotent_cmd(&metrics.cmds.restore, |_, _, state| {
+            state.restore(target, new_upper)
+        })
+        .await
+    }
+
     async fn tombstone_step(&mut self) -> Result<(bool, RoutineMaintenance), InvalidUsage<T>> {
         let metrics = Arc::clone(&self.applier.metrics);
         let mut retry = self
@@ -683,11 +769,37 @@ where
         }
     }
 
-    pub async fn become_tombstone(&mut self) -> Result<RoutineMaintenance, InvalidUsage<T>> {
-        self.applier.check_since_upper_both_empty()?;
-
+    /// Turns the shard into a tombstone.
+    ///
+    /// If the since and upper of the shard haven't both been advanced to the
+    /// empty antichain, `frontiers` decides whether this returns an error or
+    /// advances them first, which breaks any live readers or writers of the
+    /// shard.
+    pub async fn become_tombstone(
+        &mut self,
+        frontiers: FinalizeFrontiers,
+    ) -> Result<RoutineMaintenance, InvalidUsage<T>> {
         let mut maintenance = RoutineMaintenance::default();
 
+        if let Err(err) = self.applier.check_since_upper_both_empty() {
+            if frontiers == FinalizeFrontiers::RequireEmpty {
+                return Err(err);
+            }
+            warn!(
+                shard_id = %self.shard_id(),
+                "force finalizing shard with non-empty frontiers, any live readers or writers \
+                will break: {}",
+                err
+            );
+            let metrics = Arc::clone(&self.applier.metrics);
+            let (_seqno, (), more_maintenance) = self
+                .apply_unbatched_idempotent_cmd(&metrics.cmds.become_tombstone, |_, _, state| {
+                    state.force_empty_frontiers()
+                })
+                .await;
+            maintenance.merge(more_maintenance);
+        }
+
         loop {
             let (made_progress, more_maintenance) = self.tombstone_step().await?;
             maintenance.merge(more_maintenance);
@@ -699,6 +811,7 @@ where
         Ok(maintenance)
     }
 
+    #[instrument(level = "debug", name = "machine::snapshot", skip_all, fields(shard = %self.shard_id(), shard_name = self.shard_name(), handle_purpose = self.handle_purpose()))]
     pub async fn snapshot(
         &mut self,
         as_of: &Antichain<T>,
@@ -725,16 +838,24 @@ where
 
         enum Wake<'a, K, V, T, D> {
             Watch(&'a mut StateWatch<K, V, T, D>),
+            // The shard's PubSub subscription was re-established, so we might
+            // have missed diffs and have to fetch the latest state ourselves.
+            Resubscribe(&'a mut StateWatch<K, V, T, D>),
             Sleep(MetricsRetryStream),
         }
+        impl<'a, K, V, T, D> Wake<'a, K, V, T, D> {
+            async fn watch(watch: &'a mut StateWatch<K, V, T, D>, seqno: SeqNo) -> Self {
+                match watch.wait_for_seqno_ge_or_resubscribe(seqno).await {
+                    (watch, true) => Wake::Watch(watch),
+                    (watch, false) => Wake::Resubscribe(watch),
+                }
+            }
+        }
         let mut wakes = FuturesUnordered::<
             std::pin::Pin<Box<dyn Future<Output = Wake<K, V, T, D>> + Send + Sync>>,
         >::new();
         wakes.push(Box::pin(
-            watch
-                .wait_for_seqno_ge(seqno.next())
-                .map(Wake::Watch)
-                .instrument(trace_span!("snapshot::watch")),
+            Wake::watch(watch, seqno.next()).instrument(trace_span!("snapshot::watch")),
         ));
         wakes.push(Box::pin(
             sleeps
@@ -777,6 +898,14 @@ where
             // updated.
             match &wake {
                 Wake::Watch(_) => self.applier.metrics.watch.snapshot_woken_via_watch.inc(),
+                Wake::Resubscribe(_) => {
+                    self.applier
+                        .metrics
+                        .watch
+                        .snapshot_woken_via_resubscribe
+                        .inc();
+                    self.applier.fetch_and_update_state(Some(seqno)).await;
+                }
                 Wake::Sleep(_) => {
                     self.applier.metrics.watch.snapshot_woken_via_sleep.inc();
                     self.applier.fetch_and_update_state(Some(seqno)).await;
@@ -804,11 +933,8 @@ where
             };
 
             match wake {
-                Wake::Watch(watch) => wakes.push(Box::pin(
-                    watch
-                        .wait_for_seqno_ge(seqno.next())
-                        .map(Wake::Watch)
-                        .instrument(trace_span!("snapshot::watch")),
+                Wake::Watch(watch) | Wake::Resubscribe(watch) => wakes.push(Box::pin(
+                    Wake::watch(watch, seqno.next()).instrument(trace_span!("snapshot::watch")),
                 )),
                 Wake::Sleep(sleeps) => {
                     debug!(
@@ -843,6 +969,7 @@ where
         }
     }
 
+    #[instrument(level = "debug", name = "machine::next_listen_batch", skip_all, fields(shard = %self.shard_id(), shard_name = self.shard_name(), handle_purpose = self.handle_purpose()))]
     pub async fn next_listen_batch(
         &mut self,
         frontier: &Antichain<T>,
@@ -869,16 +996,24 @@ where
 
         enum Wake<'a, K, V, T, D> {
             Watch(&'a mut StateWatch<K, V, T, D>),
+            // The shard's PubSub subscription was re-established, so we might
+            // have missed diffs and have to fetch the latest state ourselves.
+            Resubscribe(&'a mut StateWatch<K, V, T, D>),
             Sleep(MetricsRetryStream),
         }
+        impl<'a, K, V, T, D> Wake<'a, K, V, T, D> {
+            async fn watch(watch: &'a mut StateWatch<K, V, T, D>, seqno: SeqNo) -> Self {
+                match watch.wait_for_seqno_ge_or_resubscribe(seqno).await {
+                    (watch, true) => Wake::Watch(watch),
+                    (watch, false) => Wake::Resubscribe(watch),
+                }
+            }
+        }
         let mut wakes = FuturesUnordered::<
             std::pin::Pin<Box<dyn Future<Output = Wake<K, V, T, D>> + Send + Sync>>,
         >::new();
         wakes.push(Box::pin(
-            watch
-                .wait_for_seqno_ge(seqno.next())
-                .map(Wake::Watch)
-                .instrument(trace_span!("snapshot::watch")),
+            Wake::watch(watch, seqno.next()).instrument(trace_span!("snapshot::watch")),
         ));
         wakes.push(Box::pin(
             sleeps
@@ -895,6 +1030,14 @@ where
             // updated.
             match &wake {
                 Wake::Watch(_) => self.applier.metrics.watch.listen_woken_via_watch.inc(),
+                Wake::Resubscribe(_) => {
+                    self.applier
+                        .metrics
+                        .watch
+                        .listen_woken_via_resubscribe
+                        .inc();
+                    self.applier.fetch_and_update_state(Some(seqno)).await;
+                }
                 Wake::Sleep(_) => {
                     self.applier.metrics.watch.listen_woken_via_sleep.inc();
                     self.applier.fetch_and_update_state(Some(seqno)).await;
@@ -907,6 +1050,12 @@ where
                         Wake::Watch(_) => {
                             self.applier.metrics.watch.listen_resolved_via_watch.inc()
                         }
+                        Wake::Resubscribe(_) => self
+                            .applier
+                            .metrics
+                            .watch
+                            .listen_resolved_via_resubscribe
+                            .inc(),
                         Wake::Sleep(_) => {
                             self.applier.metrics.watch.listen_resolved_via_sleep.inc()
                         }
@@ -927,12 +1076,8 @@ where
             // Wait a bit and try again. Intentionally don't ever log
             // this at info level.
             match wake {
-                Wake::Watch(watch) => wakes.push(Box::pin(
-                    async move {
-                        watch.wait_for_seqno_ge(seqno.next()).await;
-                        Wake::Watch(watch)
-                    }
-                    .instrument(trace_span!("snapshot::watch")),
+                Wake::Watch(watch) | Wake::Resubscribe(watch) => wakes.push(Box::pin(
+                    Wake::watch(watch, seqno.next()).instrument(trace_span!("snapshot::watch")),
                 )),
                 Wake::Sleep(sleeps) => {
                     debug!(
@@ -951,6 +1096,46 @@ where
         }
     }
 
+    /// Waits until the shard's state has a seqno greater than `seqno` and
+    /// returns the seqno, since, and upper of that state.
+    ///
+    /// Newer state is usually noticed via `watch`, which is driven by PubSub
+    /// pushes, but consensus is also polled with backoff in case a push is
+    /// missed.
+    pub async fn next_seqno_and_frontiers(
+        &mut self,
+        seqno: SeqNo,
+        watch: &mut StateWatch<K, V, T, D>,
+    ) -> (SeqNo, Antichain<T>, Antichain<T>) {
+        let retry = self.applier.cfg.dynamic.next_listen_batch_retry_params();
+        let mut sleep = Box::pin(
+            retry
+                .into_retry(SystemTime::now())
+                .into_retry_stream()
+                .sleep(),
+        );
+        loop {
+            let current = self.applier.seqno_and_frontiers();
+            if current.0 > seqno {
+                return current;
+            }
+            tokio::select! {
+                (_, reached) = watch.wait_for_seqno_ge_or_resubscribe(seqno.next()) => {
+                    // Like in next_listen_batch, a resubscribe means we might
+                    // have missed diffs and have to fetch the latest state
+                    // ourselves.
+                    if !reached {
+                        self.applier.fetch_and_update_state(Some(seqno)).await;
+                    }
+                }
+                sleeps = &mut sleep => {
+                    sleep = Box::pin(sleeps.sleep());
+                    self.applier.fetch_and_update_state(Some(seqno)).await;
+                }
+            }
+        }
+    }
+
     async fn apply_unbatched_idempotent_cmd<
         R,
         WorkFn: FnMut(
@@ -1000,10 +1185,48 @@ where
     T: Timestamp + Lattice + Codec64,
     D: Semigroup + Codec64 + Send + Sync,
 {
-    #[allow(clippy::unused_async)]
-    pub async fn start_reader_heartbeat_tasks(
-        self,
+    /// Registers the given leased reader with the shard's shared heartbeat,
+    /// which keeps its lease alive until the returned registration is dropped.
+    ///
+    /// The heartbeat is shared by all leased readers of the shard opened
+    /// through the same [crate::cache::PersistClientCache], so that it takes one
+    /// task and one state transition to renew all of their leases. It is
+    /// started by the first registration and stopped once the last one is
+    /// dropped.
+    ///
+    /// The heartbeat runs often enough to keep alive the shortest of the
+    /// registered leases, so it is restarted if `lease_duration` is shorter
+    /// than all of them.
+    pub fn register_reader_heartbeat(
+        &self,
         reader_id: LeasedReaderId,
+        lease_duration: Duration,
+        gc: &GarbageCollector<K, V, T, D>,
+    ) -> ReaderHeartbeatRegistration {
+        let heartbeats = Arc::clone(self.applier.reader_heartbeats());
+        let mut guard = heartbeats.lock().expect("lock poisoned");
+        let restart = guard
+            .min_lease_duration()
+            .map_or(true, |min| lease_duration < min);
+        guard.readers.insert(reader_id.clone(), lease_duration);
+        // Dropping the old tasks aborts them. It's fine to do so while
+        // holding the lock, as they don't need it to shut down.
+        if restart || guard.tasks.is_empty() {
+            guard.tasks = self
+                .start_reader_heartbeat_tasks(gc.clone())
+                .into_iter()
+                .map(JoinHandle::abort_on_drop)
+                .collect();
+        }
+        drop(guard);
+        ReaderHeartbeatRegistration {
+            heartbeats,
+            reader_id,
+        }
+    }
+
+    fn start_reader_heartbeat_tasks(
+        &self,
         gc: GarbageCollector<K, V, T, D>,
     ) -> Vec<JoinHandle<()>> {
         let mut ret = Vec::new();
@@ -1020,100 +1243,331 @@ where
         //
         // The real fix here is to find the misbehaving task and fix it. Remove
         // this duplication when that happens.
-        let name = format!("persist::heartbeat_read({},{})", self.shard_id(), reader_id);
+        let name = format!("persist::heartbeat_read({})", self.shard_id());
         ret.push(mz_ore::task::spawn(|| name, {
             let machine = self.clone();
-            let reader_id = reader_id.clone();
             let gc = gc.clone();
             metrics
                 .tasks
                 .heartbeat_read
-                .instrument_task(Self::reader_heartbeat_task(machine, reader_id, gc))
+                .instrument_task(Self::reader_heartbeat_task(machine, gc))
         }));
 
         let isolated_runtime = Arc::clone(&self.isolated_runtime);
-        let name = format!(
-            "persist::heartbeat_read_isolated({},{})",
-            self.shard_id(),
-            reader_id
-        );
+        let name = format!("persist::heartbeat_read_isolated({})", self.shard_id());
         ret.push(
             isolated_runtime.spawn_named(
                 || name,
                 metrics
                     .tasks
                     .heartbeat_read
-                    .instrument_task(Self::reader_heartbeat_task(self, reader_id, gc)),
+                    .instrument_task(Self::reader_heartbeat_task(self.clone(), gc)),
             ),
         );
 
         ret
     }
 
-    async fn reader_heartbeat_task(
-        mut machine: Self,
-        reader_id: LeasedReaderId,
-        gc: GarbageCollector<K, V, T, D>,
-    ) {
-        let sleep_duration = machine.applier.cfg.dynamic.reader_lease_duration() / 2;
+    async fn reader_heartbeat_task(mut machine: Self, gc: GarbageCollector<K, V, T, D>) {
+        let heartbeats = Arc::clone(machine.applier.reader_heartbeats());
+        // Readers whose lease we discovered to be expired, while they are
+        // still registered.
+        let mut expired = BTreeSet::new();
         loop {
+            let Some(min_lease_duration) = heartbeats
+                .lock()
+                .expect("lock poisoned")
+                .min_lease_duration()
+            else {
+                // The last reader was deregistered. This normally aborts the
+                // task, but shut down cleanly if it didn't.
+                return;
+            };
+            let sleep_duration = min_lease_duration / 2;
             let before_sleep = Instant::now();
             tokio::time::sleep(sleep_duration).await;
 
             let elapsed_since_before_sleeping = before_sleep.elapsed();
             if elapsed_since_before_sleeping > sleep_duration + Duration::from_secs(60) {
                 warn!(
-                    "reader ({}) of shard ({}) went {}s between heartbeats",
-                    reader_id,
+                    "readers of shard ({}) went {}s between heartbeats",
                     machine.shard_id(),
                     elapsed_since_before_sleeping.as_secs_f64()
                 );
             }
 
+            let reader_ids: Vec<_> = {
+                let heartbeats = heartbeats.lock().expect("lock poisoned");
+                if heartbeats.readers.is_empty() {
+                    // The last reader was deregistered. This normally aborts
+                    // the task, but shut down cleanly if it didn't.
+                    return;
+                }
+                expired.retain(|reader_id| heartbeats.readers.contains_key(reader_id));
+                heartbeats
+                    .readers
+                    .keys()
+                    .filter(|reader_id| !expired.contains(*reader_id))
+                    .cloned()
+                    .collect()
+            };
+            if reader_ids.is_empty() {
+                continue;
+            }
+
             let before_heartbeat = Instant::now();
-            let (_seqno, existed, maintenance) = machine
-                .heartbeat_leased_reader(&reader_id, (machine.applier.cfg.now)())
+            let (_seqno, missing, maintenance) = machine
+                .heartbeat_leased_readers(&reader_ids, (machine.applier.cfg.now)())
                 .await;
             maintenance.start_performing(&machine, &gc);
 
             let elapsed_since_heartbeat = before_heartbeat.elapsed();
             if elapsed_since_heartbeat > Duration::from_secs(60) {
                 warn!(
-                    "reader ({}) of shard ({}) heartbeat call took {}s",
-                    reader_id,
+                    "readers of shard ({}) heartbeat call took {}s",
                     machine.shard_id(),
                     elapsed_since_heartbeat.as_secs_f64(),
                 );
             }
 
-            if !existed {
-                // If the read handle was intentionally expired, this task
-                // *should* be aborted before it observes the expiration. So if
-                // we get here, this task somehow failed to keep the read lease
-                // alive. Warn loudly, because there's now a live read handle to
-                // an expired shard that will panic if used, but don't panic,
-                // just in case there is some edge case that results in this
-                // task observing the intentional expiration of a read handle.
-                warn!(
-                    "heartbeat task for reader ({}) of shard ({}) exiting due to expired lease \
-                     while read handle is live",
-                    reader_id,
-                    machine.shard_id(),
-                );
-                return;
+            let registered = heartbeats.lock().expect("lock poisoned");
+            for reader_id in missing {
+                // If the read handle was intentionally expired, it
+                // deregisters before expiring its lease. So if a reader is
+                // still registered here, its lease somehow failed to be kept
+                // alive. Warn loudly, because there's now a live read handle
+                // to an expired shard that will panic if used, but don't
+                // panic, just in case there is some edge case that results in
+                // this task observing the intentional expiration of a read
+                // handle. Either way, stop heartbeating the reader.
+                if registered.readers.contains_key(&reader_id) {
+                    warn!(
+                        "heartbeat task for reader ({}) of shard ({}) stopping due to expired \
+                         lease while read handle is live",
+                        reader_id,
+                        machine.shard_id(),
+                    );
+                    expired.insert(reader_id);
+                }
             }
         }
     }
 }
 
+/// The leased readers of a shard whose leases are kept alive by the shard's
+/// shared heartbeat. See [Machine::register_reader_heartbeat].
+#[derive(Debug, Default)]
+pub(crate) struct ReaderHeartbeats {
+    /// The registered readers, with the durations of their leases.
+    pub(crate) readers: BTreeMap<LeasedReaderId, Duration>,
+    /// The heartbeat tasks, while any readers are registered.
+    pub(crate) tasks: Vec<AbortOnDropHandle<()>>,
+}
+
+impl ReaderHeartbeats {
+    /// The shortest lease of the registered readers, if any.
+    pub(crate) fn min_lease_duration(&self) -> Option<Duration> {
+        self.readers.values().min().copied()
+    }
+}
+
+/// A leased reader's registration with its shard's shared heartbeat.
+///
+/// Dropping the last registration of a shard stops the heartbeat.
+#[derive(Debug)]
+pub(crate) struct ReaderHeartbeatRegistration {
+    heartbeats: Arc<Mutex<ReaderHeartbeats>>,
+    reader_id: LeasedReaderId,
+}
+
+impl Drop for ReaderHeartbeatRegistration {
+    fn drop(&mut self) {
+        let mut heartbeats = self.heartbeats.lock().expect("lock poisoned");
+        heartbeats.readers.remove(&self.reader_id);
+        let tasks = if heartbeats.readers.is_empty() {
+            std::mem::take(&mut heartbeats.tasks)
+        } else {
+            Vec::new()
+        };
+        // Abort the tasks outside of the lock.
+        drop(heartbeats);
+        drop(tasks);
+    }
+}
+
 pub const INFO_MIN_ATTEMPTS: usize = 3;
 
-pub async fn retry_external<R, F, WorkFn>(metrics: &RetryMetrics, mut work_fn: WorkFn) -> R
+/// A group of external (blob or consensus) operations that share a
+/// dynamically configurable retry policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalRetryGroup {
+    /// Operations against [mz_persist::location::Consensus].
+    Consensus,
+    /// Operations that only read from [mz_persist::location::Blob].
+    BlobRead,
+    /// Operations that write to or delete from [mz_persist::location::Blob].
+    BlobWrite,
+}
+
+pub(crate) const CONSENSUS_RETRY_INITIAL_BACKOFF_MS: Config<usize> = Config::new(
+    "persist_consensus_retry_initial_backoff_ms",
+    4,
+    "The initial backoff (in milliseconds) when retrying a failed consensus operation.",
+);
+
+pub(crate) const CONSENSUS_RETRY_MULTIPLIER: Config<usize> = Config::new(
+    "persist_consensus_retry_multiplier",
+    2,
+    "The backoff multiplier when retrying a failed consensus operation.",
+);
+
+pub(crate) const CONSENSUS_RETRY_CLAMP_MS: Config<usize> = Config::new(
+    "persist_consensus_retry_clamp_ms",
+    16_000,
+    "The maximum backoff (in milliseconds) when retrying a failed consensus operation.",
+);
+
+pub(crate) const CONSENSUS_RETRY_MAX_ATTEMPTS: Config<usize> = Config::new(
+    "persist_consensus_retry_max_attempts",
+    0,
+    "The maximum number of attempts for a consensus operation whose caller can \
+    surface an error, or 0 to retry indefinitely.",
+);
+
+pub(crate) const BLOB_READ_RETRY_INITIAL_BACKOFF_MS: Config<usize> = Config::new(
+    "persist_blob_read_retry_initial_backoff_ms",
+    4,
+    "The initial backoff (in milliseconds) when retrying a failed blob read.",
+);
+
+pub(crate) const BLOB_READ_RETRY_MULTIPLIER: Config<usize> = Config::new(
+    "persist_blob_read_retry_multiplier",
+    2,
+    "The backoff multiplier when retrying a failed blob read.",
+);
+
+pub(crate) const BLOB_READ_RETRY_CLAMP_MS: Config<usize> = Config::new(
+    "persist_blob_read_retry_clamp_ms",
+    16_000,
+    "The maximum backoff (in milliseconds) when retrying a failed blob read.",
+);
+
+pub(crate) const BLOB_READ_RETRY_MAX_ATTEMPTS: Config<usize> = Config::new(
+    "persist_blob_read_retry_max_attempts",
+    0,
+    "The maximum number of attempts for a blob read whose caller can surface an \
+    error, or 0 to retry indefinitely.",
+);
+
+pub(crate) const BLOB_WRITE_RETRY_INITIAL_BACKOFF_MS: Config<usize> = Config::new(
+    "persist_blob_write_retry_initial_backoff_ms",
+    4,
+    "The initial backoff (in milliseconds) when retrying a failed blob write or delete.",
+);
+
+pub(crate) const BLOB_WRITE_RETRY_MULTIPLIER: Config<usize> = Config::new(
+    "persist_blob_write_retry_multiplier",
+    2,
+    "The backoff multiplier when retrying a failed blob write or delete.",
+);
+
+pub(crate) const BLOB_WRITE_RETRY_CLAMP_MS: Config<usize> = Config::new(
+    "persist_blob_write_retry_clamp_ms",
+    16_000,
+    "The maximum backoff (in milliseconds) when retrying a failed blob write or delete.",
+);
+
+pub(crate) const BLOB_WRITE_RETRY_MAX_ATTEMPTS: Config<usize> = Config::new(
+    "persist_blob_write_retry_max_attempts",
+    0,
+    "The maximum number of attempts for a blob write or delete whose caller can \
+    surface an error, or 0 to retry indefinitely.",
+);
+
+impl ExternalRetryGroup {
+    /// Returns the retry parameters currently configured for this group, along
+    /// with the maximum number of attempts, if bounded.
+    ///
+    /// The configs are read on every call, so changes take effect for any
+    /// operation started afterward.
+    pub(crate) fn params(&self, configs: &ConfigSet) -> (RetryParameters, Option<usize>) {
+        let (initial_backoff_ms, multiplier, clamp_ms, max_attempts) = match self {
+            ExternalRetryGroup::Consensus => (
+                &CONSENSUS_RETRY_INITIAL_BACKOFF_MS,
+                &CONSENSUS_RETRY_MULTIPLIER,
+                &CONSENSUS_RETRY_CLAMP_MS,
+                &CONSENSUS_RETRY_MAX_ATTEMPTS,
+            ),
+            ExternalRetryGroup::BlobRead => (
+                &BLOB_READ_RETRY_INITIAL_BACKOFF_MS,
+                &BLOB_READ_RETRY_MULTIPLIER,
+                &BLOB_READ_RETRY_CLAMP_MS,
+                &BLOB_READ_RETRY_MAX_ATTEMPTS,
+            ),
+            ExternalRetryGroup::BlobWrite => (
+                &BLOB_WRITE_RETRY_INITIAL_BACKOFF_MS,
+                &BLOB_WRITE_RETRY_MULTIPLIER,
+                &BLOB_WRITE_RETRY_CLAMP_MS,
+                &BLOB_WRITE_RETRY_MAX_ATTEMPTS,
+            ),
+        };
+        let params = RetryParameters {
+            initial_backoff: Duration::from_millis(u64::cast_from(initial_backoff_ms.get(configs))),
+            multiplier: u32::try_from(multiplier.get(configs)).unwrap_or(u32::MAX),
+            clamp: Duration::from_millis(u64::cast_from(clamp_ms.get(configs))),
+        };
+        let max_attempts = match max_attempts.get(configs) {
+            0 => None,
+            x => Some(x),
+        };
+        (params, max_attempts)
+    }
+}
+
+/// Retries the given external operation until it succeeds, backing off
+/// according to the retry parameters configured for its
+/// [ExternalRetryGroup].
+///
+/// Any configured maximum number of attempts is ignored, because there is no
+/// way to surface the error. Use [retry_external_bounded] for callers that can
+/// handle one.
+pub async fn retry_external<R, F, WorkFn>(metrics: &RetryMetrics, work_fn: WorkFn) -> R
+where
+    F: std::future::Future<Output = Result<R, ExternalError>>,
+    WorkFn: FnMut() -> F,
+{
+    match retry_external_inner(metrics, false, work_fn).await {
+        Ok(x) => x,
+        Err(err) => unreachable!("unbounded retries returned an error: {}", err),
+    }
+}
+
+/// Like [retry_external], but gives up once the maximum number of attempts
+/// configured for the operation's [ExternalRetryGroup] (if any) is reached,
+/// returning the error from the last attempt.
+pub async fn retry_external_bounded<R, F, WorkFn>(
+    metrics: &RetryMetrics,
+    work_fn: WorkFn,
+) -> Result<R, ExternalError>
+where
+    F: std::future::Future<Output = Result<R, ExternalError>>,
+    WorkFn: FnMut() -> F,
+{
+    retry_external_inner(metrics, true, work_fn).await
+}
+
+async fn retry_external_inner<R, F, WorkFn>(
+    metrics: &RetryMetrics,
+    bounded: bool,
+    mut work_fn: WorkFn,
+) -> Result<R, ExternalError>
 where
     F: std::future::Future<Output = Result<R, ExternalError>>,
     WorkFn: FnMut() -> F,
 {
-    let mut retry = metrics.stream(Retry::persist_defaults(SystemTime::now()).into_retry_stream());
+    let (retry, max_attempts) = metrics.external_retry(SystemTime::now());
+    let max_attempts = if bounded { max_attempts } else { None };
+    let mut retry = metrics.stream(retry.into_retry_stream());
     loop {
         match work_fn().await {
             Ok(x) => {
@@ -1123,9 +1577,18 @@ where
                         metrics.name,
                     );
                 }
-                return x;
+                return Ok(x);
             }
             Err(err) => {
+                if max_attempts.map_or(false, |max| retry.attempt() + 1 >= max) {
+                    warn!(
+                        "external operation {} failed, giving up after {} attempts: {}",
+                        metrics.name,
+                        retry.attempt() + 1,
+                        err.display_with_causes()
+                    );
+                    return Err(err);
+                }
                 if retry.attempt() >= INFO_MIN_ATTEMPTS {
                     info!(
                         "external operation {} failed, retrying in {:?}: {}",
@@ -1155,7 +1618,8 @@ where
     F: std::future::Future<Output = Result<R, ExternalError>>,
     WorkFn: FnMut() -> F,
 {
-    let mut retry = metrics.stream(Retry::persist_defaults(SystemTime::now()).into_retry_stream());
+    let (retry, _max_attempts) = metrics.external_retry(SystemTime::now());
+    let mut retry = metrics.stream(retry.into_retry_stream());
     loop {
         match work_fn().await {
             Ok(x) => {
@@ -1247,12 +1711,7 @@ pub mod datadriven {
             client
                 .cfg
                 .set_config(&STREAMING_SNAPSHOT_AND_FETCH_ENABLED, true);
-            let state_versions = Arc::new(StateVersions::new(
-                client.cfg.clone(),
-                Arc::clone(&client.consensus),
-                Arc::clone(&client.blob),
-                Arc::clone(&client.metrics),
-            ));
+            let state_versions = Arc::new(client.state_versions());
             let machine = Machine::new(
                 client.cfg.clone(),
                 shard_id,
@@ -1262,6 +1721,7 @@ pub mod datadriven {
                 Arc::new(NoopPubSubSender),
                 Arc::clone(&client.isolated_runtime),
                 Diagnostics::for_tests(),
+                None,
             )
             .await
             .expect("codecs should match");
@@ -1567,6 +2027,7 @@ pub mod datadriven {
                 &datadriven.client.metrics.read.batch_fetcher,
                 &part.key,
                 &batch.desc,
+                part.checksum,
             )
             .await
             .expect("invalid batch part");
@@ -1676,6 +2137,7 @@ pub mod datadriven {
             Arc::clone(&datadriven.client.isolated_runtime),
             req,
             schemas,
+            None,
         )
         .await?;
 
@@ -1791,6 +2253,7 @@ pub mod datadriven {
                         &datadriven.client.metrics.read.batch_fetcher,
                         &part.key,
                         &batch.desc,
+                        part.checksum,
                     )
                     .await
                     .expect("invalid batch part");
@@ -2002,7 +2465,10 @@ pub mod datadriven {
         datadriven: &mut MachineState,
         _args: DirectiveArgs<'_>,
     ) -> anyhow::Result<String> {
-        let maintenance = datadriven.machine.become_tombstone().await?;
+        let maintenance = datadriven
+            .machine
+            .become_tombstone(FinalizeFrontiers::RequireEmpty)
+            .await?;
         datadriven.routine.push(maintenance);
         Ok(format!("{} ok\n", datadriven.machine.seqno()))
     }
@@ -2099,19 +2565,33 @@ pub mod datadriven {
 
 #[cfg(test)]
 pub mod tests {
+    use std::cell::Cell;
+    use std::collections::BTreeSet;
     use std::sync::Arc;
+    use std::time::Duration;
 
     use crate::cache::StateCache;
     use mz_ore::cast::CastFrom;
+    use mz_ore::metrics::MetricsRegistry;
     use mz_ore::task::spawn;
     use mz_persist::intercept::{InterceptBlob, InterceptHandle};
-    use mz_persist::location::SeqNo;
+    use mz_persist::location::{Blob, ExternalError, SeqNo};
+    use mz_persist::mem::{MemBlob, MemBlobConfig, MemConsensus};
+    use mz_persist::unreliable::{UnreliableBlob, UnreliableConsensus, UnreliableHandle};
     use timely::progress::Antichain;
 
-    use crate::internal::gc::{GarbageCollector, GcReq};
+    use crate::async_runtime::IsolatedRuntime;
+    use crate::internal::gc::{GarbageCollector, GcReq, GC_BLOB_DELETE_MAX_PER_RUN};
+    use crate::internal::machine::{
+        retry_external_bounded, BLOB_READ_RETRY_CLAMP_MS, BLOB_READ_RETRY_INITIAL_BACKOFF_MS,
+        BLOB_READ_RETRY_MAX_ATTEMPTS,
+    };
+    use crate::internal::metrics::Metrics;
     use crate::internal::state::HandleDebugState;
+    use crate::internal::watchdog::{SLOW_OP_THRESHOLDS, SLOW_OP_WARN_INTERVAL_MS};
+    use crate::rpc::NoopPubSubSender;
     use crate::tests::new_test_client;
-    use crate::ShardId;
+    use crate::{PersistClient, PersistConfig, ShardId};
 
     #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
     #[cfg_attr(miri, ignore)] // error: unsupported operation: integer-to-pointer casts and `ptr::from_exposed_addr` are not supported with `-Zmiri-strict-provenance`
@@ -2216,6 +2696,99 @@ pub mod tests {
         let _ = GarbageCollector::gc_and_truncate(&mut read.machine, req.clone()).await;
     }
 
+    #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
+    #[cfg_attr(miri, ignore)] // error: unsupported operation: integer-to-pointer casts and `ptr::from_exposed_addr` are not supported with `-Zmiri-strict-provenance`
+    async fn gc_blob_delete_max_per_run() {
+        const NUM_BATCHES: u64 = 20;
+        const MAX_PER_RUN: usize = 3;
+
+        let client = new_test_client().await;
+        // set a low rollup threshold so there are many truncation points
+        client.cfg.dynamic.set_rollup_threshold(5);
+        let (mut write, mut read) = client
+            .expect_open::<String, (), u64, i64>(ShardId::new())
+            .await;
+
+        // The reader holds back the seqno_since while we write a bunch of
+        // small batches, so the blobs made unreachable by compaction and new
+        // rollups pile up.
+        for idx in 0..NUM_BATCHES {
+            let batch = write
+                .expect_batch(&[((idx.to_string(), ()), idx, 1)], idx, idx + 1)
+                .await;
+            let (_, writer_maintenance) = write
+                .machine
+                .compare_and_append(
+                    &batch.into_hollow_batch(),
+                    &write.writer_id,
+                    &HandleDebugState::default(),
+                    (write.cfg.now)(),
+                )
+                .await
+                .expect("invalid usage")
+                .expect("unexpected upper");
+            writer_maintenance
+                .perform(&write.machine, &write.gc, write.compact.as_ref())
+                .await;
+        }
+
+        // Release the seqno hold all at once, without running the resulting
+        // maintenance, so that we drive GC by hand below.
+        let (_, _, _maintenance) = read
+            .machine
+            .downgrade_since(
+                &read.reader_id,
+                None,
+                &Antichain::from_elem(0),
+                (client.cfg.now)(),
+            )
+            .await;
+
+        client
+            .cfg
+            .set_config(&GC_BLOB_DELETE_MAX_PER_RUN, MAX_PER_RUN);
+        let req = GcReq {
+            shard_id: read.machine.shard_id(),
+            new_seqno_since: read.machine.applier.seqno_since(),
+        };
+        let mut deleted_before_truncate = BTreeSet::new();
+        let mut runs = 0;
+        let mut total_deleted = 0;
+        loop {
+            let (maintenance, results) = GarbageCollector::gc_and_truncate_resumable(
+                &mut read.machine,
+                req.clone(),
+                &mut deleted_before_truncate,
+            )
+            .await;
+            let deleted = results.batch_parts_deleted_from_blob + results.rollups_deleted_from_blob;
+            assert!(deleted <= MAX_PER_RUN, "{} vs {}", deleted, MAX_PER_RUN);
+            runs += 1;
+            total_deleted += deleted;
+            if results.blob_deletes_deferred == 0 {
+                break;
+            }
+            // A capped run always asks for a follow-up.
+            assert!(maintenance.garbage_collection.is_some());
+            assert!(runs < 1000, "gc made no progress");
+        }
+        assert!(
+            total_deleted > MAX_PER_RUN,
+            "expected gc to need multiple runs: deleted {} in {} runs",
+            total_deleted,
+            runs
+        );
+
+        // Running GC again has nothing left to do.
+        let (_, results) = GarbageCollector::gc_and_truncate(&mut read.machine, req).await;
+        assert_eq!(results.batch_parts_deleted_from_blob, 0);
+        assert_eq!(results.rollups_deleted_from_blob, 0);
+
+        // And we didn't delete anything that's still needed.
+        let snapshot = read.expect_snapshot_and_fetch(NUM_BATCHES - 1).await;
+        assert_eq!(snapshot.len(), usize::cast_from(NUM_BATCHES));
+    }
+
     // A regression test for #20776, where a bug meant that compare_and_append
     // would not fetch the latest state after an upper mismatch. This meant that
     // a write that could succeed if retried on the latest state would instead
@@ -2250,4 +2823,107 @@ pub mod tests {
         // state after an upper mismatch then this call would (incorrectly) fail
         write2.expect_compare_and_append(&data[1..2], 2, 3).await;
     }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // error: unsupported operation: integer-to-pointer casts and `ptr::from_exposed_addr` are not supported with `-Zmiri-strict-provenance`
+    async fn retry_external_max_attempts() {
+        let cfg = PersistConfig::new_for_tests();
+        let metrics = Metrics::new(&cfg, &MetricsRegistry::new());
+        let retry_metrics = &metrics.retries.external.fetch_batch_get;
+        let handle = UnreliableHandle::default();
+        let blob = UnreliableBlob::new(
+            Arc::new(MemBlob::open(MemBlobConfig::default())),
+            handle.clone(),
+        );
+        // Don't bother sleeping between attempts.
+        cfg.set_config(&BLOB_READ_RETRY_INITIAL_BACKOFF_MS, 0);
+        cfg.set_config(&BLOB_READ_RETRY_CLAMP_MS, 0);
+
+        let attempts = Cell::new(0);
+        let get = || {
+            attempts.set(attempts.get() + 1);
+            blob.get("key")
+        };
+
+        // Every call fails with a determinate error. Once the configured
+        // number of attempts is exhausted, the last error is surfaced as-is.
+        handle.partially_available(0.0, 0.0);
+        cfg.set_config(&BLOB_READ_RETRY_MAX_ATTEMPTS, 3);
+        let res = retry_external_bounded(retry_metrics, get).await;
+        assert!(matches!(res, Err(ExternalError::Determinate(_))));
+        assert_eq!(attempts.replace(0), 3);
+        assert_eq!(retry_metrics.retries.get(), 2);
+
+        // Changing the config takes effect for the next operation.
+        cfg.set_config(&BLOB_READ_RETRY_MAX_ATTEMPTS, 1);
+        let res = retry_external_bounded(retry_metrics, get).await;
+        assert!(matches!(res, Err(ExternalError::Determinate(_))));
+        assert_eq!(attempts.replace(0), 1);
+
+        // Indeterminate errors are surfaced as indeterminate.
+        handle.totally_unavailable();
+        let res = retry_external_bounded(retry_metrics, get).await;
+        assert!(matches!(res, Err(ExternalError::Indeterminate(_))));
+        assert_eq!(attempts.replace(0), 1);
+
+        // With no bound, the operation is retried until it succeeds.
+        cfg.set_config(&BLOB_READ_RETRY_MAX_ATTEMPTS, 0);
+        handle.partially_available(0.5, 0.0);
+        let res = retry_external_bounded(retry_metrics, get).await;
+        assert!(matches!(res, Ok(None)));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn slow_op_watchdog() {
+        let data = vec![
+            (("0".to_owned(), "zero".to_owned()), 0, 1),
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("3".to_owned(), "three".to_owned()), 3, 1),
+        ];
+
+        let cfg = PersistConfig::new_for_tests();
+        cfg.set_config(&SLOW_OP_THRESHOLDS, "compare_and_append@test=50".to_owned());
+        // Long enough that all of the slow writes below fall in one window.
+        cfg.set_config(&SLOW_OP_WARN_INTERVAL_MS, 60 * 60 * 1000);
+        let handle = UnreliableHandle::default();
+        handle.totally_available();
+        let consensus = Arc::new(UnreliableConsensus::new(
+            Arc::new(MemConsensus::default()),
+            handle.clone(),
+        ));
+        let metrics = Arc::new(Metrics::new(&cfg, &MetricsRegistry::new()));
+        let client = PersistClient::new(
+            cfg,
+            Arc::new(MemBlob::open(MemBlobConfig::default())),
+            consensus,
+            Arc::clone(&metrics),
+            Arc::new(IsolatedRuntime::new()),
+            Arc::new(StateCache::new_no_metrics()),
+            Arc::new(NoopPubSubSender),
+        )
+        .expect("client construction failed");
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        let cmd = &metrics.cmds.compare_and_append;
+
+        // Fast operations don't trip the watchdog.
+        write.expect_compare_and_append(&data[0..1], 0, 1).await;
+        assert_eq!(cmd.slow.get(), 0);
+        assert_eq!(cmd.slow_warned.get(), 0);
+
+        // Every slow operation is counted, but only the first one within the
+        // rate-limit window logs a warning.
+        handle.set_latency(Duration::from_millis(100));
+        write.expect_compare_and_append(&data[1..2], 1, 2).await;
+        write.expect_compare_and_append(&data[2..3], 2, 3).await;
+        write.expect_compare_and_append(&data[3..4], 3, 4).await;
+        assert_eq!(cmd.slow.get(), 3);
+        assert_eq!(cmd.slow_warned.get(), 1);
+
+        // Operations without a configured threshold are never slow.
+        assert_eq!(metrics.cmds.register.slow.get(), 0);
+    }
 }
@@ -22,6 +22,7 @@ use futures_util::FutureExt;
 use mz_persist::location::SeqNo;
 use mz_persist_types::{Codec, Codec64};
 use timely::progress::Timestamp;
+use tracing::warn;
 
 use crate::internal::compact::CompactReq;
 use crate::internal::gc::GcReq;
@@ -120,7 +121,7 @@ impl RoutineMaintenance {
             let isolated_runtime = Arc::clone(&machine.isolated_runtime);
             futures.push(
                 isolated_runtime
-                    .spawn_named(|| "persist::write_rollup", async move {
+                    .spawn_catching("persist::write_rollup", async move {
                         machine
                             .applier
                             .fetch_and_update_state(Some(rollup_seqno))
@@ -135,7 +136,14 @@ impl RoutineMaintenance {
                         );
                         machine.add_rollup_for_current_seqno().await
                     })
-                    .map(Result::unwrap_or_default)
+                    // Rollups are written with a compare-and-set, so a panic
+                    // leaves state untouched and the next maintenance retries.
+                    .map(|res| {
+                        res.unwrap_or_else(|err| {
+                            warn!("failed to write rollup: {}", err);
+                            RoutineMaintenance::default()
+                        })
+                    })
                     .boxed(),
             );
         }
@@ -12,7 +12,7 @@
 use async_stream::stream;
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex, Weak};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -31,17 +31,24 @@ use mz_persist::location::{
     VersionedData,
 };
 use mz_persist::metrics::S3BlobMetrics;
-use mz_persist::retry::RetryStream;
+use mz_persist::retry::{Retry, RetryStream};
 use mz_persist_types::Codec64;
 use mz_postgres_client::metrics::PostgresClientMetrics;
 use prometheus::core::{AtomicI64, AtomicU64, Collector, Desc, GenericGauge};
 use prometheus::proto::MetricFamily;
 use prometheus::{CounterVec, Gauge, GaugeVec, Histogram, HistogramVec, IntCounterVec};
+use timely::order::{Product, TotalOrder};
 use timely::progress::Antichain;
 use tokio_metrics::TaskMonitor;
 use tracing::{error, instrument};
 
+use crate::dyn_cfg::ConfigSet;
+use crate::health::{HealthWindow, HealthWindows, PersistHealth};
+use crate::internal::machine::ExternalRetryGroup;
 use crate::internal::paths::BlobKey;
+use crate::internal::watchdog::SlowOpWatchdog;
+use crate::iter::ConsolidationStats;
+use crate::usage::{ShardActivityWindow, ShardOp};
 use crate::{PersistConfig, ShardId};
 
 /// Prometheus monitoring metrics.
@@ -50,6 +57,7 @@ use crate::{PersistConfig, ShardId};
 /// Arc.
 pub struct Metrics {
     _vecs: MetricsVecs,
+    health: Arc<HealthWindows>,
     _uptime: ComputedGauge,
 
     /// Metrics for [Blob] usage.
@@ -71,6 +79,10 @@ pub struct Metrics {
     pub gc: GcMetrics,
     /// Metrics for leasing and automatic lease expiry.
     pub lease: LeaseMetrics,
+    /// Metrics for coalescing appends.
+    pub coalesce: CoalesceMetrics,
+    /// Metrics for the isolated runtime.
+    pub isolated_runtime: IsolatedRuntimeMetrics,
     /// Metrics for various encodings and decodings.
     pub codecs: CodecsMetrics,
     /// Metrics for (incremental) state updates and fetches.
@@ -97,6 +109,9 @@ pub struct Metrics {
     /// Metrics for the persist sink.
     pub sink: SinkMetrics,
 
+    /// Metrics for the persist source.
+    pub source: SourceMetrics,
+
     /// Metrics for S3-backed blob implementation
     pub s3_blob: S3BlobMetrics,
     /// Metrics for Postgres-backed consensus implementation
@@ -129,13 +144,15 @@ impl Metrics {
             blob: vecs.blob_metrics(),
             consensus: vecs.consensus_metrics(),
             cmds: vecs.cmds_metrics(registry),
-            retries: vecs.retries_metrics(),
+            retries: vecs.retries_metrics(&cfg.configs),
             codecs: vecs.codecs_metrics(),
             user: BatchWriteMetrics::new(registry, "user"),
             read: vecs.batch_part_read_metrics(),
             compaction: CompactionMetrics::new(registry),
             gc: GcMetrics::new(registry),
             lease: LeaseMetrics::new(registry),
+            coalesce: CoalesceMetrics::new(registry),
+            isolated_runtime: IsolatedRuntimeMetrics::new(registry),
             state: StateMetrics::new(registry),
             shards: ShardsMetrics::new(registry),
             audit: UsageAuditMetrics::new(registry),
@@ -147,8 +164,10 @@ impl Metrics {
             blob_cache_mem: BlobMemCache::new(registry),
             tasks: TasksMetrics::new(registry),
             sink: SinkMetrics::new(registry),
+            source: SourceMetrics::new(registry),
             s3_blob: S3BlobMetrics::new(registry),
             postgres_consensus: PostgresClientMetrics::new(registry, "mz_persist"),
+            health: Arc::clone(&vecs.health),
             _vecs: vecs,
             _uptime: uptime,
         }
@@ -168,6 +187,17 @@ impl Metrics {
             total_written as f64 / user_written as f64
         }
     }
+
+    /// Returns a summary of the recent health of the blob and consensus
+    /// locations, as observed by this process.
+    pub fn health(&self) -> PersistHealth {
+        PersistHealth {
+            window_secs: HealthWindows::window_secs(),
+            ops: self.health.snapshot(),
+            pubsub_connected: self.pubsub_client.grpc_connection.connected.get() > 0,
+            shards_with_failed_maintenance: self.shards.maintenance_failing_count(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -177,6 +207,8 @@ struct MetricsVecs {
     cmd_succeeded: IntCounterVec,
     cmd_failed: IntCounterVec,
     cmd_seconds: CounterVec,
+    cmd_slow: IntCounterVec,
+    cmd_slow_warned: IntCounterVec,
 
     external_op_started: IntCounterVec,
     external_op_succeeded: IntCounterVec,
@@ -203,6 +235,7 @@ struct MetricsVecs {
     read_part_goodbytes: IntCounterVec,
     read_part_count: IntCounterVec,
     read_part_seconds: CounterVec,
+    read_part_checksum_mismatches: IntCounterVec,
 
     lock_acquire_count: IntCounterVec,
     lock_blocking_acquire_count: IntCounterVec,
@@ -210,6 +243,8 @@ struct MetricsVecs {
 
     /// A minimal set of metrics imported into honeycomb for alerting.
     alerts_metrics: Arc<AlertsMetrics>,
+    /// Sliding windows of external op outcomes, for [Metrics::health].
+    health: Arc<HealthWindows>,
 }
 
 impl MetricsVecs {
@@ -240,6 +275,16 @@ impl MetricsVecs {
                 help: "time spent applying commands",
                 var_labels: ["cmd"],
             )),
+            cmd_slow: registry.register(metric!(
+                name: "mz_persist_cmd_slow_count",
+                help: "count of commands that exceeded their slow operation threshold",
+                var_labels: ["cmd"],
+            )),
+            cmd_slow_warned: registry.register(metric!(
+                name: "mz_persist_cmd_slow_warned_count",
+                help: "count of (rate limited) slow operation warnings logged for commands",
+                var_labels: ["cmd"],
+            )),
 
             external_op_started: registry.register(metric!(
                 name: "mz_persist_external_started_count",
@@ -355,6 +400,11 @@ impl MetricsVecs {
                 help: "time spent reading batch parts",
                 var_labels: ["op"],
             )),
+            read_part_checksum_mismatches: registry.register(metric!(
+                name: "mz_persist_read_batch_part_checksum_mismatches",
+                help: "count of batch parts read whose checksum didn't match",
+                var_labels: ["op"],
+            )),
 
             lock_acquire_count: registry.register(metric!(
                 name: "mz_persist_lock_acquire_count",
@@ -373,6 +423,7 @@ impl MetricsVecs {
             )),
 
             alerts_metrics: Arc::new(AlertsMetrics::new(registry)),
+            health: Arc::new(HealthWindows::default()),
         }
     }
 
@@ -394,6 +445,8 @@ impl MetricsVecs {
             expire_writer: self.cmd_metrics("expire_writer"),
             merge_res: self.cmd_metrics("merge_res"),
             become_tombstone: self.cmd_metrics("become_tombstone"),
+            set_tuning: self.cmd_metrics("set_tuning"),
+            restore: self.cmd_metrics("restore"),
         }
     }
 
@@ -405,28 +458,56 @@ impl MetricsVecs {
             cas_mismatch: self.cmd_cas_mismatch.with_label_values(&[cmd]),
             failed: self.cmd_failed.with_label_values(&[cmd]),
             seconds: self.cmd_seconds.with_label_values(&[cmd]),
+            slow: self.cmd_slow.with_label_values(&[cmd]),
+            slow_warned: self.cmd_slow_warned.with_label_values(&[cmd]),
         }
     }
 
-    fn retries_metrics(&self) -> RetriesMetrics {
+    fn retries_metrics(&self, configs: &ConfigSet) -> RetriesMetrics {
+        let external = |name: &str, group: ExternalRetryGroup| {
+            let mut metrics = self.retry_metrics(name);
+            metrics.external = Some((group, configs.clone()));
+            metrics
+        };
         RetriesMetrics {
             determinate: RetryDeterminate {
-                apply_unbatched_cmd_cas: self.retry_metrics("apply_unbatched_cmd::cas"),
+                apply_unbatched_cmd_cas: external(
+                    "apply_unbatched_cmd::cas",
+                    ExternalRetryGroup::Consensus,
+                ),
             },
             external: RetryExternal {
-                batch_delete: self.retry_metrics("batch::delete"),
-                batch_set: self.retry_metrics("batch::set"),
-                blob_open: self.retry_metrics("blob::open"),
-                compaction_noop_delete: self.retry_metrics("compaction_noop::delete"),
-                consensus_open: self.retry_metrics("consensus::open"),
-                fetch_batch_get: self.retry_metrics("fetch_batch::get"),
-                fetch_state_scan: self.retry_metrics("fetch_state::scan"),
-                gc_truncate: self.retry_metrics("gc::truncate"),
-                maybe_init_cas: self.retry_metrics("maybe_init::cas"),
-                rollup_delete: self.retry_metrics("rollup::delete"),
-                rollup_get: self.retry_metrics("rollup::get"),
-                rollup_set: self.retry_metrics("rollup::set"),
-                storage_usage_shard_size: self.retry_metrics("storage_usage::shard_size"),
+                batch_delete: external("batch::delete", ExternalRetryGroup::BlobWrite),
+                batch_set: external("batch::set", ExternalRetryGroup::BlobWrite),
+                blob_open: external("blob::open", ExternalRetryGroup::BlobRead),
+                compaction_noop_delete: external(
+                    "compaction_noop::delete",
+                    ExternalRetryGroup::BlobWrite,
+                ),
+                consensus_open: external("consensus::open", ExternalRetryGroup::Consensus),
+                fetch_batch_get: external("fetch_batch::get", ExternalRetryGroup::BlobRead),
+                fetch_state_scan: external("fetch_state::scan", ExternalRetryGroup::Consensus),
+                gc_truncate: external("gc::truncate", ExternalRetryGroup::Consensus),
+                maybe_init_cas: external("maybe_init::cas", ExternalRetryGroup::Consensus),
+                rollup_delete: external("rollup::delete", ExternalRetryGroup::BlobWrite),
+                rollup_get: external("rollup::get", ExternalRetryGroup::BlobRead),
+                rollup_set: external("rollup::set", ExternalRetryGroup::BlobWrite),
+                storage_usage_shard_size: external(
+                    "storage_usage::shard_size",
+                    ExternalRetryGroup::BlobRead,
+                ),
+                tombstone_purge_delete: external(
+                    "tombstone_purge::delete",
+                    ExternalRetryGroup::BlobWrite,
+                ),
+                tombstone_purge_list: external(
+                    "tombstone_purge::list",
+                    ExternalRetryGroup::BlobRead,
+                ),
+                tombstone_purge_consensus_delete: external(
+                    "tombstone_purge::consensus_delete",
+                    ExternalRetryGroup::Consensus,
+                ),
             },
             compare_and_append_idempotent: self.retry_metrics("compare_and_append_idempotent"),
             fetch_latest_state: self.retry_metrics("fetch_latest_state"),
@@ -444,6 +525,7 @@ impl MetricsVecs {
             finished: self.retry_finished.with_label_values(&[name]),
             retries: self.retry_retries.with_label_values(&[name]),
             sleep_seconds: self.retry_sleep_seconds.with_label_values(&[name]),
+            external: None,
         }
     }
 
@@ -487,6 +569,7 @@ impl MetricsVecs {
             scan: self.external_op_metrics("consensus_scan", false),
             truncate: self.external_op_metrics("consensus_truncate", false),
             truncated_count: self.external_consensus_truncated_count.clone(),
+            delete: self.external_op_metrics("consensus_delete", false),
             rtt_latency: self.external_rtt_latency.with_label_values(&["consensus"]),
         }
     }
@@ -504,6 +587,7 @@ impl MetricsVecs {
                 None
             },
             alerts_metrics: Arc::clone(&self.alerts_metrics),
+            health: self.health.window(op),
         }
     }
 
@@ -522,6 +606,7 @@ impl MetricsVecs {
             part_goodbytes: self.read_part_goodbytes.with_label_values(&[op]),
             part_count: self.read_part_count.with_label_values(&[op]),
             seconds: self.read_part_seconds.with_label_values(&[op]),
+            checksum_mismatches: self.read_part_checksum_mismatches.with_label_values(&[op]),
         }
     }
 
@@ -554,6 +639,8 @@ pub struct CmdMetrics {
     pub(crate) succeeded: IntCounter,
     pub(crate) failed: IntCounter,
     pub(crate) seconds: Counter,
+    pub(crate) slow: IntCounter,
+    pub(crate) slow_warned: IntCounter,
 }
 
 impl CmdMetrics {
@@ -596,6 +683,8 @@ pub struct CmdsMetrics {
     pub(crate) expire_writer: CmdMetrics,
     pub(crate) merge_res: CmdMetrics,
     pub(crate) become_tombstone: CmdMetrics,
+    pub(crate) set_tuning: CmdMetrics,
+    pub(crate) restore: CmdMetrics,
 }
 
 #[derive(Debug)]
@@ -605,12 +694,30 @@ pub struct RetryMetrics {
     pub(crate) finished: IntCounter,
     pub(crate) retries: IntCounter,
     pub(crate) sleep_seconds: Counter,
+    /// For operations retried by [crate::internal::machine::retry_external],
+    /// the group whose (dynamically configurable) retry parameters apply.
+    pub(crate) external: Option<(ExternalRetryGroup, ConfigSet)>,
 }
 
 impl RetryMetrics {
     pub(crate) fn stream(&self, retry: RetryStream) -> MetricsRetryStream {
         MetricsRetryStream::new(retry, self)
     }
+
+    /// Returns the backoff and the maximum number of attempts (if bounded)
+    /// currently configured for this external operation.
+    ///
+    /// Operations that don't belong to an [ExternalRetryGroup] use the persist
+    /// defaults and are retried indefinitely.
+    pub(crate) fn external_retry(&self, now: SystemTime) -> (Retry, Option<usize>) {
+        match &self.external {
+            Some((group, configs)) => {
+                let (params, max_attempts) = group.params(configs);
+                (params.into_retry(now), max_attempts)
+            }
+            None => (Retry::persist_defaults(now), None),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -633,6 +740,9 @@ pub struct RetryExternal {
     pub(crate) rollup_get: RetryMetrics,
     pub(crate) rollup_set: RetryMetrics,
     pub(crate) storage_usage_shard_size: RetryMetrics,
+    pub(crate) tombstone_purge_delete: RetryMetrics,
+    pub(crate) tombstone_purge_list: RetryMetrics,
+    pub(crate) tombstone_purge_consensus_delete: RetryMetrics,
 }
 
 #[derive(Debug)]
@@ -662,6 +772,7 @@ pub struct ReadMetrics {
     pub(crate) part_goodbytes: IntCounter,
     pub(crate) part_count: IntCounter,
     pub(crate) seconds: Counter,
+    pub(crate) checksum_mismatches: IntCounter,
 }
 
 // This one is Clone in contrast to the others because it has to get moved into
@@ -672,6 +783,8 @@ pub struct BatchWriteMetrics {
     pub(crate) goodbytes: IntCounter,
     pub(crate) seconds: Counter,
     pub(crate) write_stalls: IntCounter,
+    pub(crate) updates_in: IntCounter,
+    pub(crate) updates_written: IntCounter,
 
     pub(crate) step_consolidation: Counter,
     pub(crate) step_columnar_encoding: Counter,
@@ -701,6 +814,17 @@ impl BatchWriteMetrics {
                     name
                 ),
             )),
+            updates_in: registry.register(metric!(
+                name: format!("mz_persist_{}_updates_in", name),
+                help: format!("count of updates added to {} batch builders", name),
+            )),
+            updates_written: registry.register(metric!(
+                name: format!("mz_persist_{}_updates_written", name),
+                help: format!(
+                    "count of {} updates written to batch parts, after any consolidation",
+                    name
+                ),
+            )),
             step_consolidation: registry.register(metric!(
                 name: format!("mz_persist_{}_step_consolidation", name),
                 help: format!("time spent consolidating {} updates", name),
@@ -731,9 +855,12 @@ pub struct CompactionMetrics {
     pub(crate) timed_out: IntCounter,
     pub(crate) failed: IntCounter,
     pub(crate) noop: IntCounter,
+    pub(crate) cancelled: IntCounter,
+    pub(crate) cancelled_bytes_avoided: IntCounter,
     pub(crate) seconds: Counter,
     pub(crate) concurrency_waits: IntCounter,
     pub(crate) queued_seconds: Counter,
+    pub(crate) queue_timed_out: IntCounter,
     pub(crate) memory_violations: IntCounter,
     pub(crate) runs_compacted: IntCounter,
     pub(crate) chunks_compacted: IntCounter,
@@ -748,6 +875,8 @@ pub struct CompactionMetrics {
 
     pub(crate) batch: BatchWriteMetrics,
     pub(crate) steps: CompactionStepTimings,
+    pub(crate) maintenance: CompactionEfficacyMetrics,
+    pub(crate) forced: CompactionEfficacyMetrics,
 
     pub(crate) _steps_vec: CounterVec,
 }
@@ -759,6 +888,7 @@ impl CompactionMetrics {
                 help: "time spent on individual steps of compaction",
                 var_labels: ["step"],
         ));
+        let efficacy = CompactionEfficacyVecs::new(registry);
 
         CompactionMetrics {
             requested: registry.register(metric!(
@@ -793,6 +923,14 @@ impl CompactionMetrics {
                 name: "mz_persist_compaction_noop",
                 help: "count of compactions discarded (obsolete)",
             )),
+            cancelled: registry.register(metric!(
+                name: "mz_persist_compaction_cancelled",
+                help: "count of compactions abandoned in-flight because their output was no longer useful",
+            )),
+            cancelled_bytes_avoided: registry.register(metric!(
+                name: "mz_persist_compaction_cancelled_bytes_avoided",
+                help: "total encoded size of input parts that abandoned compactions did not fetch",
+            )),
             seconds: registry.register(metric!(
                 name: "mz_persist_compaction_seconds",
                 help: "time spent in compaction",
@@ -805,6 +943,10 @@ impl CompactionMetrics {
                 name: "mz_persist_compaction_queued_seconds",
                 help: "time that compaction requests spent queued",
             )),
+            queue_timed_out: registry.register(metric!(
+                name: "mz_persist_compaction_queue_timed_out",
+                help: "count of compaction requests dropped after timing out waiting on the concurrency limit",
+            )),
             memory_violations: registry.register(metric!(
                 name: "mz_persist_compaction_memory_violations",
                 help: "count of compaction memory requirement violations",
@@ -847,11 +989,133 @@ impl CompactionMetrics {
             )),
             batch: BatchWriteMetrics::new(registry, "compaction"),
             steps: CompactionStepTimings::new(step_timings.clone()),
+            maintenance: efficacy.get("maintenance"),
+            forced: efficacy.get("force"),
             _steps_vec: step_timings,
         }
     }
 }
 
+#[derive(Debug)]
+struct CompactionEfficacyVecs {
+    count: IntCounterVec,
+    seconds: CounterVec,
+    input_bytes: IntCounterVec,
+    output_bytes: IntCounterVec,
+    input_updates: IntCounterVec,
+    output_updates: IntCounterVec,
+    runs_merged: IntCounterVec,
+    write_amplification: CounterVec,
+}
+
+impl CompactionEfficacyVecs {
+    fn new(registry: &MetricsRegistry) -> Self {
+        CompactionEfficacyVecs {
+            count: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_count",
+                help: "count of compaction requests completed",
+                var_labels: ["source"],
+            )),
+            seconds: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_seconds",
+                help: "time spent on completed compaction requests",
+                var_labels: ["source"],
+            )),
+            input_bytes: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_input_bytes",
+                help: "total encoded size of the inputs to completed compaction requests",
+                var_labels: ["source"],
+            )),
+            output_bytes: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_output_bytes",
+                help: "total encoded size of the outputs of completed compaction requests",
+                var_labels: ["source"],
+            )),
+            input_updates: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_input_updates",
+                help: "count of updates in the inputs to completed compaction requests",
+                var_labels: ["source"],
+            )),
+            output_updates: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_output_updates",
+                help: "count of updates in the outputs of completed compaction requests",
+                var_labels: ["source"],
+            )),
+            runs_merged: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_runs_merged",
+                help: "count of runs merged by completed compaction requests",
+                var_labels: ["source"],
+            )),
+            write_amplification: registry.register(metric!(
+                name: "mz_persist_compaction_efficacy_write_amplification",
+                help: "sum over completed compaction requests of output bytes divided by input bytes",
+                var_labels: ["source"],
+            )),
+        }
+    }
+
+    fn get(&self, source: &str) -> CompactionEfficacyMetrics {
+        CompactionEfficacyMetrics {
+            count: self.count.with_label_values(&[source]),
+            seconds: self.seconds.with_label_values(&[source]),
+            input_bytes: self.input_bytes.with_label_values(&[source]),
+            output_bytes: self.output_bytes.with_label_values(&[source]),
+            input_updates: self.input_updates.with_label_values(&[source]),
+            output_updates: self.output_updates.with_label_values(&[source]),
+            runs_merged: self.runs_merged.with_label_values(&[source]),
+            write_amplification: self.write_amplification.with_label_values(&[source]),
+        }
+    }
+}
+
+/// Metrics describing how much work completed compaction requests did and how
+/// much they shrank their inputs, for a single source of requests (maintenance
+/// or force compaction).
+#[derive(Debug)]
+pub struct CompactionEfficacyMetrics {
+    pub(crate) count: IntCounter,
+    pub(crate) seconds: Counter,
+    pub(crate) input_bytes: IntCounter,
+    pub(crate) output_bytes: IntCounter,
+    pub(crate) input_updates: IntCounter,
+    pub(crate) output_updates: IntCounter,
+    pub(crate) runs_merged: IntCounter,
+    pub(crate) write_amplification: Counter,
+}
+
+impl CompactionEfficacyMetrics {
+    /// Records a completed compaction request with the given input and output
+    /// stats.
+    pub(crate) fn observe(
+        &self,
+        input: CompactionEfficacyStats,
+        output: CompactionEfficacyStats,
+        elapsed: Duration,
+    ) {
+        self.count.inc();
+        self.seconds.inc_by(elapsed.as_secs_f64());
+        self.input_bytes.inc_by(u64::cast_from(input.bytes));
+        self.output_bytes.inc_by(u64::cast_from(output.bytes));
+        self.input_updates.inc_by(u64::cast_from(input.updates));
+        self.output_updates.inc_by(u64::cast_from(output.updates));
+        self.runs_merged.inc_by(u64::cast_from(input.runs));
+        // A request with no input bytes (e.g. one only advancing the since of
+        // empty batches) has no meaningful amplification.
+        if input.bytes > 0 {
+            self.write_amplification
+                .inc_by(f64::cast_lossy(output.bytes) / f64::cast_lossy(input.bytes));
+        }
+    }
+}
+
+/// The size of one side (inputs or output) of a compaction request.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CompactionEfficacyStats {
+    pub(crate) bytes: usize,
+    pub(crate) updates: usize,
+    pub(crate) runs: usize,
+}
+
 #[derive(Debug)]
 pub struct CompactionStepTimings {
     pub(crate) part_fetch_seconds: Counter,
@@ -874,6 +1138,8 @@ pub struct GcMetrics {
     pub(crate) finished: IntCounter,
     pub(crate) merged: IntCounter,
     pub(crate) seconds: Counter,
+    pub(crate) capped: IntCounter,
+    pub(crate) deferred_blob_deletes: IntCounter,
     pub(crate) steps: GcStepTimings,
 }
 
@@ -933,6 +1199,14 @@ impl GcMetrics {
                 name: "mz_persist_gc_seconds",
                 help: "time spent in garbage collections",
             )),
+            capped: registry.register(metric!(
+                name: "mz_persist_gc_capped",
+                help: "count of garbage collections stopped early by the per-run blob delete cap",
+            )),
+            deferred_blob_deletes: registry.register(metric!(
+                name: "mz_persist_gc_deferred_blob_deletes",
+                help: "count of blob deletes deferred to a later garbage collection by the per-run blob delete cap",
+            )),
             steps: GcStepTimings::new(step_timings),
         }
     }
@@ -942,6 +1216,7 @@ impl GcMetrics {
 pub struct LeaseMetrics {
     pub(crate) timeout_read: IntCounter,
     pub(crate) dropped_part: IntCounter,
+    pub(crate) leaked_part: IntCounter,
 }
 
 impl LeaseMetrics {
@@ -955,6 +1230,67 @@ impl LeaseMetrics {
                 name: "mz_persist_lease_dropped_part",
                 help: "count of LeasedBatchParts that were dropped without being politely returned",
             )),
+            leaked_part: registry.register(metric!(
+                name: "mz_persist_lease_leaked_part",
+                help: "count of dropped LeasedBatchParts whose lease could not be returned to the issuing ReadHandle",
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CoalesceMetrics {
+    pub(crate) appends: IntCounter,
+    pub(crate) coalesced: IntCounter,
+    pub(crate) flushes: IntCounter,
+}
+
+impl CoalesceMetrics {
+    fn new(registry: &MetricsRegistry) -> Self {
+        CoalesceMetrics {
+            appends: registry.register(metric!(
+                name: "mz_persist_coalesce_appends",
+                help: "count of appends submitted for coalescing",
+            )),
+            coalesced: registry.register(metric!(
+                name: "mz_persist_coalesce_coalesced_appends",
+                help: "count of appends merged into another append's compare_and_append",
+            )),
+            flushes: registry.register(metric!(
+                name: "mz_persist_coalesce_flushes",
+                help: "count of compare_and_appends issued for (possibly merged) coalesced appends",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IsolatedRuntimeMetrics {
+    pub(crate) workers: UIntGauge,
+    pub(crate) queue_depth: UIntGauge,
+    pub(crate) busy_seconds: Counter,
+    pub(crate) panics: IntCounter,
+}
+
+impl IsolatedRuntimeMetrics {
+    pub(crate) fn new(registry: &MetricsRegistry) -> Self {
+        IsolatedRuntimeMetrics {
+            workers: registry.register(metric!(
+                name: "mz_persist_isolated_runtime_workers",
+                help: "number of worker threads in the isolated runtime",
+            )),
+            queue_depth: registry.register(metric!(
+                name: "mz_persist_isolated_runtime_queue_depth",
+                help: "count of tasks spawned on the isolated runtime that have not yet completed",
+            )),
+            busy_seconds: registry.register(metric!(
+                name: "mz_persist_isolated_runtime_busy_seconds",
+                help: "time spent polling tasks on the isolated runtime, divide the rate by the number of workers for the busy ratio",
+            )),
+            panics: registry.register(metric!(
+                name: "mz_persist_isolated_runtime_panics",
+                help: "count of panics in persist-internal work that were caught and converted into errors",
+            )),
         }
     }
 }
@@ -1159,6 +1495,7 @@ pub struct ShardsMetrics {
     _count: ComputedIntGauge,
     since: mz_ore::metrics::IntGaugeVec,
     upper: mz_ore::metrics::IntGaugeVec,
+    seqno: mz_ore::metrics::UIntGaugeVec,
     encoded_rollup_size: mz_ore::metrics::UIntGaugeVec,
     encoded_diff_size: mz_ore::metrics::IntCounterVec,
     hollow_batch_count: mz_ore::metrics::UIntGaugeVec,
@@ -1167,13 +1504,19 @@ pub struct ShardsMetrics {
     update_count: mz_ore::metrics::UIntGaugeVec,
     rollup_count: mz_ore::metrics::UIntGaugeVec,
     largest_batch_size: mz_ore::metrics::UIntGaugeVec,
+    uncompacted_bytes: mz_ore::metrics::UIntGaugeVec,
+    maintenance_failing: mz_ore::metrics::UIntGaugeVec,
     seqnos_held: mz_ore::metrics::UIntGaugeVec,
     seqnos_since_last_rollup: mz_ore::metrics::UIntGaugeVec,
     gc_seqno_held_parts: mz_ore::metrics::UIntGaugeVec,
     gc_live_diffs: mz_ore::metrics::UIntGaugeVec,
+    gc_deferred_blob_deletes: mz_ore::metrics::UIntGaugeVec,
     gc_finished: mz_ore::metrics::IntCounterVec,
+    decode_failures: mz_ore::metrics::IntCounterVec,
     compaction_applied: mz_ore::metrics::IntCounterVec,
     cmd_succeeded: mz_ore::metrics::IntCounterVec,
+    appends: mz_ore::metrics::IntCounterVec,
+    batch_bytes: mz_ore::metrics::IntCounterVec,
     usage_current_state_batches_bytes: mz_ore::metrics::UIntGaugeVec,
     usage_current_state_rollups_bytes: mz_ore::metrics::UIntGaugeVec,
     usage_referenced_not_current_state_bytes: mz_ore::metrics::UIntGaugeVec,
@@ -1182,10 +1525,19 @@ pub struct ShardsMetrics {
     pubsub_push_diff_applied: mz_ore::metrics::IntCounterVec,
     pubsub_push_diff_not_applied_stale: mz_ore::metrics::IntCounterVec,
     pubsub_push_diff_not_applied_out_of_order: mz_ore::metrics::IntCounterVec,
+    pubsub_push_diff_max_seqno_lag: mz_ore::metrics::UIntGaugeVec,
+    pubsub_push_diff_sent: mz_ore::metrics::IntCounterVec,
+    consensus_cas: mz_ore::metrics::IntCounterVec,
+    consensus_scan_bytes: mz_ore::metrics::IntCounterVec,
+    consensus_truncations: mz_ore::metrics::IntCounterVec,
     blob_gets: mz_ore::metrics::IntCounterVec,
     blob_sets: mz_ore::metrics::IntCounterVec,
     live_writers: mz_ore::metrics::UIntGaugeVec,
     unconsolidated_snapshot: mz_ore::metrics::IntCounterVec,
+    read_consolidation_runs: mz_ore::metrics::IntCounterVec,
+    read_consolidation_updates_in: mz_ore::metrics::IntCounterVec,
+    read_consolidation_updates_out: mz_ore::metrics::IntCounterVec,
+    read_consolidation_bytes: mz_ore::metrics::IntCounterVec,
     backpressure_emitted_bytes: IntCounterVec,
     backpressure_last_backpressured_bytes: UIntGaugeVec,
     backpressure_retired_bytes: IntCounterVec,
@@ -1221,6 +1573,11 @@ impl ShardsMetrics {
                 help: "upper by shard",
                 var_labels: ["shard", "name"],
             )),
+            seqno: registry.register(metric!(
+                name: "mz_persist_shard_seqno",
+                help: "seqno of the latest state applied by shard",
+                var_labels: ["shard", "name"],
+            )),
             encoded_rollup_size: registry.register(metric!(
                 name: "mz_persist_shard_rollup_size_bytes",
                 help: "total encoded rollup size by shard",
@@ -1261,6 +1618,16 @@ impl ShardsMetrics {
                 help: "largest encoded batch size by shard",
                 var_labels: ["shard", "name"],
             )),
+            uncompacted_bytes: registry.register(metric!(
+                name: "mz_persist_shard_uncompacted_bytes",
+                help: "encoded size of batches awaiting compaction by shard",
+                var_labels: ["shard", "name"],
+            )),
+            maintenance_failing: registry.register(metric!(
+                name: "mz_persist_shard_maintenance_failing",
+                help: "whether the last compaction attempt by shard failed",
+                var_labels: ["shard", "name"],
+            )),
             seqnos_held: registry.register(metric!(
                 name: "mz_persist_shard_seqnos_held",
                 help: "maximum count of gc-ineligible states by shard",
@@ -1281,11 +1648,21 @@ impl ShardsMetrics {
                 help: "the number of diffs (or, alternatively, the number of seqnos) present in consensus state at GC time",
                 var_labels: ["shard", "name"],
             )),
+            gc_deferred_blob_deletes: registry.register(metric!(
+                name: "mz_persist_shard_gc_deferred_blob_deletes",
+                help: "count of blobs known to be eligible for deletion but deferred by the per-run blob delete cap at GC time",
+                var_labels: ["shard", "name"],
+            )),
             gc_finished: registry.register(metric!(
                 name: "mz_persist_shard_gc_finished",
                 help: "count of garbage collections finished by shard",
                 var_labels: ["shard", "name"],
             )),
+            decode_failures: registry.register(metric!(
+                name: "mz_persist_shard_decode_failures",
+                help: "count of fetched keys and values that failed to decode by shard",
+                var_labels: ["shard", "name"],
+            )),
             compaction_applied: registry.register(metric!(
                 name: "mz_persist_shard_compaction_applied",
                 help: "count of compactions applied to state by shard",
@@ -1296,6 +1673,16 @@ impl ShardsMetrics {
                 help: "count of commands succeeded by shard",
                 var_labels: ["shard", "name"],
             )),
+            appends: registry.register(metric!(
+                name: "mz_persist_shard_appends",
+                help: "count of batches successfully appended by shard",
+                var_labels: ["shard", "name"],
+            )),
+            batch_bytes: registry.register(metric!(
+                name: "mz_persist_shard_batch_bytes",
+                help: "total size of batch parts written to Blob by shard",
+                var_labels: ["shard", "name"],
+            )),
             usage_current_state_batches_bytes: registry.register(metric!(
                 name: "mz_persist_shard_usage_current_state_batches_bytes",
                 help: "data in batches/parts referenced by current version of state",
@@ -1336,6 +1723,31 @@ impl ShardsMetrics {
                 help: "number of diffs received via pubsub that did not apply due to out-of-order delivery",
                 var_labels: ["shard", "name"],
             )),
+            pubsub_push_diff_max_seqno_lag: registry.register(metric!(
+                name: "mz_persist_shard_pubsub_diff_max_seqno_lag",
+                help: "max observed gap between the seqno of a diff received via pubsub and the locally applied seqno",
+                var_labels: ["shard", "name"],
+            )),
+            pubsub_push_diff_sent: registry.register(metric!(
+                name: "mz_persist_shard_pubsub_diff_sent",
+                help: "number of diffs pushed to pubsub by shard",
+                var_labels: ["shard", "name"],
+            )),
+            consensus_cas: registry.register(metric!(
+                name: "mz_persist_shard_consensus_cas",
+                help: "number of Consensus::compare_and_set calls for this shard",
+                var_labels: ["shard", "name"],
+            )),
+            consensus_scan_bytes: registry.register(metric!(
+                name: "mz_persist_shard_consensus_scan_bytes",
+                help: "total size of diffs returned by Consensus::scan calls for this shard",
+                var_labels: ["shard", "name"],
+            )),
+            consensus_truncations: registry.register(metric!(
+                name: "mz_persist_shard_consensus_truncations",
+                help: "number of Consensus::truncate calls for this shard",
+                var_labels: ["shard", "name"],
+            )),
             blob_gets: registry.register(metric!(
                 name: "mz_persist_shard_blob_gets",
                 help: "number of Blob::get calls for this shard",
@@ -1356,6 +1768,26 @@ impl ShardsMetrics {
                 help: "in snapshot_and_read, the number of times consolidating the raw data wasn't enough to produce consolidated output",
                 var_labels: ["shard", "name"],
             )),
+            read_consolidation_runs: registry.register(metric!(
+                name: "mz_persist_shard_read_consolidation_runs",
+                help: "number of runs merged by streaming consolidation while reading this shard",
+                var_labels: ["shard", "name"],
+            )),
+            read_consolidation_updates_in: registry.register(metric!(
+                name: "mz_persist_shard_read_consolidation_updates_in",
+                help: "number of updates fed into streaming consolidation while reading this shard",
+                var_labels: ["shard", "name"],
+            )),
+            read_consolidation_updates_out: registry.register(metric!(
+                name: "mz_persist_shard_read_consolidation_updates_out",
+                help: "number of updates returned by streaming consolidation while reading this shard",
+                var_labels: ["shard", "name"],
+            )),
+            read_consolidation_bytes: registry.register(metric!(
+                name: "mz_persist_shard_read_consolidation_bytes",
+                help: "encoded bytes of the parts merged by streaming consolidation while reading this shard",
+                var_labels: ["shard", "name"],
+            )),
             backpressure_emitted_bytes: registry.register(metric!(
                 name: "mz_persist_backpressure_emitted_bytes",
                 help: "A counter with the number of emitted bytes.",
@@ -1393,6 +1825,24 @@ impl ShardsMetrics {
         shard
     }
 
+    /// Returns the metrics of the given shard, if it's open in this process.
+    pub(crate) fn get(&self, shard_id: &ShardId) -> Option<Arc<ShardMetrics>> {
+        let shards = self.shards.lock().expect("mutex poisoned");
+        shards.get(shard_id).and_then(|shard| shard.upgrade())
+    }
+
+    /// Returns the number of shards open in this process whose last
+    /// compaction attempt failed.
+    pub(crate) fn maintenance_failing_count(&self) -> usize {
+        let mut ret = 0;
+        Self::compute(&self.shards, |m| {
+            if m.maintenance_failing.get() > 0 {
+                ret += 1;
+            }
+        });
+        ret
+    }
+
     fn compute<F: FnMut(&ShardMetrics)>(
         shards: &Arc<Mutex<BTreeMap<ShardId, Weak<ShardMetrics>>>>,
         mut f: F,
@@ -1417,7 +1867,10 @@ pub struct ShardMetrics {
     pub shard_id: ShardId,
     pub since: DeleteOnDropGauge<'static, AtomicI64, Vec<String>>,
     pub upper: DeleteOnDropGauge<'static, AtomicI64, Vec<String>>,
+    pub seqno: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub largest_batch_size: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
+    pub uncompacted_bytes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
+    pub maintenance_failing: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub latest_rollup_size: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub encoded_diff_size: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub hollow_batch_count: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
@@ -1429,6 +1882,7 @@ pub struct ShardMetrics {
     pub seqnos_since_last_rollup: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub gc_seqno_held_parts: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub gc_live_diffs: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
+    pub gc_deferred_blob_deletes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub usage_current_state_batches_bytes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub usage_current_state_rollups_bytes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub usage_referenced_not_current_state_bytes:
@@ -1436,20 +1890,34 @@ pub struct ShardMetrics {
     pub usage_not_leaked_not_referenced_bytes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub usage_leaked_bytes: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub gc_finished: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub decode_failures: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub compaction_applied: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub cmd_succeeded: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub appends: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub batch_bytes: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub pubsub_push_diff_applied: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub pubsub_push_diff_not_applied_stale: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub pubsub_push_diff_not_applied_out_of_order:
         DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub pubsub_push_diff_max_seqno_lag: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
+    pub pubsub_push_diff_sent: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub consensus_cas: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub consensus_scan_bytes: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub consensus_truncations: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub blob_gets: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub blob_sets: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub live_writers: DeleteOnDropGauge<'static, AtomicU64, Vec<String>>,
     pub unconsolidated_snapshot: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub read_consolidation_runs: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub read_consolidation_updates_in: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub read_consolidation_updates_out: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub read_consolidation_bytes: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
     pub backpressure_emitted_bytes: Arc<DeleteOnDropCounter<'static, AtomicU64, Vec<String>>>,
     pub backpressure_last_backpressured_bytes:
         Arc<DeleteOnDropGauge<'static, AtomicU64, Vec<String>>>,
     pub backpressure_retired_bytes: Arc<DeleteOnDropCounter<'static, AtomicU64, Vec<String>>>,
+    pub(crate) slow_ops: SlowOpWatchdog,
+    pub(crate) activity: ShardActivityWindow,
 }
 
 impl ShardMetrics {
@@ -1463,6 +1931,9 @@ impl ShardMetrics {
             upper: shards_metrics
                 .upper
                 .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
+            seqno: shards_metrics
+                .seqno
+                .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
             latest_rollup_size: shards_metrics
                 .encoded_rollup_size
                 .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
@@ -1487,6 +1958,12 @@ impl ShardMetrics {
             largest_batch_size: shards_metrics
                 .largest_batch_size
                 .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
+            uncompacted_bytes: shards_metrics
+                .uncompacted_bytes
+                .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
+            maintenance_failing: shards_metrics
+                .maintenance_failing
+                .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
             seqnos_held: shards_metrics
                 .seqnos_held
                 .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
@@ -1499,15 +1976,27 @@ impl ShardMetrics {
             gc_live_diffs: shards_metrics
                 .gc_live_diffs
                 .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
+            gc_deferred_blob_deletes: shards_metrics
+                .gc_deferred_blob_deletes
+                .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
             gc_finished: shards_metrics
                 .gc_finished
                 .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            decode_failures: shards_metrics
+                .decode_failures
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
             compaction_applied: shards_metrics
                 .compaction_applied
                 .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
             cmd_succeeded: shards_metrics
                 .cmd_succeeded
                 .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            appends: shards_metrics
+                .appends
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            batch_bytes: shards_metrics
+                .batch_bytes
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
             usage_current_state_batches_bytes: shards_metrics
                 .usage_current_state_batches_bytes
                 .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
@@ -1532,6 +2021,21 @@ impl ShardMetrics {
             pubsub_push_diff_not_applied_out_of_order: shards_metrics
                 .pubsub_push_diff_not_applied_out_of_order
                 .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            pubsub_push_diff_max_seqno_lag: shards_metrics
+                .pubsub_push_diff_max_seqno_lag
+                .get_delete_on_drop_gauge(vec![shard.clone(), name.to_string()]),
+            pubsub_push_diff_sent: shards_metrics
+                .pubsub_push_diff_sent
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            consensus_cas: shards_metrics
+                .consensus_cas
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            consensus_scan_bytes: shards_metrics
+                .consensus_scan_bytes
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            consensus_truncations: shards_metrics
+                .consensus_truncations
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
             blob_gets: shards_metrics
                 .blob_gets
                 .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
@@ -1544,6 +2048,18 @@ impl ShardMetrics {
             unconsolidated_snapshot: shards_metrics
                 .unconsolidated_snapshot
                 .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            read_consolidation_runs: shards_metrics
+                .read_consolidation_runs
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            read_consolidation_updates_in: shards_metrics
+                .read_consolidation_updates_in
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            read_consolidation_updates_out: shards_metrics
+                .read_consolidation_updates_out
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
+            read_consolidation_bytes: shards_metrics
+                .read_consolidation_bytes
+                .get_delete_on_drop_counter(vec![shard.clone(), name.to_string()]),
             backpressure_emitted_bytes: Arc::new(
                 shards_metrics
                     .backpressure_emitted_bytes
@@ -1559,15 +2075,75 @@ impl ShardMetrics {
                     .backpressure_retired_bytes
                     .get_delete_on_drop_counter(vec![shard, name.to_string()]),
             ),
+            slow_ops: SlowOpWatchdog::default(),
+            activity: ShardActivityWindow::new(),
         }
     }
 
+    /// Records an operation against this shard, both in the cumulative
+    /// counters and in the trailing activity window.
+    pub(crate) fn record(&self, op: ShardOp) {
+        match op {
+            ShardOp::Append => self.appends.inc(),
+            ShardOp::ConsensusCas => self.consensus_cas.inc(),
+            ShardOp::ConsensusScan { bytes } => self.consensus_scan_bytes.inc_by(bytes),
+            ShardOp::ConsensusTruncate => self.consensus_truncations.inc(),
+            ShardOp::PubSubPush => self.pubsub_push_diff_sent.inc(),
+        }
+        self.activity.record(op);
+    }
+
+    pub(crate) fn record_read_consolidation(&self, stats: &ConsolidationStats) {
+        self.read_consolidation_runs
+            .inc_by(u64::cast_from(stats.runs_merged));
+        self.read_consolidation_updates_in
+            .inc_by(u64::cast_from(stats.updates_in));
+        self.read_consolidation_updates_out
+            .inc_by(u64::cast_from(stats.updates_out));
+        self.read_consolidation_bytes
+            .inc_by(u64::cast_from(stats.bytes_processed));
+    }
+
     pub fn set_since<T: Codec64>(&self, since: &Antichain<T>) {
-        self.since.set(encode_ts_metric(since))
+        self.since.set(encode_codec64_ts_metric(since))
     }
 
     pub fn set_upper<T: Codec64>(&self, upper: &Antichain<T>) {
-        self.upper.set(encode_ts_metric(upper))
+        self.upper.set(encode_codec64_ts_metric(upper))
+    }
+
+    /// The total size of the batch parts written to Blob for this shard by
+    /// this process.
+    pub fn bytes_written(&self) -> u64 {
+        self.batch_bytes.get()
+    }
+
+    /// The number of batches appended to this shard by this process.
+    pub fn appends(&self) -> u64 {
+        self.appends.get()
+    }
+
+    /// The number of compactions applied to this shard by this process.
+    pub fn compactions(&self) -> u64 {
+        self.compaction_applied.get()
+    }
+
+    /// The number of garbage collections finished for this shard by this
+    /// process.
+    pub fn gcs(&self) -> u64 {
+        self.gc_finished.get()
+    }
+
+    /// The number of updates in this shard, as of the latest state seen by
+    /// this process.
+    pub fn updates(&self) -> u64 {
+        self.update_count.get()
+    }
+
+    /// The number of batch parts in this shard, as of the latest state seen by
+    /// this process.
+    pub fn batch_parts(&self) -> u64 {
+        self.batch_part_count.get()
     }
 }
 
@@ -1592,6 +2168,12 @@ pub struct UsageAuditMetrics {
     pub step_state: Counter,
     /// Time spent doing math
     pub step_math: Counter,
+    /// Count of shards whose referenced usage was recomputed from their live
+    /// states
+    pub shards_recomputed: IntCounter,
+    /// Count of shards whose referenced usage was reused from a previous run
+    /// because their live states hadn't changed
+    pub shards_reused: IntCounter,
 }
 
 impl UsageAuditMetrics {
@@ -1629,6 +2211,14 @@ impl UsageAuditMetrics {
             step_blob_metadata: step_timings.with_label_values(&["blob_metadata"]),
             step_state: step_timings.with_label_values(&["state"]),
             step_math: step_timings.with_label_values(&["math"]),
+            shards_recomputed: registry.register(metric!(
+                name: "mz_persist_audit_shards_recomputed",
+                help: "count of shards whose referenced usage was recomputed",
+            )),
+            shards_reused: registry.register(metric!(
+                name: "mz_persist_audit_shards_reused",
+                help: "count of shards whose referenced usage was reused from a previous run",
+            )),
         }
     }
 }
@@ -1830,6 +2420,123 @@ impl SinkMetrics {
     }
 }
 
+/// Metrics for the persist source. (While this lies slightly outside the usual
+/// abstraction boundary of the client, it's convenient to manage them together.)
+#[derive(Debug)]
+pub struct SourceMetrics {
+    // Like ShardsMetrics, these use the DeleteOnDrop wrappers, so that a
+    // shard's source metrics go away once no source in this process reads it.
+    read_frontier: mz_ore::metrics::IntGaugeVec,
+    upper: mz_ore::metrics::IntGaugeVec,
+    lag: mz_ore::metrics::IntGaugeVec,
+    parts_fetched: mz_ore::metrics::IntCounterVec,
+    decoded_bytes: mz_ore::metrics::IntCounterVec,
+    // We hand out `Arc<ShardSourceMetrics>` to the operators of sources, but
+    // store it here as `Weak`, so that every worker and operator reading the
+    // same shard under the same name shares (and deletes) the same metrics.
+    shards: Arc<Mutex<BTreeMap<(ShardId, String), Weak<ShardSourceMetrics>>>>,
+}
+
+impl SourceMetrics {
+    fn new(registry: &MetricsRegistry) -> Self {
+        SourceMetrics {
+            read_frontier: registry.register(metric!(
+                name: "mz_persist_source_read_frontier",
+                help: "frontier up to which persist sources have fetched the data of a shard",
+                var_labels: ["shard", "name"],
+            )),
+            upper: registry.register(metric!(
+                name: "mz_persist_source_upper",
+                help: "upper of a shard as most recently observed by persist sources",
+                var_labels: ["shard", "name"],
+            )),
+            lag: registry.register(metric!(
+                name: "mz_persist_source_lag",
+                help: "distance in timestamp units between the observed upper and read frontier of persist sources",
+                var_labels: ["shard", "name"],
+            )),
+            parts_fetched: registry.register(metric!(
+                name: "mz_persist_source_parts_fetched",
+                help: "count of parts fetched by persist sources",
+                var_labels: ["shard", "name"],
+            )),
+            decoded_bytes: registry.register(metric!(
+                name: "mz_persist_source_decoded_bytes",
+                help: "total encoded size of parts fetched and decoded by persist sources",
+                var_labels: ["shard", "name"],
+            )),
+            shards: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Obtains a [ShardSourceMetrics] instance, which allows for metric
+    /// reporting from a persist source reading the given shard. The `name` is
+    /// the shard name from the source's [crate::Diagnostics].
+    ///
+    /// The metrics are removed once the last instance for the shard and name
+    /// is dropped.
+    pub fn for_shard(&self, shard_id: &ShardId, name: &str) -> Arc<ShardSourceMetrics> {
+        let mut shards = self.shards.lock().expect("mutex poisoned");
+        let key = (*shard_id, name.to_owned());
+        if let Some(shard) = shards.get(&key) {
+            if let Some(shard) = shard.upgrade() {
+                return shard;
+            } else {
+                assert!(shards.remove(&key).is_some());
+            }
+        }
+        let labels = vec![shard_id.to_string(), name.to_owned()];
+        let shard = Arc::new(ShardSourceMetrics {
+            read_frontier: self.read_frontier.get_delete_on_drop_gauge(labels.clone()),
+            upper: self.upper.get_delete_on_drop_gauge(labels.clone()),
+            lag: self.lag.get_delete_on_drop_gauge(labels.clone()),
+            parts_fetched: self
+                .parts_fetched
+                .get_delete_on_drop_counter(labels.clone()),
+            decoded_bytes: self.decoded_bytes.get_delete_on_drop_counter(labels),
+        });
+        assert!(shards.insert(key, Arc::downgrade(&shard)).is_none());
+        shard
+    }
+}
+
+/// Metrics for the persist source that are labeled per-shard.
+#[derive(Debug)]
+pub struct ShardSourceMetrics {
+    pub(crate) read_frontier: DeleteOnDropGauge<'static, AtomicI64, Vec<String>>,
+    pub(crate) upper: DeleteOnDropGauge<'static, AtomicI64, Vec<String>>,
+    pub(crate) lag: DeleteOnDropGauge<'static, AtomicI64, Vec<String>>,
+    pub(crate) parts_fetched: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+    pub(crate) decoded_bytes: DeleteOnDropCounter<'static, AtomicU64, Vec<String>>,
+}
+
+impl ShardSourceMetrics {
+    /// Reports the upper of the shard, as observed by the source.
+    pub fn set_upper<T: TsMetricEncode>(&self, upper: &Antichain<T>) {
+        self.upper.set(encode_ts_metric(upper));
+        self.update_lag();
+    }
+
+    /// Reports the frontier up to which the source has fetched the shard's
+    /// data.
+    pub fn set_read_frontier<T: TsMetricEncode>(&self, read_frontier: &Antichain<T>) {
+        self.read_frontier.set(encode_ts_metric(read_frontier));
+        self.update_lag();
+    }
+
+    /// Reports a fetched part with the given encoded size.
+    pub fn inc_fetched(&self, encoded_size_bytes: usize) {
+        self.parts_fetched.inc();
+        self.decoded_bytes
+            .inc_by(u64::cast_from(encoded_size_bytes));
+    }
+
+    fn update_lag(&self) {
+        let lag = self.upper.get().saturating_sub(self.read_frontier.get());
+        self.lag.set(std::cmp::max(lag, 0));
+    }
+}
+
 /// Metrics for the persist sink that are labeled per-worker.
 #[derive(Clone, Debug)]
 pub struct SinkWorkerMetrics {
@@ -1964,6 +2671,7 @@ impl PubSubClientMetrics {
 pub struct PubSubGrpcClientConnectionMetrics {
     pub(crate) connected: UIntGauge,
     pub(crate) connection_established_count: IntCounter,
+    pub(crate) reconnect_count: IntCounter,
     pub(crate) connect_call_attempt_count: IntCounter,
     pub(crate) broadcast_recv_lagged_count: IntCounter,
     pub(crate) grpc_error_count: IntCounter,
@@ -1980,6 +2688,10 @@ impl PubSubGrpcClientConnectionMetrics {
                     name: "mz_persist_pubsub_client_grpc_connection_established_count",
                     help: "count of grpc connection establishments to pubsub server",
             )),
+            reconnect_count: registry.register(metric!(
+                    name: "mz_persist_pubsub_client_grpc_reconnect_count",
+                    help: "count of attempts to reestablish a lost grpc connection to pubsub server",
+            )),
             connect_call_attempt_count: registry.register(metric!(
                     name: "mz_persist_pubsub_client_grpc_connect_call_attempt_count",
                     help: "count of connection call attempts (including retries) to pubsub server",
@@ -2113,14 +2825,18 @@ pub struct WatchMetrics {
     pub(crate) listen_woken_via_sleep: IntCounter,
     pub(crate) listen_resolved_via_watch: IntCounter,
     pub(crate) listen_resolved_via_sleep: IntCounter,
+    pub(crate) listen_woken_via_resubscribe: IntCounter,
+    pub(crate) listen_resolved_via_resubscribe: IntCounter,
     pub(crate) snapshot_woken_via_watch: IntCounter,
     pub(crate) snapshot_woken_via_sleep: IntCounter,
+    pub(crate) snapshot_woken_via_resubscribe: IntCounter,
     pub(crate) notify_sent: IntCounter,
     pub(crate) notify_noop: IntCounter,
     pub(crate) notify_recv: IntCounter,
     pub(crate) notify_lagged: IntCounter,
     pub(crate) notify_wait_started: IntCounter,
     pub(crate) notify_wait_finished: IntCounter,
+    pub(crate) notify_resubscribed: IntCounter,
 }
 
 impl WatchMetrics {
@@ -2142,6 +2858,14 @@ impl WatchMetrics {
                 name: "mz_persist_listen_resolved_via_sleep",
                 help: "count of listen next batches resolved via sleep",
             )),
+            listen_woken_via_resubscribe: registry.register(metric!(
+                name: "mz_persist_listen_woken_via_resubscribe",
+                help: "count of listen next batches wakes via pubsub resubscription",
+            )),
+            listen_resolved_via_resubscribe: registry.register(metric!(
+                name: "mz_persist_listen_resolved_via_resubscribe",
+                help: "count of listen next batches resolved via pubsub resubscription",
+            )),
             snapshot_woken_via_watch: registry.register(metric!(
                 name: "mz_persist_snapshot_woken_via_watch",
                 help: "count of snapshot wakes via watch notify",
@@ -2150,6 +2874,10 @@ impl WatchMetrics {
                 name: "mz_persist_snapshot_woken_via_sleep",
                 help: "count of snapshot wakes via sleep",
             )),
+            snapshot_woken_via_resubscribe: registry.register(metric!(
+                name: "mz_persist_snapshot_woken_via_resubscribe",
+                help: "count of snapshot wakes via pubsub resubscription",
+            )),
             notify_sent: registry.register(metric!(
                 name: "mz_persist_watch_notify_sent",
                 help: "count of watch notifications sent to a non-empty broadcast channel",
@@ -2174,6 +2902,10 @@ impl WatchMetrics {
                 name: "mz_persist_watch_notify_wait_finished",
                 help: "count of watch wait calls resolved",
             )),
+            notify_resubscribed: registry.register(metric!(
+                name: "mz_persist_watch_notify_resubscribed",
+                help: "count of watch wait calls cut short by a pubsub resubscription",
+            )),
         }
     }
 }
@@ -2310,6 +3042,7 @@ pub struct ExternalOpMetrics {
     seconds: Counter,
     seconds_histogram: Option<Histogram>,
     alerts_metrics: Arc<AlertsMetrics>,
+    health: Arc<HealthWindow>,
 }
 
 impl ExternalOpMetrics {
@@ -2326,11 +3059,13 @@ impl ExternalOpMetrics {
         self.started.inc();
         let start = Instant::now();
         let res = op_fn().await;
-        let elapsed_seconds = start.elapsed().as_secs_f64();
+        let elapsed = start.elapsed();
+        let elapsed_seconds = elapsed.as_secs_f64();
         self.seconds.inc_by(elapsed_seconds);
         if let Some(h) = &self.seconds_histogram {
             h.observe(elapsed_seconds);
         }
+        self.health.record(elapsed, res.is_ok());
         match res.as_ref() {
             Ok(_) => self.succeeded.inc(),
             Err(err) => {
@@ -2368,11 +3103,13 @@ impl ExternalOpMetrics {
             } else {
                 self.failed.inc()
             }
-            let elapsed_seconds = start.elapsed().as_secs_f64();
+            let elapsed = start.elapsed();
+            let elapsed_seconds = elapsed.as_secs_f64();
             self.seconds.inc_by(elapsed_seconds);
             if let Some(h) = &self.seconds_histogram {
                 h.observe(elapsed_seconds);
             }
+            self.health.record(elapsed, succeeded);
         }
     }
 }
@@ -2510,6 +3247,7 @@ pub struct ConsensusMetrics {
     scan: ExternalOpMetrics,
     truncate: ExternalOpMetrics,
     truncated_count: IntCounter,
+    delete: ExternalOpMetrics,
     pub rtt_latency: Gauge,
 }
 
@@ -2630,6 +3368,15 @@ impl Consensus for MetricsConsensus {
             .inc_by(u64::cast_from(deleted));
         Ok(deleted)
     }
+
+    #[instrument(name = "consensus::delete", skip_all, fields(shard=key))]
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        self.metrics
+            .consensus
+            .delete
+            .run_op(|| self.consensus.delete(key, expected), Self::on_err)
+            .await
+    }
 }
 
 /// A standard set of metrics for an async task. Call [TaskMetrics::instrument_task] to instrument
@@ -2741,17 +3488,52 @@ fn blob_key_shard_id(key: &str) -> Option<String> {
     Some(shard_id.to_string())
 }
 
+/// A timestamp that can be reported as the value of a gauge metric.
+///
+/// Totally ordered [Codec64] timestamps get this for free, by interpreting
+/// their encoding as a little-endian i64. This is sensible for all values that
+/// mz actually produces, which are u64s (and if we switch them, it will be to
+/// i64s) that fit in an i64. Partially ordered timestamps have to opt in, e.g.
+/// product timestamps by reporting their outer coordinate via
+/// [encode_outer_ts_metric].
+pub trait TsMetricEncode {
+    /// Encodes the timestamp into an i64 acceptable for use in metrics.
+    fn encode_ts_metric(&self) -> i64;
+}
+
+impl<T: Codec64 + TotalOrder> TsMetricEncode for T {
+    fn encode_ts_metric(&self) -> i64 {
+        i64::from_le_bytes(Codec64::encode(self))
+    }
+}
+
+/// Encodes a product timestamp into an i64 acceptable for use in metrics, by
+/// encoding its outer coordinate.
+///
+/// This is intended for [TsMetricEncode] impls of product timestamps.
+pub fn encode_outer_ts_metric<O: TsMetricEncode, I>(ts: &Product<O, I>) -> i64 {
+    ts.outer.encode_ts_metric()
+}
+
 /// Encode a frontier into an i64 acceptable for use in metrics.
-pub fn encode_ts_metric<T: Codec64>(ts: &Antichain<T>) -> i64 {
-    // We have two problems in mapping a persist frontier into a metric.
-    // First is that we only have a `T: Timestamp+Codec64`. Second, is
-    // mapping an antichain to a single counter value. We solve both by
-    // taking advantage of the fact that in practice, timestamps in mz are
-    // currently always a u64 (and if we switch them, it will be to an i64).
-    // This means that for all values that mz would actually produce,
-    // interpreting the the encoded bytes as a little-endian i64 will work.
-    // Both of them impl PartialOrder, so in practice, there will always be
-    // zero or one elements in the antichain.
+///
+/// An antichain of partially ordered timestamps can have several elements, in
+/// which case this reports the smallest of their encodings. The empty
+/// antichain, i.e. a closed frontier, is reported as i64::MAX.
+pub fn encode_ts_metric<T: TsMetricEncode>(ts: &Antichain<T>) -> i64 {
+    ts.elements()
+        .iter()
+        .map(TsMetricEncode::encode_ts_metric)
+        .min()
+        .unwrap_or(i64::MAX)
+}
+
+/// [encode_ts_metric] for timestamps that are only known to be [Codec64], as
+/// in persist's own per-shard metrics.
+///
+/// This interprets the encoding of the first element of the frontier, which is
+/// only meaningful for totally ordered timestamps like the ones mz uses.
+fn encode_codec64_ts_metric<T: Codec64>(ts: &Antichain<T>) -> i64 {
     match ts.elements().first() {
         Some(ts) => i64::from_le_bytes(Codec64::encode(ts)),
         None => i64::MAX,
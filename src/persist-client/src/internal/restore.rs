@@ -7,16 +7,21 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-//! See documentation on [[restore_blob]].
+//! See documentation on [[restore_blob]] and [[fetch_restorable_state]].
+
+use std::collections::BTreeSet;
 
 use crate::internal::encoding::UntypedState;
-use crate::internal::paths::BlobKey;
-use crate::internal::state::State;
+use crate::internal::paths::{BlobKey, BlobKeyPrefix};
+use crate::internal::state::{State, StateCollections};
 use crate::internal::state_diff::{StateDiff, StateFieldValDiff};
 use crate::internal::state_versions::StateVersions;
 use crate::ShardId;
-use anyhow::anyhow;
-use mz_persist::location::Blob;
+use anyhow::{anyhow, bail};
+use differential_dataflow::lattice::Lattice;
+use mz_persist::location::{Blob, SeqNo};
+use mz_persist_types::Codec64;
+use timely::progress::Timestamp;
 use tracing::info;
 
 /// Attempt to restore all the blobs referenced by the current state in consensus.
@@ -102,3 +107,62 @@ pub(crate) async fn restore_blob(
     }
     Ok(not_restored)
 }
+
+/// Returns the collections of the given shard's state as of `seqno`, for
+/// restoring the shard to that version (see [StateCollections::restore]).
+///
+/// Fails if `seqno` is not a live version of the shard's state, e.g. because
+/// it was already truncated by GC, or if any batch part referenced by that
+/// version is gone from Blob.
+pub(crate) async fn fetch_restorable_state<T>(
+    versions: &StateVersions,
+    blob: &(dyn Blob + Send + Sync),
+    shard_id: ShardId,
+    seqno: SeqNo,
+) -> anyhow::Result<StateCollections<T>>
+where
+    T: Timestamp + Lattice + Codec64,
+{
+    let Some(states) = versions.fetch_all_live_states::<T>(shard_id).await else {
+        bail!("shard {shard_id} is not initialized");
+    };
+    let mut states = states.check_ts_codec()?;
+    let earliest = states.state().seqno;
+    let mut target = None;
+    while let Some(state) = states.next(|_| {}) {
+        if state.seqno == seqno {
+            target = Some(state.collections.clone());
+            break;
+        }
+    }
+    let Some(target) = target else {
+        bail!(
+            "seqno {seqno} is not a live version of shard {shard_id}: live versions are {} through {}",
+            earliest,
+            states.state().seqno
+        );
+    };
+    if target.is_tombstone() {
+        bail!("seqno {seqno} of shard {shard_id} is a tombstone");
+    }
+
+    let prefix = BlobKeyPrefix::Shard(&shard_id).to_string();
+    let mut keys = BTreeSet::new();
+    blob.list_keys_and_metadata(&prefix, &mut |metadata| {
+        keys.insert(metadata.key.to_owned());
+    })
+    .await?;
+    let mut missing = Vec::new();
+    for batch in target.trace.batches() {
+        for part in &batch.parts {
+            let key = part.key.complete(&shard_id);
+            if !keys.contains(&*key) {
+                missing.push(key);
+            }
+        }
+    }
+    if !missing.is_empty() {
+        bail!("seqno {seqno} of shard {shard_id} references blobs that are gone: {missing:?}");
+    }
+    Ok(target)
+}
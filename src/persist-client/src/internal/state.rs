@@ -40,7 +40,7 @@ use crate::internal::paths::{PartialBatchKey, PartialRollupKey};
 use crate::internal::trace::{ApplyMergeResult, FueledMergeReq, FueledMergeRes, Trace};
 use crate::read::LeasedReaderId;
 use crate::write::WriterId;
-use crate::{PersistConfig, ShardId};
+use crate::{CriticalReaderInfo, Diagnostics, PersistConfig, SchemaId, ShardId, ShardTuning};
 
 include!(concat!(
     env!("OUT_DIR"),
@@ -170,6 +170,13 @@ pub struct HollowBatchPart {
     #[serde(serialize_with = "serialize_part_stats")]
     #[proptest(strategy = "super::encoding::any_some_lazy_part_stats()")]
     pub stats: Option<LazyPartStats>,
+    /// The schema the part was written with, if it was written with one other
+    /// than the schema the shard was opened with.
+    pub schema_id: Option<SchemaId>,
+    /// A CRC-32 checksum of the encoded part, verified whenever the part is
+    /// fetched. Parts written before checksums were introduced don't have one
+    /// and skip verification.
+    pub checksum: Option<u32>,
 }
 
 /// A [Batch] but with the updates themselves stored externally.
@@ -359,6 +366,8 @@ pub struct StateCollections<T> {
     pub(crate) critical_readers: BTreeMap<CriticalReaderId, CriticalReaderState<T>>,
     pub(crate) writers: BTreeMap<WriterId, WriterState<T>>,
 
+    pub(crate) tuning: ShardTuning,
+
     // - Invariant: `trace.since == meet(all reader.since)`
     // - Invariant: `trace.since` doesn't regress across state versions.
     // - Invariant: `trace.upper` doesn't regress across state versions.
@@ -755,6 +764,37 @@ where
         }
     }
 
+    pub fn heartbeat_leased_readers(
+        &mut self,
+        reader_ids: &[LeasedReaderId],
+        heartbeat_timestamp_ms: u64,
+    ) -> ControlFlow<NoOpStateTransition<Vec<LeasedReaderId>>, Vec<LeasedReaderId>> {
+        // We expire all readers if the upper and since both advance to the
+        // empty antichain. Gracefully handle this. At the same time,
+        // short-circuit the cmd application so we don't needlessly create new
+        // SeqNos.
+        if self.is_tombstone() {
+            return Break(NoOpStateTransition(reader_ids.to_vec()));
+        }
+
+        let mut missing = Vec::new();
+        for reader_id in reader_ids {
+            match self.leased_readers.get_mut(reader_id) {
+                Some(reader_state) => {
+                    reader_state.last_heartbeat_timestamp_ms = std::cmp::max(
+                        heartbeat_timestamp_ms,
+                        reader_state.last_heartbeat_timestamp_ms,
+                    );
+                }
+                None => missing.push(reader_id.clone()),
+            }
+        }
+        // Even if all of the readers are missing, we still commit the state
+        // change so that this gets linearized (maybe we're looking at old
+        // state).
+        Continue(missing)
+    }
+
     pub fn expire_leased_reader(
         &mut self,
         reader_id: &LeasedReaderId,
@@ -928,6 +968,123 @@ where
         batch_count <= 1 && is_empty
     }
 
+    /// Replaces the shard's tuning overrides.
+    pub fn set_tuning(&mut self, tuning: &ShardTuning) -> ControlFlow<NoOpStateTransition<()>, ()> {
+        if self.is_tombstone() || &self.tuning == tuning {
+            return Break(NoOpStateTransition(()));
+        }
+        self.tuning = tuning.clone();
+        Continue(())
+    }
+
+    /// Registers a leased reader whose seqno capability is `hold_seqno`
+    /// rather than the current seqno, keeping GC from deleting anything
+    /// referenced by the state versions since `hold_seqno`.
+    ///
+    /// Returns false without registering anything if GC may already have
+    /// deleted some of that, i.e. if `hold_seqno` is before the seqno since of
+    /// the most recent GC request.
+    pub fn register_seqno_hold(
+        &mut self,
+        hostname: &str,
+        reader_id: &LeasedReaderId,
+        purpose: &str,
+        hold_seqno: SeqNo,
+        lease_duration: Duration,
+        heartbeat_timestamp_ms: u64,
+    ) -> ControlFlow<NoOpStateTransition<bool>, bool> {
+        if self.is_tombstone() || hold_seqno < self.last_gc_req {
+            return Break(NoOpStateTransition(false));
+        }
+        match self.register_leased_reader(
+            hostname,
+            reader_id,
+            purpose,
+            hold_seqno,
+            lease_duration,
+            heartbeat_timestamp_ms,
+        ) {
+            Continue(_) => Continue(true),
+            Break(_) => Break(NoOpStateTransition(false)),
+        }
+    }
+
+    /// Replaces the contents of the shard with those of `target`, the
+    /// collections of an earlier version of this shard's state.
+    ///
+    /// Neither the since nor the upper of the shard ever regress: the restored
+    /// shard keeps its current since, and everything written after `target`
+    /// is replaced by an empty batch that advances the upper to `new_upper`.
+    /// `new_upper` must be strictly past the current upper, so that every live
+    /// writer is fenced out by an upper mismatch on its next write. All writer
+    /// registrations are removed as well, so that none of them can mistake a
+    /// retried write for one that already committed. Rollups, the last GC
+    /// request, readers, and tuning overrides are kept as they are.
+    ///
+    /// Returns the current upper of the shard if it isn't strictly before
+    /// `new_upper`, or if `target` isn't an earlier version of this shard.
+    pub fn restore(
+        &mut self,
+        target: &StateCollections<T>,
+        new_upper: &Antichain<T>,
+    ) -> ControlFlow<NoOpStateTransition<Result<(), Upper<T>>>, Result<(), Upper<T>>> {
+        if !PartialOrder::less_than(self.trace.upper(), new_upper)
+            || !PartialOrder::less_equal(target.trace.upper(), self.trace.upper())
+        {
+            return Break(NoOpStateTransition(Err(Upper(self.trace.upper().clone()))));
+        }
+
+        let mut trace = Trace::default();
+        trace.downgrade_since(self.trace.since());
+        for batch in target.trace.batches() {
+            let _merge_reqs = trace.push_batch(batch.clone());
+        }
+        let _merge_reqs = trace.push_batch(HollowBatch {
+            desc: Description::new(
+                target.trace.upper().clone(),
+                new_upper.clone(),
+                Antichain::from_elem(T::minimum()),
+            ),
+            parts: Vec::new(),
+            runs: Vec::new(),
+            len: 0,
+        });
+        self.trace = trace;
+        self.writers.clear();
+        Continue(Ok(()))
+    }
+
+    /// Advances both the since and upper of the shard to the empty antichain,
+    /// regardless of any reader or writer capabilities that would otherwise
+    /// hold them back.
+    ///
+    /// This is only intended for forcibly finalizing a shard whose frontiers
+    /// are stuck, e.g. because a critical since handle was lost. Any live
+    /// readers or writers of the shard will break.
+    pub fn force_empty_frontiers(&mut self) -> ControlFlow<NoOpStateTransition<()>, ()> {
+        if self.trace.upper().is_empty() && self.trace.since().is_empty() {
+            return Break(NoOpStateTransition(()));
+        }
+
+        if !self.trace.upper().is_empty() {
+            // Close the shard with an empty batch, the same as a writer
+            // advancing the upper to [] would. Nothing will ever read this
+            // shard again, so there's no point in compacting it.
+            let _merge_reqs = self.trace.push_batch(HollowBatch {
+                desc: Description::new(
+                    self.trace.upper().clone(),
+                    Antichain::new(),
+                    Antichain::from_elem(T::minimum()),
+                ),
+                parts: Vec::new(),
+                runs: Vec::new(),
+                len: 0,
+            });
+        }
+        self.trace.downgrade_since(&Antichain::new());
+        Continue(())
+    }
+
     pub fn become_tombstone_and_shrink(&mut self) -> ControlFlow<NoOpStateTransition<()>, ()> {
         assert_eq!(self.trace.upper(), &Antichain::new());
         assert_eq!(self.trace.since(), &Antichain::new());
@@ -1006,6 +1163,11 @@ pub struct State<T> {
     /// Hostname of the persist user that created this version of state. For
     /// debugging.
     pub(crate) hostname: String,
+    /// Diagnostics of the handle that initialized the shard (and so registered
+    /// its codecs), if known. For debugging.
+    ///
+    /// This is None for shards initialized before we started recording it.
+    pub(crate) registered_by: Option<Diagnostics>,
     pub(crate) collections: StateCollections<T>,
 }
 
@@ -1034,6 +1196,7 @@ impl<K, V, T: Clone, D> TypedState<K, V, T, D> {
                 seqno: self.seqno.clone(),
                 walltime_ms: self.walltime_ms,
                 hostname,
+                registered_by: self.registered_by.clone(),
                 collections: self.collections.clone(),
             },
             _phantom: PhantomData,
@@ -1048,6 +1211,7 @@ impl<K, V, T: Clone, D> TypedState<K, V, T, D> {
                 seqno: self.seqno.clone(),
                 walltime_ms: self.walltime_ms,
                 hostname: self.hostname.clone(),
+                registered_by: self.registered_by.clone(),
                 collections: self.collections.clone(),
             },
             _phantom: PhantomData,
@@ -1115,12 +1279,14 @@ where
             seqno: SeqNo::minimum(),
             walltime_ms,
             hostname,
+            registered_by: None,
             collections: StateCollections {
                 last_gc_req: SeqNo::minimum(),
                 rollups: BTreeMap::new(),
                 leased_readers: BTreeMap::new(),
                 critical_readers: BTreeMap::new(),
                 writers: BTreeMap::new(),
+                tuning: ShardTuning::default(),
                 trace: Trace::default(),
             },
         };
@@ -1144,6 +1310,7 @@ where
             seqno: self.seqno.next(),
             walltime_ms: (cfg.now)(),
             hostname: cfg.hostname.clone(),
+            registered_by: self.registered_by.clone(),
             collections: self.collections.clone(),
         };
         // Make sure walltime_ms is strictly increasing, in case clocks are
@@ -1181,10 +1348,29 @@ where
         self.collections.trace.upper()
     }
 
+    pub fn critical_reader_infos(&self) -> Vec<CriticalReaderInfo<T>> {
+        self.collections
+            .critical_readers
+            .iter()
+            .map(|(id, reader)| CriticalReaderInfo {
+                id: id.clone(),
+                purpose: reader.debug.purpose.clone(),
+                hostname: reader.debug.hostname.clone(),
+                since: reader.since.clone(),
+                opaque_hex: hex::encode(reader.opaque.0),
+                opaque_codec: reader.opaque_codec.clone(),
+            })
+            .collect()
+    }
+
     pub fn spine_batch_count(&self) -> usize {
         self.collections.trace.num_spine_batches()
     }
 
+    pub fn uncompacted_bytes(&self) -> usize {
+        self.collections.trace.uncompacted_bytes()
+    }
+
     pub fn size_metrics(&self) -> StateSizeMetrics {
         let mut ret = StateSizeMetrics::default();
         self.map_blobs(|x| match x {
@@ -1422,6 +1608,7 @@ impl<T: Serialize> Serialize for State<T> {
             seqno,
             walltime_ms,
             hostname,
+            registered_by,
             collections:
                 StateCollections {
                     last_gc_req,
@@ -1429,20 +1616,23 @@ impl<T: Serialize> Serialize for State<T> {
                     leased_readers,
                     critical_readers,
                     writers,
+                    tuning,
                     trace,
                 },
         } = self;
-        let mut s = s.serialize_struct("State", 13)?;
+        let mut s = s.serialize_struct("State", 15)?;
         let () = s.serialize_field("applier_version", &applier_version.to_string())?;
         let () = s.serialize_field("shard_id", shard_id)?;
         let () = s.serialize_field("seqno", seqno)?;
         let () = s.serialize_field("walltime_ms", walltime_ms)?;
         let () = s.serialize_field("hostname", hostname)?;
+        let () = s.serialize_field("registered_by", registered_by)?;
         let () = s.serialize_field("last_gc_req", last_gc_req)?;
         let () = s.serialize_field("rollups", rollups)?;
         let () = s.serialize_field("leased_readers", leased_readers)?;
         let () = s.serialize_field("critical_readers", critical_readers)?;
         let () = s.serialize_field("writers", writers)?;
+        let () = s.serialize_field("tuning", tuning)?;
         let () = s.serialize_field("since", &trace.since().elements())?;
         let () = s.serialize_field("upper", &trace.upper().elements())?;
         let () = s.serialize_field("batches", &trace.batches().into_iter().collect::<Vec<_>>())?;
@@ -1606,6 +1796,7 @@ pub(crate) mod tests {
                 any::<SeqNo>(),
                 any::<u64>(),
                 any::<String>(),
+                any::<Option<Diagnostics>>(),
                 any::<SeqNo>(),
                 proptest::collection::btree_map(any::<SeqNo>(), any::<HollowRollup>(), 1..3),
                 proptest::collection::btree_map(
@@ -1619,6 +1810,7 @@ pub(crate) mod tests {
                     1..3,
                 ),
                 proptest::collection::btree_map(any::<WriterId>(), any_writer_state::<T>(), 0..3),
+                any::<ShardTuning>(),
                 any_trace::<T>(num_trace_batches),
             ),
             |(
@@ -1626,11 +1818,13 @@ pub(crate) mod tests {
                 seqno,
                 walltime_ms,
                 hostname,
+                registered_by,
                 last_gc_req,
                 rollups,
                 leased_readers,
                 critical_readers,
                 writers,
+                tuning,
                 trace,
             )| State {
                 applier_version: semver::Version::new(1, 2, 3),
@@ -1638,12 +1832,14 @@ pub(crate) mod tests {
                 seqno,
                 walltime_ms,
                 hostname,
+                registered_by,
                 collections: StateCollections {
                     last_gc_req,
                     rollups,
                     leased_readers,
                     critical_readers,
                     writers,
+                    tuning,
                     trace,
                 },
             },
@@ -1664,6 +1860,8 @@ pub(crate) mod tests {
                     encoded_size_bytes: 0,
                     key_lower: vec![],
                     stats: None,
+                    schema_id: None,
+                    checksum: None,
                 })
                 .collect(),
             len,
@@ -32,7 +32,7 @@ use crate::internal::state::{
 use crate::internal::trace::{FueledMergeRes, Trace};
 use crate::read::LeasedReaderId;
 use crate::write::WriterId;
-use crate::{Metrics, PersistConfig, ShardId};
+use crate::{Metrics, PersistConfig, ShardId, ShardTuning};
 
 use StateFieldValDiff::*;
 
@@ -76,6 +76,7 @@ pub struct StateDiff<T> {
     pub(crate) leased_readers: Vec<StateFieldDiff<LeasedReaderId, LeasedReaderState<T>>>,
     pub(crate) critical_readers: Vec<StateFieldDiff<CriticalReaderId, CriticalReaderState<T>>>,
     pub(crate) writers: Vec<StateFieldDiff<WriterId, WriterState<T>>>,
+    pub(crate) tuning: Vec<StateFieldDiff<(), ShardTuning>>,
     pub(crate) since: Vec<StateFieldDiff<(), Antichain<T>>>,
     pub(crate) spine: Vec<StateFieldDiff<HollowBatch<T>, ()>>,
 }
@@ -100,6 +101,7 @@ impl<T: Timestamp + Codec64> StateDiff<T> {
             leased_readers: Vec::default(),
             critical_readers: Vec::default(),
             writers: Vec::default(),
+            tuning: Vec::default(),
             since: Vec::default(),
             spine: Vec::default(),
         }
@@ -116,6 +118,8 @@ impl<T: Timestamp + Lattice + Codec64> StateDiff<T> {
             seqno: from_seqno,
             hostname: from_hostname,
             walltime_ms: _, // Intentionally unused
+            // Only set when the shard is initialized, so never diffed.
+            registered_by: _,
             collections:
                 StateCollections {
                     last_gc_req: from_last_gc_req,
@@ -123,6 +127,7 @@ impl<T: Timestamp + Lattice + Codec64> StateDiff<T> {
                     leased_readers: from_leased_readers,
                     critical_readers: from_critical_readers,
                     writers: from_writers,
+                    tuning: from_tuning,
                     trace: from_trace,
                 },
         } = from;
@@ -132,6 +137,7 @@ impl<T: Timestamp + Lattice + Codec64> StateDiff<T> {
             seqno: to_seqno,
             walltime_ms: to_walltime_ms,
             hostname: to_hostname,
+            registered_by: _,
             collections:
                 StateCollections {
                     last_gc_req: to_last_gc_req,
@@ -139,6 +145,7 @@ impl<T: Timestamp + Lattice + Codec64> StateDiff<T> {
                     leased_readers: to_leased_readers,
                     critical_readers: to_critical_readers,
                     writers: to_writers,
+                    tuning: to_tuning,
                     trace: to_trace,
                 },
         } = to;
@@ -166,6 +173,7 @@ impl<T: Timestamp + Lattice + Codec64> StateDiff<T> {
             &mut diffs.critical_readers,
         );
         diff_field_sorted_iter(from_writers.iter(), to_writers, &mut diffs.writers);
+        diff_field_single(from_tuning, to_tuning, &mut diffs.tuning);
         diff_field_single(from_trace.since(), to_trace.since(), &mut diffs.since);
         diff_field_spine(from_trace, to_trace, &mut diffs.spine);
         diffs
@@ -339,6 +347,7 @@ impl<T: Timestamp + Lattice + Codec64> State<T> {
             leased_readers: diff_leased_readers,
             critical_readers: diff_critical_readers,
             writers: diff_writers,
+            tuning: diff_tuning,
             since: diff_since,
             spine: diff_spine,
         } = diff;
@@ -371,6 +380,7 @@ impl<T: Timestamp + Lattice + Codec64> State<T> {
             leased_readers,
             critical_readers,
             writers,
+            tuning,
             trace,
         } = &mut self.collections;
 
@@ -379,6 +389,7 @@ impl<T: Timestamp + Lattice + Codec64> State<T> {
         apply_diffs_map("leased_readers", diff_leased_readers, leased_readers)?;
         apply_diffs_map("critical_readers", diff_critical_readers, critical_readers)?;
         apply_diffs_map("writers", diff_writers, writers)?;
+        apply_diffs_single("tuning", diff_tuning, tuning)?;
 
         for x in diff_since {
             match x.val {
@@ -642,6 +653,22 @@ fn apply_diffs_spine<T: Timestamp + Lattice>(
     mut diffs: Vec<StateFieldDiff<HollowBatch<T>, ()>>,
     trace: &mut Trace<T>,
 ) -> Result<(), String> {
+    // Special case: sniff out a diff that replaces the entire trace (as
+    // restoring a shard to an earlier version does) and rebuild the trace from
+    // its inserts. This works no matter how our spine happens to be arranged.
+    if let Some(batches) = sniff_replacement(&diffs, trace.upper()) {
+        let mut new_trace = Trace::default();
+        new_trace.downgrade_since(trace.since());
+        for batch in batches {
+            // Ignore merge_reqs because whichever process generated this diff
+            // is assigned the work.
+            let () = new_trace.push_batch_no_merge_reqs(batch.clone());
+        }
+        *trace = new_trace;
+        metrics.state.apply_spine_fast_path.inc();
+        return Ok(());
+    }
+
     // Another special case: sniff out a newly inserted batch (one whose lower
     // lines up with the current upper) and handle that now. Then fall through
     // to the rest of the handling on whatever is left.
@@ -800,6 +827,51 @@ fn apply_diffs_spine<T: Timestamp + Lattice>(
     Ok(())
 }
 
+/// Returns the batches inserted by `diffs`, in order, if it deletes every
+/// batch of a trace with the given upper.
+///
+/// The deletes need not line up with the batches of our own copy of the trace,
+/// it's enough that they cover everything from the minimum timestamp up to its
+/// upper: the inserts are then the complete new trace.
+fn sniff_replacement<'a, T: Timestamp + Lattice>(
+    diffs: &'a [StateFieldDiff<HollowBatch<T>, ()>],
+    upper: &Antichain<T>,
+) -> Option<Vec<&'a HollowBatch<T>>> {
+    let mut deletes = Vec::new();
+    let mut inserts = Vec::new();
+    for diff in diffs.iter() {
+        match diff.val {
+            StateFieldValDiff::Delete(()) => deletes.push(&diff.key),
+            StateFieldValDiff::Insert(()) => inserts.push(&diff.key),
+            StateFieldValDiff::Update((), ()) => return None,
+        }
+    }
+    if deletes.is_empty() || inserts.is_empty() {
+        return None;
+    }
+    let deletes = chain_from_minimum(deletes)?;
+    if deletes.last().map(|b| b.desc.upper()) != Some(upper) {
+        return None;
+    }
+    chain_from_minimum(inserts)
+}
+
+/// Orders `batches` so that the first starts at the minimum timestamp and each
+/// of the others starts at the upper of the one before it, if possible.
+fn chain_from_minimum<T: Timestamp>(
+    mut batches: Vec<&HollowBatch<T>>,
+) -> Option<Vec<&HollowBatch<T>>> {
+    let mut chain = Vec::with_capacity(batches.len());
+    let mut upper = Antichain::from_elem(T::minimum());
+    while !batches.is_empty() {
+        let idx = batches.iter().position(|b| b.desc.lower() == &upper)?;
+        let batch = batches.swap_remove(idx);
+        upper.clone_from(batch.desc.upper());
+        chain.push(batch);
+    }
+    Some(chain)
+}
+
 fn sniff_insert<T: Timestamp + Lattice>(
     diffs: &mut Vec<StateFieldDiff<HollowBatch<T>, ()>>,
     upper: &Antichain<T>,
@@ -14,7 +14,7 @@ use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::ops::ControlFlow::{Break, Continue};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use bytes::Bytes;
 use differential_dataflow::difference::Semigroup;
@@ -31,16 +31,22 @@ use prost::Message;
 use timely::progress::Timestamp;
 use tracing::{debug, debug_span, trace, warn, Instrument};
 
-use crate::error::{CodecMismatch, CodecMismatchT};
+use crate::error::{AllowCodecMismatch, CodecMismatch, CodecMismatchT};
 use crate::internal::encoding::{Rollup, UntypedState};
 use crate::internal::machine::{retry_determinate, retry_external};
 use crate::internal::metrics::ShardMetrics;
-use crate::internal::paths::{BlobKey, PartialBlobKey, PartialRollupKey, RollupId};
+use crate::internal::paths::{BlobKey, BlobKeyPrefix, PartialBlobKey, PartialRollupKey, RollupId};
 #[cfg(debug_assertions)]
 use crate::internal::state::HollowBatch;
-use crate::internal::state::{HollowBlobRef, HollowRollup, NoOpStateTransition, State, TypedState};
+use crate::internal::state::{
+    HollowBlobRef, HollowRollup, NoOpStateTransition, ProtoRollup, ProtoStateDiff, State,
+    TypedState,
+};
 use crate::internal::state_diff::{StateDiff, StateFieldValDiff};
-use crate::{Metrics, PersistConfig, ShardId};
+use crate::usage::ShardOp;
+use crate::{
+    Diagnostics, Metrics, PersistConfig, PurgeTombstoneResult, ShardCodecs, ShardId, ShardStatus,
+};
 
 /// A durable, truncatable log of versions of [State].
 ///
@@ -141,9 +147,15 @@ impl StateVersions {
 
     /// Fetches the `current` state of the requested shard, or creates it if
     /// uninitialized.
+    ///
+    /// If this creates the shard, `diagnostics` is recorded in its state as
+    /// the handle that registered the shard's codecs. A codec mismatch with an
+    /// existing shard is returned as an error unless `allow` permits it.
     pub async fn maybe_init_shard<K, V, T, D>(
         &self,
         shard_metrics: &ShardMetrics,
+        diagnostics: &Diagnostics,
+        allow: Option<&AllowCodecMismatch>,
     ) -> Result<TypedState<K, V, T, D>, Box<CodecMismatch>>
     where
         K: Debug + Codec,
@@ -159,15 +171,17 @@ impl StateVersions {
             return self
                 .fetch_current_state(&shard_id, recent_live_diffs.0)
                 .await
-                .check_codecs(&shard_id);
+                .check_codecs_allowing(&shard_id, allow);
         }
 
         // Shard is not initialized, try initializing it.
-        let (initial_state, initial_diff) = self.write_initial_rollup(shard_metrics).await;
+        let (initial_state, initial_diff) =
+            self.write_initial_rollup(shard_metrics, diagnostics).await;
         let (cas_res, _diff) =
             retry_external(&self.metrics.retries.external.maybe_init_cas, || async {
                 self.try_compare_and_set_current(
                     "maybe_init_shard",
+                    diagnostics,
                     shard_metrics,
                     None,
                     &initial_state,
@@ -184,7 +198,7 @@ impl StateVersions {
                 let state = self
                     .fetch_current_state(&shard_id, recent_live_diffs.0)
                     .await
-                    .check_codecs(&shard_id);
+                    .check_codecs_allowing(&shard_id, allow);
 
                 // Clean up the rollup blob that we were trying to reference.
                 //
@@ -220,6 +234,7 @@ impl StateVersions {
     pub async fn try_compare_and_set_current<K, V, T, D>(
         &self,
         cmd_name: &str,
+        diagnostics: &Diagnostics,
         shard_metrics: &ShardMetrics,
         expected: Option<SeqNo>,
         new_state: &TypedState<K, V, T, D>,
@@ -259,9 +274,16 @@ impl StateVersions {
                     .await
             },
         )
-        .instrument(debug_span!("apply_unbatched_cmd::cas", payload_len))
-        .await
-        .map_err(|err| {
+        .instrument(debug_span!(
+            "apply_unbatched_cmd::cas",
+            shard = %new_state.shard_id,
+            shard_name = %diagnostics.shard_name,
+            handle_purpose = %diagnostics.handle_purpose,
+            payload_len
+        ))
+        .await;
+        shard_metrics.record(ShardOp::ConsensusCas);
+        let cas_res = cas_res.map_err(|err| {
             debug!("apply_unbatched_cmd {} errored: {}", cmd_name, err);
             err
         })?;
@@ -286,6 +308,9 @@ impl StateVersions {
                 shard_metrics
                     .spine_batch_count
                     .set(u64::cast_from(new_state.spine_batch_count()));
+                shard_metrics
+                    .uncompacted_bytes
+                    .set(u64::cast_from(new_state.uncompacted_bytes()));
                 let size_metrics = new_state.size_metrics();
                 shard_metrics
                     .hollow_batch_count
@@ -480,9 +505,31 @@ impl StateVersions {
         })
         .instrument(debug_span!("fetch_state::scan"))
         .await;
+        self.record_scan(shard_id, &diffs);
         AllLiveDiffs(diffs)
     }
 
+    /// Returns the seqnos of the earliest and latest live diffs of a shard, or
+    /// None if it's uninitialized.
+    ///
+    /// This is much cheaper than [Self::fetch_all_live_diffs] and is enough to
+    /// tell whether the set of live states of a shard has changed.
+    pub async fn fetch_live_seqnos(&self, shard_id: &ShardId) -> Option<(SeqNo, SeqNo)> {
+        let path = shard_id.to_string();
+        let earliest = retry_external(&self.metrics.retries.external.fetch_state_scan, || async {
+            self.consensus.scan(&path, SeqNo::minimum(), 1).await
+        })
+        .instrument(debug_span!("fetch_state::scan"))
+        .await;
+        let earliest = earliest.first()?.seqno;
+        let latest = retry_external(&self.metrics.retries.external.fetch_state_scan, || async {
+            self.consensus.head(&path).await
+        })
+        .instrument(debug_span!("fetch_state::head"))
+        .await?;
+        Some((earliest, latest.seqno))
+    }
+
     /// Fetches recent live_diffs for a shard. Intended for when a caller needs to fetch
     /// the latest state in Consensus.
     ///
@@ -503,6 +550,7 @@ impl StateVersions {
             })
             .instrument(debug_span!("fetch_state::scan"))
             .await;
+        self.record_scan(shard_id, &oldest_diffs);
 
         // fast-path: we found all known diffs in a single page of our scan. we expect almost all
         // calls to go down this path, unless a reader has a very long seqno-hold on the shard.
@@ -546,6 +594,7 @@ impl StateVersions {
                     })
                     .instrument(debug_span!("fetch_state::slow_path::scan"))
                     .await;
+                self.record_scan(shard_id, &diffs);
                 RecentLiveDiffs(diffs)
             }
             Ok(_) => panic!(
@@ -562,15 +611,23 @@ impl StateVersions {
     /// call within `fetch_recent_live_diffs`.
     pub async fn fetch_all_live_diffs_gt_seqno<K, V, T, D>(
         &self,
+        diagnostics: &Diagnostics,
         shard_id: &ShardId,
         seqno: SeqNo,
     ) -> Vec<VersionedData> {
         let path = shard_id.to_string();
-        retry_external(&self.metrics.retries.external.fetch_state_scan, || async {
+        let diffs = retry_external(&self.metrics.retries.external.fetch_state_scan, || async {
             self.consensus.scan(&path, seqno.next(), SCAN_ALL).await
         })
-        .instrument(debug_span!("fetch_state::scan"))
-        .await
+        .instrument(debug_span!(
+            "fetch_state::scan",
+            shard = %shard_id,
+            shard_name = %diagnostics.shard_name,
+            handle_purpose = %diagnostics.handle_purpose,
+        ))
+        .await;
+        self.record_scan(shard_id, &diffs);
+        diffs
     }
 
     /// Truncates any diffs in consensus less than the given seqno.
@@ -581,6 +638,20 @@ impl StateVersions {
         })
         .instrument(debug_span!("gc::truncate"))
         .await;
+        if let Some(shard_metrics) = self.metrics.shards.get(shard_id) {
+            shard_metrics.record(ShardOp::ConsensusTruncate);
+        }
+    }
+
+    /// Attributes the diffs returned by a scan of Consensus to the shard, if
+    /// it's open in this process.
+    fn record_scan(&self, shard_id: &ShardId, diffs: &[VersionedData]) {
+        if let Some(shard_metrics) = self.metrics.shards.get(shard_id) {
+            let bytes = diffs.iter().map(|diff| diff.data.len()).sum::<usize>();
+            shard_metrics.record(ShardOp::ConsensusScan {
+                bytes: u64::cast_from(bytes),
+            });
+        }
     }
 
     // Writes a self-referential rollup to blob storage and returns the diff
@@ -589,6 +660,7 @@ impl StateVersions {
     async fn write_initial_rollup<K, V, T, D>(
         &self,
         shard_metrics: &ShardMetrics,
+        diagnostics: &Diagnostics,
     ) -> (TypedState<K, V, T, D>, StateDiff<T>)
     where
         K: Debug + Codec,
@@ -596,12 +668,13 @@ impl StateVersions {
         T: Timestamp + Lattice + Codec64,
         D: Semigroup + Codec64,
     {
-        let empty_state = TypedState::new(
+        let mut empty_state = TypedState::new(
             self.cfg.build_version.clone(),
             shard_metrics.shard_id,
             self.cfg.hostname.clone(),
             (self.cfg.now)(),
         );
+        empty_state.registered_by = Some(diagnostics.clone());
         let rollup_seqno = empty_state.seqno.next();
         let rollup = HollowRollup {
             key: PartialRollupKey::new(rollup_seqno, &RollupId::new()),
@@ -630,7 +703,7 @@ impl StateVersions {
             vec![],
             rollup.key,
         );
-        let () = self.write_rollup_blob(&rollup).await;
+        let () = self.write_rollup_blob(diagnostics, &rollup).await;
         assert_eq!(initial_state.seqno, rollup.seqno);
 
         let diff = StateDiff::from_diff(&empty_state.state, &initial_state.state);
@@ -639,6 +712,7 @@ impl StateVersions {
 
     pub async fn write_rollup_for_state<K, V, T, D>(
         &self,
+        diagnostics: &Diagnostics,
         shard_metrics: &ShardMetrics,
         state: TypedState<K, V, T, D>,
         rollup_id: &RollupId,
@@ -656,7 +730,11 @@ impl StateVersions {
         // needing an additional API call here. This would reduce Consensus load
         // / avoid races with Consensus truncation, but is trickier to write.
         let diffs: Vec<_> = self
-            .fetch_all_live_diffs_gt_seqno::<K, V, T, D>(&state.shard_id, *latest_rollup_seqno)
+            .fetch_all_live_diffs_gt_seqno::<K, V, T, D>(
+                diagnostics,
+                &state.shard_id,
+                *latest_rollup_seqno,
+            )
             .await;
 
         match diffs.first() {
@@ -704,7 +782,7 @@ impl StateVersions {
 
         let key = PartialRollupKey::new(state.seqno, rollup_id);
         let rollup = self.encode_rollup_blob(shard_metrics, state, diffs, key);
-        let () = self.write_rollup_blob(&rollup).await;
+        let () = self.write_rollup_blob(diagnostics, &rollup).await;
 
         self.metrics.state.rollup_write_success.inc();
 
@@ -754,7 +832,7 @@ impl StateVersions {
     }
 
     /// Writes the given state rollup out to blob.
-    pub async fn write_rollup_blob(&self, rollup: &EncodedRollup) {
+    pub async fn write_rollup_blob(&self, diagnostics: &Diagnostics, rollup: &EncodedRollup) {
         let payload_len = rollup.buf.len();
         retry_external(&self.metrics.retries.external.rollup_set, || async {
             self.blob
@@ -765,7 +843,13 @@ impl StateVersions {
                 )
                 .await
         })
-        .instrument(debug_span!("rollup::set", payload_len))
+        .instrument(debug_span!(
+            "rollup::set",
+            shard = %rollup.shard_id,
+            shard_name = %diagnostics.shard_name,
+            handle_purpose = %diagnostics.handle_purpose,
+            payload_len
+        ))
         .await;
     }
 
@@ -874,6 +958,208 @@ impl StateVersions {
         .await
         .instrument(debug_span!("rollup::delete"));
     }
+
+    /// Returns the [ShardStatus] of the given shard, without requiring the
+    /// caller to know its types.
+    ///
+    /// Applying the live diffs still requires a real timestamp type, so only
+    /// shards with a `u64` or `i64` ts codec are supported.
+    pub async fn fetch_shard_status(
+        &self,
+        shard_id: &ShardId,
+    ) -> Result<ShardStatus, anyhow::Error> {
+        loop {
+            let live_diffs = self.fetch_recent_live_diffs::<u64>(shard_id).await.0;
+            let Some(latest_diff) = live_diffs.last() else {
+                return Ok(ShardStatus::missing());
+            };
+            // Peek at the ts codec of the latest rollup without decoding any
+            // timestamps.
+            let Some(rollup) = self.fetch_latest_proto_rollup(shard_id, latest_diff).await else {
+                continue;
+            };
+            let ts_codec = rollup.ts_codec;
+            let status = if ts_codec == u64::codec_name() {
+                self.fetch_current_state::<u64>(shard_id, live_diffs)
+                    .await
+                    .shard_status(shard_id)
+            } else if ts_codec == i64::codec_name() {
+                self.fetch_current_state::<i64>(shard_id, live_diffs)
+                    .await
+                    .shard_status(shard_id)
+            } else {
+                return Err(anyhow::anyhow!(
+                    "shard {} has unsupported ts codec {:?}",
+                    shard_id,
+                    ts_codec
+                ));
+            };
+            return Ok(status?);
+        }
+    }
+
+    /// Returns the [ShardCodecs] of the given shard, or None if it has never
+    /// been used, without requiring the caller to know its types.
+    pub async fn fetch_shard_codecs(&self, shard_id: &ShardId) -> Option<ShardCodecs> {
+        loop {
+            let live_diffs = self.fetch_recent_live_diffs::<u64>(shard_id).await.0;
+            let latest_diff = live_diffs.last()?;
+            // The codecs of a shard never change, so the latest rollup is as
+            // good as the current state.
+            let Some(rollup) = self.fetch_latest_proto_rollup(shard_id, latest_diff).await else {
+                continue;
+            };
+            return Some(ShardCodecs {
+                key: rollup.key_codec,
+                val: rollup.val_codec,
+                ts: rollup.ts_codec,
+                diff: rollup.diff_codec,
+                registered_by: rollup
+                    .registered_by
+                    .into_rust()
+                    .expect("internal error: invalid encoded state"),
+            });
+        }
+    }
+
+    /// Fetches the (undecoded) rollup referenced by the given diff.
+    ///
+    /// Returns None if the rollup is gone, in which case the diff must be out
+    /// of date and the caller should fetch the live diffs again.
+    async fn fetch_latest_proto_rollup(
+        &self,
+        shard_id: &ShardId,
+        latest_diff: &VersionedData,
+    ) -> Option<ProtoRollup> {
+        let latest_diff = ProtoStateDiff::decode(latest_diff.data.clone())
+            .expect("internal error: invalid encoded state");
+        let rollup_key = PartialRollupKey(latest_diff.latest_rollup_key);
+        let rollup = retry_external(&self.metrics.retries.external.rollup_get, || async {
+            self.blob.get(&rollup_key.complete(shard_id)).await
+        })
+        .instrument(debug_span!("rollup::get"))
+        .await?;
+        Some(ProtoRollup::decode(rollup).expect("internal error: invalid encoded state"))
+    }
+
+    /// Deletes everything in consensus and blob for the given shard, iff it is
+    /// a tombstone and has been one for at least `older_than`.
+    ///
+    /// The caller is responsible for ensuring that nothing in this process is
+    /// still using the shard.
+    pub async fn purge_tombstone<K, V, T, D>(
+        &self,
+        shard_id: &ShardId,
+        older_than: Duration,
+    ) -> Result<PurgeTombstoneResult, Box<CodecMismatch>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64,
+    {
+        let path = shard_id.to_string();
+        loop {
+            let live_diffs = self.fetch_recent_live_diffs::<T>(shard_id).await.0;
+            if live_diffs.is_empty() {
+                return Ok(PurgeTombstoneResult::NotFound);
+            }
+            let state = self
+                .fetch_current_state::<T>(shard_id, live_diffs)
+                .await
+                .check_codecs::<K, V, D>(shard_id)?;
+            if !state.collections.is_tombstone() {
+                return Ok(PurgeTombstoneResult::NotTombstone);
+            }
+            let age = Duration::from_millis((self.cfg.now)().saturating_sub(state.walltime_ms));
+            if age < older_than {
+                return Ok(PurgeTombstoneResult::TooRecent { age });
+            }
+
+            // List the blobs before deleting the shard from consensus. Once
+            // it's gone, the shard can be re-initialized, and anything written
+            // after that belongs to the new incarnation.
+            let prefix = BlobKeyPrefix::Shard(shard_id).to_string();
+            let keys = retry_external(
+                &self.metrics.retries.external.tombstone_purge_list,
+                || async {
+                    let mut keys = Vec::new();
+                    self.blob
+                        .list_keys_and_metadata(&prefix, &mut |metadata| {
+                            keys.push(metadata.key.to_owned())
+                        })
+                        .await?;
+                    Ok(keys)
+                },
+            )
+            .instrument(debug_span!("tombstone_purge::list"))
+            .await;
+
+            // Deleting isn't idempotent, so we can't blindly retry it: if an
+            // attempt that returned an Indeterminate error actually went
+            // through, the retry would mismatch against (or, at a colliding
+            // seqno, even delete) a re-initialized shard.
+            let res = retry_determinate(
+                &self
+                    .metrics
+                    .retries
+                    .external
+                    .tombstone_purge_consensus_delete,
+                || async { self.consensus.delete(&path, state.seqno).await },
+            )
+            .instrument(debug_span!("tombstone_purge::consensus_delete"))
+            .await;
+            match res {
+                Ok(CaSResult::Committed) => {}
+                // Something (e.g. GC) wrote a new version of the tombstone
+                // since we fetched it. Try again.
+                Ok(CaSResult::ExpectationMismatch) => continue,
+                Err(indeterminate) => {
+                    warn!(
+                        "purging tombstone of shard {} returned an indeterminate error: {}",
+                        shard_id, indeterminate
+                    );
+                    if self.tombstone_still_present::<T>(shard_id, &keys).await {
+                        continue;
+                    }
+                }
+            }
+
+            for key in keys.iter() {
+                retry_external(
+                    &self.metrics.retries.external.tombstone_purge_delete,
+                    || async { self.blob.delete(key).await },
+                )
+                .instrument(debug_span!("tombstone_purge::delete"))
+                .await;
+            }
+            return Ok(PurgeTombstoneResult::Purged {
+                blobs_deleted: keys.len(),
+            });
+        }
+    }
+
+    /// Returns whether the incarnation of the shard whose blobs are `keys` is
+    /// still in consensus, i.e. whether a consensus delete that returned an
+    /// Indeterminate error did not go through.
+    ///
+    /// Every blob referenced by a re-initialized shard is written after the
+    /// delete, so it can't be in `keys`. The old incarnation, on the other
+    /// hand, references at least one rollup that was listed.
+    async fn tombstone_still_present<T>(&self, shard_id: &ShardId, keys: &[String]) -> bool
+    where
+        T: Timestamp + Lattice + Codec64,
+    {
+        let live_diffs = self.fetch_recent_live_diffs::<T>(shard_id).await.0;
+        if live_diffs.is_empty() {
+            return false;
+        }
+        let state = self.fetch_current_state::<T>(shard_id, live_diffs).await;
+        state.rollups().values().any(|rollup| {
+            let key = rollup.key.complete(shard_id);
+            keys.iter().any(|x| x == &*key)
+        })
+    }
 }
 
 pub struct UntypedStateVersionsIter<T> {
@@ -1008,6 +1294,7 @@ impl<T: Timestamp + Lattice + Codec64> StateVersionsIter<T> {
                 seqno: self.state.seqno.clone(),
                 walltime_ms: self.state.walltime_ms.clone(),
                 hostname: self.state.hostname.clone(),
+                registered_by: self.state.registered_by.clone(),
                 collections: self.state.collections.clone(),
             },
             self.key_codec.clone(),
@@ -1131,12 +1418,7 @@ mod tests {
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn fetch_all_live_states_regression_uninitialized() {
         let client = new_test_client().await;
-        let state_versions = StateVersions::new(
-            client.cfg.clone(),
-            Arc::clone(&client.consensus),
-            Arc::clone(&client.blob),
-            Arc::clone(&client.metrics),
-        );
+        let state_versions = client.state_versions();
         assert!(state_versions
             .fetch_all_live_states::<u64>(ShardId::new())
             .await
@@ -141,6 +141,26 @@ impl<T> Trace<T> {
         ret
     }
 
+    /// The encoded size of the batches that are waiting on an outstanding
+    /// compaction request.
+    pub fn uncompacted_bytes(&self) -> usize {
+        let mut ret = 0;
+        self.spine.map_batches(|b| match b {
+            SpineBatch::Merged(_) => {}
+            SpineBatch::Fueled { parts, .. } => {
+                for b in parts.iter() {
+                    ret += b
+                        .batch
+                        .parts
+                        .iter()
+                        .map(|x| x.encoded_size_bytes)
+                        .sum::<usize>();
+                }
+            }
+        });
+        ret
+    }
+
     #[cfg(test)]
     pub fn num_hollow_batches(&self) -> usize {
         let mut ret = 0;
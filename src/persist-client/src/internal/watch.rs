@@ -12,7 +12,7 @@
 use std::sync::Arc;
 
 use mz_persist::location::SeqNo;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 use tracing::debug;
 
 use crate::cache::LockingTypedState;
@@ -72,6 +72,7 @@ pub struct StateWatch<K, V, T, D> {
     state: Arc<LockingTypedState<K, V, T, D>>,
     seqno_high_water: SeqNo,
     rx: broadcast::Receiver<SeqNo>,
+    resubscribes: watch::Receiver<()>,
 }
 
 impl<K, V, T, D> StateWatch<K, V, T, D> {
@@ -84,11 +85,13 @@ impl<K, V, T, D> StateWatch<K, V, T, D> {
         // seqno_high_water we get here.
         let rx = state.notifier().tx.subscribe();
         let seqno_high_water = state.read_lock(&metrics.locks.watch, |x| x.seqno);
+        let resubscribes = state.subscription_token().resubscribes();
         StateWatch {
             metrics,
             state,
             seqno_high_water,
             rx,
+            resubscribes,
         }
     }
 
@@ -130,6 +133,60 @@ impl<K, V, T, D> StateWatch<K, V, T, D> {
         );
         self
     }
+
+    /// Like [Self::wait_for_seqno_ge], but also returns early if the shard's
+    /// PubSub subscription was re-established since this watch was created or
+    /// last returned because of it. Diffs pushed
+    /// while it wasn't might have been missed, so the caller should then
+    /// refresh the state from consensus instead of waiting for them.
+    ///
+    /// Returns whether the State has a SeqNo >= the requested one.
+    ///
+    /// This method is cancel-safe.
+    pub async fn wait_for_seqno_ge_or_resubscribe(
+        &mut self,
+        requested: SeqNo,
+    ) -> (&mut Self, bool) {
+        // Wait on a copy of the receiver, which remembers what the original
+        // has seen, because `wait_for_seqno_ge` borrows all of self. Only copy
+        // it back once we're done, so a cancelled wait doesn't lose anything.
+        let mut resubscribes = self.resubscribes.clone();
+        let reached = tokio::select! {
+            _ = self.wait_for_seqno_ge(requested) => true,
+            _ = resubscribes.changed() => false,
+        };
+        self.resubscribes = resubscribes;
+        if !reached {
+            self.metrics.watch.notify_resubscribed.inc();
+        }
+        (self, reached)
+    }
+
+    /// Returns whether the State has a SeqNo greater than the one this watch
+    /// last observed, without blocking.
+    pub(crate) fn try_advance(&mut self) -> bool {
+        let mut advanced = false;
+        loop {
+            match self.rx.try_recv() {
+                Ok(x) => {
+                    self.metrics.watch.notify_recv.inc();
+                    assert!(x >= self.seqno_high_water);
+                    advanced |= x > self.seqno_high_water;
+                    self.seqno_high_water = x;
+                }
+                Err(broadcast::error::TryRecvError::Empty) => return advanced,
+                Err(broadcast::error::TryRecvError::Closed) => {
+                    unreachable!("we're holding on to a reference to the sender")
+                }
+                Err(broadcast::error::TryRecvError::Lagged(_)) => {
+                    // See the comment in `wait_for_seqno_ge`: the most recent
+                    // value is still available, so just loop around.
+                    self.metrics.watch.notify_lagged.inc();
+                    continue;
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -204,6 +261,69 @@ mod tests {
         assert!(w1.wait_for_seqno_ge(SeqNo(2)).now_or_never().is_none());
     }
 
+    #[mz_ore::test(tokio::test)]
+    async fn state_watch_resubscribe() {
+        mz_ore::test::init_logging();
+        let metrics = Arc::new(Metrics::new(
+            &PersistConfig::new_for_tests(),
+            &MetricsRegistry::new(),
+        ));
+        let cache = StateCache::new_no_metrics();
+        let shard_id = ShardId::new();
+        let state = cache
+            .get::<(), (), u64, i64, _, _>(
+                shard_id,
+                || async {
+                    Ok(TypedState::new(
+                        DUMMY_BUILD_INFO.semver_version(),
+                        shard_id,
+                        "host".to_owned(),
+                        0u64,
+                    ))
+                },
+                &Diagnostics::for_tests(),
+            )
+            .await
+            .unwrap();
+
+        // Nothing happened yet, so a watch for 1 doesn't resolve.
+        let mut w0 = StateWatch::new(Arc::clone(&state), Arc::clone(&metrics));
+        assert!(w0
+            .wait_for_seqno_ge_or_resubscribe(SeqNo(1))
+            .now_or_never()
+            .is_none());
+
+        // A re-subscription while nobody is waiting isn't lost: the next wait
+        // returns because of it, but only that one.
+        state.subscription_token().notify_resubscribed();
+        let (_, reached) = w0
+            .wait_for_seqno_ge_or_resubscribe(SeqNo(1))
+            .now_or_never()
+            .expect("resubscribe was missed");
+        assert!(!reached);
+        assert!(w0
+            .wait_for_seqno_ge_or_resubscribe(SeqNo(1))
+            .now_or_never()
+            .is_none());
+
+        // Watches created after a re-subscription don't see it.
+        let mut w1 = StateWatch::new(Arc::clone(&state), Arc::clone(&metrics));
+        assert!(w1
+            .wait_for_seqno_ge_or_resubscribe(SeqNo(1))
+            .now_or_never()
+            .is_none());
+
+        // The seqno still resolves the wait as usual.
+        state.write_lock(&metrics.locks.applier_write, |state| {
+            state.seqno = state.seqno.next()
+        });
+        let (_, reached) = w1
+            .wait_for_seqno_ge_or_resubscribe(SeqNo(1))
+            .now_or_never()
+            .expect("seqno was reached");
+        assert!(reached);
+    }
+
     #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
     #[cfg_attr(miri, ignore)] // error: unsupported operation: integer-to-pointer casts and `ptr::from_exposed_addr` are not supported with `-Zmiri-strict-provenance`
     async fn state_watch_concurrency() {
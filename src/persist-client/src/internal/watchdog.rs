@@ -0,0 +1,209 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Logging of persist operations that take longer than expected.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use mz_ore::cast::CastFrom;
+use tracing::warn;
+
+use crate::dyn_cfg::Config;
+use crate::internal::metrics::CmdMetrics;
+use crate::{PersistConfig, ShardId};
+
+pub(crate) const SLOW_OP_THRESHOLDS: Config<String> = Config::new(
+    "persist_slow_op_thresholds",
+    "",
+    "A comma-separated list of `op=millis` or `op@purpose_prefix=millis` \
+    entries. A persist command taking longer than the threshold for its \
+    operation (and the longest matching handle purpose prefix, if any) logs a \
+    warning. Empty to disable.",
+);
+
+pub(crate) const SLOW_OP_WARN_INTERVAL_MS: Config<usize> = Config::new(
+    "persist_slow_op_warn_interval_ms",
+    60_000,
+    "The minimum interval (in milliseconds) between slow operation warnings \
+    for the same shard and operation.",
+);
+
+/// Parsed form of [SLOW_OP_THRESHOLDS].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct SlowOpThresholds {
+    /// Keyed by (operation, handle purpose prefix). An entry without a purpose
+    /// prefix is stored with an empty prefix, which matches every purpose.
+    thresholds: BTreeMap<(String, String), Duration>,
+}
+
+impl SlowOpThresholds {
+    /// Parses the thresholds from their dyncfg representation.
+    ///
+    /// Invalid entries are ignored, so that a typo in one of them doesn't
+    /// disable the others.
+    pub fn parse(s: &str) -> Self {
+        let mut thresholds = BTreeMap::new();
+        for entry in s.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let Some((key, millis)) = entry.split_once('=') else {
+                continue;
+            };
+            let Ok(millis) = millis.trim().parse::<u64>() else {
+                continue;
+            };
+            let (op, purpose) = key.split_once('@').unwrap_or((key, ""));
+            thresholds.insert(
+                (op.trim().to_owned(), purpose.trim().to_owned()),
+                Duration::from_millis(millis),
+            );
+        }
+        SlowOpThresholds { thresholds }
+    }
+
+    /// Returns the threshold for `op` run by a handle with the given
+    /// `purpose`, if one is configured.
+    ///
+    /// The entry with the longest purpose prefix matching `purpose` wins.
+    pub fn threshold(&self, op: &str, purpose: &str) -> Option<Duration> {
+        self.thresholds
+            .iter()
+            .filter(|((x, prefix), _)| x == op && purpose.starts_with(prefix.as_str()))
+            .max_by_key(|((_, prefix), _)| prefix.len())
+            .map(|(_, threshold)| *threshold)
+    }
+}
+
+/// Rate-limits slow operation warnings for a single shard.
+#[derive(Debug, Default)]
+pub(crate) struct SlowOpWatchdog {
+    /// The value of [SLOW_OP_THRESHOLDS] the cached thresholds were parsed
+    /// from, so that they're only re-parsed when the config changes.
+    thresholds: Mutex<(String, Arc<SlowOpThresholds>)>,
+    last_warned: Mutex<BTreeMap<String, Instant>>,
+}
+
+impl SlowOpWatchdog {
+    /// Returns the currently configured thresholds, or None if the watchdog
+    /// is disabled.
+    fn thresholds(&self, cfg: &PersistConfig) -> Option<Arc<SlowOpThresholds>> {
+        let raw = SLOW_OP_THRESHOLDS.shared(&cfg.configs);
+        let raw = raw.read().expect("lock poisoned");
+        // Fast path for the (default) case where the watchdog is disabled.
+        if raw.is_empty() {
+            return None;
+        }
+        let mut cached = self.thresholds.lock().expect("mutex poisoned");
+        if cached.0 != *raw {
+            *cached = (raw.clone(), Arc::new(SlowOpThresholds::parse(&raw)));
+        }
+        Some(Arc::clone(&cached.1))
+    }
+
+    /// Checks a completed run of `cmd` against the configured thresholds,
+    /// logging a warning if it exceeded its threshold and no warning has been
+    /// logged for the same operation within the warn interval.
+    ///
+    /// Returns whether a warning was logged.
+    pub(crate) fn observe(
+        &self,
+        cfg: &PersistConfig,
+        cmd: &CmdMetrics,
+        shard_id: &ShardId,
+        purpose: &str,
+        elapsed: Duration,
+        attempts: usize,
+    ) -> bool {
+        let Some(threshold) = self
+            .thresholds(cfg)
+            .and_then(|x| x.threshold(&cmd.name, purpose))
+        else {
+            return false;
+        };
+        if elapsed < threshold {
+            return false;
+        }
+        cmd.slow.inc();
+
+        let interval =
+            Duration::from_millis(u64::cast_from(SLOW_OP_WARN_INTERVAL_MS.get(&cfg.configs)));
+        let now = Instant::now();
+        let mut last_warned = self.last_warned.lock().expect("mutex poisoned");
+        if let Some(last) = last_warned.get(&cmd.name) {
+            if now.saturating_duration_since(*last) < interval {
+                return false;
+            }
+        }
+        last_warned.insert(cmd.name.clone(), now);
+        drop(last_warned);
+
+        cmd.slow_warned.inc();
+        warn!(
+            shard_id = %shard_id,
+            purpose,
+            op = %cmd.name,
+            duration = ?elapsed,
+            threshold = ?threshold,
+            attempts,
+            "slow persist operation",
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[mz_ore::test]
+    fn slow_op_thresholds() {
+        let thresholds = SlowOpThresholds::parse(
+            "compare_and_append=100, compare_and_append@compute=50,\
+            compare_and_append@compute::sink=10,register=bogus,heartbeat_reader",
+        );
+        let ms = Duration::from_millis;
+        assert_eq!(
+            thresholds.threshold("compare_and_append", "storage"),
+            Some(ms(100))
+        );
+        assert_eq!(
+            thresholds.threshold("compare_and_append", "compute::source"),
+            Some(ms(50))
+        );
+        assert_eq!(
+            thresholds.threshold("compare_and_append", "compute::sink 1"),
+            Some(ms(10))
+        );
+        assert_eq!(thresholds.threshold("register", "storage"), None);
+        assert_eq!(thresholds.threshold("heartbeat_reader", "storage"), None);
+        assert_eq!(thresholds.threshold("downgrade_since", "storage"), None);
+        assert_eq!(SlowOpThresholds::parse(""), SlowOpThresholds::default());
+    }
+
+    #[mz_ore::test]
+    fn slow_op_thresholds_cached() {
+        let cfg = PersistConfig::new_for_tests();
+        let watchdog = SlowOpWatchdog::default();
+        assert_eq!(watchdog.thresholds(&cfg), None);
+
+        // The thresholds are only parsed again once the config changes.
+        cfg.set_config(&SLOW_OP_THRESHOLDS, "compare_and_append=100".to_owned());
+        let thresholds = watchdog.thresholds(&cfg).expect("enabled");
+        assert!(Arc::ptr_eq(
+            &thresholds,
+            &watchdog.thresholds(&cfg).expect("enabled")
+        ));
+        cfg.set_config(&SLOW_OP_THRESHOLDS, "compare_and_append=50".to_owned());
+        let thresholds = watchdog.thresholds(&cfg).expect("enabled");
+        assert_eq!(
+            thresholds.threshold("compare_and_append", "storage"),
+            Some(Duration::from_millis(50))
+        );
+    }
+}
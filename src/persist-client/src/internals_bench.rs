@@ -12,41 +12,395 @@
 #![allow(missing_docs)]
 
 use std::hint::black_box;
+use std::ops::ControlFlow::{Break, Continue};
+use std::sync::Arc;
 use std::time::Instant;
 
+use bytes::Bytes;
 use differential_dataflow::trace::Description;
-use timely::progress::Antichain;
+use mz_ore::metrics::MetricsRegistry;
+use mz_persist::location::{SeqNo, VersionedData};
+use mz_persist_types::codec_impls::VecU8Schema;
+use mz_proto::RustType;
+use prost::Message;
+use timely::progress::{Antichain, Timestamp};
 use tracing::info;
+use uuid::Uuid;
 
-use crate::internal::state::HollowBatch;
+use crate::internal::encoding::{Rollup, UntypedState};
+use crate::internal::metrics::Metrics;
+use crate::internal::paths::{PartialBatchKey, PartialRollupKey, RollupId};
+use crate::internal::state::{
+    HandleDebugState, HollowBatch, HollowBatchPart, HollowRollup, IdempotencyToken, TypedState,
+    WriterState,
+};
+use crate::internal::state_diff::StateDiff;
 use crate::internal::trace::Trace;
+use crate::read::LeasedReaderId;
+use crate::write::WriterId;
+use crate::{Diagnostics, PersistClient, PersistConfig, ShardId};
 
 pub fn trace_push_batch_one_iter(num_batches: usize) {
+    black_box(trace_push_batch_pattern_one_iter(
+        SpinePattern::OneNonEmptyThenEmpty,
+        num_batches,
+    ));
+}
+
+/// A sequence of batch sizes to push into a [Trace].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpinePattern {
+    /// A single non-empty batch followed by a large number of empty batches
+    /// and no compaction. This is a particularly problematic workload for our
+    /// fork of Spine which came up during deserialization of State in #17214.
+    OneNonEmptyThenEmpty,
+    /// Only empty batches.
+    AllEmpty,
+    /// Only non-empty batches of the same size.
+    AllNonEmpty,
+    /// Non-empty batches interleaved with empty ones.
+    Alternating,
+    /// Non-empty batches whose sizes cycle through powers of two, so that
+    /// neighboring batches are rarely of a similar size.
+    Sawtooth,
+}
+
+impl SpinePattern {
+    pub const ALL: &'static [SpinePattern] = &[
+        SpinePattern::OneNonEmptyThenEmpty,
+        SpinePattern::AllEmpty,
+        SpinePattern::AllNonEmpty,
+        SpinePattern::Alternating,
+        SpinePattern::Sawtooth,
+    ];
+
+    /// The number of updates in the batch at the given index.
+    fn batch_len(&self, idx: usize) -> usize {
+        match self {
+            SpinePattern::OneNonEmptyThenEmpty => usize::from(idx == 0),
+            SpinePattern::AllEmpty => 0,
+            SpinePattern::AllNonEmpty => 1,
+            SpinePattern::Alternating => idx % 2,
+            SpinePattern::Sawtooth => 1 << (idx % 16),
+        }
+    }
+}
+
+/// Pushes `num_batches` batches following `pattern` into an empty [Trace],
+/// without compaction, and returns the number of merge requests generated.
+pub fn trace_push_batch_pattern_one_iter(pattern: SpinePattern, num_batches: usize) -> usize {
     let mut trace = Trace::<usize>::default();
+    let mut merge_reqs = 0;
     let mut start = Instant::now();
     for ts in 0..num_batches {
         if ts % 1000 == 0 {
-            info!("{} {:?}", ts, start.elapsed());
+            info!("{:?} {} {:?}", pattern, ts, start.elapsed());
             start = Instant::now();
         }
-        // A single non-empty batch followed by a large number of empty batches
-        // and no compaction. This is a particularly problematic workload for
-        // our fork of Spine which came up during deserialization of State in
-        // #17214.
-        //
-        // Other, much better handled, workloads include all empty or all
-        // non-empty.
-        let len = if ts == 0 { 1 } else { 0 };
-        let _ = trace.push_batch(HollowBatch {
+        merge_reqs += trace
+            .push_batch(HollowBatch {
+                desc: Description::new(
+                    Antichain::from_elem(ts),
+                    Antichain::from_elem(ts + 1),
+                    Antichain::from_elem(0),
+                ),
+                parts: vec![],
+                len: pattern.batch_len(ts),
+                runs: vec![],
+            })
+            .len();
+    }
+    black_box(trace);
+    merge_reqs
+}
+
+/// The size of a synthetic shard state built by [StateFixture::new].
+#[derive(Debug, Clone, Copy)]
+pub struct StateParams {
+    /// The number of batches in the shard's trace.
+    pub num_batches: usize,
+    /// The number of parts in each batch.
+    pub num_parts_per_batch: usize,
+    /// The number of registered leased readers.
+    pub num_readers: usize,
+    /// The number of registered writers, in addition to the one appending the
+    /// batches.
+    pub num_writers: usize,
+}
+
+/// A synthetic shard state, for benchmarking state transitions and encoding
+/// without going through Blob or Consensus.
+pub struct StateFixture {
+    cfg: PersistConfig,
+    metrics: Metrics,
+    writer_id: WriterId,
+    state: TypedState<Vec<u8>, Vec<u8>, u64, i64>,
+}
+
+/// A copy of the state in a [StateFixture], to be consumed by a benchmark
+/// iteration.
+pub struct FixtureState(TypedState<Vec<u8>, Vec<u8>, u64, i64>);
+
+impl StateFixture {
+    const LEASE_DURATION_MS: u64 = 15 * 60 * 1000;
+
+    /// Returns a state with the given size, as of its last append.
+    pub fn new(params: StateParams) -> Self {
+        let cfg = PersistConfig::new_for_tests();
+        let metrics = Metrics::new(&cfg, &MetricsRegistry::new());
+        let mut state = TypedState::new(
+            cfg.build_version.clone(),
+            ShardId::new(),
+            cfg.hostname.clone(),
+            (cfg.now)(),
+        );
+        let now = (cfg.now)();
+
+        // Every state after the initial one has at least one rollup.
+        let rollup = HollowRollup {
+            key: PartialRollupKey::new(SeqNo::minimum(), &RollupId::new()),
+            encoded_size_bytes: None,
+        };
+        let _ = state.collections.add_rollup((SeqNo::minimum(), &rollup));
+        for _ in 0..params.num_readers {
+            let _ = state.collections.register_leased_reader(
+                &cfg.hostname,
+                &LeasedReaderId::new(),
+                "bench",
+                SeqNo::minimum(),
+                cfg.dynamic.reader_lease_duration(),
+                now,
+            );
+        }
+        for _ in 0..params.num_writers {
+            state.collections.writers.insert(
+                WriterId::new(),
+                WriterState {
+                    last_heartbeat_timestamp_ms: now,
+                    lease_duration_ms: Self::LEASE_DURATION_MS,
+                    most_recent_write_token: IdempotencyToken::new(),
+                    most_recent_write_upper: Antichain::from_elem(u64::minimum()),
+                    debug: Self::debug_state(&cfg),
+                },
+            );
+        }
+
+        let mut fixture = StateFixture {
+            cfg,
+            metrics,
+            writer_id: WriterId::new(),
+            state,
+        };
+        for _ in 0..params.num_batches {
+            let batch = fixture.next_batch(params.num_parts_per_batch);
+            let res = fixture.state.collections.compare_and_append(
+                &batch,
+                &fixture.writer_id,
+                now,
+                Self::LEASE_DURATION_MS,
+                &IdempotencyToken::new(),
+                &Self::debug_state(&fixture.cfg),
+            );
+            assert!(res.is_continue(), "fixture batches should append cleanly");
+        }
+        fixture
+    }
+
+    /// Returns a copy of the fixture's state.
+    pub fn state(&self) -> FixtureState {
+        FixtureState(self.state.clone_for_rollup())
+    }
+
+    /// The number of batches in the fixture's trace.
+    pub fn num_batches(&self) -> usize {
+        self.state.collections.trace.num_hollow_batches()
+    }
+
+    /// Returns `num_diffs` encoded state diffs, each of which appends a new
+    /// batch with `num_parts` parts on top of the previous one, starting at
+    /// the fixture's state.
+    pub fn diffs(&self, num_diffs: usize, num_parts: usize) -> Vec<VersionedData> {
+        let mut state = self.state.clone_for_rollup();
+        let mut diffs = Vec::with_capacity(num_diffs);
+        for _ in 0..num_diffs {
+            let batch = Self::batch_at(state.upper(), num_parts);
+            let (_, new_state) = self.compare_and_append(&state, &batch);
+            let diff = StateDiff::from_diff(&state.state, &new_state.state);
+            let mut buf = Vec::new();
+            diff.encode(&mut buf);
+            diffs.push(VersionedData {
+                seqno: new_state.seqno,
+                data: Bytes::from(buf),
+            });
+            state = new_state;
+        }
+        diffs
+    }
+
+    /// Applies the given encoded diffs, as returned by [Self::diffs], to a
+    /// copy of the fixture's state.
+    pub fn apply_diffs_one_iter(&self, state: FixtureState, diffs: &[VersionedData]) {
+        let FixtureState(mut state) = state;
+        state.apply_encoded_diffs(&self.cfg, &self.metrics, diffs);
+        assert_eq!(Some(state.seqno), diffs.last().map(|x| x.seqno));
+        black_box(state);
+    }
+
+    /// Computes the state transition of appending a single new batch to the
+    /// fixture's state, without modifying it.
+    ///
+    /// Returns the number of merge requests generated by the append.
+    pub fn compare_and_append_one_iter(&self) -> usize {
+        let batch = Self::batch_at(self.state.upper(), 1);
+        let (merge_reqs, new_state) = self.compare_and_append(&self.state, &batch);
+        black_box(new_state);
+        merge_reqs
+    }
+
+    /// Encodes the fixture's state as a rollup.
+    pub fn encode_one_iter(&self) -> Bytes {
+        let rollup = Rollup::from_untyped_state_without_diffs(UntypedState::from(
+            self.state.clone_for_rollup(),
+        ));
+        let mut buf = Vec::new();
+        rollup
+            .into_proto()
+            .encode(&mut buf)
+            .expect("no required fields means no initialization errors");
+        Bytes::from(buf)
+    }
+
+    /// Decodes a rollup returned by [Self::encode_one_iter].
+    pub fn decode_one_iter(&self, buf: &Bytes) {
+        let state = UntypedState::<u64>::decode(&self.cfg.build_version, Bytes::clone(buf))
+            .check_codecs::<Vec<u8>, Vec<u8>, i64>(&self.state.shard_id)
+            .expect("codecs should match");
+        assert_eq!(state.seqno, self.state.seqno);
+        black_box(state);
+    }
+
+    fn compare_and_append(
+        &self,
+        state: &TypedState<Vec<u8>, Vec<u8>, u64, i64>,
+        batch: &HollowBatch<u64>,
+    ) -> (usize, TypedState<Vec<u8>, Vec<u8>, u64, i64>) {
+        let res = state.clone_apply(&self.cfg, &mut |_seqno, cfg, state| {
+            state.compare_and_append(
+                batch,
+                &self.writer_id,
+                (cfg.now)(),
+                Self::LEASE_DURATION_MS,
+                &IdempotencyToken::new(),
+                &Self::debug_state(cfg),
+            )
+        });
+        match res {
+            Continue((merge_reqs, new_state)) => (merge_reqs.len(), new_state),
+            Break(err) => panic!("unexpected compare_and_append failure: {:?}", err),
+        }
+    }
+
+    fn next_batch(&self, num_parts: usize) -> HollowBatch<u64> {
+        Self::batch_at(self.state.upper(), num_parts)
+    }
+
+    /// Returns a batch of `num_parts` parts, with one update each, that
+    /// advances the given shard upper by one.
+    fn batch_at(upper: &Antichain<u64>, num_parts: usize) -> HollowBatch<u64> {
+        let lower = *upper.as_option().expect("shard should not be closed");
+        HollowBatch {
             desc: Description::new(
-                Antichain::from_elem(ts),
-                Antichain::from_elem(ts + 1),
-                Antichain::from_elem(0),
+                Antichain::from_elem(lower),
+                Antichain::from_elem(lower + 1),
+                Antichain::from_elem(u64::minimum()),
             ),
-            parts: vec![],
-            len,
+            parts: (0..num_parts)
+                .map(|_| HollowBatchPart {
+                    key: PartialBatchKey(Uuid::new_v4().to_string()),
+                    encoded_size_bytes: 1024,
+                    key_lower: vec![],
+                    stats: None,
+                    schema_id: None,
+                    checksum: None,
+                })
+                .collect(),
+            len: num_parts,
             runs: vec![],
+        }
+    }
+
+    fn debug_state(cfg: &PersistConfig) -> HandleDebugState {
+        HandleDebugState {
+            hostname: cfg.hostname.clone(),
+            purpose: "bench".to_owned(),
+        }
+    }
+}
+
+/// Writes `updates` into a batch of a new shard, either one at a time with
+/// [crate::batch::BatchBuilder::add] or all at once with
+/// [crate::batch::BatchBuilder::add_many], and then deletes the batch.
+pub async fn batch_builder_add_one_iter(
+    client: &PersistClient,
+    updates: &[((Vec<u8>, Vec<u8>), u64, i64)],
+    add_many: bool,
+) {
+    let mut write = client
+        .open_writer::<Vec<u8>, Vec<u8>, u64, i64>(
+            ShardId::new(),
+            Arc::new(VecU8Schema),
+            Arc::new(VecU8Schema),
+            Diagnostics::for_tests(),
+        )
+        .await
+        .expect("codecs should match");
+    let mut builder = write.builder(Antichain::from_elem(0));
+    if add_many {
+        builder.add_many(updates).await.expect("invalid usage");
+    } else {
+        for ((k, v), t, d) in updates {
+            builder.add(k, v, t, d).await.expect("invalid usage");
+        }
+    }
+    let batch = builder
+        .finish(Antichain::new())
+        .await
+        .expect("invalid usage");
+    black_box(batch.batch.len);
+    batch.delete().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs the setup and a single iteration of each benchmark with small
+    // sizes, so that the benchmarks don't bit-rot.
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn bench_smoke() {
+        for pattern in SpinePattern::ALL {
+            let _ = trace_push_batch_pattern_one_iter(*pattern, 100);
+        }
+
+        let fixture = StateFixture::new(StateParams {
+            num_batches: 20,
+            num_parts_per_batch: 2,
+            num_readers: 3,
+            num_writers: 3,
         });
+        assert_eq!(fixture.num_batches(), 20);
+        assert_eq!(fixture.state.collections.leased_readers.len(), 3);
+        // The appending writer is registered in addition to the idle ones.
+        assert_eq!(fixture.state.collections.writers.len(), 4);
+
+        let diffs = fixture.diffs(5, 2);
+        assert_eq!(diffs.len(), 5);
+        fixture.apply_diffs_one_iter(fixture.state(), &diffs);
+
+        let _ = fixture.compare_and_append_one_iter();
+
+        let buf = fixture.encode_one_iter();
+        fixture.decode_one_iter(&buf);
     }
-    black_box(trace);
 }
@@ -31,7 +31,9 @@ use semver::Version;
 use timely::progress::Timestamp;
 use tracing::{debug_span, Instrument};
 
-use crate::fetch::{fetch_batch_part, Cursor, EncodedPart, FetchBatchFilter, LeasedBatchPart};
+use crate::fetch::{
+    fetch_batch_part, retry_leased_fetch, Cursor, EncodedPart, FetchBatchFilter, LeasedBatchPart,
+};
 use crate::internal::metrics::{BatchPartReadMetrics, ReadMetrics, ShardMetrics};
 use crate::internal::paths::{PartialBatchKey, WriterKey};
 use crate::internal::state::HollowBatchPart;
@@ -44,6 +46,31 @@ use crate::ShardId;
 /// according to the current definition.
 pub const MINIMUM_CONSOLIDATED_VERSION: Version = Version::new(0, 67, 0);
 
+/// Statistics about the work done by a [Consolidator] while merging its runs.
+///
+/// A large gap between `updates_in` and `updates_out` means the data read
+/// consolidates well, which is a sign that the shard could use compaction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConsolidationStats {
+    /// The number of non-empty runs enqueued for merging.
+    pub runs_merged: usize,
+    /// The number of updates read from the fetched parts.
+    pub updates_in: usize,
+    /// The number of updates returned after consolidation.
+    pub updates_out: usize,
+    /// The encoded size of the fetched parts.
+    pub bytes_processed: usize,
+}
+
+impl ConsolidationStats {
+    fn merge(&mut self, other: &ConsolidationStats) {
+        self.runs_merged += other.runs_merged;
+        self.updates_in += other.updates_in;
+        self.updates_out += other.updates_out;
+        self.bytes_processed += other.bytes_processed;
+    }
+}
+
 type Tuple<T, D> = ((Vec<u8>, Vec<u8>), T, D);
 type TupleRef<'a, T, D> = (&'a [u8], &'a [u8], T, D);
 
@@ -67,6 +94,7 @@ pub(crate) enum FetchData<T> {
         shard_metrics: Arc<ShardMetrics>,
         part_key: PartialBatchKey,
         part_desc: Description<T>,
+        part_checksum: Option<u32>,
         key_lower: Vec<u8>,
     },
     Leased {
@@ -110,6 +138,7 @@ impl<T: Codec64 + Timestamp + Lattice> FetchData<T> {
                 shard_metrics,
                 part_key,
                 part_desc,
+                part_checksum,
                 ..
             } => fetch_batch_part(
                 &shard_id,
@@ -119,9 +148,10 @@ impl<T: Codec64 + Timestamp + Lattice> FetchData<T> {
                 read_metrics(&metrics.read),
                 &part_key,
                 &part_desc,
+                part_checksum,
             )
             .await
-            .map_err(|blob_key| anyhow!("missing unleased key {blob_key}")),
+            .map_err(|err| anyhow!("could not fetch batch part: {err}")),
             FetchData::Leased {
                 blob,
                 read_metrics,
@@ -131,17 +161,20 @@ impl<T: Codec64 + Timestamp + Lattice> FetchData<T> {
             } => {
                 // We do not use fetch_leased_part, since that requires more type info
                 // than we have available here.
-                let fetched = fetch_batch_part(
-                    &part.shard_id,
-                    &*blob,
-                    &part.metrics,
-                    &*shard_metrics,
-                    read_metrics(&part.metrics.read),
-                    &part.key,
-                    &part.desc,
-                )
+                let fetched = retry_leased_fetch("consolidator", || {
+                    fetch_batch_part(
+                        &part.shard_id,
+                        &*blob,
+                        &part.metrics,
+                        &*shard_metrics,
+                        read_metrics(&part.metrics.read),
+                        &part.key,
+                        &part.desc,
+                        part.checksum,
+                    )
+                })
                 .await
-                .map_err(|blob_key| anyhow!("missing unleased key {blob_key}"));
+                .map_err(|err| anyhow!("could not fetch batch part: {err}"));
                 lease_returner.return_leased_part(part);
                 fetched
             }
@@ -175,10 +208,20 @@ impl<'a, T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidation
         part: EncodedPart<T>,
         filter: &'a FetchBatchFilter<T>,
         maybe_unconsolidated: bool,
+        stats: &mut ConsolidationStats,
     ) -> Self {
         let mut cursor = Cursor::default();
         if part.maybe_unconsolidated() || maybe_unconsolidated {
-            Self::from_iter(ConsolidationPartIter::encoded(&part, &mut cursor, filter))
+            let mut updates = 0;
+            let iter = ConsolidationPartIter::encoded(&part, &mut cursor, filter)
+                .inspect(|_| updates += 1);
+            let part = Self::from_iter(iter);
+            // The updates that survive this pre-consolidation are counted
+            // when they're merged, so only count the ones it folded away.
+            if let ConsolidationPart::Sorted { data, .. } = &part {
+                stats.updates_in += updates - data.len();
+            }
+            part
         } else {
             ConsolidationPart::Encoded { part, cursor }
         }
@@ -251,6 +294,7 @@ pub(crate) struct Consolidator<T, D> {
     // iter is dropped.
     initial_state: Option<Tuple<T, D>>,
     drop_stash: Option<Tuple<T, D>>,
+    stats: ConsolidationStats,
 }
 
 impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D> {
@@ -269,6 +313,7 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
             budget: prefetch_budget_bytes,
             initial_state: None,
             drop_stash: None,
+            stats: ConsolidationStats::default(),
         }
     }
 
@@ -301,13 +346,14 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
                         shard_metrics: Arc::clone(shard_metrics),
                         part_key: part.key.clone(),
                         part_desc: desc.clone(),
+                        part_checksum: part.checksum,
                         key_lower: part.key_lower.clone(),
                     },
                 };
                 (c_part, part.encoded_size_bytes)
             })
             .collect();
-        self.runs.push(run);
+        self.push_run(run);
     }
 
     /// Add a leased run of data to be consolidated.
@@ -335,6 +381,13 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
                 (queued, size)
             })
             .collect();
+        self.push_run(run);
+    }
+
+    fn push_run(&mut self, run: VecDeque<(ConsolidationPart<T, D>, usize)>) {
+        if !run.is_empty() {
+            self.stats.runs_merged += 1;
+        }
         self.runs.push(run);
     }
 
@@ -366,6 +419,7 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
         let mut iter = ConsolidatingIter::new(
             self.initial_state.as_ref().map(borrow_tuple),
             &mut self.drop_stash,
+            &mut self.stats,
         );
 
         for run in &mut self.runs {
@@ -418,11 +472,12 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
             .runs
             .iter_mut()
             .map(|run| async {
-                let part = &mut run.front_mut().expect("trimmed run should be nonempty").0;
+                let (part, size) = run.front_mut().expect("trimmed run should be nonempty");
                 match part.kvt_lower() {
-                    Some(lower) if lower > global_lower => return Ok(false),
+                    Some(lower) if lower > global_lower => return Ok(None),
                     _ => {}
                 }
+                let mut stats = ConsolidationStats::default();
                 match part {
                     ConsolidationPart::Queued { data } => {
                         self.metrics.compaction.parts_waited.inc();
@@ -432,7 +487,9 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
                             data.take().fetch().await?,
                             &self.filter,
                             maybe_unconsolidated,
+                            &mut stats,
                         );
+                        stats.bytes_processed += *size;
                     }
                     ConsolidationPart::Prefetched {
                         handle,
@@ -449,21 +506,27 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
                             handle.await??,
                             &self.filter,
                             *maybe_unconsolidated,
+                            &mut stats,
                         );
+                        stats.bytes_processed += *size;
                     }
                     ConsolidationPart::Encoded { .. } | ConsolidationPart::Sorted { .. } => {}
                 }
-                Ok::<_, anyhow::Error>(true)
+                Ok::<_, anyhow::Error>(Some(stats))
             })
             .collect();
 
         // Wait for all the needed parts to be fetched, and assert that there's at least one.
         let mut total_ready = 0;
+        let mut fetched_stats = ConsolidationStats::default();
         while let Some(awaited) = ready_futures.next().await {
-            if awaited? {
+            if let Some(stats) = awaited? {
+                fetched_stats.merge(&stats);
                 total_ready += 1;
             }
         }
+        drop(ready_futures);
+        self.stats.merge(&fetched_stats);
         assert!(
             total_ready > 0,
             "at least one part should be fetched and ready to go"
@@ -554,6 +617,13 @@ impl<T: Timestamp + Codec64 + Lattice, D: Codec64 + Semigroup> Consolidator<T, D
     }
 }
 
+impl<T, D> Consolidator<T, D> {
+    /// Statistics about the work this consolidator has done so far.
+    pub(crate) fn stats(&self) -> ConsolidationStats {
+        self.stats
+    }
+}
+
 impl<T, D> Drop for Consolidator<T, D> {
     fn drop(&mut self) {
         for run in &self.runs {
@@ -714,6 +784,7 @@ pub(crate) struct ConsolidatingIter<'a, T: Timestamp, D> {
     upper_bound: Option<(&'a [u8], &'a [u8], T)>,
     state: Option<TupleRef<'a, T, D>>,
     drop_stash: &'a mut Option<Tuple<T, D>>,
+    stats: &'a mut ConsolidationStats,
 }
 
 impl<'a, T, D> ConsolidatingIter<'a, T, D>
@@ -724,6 +795,7 @@ where
     pub fn new(
         init_state: Option<TupleRef<'a, T, D>>,
         drop_stash: &'a mut Option<Tuple<T, D>>,
+        stats: &'a mut ConsolidationStats,
     ) -> Self {
         Self {
             parts: vec![],
@@ -731,6 +803,7 @@ where
             upper_bound: None,
             state: init_state,
             drop_stash,
+            stats,
         }
     }
 
@@ -782,6 +855,7 @@ where
                         let (_, _, _, d1) = part
                             .pop(&mut self.parts)
                             .expect("popping from a non-empty iterator");
+                        self.stats.updates_in += 1;
                         d0.plus_equals(&d1);
                     } else {
                         break;
@@ -796,6 +870,7 @@ where
                     }
 
                     self.state = part.pop(&mut self.parts);
+                    self.stats.updates_in += 1;
                 }
             } else {
                 if part.last_in_run {
@@ -823,7 +898,11 @@ where
         loop {
             match self.consolidate() {
                 Some((_, _, _, d)) if d.is_zero() => continue,
-                other => break other,
+                Some(update) => {
+                    self.stats.updates_out += 1;
+                    break Some(update);
+                }
+                None => break None,
             }
         }
     }
@@ -901,6 +980,7 @@ mod tests {
                     budget: 0,
                     initial_state: None,
                     drop_stash: None,
+                    stats: ConsolidationStats::default(),
                 };
 
                 let mut out = vec![];
@@ -934,6 +1014,53 @@ mod tests {
         });
     }
 
+    #[mz_ore::test]
+    fn consolidation_stats() {
+        let metrics = Arc::new(Metrics::new(
+            &PersistConfig::new_for_tests(),
+            &MetricsRegistry::new(),
+        ));
+        let mut consolidator: Consolidator<u64, i64> = Consolidator::new(
+            metrics,
+            FetchBatchFilter::Compaction {
+                since: Antichain::from_elem(0),
+            },
+            0,
+        );
+
+        let update = |k: &str, d: i64| ((k.as_bytes().to_vec(), vec![]), 1u64, d);
+        let runs = [
+            vec![update("a", 1), update("b", 1)],
+            vec![update("a", 1), update("c", 1)],
+            vec![update("b", -1)],
+        ];
+        for run in runs {
+            let part = ConsolidationPart::from_iter(run.iter().map(borrow_tuple));
+            consolidator.push_run(VecDeque::from([(part, 0)]));
+        }
+
+        let mut out = vec![];
+        loop {
+            consolidator.trim();
+            let Some(iter) = consolidator.iter() else {
+                break;
+            };
+            out.extend(iter.map(clone_tuple));
+        }
+
+        // The two runs with `a` merge into one update and the `b`s cancel out.
+        assert_eq!(out, vec![update("a", 2), update("c", 1)]);
+        assert_eq!(
+            consolidator.stats(),
+            ConsolidationStats {
+                runs_merged: 3,
+                updates_in: 5,
+                updates_out: 2,
+                bytes_processed: 0,
+            }
+        );
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn prefetches() {
@@ -973,6 +1100,8 @@ mod tests {
                         encoded_size_bytes,
                         key_lower: vec![],
                         stats: None,
+                        schema_id: None,
+                        checksum: None,
                     })
                     .collect();
                 consolidator.enqueue_run(
@@ -19,19 +19,22 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::BufMut;
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
+use futures::Stream;
 use mz_build_info::{build_info, BuildInfo};
-use mz_persist::location::{Blob, Consensus, ExternalError};
+use mz_persist::location::{Blob, Consensus, ExternalError, SeqNo};
 use mz_persist_types::codec_impls::{SimpleDecoder, SimpleEncoder, SimpleSchema};
 use mz_persist_types::columnar::{ColumnPush, Schema};
 use mz_persist_types::dyn_struct::{ColumnsMut, ColumnsRef, DynStructCfg};
 use mz_persist_types::{Codec, Codec64, Opaque};
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
-use timely::progress::Timestamp;
+use timely::order::TotalOrder;
+use timely::progress::{Antichain, Timestamp};
 use tracing::instrument;
 use uuid::Uuid;
 
@@ -39,15 +42,17 @@ use crate::async_runtime::IsolatedRuntime;
 use crate::cache::{PersistClientCache, StateCache};
 use crate::cfg::PersistConfig;
 use crate::critical::{CriticalReaderId, SinceHandle};
-use crate::error::InvalidUsage;
+use crate::error::{AllowCodecMismatch, CodecMismatch, InvalidUsage};
 use crate::fetch::BatchFetcher;
+use crate::health::{HealthCheck, PersistHealth};
 use crate::internal::compact::Compactor;
 use crate::internal::encoding::{parse_id, Schemas};
 use crate::internal::gc::GarbageCollector;
 use crate::internal::machine::{retry_external, Machine};
 use crate::internal::state_versions::StateVersions;
-use crate::metrics::Metrics;
-use crate::read::{LeasedReaderId, ReadHandle};
+use crate::metrics::{Metrics, ShardMetrics};
+use crate::read::{LeasedReaderId, ReadHandle, ReadOnlyHandle};
+use crate::retention::{RetentionHandle, RetentionWindow};
 use crate::rpc::PubSubSender;
 use crate::write::{WriteHandle, WriterId};
 
@@ -64,12 +69,15 @@ pub mod cli {
 pub mod critical;
 pub mod dyn_cfg;
 pub mod error;
+pub mod export;
 pub mod fetch;
+pub mod health;
 pub mod internals_bench;
 pub mod metrics {
     //! Utilities related to metrics.
     pub use crate::internal::metrics::{
-        encode_ts_metric, Metrics, SinkMetrics, SinkWorkerMetrics, UpdateDelta,
+        encode_outer_ts_metric, encode_ts_metric, Metrics, ShardMetrics, SinkMetrics,
+        SinkWorkerMetrics, TsMetricEncode, UpdateDelta,
     };
 }
 pub mod operators {
@@ -78,6 +86,7 @@ pub mod operators {
 }
 pub mod iter;
 pub mod read;
+pub mod retention;
 pub mod rpc;
 pub mod stats;
 pub mod usage;
@@ -101,6 +110,7 @@ mod internal {
     pub mod state_versions;
     pub mod trace;
     pub mod watch;
+    pub mod watchdog;
 
     #[cfg(test)]
     pub mod datadriven;
@@ -133,6 +143,15 @@ impl PersistLocation {
     }
 }
 
+/// An opaque identifier for a version of a shard's schema.
+///
+/// Persist doesn't yet keep track of the schemas of a shard, so these are
+/// currently assigned by the caller.
+#[derive(
+    Arbitrary, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct SchemaId(pub usize);
+
 /// An opaque identifier for a persist durable TVC (aka shard).
 ///
 /// The [std::string::ToString::to_string] format of this may be stored durably
@@ -182,11 +201,21 @@ impl ShardId {
     pub fn new() -> Self {
         ShardId(Uuid::new_v4().as_bytes().to_owned())
     }
+
+    /// Returns a [ShardId] derived deterministically from `seed`, such that the
+    /// same seed yields the same id across runs and processes.
+    ///
+    /// This is intended only for making tests reproducible: the id is a
+    /// name-based (v5) UUID of the seed, which is neither secret nor
+    /// cryptographically secure. Production code should use [ShardId::new].
+    pub fn from_seed(seed: &str) -> Self {
+        ShardId(Uuid::new_v5(&Uuid::NAMESPACE_OID, seed.as_bytes()).into_bytes())
+    }
 }
 
 /// Additional diagnostic information used within Persist
 /// e.g. for logging, metric labels, etc.
-#[derive(Clone, Debug)]
+#[derive(Arbitrary, Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Diagnostics {
     /// A user-friendly name for the shard.
     pub shard_name: String,
@@ -212,6 +241,194 @@ impl Diagnostics {
     }
 }
 
+/// Per-shard overrides of the batch and compaction tuning, as set by
+/// [PersistClient::set_shard_tuning].
+///
+/// The tuning is recorded in the shard's state, so that every writer of the
+/// shard agrees on it. Any setting that is None falls back to the process's
+/// [PersistConfig].
+#[derive(Arbitrary, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ShardTuning {
+    /// Overrides the target size of blobs written for the shard.
+    pub blob_target_size: Option<usize>,
+    /// Overrides the number of parts a batch builder of the shard may have
+    /// outstanding (being uploaded) at once.
+    pub batch_builder_max_outstanding_parts: Option<usize>,
+    /// Overrides the memory bound of compactions of the shard. Compaction
+    /// always uses at least four times the blob target size.
+    pub compaction_memory_bound_bytes: Option<usize>,
+}
+
+/// Options for opening handles to a shard, e.g. via
+/// [PersistClient::open_leased_reader_with_options].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenOptions {
+    /// Whether to initialize the shard if it has never been used before.
+    ///
+    /// If false, opening a shard without existing state returns
+    /// [InvalidUsage::ShardNeverUsed] and leaves no state behind.
+    pub create_if_missing: bool,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        OpenOptions {
+            create_if_missing: true,
+        }
+    }
+}
+
+/// How [PersistClient::finalize_shard] treats a shard whose `since` and
+/// `upper` haven't both been advanced to `[]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizeFrontiers {
+    /// Return an error.
+    RequireEmpty,
+    /// Advance them to `[]`, even if a reader or writer is still holding them
+    /// back. This is only intended for cleaning up shards whose frontiers are
+    /// stuck, e.g. because a critical since handle was lost. Any live readers
+    /// or writers of the shard will break.
+    ForceEmpty,
+}
+
+/// The outcome of [PersistClient::purge_tombstone].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeTombstoneResult {
+    /// The shard was deleted from consensus, along with the given number of
+    /// blobs.
+    Purged {
+        /// The number of blobs that were deleted.
+        blobs_deleted: usize,
+    },
+    /// The shard doesn't exist, perhaps because it was already purged.
+    NotFound,
+    /// The shard is not a tombstone.
+    NotTombstone,
+    /// The shard has been a tombstone for less than the requested duration.
+    TooRecent {
+        /// How long the shard has been a tombstone.
+        age: Duration,
+    },
+}
+
+/// The status of a shard, as returned by [PersistClient::shard_status].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardStatus {
+    /// Whether the shard has been initialized. If false, the remaining fields
+    /// are all empty.
+    pub exists: bool,
+    /// Whether the shard has been finalized. See [PersistClient::is_finalized].
+    pub is_finalized: bool,
+    /// The elements of the shard's upper, each encoded with [Codec64::encode].
+    pub upper_codec64_bytes: Vec<[u8; 8]>,
+    /// The elements of the shard's since, each encoded with [Codec64::encode].
+    pub since_codec64_bytes: Vec<[u8; 8]>,
+    /// The (key, val, ts, diff) codec names in the shard's durable state.
+    pub codecs: Option<(String, String, String, String)>,
+}
+
+impl ShardStatus {
+    fn missing() -> Self {
+        ShardStatus {
+            exists: false,
+            is_finalized: false,
+            upper_codec64_bytes: Vec::new(),
+            since_codec64_bytes: Vec::new(),
+            codecs: None,
+        }
+    }
+}
+
+/// A critical reader of a shard, as returned by
+/// [PersistClient::critical_readers].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CriticalReaderInfo<T> {
+    /// The id of the reader.
+    pub id: CriticalReaderId,
+    /// The purpose the reader was most recently registered with.
+    pub purpose: String,
+    /// The hostname the reader was most recently registered from.
+    pub hostname: String,
+    /// The since capability of the reader.
+    pub since: Antichain<T>,
+    /// The reader's opaque, as the hex of its [Codec64] encoding.
+    pub opaque_hex: String,
+    /// The name of the [Codec64] used to encode the reader's opaque.
+    pub opaque_codec: String,
+}
+
+/// The codecs a shard was initialized with, as returned by
+/// [PersistClient::shard_codecs].
+///
+/// Persist doesn't yet keep track of the schemas of a shard, so only the names
+/// of its codecs are available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardCodecs {
+    /// The name of the key codec.
+    pub key: String,
+    /// The name of the val codec.
+    pub val: String,
+    /// The name of the ts codec.
+    pub ts: String,
+    /// The name of the diff codec.
+    pub diff: String,
+    /// The diagnostics of the handle that initialized the shard, if known.
+    pub registered_by: Option<Diagnostics>,
+}
+
+impl ShardCodecs {
+    /// Returns an error if opening a handle to the shard with the given types
+    /// would fail with a codec mismatch.
+    pub fn check<K: Codec, V: Codec, T: Codec64, D: Codec64>(
+        &self,
+    ) -> Result<(), Box<CodecMismatch>> {
+        if K::codec_name() == self.key
+            && V::codec_name() == self.val
+            && T::codec_name() == self.ts
+            && D::codec_name() == self.diff
+        {
+            return Ok(());
+        }
+        Err(Box::new(CodecMismatch {
+            requested: (
+                K::codec_name(),
+                V::codec_name(),
+                T::codec_name(),
+                D::codec_name(),
+                None,
+            ),
+            actual: (
+                self.key.clone(),
+                self.val.clone(),
+                self.ts.clone(),
+                self.diff.clone(),
+                None,
+            ),
+            registered_by: self.registered_by.clone(),
+        }))
+    }
+}
+
+/// A change to the frontiers of a shard, as returned by
+/// [PersistClient::watch_shard].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardStateEvent<T> {
+    /// The seqno of the version of state the frontiers were read from.
+    pub seqno: SeqNo,
+    /// The since of the shard as of `seqno`.
+    pub since: Antichain<T>,
+    /// The upper of the shard as of `seqno`.
+    pub upper: Antichain<T>,
+}
+
+/// The live diffs of a shard, as returned by [PersistClient::inspect_shard_diffs].
+#[derive(Debug, Serialize)]
+struct LiveDiffsSummary {
+    min_seqno: SeqNo,
+    max_seqno: SeqNo,
+    count: usize,
+}
+
 /// A handle for interacting with the set of persist shard made durable at a
 /// single [PersistLocation].
 ///
@@ -259,7 +476,9 @@ impl PersistClient {
         pubsub_sender: Arc<dyn PubSubSender>,
     ) -> Result<Self, ExternalError> {
         // TODO: Verify somehow that blob matches consensus to prevent
-        // accidental misuse.
+        // accidental misuse. We only have the URIs in
+        // [PersistClientCache::open], which checks them with
+        // [PersistConfig::validate].
         Ok(PersistClient {
             cfg,
             blob,
@@ -280,6 +499,16 @@ impl PersistClient {
             .expect("in-mem location is valid")
     }
 
+    /// Returns a [StateVersions] backed by this client's blob and consensus.
+    pub(crate) fn state_versions(&self) -> StateVersions {
+        StateVersions::new(
+            self.cfg.clone(),
+            Arc::clone(&self.consensus),
+            Arc::clone(&self.blob),
+            Arc::clone(&self.metrics),
+        )
+    }
+
     async fn make_machine<K, V, T, D>(
         &self,
         shard_id: ShardId,
@@ -291,12 +520,25 @@ impl PersistClient {
         T: Timestamp + Lattice + Codec64,
         D: Semigroup + Codec64 + Send + Sync,
     {
-        let state_versions = StateVersions::new(
-            self.cfg.clone(),
-            Arc::clone(&self.consensus),
-            Arc::clone(&self.blob),
-            Arc::clone(&self.metrics),
-        );
+        self.make_machine_allowing(shard_id, diagnostics, None)
+            .await
+    }
+
+    /// [Self::make_machine], but ignoring any codec mismatch with durable
+    /// storage that `allow_codec_mismatch` permits.
+    async fn make_machine_allowing<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        diagnostics: Diagnostics,
+        allow_codec_mismatch: Option<AllowCodecMismatch>,
+    ) -> Result<Machine<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let state_versions = self.state_versions();
         let machine = Machine::<K, V, T, D>::new(
             self.cfg.clone(),
             shard_id,
@@ -306,11 +548,37 @@ impl PersistClient {
             Arc::clone(&self.pubsub_sender),
             Arc::clone(&self.isolated_runtime),
             diagnostics.clone(),
+            allow_codec_mismatch,
         )
         .await?;
         Ok(machine)
     }
 
+    /// [Self::make_machine_allowing], but honoring the given [OpenOptions].
+    async fn make_machine_with_options<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        diagnostics: Diagnostics,
+        options: OpenOptions,
+        allow_codec_mismatch: Option<AllowCodecMismatch>,
+    ) -> Result<Machine<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        if !options.create_if_missing {
+            let state_versions = self.state_versions();
+            let live_diffs = state_versions.fetch_recent_live_diffs::<T>(&shard_id).await;
+            if live_diffs.0.is_empty() {
+                return Err(InvalidUsage::ShardNeverUsed { shard_id });
+            }
+        }
+        self.make_machine_allowing(shard_id, diagnostics, allow_codec_mismatch)
+            .await
+    }
+
     /// Provides capabilities for the durable TVC identified by `shard_id` at
     /// its current since and upper frontiers.
     ///
@@ -328,7 +596,7 @@ impl PersistClient {
     /// The `schema` parameter is currently unused, but should be an object
     /// that represents the schema of the data in the shard. This will be required
     /// in the future.
-    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
     pub async fn open<K, V, T, D>(
         &self,
         shard_id: ShardId,
@@ -355,6 +623,59 @@ impl PersistClient {
         ))
     }
 
+    /// [Self::open], but calling `allow` instead of failing if the codecs of
+    /// the shard in durable storage don't match `K`, `V`, `T`, and `D`. The
+    /// handles are returned if it returns true, otherwise the
+    /// [InvalidUsage::CodecMismatch] is.
+    ///
+    /// This is intended for carefully tested schema migrations, where the new
+    /// types are known to be able to read data written with the old ones.
+    /// Persist can't check that, so a wrong answer from `allow` can result in
+    /// incorrect data or panics while decoding it. Handles with the
+    /// old types may also start seeing codec mismatches once the shard's state
+    /// is rewritten by these ones.
+    ///
+    /// Note that the hook is only consulted for the shard's durable state: a
+    /// mismatch with handles of other types open in the same process is
+    /// always an error.
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn open_with_codec_check<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+        allow: impl Fn(&CodecMismatch) -> bool + Send + Sync + 'static,
+    ) -> Result<(WriteHandle<K, V, T, D>, ReadHandle<K, V, T, D>), InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let allow = AllowCodecMismatch::new(allow);
+        Ok((
+            self.open_writer_allowing(
+                shard_id,
+                Arc::clone(&key_schema),
+                Arc::clone(&val_schema),
+                diagnostics.clone(),
+                OpenOptions::default(),
+                Some(allow.clone()),
+            )
+            .await?,
+            self.open_leased_reader_allowing(
+                shard_id,
+                key_schema,
+                val_schema,
+                diagnostics,
+                OpenOptions::default(),
+                Some(allow),
+            )
+            .await?,
+        ))
+    }
+
     /// [Self::open], but returning only a [ReadHandle].
     ///
     /// Use this to save latency and a bit of persist traffic if you're just
@@ -363,7 +684,6 @@ impl PersistClient {
     /// The `_schema` parameter is currently unused, but should be an object
     /// that represents the schema of the data in the shard. This will be required
     /// in the future.
-    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
     pub async fn open_leased_reader<K, V, T, D>(
         &self,
         shard_id: ShardId,
@@ -377,16 +697,71 @@ impl PersistClient {
         T: Timestamp + Lattice + Codec64,
         D: Semigroup + Codec64 + Send + Sync,
     {
-        let mut machine = self.make_machine(shard_id, diagnostics.clone()).await?;
+        self.open_leased_reader_with_options(
+            shard_id,
+            key_schema,
+            val_schema,
+            diagnostics,
+            OpenOptions::default(),
+        )
+        .await
+    }
+
+    /// [Self::open_leased_reader], but honoring the given [OpenOptions].
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn open_leased_reader_with_options<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+        options: OpenOptions,
+    ) -> Result<ReadHandle<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        self.open_leased_reader_allowing(
+            shard_id,
+            key_schema,
+            val_schema,
+            diagnostics,
+            options,
+            None,
+        )
+        .await
+    }
+
+    async fn open_leased_reader_allowing<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+        options: OpenOptions,
+        allow_codec_mismatch: Option<AllowCodecMismatch>,
+    ) -> Result<ReadHandle<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let mut machine = self
+            .make_machine_with_options(shard_id, diagnostics.clone(), options, allow_codec_mismatch)
+            .await?;
         let gc = GarbageCollector::new(machine.clone(), Arc::clone(&self.isolated_runtime));
 
         let reader_id = LeasedReaderId::new();
         let heartbeat_ts = (self.cfg.now)();
+        let lease_duration = self.cfg.dynamic.reader_lease_duration();
         let (reader_state, maintenance) = machine
             .register_leased_reader(
                 &reader_id,
                 &diagnostics.handle_purpose,
-                self.cfg.dynamic.reader_lease_duration(),
+                lease_duration,
                 heartbeat_ts,
             )
             .await;
@@ -403,16 +778,54 @@ impl PersistClient {
             Arc::clone(&self.blob),
             reader_id,
             schemas,
+            lease_duration,
             reader_state.since,
             heartbeat_ts,
-        )
-        .await;
+        );
 
         Ok(reader)
     }
 
+    /// Provides a [ReadOnlyHandle] to the durable TVC identified by `shard_id`.
+    ///
+    /// Unlike [Self::open_leased_reader], this doesn't register a reader, so
+    /// it never writes to consensus (in particular, it doesn't initialize a
+    /// shard that has never been used, and instead returns
+    /// [InvalidUsage::ShardNeverUsed]). See [ReadOnlyHandle] for the caveats
+    /// that come with not holding a capability.
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn open_readonly_reader<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+    ) -> Result<ReadOnlyHandle<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let options = OpenOptions {
+            create_if_missing: false,
+        };
+        let machine = self
+            .make_machine_with_options(shard_id, diagnostics, options, None)
+            .await?;
+        Ok(ReadOnlyHandle {
+            metrics: Arc::clone(&self.metrics),
+            machine,
+            blob: Arc::clone(&self.blob),
+            schemas: Schemas {
+                key: key_schema,
+                val: val_schema,
+            },
+        })
+    }
+
     /// Creates and returns a [BatchFetcher] for the given shard id.
-    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
     pub async fn create_batch_fetcher<K, V, T, D>(
         &self,
         shard_id: ShardId,
@@ -426,12 +839,7 @@ impl PersistClient {
         T: Timestamp + Lattice + Codec64,
         D: Semigroup + Codec64 + Send + Sync,
     {
-        let state_versions = StateVersions::new(
-            self.cfg.clone(),
-            Arc::clone(&self.consensus),
-            Arc::clone(&self.blob),
-            Arc::clone(&self.metrics),
-        );
+        let state_versions = self.state_versions();
         let shard_metrics = self
             .metrics
             .shards
@@ -443,8 +851,42 @@ impl PersistClient {
         // the `BatchFetcher` but acts as a safety net against accidental
         // mis-use.
         let _ = state_versions
-            .maybe_init_shard::<K, V, T, D>(&shard_metrics)
+            .maybe_init_shard::<K, V, T, D>(&shard_metrics, &diagnostics, None)
             .await;
+
+        self.create_batch_fetcher_unchecked(shard_id, key_schema, val_schema, diagnostics)
+    }
+
+    /// Creates and returns a [BatchFetcher] for the given shard id, without
+    /// checking the shard's codecs against `K`, `V`, `T`, and `D` or
+    /// initializing the shard.
+    ///
+    /// Unlike [Self::create_batch_fetcher], this doesn't involve a round-trip
+    /// to consensus, so it's cheap to create many fetchers. Fetching parts from
+    /// other shards is still rejected with
+    /// [InvalidUsage::BatchNotFromThisShard].
+    ///
+    /// **IMPORTANT**: This is only safe to use if the caller has already
+    /// validated that the shard exists with exactly these types, e.g. by
+    /// opening a handle to it. Otherwise, fetched parts may silently fail to
+    /// decode, or decode to garbage.
+    pub fn create_batch_fetcher_unchecked<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+    ) -> BatchFetcher<K, V, T, D>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let shard_metrics = self
+            .metrics
+            .shards
+            .shard(&shard_id, &diagnostics.shard_name);
         let schemas = Schemas {
             key: key_schema,
             val: val_schema,
@@ -485,6 +927,50 @@ impl PersistClient {
     pub const CONTROLLER_CRITICAL_SINCE: CriticalReaderId =
         CriticalReaderId([0, 0, 0, 0, 17, 17, 34, 34, 51, 51, 68, 68, 68, 68, 68, 68]);
 
+    /// Writes a consolidated snapshot of the shard identified by `shard_id` as
+    /// of `as_of` into `writer` as a Parquet file, returning the number of
+    /// updates written.
+    ///
+    /// This is intended for getting the contents of a shard into analytics
+    /// tools, see [export::ExportFormat] for the layout of the file. The
+    /// snapshot is streamed out in row groups, so memory usage is bounded
+    /// regardless of the size of the shard.
+    ///
+    /// Like a snapshot, this waits until `as_of` is less than the shard's
+    /// upper and returns an error if it is not at least the shard's since.
+    pub async fn export_snapshot_parquet<K, V, T, D, W>(
+        &self,
+        shard_id: ShardId,
+        as_of: Antichain<T>,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        format: export::ExportFormat,
+        writer: W,
+    ) -> Result<usize, anyhow::Error>
+    where
+        K: Debug + Codec + Ord,
+        V: Debug + Codec + Ord,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+        W: futures::AsyncWrite + Unpin,
+    {
+        let mut read = self
+            .open_leased_reader::<K, V, T, D>(
+                shard_id,
+                key_schema,
+                val_schema,
+                Diagnostics::from_purpose("export snapshot parquet"),
+            )
+            .await
+            .map_err(|err| anyhow::anyhow!("opening reader: {}", err))?;
+        let row_group_bytes = self.cfg.dynamic.blob_target_size();
+        let ret =
+            export::export_snapshot_parquet(&mut read, as_of, format, row_group_bytes, writer)
+                .await;
+        read.expire().await;
+        ret
+    }
+
     /// Provides a capability for the durable TVC identified by `shard_id` at
     /// its current since frontier.
     ///
@@ -503,8 +989,8 @@ impl PersistClient {
     ///
     /// If `shard_id` has never been used before, initializes a new shard and
     /// return a handle with its `since` frontier set to the initial value of
-    /// `Antichain::from_elem(T::minimum())`.
-    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
+    /// `Antichain::from_elem(T::minimum())`. See
+    /// [Self::open_critical_since_with_options] to instead return an error.
     pub async fn open_critical_since<K, V, T, D, O>(
         &self,
         shard_id: ShardId,
@@ -518,7 +1004,34 @@ impl PersistClient {
         D: Semigroup + Codec64 + Send + Sync,
         O: Opaque + Codec64,
     {
-        let mut machine = self.make_machine(shard_id, diagnostics.clone()).await?;
+        self.open_critical_since_with_options(
+            shard_id,
+            reader_id,
+            diagnostics,
+            OpenOptions::default(),
+        )
+        .await
+    }
+
+    /// [Self::open_critical_since], but honoring the given [OpenOptions].
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn open_critical_since_with_options<K, V, T, D, O>(
+        &self,
+        shard_id: ShardId,
+        reader_id: CriticalReaderId,
+        diagnostics: Diagnostics,
+        options: OpenOptions,
+    ) -> Result<SinceHandle<K, V, T, D, O>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+        O: Opaque + Codec64,
+    {
+        let mut machine = self
+            .make_machine_with_options(shard_id, diagnostics.clone(), options, None)
+            .await?;
         let gc = GarbageCollector::new(machine.clone(), Arc::clone(&self.isolated_runtime));
 
         let (state, maintenance) = machine
@@ -536,6 +1049,45 @@ impl PersistClient {
         Ok(handle)
     }
 
+    /// Retains approximately `window` of history in the shard, using a
+    /// critical since registered under `reader_id`.
+    ///
+    /// The returned [RetentionHandle] is started and downgrades the since to
+    /// `now - window` on the cadence of critical since downgrades. It takes
+    /// over the since from any previous handle for the same `reader_id`, so
+    /// callers should use a fixed `reader_id` per shard and call this again
+    /// after a restart. See [RetentionHandle] for what happens if the process
+    /// is lost.
+    ///
+    /// The same caveats as [Self::open_critical_since] apply to `reader_id`,
+    /// which must not be used for any other purpose.
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn apply_retention<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        reader_id: CriticalReaderId,
+        window: RetentionWindow<T>,
+        diagnostics: Diagnostics,
+    ) -> Result<RetentionHandle<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + TotalOrder + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let since = self
+            .open_critical_since::<K, V, T, D, u64>(shard_id, reader_id, diagnostics)
+            .await?;
+        let handle = RetentionHandle::new(
+            since,
+            window,
+            self.cfg.now.clone(),
+            self.cfg.critical_downgrade_interval,
+        )
+        .await;
+        Ok(handle)
+    }
+
     /// [Self::open], but returning only a [WriteHandle].
     ///
     /// Use this to save latency and a bit of persist traffic if you're just
@@ -544,7 +1096,6 @@ impl PersistClient {
     /// The `_schema` parameter is currently unused, but should be an object
     /// that represents the schema of the data in the shard. This will be required
     /// in the future.
-    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
     pub async fn open_writer<K, V, T, D>(
         &self,
         shard_id: ShardId,
@@ -558,10 +1109,57 @@ impl PersistClient {
         T: Timestamp + Lattice + Codec64,
         D: Semigroup + Codec64 + Send + Sync,
     {
-        let machine = self.make_machine(shard_id, diagnostics.clone()).await?;
-        let gc = GarbageCollector::new(machine.clone(), Arc::clone(&self.isolated_runtime));
-        let writer_id = WriterId::new();
-        let schemas = Schemas {
+        self.open_writer_with_options(
+            shard_id,
+            key_schema,
+            val_schema,
+            diagnostics,
+            OpenOptions::default(),
+        )
+        .await
+    }
+
+    /// [Self::open_writer], but honoring the given [OpenOptions].
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn open_writer_with_options<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+        options: OpenOptions,
+    ) -> Result<WriteHandle<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        self.open_writer_allowing(shard_id, key_schema, val_schema, diagnostics, options, None)
+            .await
+    }
+
+    async fn open_writer_allowing<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        key_schema: Arc<K::Schema>,
+        val_schema: Arc<V::Schema>,
+        diagnostics: Diagnostics,
+        options: OpenOptions,
+        allow_codec_mismatch: Option<AllowCodecMismatch>,
+    ) -> Result<WriteHandle<K, V, T, D>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let machine = self
+            .make_machine_with_options(shard_id, diagnostics.clone(), options, allow_codec_mismatch)
+            .await?;
+        let gc = GarbageCollector::new(machine.clone(), Arc::clone(&self.isolated_runtime));
+        let writer_id = WriterId::new();
+        let schemas = Schemas {
             key: key_schema,
             val: val_schema,
         };
@@ -598,6 +1196,210 @@ impl PersistClient {
         Ok(machine.is_finalized())
     }
 
+    /// Records per-shard overrides of the batch and compaction tuning in the
+    /// state of the given shard, replacing any previous ones.
+    ///
+    /// Writers of the shard, in this process or any other, use the new tuning
+    /// for batches they start building after they next sync the shard's
+    /// state. Background compactions do the same, but compactions forced via
+    /// the admin tool use its flags instead.
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
+    pub async fn set_shard_tuning<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        tuning: ShardTuning,
+        diagnostics: Diagnostics,
+    ) -> Result<(), InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let mut machine = self
+            .make_machine::<K, V, T, D>(shard_id, diagnostics)
+            .await?;
+
+        let (_seqno, maintenance) = machine.set_tuning(tuning).await;
+        let gc = GarbageCollector::new(machine.clone(), Arc::clone(&self.isolated_runtime));
+
+        let () = maintenance.perform(&machine, &gc).await;
+
+        Ok(())
+    }
+
+    /// Returns the status of the given shard, without requiring the caller to
+    /// know its types.
+    ///
+    /// Unlike [Self::is_finalized], this never initializes the shard, and the
+    /// shard's frontiers are returned in their [Codec64] encoding.
+    pub async fn shard_status(&self, shard_id: ShardId) -> Result<ShardStatus, anyhow::Error> {
+        let state_versions = self.state_versions();
+        state_versions.fetch_shard_status(&shard_id).await
+    }
+
+    /// Returns the codecs the given shard was initialized with, or None if it
+    /// has never been used.
+    ///
+    /// Unlike opening a handle, this doesn't require knowing the shard's types
+    /// and never initializes the shard, so it can be used to validate types
+    /// (see [ShardCodecs::check]) before opening it. If this process already
+    /// has the shard's state cached, no round-trip to consensus is needed.
+    pub async fn shard_codecs(&self, shard_id: ShardId) -> Option<ShardCodecs> {
+        if let Some(state) = self
+            .shared_states
+            .get_state_weak(&shard_id)
+            .and_then(|x| x.upgrade())
+        {
+            let (key, val, ts, diff, _) = state.codecs();
+            return Some(ShardCodecs {
+                key,
+                val,
+                ts,
+                diff,
+                registered_by: state.registered_by(),
+            });
+        }
+
+        let state_versions = self.state_versions();
+        state_versions.fetch_shard_codecs(&shard_id).await
+    }
+
+    /// Returns the since and upper of the given shard, in that order.
+    ///
+    /// Unlike opening a handle, this doesn't register a reader or writer or
+    /// otherwise modify the shard's state, so it's cheap to call for many
+    /// shards. If this process already has the shard's state cached, the
+    /// cached (and potentially slightly stale) frontiers are returned without
+    /// a round-trip to consensus. A shard that was never used is reported
+    /// with the frontiers it would be initialized with, and isn't created.
+    pub async fn shard_frontiers<T>(
+        &self,
+        shard_id: ShardId,
+    ) -> Result<(Antichain<T>, Antichain<T>), anyhow::Error>
+    where
+        T: Timestamp + Lattice + Codec64,
+    {
+        if let Some(state) = self
+            .shared_states
+            .get_state_weak(&shard_id)
+            .and_then(|x| x.upgrade())
+        {
+            let (ts_codec, since, upper) = state.codec64_frontiers();
+            if ts_codec != T::codec_name() {
+                return Err(anyhow::anyhow!(
+                    "requested ts codec {:?} did not match one in durable storage {:?}",
+                    T::codec_name(),
+                    ts_codec
+                ));
+            }
+            let decode = |x: Vec<[u8; 8]>| x.into_iter().map(T::decode).collect();
+            return Ok((decode(since), decode(upper)));
+        }
+
+        let state_versions = self.state_versions();
+        let live_diffs = state_versions
+            .fetch_recent_live_diffs::<T>(&shard_id)
+            .await
+            .0;
+        if live_diffs.is_empty() {
+            let minimum = Antichain::from_elem(T::minimum());
+            return Ok((minimum.clone(), minimum));
+        }
+        let state = state_versions
+            .fetch_current_state::<T>(&shard_id, live_diffs)
+            .await
+            .check_ts_codec(&shard_id)?;
+        Ok((
+            state.collections.trace.since().clone(),
+            state.collections.trace.upper().clone(),
+        ))
+    }
+
+    /// Returns the critical readers registered on the given shard, in order of
+    /// their ids.
+    ///
+    /// This is meant for figuring out which of several critical readers is
+    /// holding back the since of a shard. Like [Self::shard_status], this
+    /// never modifies the shard's state, and a shard that was never used has
+    /// no critical readers.
+    pub async fn critical_readers<T>(
+        &self,
+        shard_id: ShardId,
+    ) -> Result<Vec<CriticalReaderInfo<T>>, anyhow::Error>
+    where
+        T: Timestamp + Lattice + Codec64,
+    {
+        let state_versions = self.state_versions();
+        let live_diffs = state_versions
+            .fetch_recent_live_diffs::<T>(&shard_id)
+            .await
+            .0;
+        if live_diffs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let state = state_versions
+            .fetch_current_state::<T>(&shard_id, live_diffs)
+            .await
+            .check_ts_codec(&shard_id)?;
+        Ok(state.critical_reader_infos())
+    }
+
+    /// Returns a stream of the changes to the since and upper of the given
+    /// shard, starting with its current ones.
+    ///
+    /// This is meant for reacting to other handles, in this process or any
+    /// other, advancing the frontiers of a shard without having to poll it.
+    /// Changes are usually noticed via PubSub pushes, with a polling fallback
+    /// in case a push is missed. Consecutive changes may be coalesced into a
+    /// single event, and versions of state that didn't change either frontier
+    /// (e.g. lease heartbeats) don't produce events, but events are always
+    /// delivered in increasing seqno order.
+    ///
+    /// Like [Self::is_finalized], this initializes the shard if it has never
+    /// been used. The stream never ends, so drop it to stop watching.
+    pub async fn watch_shard<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        diagnostics: Diagnostics,
+    ) -> Result<impl Stream<Item = ShardStateEvent<T>>, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        let mut machine = self
+            .make_machine::<K, V, T, D>(shard_id, diagnostics)
+            .await?;
+        let mut watch = machine.applier.watch();
+        let stream = async_stream::stream! {
+            let (mut seqno, mut since, mut upper) = machine.applier.seqno_and_frontiers();
+            yield ShardStateEvent {
+                seqno,
+                since: since.clone(),
+                upper: upper.clone(),
+            };
+            loop {
+                let (next_seqno, next_since, next_upper) =
+                    machine.next_seqno_and_frontiers(seqno, &mut watch).await;
+                assert!(next_seqno > seqno);
+                seqno = next_seqno;
+                if next_since == since && next_upper == upper {
+                    continue;
+                }
+                since = next_since;
+                upper = next_upper;
+                yield ShardStateEvent {
+                    seqno,
+                    since: since.clone(),
+                    upper: upper.clone(),
+                };
+            }
+        };
+        Ok(stream)
+    }
+
     /// If a shard is guaranteed to never be used again, finalize it to delete
     /// the associated data and release any associated resources. (Except for a
     /// little state in consensus we use to represent the tombstone.)
@@ -608,11 +1410,16 @@ impl PersistClient {
     ///
     /// Once `finalize_shard` has been called, the result of future operations on
     /// the shard are not defined. They may return errors or succeed as a noop.
-    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
+    ///
+    /// **IMPORTANT**: With [FinalizeFrontiers::ForceEmpty], the `since` and
+    /// `upper` are advanced to `[]` instead of returning an error, which breaks
+    /// any live readers or writers of the shard.
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id, shard_name = %diagnostics.shard_name, handle_purpose = %diagnostics.handle_purpose))]
     pub async fn finalize_shard<K, V, T, D>(
         &self,
         shard_id: ShardId,
         diagnostics: Diagnostics,
+        frontiers: FinalizeFrontiers,
     ) -> Result<(), InvalidUsage<T>>
     where
         K: Debug + Codec,
@@ -624,7 +1431,7 @@ impl PersistClient {
             .make_machine::<K, V, T, D>(shard_id, diagnostics)
             .await?;
 
-        let maintenance = machine.become_tombstone().await?;
+        let maintenance = machine.become_tombstone(frontiers).await?;
         let gc = GarbageCollector::new(machine.clone(), Arc::clone(&self.isolated_runtime));
 
         let () = maintenance.perform(&machine, &gc).await;
@@ -632,6 +1439,44 @@ impl PersistClient {
         Ok(())
     }
 
+    /// Permanently deletes what [Self::finalize_shard] leaves behind for a
+    /// shard: its state in consensus and any remaining blobs.
+    ///
+    /// This is only done if the shard is a tombstone and has been one for at
+    /// least `older_than`. Otherwise, the returned [PurgeTombstoneResult]
+    /// explains why nothing was purged.
+    ///
+    /// **IMPORTANT**: After a shard is purged, there is no record that it ever
+    /// existed. Opening it again (including via [Self::is_finalized]) creates a
+    /// brand-new, empty, writable shard with the same id. Callers must
+    /// guarantee that nothing will use the shard again before purging it, and
+    /// nothing in this process may hold a handle to the shard while it's
+    /// purged. Because this is irreversible, `force` must be set to true, or
+    /// an error is returned.
+    #[instrument(level = "debug", skip_all, fields(shard = %shard_id))]
+    pub async fn purge_tombstone<K, V, T, D>(
+        &self,
+        shard_id: ShardId,
+        older_than: Duration,
+        force: bool,
+    ) -> Result<PurgeTombstoneResult, InvalidUsage<T>>
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        T: Timestamp + Lattice + Codec64,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        if !force {
+            return Err(InvalidUsage::PurgeNotForced { shard_id });
+        }
+        let state_versions = self.state_versions();
+        let res = state_versions
+            .purge_tombstone::<K, V, T, D>(&shard_id, older_than)
+            .await
+            .map_err(InvalidUsage::CodecMismatch)?;
+        Ok(res)
+    }
+
     /// Returns the internal state of the shard for debugging and QA.
     ///
     /// We'll be thoughtful about making unnecessary changes, but the **output
@@ -641,12 +1486,7 @@ impl PersistClient {
         &self,
         shard_id: &ShardId,
     ) -> Result<impl serde::Serialize, anyhow::Error> {
-        let state_versions = StateVersions::new(
-            self.cfg.clone(),
-            Arc::clone(&self.consensus),
-            Arc::clone(&self.blob),
-            Arc::clone(&self.metrics),
-        );
+        let state_versions = self.state_versions();
         // TODO: Don't fetch all live diffs. Feels like we should pull out a new
         // method in StateVersions for fetching the latest version of State of a
         // shard that might or might not exist.
@@ -661,6 +1501,28 @@ impl PersistClient {
         Ok(state)
     }
 
+    /// Returns the sequence numbers of the live diffs of the shard in
+    /// Consensus, for debugging and QA.
+    ///
+    /// Like [Self::inspect_shard], the **output of this method needs to be
+    /// gated from users**, so that it's not subject to our backward
+    /// compatibility guarantees.
+    pub async fn inspect_shard_diffs(
+        &self,
+        shard_id: &ShardId,
+    ) -> Result<impl serde::Serialize, anyhow::Error> {
+        let state_versions = self.state_versions();
+        let versions = state_versions.fetch_all_live_diffs(shard_id).await;
+        let (Some(min), Some(max)) = (versions.0.first(), versions.0.last()) else {
+            return Err(anyhow::anyhow!("{} does not exist", shard_id));
+        };
+        Ok(LiveDiffsSummary {
+            min_seqno: min.seqno,
+            max_seqno: max.seqno,
+            count: versions.0.len(),
+        })
+    }
+
     /// Test helper for a [Self::open] call that is expected to succeed.
     #[cfg(test)]
     #[track_caller]
@@ -692,6 +1554,38 @@ impl PersistClient {
     pub fn metrics(&self) -> &Arc<Metrics> {
         &self.metrics
     }
+
+    /// Returns a summary of the recent health of the blob and consensus
+    /// locations backing this client, as observed by this process.
+    ///
+    /// The summary is pre-aggregated, so this is cheap to call (e.g. from a
+    /// health endpoint).
+    pub fn health(&self) -> PersistHealth {
+        self.metrics.health()
+    }
+
+    /// Actively checks connectivity to the blob and consensus locations
+    /// backing this client.
+    ///
+    /// This writes, reads back, and deletes a small probe in blob, and reads
+    /// the head of a well-known key in consensus. Each operation is attempted
+    /// once, without retries, so an unhealthy location is reported rather than
+    /// waited out. Unlike [Self::health], this makes external calls, so it's
+    /// best suited to startup and diagnostics.
+    pub async fn health_check(&self) -> HealthCheck {
+        HealthCheck {
+            blob: health::check_blob(self.blob.as_ref()).await,
+            consensus: health::check_consensus(self.consensus.as_ref()).await,
+        }
+    }
+
+    /// Return the per-shard metrics for the given shard.
+    ///
+    /// This is the same object used internally by the read and write handles
+    /// of the shard in this process, so it reflects their activity.
+    pub fn shard_metrics(&self, shard_id: &ShardId, shard_name: &str) -> Arc<ShardMetrics> {
+        self.metrics.shards.shard(shard_id, shard_name)
+    }
 }
 
 impl Codec for ShardId {
@@ -736,6 +1630,7 @@ impl Schema<ShardId> for ShardIdSchema {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::future::Future;
     use std::mem;
     use std::pin::Pin;
@@ -745,9 +1640,14 @@ mod tests {
 
     use differential_dataflow::consolidation::consolidate_updates;
     use differential_dataflow::lattice::Lattice;
+    use futures::StreamExt;
     use futures_task::noop_waker;
-
+    use mz_ore::metrics::MetricsRegistry;
     use mz_persist::indexed::encoding::BlobTraceBatchPart;
+    use mz_persist::mem::{MemBlob, MemBlobConfig, MemConsensus};
+    use mz_persist::unreliable::{
+        Fault, Op, UnreliableBlob, UnreliableConsensus, UnreliableHandle,
+    };
     use mz_persist::workload::DataGenerator;
     use mz_persist_types::codec_impls::{StringSchema, VecU8Schema};
     use mz_proto::protobuf_roundtrip;
@@ -759,9 +1659,13 @@ mod tests {
     use timely::progress::Antichain;
 
     use crate::cache::PersistClientCache;
-    use crate::error::{CodecConcreteType, CodecMismatch, UpperMismatch};
-    use crate::internal::paths::BlobKey;
-    use crate::read::ListenEvent;
+    use crate::cfg::{ManualClock, PersistParameters, RetryParameters};
+    use crate::error::{CodecConcreteType, CodecMismatch, SnapshotError, UpperMismatch};
+    use crate::fetch::{DecodeError, DecodeResult, FetchBatchError};
+    use crate::internal::paths::{BlobKey, BlobKeyPrefix};
+    use crate::metrics::{encode_outer_ts_metric, encode_ts_metric, TsMetricEncode};
+    use crate::read::{ListenEvent, Since};
+    use crate::rpc::{NoopPubSubSender, PubSubClientConnection};
 
     use super::*;
 
@@ -817,6 +1721,12 @@ mod tests {
         }
     }
 
+    impl TsMetricEncode for CodecProduct {
+        fn encode_ts_metric(&self) -> i64 {
+            encode_outer_ts_metric(&self.0)
+        }
+    }
+
     impl Lattice for CodecProduct {
         fn join(&self, other: &Self) -> Self {
             Self(self.0.join(&other.0))
@@ -847,10 +1757,56 @@ mod tests {
             .expect("client construction failed")
     }
 
+    /// Returns a new client, configured like [new_test_client], whose in-mem
+    /// blob and consensus inject the faults configured in `faults`.
+    pub fn new_fault_injected_test_client(faults: &UnreliableHandle) -> PersistClient {
+        let cache = new_test_client_cache();
+        let blob = UnreliableBlob::new(
+            Arc::new(MemBlob::open(MemBlobConfig::default())),
+            faults.clone(),
+        );
+        let consensus = UnreliableConsensus::new(Arc::new(MemConsensus::default()), faults.clone());
+        PersistClient::new(
+            cache.cfg.clone(),
+            Arc::new(blob),
+            Arc::new(consensus),
+            Arc::clone(&cache.metrics),
+            Arc::new(IsolatedRuntime::new()),
+            Arc::new(StateCache::new_no_metrics()),
+            Arc::new(NoopPubSubSender),
+        )
+        .expect("client construction failed")
+    }
+
+    /// Returns a new [new_test_client] along with write and read handles to a
+    /// new shard of `String` keys and values.
+    pub async fn new_test_shard() -> (
+        PersistClient,
+        WriteHandle<String, String, u64, i64>,
+        ReadHandle<String, String, u64, i64>,
+    ) {
+        let client = new_test_client().await;
+        let (write, read) = client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        (client, write, read)
+    }
+
+    /// Returns the first `n` of the updates `(("1", "one"), 1, 1)`,
+    /// `(("2", "two"), 2, 1)` and `(("3", "three"), 3, 1)`.
+    pub fn test_data(n: usize) -> Vec<((String, String), u64, i64)> {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("3".to_owned(), "three".to_owned()), 3, 1),
+        ];
+        data[..n].to_vec()
+    }
+
     pub fn all_ok<'a, K, V, T, D, I>(
         iter: I,
         as_of: T,
-    ) -> Vec<((Result<K, String>, Result<V, String>), T, D)>
+    ) -> Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>
     where
         K: Ord + Clone + 'a,
         V: Ord + Clone + 'a,
@@ -876,7 +1832,7 @@ mod tests {
         key: &BlobKey,
     ) -> (
         BlobTraceBatchPart<T>,
-        Vec<((Result<K, String>, Result<V, String>), T, D)>,
+        Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>,
     )
     where
         K: Codec,
@@ -891,14 +1847,40 @@ mod tests {
             .expect("missing part");
         let part = BlobTraceBatchPart::decode(&value).expect("failed to decode part");
         let mut updates = Vec::new();
-        for chunk in part.updates.iter() {
-            for ((k, v), t, d) in chunk.iter() {
-                updates.push(((K::decode(k), V::decode(v)), T::decode(t), D::decode(d)));
-            }
+        let updates_iter = part.updates.iter().flat_map(|chunk| chunk.iter());
+        for (offset, ((k, v), t, d)) in updates_iter.enumerate() {
+            let k = K::decode(k).map_err(|reason| {
+                DecodeError::new::<K>(Some(key.to_string()), Some(offset), reason)
+            });
+            let v = V::decode(v).map_err(|reason| {
+                DecodeError::new::<V>(Some(key.to_string()), Some(offset), reason)
+            });
+            updates.push(((k, v), T::decode(t), D::decode(d)));
         }
         (part, updates)
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn handle_shard_ids() {
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (write, read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let since = client
+            .open_critical_since::<String, String, u64, i64, u64>(
+                shard_id,
+                PersistClient::CONTROLLER_CRITICAL_SINCE,
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("codec mismatch");
+        assert_eq!(write.shard_id(), shard_id);
+        assert_eq!(read.shard_id(), shard_id);
+        assert_eq!(since.shard_id(), shard_id);
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn sanity_check() {
@@ -1018,9 +2000,21 @@ mod tests {
             .expect("invalid shard id");
         let mut client = new_test_client().await;
 
+        // Open the shard with distinct diagnostics, so that we can check that
+        // codec mismatches point back at the handle that registered them.
+        let registered_by = Diagnostics {
+            shard_name: "shard0".to_owned(),
+            handle_purpose: "registering handle".to_owned(),
+        };
         let (mut write0, mut read0) = client
-            .expect_open::<String, String, u64, i64>(shard_id0)
-            .await;
+            .open::<String, String, u64, i64>(
+                shard_id0,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                registered_by.clone(),
+            )
+            .await
+            .expect("codec mismatch");
 
         write0.expect_compare_and_append(&data, 0, 4).await;
 
@@ -1049,6 +2043,7 @@ mod tests {
                 InvalidUsage::CodecMismatch(Box::new(CodecMismatch {
                     requested: codecs("Vec<u8>", "String", "u64", "i64"),
                     actual: codecs("String", "String", "u64", "i64"),
+                    registered_by: Some(registered_by.clone()),
                 }))
             );
             assert_eq!(
@@ -1064,6 +2059,7 @@ mod tests {
                 InvalidUsage::CodecMismatch(Box::new(CodecMismatch {
                     requested: codecs("String", "Vec<u8>", "u64", "i64"),
                     actual: codecs("String", "String", "u64", "i64"),
+                    registered_by: Some(registered_by.clone()),
                 }))
             );
             assert_eq!(
@@ -1079,6 +2075,7 @@ mod tests {
                 InvalidUsage::CodecMismatch(Box::new(CodecMismatch {
                     requested: codecs("String", "String", "i64", "i64"),
                     actual: codecs("String", "String", "u64", "i64"),
+                    registered_by: Some(registered_by.clone()),
                 }))
             );
             assert_eq!(
@@ -1094,6 +2091,7 @@ mod tests {
                 InvalidUsage::CodecMismatch(Box::new(CodecMismatch {
                     requested: codecs("String", "String", "u64", "u64"),
                     actual: codecs("String", "String", "u64", "i64"),
+                    registered_by: Some(registered_by.clone()),
                 }))
             );
 
@@ -1113,6 +2111,7 @@ mod tests {
                 InvalidUsage::CodecMismatch(Box::new(CodecMismatch {
                     requested: codecs("Vec<u8>", "String", "u64", "i64"),
                     actual: codecs("String", "String", "u64", "i64"),
+                    registered_by: Some(registered_by.clone()),
                 }))
             );
             assert_eq!(
@@ -1128,6 +2127,7 @@ mod tests {
                 InvalidUsage::CodecMismatch(Box::new(CodecMismatch {
                     requested: codecs("Vec<u8>", "String", "u64", "i64"),
                     actual: codecs("String", "String", "u64", "i64"),
+                    registered_by: Some(registered_by.clone()),
                 }))
             );
         }
@@ -1150,16 +2150,23 @@ mod tests {
                     Diagnostics::for_tests(),
                 )
                 .await;
+            let unchecked_fetcher1 = client
+                .create_batch_fetcher_unchecked::<String, String, u64, i64>(
+                    shard_id1,
+                    Default::default(),
+                    Default::default(),
+                    Diagnostics::for_tests(),
+                );
             for batch in snap {
                 let res = fetcher1.fetch_leased_part(&batch).await;
+                let unchecked_res = unchecked_fetcher1.fetch_leased_part(&batch).await;
                 read0.process_returned_leased_part(batch);
-                assert_eq!(
-                    res.unwrap_err(),
-                    InvalidUsage::BatchNotFromThisShard {
-                        batch_shard: shard_id0,
-                        handle_shard: shard_id1,
-                    }
-                );
+                let expected = InvalidUsage::BatchNotFromThisShard {
+                    batch_shard: shard_id0,
+                    handle_shard: shard_id1,
+                };
+                assert_eq!(res.unwrap_err(), expected);
+                assert_eq!(unchecked_res.unwrap_err(), expected);
             }
         }
 
@@ -1306,63 +2313,523 @@ mod tests {
 
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
-    async fn fetch_upper() {
+    async fn shard_metrics() {
         let data = vec![
             (("1".to_owned(), "one".to_owned()), 1, 1),
             (("2".to_owned(), "two".to_owned()), 2, 1),
         ];
 
         let client = new_test_client().await;
-
         let shard_id = ShardId::new();
-
-        let (mut write1, _read1) = client
-            .expect_open::<String, String, u64, i64>(shard_id)
-            .await;
-
-        let (mut write2, _read2) = client
+        let (mut write, _read) = client
             .expect_open::<String, String, u64, i64>(shard_id)
             .await;
-
-        write1
-            .expect_append(&data[..], write1.upper().clone(), vec![3])
+        let (mut other, _other_read) = client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
             .await;
 
-        // The shard-global upper does advance, even if this writer didn't advance its local upper.
-        assert_eq!(write2.fetch_recent_upper().await, &Antichain::from_elem(3));
-
-        // The writer-local upper should advance, even if it was another writer
-        // that advanced the frontier.
-        assert_eq!(write2.upper(), &Antichain::from_elem(3));
+        // The handle is the same object used by the machinery, so it sees
+        // writes made through the shard's handles.
+        let shard_metrics = client.shard_metrics(&shard_id, "");
+        assert_eq!(shard_metrics.appends(), 0);
+        assert_eq!(shard_metrics.bytes_written(), 0);
+
+        write.expect_compare_and_append(&data[..1], 0, 2).await;
+        write.expect_compare_and_append(&data[1..], 2, 3).await;
+        other.expect_compare_and_append(&data, 0, 3).await;
+
+        assert_eq!(shard_metrics.appends(), 2);
+        assert!(shard_metrics.bytes_written() > 0);
+        assert!(Arc::ptr_eq(
+            &shard_metrics,
+            &client.shard_metrics(&shard_id, "")
+        ));
     }
 
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
-    async fn append_with_invalid_upper() {
+    async fn fetch_decode_error() {
         let data = vec![
+            (("".to_owned(), "zero".to_owned()), 1, 1),
             (("1".to_owned(), "one".to_owned()), 1, 1),
-            (("2".to_owned(), "two".to_owned()), 2, 1),
         ];
 
         let client = new_test_client().await;
-
         let shard_id = ShardId::new();
-
-        let (mut write, _read) = client
+        let (mut write, mut read) = client
             .expect_open::<String, String, u64, i64>(shard_id)
             .await;
+        write.expect_compare_and_append(&data, 0, 2).await;
 
-        write
-            .expect_append(&data[..], write.upper().clone(), vec![3])
-            .await;
+        // Read the keys back as `()`, which only decodes from empty bytes.
+        let fetcher = client.create_batch_fetcher_unchecked::<(), String, u64, i64>(
+            shard_id,
+            Default::default(),
+            Default::default(),
+            Diagnostics::for_tests(),
+        );
+        let snap = read
+            .snapshot(Antichain::from_elem(1))
+            .await
+            .expect("cannot serve requested as_of");
+        let mut updates = Vec::new();
+        for part in snap {
+            let fetched = fetcher
+                .fetch_leased_part(&part)
+                .await
+                .expect("part is from this shard")
+                .expect("part is fetchable");
+            updates.extend(fetched);
+            read.process_returned_leased_part(part);
+        }
+        updates.sort();
+
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].0, (Ok(()), Ok("zero".to_owned())));
+        let (key, val) = &updates[1].0;
+        assert_eq!(val, &Ok("one".to_owned()));
+        let err = key.as_ref().expect_err("key should not decode");
+        assert_eq!(err.codec_name, "()");
+        assert_eq!(err.reason, "decode expected empty buf got 1 bytes");
+        assert!(err
+            .blob_key
+            .as_ref()
+            .expect("fetched parts know their blob")
+            .starts_with(&shard_id.to_string()));
+        assert_eq!(err.offset, Some(1));
+        assert_eq!(client.shard_metrics(&shard_id, "").decode_failures.get(), 1);
+    }
 
-        let data = vec![
-            (("5".to_owned(), "fünf".to_owned()), 5, 1),
-            (("6".to_owned(), "sechs".to_owned()), 6, 1),
-        ];
-        let res = write
-            .append(
-                data.iter(),
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn open_create_if_missing() {
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let no_create = OpenOptions {
+            create_if_missing: false,
+        };
+        let expected = InvalidUsage::ShardNeverUsed { shard_id };
+
+        // Opening a never-used shard without creating it errors and leaves no
+        // state behind.
+        assert_eq!(
+            client
+                .open_leased_reader_with_options::<String, String, u64, i64>(
+                    shard_id,
+                    Arc::new(StringSchema),
+                    Arc::new(StringSchema),
+                    Diagnostics::for_tests(),
+                    no_create,
+                )
+                .await
+                .unwrap_err(),
+            expected
+        );
+        assert_eq!(
+            client
+                .open_writer_with_options::<String, String, u64, i64>(
+                    shard_id,
+                    Arc::new(StringSchema),
+                    Arc::new(StringSchema),
+                    Diagnostics::for_tests(),
+                    no_create,
+                )
+                .await
+                .unwrap_err(),
+            expected
+        );
+        assert_eq!(
+            client
+                .open_critical_since_with_options::<String, String, u64, i64, i64>(
+                    shard_id,
+                    PersistClient::CONTROLLER_CRITICAL_SINCE,
+                    Diagnostics::for_tests(),
+                    no_create,
+                )
+                .await
+                .unwrap_err(),
+            expected
+        );
+        assert_eq!(
+            client
+                .consensus
+                .head(&shard_id.to_string())
+                .await
+                .expect("consensus available"),
+            None
+        );
+
+        // With create_if_missing, the shard is initialized as usual.
+        let since = client
+            .open_critical_since_with_options::<String, String, u64, i64, i64>(
+                shard_id,
+                PersistClient::CONTROLLER_CRITICAL_SINCE,
+                Diagnostics::for_tests(),
+                OpenOptions::default(),
+            )
+            .await
+            .expect("codec mismatch");
+        assert_eq!(since.since(), &Antichain::from_elem(0));
+
+        // Once the shard exists, it can be opened without creating it.
+        let mut write = client
+            .open_writer_with_options::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                Diagnostics::for_tests(),
+                no_create,
+            )
+            .await
+            .expect("shard exists");
+        let data = vec![(("1".to_owned(), "one".to_owned()), 1, 1)];
+        write.expect_compare_and_append(&data, 0, 2).await;
+        let mut read = client
+            .open_leased_reader_with_options::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                Diagnostics::for_tests(),
+                no_create,
+            )
+            .await
+            .expect("shard exists");
+        assert_eq!(read.expect_snapshot_and_fetch(1).await, all_ok(&data, 1));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn open_readonly_reader() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("3".to_owned(), "three".to_owned()), 3, 1),
+        ];
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // A read-only reader doesn't initialize a never-used shard.
+        assert_eq!(
+            client
+                .open_readonly_reader::<String, String, u64, i64>(
+                    shard_id,
+                    Arc::new(StringSchema),
+                    Arc::new(StringSchema),
+                    Diagnostics::for_tests(),
+                )
+                .await
+                .unwrap_err(),
+            InvalidUsage::ShardNeverUsed { shard_id }
+        );
+
+        let (mut write, read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data[..2], 0, 3).await;
+
+        // Returns the seqno and the leased readers in the latest state of the
+        // shard.
+        async fn readers(client: &PersistClient, shard_id: ShardId) -> (u64, Vec<String>) {
+            let state = client
+                .inspect_shard::<u64>(&shard_id)
+                .await
+                .expect("shard exists");
+            let state = serde_json::to_value(state).expect("serializable");
+            let readers = state["leased_readers"]
+                .as_object()
+                .expect("map")
+                .keys()
+                .cloned()
+                .collect();
+            (state["seqno"].as_u64().expect("seqno"), readers)
+        }
+        let before = readers(&client, shard_id).await;
+        assert_eq!(before.1, vec![read.reader_id.to_string()]);
+
+        let mut readonly = client
+            .open_readonly_reader::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("shard exists");
+        assert_eq!(readonly.since(), Antichain::from_elem(0));
+        let snapshot = readonly
+            .snapshot_and_fetch(Antichain::from_elem(2))
+            .await
+            .expect("as_of beyond since");
+        assert_eq!(snapshot, all_ok(&data[..2], 2));
+        drop(readonly);
+
+        // Neither opening, reading, nor dropping the handle wrote any state.
+        assert_eq!(readers(&client, shard_id).await, before);
+
+        // Listens are served without registering a reader either.
+        let readonly = client
+            .open_readonly_reader::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("shard exists");
+        let mut listen = readonly
+            .listen(Antichain::from_elem(2))
+            .await
+            .expect("as_of beyond since");
+        write.expect_compare_and_append(&data[2..], 3, 4).await;
+        let mut updates = Vec::new();
+        while listen.frontier().less_than(&4) {
+            for event in listen.fetch_next().await.expect("since not advanced") {
+                if let ListenEvent::Updates(mut x) = event {
+                    updates.append(&mut x);
+                }
+            }
+        }
+        assert_eq!(updates, all_ok(&data[2..], 2));
+        assert_eq!(readers(&client, shard_id).await.1, before.1);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn readonly_reader_since_advanced() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+
+        let mut readonly = client
+            .open_readonly_reader::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("shard exists");
+        let as_of = Antichain::from_elem(1);
+        let batches = readonly
+            .machine
+            .snapshot(&as_of)
+            .await
+            .expect("as_of beyond since");
+
+        // Race the read with compaction and GC deleting the blobs of the
+        // snapshot. Delete them directly instead of waiting for that to
+        // happen.
+        for batch in batches.iter() {
+            for part in batch.parts.iter() {
+                client
+                    .blob
+                    .delete(&part.key.complete(&shard_id))
+                    .await
+                    .expect("blob available");
+            }
+        }
+
+        // The since hasn't passed the as_of, so the read fails with an error
+        // that says it can be retried at the same as_of.
+        assert_eq!(
+            readonly.fetch_snapshot(&as_of, batches.clone()).await,
+            Err(SnapshotError::Compacted)
+        );
+
+        // Once the since has passed the as_of, instead of returning partial
+        // data, the read fails with the new since.
+        read.downgrade_since(&Antichain::from_elem(2)).await;
+        assert_eq!(
+            readonly.fetch_snapshot(&as_of, batches).await,
+            Err(SnapshotError::SinceAdvanced(Since(Antichain::from_elem(2))))
+        );
+
+        // A fresh snapshot can't be served at the old as_of anymore.
+        assert_eq!(
+            readonly.snapshot_and_fetch(as_of).await,
+            Err(SnapshotError::Since(Since(Antichain::from_elem(2))))
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn snapshot_fetch_error() {
+        let data = test_data(2);
+
+        let faults = UnreliableHandle::new(0, 1.0, 0.0);
+        let client = new_fault_injected_test_client(&faults);
+        client
+            .cfg
+            .set_config(&crate::internal::machine::BLOB_READ_RETRY_MAX_ATTEMPTS, 1);
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+
+        let mut readonly = client
+            .open_readonly_reader::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("shard exists");
+        let as_of = Antichain::from_elem(2);
+        let batches = readonly
+            .machine
+            .snapshot(&as_of)
+            .await
+            .expect("as_of beyond since");
+
+        // Once the blob read retry budget runs out, the error is returned to
+        // the caller instead of taking down the process.
+        faults.fail_randomly(Op::BlobGet, 1.0, Fault::Determinate);
+        match read.snapshot_and_fetch(as_of.clone()).await {
+            Err(SnapshotError::Fetch(FetchBatchError::External(..))) => {}
+            res => panic!("expected a fetch error: {:?}", res),
+        }
+        match readonly.fetch_snapshot(&as_of, batches).await {
+            Err(SnapshotError::Fetch(FetchBatchError::External(..))) => {}
+            res => panic!("expected a fetch error: {:?}", res),
+        }
+
+        // The reads succeed again once the blob store recovers.
+        faults.clear_op_faults();
+        assert_eq!(
+            read.snapshot_and_fetch(as_of.clone()).await,
+            Ok(all_ok(&data, 2))
+        );
+        assert_eq!(
+            readonly.snapshot_and_fetch(as_of).await,
+            Ok(all_ok(&data, 2))
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn batch_fetcher_unchecked() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // Creating an unchecked fetcher doesn't touch consensus, so it doesn't
+        // even initialize the shard.
+        let _ = client.create_batch_fetcher_unchecked::<String, String, u64, i64>(
+            shard_id,
+            Default::default(),
+            Default::default(),
+            Diagnostics::for_tests(),
+        );
+        assert_eq!(
+            client
+                .consensus
+                .head(&shard_id.to_string())
+                .await
+                .expect("consensus available"),
+            None
+        );
+
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+
+        // Once the shard exists, the fetcher works like a checked one.
+        let fetcher = client.create_batch_fetcher_unchecked::<String, String, u64, i64>(
+            shard_id,
+            Default::default(),
+            Default::default(),
+            Diagnostics::for_tests(),
+        );
+        let mut actual = Vec::new();
+        let parts = read
+            .snapshot(Antichain::from_elem(2))
+            .await
+            .expect("cannot serve requested as_of");
+        for part in parts {
+            let fetched = fetcher
+                .fetch_leased_part(&part)
+                .await
+                .expect("part is from this shard")
+                .expect("part is fetchable");
+            actual.extend(fetched);
+            read.process_returned_leased_part(part);
+        }
+        consolidate_updates(&mut actual);
+        assert_eq!(actual, all_ok(&data, 2));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn fetch_upper() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+
+        let shard_id = ShardId::new();
+
+        let (mut write1, _read1) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+
+        let (mut write2, _read2) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+
+        write1
+            .expect_append(&data[..], write1.upper().clone(), vec![3])
+            .await;
+
+        // The shard-global upper does advance, even if this writer didn't advance its local upper.
+        assert_eq!(write2.fetch_recent_upper().await, &Antichain::from_elem(3));
+
+        // The writer-local upper should advance, even if it was another writer
+        // that advanced the frontier.
+        assert_eq!(write2.upper(), &Antichain::from_elem(3));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn append_with_invalid_upper() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+
+        let shard_id = ShardId::new();
+
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+
+        write
+            .expect_append(&data[..], write.upper().clone(), vec![3])
+            .await;
+
+        let data = vec![
+            (("5".to_owned(), "fünf".to_owned()), 5, 1),
+            (("6".to_owned(), "sechs".to_owned()), 6, 1),
+        ];
+        let res = write
+            .append(
+                data.iter(),
                 Antichain::from_elem(5),
                 Antichain::from_elem(7),
             )
@@ -1389,11 +2856,7 @@ mod tests {
             true
         }
 
-        let client = new_test_client().await;
-
-        let (write, read) = client
-            .expect_open::<String, String, u64, i64>(ShardId::new())
-            .await;
+        let (client, write, read) = new_test_shard().await;
 
         assert!(is_send_sync(client));
         assert!(is_send_sync(write));
@@ -1403,11 +2866,7 @@ mod tests {
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn compare_and_append() {
-        let data = vec![
-            (("1".to_owned(), "one".to_owned()), 1, 1),
-            (("2".to_owned(), "two".to_owned()), 2, 1),
-            (("3".to_owned(), "three".to_owned()), 3, 1),
-        ];
+        let data = test_data(3);
 
         let id = ShardId::new();
         let client = new_test_client().await;
@@ -1723,6 +3182,23 @@ mod tests {
         );
     }
 
+    #[mz_ore::test]
+    fn shard_id_from_seed() {
+        assert_eq!(
+            ShardId::from_seed("persist-test"),
+            ShardId::from_seed("persist-test")
+        );
+        assert_ne!(
+            ShardId::from_seed("persist-test"),
+            ShardId::from_seed("persist-test2")
+        );
+        // The derivation must be stable across processes and releases.
+        assert_eq!(
+            ShardId::from_seed("persist-test").to_string(),
+            "s85c58ec7-eb02-5542-9eed-5a577adc3a28"
+        );
+    }
+
     #[mz_ore::test]
     fn shard_id_human_readable_serde() {
         #[derive(Debug, Serialize, Deserialize)]
@@ -1761,9 +3237,19 @@ mod tests {
     async fn concurrency() {
         let data = DataGenerator::small();
 
+        // Writers must tolerate flaky blob and consensus operations, including
+        // compare_and_set calls whose outcome is unknown to them.
+        let faults = UnreliableHandle::new(0, 1.0, 0.0);
+        for op in Op::BLOB.iter().chain(Op::CONSENSUS) {
+            faults.fail_randomly(*op, 0.05, Fault::Determinate);
+        }
+        faults.fail_next(Op::ConsensusCompareAndSet, 3, Fault::Indeterminate);
+        faults.fail_next(Op::ConsensusCompareAndSet, 1, Fault::IndeterminateAfterRun);
+        faults.set_op_latency(Op::ConsensusCompareAndSet, Duration::from_millis(1));
+
         const NUM_WRITERS: usize = 2;
         let id = ShardId::new();
-        let client = new_test_client().await;
+        let client = new_fault_injected_test_client(&faults);
         let mut handles = Vec::<mz_ore::task::JoinHandle<()>>::new();
         for idx in 0..NUM_WRITERS {
             let (data, client) = (data.clone(), client.clone());
@@ -1826,10 +3312,8 @@ mod tests {
                 let (mut write, _) = client.expect_open::<Vec<u8>, Vec<u8>, u64, i64>(id).await;
 
                 while let Some(batch) = batch_rx.recv().await {
-                    let lower = batch.lower().clone();
-                    let upper = batch.upper().clone();
                     write
-                        .append_batch(batch, lower, upper)
+                        .append_batch_owned(batch)
                         .await
                         .expect("invalid usage")
                         .expect("unexpected upper");
@@ -1849,6 +3333,7 @@ mod tests {
             read.expect_snapshot_and_fetch(max_ts).await,
             all_ok(expected.iter(), max_ts)
         );
+        assert!(faults.injected(Op::ConsensusCompareAndSet) >= 4);
     }
 
     // Regression test for #12131. Snapshot with as_of >= upper would
@@ -1929,32 +3414,263 @@ mod tests {
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn heartbeat_task_shutdown() {
-        // Verify that the ReadHandle and WriteHandle background heartbeat tasks
-        // shut down cleanly after the handle is expired.
+        // Verify that the shared ReadHandle background heartbeat tasks shut
+        // down cleanly after the last handle is expired.
         let mut cache = new_test_client_cache();
         cache
             .cfg
             .dynamic
             .set_reader_lease_duration(Duration::from_millis(1));
         cache.cfg.writer_lease_duration = Duration::from_millis(1);
-        let (_write, mut read) = cache
+        let (_write, read) = cache
             .open(PersistLocation::new_in_mem())
             .await
             .expect("client construction failed")
             .expect_open::<(), (), u64, i64>(ShardId::new())
             .await;
-        let mut read_unexpired_state = read
-            .unexpired_state
-            .take()
-            .expect("handle should have unexpired state");
+        // Take the tasks, so that expiring the handle doesn't abort them.
+        let read_heartbeat_tasks = mem::take(
+            &mut read
+                .machine
+                .applier
+                .reader_heartbeats()
+                .lock()
+                .expect("lock poisoned")
+                .tasks,
+        );
+        assert!(!read_heartbeat_tasks.is_empty());
         read.expire().await;
-        for read_heartbeat_task in mem::take(&mut read_unexpired_state._heartbeat_tasks) {
+        for read_heartbeat_task in read_heartbeat_tasks {
             let () = read_heartbeat_task
                 .await
                 .expect("task should shutdown cleanly");
         }
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn reader_lease_expiry_manual_clock() {
+        let data = [
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let clock = ManualClock::new(1_000_000);
+        let cache = PersistClientCache::new(
+            PersistConfig::for_tests_with_now(clock.now_fn()),
+            &MetricsRegistry::new(),
+            |_, _| PubSubClientConnection::noop(),
+        );
+        let lease_duration = cache.cfg.dynamic.reader_lease_duration();
+        let (mut write, mut read) = cache
+            .open(PersistLocation::new_in_mem())
+            .await
+            .expect("client construction failed")
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        let registered_ts = clock.now();
+
+        // Right at the lease deadline, the reader is still alive, even after
+        // another state transition has had the chance to expire it.
+        //
+        // NB: Heartbeating with the registration timestamp doesn't extend the
+        // lease, so we can use it to check whether the reader still exists.
+        clock.advance(lease_duration);
+        write.expect_compare_and_append(&data[..1], 0, 2).await;
+        let (_, existed, _) = read
+            .machine
+            .heartbeat_leased_reader(&read.reader_id, registered_ts)
+            .await;
+        assert!(existed);
+
+        // Once the clock crosses the deadline, the next state transition
+        // expires it.
+        clock.advance(Duration::from_millis(1));
+        write.expect_compare_and_append(&data[1..], 2, 3).await;
+        let (_, existed, _) = read
+            .machine
+            .heartbeat_leased_reader(&read.reader_id, clock.now())
+            .await;
+        assert!(!existed);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn watch_shard() {
+        let data = [
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+        // Override the polling fallback so that it's useless, to check that
+        // the events are driven by the writes themselves.
+        PersistParameters {
+            next_listen_batch_retryer: Some(RetryParameters {
+                initial_backoff: Duration::from_secs(1_000_000),
+                multiplier: 1,
+                clamp: Duration::from_secs(1_000_000),
+            }),
+            ..PersistParameters::default()
+        }
+        .apply(&client.cfg);
+
+        let shard_id = ShardId::new();
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let events = client
+            .watch_shard::<String, String, u64, i64>(shard_id, Diagnostics::for_tests())
+            .await
+            .expect("codec mismatch");
+        futures::pin_mut!(events);
+
+        let event = events.next().await.expect("stream never ends");
+        assert_eq!(event.since, Antichain::from_elem(0));
+        assert_eq!(event.upper, Antichain::from_elem(0));
+        let mut seqno = event.seqno;
+
+        write.expect_compare_and_append(&data[..1], 0, 2).await;
+        write.expect_compare_and_append(&data[1..], 2, 3).await;
+        // The two appends might be coalesced into a single event, but the
+        // last one must always be observed.
+        loop {
+            let event = events.next().await.expect("stream never ends");
+            assert!(event.seqno > seqno);
+            seqno = event.seqno;
+            if event.upper == Antichain::from_elem(3) {
+                break;
+            }
+            assert_eq!(event.upper, Antichain::from_elem(2));
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn heartbeat_shared_by_readers() {
+        const NUM_READERS: usize = 10;
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (_write, read) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+        let heartbeats = Arc::clone(read.machine.applier.reader_heartbeats());
+        let num_tasks = heartbeats.lock().expect("lock poisoned").tasks.len();
+        assert!(num_tasks > 0);
+
+        // Additional readers of the shard register with the same heartbeat,
+        // instead of starting their own.
+        let mut readers = vec![read];
+        while readers.len() < NUM_READERS {
+            let (_write, read) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+            readers.push(read);
+        }
+        let read = readers[0].clone("heartbeat_shared_by_readers").await;
+        readers.push(read);
+        {
+            let heartbeats = heartbeats.lock().expect("lock poisoned");
+            assert_eq!(heartbeats.readers.len(), NUM_READERS + 1);
+            assert_eq!(heartbeats.tasks.len(), num_tasks);
+        }
+
+        // The heartbeat keeps running until the last reader is gone, whether
+        // it's expired or dropped.
+        let last = readers.pop().expect("readers are not empty");
+        for read in readers.drain(..NUM_READERS / 2) {
+            read.expire().await;
+        }
+        drop(readers);
+        {
+            let heartbeats = heartbeats.lock().expect("lock poisoned");
+            assert_eq!(heartbeats.readers.len(), 1);
+            assert_eq!(heartbeats.tasks.len(), num_tasks);
+        }
+        last.expire().await;
+        {
+            let heartbeats = heartbeats.lock().expect("lock poisoned");
+            assert!(heartbeats.readers.is_empty());
+            assert!(heartbeats.tasks.is_empty());
+        }
+
+        // A new reader starts it up again.
+        let (_write, _read) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+        assert_eq!(
+            heartbeats.lock().expect("lock poisoned").tasks.len(),
+            num_tasks
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn clone_with_lease() {
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (_write, read) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+        let default_lease = client.cfg.dynamic.reader_lease_duration();
+        let short_lease = Duration::from_secs(1);
+        assert!(short_lease < default_lease);
+
+        // Returns the lease durations (in millis) of the leased readers in
+        // the latest state of the shard, keyed by reader id.
+        async fn leases(client: &PersistClient, shard_id: ShardId) -> BTreeMap<String, u64> {
+            let state = client
+                .inspect_shard::<u64>(&shard_id)
+                .await
+                .expect("shard exists");
+            let state = serde_json::to_value(state).expect("serializable");
+            state["leased_readers"]
+                .as_object()
+                .expect("map")
+                .iter()
+                .map(|(reader_id, reader)| {
+                    let lease = reader["lease_duration_ms"].as_u64().expect("lease");
+                    (reader_id.clone(), lease)
+                })
+                .collect()
+        }
+
+        let ms = |x: Duration| u64::try_from(x.as_millis()).expect("lease fits");
+        let clone = read.clone_with_lease("clone_with_lease", short_lease).await;
+        assert_eq!(
+            leases(&client, shard_id).await,
+            BTreeMap::from([
+                (read.reader_id.to_string(), ms(default_lease)),
+                (clone.reader_id.to_string(), ms(short_lease)),
+            ])
+        );
+
+        // The shared heartbeat keeps the shorter lease alive too.
+        let heartbeats = Arc::clone(read.machine.applier.reader_heartbeats());
+        assert_eq!(
+            heartbeats
+                .lock()
+                .expect("lock poisoned")
+                .min_lease_duration(),
+            Some(short_lease)
+        );
+
+        // Dropping the clone releases its lease, but not the parent's.
+        drop(clone);
+        assert_eq!(
+            heartbeats
+                .lock()
+                .expect("lock poisoned")
+                .min_lease_duration(),
+            Some(default_lease)
+        );
+        mz_ore::retry::Retry::default()
+            .max_duration(Duration::from_secs(60))
+            .retry_async(|_| async {
+                let leases = leases(&client, shard_id).await;
+                if leases == BTreeMap::from([(read.reader_id.to_string(), ms(default_lease))]) {
+                    Ok(())
+                } else {
+                    Err(leases)
+                }
+            })
+            .await
+            .expect("clone should be expired");
+    }
+
     /// Regression test for 16743, where the nightly tests found that calling
     /// maybe_heartbeat_writer or maybe_heartbeat_reader on a "tombstone" shard
     /// would panic.
@@ -1978,6 +3694,791 @@ mod tests {
         read.maybe_heartbeat_reader().await;
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn purge_tombstone() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+
+        // Purging requires an explicit opt-in and only touches tombstones.
+        let purge = |older_than| {
+            client.purge_tombstone::<String, String, u64, i64>(shard_id, older_than, true)
+        };
+        assert_eq!(
+            client
+                .purge_tombstone::<String, String, u64, i64>(shard_id, Duration::ZERO, false)
+                .await,
+            Err(InvalidUsage::PurgeNotForced { shard_id })
+        );
+        assert_eq!(
+            purge(Duration::ZERO).await,
+            Ok(PurgeTombstoneResult::NotTombstone)
+        );
+        assert_eq!(
+            client
+                .purge_tombstone::<String, String, u64, i64>(ShardId::new(), Duration::ZERO, true)
+                .await,
+            Ok(PurgeTombstoneResult::NotFound)
+        );
+
+        // Turn the shard into a tombstone.
+        const EMPTY: &[((String, String), u64, i64)] = &[];
+        let () = read.downgrade_since(&Antichain::new()).await;
+        let () = write
+            .compare_and_append(EMPTY, Antichain::from_elem(3), Antichain::new())
+            .await
+            .expect("usage should be valid")
+            .expect("upper should match");
+        read.expire().await;
+        write.expire().await;
+        client
+            .finalize_shard::<String, String, u64, i64>(
+                shard_id,
+                Diagnostics::for_tests(),
+                FinalizeFrontiers::RequireEmpty,
+            )
+            .await
+            .expect("invalid usage");
+        assert!(client
+            .consensus
+            .head(&shard_id.to_string())
+            .await
+            .expect("consensus available")
+            .is_some());
+
+        match purge(Duration::from_secs(60 * 60)).await {
+            Ok(PurgeTombstoneResult::TooRecent { .. }) => {}
+            x => panic!("expected the tombstone to be too recent: {:?}", x),
+        }
+        match purge(Duration::ZERO).await {
+            Ok(PurgeTombstoneResult::Purged { .. }) => {}
+            x => panic!("expected the tombstone to be purged: {:?}", x),
+        }
+
+        // Nothing is left of the shard in consensus or blob.
+        assert_eq!(
+            client
+                .consensus
+                .head(&shard_id.to_string())
+                .await
+                .expect("consensus available"),
+            None
+        );
+        let mut blobs = Vec::new();
+        client
+            .blob
+            .list_keys_and_metadata(&BlobKeyPrefix::Shard(&shard_id).to_string(), &mut |x| {
+                blobs.push(x.key.to_owned())
+            })
+            .await
+            .expect("blob available");
+        assert_eq!(blobs, Vec::<String>::new());
+        assert_eq!(
+            purge(Duration::ZERO).await,
+            Ok(PurgeTombstoneResult::NotFound)
+        );
+
+        // Opening the shard again creates a brand-new shard.
+        assert_eq!(
+            client
+                .is_finalized::<String, String, u64, i64>(shard_id, Diagnostics::for_tests())
+                .await,
+            Ok(false)
+        );
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        assert_eq!(write.upper(), &Antichain::from_elem(0));
+        write.expect_compare_and_append(&data[..1], 0, 2).await;
+        assert_eq!(
+            read.expect_snapshot_and_fetch(1).await,
+            all_ok(&data[..1], 1)
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn purge_tombstone_indeterminate() {
+        let faults = UnreliableHandle::new(0, 1.0, 0.0);
+        let client = new_fault_injected_test_client(&faults);
+
+        for fault in [Fault::Indeterminate, Fault::IndeterminateAfterRun] {
+            let shard_id = ShardId::new();
+            let (mut write, mut read) = client
+                .expect_open::<String, String, u64, i64>(shard_id)
+                .await;
+            const EMPTY: &[((String, String), u64, i64)] = &[];
+            let () = read.downgrade_since(&Antichain::new()).await;
+            let () = write
+                .compare_and_append(EMPTY, Antichain::from_elem(0), Antichain::new())
+                .await
+                .expect("usage should be valid")
+                .expect("upper should match");
+            read.expire().await;
+            write.expire().await;
+            client
+                .finalize_shard::<String, String, u64, i64>(
+                    shard_id,
+                    Diagnostics::for_tests(),
+                    FinalizeFrontiers::RequireEmpty,
+                )
+                .await
+                .expect("invalid usage");
+
+            // Whether or not the delete actually went through, the purge
+            // figures it out and cleans up after the shard.
+            faults.fail_next(Op::ConsensusDelete, 1, fault);
+            match client
+                .purge_tombstone::<String, String, u64, i64>(shard_id, Duration::ZERO, true)
+                .await
+            {
+                Ok(PurgeTombstoneResult::Purged { blobs_deleted }) => {
+                    assert!(blobs_deleted > 0, "{:?}", fault)
+                }
+                x => panic!("expected the tombstone to be purged: {:?}", x),
+            }
+            assert_eq!(
+                client
+                    .consensus
+                    .head(&shard_id.to_string())
+                    .await
+                    .expect("consensus available"),
+                None
+            );
+            let mut blobs = Vec::new();
+            client
+                .blob
+                .list_keys_and_metadata(&BlobKeyPrefix::Shard(&shard_id).to_string(), &mut |x| {
+                    blobs.push(x.key.to_owned())
+                })
+                .await
+                .expect("blob available");
+            assert_eq!(blobs, Vec::<String>::new(), "{:?}", fault);
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn force_finalize_shard() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let (mut write, read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+        read.expire().await;
+
+        // Close the shard for writes, but "lose" a critical since handle that
+        // is holding back the since.
+        let since = client
+            .open_critical_since::<String, String, u64, i64, i64>(
+                shard_id,
+                CriticalReaderId::new(),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("codec mismatch");
+        drop(since);
+        const EMPTY: &[((String, String), u64, i64)] = &[];
+        let () = write
+            .compare_and_append(EMPTY, Antichain::from_elem(3), Antichain::new())
+            .await
+            .expect("usage should be valid")
+            .expect("upper should match");
+        write.expire().await;
+
+        // A regular finalization refuses to proceed.
+        assert_eq!(
+            client
+                .finalize_shard::<String, String, u64, i64>(
+                    shard_id,
+                    Diagnostics::for_tests(),
+                    FinalizeFrontiers::RequireEmpty,
+                )
+                .await,
+            Err(InvalidUsage::FinalizationError {
+                since: Antichain::from_elem(0),
+                upper: Antichain::new(),
+            })
+        );
+        assert_eq!(
+            client
+                .is_finalized::<String, String, u64, i64>(shard_id, Diagnostics::for_tests())
+                .await,
+            Ok(false)
+        );
+
+        // But a forced one does, and is idempotent.
+        for _ in 0..2 {
+            client
+                .finalize_shard::<String, String, u64, i64>(
+                    shard_id,
+                    Diagnostics::for_tests(),
+                    FinalizeFrontiers::ForceEmpty,
+                )
+                .await
+                .expect("invalid usage");
+        }
+        assert_eq!(
+            client
+                .is_finalized::<String, String, u64, i64>(shard_id, Diagnostics::for_tests())
+                .await,
+            Ok(true)
+        );
+        let status = client.shard_status(shard_id).await.expect("valid codecs");
+        assert_eq!(status.upper_codec64_bytes, Vec::<[u8; 8]>::new());
+        assert_eq!(status.since_codec64_bytes, Vec::<[u8; 8]>::new());
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn shard_codecs() {
+        let mut client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // A shard that was never used has no codecs, and asking about it
+        // doesn't create it.
+        assert_eq!(client.shard_codecs(shard_id).await, None);
+        assert_eq!(client.shard_codecs(shard_id).await, None);
+
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write
+            .expect_compare_and_append(&[(("1".to_owned(), "one".to_owned()), 1, 1)], 0, 2)
+            .await;
+        let expected = ShardCodecs {
+            key: String::codec_name(),
+            val: String::codec_name(),
+            ts: u64::codec_name(),
+            diff: i64::codec_name(),
+            registered_by: Some(Diagnostics::for_tests()),
+        };
+
+        // Served from this process's cached state.
+        let codecs = client.shard_codecs(shard_id).await.expect("shard exists");
+        assert_eq!(codecs, expected);
+        assert_eq!(codecs.check::<String, String, u64, i64>(), Ok(()));
+        match codecs.check::<Vec<u8>, Vec<u8>, u64, i64>() {
+            Err(err) => assert_eq!(err.actual.0, String::codec_name()),
+            Ok(()) => panic!("expected a codec mismatch"),
+        }
+
+        // And from durable state.
+        client.shared_states = Arc::new(StateCache::new_no_metrics());
+        assert_eq!(client.shard_codecs(shard_id).await, Some(expected));
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn open_with_codec_check() {
+        let mut client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write
+            .expect_compare_and_append(&[(("1".to_owned(), "one".to_owned()), 1, 1)], 0, 2)
+            .await;
+        write.expire().await;
+
+        // Simulate a process that was restarted with new types, which are able
+        // to read the old ones.
+        client.shared_states = Arc::new(StateCache::new_no_metrics());
+        let res = client
+            .open_with_codec_check::<Vec<u8>, Vec<u8>, u64, i64>(
+                shard_id,
+                Arc::new(VecU8Schema),
+                Arc::new(VecU8Schema),
+                Diagnostics::for_tests(),
+                |_| false,
+            )
+            .await;
+        match res {
+            Err(InvalidUsage::CodecMismatch(err)) => {
+                assert_eq!(err.actual(), ("String", "String", "u64", "i64"))
+            }
+            res => panic!("expected a codec mismatch: {:?}", res.map(|_| ())),
+        }
+
+        client.shared_states = Arc::new(StateCache::new_no_metrics());
+        let (_write, mut read) = client
+            .open_with_codec_check::<Vec<u8>, Vec<u8>, u64, i64>(
+                shard_id,
+                Arc::new(VecU8Schema),
+                Arc::new(VecU8Schema),
+                Diagnostics::for_tests(),
+                |err| {
+                    err.requested() == ("Vec<u8>", "Vec<u8>", "u64", "i64")
+                        && err.actual() == ("String", "String", "u64", "i64")
+                },
+            )
+            .await
+            .expect("mismatch is allowed");
+        assert_eq!(
+            read.expect_snapshot_and_fetch(1).await,
+            vec![((Ok(b"1".to_vec()), Ok(b"one".to_vec())), 1, 1)]
+        );
+    }
+
+    #[mz_ore::test]
+    fn ts_metric_encode() {
+        assert_eq!(encode_ts_metric(&Antichain::from_elem(7u64)), 7);
+        assert_eq!(encode_ts_metric(&Antichain::from_elem(-7i64)), -7);
+        assert_eq!(
+            encode_ts_metric(&Antichain::from_elem(CodecProduct::new(7, 3))),
+            7
+        );
+
+        // Frontiers of partial orders can have several elements, of which the
+        // smallest is reported.
+        let frontier: Antichain<_> = [CodecProduct::new(7, 3), CodecProduct::new(5, 9)]
+            .into_iter()
+            .collect();
+        assert_eq!(frontier.len(), 2);
+        assert_eq!(encode_ts_metric(&frontier), 5);
+
+        // Closed frontiers are reported as the largest possible value.
+        assert_eq!(encode_ts_metric(&Antichain::<u64>::new()), i64::MAX);
+        assert_eq!(encode_ts_metric(&Antichain::<i64>::new()), i64::MAX);
+        assert_eq!(
+            encode_ts_metric(&Antichain::<CodecProduct>::new()),
+            i64::MAX
+        );
+    }
+
+    /// A [tracing::Subscriber] that captures the name and fields of every span
+    /// created while it's the default.
+    #[derive(Default)]
+    struct SpanCapture {
+        next_id: std::sync::atomic::AtomicU64,
+        spans: std::sync::Mutex<Vec<(&'static str, BTreeMap<&'static str, String>)>>,
+    }
+
+    impl tracing::Subscriber for SpanCapture {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            struct Fields<'a>(&'a mut BTreeMap<&'static str, String>);
+            impl tracing::field::Visit for Fields<'_> {
+                fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                    self.0.insert(field.name(), value.to_owned());
+                }
+                fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn Debug) {
+                    self.0.insert(field.name(), format!("{:?}", value));
+                }
+            }
+            let mut fields = BTreeMap::new();
+            span.record(&mut Fields(&mut fields));
+            let mut spans = self.spans.lock().expect("lock poisoned");
+            spans.push((span.metadata().name(), fields));
+            let id = self
+                .next_id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tracing::span::Id::from_u64(id + 1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, _event: &tracing::Event<'_>) {}
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn spans_include_diagnostics() {
+        let capture = Arc::new(SpanCapture::default());
+        let _guard = tracing::subscriber::set_default(Arc::clone(&capture));
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let diagnostics = Diagnostics {
+            shard_name: "orders".to_owned(),
+            handle_purpose: "span test".to_owned(),
+        };
+        let (mut write, mut read) = client
+            .open::<String, String, u64, i64>(
+                shard_id,
+                Arc::new(StringSchema),
+                Arc::new(StringSchema),
+                diagnostics,
+            )
+            .await
+            .expect("codec mismatch");
+        write
+            .expect_compare_and_append(&[(("1".to_owned(), "one".to_owned()), 1, 1)], 0, 2)
+            .await;
+        assert_eq!(read.expect_snapshot_and_fetch(1).await.len(), 1);
+
+        let spans = capture.spans.lock().expect("lock poisoned");
+        for name in [
+            "open",
+            "open_writer_with_options",
+            "apply_unbatched_cmd",
+            "apply_unbatched_cmd::cas",
+            "machine::compare_and_append",
+            "machine::snapshot",
+        ] {
+            let (_, fields) = spans
+                .iter()
+                .find(|(span, _)| *span == name)
+                .unwrap_or_else(|| panic!("missing span {}", name));
+            assert_eq!(fields.get("shard"), Some(&shard_id.to_string()), "{}", name);
+            assert_eq!(fields.get("shard_name").map(String::as_str), Some("orders"));
+            assert_eq!(
+                fields.get("handle_purpose").map(String::as_str),
+                Some("span test")
+            );
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn shard_tuning() {
+        let data = (0..100)
+            .map(|i| ((i.to_string(), "x".repeat(100)), 1u64, 1i64))
+            .collect::<Vec<_>>();
+
+        let mut client = new_test_client().await;
+        client.cfg.dynamic.set_blob_target_size(1024 * 1024);
+        let (small, large) = (ShardId::new(), ShardId::new());
+        let (mut small_write, _) = client.expect_open::<String, String, u64, i64>(small).await;
+        let (mut large_write, _) = client.expect_open::<String, String, u64, i64>(large).await;
+
+        // Writers that are already open pick up the tuning of their shard.
+        let tuning = ShardTuning {
+            blob_target_size: Some(1000),
+            ..Default::default()
+        };
+        client
+            .set_shard_tuning::<String, String, u64, i64>(
+                small,
+                tuning.clone(),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("valid usage");
+        let small_batch = small_write.expect_batch(&data, 0, 2).await;
+        let large_batch = large_write.expect_batch(&data, 0, 2).await;
+        assert!(small_batch.batch.parts.len() > 1);
+        assert_eq!(large_batch.batch.parts.len(), 1);
+        small_batch.delete().await;
+        large_batch.delete().await;
+
+        // The tuning is durable, so writers in other processes agree on it.
+        client.shared_states = Arc::new(StateCache::new_no_metrics());
+        let (mut small_write, _) = client.expect_open::<String, String, u64, i64>(small).await;
+        assert_eq!(small_write.machine.applier.tuning(), tuning);
+        let small_batch = small_write.expect_batch(&data, 0, 2).await;
+        assert!(small_batch.batch.parts.len() > 1);
+        small_batch.delete().await;
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn shard_status() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // A shard that was never used doesn't exist, and asking about it
+        // doesn't create it.
+        let status = client.shard_status(shard_id).await.expect("valid codecs");
+        assert_eq!(status, ShardStatus::missing());
+        assert_eq!(
+            client
+                .consensus
+                .head(&shard_id.to_string())
+                .await
+                .expect("consensus available"),
+            None
+        );
+
+        // A live shard.
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+        read.downgrade_since(&Antichain::from_elem(2)).await;
+        let status = client.shard_status(shard_id).await.expect("valid codecs");
+        assert_eq!(
+            status,
+            ShardStatus {
+                exists: true,
+                is_finalized: false,
+                upper_codec64_bytes: vec![3u64.encode()],
+                since_codec64_bytes: vec![2u64.encode()],
+                codecs: Some((
+                    String::codec_name(),
+                    String::codec_name(),
+                    u64::codec_name(),
+                    i64::codec_name(),
+                )),
+            }
+        );
+
+        // A tombstone.
+        const EMPTY: &[((String, String), u64, i64)] = &[];
+        let () = read.downgrade_since(&Antichain::new()).await;
+        let () = write
+            .compare_and_append(EMPTY, Antichain::from_elem(3), Antichain::new())
+            .await
+            .expect("usage should be valid")
+            .expect("upper should match");
+        read.expire().await;
+        write.expire().await;
+        client
+            .finalize_shard::<String, String, u64, i64>(
+                shard_id,
+                Diagnostics::for_tests(),
+                FinalizeFrontiers::RequireEmpty,
+            )
+            .await
+            .expect("invalid usage");
+        let status = client.shard_status(shard_id).await.expect("valid codecs");
+        assert!(status.exists);
+        assert!(status.is_finalized);
+        assert_eq!(status.upper_codec64_bytes, Vec::<[u8; 8]>::new());
+        assert_eq!(status.since_codec64_bytes, Vec::<[u8; 8]>::new());
+
+        // Shards with other timestamp types are supported too.
+        let shard_id = ShardId::new();
+        let (mut write, _read) = client
+            .expect_open::<String, String, i64, i64>(shard_id)
+            .await;
+        write
+            .expect_compare_and_append(&[(("1".to_owned(), "one".to_owned()), -1, 1)], i64::MIN, 0)
+            .await;
+        let status = client.shard_status(shard_id).await.expect("valid codecs");
+        assert_eq!(status.upper_codec64_bytes, vec![0i64.encode()]);
+        assert_eq!(status.since_codec64_bytes, vec![i64::MIN.encode()]);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn critical_readers() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // A shard that was never used has no critical readers.
+        assert_eq!(
+            client
+                .critical_readers::<u64>(shard_id)
+                .await
+                .expect("valid codecs"),
+            vec![]
+        );
+
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+
+        // Register two critical readers, with distinct opaques and sinces.
+        let controller_id = CriticalReaderId::new();
+        let mut controller = client
+            .open_critical_since::<String, String, u64, i64, u64>(
+                shard_id,
+                controller_id.clone(),
+                Diagnostics::from_purpose("controller"),
+            )
+            .await
+            .expect("codec mismatch");
+        controller
+            .compare_and_downgrade_since(&0, (&0x0102_0304, &Antichain::from_elem(1)))
+            .await
+            .expect("opaque mismatch");
+        let sink_id = CriticalReaderId::new();
+        let mut sink = client
+            .open_critical_since::<String, String, u64, i64, i64>(
+                shard_id,
+                sink_id.clone(),
+                Diagnostics::from_purpose("sink"),
+            )
+            .await
+            .expect("codec mismatch");
+        sink.compare_and_downgrade_since(&i64::MIN, (&-1, &Antichain::from_elem(2)))
+            .await
+            .expect("opaque mismatch");
+
+        let state_before = client
+            .consensus
+            .head(&shard_id.to_string())
+            .await
+            .expect("consensus available");
+        let mut expected = vec![
+            CriticalReaderInfo {
+                id: controller_id,
+                purpose: "controller".to_owned(),
+                hostname: "tests".to_owned(),
+                since: Antichain::from_elem(1),
+                opaque_hex: "0403020100000000".to_owned(),
+                opaque_codec: u64::codec_name(),
+            },
+            CriticalReaderInfo {
+                id: sink_id,
+                purpose: "sink".to_owned(),
+                hostname: "tests".to_owned(),
+                since: Antichain::from_elem(2),
+                opaque_hex: "ffffffffffffffff".to_owned(),
+                opaque_codec: i64::codec_name(),
+            },
+        ];
+        expected.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(
+            client
+                .critical_readers::<u64>(shard_id)
+                .await
+                .expect("valid codecs"),
+            expected
+        );
+
+        // Listing the critical readers doesn't modify the shard's state.
+        assert_eq!(
+            client
+                .consensus
+                .head(&shard_id.to_string())
+                .await
+                .expect("consensus available"),
+            state_before
+        );
+
+        // Asking for the wrong ts type is an error.
+        assert!(client.critical_readers::<i64>(shard_id).await.is_err());
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn shard_frontiers() {
+        let data = test_data(2);
+
+        let mut client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // A shard that was never used reports its initial frontiers, but
+        // asking about it doesn't create it.
+        assert_eq!(
+            client
+                .shard_frontiers::<u64>(shard_id)
+                .await
+                .expect("valid codecs"),
+            (Antichain::from_elem(0), Antichain::from_elem(0))
+        );
+        assert_eq!(
+            client
+                .consensus
+                .head(&shard_id.to_string())
+                .await
+                .expect("consensus available"),
+            None
+        );
+
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+        read.downgrade_since(&Antichain::from_elem(2)).await;
+
+        // Returns the seqno and the number of leased readers, critical readers,
+        // and writers in the latest state of the shard.
+        async fn registrations(
+            client: &PersistClient,
+            shard_id: ShardId,
+        ) -> (u64, usize, usize, usize) {
+            let state = client
+                .inspect_shard::<u64>(&shard_id)
+                .await
+                .expect("shard exists");
+            let state = serde_json::to_value(state).expect("serializable");
+            let len = |field: &str| state[field].as_object().expect("map").len();
+            (
+                state["seqno"].as_u64().expect("seqno"),
+                len("leased_readers"),
+                len("critical_readers"),
+                len("writers"),
+            )
+        }
+        let before = registrations(&client, shard_id).await;
+        let expected = (Antichain::from_elem(2), Antichain::from_elem(3));
+
+        // The state is cached because we hold handles to the shard.
+        assert_eq!(
+            client
+                .shard_frontiers::<u64>(shard_id)
+                .await
+                .expect("valid codecs"),
+            expected
+        );
+        assert!(client.shard_frontiers::<i64>(shard_id).await.is_err());
+
+        // Without the cache, the state is fetched from durable storage.
+        client.shared_states = Arc::new(StateCache::new_no_metrics());
+        assert_eq!(
+            client
+                .shard_frontiers::<u64>(shard_id)
+                .await
+                .expect("valid codecs"),
+            expected
+        );
+
+        // Neither call registered anything or otherwise modified state.
+        assert_eq!(registrations(&client, shard_id).await, before);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn inspect_shard_diffs() {
+        let data = test_data(2);
+
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        assert!(client.inspect_shard_diffs(&shard_id).await.is_err());
+
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data[..1], 0, 2).await;
+        write.expect_compare_and_append(&data[1..], 2, 3).await;
+
+        let diffs = client
+            .inspect_shard_diffs(&shard_id)
+            .await
+            .expect("shard exists");
+        let diffs = serde_json::to_value(diffs).expect("serializable");
+        let min = diffs["min_seqno"].as_u64().expect("min_seqno");
+        let max = diffs["max_seqno"].as_u64().expect("max_seqno");
+        let count = diffs["count"].as_u64().expect("count");
+        // Nothing has been truncated by GC, so the diffs are contiguous from
+        // the first one to the current head.
+        assert_eq!(count, max - min + 1);
+        let head = client
+            .consensus
+            .head(&shard_id.to_string())
+            .await
+            .expect("consensus available")
+            .expect("shard exists");
+        assert_eq!(max, head.seqno.0);
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(4096))]
 
@@ -40,7 +40,8 @@ use timely::PartialOrder;
 use tracing::{debug, trace};
 
 use crate::cfg::RetryParameters;
-use crate::fetch::{FetchedPart, SerdeLeasedBatchPart};
+use crate::fetch::{retry_leased_fetch, FetchedPart, SerdeLeasedBatchPart};
+use crate::internal::metrics::ShardSourceMetrics;
 use crate::read::SubscriptionLeaseReturner;
 use crate::stats::PartStats;
 use crate::{Diagnostics, PersistClient, ShardId};
@@ -199,8 +200,10 @@ where
     let listen_handle = Rc::new(RefCell::new(None));
     let return_listen_handle = Rc::clone(&listen_handle);
 
-    // Create a oneshot channel to give the part returner a SubscriptionLeaseReturner
-    let (tx, rx) = tokio::sync::oneshot::channel::<SubscriptionLeaseReturner>();
+    // Create a oneshot channel to give the part returner a SubscriptionLeaseReturner and the
+    // metrics it reports the read frontier to
+    let (tx, rx) =
+        tokio::sync::oneshot::channel::<(SubscriptionLeaseReturner, Arc<ShardSourceMetrics>)>();
     let mut builder = AsyncOperatorBuilder::new(
         format!("shard_source_descs_return({})", name),
         scope.clone(),
@@ -214,19 +217,23 @@ where
     // This operator doesn't need to use a token because it naturally exits when its input
     // frontier reaches the empty antichain.
     builder.build(move |_caps| async move {
-        let Ok(mut lease_returner) = rx.await else {
+        let Ok((mut lease_returner, source_metrics)) = rx.await else {
             // Either we're not the chosen worker or the dataflow was shutdown before the
             // subscriber was even created.
             return;
         };
         while let Some(event) = completed_fetches.next().await {
-            let Event::Data(_cap, data) = event else {
-                continue;
-            };
-            for part in data {
-                lease_returner.return_leased_part(
-                    lease_returner.leased_part_from_exchangeable::<G::Timestamp>(part),
-                );
+            match event {
+                Event::Data(_cap, data) => {
+                    for part in data {
+                        lease_returner.return_leased_part(
+                            lease_returner.leased_part_from_exchangeable::<G::Timestamp>(part),
+                        );
+                    }
+                }
+                // Parts are only reported as completed once they have been
+                // fetched, so this frontier is how far the source has read.
+                Event::Progress(frontier) => source_metrics.set_read_frontier(&frontier),
             }
         }
         // Make it explicit that the subscriber is kept alive until we have finished returning parts
@@ -282,6 +289,7 @@ where
 
         let cfg = read.cfg.clone();
         let metrics = Arc::clone(&read.metrics);
+        let source_metrics = metrics.source.for_shard(&shard_id, &name_owned);
 
         let as_of = as_of.unwrap_or_else(|| read.since().clone());
 
@@ -315,7 +323,7 @@ where
         // We're about to start producing parts to be fetched whose leases will be returned by the
         // `shard_source_descs_return` operator above. In order for that operator to successfully
         // return the leases we send it the lease returner associated with our shared subscriber.
-        tx.send(read.lease_returner().clone())
+        tx.send((read.lease_returner().clone(), Arc::clone(&source_metrics)))
             .expect("lease returner exited before desc producer");
         let mut lease_returner = read.lease_returner().clone();
 
@@ -359,6 +367,7 @@ where
         // times greater or equal to `until`, which means they can be dropped in their entirety.
         while !PartialOrder::less_equal(&until, &current_frontier) {
             let (parts, progress) = shard_stream.next().await.expect("infinite stream");
+            source_metrics.set_upper(&progress);
 
             // Emit the part at the `(ts, 0)` time. The `granular_backpressure`
             // operator will refine this further, if its enabled.
@@ -458,9 +467,10 @@ where
     let name_owned = name.to_owned();
 
     let shutdown_button = builder.build(move |_capabilities| async move {
+        let client = client.await;
+        let source_metrics = client.metrics.source.for_shard(&shard_id, &name_owned);
         let fetcher = {
             client
-                .await
                 .create_batch_fetcher::<K, V, T, D>(
                     shard_id,
                     key_schema,
@@ -479,10 +489,19 @@ where
                 // panicking, so swap them to an owned version.
                 for (_idx, part) in data {
                     let leased_part = fetcher.leased_part_from_exchangeable(part);
-                    let fetched = fetcher
-                        .fetch_leased_part(&leased_part)
-                        .await
-                        .expect("shard_id should match across all workers");
+                    let reader_name = format!("shard_source_fetch({}) {}", name_owned, shard_id);
+                    let fetched = retry_leased_fetch(&reader_name, || async {
+                        fetcher
+                            .fetch_leased_part(&leased_part)
+                            .await
+                            .expect("shard_id should match across all workers")
+                    })
+                    .await;
+                    let fetched = match fetched {
+                        Ok(fetched) => fetched,
+                        Err(err) => panic!("{} could not fetch batch part: {}", reader_name, err),
+                    };
+                    source_metrics.inc_fetched(leased_part.encoded_size_bytes());
                     {
                         // Do very fine-grained output activation/session
                         // creation to ensure that we don't hold activated
@@ -653,6 +672,82 @@ mod tests {
         assert_eq!(res, Antichain::from_elem(expected_frontier));
     }
 
+    /// Verifies that a `shard_source` reports its progress through the shard
+    /// in the per-shard source metrics.
+    #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn test_shard_source_metrics() {
+        let persist_client = PersistClient::new_for_tests().await;
+
+        let shard_id = ShardId::new();
+
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 0, 1),
+            (("2".to_owned(), "two".to_owned()), 1, 1),
+            (("3".to_owned(), "three".to_owned()), 2, 1),
+        ];
+        let (mut write, _read) = persist_client
+            .expect_open::<String, String, u64, u64>(shard_id)
+            .await;
+        write.expect_compare_and_append(&data, 0, 3).await;
+
+        let client_metrics = Arc::clone(&persist_client.metrics);
+        let metrics = client_metrics.source.for_shard(&shard_id, "test_source");
+        assert_eq!(metrics.parts_fetched.get(), 0);
+
+        let source_metrics = Arc::clone(&metrics);
+        timely::execute::execute_directly(move |worker| {
+            let until = Antichain::new();
+
+            let (_probe, _token) = worker.dataflow::<u64, _, _>(|scope| {
+                let (stream, token) = scope.scoped::<u64, _, _>("hybrid", |scope| {
+                    let transformer = move |_, descs: &Stream<_, _>, _| (descs.clone(), vec![]);
+                    let (stream, tokens) = shard_source::<String, String, u64, u64, _, _, _, _>(
+                        scope,
+                        "test_source",
+                        move || std::future::ready(persist_client.clone()),
+                        shard_id,
+                        None,
+                        SnapshotMode::Include,
+                        until,
+                        Some(transformer),
+                        Arc::new(
+                            <std::string::String as mz_persist_types::Codec>::Schema::default(),
+                        ),
+                        Arc::new(
+                            <std::string::String as mz_persist_types::Codec>::Schema::default(),
+                        ),
+                        |_fetch, _frontier| true,
+                        false.then_some(|| unreachable!()),
+                    );
+                    (stream.leave(), tokens)
+                });
+
+                let probe = stream.probe();
+
+                (probe, token)
+            });
+
+            while source_metrics.read_frontier.get() < 3 || source_metrics.parts_fetched.get() == 0
+            {
+                worker.step();
+            }
+        });
+
+        assert_eq!(metrics.upper.get(), 3);
+        assert_eq!(metrics.read_frontier.get(), 3);
+        assert_eq!(metrics.lag.get(), 0);
+        assert!(metrics.parts_fetched.get() > 0);
+        assert!(metrics.decoded_bytes.get() > 0);
+
+        // Once nothing reads the shard anymore, its metrics are removed, so
+        // the next source to read it starts from scratch.
+        drop(metrics);
+        let metrics = client_metrics.source.for_shard(&shard_id, "test_source");
+        assert_eq!(metrics.parts_fetched.get(), 0);
+        assert_eq!(metrics.decoded_bytes.get(), 0);
+    }
+
     async fn initialize_shard(
         persist_client: &PersistClient,
         shard_id: ShardId,
@@ -21,7 +21,7 @@ use differential_dataflow::lattice::Lattice;
 use differential_dataflow::trace::Description;
 use futures::Stream;
 use mz_ore::now::EpochMillis;
-use mz_ore::task::{AbortOnDropHandle, JoinHandle, RuntimeExt};
+use mz_ore::task::RuntimeExt;
 use mz_persist::location::{Blob, SeqNo};
 use mz_persist_types::{Codec, Codec64};
 use proptest_derive::Arbitrary;
@@ -29,21 +29,25 @@ use serde::{Deserialize, Serialize};
 use timely::progress::{Antichain, Timestamp};
 use timely::PartialOrder;
 use tokio::runtime::Handle;
-use tracing::{debug_span, instrument, warn, Instrument};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::{debug, debug_span, instrument, warn, Instrument};
 use uuid::Uuid;
 
 use crate::cfg::RetryParameters;
 use crate::dyn_cfg::Config;
+use crate::error::{InternalPanic, SnapshotError};
 use crate::fetch::{
-    fetch_leased_part, FetchBatchFilter, FetchedPart, LeasedBatchPart, SerdeLeasedBatchPart,
+    fetch_batch_part, fetch_leased_part, fetch_leased_part_retrying, DecodeError, DecodeResult,
+    FetchBatchError, FetchBatchFilter, FetchedPart, LeasedBatchPart, SerdeLeasedBatchPart,
     SerdeLeasedBatchPartMetadata,
 };
 use crate::internal::encoding::Schemas;
-use crate::internal::machine::Machine;
-use crate::internal::metrics::Metrics;
+use crate::internal::machine::{Machine, ReaderHeartbeatRegistration};
+use crate::internal::metrics::{Metrics, ReadMetrics, ShardMetrics};
 use crate::internal::state::{HollowBatch, HollowBatchPart};
 use crate::internal::watch::StateWatch;
-use crate::iter::Consolidator;
+use crate::iter::{ConsolidationStats, Consolidator};
+use crate::stats::SnapshotStats;
 use crate::{parse_id, GarbageCollector, PersistConfig, ShardId};
 
 pub use crate::internal::encoding::LazyPartStats;
@@ -152,7 +156,7 @@ where
     #[instrument(level = "debug", skip_all, fields(shard = %self.listen.handle.machine.shard_id()))]
     pub async fn fetch_next(
         &mut self,
-    ) -> Vec<ListenEvent<T, ((Result<K, String>, Result<V, String>), T, D)>> {
+    ) -> Vec<ListenEvent<T, ((DecodeResult<K>, DecodeResult<V>), T, D)>> {
         let events = self.next(None).await;
         let new_len = events
             .iter()
@@ -180,6 +184,9 @@ where
     }
 
     /// Fetches the contents of `part` and returns its lease.
+    ///
+    /// Failures to fetch the part from blob storage are retried until they
+    /// succeed, see [Listen::fetch_next].
     pub async fn fetch_batch_part(&mut self, part: LeasedBatchPart<T>) -> FetchedPart<K, V, T, D> {
         self.listen.fetch_batch_part(part).await
     }
@@ -243,6 +250,7 @@ where
     as_of: Antichain<T>,
     since: Antichain<T>,
     frontier: Antichain<T>,
+    consolidate: bool,
 }
 
 impl<K, V, T, D> Listen<K, V, T, D>
@@ -266,13 +274,14 @@ where
             since,
             frontier: as_of.clone(),
             as_of,
+            consolidate: true,
         }
     }
 
     /// Convert listener into futures::Stream
     pub fn into_stream(
         mut self,
-    ) -> impl Stream<Item = ListenEvent<T, ((Result<K, String>, Result<V, String>), T, D)>> {
+    ) -> impl Stream<Item = ListenEvent<T, ((DecodeResult<K>, DecodeResult<V>), T, D)>> {
         async_stream::stream!({
             loop {
                 for msg in self.fetch_next().await {
@@ -287,6 +296,18 @@ where
         &self.frontier
     }
 
+    /// Configures whether the updates in each fetched part are consolidated
+    /// before being returned in [ListenEvent::Updates] (the default).
+    ///
+    /// Consolidation is only a best-effort reduction in the number of updates,
+    /// so consumers that consolidate their input anyway can turn it off to skip
+    /// the work. Consumers that do so must be prepared to receive several
+    /// updates for the same key, value, and time, including ones with diffs
+    /// that cancel each other out. [ListenEvent::Progress] is unaffected.
+    pub fn set_consolidate_updates(&mut self, consolidate: bool) {
+        self.consolidate = consolidate;
+    }
+
     /// Attempt to pull out the next values of this subscription.
     ///
     /// The returned [`LeasedBatchPart`] is appropriate to use with
@@ -390,14 +411,19 @@ where
     /// and not necessarily consolidated. However, the timestamp of each individual update will be
     /// greater than or equal to the last received [ListenEvent::Progress] frontier (or this
     /// [Listen]'s initial `as_of` frontier if no progress event has been emitted yet) and less
-    /// than the next [ListenEvent::Progress] frontier.
+    /// than the next [ListenEvent::Progress] frontier. See
+    /// [Self::set_consolidate_updates] to skip even the best-effort
+    /// consolidation within each part.
     ///
     /// If you have a use for consolidated listen output, given that snapshots can't be
     /// consolidated, come talk to us!
+    ///
+    /// There's no way to return an error from here, so a part that can't be fetched from blob
+    /// storage is retried until it can be: the listen's lease keeps its blob around.
     #[instrument(level = "debug", name = "listen::next", skip_all, fields(shard = %self.handle.machine.shard_id()))]
     pub async fn fetch_next(
         &mut self,
-    ) -> Vec<ListenEvent<T, ((Result<K, String>, Result<V, String>), T, D)>> {
+    ) -> Vec<ListenEvent<T, ((DecodeResult<K>, DecodeResult<V>), T, D)>> {
         let (parts, progress) = self.next(None).await;
         let mut ret = Vec::with_capacity(parts.len() + 1);
         for part in parts {
@@ -416,7 +442,7 @@ where
     /// This is broken out into its own function to provide a trivial means for
     /// [`Subscribe`], which contains a [`Listen`], to fetch batches.
     async fn fetch_batch_part(&mut self, part: LeasedBatchPart<T>) -> FetchedPart<K, V, T, D> {
-        let fetched_part = fetch_leased_part(
+        let mut fetched_part = fetch_leased_part_retrying(
             &part,
             self.handle.blob.as_ref(),
             Arc::clone(&self.handle.metrics),
@@ -426,6 +452,7 @@ where
             self.handle.schemas.clone(),
         )
         .await;
+        fetched_part.set_consolidate(self.consolidate);
         self.handle.process_returned_leased_part(part);
         fetched_part
     }
@@ -451,7 +478,7 @@ where
         &mut self,
         ts: &T,
     ) -> (
-        Vec<((Result<K, String>, Result<V, String>), T, D)>,
+        Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>,
         Antichain<T>,
     ) {
         let mut updates = Vec::new();
@@ -473,6 +500,9 @@ where
 #[derive(Clone, Debug)]
 pub(crate) struct SubscriptionLeaseReturner {
     leased_seqnos: Arc<Mutex<BTreeMap<SeqNo, usize>>>,
+    /// Handed to every [`LeasedBatchPart`] issued from this returner, so that
+    /// dropping the part returns its lease to the issuing [`ReadHandle`].
+    dropped_lease_tx: UnboundedSender<SeqNo>,
     reader_id: LeasedReaderId,
     metrics: Arc<Metrics>,
 }
@@ -483,7 +513,11 @@ impl SubscriptionLeaseReturner {
         &self,
         x: SerdeLeasedBatchPart,
     ) -> LeasedBatchPart<T> {
-        LeasedBatchPart::from(x, Arc::clone(&self.metrics))
+        LeasedBatchPart::from(
+            x,
+            Arc::clone(&self.metrics),
+            Some(self.dropped_lease_tx.clone()),
+        )
     }
 
     pub(crate) fn return_leased_part<T: Timestamp + Codec64>(
@@ -491,20 +525,24 @@ impl SubscriptionLeaseReturner {
         mut leased_part: LeasedBatchPart<T>,
     ) {
         if let Some(lease) = leased_part.return_lease(&self.reader_id) {
-            // Tracks that a `SeqNo` lease has been returned and can be dropped. Once
-            // a `SeqNo` has no more outstanding leases, it can be removed, and
-            // `Self::downgrade_since` no longer needs to prevent it from being
-            // garbage collected.
-            let mut leased_seqnos = self.leased_seqnos.lock().expect("lock poisoned");
-            let remaining_leases = leased_seqnos
-                .get_mut(&lease)
-                .expect("leased SeqNo returned, but lease not issued from this ReadHandle");
-
-            *remaining_leases -= 1;
-
-            if remaining_leases == &0 {
-                leased_seqnos.remove(&lease);
-            }
+            self.release(lease);
+        }
+    }
+
+    /// Tracks that a `SeqNo` lease has been returned and can be dropped. Once
+    /// a `SeqNo` has no more outstanding leases, it can be removed, and
+    /// `ReadHandle::downgrade_since` no longer needs to prevent it from being
+    /// garbage collected.
+    fn release(&self, lease: SeqNo) {
+        let mut leased_seqnos = self.leased_seqnos.lock().expect("lock poisoned");
+        let remaining_leases = leased_seqnos
+            .get_mut(&lease)
+            .expect("leased SeqNo returned, but lease not issued from this ReadHandle");
+
+        *remaining_leases -= 1;
+
+        if remaining_leases == &0 {
+            leased_seqnos.remove(&lease);
         }
     }
 }
@@ -545,10 +583,14 @@ where
     pub(crate) blob: Arc<dyn Blob + Send + Sync>,
     pub(crate) reader_id: LeasedReaderId,
     pub(crate) schemas: Schemas<K, V>,
+    lease_duration: Duration,
 
     since: Antichain<T>,
     pub(crate) last_heartbeat: EpochMillis,
     lease_returner: SubscriptionLeaseReturner,
+    /// Leases returned by [`LeasedBatchPart`]s that were dropped without being
+    /// explicitly returned, released on the next `downgrade_since`.
+    dropped_lease_rx: UnboundedReceiver<SeqNo>,
     pub(crate) unexpired_state: Option<UnexpiredReadHandleState>,
 }
 
@@ -559,7 +601,7 @@ where
     T: Timestamp + Lattice + Codec64,
     D: Semigroup + Codec64 + Send + Sync,
 {
-    pub(crate) async fn new(
+    pub(crate) fn new(
         cfg: PersistConfig,
         metrics: Arc<Metrics>,
         machine: Machine<K, V, T, D>,
@@ -567,9 +609,11 @@ where
         blob: Arc<dyn Blob + Send + Sync>,
         reader_id: LeasedReaderId,
         schemas: Schemas<K, V>,
+        lease_duration: Duration,
         since: Antichain<T>,
         last_heartbeat: EpochMillis,
     ) -> Self {
+        let (dropped_lease_tx, dropped_lease_rx) = mpsc::unbounded_channel();
         ReadHandle {
             cfg,
             metrics: Arc::clone(&metrics),
@@ -578,20 +622,18 @@ where
             blob,
             reader_id: reader_id.clone(),
             schemas,
+            lease_duration,
             since,
             last_heartbeat,
             lease_returner: SubscriptionLeaseReturner {
                 leased_seqnos: Arc::new(Mutex::new(BTreeMap::new())),
+                dropped_lease_tx,
                 reader_id: reader_id.clone(),
                 metrics,
             },
+            dropped_lease_rx,
             unexpired_state: Some(UnexpiredReadHandleState {
-                _heartbeat_tasks: machine
-                    .start_reader_heartbeat_tasks(reader_id, gc)
-                    .await
-                    .into_iter()
-                    .map(JoinHandle::abort_on_drop)
-                    .collect(),
+                _heartbeat: machine.register_reader_heartbeat(reader_id, lease_duration, &gc),
             }),
         }
     }
@@ -620,6 +662,12 @@ where
     /// timestamp, making the call a no-op).
     #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
     pub async fn downgrade_since(&mut self, new_since: &Antichain<T>) {
+        // Release the leases of any parts that were dropped since the last
+        // call, so that they don't hold back the seqno since.
+        while let Ok(lease) = self.dropped_lease_rx.try_recv() {
+            self.lease_returner.release(lease);
+        }
+
         // Guaranteed to be the smallest/oldest outstanding lease on a `SeqNo`.
         let outstanding_seqno = self
             .lease_returner
@@ -726,6 +774,30 @@ where
         Ok(leased_parts)
     }
 
+    /// Returns aggregate statistics about the contents of the shard TVC at
+    /// `as_of`, computed from the metadata in state without fetching any
+    /// parts.
+    ///
+    /// This command returns the stats once the contents of the shard as of
+    /// `as_of` are known. This may "block" (in an async-friendly way) if
+    /// `as_of` is greater or equal to the current `upper` of the shard.
+    ///
+    /// The `Since` error indicates that the requested `as_of` cannot be served
+    /// (the caller has out of date information) and includes the smallest
+    /// `as_of` that would have been accepted.
+    #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
+    pub async fn snapshot_stats(
+        &mut self,
+        as_of: Antichain<T>,
+    ) -> Result<SnapshotStats<T>, Since<T>> {
+        let batches = self.machine.snapshot(&as_of).await?;
+        Ok(SnapshotStats::from_batches(
+            self.machine.shard_id(),
+            as_of,
+            &batches,
+        ))
+    }
+
     /// Returns a snapshot of all of a shard's data using `as_of`, followed by
     /// listening to any future updates.
     ///
@@ -756,7 +828,9 @@ where
             key: part.key,
             stats: part.stats,
             encoded_size_bytes: part.encoded_size_bytes,
+            checksum: part.checksum,
             leased_seqno: Some(self.lease_seqno()),
+            lease_return_tx: Some(self.lease_returner.dropped_lease_tx.clone()),
             filter_pushdown_audit: false,
             key_lower: part.key_lower,
         }
@@ -811,17 +885,24 @@ where
     /// same `since`.
     #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
     pub async fn clone(&self, purpose: &str) -> Self {
+        self.clone_with_lease(purpose, self.cfg.dynamic.reader_lease_duration())
+            .await
+    }
+
+    /// Returns an independent [ReadHandle] with a new [LeasedReaderId] but the
+    /// same `since`, whose lease has the given duration instead of the
+    /// configured default.
+    ///
+    /// The lease is independent of that of `self`: expiring or dropping the
+    /// returned handle releases only its own lease.
+    #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
+    pub async fn clone_with_lease(&self, purpose: &str, lease_duration: Duration) -> Self {
         let new_reader_id = LeasedReaderId::new();
         let mut machine = self.machine.clone();
         let gc = self.gc.clone();
         let heartbeat_ts = (self.cfg.now)();
         let (reader_state, maintenance) = machine
-            .register_leased_reader(
-                &new_reader_id,
-                purpose,
-                self.cfg.dynamic.reader_lease_duration(),
-                heartbeat_ts,
-            )
+            .register_leased_reader(&new_reader_id, purpose, lease_duration, heartbeat_ts)
             .await;
         maintenance.start_performing(&machine, &gc);
         // The point of clone is that you're guaranteed to have the same (or
@@ -835,10 +916,10 @@ where
             Arc::clone(&self.blob),
             new_reader_id,
             self.schemas.clone(),
+            lease_duration,
             reader_state.since,
             heartbeat_ts,
-        )
-        .await;
+        );
         new_reader
     }
 
@@ -855,7 +936,7 @@ where
         // NB: min_elapsed is intentionally smaller than the one in
         // maybe_heartbeat_reader (this is the preferential treatment mentioned
         // above).
-        let min_elapsed = self.cfg.dynamic.reader_lease_duration() / 4;
+        let min_elapsed = self.lease_duration / 4;
         let elapsed_since_last_heartbeat =
             Duration::from_millis((self.cfg.now)().saturating_sub(self.last_heartbeat));
         if elapsed_since_last_heartbeat >= min_elapsed {
@@ -871,14 +952,12 @@ where
     /// compared to PersistConfig::FAKE_READ_LEASE_DURATION.
     #[allow(dead_code)]
     pub(crate) async fn maybe_heartbeat_reader(&mut self) {
-        let min_elapsed = self.cfg.dynamic.reader_lease_duration() / 2;
+        let min_elapsed = self.lease_duration / 2;
         let heartbeat_ts = (self.cfg.now)();
         let elapsed_since_last_heartbeat =
             Duration::from_millis(heartbeat_ts.saturating_sub(self.last_heartbeat));
         if elapsed_since_last_heartbeat >= min_elapsed {
-            if elapsed_since_last_heartbeat
-                > self.machine.applier.cfg.dynamic.reader_lease_duration()
-            {
+            if elapsed_since_last_heartbeat > self.lease_duration {
                 warn!(
                     "reader ({}) of shard ({}) went {}s between heartbeats",
                     self.reader_id,
@@ -942,7 +1021,7 @@ where
 /// State for a read handle that has not been explicitly expired.
 #[derive(Debug)]
 pub(crate) struct UnexpiredReadHandleState {
-    pub(crate) _heartbeat_tasks: Vec<AbortOnDropHandle<()>>,
+    pub(crate) _heartbeat: ReaderHeartbeatRegistration,
 }
 
 /// An incremental cursor through a particular shard, returned from [ReadHandle::snapshot_cursor].
@@ -953,6 +1032,7 @@ pub(crate) struct UnexpiredReadHandleState {
 #[derive(Debug)]
 pub struct Cursor<K: Codec, V: Codec, T: Timestamp + Codec64, D> {
     consolidator: Consolidator<T, D>,
+    shard_metrics: Arc<ShardMetrics>,
     _schemas: Schemas<K, V>,
 }
 
@@ -966,15 +1046,53 @@ where
     /// Grab the next batch of consolidated data.
     pub async fn next(
         &mut self,
-    ) -> Option<impl Iterator<Item = ((Result<K, String>, Result<V, String>), T, D)> + '_> {
+    ) -> Option<impl Iterator<Item = ((DecodeResult<K>, DecodeResult<V>), T, D)> + '_> {
         let iter = self
             .consolidator
             .next()
             .await
             .expect("fetching a leased part")?;
-        let iter = iter.map(|(k, v, t, d)| ((K::decode(k), V::decode(v)), t, d));
+        let shard_metrics = &self.shard_metrics;
+        let iter = iter.map(move |(k, v, t, d)| {
+            // Consolidation merges updates from many parts, so we don't know
+            // which one an update that fails to decode came from.
+            let k = K::decode(k).map_err(|reason| {
+                shard_metrics.decode_failures.inc();
+                DecodeError::new::<K>(None, None, reason)
+            });
+            let v = V::decode(v).map_err(|reason| {
+                shard_metrics.decode_failures.inc();
+                DecodeError::new::<V>(None, None, reason)
+            });
+            ((k, v), t, d)
+        });
         Some(iter)
     }
+
+    /// Grab the next batch of consolidated data, without decoding the keys
+    /// and values.
+    pub(crate) async fn next_encoded(
+        &mut self,
+    ) -> Option<impl Iterator<Item = (&[u8], &[u8], T, D)> + '_> {
+        self.consolidator
+            .next()
+            .await
+            .expect("fetching a leased part")
+    }
+}
+
+impl<K: Codec, V: Codec, T: Timestamp + Codec64, D> Cursor<K, V, T, D> {
+    /// Statistics about the consolidation work done by this cursor so far.
+    pub fn consolidation_stats(&self) -> ConsolidationStats {
+        self.consolidator.stats()
+    }
+}
+
+impl<K: Codec, V: Codec, T: Timestamp + Codec64, D> Drop for Cursor<K, V, T, D> {
+    fn drop(&mut self) {
+        self.shard_metrics
+            .record_read_consolidation(&self.consolidator.stats());
+    }
 }
 
 pub(crate) const STREAMING_SNAPSHOT_AND_FETCH_ENABLED: Config<bool> = Config::new(
@@ -1006,7 +1124,26 @@ where
     pub async fn snapshot_and_fetch(
         &mut self,
         as_of: Antichain<T>,
-    ) -> Result<Vec<((Result<K, String>, Result<V, String>), T, D)>, Since<T>> {
+    ) -> Result<Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>, SnapshotError<T>> {
+        let (contents, _stats) = self.snapshot_and_fetch_with_stats(as_of).await?;
+        Ok(contents)
+    }
+
+    /// Like [Self::snapshot_and_fetch], but also returns statistics about the
+    /// consolidation done while reading the snapshot.
+    ///
+    /// Unless streaming consolidation is enabled, each fetched part counts as
+    /// one merged run.
+    pub async fn snapshot_and_fetch_with_stats(
+        &mut self,
+        as_of: Antichain<T>,
+    ) -> Result<
+        (
+            Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>,
+            ConsolidationStats,
+        ),
+        SnapshotError<T>,
+    > {
         if STREAMING_SNAPSHOT_AND_FETCH_ENABLED.get(&self.machine.applier.cfg.configs) {
             return self.snapshot_and_fetch_streaming(as_of).await;
         }
@@ -1014,9 +1151,13 @@ where
         let snap = self.snapshot(as_of).await?;
 
         let mut contents = Vec::new();
+        let mut stats = ConsolidationStats::default();
         let mut last_consolidate_len = 0;
         let mut is_consolidated = true;
-        for part in snap {
+        let mut parts = snap.into_iter();
+        while let Some(part) = parts.next() {
+            stats.runs_merged += 1;
+            stats.bytes_processed += part.encoded_size_bytes();
             let fetched_part = fetch_leased_part(
                 &part,
                 self.blob.as_ref(),
@@ -1028,7 +1169,31 @@ where
             )
             .await;
             self.process_returned_leased_part(part);
-            contents.extend(fetched_part);
+            let fetched_part = match fetched_part {
+                Ok(fetched_part) => fetched_part,
+                Err(err) => {
+                    for part in parts {
+                        self.process_returned_leased_part(part);
+                    }
+                    return Err(SnapshotError::Fetch(err));
+                }
+            };
+            // Decoding runs user codecs, so a panic here is most likely a bad
+            // row and not corrupted persist state: surface it as an error
+            // instead of taking down the process.
+            let len_before = contents.len();
+            let decoded = InternalPanic::catch(
+                "snapshot_and_fetch::decode",
+                &self.metrics.isolated_runtime.panics,
+                || contents.extend(fetched_part),
+            );
+            if let Err(err) = decoded {
+                for part in parts {
+                    self.process_returned_leased_part(part);
+                }
+                return Err(SnapshotError::InternalPanic(err));
+            }
+            stats.updates_in += contents.len() - len_before;
             // NB: FetchedPart streaming consolidates its output, but it's possible
             // that decoding introduces duplicates again.
             is_consolidated = false;
@@ -1047,7 +1212,12 @@ where
         if !is_consolidated {
             consolidate_updates(&mut contents);
         }
-        Ok(contents)
+        stats.updates_out = contents.len();
+        self.machine
+            .applier
+            .shard_metrics
+            .record_read_consolidation(&stats);
+        Ok((contents, stats))
     }
 
     /// Generates a [Self::snapshot], and fetches all of the batches it
@@ -1058,11 +1228,21 @@ where
     async fn snapshot_and_fetch_streaming(
         &mut self,
         as_of: Antichain<T>,
-    ) -> Result<Vec<((Result<K, String>, Result<V, String>), T, D)>, Since<T>> {
+    ) -> Result<
+        (
+            Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>,
+            ConsolidationStats,
+        ),
+        SnapshotError<T>,
+    > {
         let mut cursor = self.snapshot_cursor(as_of, |_| true).await?;
         let mut contents = Vec::new();
         while let Some(iter) = cursor.next().await {
-            contents.extend(iter);
+            InternalPanic::catch(
+                "snapshot_and_fetch::decode",
+                &self.metrics.isolated_runtime.panics,
+                || contents.extend(iter),
+            )?;
         }
 
         // We don't currently guarantee that encoding is one-to-one, so we still need to
@@ -1078,7 +1258,9 @@ where
                 .inc();
         }
 
-        Ok(contents)
+        // The cursor records the stats in the shard metrics when it's dropped.
+        let stats = cursor.consolidation_stats();
+        Ok((contents, stats))
     }
 
     /// Generates a [Self::snapshot], and fetches all of the batches it
@@ -1127,6 +1309,7 @@ where
 
         Ok(Cursor {
             consolidator,
+            shard_metrics: Arc::clone(&self.machine.applier.shard_metrics),
             _schemas: self.schemas.clone(),
         })
     }
@@ -1138,7 +1321,7 @@ where
     pub async fn snapshot_and_stream(
         &mut self,
         as_of: Antichain<T>,
-    ) -> Result<impl Stream<Item = ((Result<K, String>, Result<V, String>), T, D)>, Since<T>> {
+    ) -> Result<impl Stream<Item = ((DecodeResult<K>, DecodeResult<V>), T, D)>, Since<T>> {
         let snap = self.snapshot(as_of).await?;
 
         let blob = Arc::clone(&self.blob);
@@ -1150,7 +1333,13 @@ where
         let mut lease_returner = self.lease_returner.clone();
         let stream = async_stream::stream! {
             for part in snap {
-                let mut fetched_part = fetch_leased_part(
+                let mut stats = ConsolidationStats {
+                    runs_merged: 1,
+                    bytes_processed: part.encoded_size_bytes(),
+                    ..Default::default()
+                };
+                // The stream has no way to return an error.
+                let mut fetched_part = fetch_leased_part_retrying(
                     &part,
                     blob.as_ref(),
                     Arc::clone(&metrics),
@@ -1163,8 +1352,13 @@ where
                 lease_returner.return_leased_part(part);
 
                 while let Some(next) = fetched_part.next() {
+                    stats.updates_in += 1;
                     yield next;
                 }
+                // Nothing is consolidated across parts here, so every update
+                // that goes in also comes out.
+                stats.updates_out = stats.updates_in;
+                shard_metrics.record_read_consolidation(&stats);
             }
         };
 
@@ -1186,7 +1380,7 @@ where
     pub async fn expect_snapshot_and_fetch(
         &mut self,
         as_of: T,
-    ) -> Vec<((Result<K, String>, Result<V, String>), T, D)> {
+    ) -> Vec<((DecodeResult<K>, DecodeResult<V>), T, D)> {
         let mut ret = self
             .snapshot_and_fetch(Antichain::from_elem(as_of))
             .await
@@ -1243,26 +1437,290 @@ where
     }
 }
 
+/// A handle for reading a shard without registering a reader.
+///
+/// Unlike a [ReadHandle], this holds no lease and no capability on the shard,
+/// so opening, using, and dropping one never writes to consensus and never
+/// spawns heartbeat tasks: snapshots and listens are served purely from
+/// fetched state and blob reads. This makes it cheap for tools that only want
+/// to take a quick look at a shard.
+///
+/// The flip side is that nothing prevents the since of the shard from
+/// advancing underneath the handle, or the data it's reading from being
+/// compacted and garbage collected. Reads racing with either fail with a
+/// retryable error ([SnapshotError::Compacted] or
+/// [SnapshotError::SinceAdvanced]) instead of returning incorrect data.
+#[derive(Debug)]
+pub struct ReadOnlyHandle<K: Codec, V: Codec, T, D> {
+    pub(crate) metrics: Arc<Metrics>,
+    pub(crate) machine: Machine<K, V, T, D>,
+    pub(crate) blob: Arc<dyn Blob + Send + Sync>,
+    pub(crate) schemas: Schemas<K, V>,
+}
+
+impl<K, V, T, D> ReadOnlyHandle<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    /// This handle's shard id.
+    pub fn shard_id(&self) -> ShardId {
+        self.machine.shard_id()
+    }
+
+    /// The shard-global `since` frontier, as of the most recently fetched
+    /// state.
+    ///
+    /// This handle doesn't hold back the since, so it may have advanced
+    /// further by the time this returns.
+    pub fn since(&self) -> Antichain<T> {
+        self.machine.applier.since()
+    }
+
+    /// Returns an ongoing subscription of updates to a shard.
+    ///
+    /// Like [ReadHandle::listen], but without a capability: if the since of
+    /// the shard advances past the frontier of the returned listen before it
+    /// catches up, [ReadOnlyListen::fetch_next] returns an error.
+    #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
+    pub async fn listen(self, as_of: Antichain<T>) -> Result<ReadOnlyListen<K, V, T, D>, Since<T>> {
+        let () = self.machine.verify_listen(&as_of)?;
+        let watch = self.machine.applier.watch();
+        Ok(ReadOnlyListen {
+            handle: self,
+            watch,
+            frontier: as_of.clone(),
+            as_of,
+        })
+    }
+
+    /// Fetches the given part of a batch with the given description.
+    ///
+    /// Without a seqno lease, the blob of the part may have been garbage
+    /// collected after some compaction replaced it. If so, returns
+    /// [SnapshotError::SinceAdvanced] with the since of the latest state if it
+    /// is no longer less than or equal to `frontier`, the frontier the read
+    /// is at, and [SnapshotError::Compacted] otherwise.
+    async fn fetch_part(
+        &self,
+        desc: &Description<T>,
+        part: &HollowBatchPart,
+        frontier: &Antichain<T>,
+        ts_filter: FetchBatchFilter<T>,
+        read_metrics: &ReadMetrics,
+    ) -> Result<FetchedPart<K, V, T, D>, SnapshotError<T>> {
+        let shard_metrics = &self.machine.applier.shard_metrics;
+        let encoded_part = fetch_batch_part(
+            &self.machine.shard_id(),
+            self.blob.as_ref(),
+            &self.metrics,
+            shard_metrics,
+            read_metrics,
+            &part.key,
+            desc,
+            part.checksum,
+        )
+        .await;
+        match encoded_part {
+            Ok(encoded_part) => Ok(FetchedPart::new(
+                Arc::clone(&self.metrics),
+                Arc::clone(shard_metrics),
+                ts_filter,
+                encoded_part,
+                self.schemas.clone(),
+                None,
+            )),
+            Err(FetchBatchError::Missing(blob_key)) => {
+                self.machine.applier.fetch_and_update_state(None).await;
+                let since = self.machine.applier.since();
+                debug!(
+                    "{} read-only reader found blob {} missing, since is now {:?}",
+                    self.machine.shard_id(),
+                    blob_key,
+                    since.elements()
+                );
+                if PartialOrder::less_equal(&since, frontier) {
+                    Err(SnapshotError::Compacted)
+                } else {
+                    Err(SnapshotError::SinceAdvanced(Since(since)))
+                }
+            }
+            Err(err) => Err(SnapshotError::Fetch(err)),
+        }
+    }
+}
+
+impl<K, V, T, D> ReadOnlyHandle<K, V, T, D>
+where
+    K: Debug + Codec + Ord,
+    V: Debug + Codec + Ord,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    /// Returns all of the contents of the shard TVC at `as_of`, consolidated.
+    ///
+    /// Like [ReadHandle::snapshot_and_fetch], but without a capability: if the
+    /// data of the snapshot is garbage collected while it's being fetched,
+    /// returns [SnapshotError::Compacted] if the read may be retried at the
+    /// same `as_of`, and [SnapshotError::SinceAdvanced] if it may only be
+    /// retried at a later one.
+    #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
+    pub async fn snapshot_and_fetch(
+        &mut self,
+        as_of: Antichain<T>,
+    ) -> Result<Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>, SnapshotError<T>> {
+        let batches = self.machine.snapshot(&as_of).await?;
+        self.fetch_snapshot(&as_of, batches).await
+    }
+
+    /// Fetches and consolidates the contents of the given snapshot batches.
+    pub(crate) async fn fetch_snapshot(
+        &self,
+        as_of: &Antichain<T>,
+        batches: Vec<HollowBatch<T>>,
+    ) -> Result<Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>, SnapshotError<T>> {
+        let mut contents = Vec::new();
+        for batch in batches {
+            for part in batch.parts.iter() {
+                let ts_filter = FetchBatchFilter::Snapshot {
+                    as_of: as_of.clone(),
+                };
+                let fetched_part = self
+                    .fetch_part(
+                        &batch.desc,
+                        part,
+                        as_of,
+                        ts_filter,
+                        &self.metrics.read.snapshot,
+                    )
+                    .await?;
+                // Decoding runs user codecs, see ReadHandle::snapshot_and_fetch.
+                InternalPanic::catch(
+                    "snapshot_and_fetch::decode",
+                    &self.metrics.isolated_runtime.panics,
+                    || contents.extend(fetched_part),
+                )?;
+            }
+        }
+        consolidate_updates(&mut contents);
+        Ok(contents)
+    }
+}
+
+/// An ongoing subscription of updates to a shard, returned from
+/// [ReadOnlyHandle::listen].
+#[derive(Debug)]
+pub struct ReadOnlyListen<K: Codec, V: Codec, T, D> {
+    handle: ReadOnlyHandle<K, V, T, D>,
+    watch: StateWatch<K, V, T, D>,
+
+    as_of: Antichain<T>,
+    frontier: Antichain<T>,
+}
+
+impl<K, V, T, D> ReadOnlyListen<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    /// An exclusive upper bound on the progress of this listen.
+    pub fn frontier(&self) -> &Antichain<T> {
+        &self.frontier
+    }
+
+    /// Attempt to pull out the next values of this subscription.
+    ///
+    /// See [Listen::fetch_next] for the semantics of the returned events.
+    ///
+    /// A [SnapshotError::SinceAdvanced] error indicates that the since of the
+    /// shard advanced past the frontier of this listen, so it can no longer
+    /// make progress. A new listen may be started at an `as_of` beyond the
+    /// included since. A [SnapshotError::Compacted] error indicates that the
+    /// data it was about to read was compacted and garbage collected, but the
+    /// since hasn't passed its frontier: calling this again retries the read.
+    /// A [SnapshotError::Fetch] error indicates that a part could not be
+    /// fetched from blob storage.
+    #[instrument(level = "debug", name = "read_only_listen::next", skip_all, fields(shard = %self.handle.machine.shard_id()))]
+    pub async fn fetch_next(
+        &mut self,
+    ) -> Result<Vec<ListenEvent<T, ((DecodeResult<K>, DecodeResult<V>), T, D)>>, SnapshotError<T>>
+    {
+        let batch = self
+            .handle
+            .machine
+            .next_listen_batch(&self.frontier, &mut self.watch, None, None)
+            .await;
+
+        // A [Listen] holds back the since so that this always holds. We don't,
+        // so check it instead: otherwise we couldn't tell the updates at the
+        // frontier apart from earlier ones. See [Listen::next] for details.
+        let distinguishable = PartialOrder::less_than(batch.desc.since(), &self.frontier)
+            || (self.frontier == self.as_of
+                && PartialOrder::less_equal(batch.desc.since(), &self.frontier));
+        if !distinguishable {
+            return Err(SnapshotError::SinceAdvanced(Since(
+                batch.desc.since().clone(),
+            )));
+        }
+
+        let mut ret = Vec::with_capacity(batch.parts.len() + 1);
+        for part in batch.parts.iter() {
+            let ts_filter = FetchBatchFilter::Listen {
+                as_of: self.as_of.clone(),
+                lower: self.frontier.clone(),
+            };
+            let fetched_part = self
+                .handle
+                .fetch_part(
+                    &batch.desc,
+                    part,
+                    &self.frontier,
+                    ts_filter,
+                    &self.handle.metrics.read.listen,
+                )
+                .await?;
+            let updates = fetched_part.collect::<Vec<_>>();
+            if !updates.is_empty() {
+                ret.push(ListenEvent::Updates(updates));
+            }
+        }
+
+        self.frontier = batch.desc.upper().clone();
+        ret.push(ListenEvent::Progress(self.frontier.clone()));
+        Ok(ret)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::pin;
     use std::str::FromStr;
 
+    use bytes::{BufMut, Bytes};
     use mz_build_info::DUMMY_BUILD_INFO;
     use mz_ore::cast::CastFrom;
     use mz_ore::metrics::MetricsRegistry;
     use mz_ore::now::SYSTEM_TIME;
+    use mz_persist::location::Atomicity;
     use mz_persist::mem::{MemBlob, MemBlobConfig, MemConsensus};
     use mz_persist::unreliable::{UnreliableConsensus, UnreliableHandle};
+    use mz_persist_types::codec_impls::{SimpleDecoder, SimpleEncoder, SimpleSchema};
+    use mz_persist_types::columnar::{ColumnPush, Schema};
+    use mz_persist_types::dyn_struct::{ColumnsMut, ColumnsRef, DynStructCfg};
     use serde::{Deserialize, Serialize};
     use serde_json::json;
     use tokio_stream::StreamExt;
 
     use crate::async_runtime::IsolatedRuntime;
     use crate::cache::StateCache;
+    use crate::dyn_cfg::ConfigGuard;
     use crate::internal::metrics::Metrics;
     use crate::rpc::NoopPubSubSender;
-    use crate::tests::{all_ok, new_test_client};
+    use crate::tests::{all_ok, new_test_client, new_test_shard, test_data};
     use crate::{Diagnostics, PersistClient, PersistConfig, ShardId};
 
     use super::*;
@@ -1347,6 +1805,57 @@ mod tests {
         )
     }
 
+    // Verifies that a listen with consolidation turned off returns the raw
+    // updates, which consolidate to the same thing as the default output.
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn listen_unconsolidated() {
+        let data = &[
+            (("k".to_owned(), "v".to_owned()), 1, 1),
+            (("k".to_owned(), "v".to_owned()), 1, 1),
+            (("k2".to_owned(), "v".to_owned()), 1, 1),
+            (("k2".to_owned(), "v".to_owned()), 1, -1),
+            (("k3".to_owned(), "v".to_owned()), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+        client.cfg.dynamic.set_blob_target_size(1000); // So our batch stays together!
+        let shard_id = crate::ShardId::new();
+        let (mut write, read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let (_, raw_read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+
+        write.expect_compare_and_append(data, 0, 3).await;
+
+        let mut listen = read.expect_listen(0).await;
+        let (mut consolidated, consolidated_progress) = listen.read_until(&3).await;
+
+        let mut raw_listen = raw_read.expect_listen(0).await;
+        raw_listen.set_consolidate_updates(false);
+        let (mut raw, raw_progress) = raw_listen.read_until(&3).await;
+
+        assert_eq!(consolidated_progress, raw_progress);
+        assert_eq!(consolidated.len(), 2);
+        assert_eq!(raw.len(), 5);
+
+        consolidate_updates(&mut consolidated);
+        consolidate_updates(&mut raw);
+        assert_eq!(consolidated, raw);
+        assert_eq!(
+            raw,
+            all_ok(
+                &[
+                    (("k".to_owned(), "v".to_owned()), 1, 2),
+                    (("k3".to_owned(), "v".to_owned()), 2, 1),
+                ],
+                0
+            )
+        );
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn snapshot_and_stream() {
@@ -1385,6 +1894,42 @@ mod tests {
         assert_eq!(data.as_slice(), snapshot_rows.as_slice());
     }
 
+    // Verifies that a stream over a part corrupted at rest fails, instead of
+    // refetching the part forever.
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    #[should_panic(expected = "checksum mismatch")]
+    async fn snapshot_and_stream_corrupted_part() {
+        let data = test_data(3);
+        let (_client, mut write, mut read) = new_test_shard().await;
+
+        let mut batch = write.expect_batch(&data, 0, 4).await;
+        let key = batch.batch.parts[0].key.complete(&write.shard_id());
+        let mut value = write
+            .blob
+            .get(&key)
+            .await
+            .expect("blob available")
+            .expect("part exists")
+            .into_contiguous();
+        let idx = value.len() / 2;
+        value[idx] ^= 0xff;
+        write
+            .blob
+            .set(&key, Bytes::from(value), Atomicity::RequireAtomic)
+            .await
+            .expect("blob available");
+        write
+            .expect_compare_and_append_batch(&mut [&mut batch], 0, 4)
+            .await;
+
+        let mut snapshot = pin::pin!(read
+            .snapshot_and_stream(Antichain::from_elem(3))
+            .await
+            .expect("cannot serve requested as_of"));
+        while snapshot.next().await.is_some() {}
+    }
+
     // Verifies the semantics of `SeqNo` leases + checks dropping `LeasedBatchPart` semantics.
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // https://github.com/MaterializeInc/materialize/issues/19983
@@ -1551,6 +2096,60 @@ mod tests {
         drop(subscribe);
     }
 
+    // Verifies that dropping `LeasedBatchPart`s without returning them doesn't
+    // hold back the seqno since, including when they outlive their handle.
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn drop_leased_parts() {
+        let data = vec![
+            (("0".to_owned(), "zero".to_owned()), 0, 1),
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let (mut write, mut read) = new_test_client()
+            .await
+            .expect_open::<String, String, u64, i64>(crate::ShardId::new())
+            .await;
+
+        write.expect_compare_and_append(&data[0..1], 0, 1).await;
+        write.expect_compare_and_append(&data[1..2], 1, 2).await;
+
+        let as_of = Antichain::from_elem(1);
+        let parts = read.snapshot(as_of.clone()).await.expect("as_of is valid");
+        assert!(
+            !parts.is_empty(),
+            "snapshot must have parts for test to be meaningful"
+        );
+        let leased_seqno = parts[0].leased_seqno.expect("part is leased");
+
+        // While the parts are held, their lease holds back the seqno since.
+        write.expect_compare_and_append(&data[2..3], 2, 3).await;
+        read.downgrade_since(&as_of).await;
+        assert!(read.machine.applier.seqno_since() <= leased_seqno);
+
+        // Dropping them releases the lease on the next downgrade.
+        drop(parts);
+        read.downgrade_since(&as_of).await;
+        assert!(read
+            .lease_returner
+            .leased_seqnos
+            .lock()
+            .expect("lock poisoned")
+            .is_empty());
+        assert!(read.machine.applier.seqno_since() > leased_seqno);
+
+        // Parts that outlive their handle can't return their lease, but
+        // dropping them is still safe.
+        let parts = read.snapshot(as_of).await.expect("as_of is valid");
+        let metrics = Arc::clone(&read.metrics);
+        let leaked_before = metrics.lease.leaked_part.get();
+        read.expire().await;
+        let leaked = u64::cast_from(parts.len());
+        drop(parts);
+        assert_eq!(metrics.lease.leaked_part.get(), leaked_before + leaked);
+    }
+
     #[mz_ore::test]
     fn reader_id_human_readable_serde() {
         #[derive(Debug, Serialize, Deserialize)]
@@ -1641,4 +2240,221 @@ mod tests {
             (all_ok(&data[1..], 1), Antichain::from_elem(3))
         );
     }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn snapshot_stats() {
+        let data = vec![
+            (("0".to_owned(), "zero".to_owned()), 0, 1),
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("0".to_owned(), "zero".to_owned()), 2, -1),
+        ];
+
+        let (mut write, mut read) = new_test_client()
+            .await
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        write.expect_compare_and_append(&data[0..1], 0, 1).await;
+        write.expect_compare_and_append(&data[1..2], 1, 2).await;
+        write.expect_compare_and_append(&data[2..4], 2, 3).await;
+
+        let as_of = Antichain::from_elem(2);
+        let stats = read
+            .snapshot_stats(as_of.clone())
+            .await
+            .expect("as_of is valid");
+        assert_eq!(stats.as_of, as_of);
+        assert!(!stats.is_lower_bound);
+        // The stats count every update, even the ones that consolidate out.
+        assert_eq!(stats.num_updates, data.len());
+        assert_eq!(
+            read.expect_snapshot_and_fetch(2).await.len(),
+            data.len() - 2
+        );
+
+        let parts = read.snapshot(as_of.clone()).await.expect("as_of is valid");
+        assert_eq!(stats.num_parts, parts.len());
+        assert_eq!(
+            stats.encoded_size_bytes,
+            parts.iter().map(|x| x.encoded_size_bytes()).sum::<usize>()
+        );
+        for part in parts {
+            read.process_returned_leased_part(part);
+        }
+
+        // A batch written before we recorded update counts makes the count a
+        // lower bound.
+        let mut batches = read.machine.snapshot(&as_of).await.expect("as_of is valid");
+        let missing_len = batches
+            .iter_mut()
+            .find(|x| x.len > 0)
+            .expect("non-empty batch");
+        let missing = missing_len.len;
+        missing_len.len = 0;
+        let stats = SnapshotStats::from_batches(read.shard_id(), as_of, &batches);
+        assert!(stats.is_lower_bound);
+        assert_eq!(stats.num_updates, data.len() - missing);
+    }
+
+    /// A key that panics when decoding the bytes `"boom"`.
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct PanicOnDecode(String);
+
+    impl Codec for PanicOnDecode {
+        type Schema = PanicOnDecodeSchema;
+
+        fn codec_name() -> String {
+            "PanicOnDecode".into()
+        }
+
+        fn encode<B: BufMut>(&self, buf: &mut B) {
+            buf.put(self.0.as_bytes())
+        }
+
+        fn decode<'a>(buf: &'a [u8]) -> Result<Self, String> {
+            assert_ne!(buf, b"boom", "injected decode panic");
+            String::decode(buf).map(PanicOnDecode)
+        }
+    }
+
+    #[derive(Debug)]
+    struct PanicOnDecodeSchema;
+
+    impl Schema<PanicOnDecode> for PanicOnDecodeSchema {
+        type Encoder<'a> = SimpleEncoder<'a, PanicOnDecode, String>;
+
+        type Decoder<'a> = SimpleDecoder<'a, PanicOnDecode, String>;
+
+        fn columns(&self) -> DynStructCfg {
+            SimpleSchema::<PanicOnDecode, String>::columns(&())
+        }
+
+        fn decoder<'a>(&self, cols: ColumnsRef<'a>) -> Result<Self::Decoder<'a>, String> {
+            SimpleSchema::<PanicOnDecode, String>::decoder(cols, |val, ret| {
+                *ret = PanicOnDecode(val.to_owned())
+            })
+        }
+
+        fn encoder<'a>(&self, cols: ColumnsMut<'a>) -> Result<Self::Encoder<'a>, String> {
+            SimpleSchema::<PanicOnDecode, String>::push_encoder(cols, |col, val| {
+                ColumnPush::<String>::push(col, &val.0)
+            })
+        }
+    }
+
+    // Verifies that both snapshot_and_fetch paths report the consolidation
+    // they do, and record it in the shard metrics.
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn snapshot_and_fetch_stats() {
+        let data = vec![
+            (("a".to_owned(), "one".to_owned()), 0, 1),
+            (("a".to_owned(), "one".to_owned()), 1, -1),
+            (("b".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let (client, mut write, mut read) = new_test_shard().await;
+        write.expect_compare_and_append(&data[0..1], 0, 1).await;
+        write.expect_compare_and_append(&data[1..2], 1, 2).await;
+        write.expect_compare_and_append(&data[2..3], 2, 3).await;
+
+        let shard_metrics = Arc::clone(&read.machine.applier.shard_metrics);
+        let _guard = ConfigGuard::new(&client.cfg.configs);
+        for streaming in [false, true] {
+            client
+                .cfg
+                .set_config(&STREAMING_SNAPSHOT_AND_FETCH_ENABLED, streaming);
+            let bytes_before = shard_metrics.read_consolidation_bytes.get();
+            let updates_in_before = shard_metrics.read_consolidation_updates_in.get();
+            let (contents, stats) = read
+                .snapshot_and_fetch_with_stats(Antichain::from_elem(2))
+                .await
+                .expect("as_of beyond since");
+
+            // The `a`s cancel out.
+            assert_eq!(contents, all_ok(&data[2..3], 2), "streaming={}", streaming);
+            assert_eq!(stats.updates_in, 3, "streaming={}", streaming);
+            assert_eq!(stats.updates_out, 1, "streaming={}", streaming);
+            assert!(
+                stats.runs_merged > 0,
+                "streaming={}: {:?}",
+                streaming,
+                stats
+            );
+            assert!(
+                stats.bytes_processed > 0,
+                "streaming={}: {:?}",
+                streaming,
+                stats
+            );
+            assert_eq!(
+                shard_metrics.read_consolidation_bytes.get() - bytes_before,
+                u64::cast_from(stats.bytes_processed),
+                "streaming={}",
+                streaming
+            );
+            assert_eq!(
+                shard_metrics.read_consolidation_updates_in.get() - updates_in_before,
+                3,
+                "streaming={}",
+                streaming
+            );
+        }
+    }
+
+    // Verifies that a panic while decoding a snapshot is returned as an error
+    // instead of taking down the process.
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn snapshot_and_fetch_decode_panic() {
+        let data = vec![
+            ((PanicOnDecode("a".to_owned()), "one".to_owned()), 0, 1),
+            ((PanicOnDecode("boom".to_owned()), "two".to_owned()), 1, 1),
+            ((PanicOnDecode("c".to_owned()), "three".to_owned()), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+        let (mut write, mut read) = client
+            .expect_open::<PanicOnDecode, String, u64, i64>(ShardId::new())
+            .await;
+        write.expect_compare_and_append(&data[0..1], 0, 1).await;
+        write.expect_compare_and_append(&data[1..2], 1, 2).await;
+        write.expect_compare_and_append(&data[2..3], 2, 3).await;
+
+        let panics = &client.metrics.isolated_runtime.panics;
+        let _guard = ConfigGuard::new(&client.cfg.configs);
+        for streaming in [false, true] {
+            client
+                .cfg
+                .set_config(&STREAMING_SNAPSHOT_AND_FETCH_ENABLED, streaming);
+            let panics_before = panics.get();
+            match read.snapshot_and_fetch(Antichain::from_elem(2)).await {
+                Err(SnapshotError::InternalPanic(err)) => {
+                    assert_eq!(err.operation, "snapshot_and_fetch::decode");
+                    assert!(
+                        err.message.contains("injected decode panic"),
+                        "{}",
+                        err.message
+                    );
+                }
+                res => panic!(
+                    "streaming={}: expected an internal panic: {:?}",
+                    streaming, res
+                ),
+            }
+            assert_eq!(panics.get(), panics_before + 1);
+        }
+
+        // The reader is still usable, and reads that don't hit the bad key
+        // still succeed.
+        assert_eq!(
+            read.snapshot_and_fetch(Antichain::from_elem(0)).await,
+            Ok(vec![(
+                (Ok(PanicOnDecode("a".to_owned())), Ok("one".to_owned())),
+                0,
+                1
+            )])
+        );
+    }
 }
@@ -0,0 +1,388 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A helper for retaining a fixed window of history in a shard.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use differential_dataflow::difference::Semigroup;
+use differential_dataflow::lattice::Lattice;
+use mz_ore::now::{EpochMillis, NowFn};
+use mz_ore::task::AbortOnDropHandle;
+use mz_persist_types::{Codec, Codec64};
+use timely::order::TotalOrder;
+use timely::progress::{Antichain, Timestamp};
+use timely::PartialOrder;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+use crate::critical::SinceHandle;
+use crate::ShardId;
+
+/// A window of history to retain in a shard, expressed in wall-clock time.
+#[derive(Clone)]
+pub struct RetentionWindow<T> {
+    window: Duration,
+    to_timestamp: Arc<dyn Fn(EpochMillis) -> T + Send + Sync>,
+}
+
+impl<T> Debug for RetentionWindow<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetentionWindow")
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> RetentionWindow<T> {
+    /// Returns a window of the given length for a shard whose timestamps are
+    /// derived from wall-clock milliseconds by `to_timestamp`.
+    ///
+    /// `to_timestamp` must be monotonic: later wall-clock times must map to
+    /// timestamps that are greater or equal.
+    pub fn new<F>(window: Duration, to_timestamp: F) -> Self
+    where
+        F: Fn(EpochMillis) -> T + Send + Sync + 'static,
+    {
+        RetentionWindow {
+            window,
+            to_timestamp: Arc::new(to_timestamp),
+        }
+    }
+
+    /// The length of this window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The since that retains this window as of the wall-clock time `now`.
+    fn since_at(&self, now: EpochMillis) -> Antichain<T> {
+        let window = u64::try_from(self.window.as_millis()).unwrap_or(u64::MAX);
+        Antichain::from_elem((self.to_timestamp)(now.saturating_sub(window)))
+    }
+}
+
+impl RetentionWindow<u64> {
+    /// Returns a window of the given length for a shard whose timestamps are
+    /// milliseconds since the Unix epoch.
+    pub fn millis(window: Duration) -> Self {
+        Self::new(window, |millis| millis)
+    }
+}
+
+/// The status of a [RetentionHandle].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetentionStatus<T> {
+    /// The since held by the handle. History at and beyond this frontier is
+    /// retained in the shard.
+    pub retained_lower: Antichain<T>,
+    /// Whether another process took over retention for the shard, in which
+    /// case this handle no longer downgrades the since.
+    pub fenced: bool,
+}
+
+/// A handle that retains a fixed window of history in a shard, returned by
+/// [crate::PersistClient::apply_retention].
+///
+/// While running, the handle periodically downgrades a critical since so that
+/// the shard retains approximately the requested window of history and
+/// everything older is available for compaction.
+///
+/// Like any [SinceHandle], the since is not released when this handle is
+/// dropped or when the process is lost. Instead, it stays where it was last
+/// downgraded: reads and writes to the shard are unaffected, but history
+/// accumulates until retention is resumed. Calling
+/// [crate::PersistClient::apply_retention] again with the same
+/// [crate::critical::CriticalReaderId], e.g. after a restart, takes over the
+/// since from any previous (possibly zombie) handle and fences it out. Use
+/// [Self::expire] to stop retaining history altogether.
+#[derive(Debug)]
+pub struct RetentionHandle<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + TotalOrder + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    shard_id: ShardId,
+    status: Arc<Mutex<RetentionStatus<T>>>,
+    // Exactly one of these is set, depending on whether the task is running.
+    stopped: Option<Retainer<K, V, T, D>>,
+    running: Option<(oneshot::Sender<()>, AbortOnDropHandle<Retainer<K, V, T, D>>)>,
+}
+
+impl<K, V, T, D> RetentionHandle<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + TotalOrder + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    /// Takes over the since of `since` and starts retaining `window` of
+    /// history, downgrading the since every `interval`.
+    pub(crate) async fn new(
+        mut since: SinceHandle<K, V, T, D, u64>,
+        window: RetentionWindow<T>,
+        now: NowFn,
+        interval: Duration,
+    ) -> Self {
+        // Bump the opaque token so that any previous handle for this reader
+        // is fenced out on its next downgrade.
+        let token = loop {
+            let current = *since.opaque();
+            let token = current + 1;
+            let current_since = since.since().clone();
+            match since
+                .compare_and_downgrade_since(&current, (&token, &current_since))
+                .await
+            {
+                Ok(_) => break token,
+                Err(_) => continue,
+            }
+        };
+
+        let status = Arc::new(Mutex::new(RetentionStatus {
+            retained_lower: since.since().clone(),
+            fenced: false,
+        }));
+        let mut handle = RetentionHandle {
+            shard_id: since.shard_id(),
+            status: Arc::clone(&status),
+            stopped: Some(Retainer {
+                since,
+                window,
+                token,
+                now,
+                interval,
+                status,
+            }),
+            running: None,
+        };
+        handle.start();
+        handle
+    }
+
+    /// The shard whose history is retained by this handle.
+    pub fn shard_id(&self) -> ShardId {
+        self.shard_id
+    }
+
+    /// Whether this handle is currently downgrading the since.
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// The current status of this handle.
+    pub fn status(&self) -> RetentionStatus<T> {
+        self.status.lock().expect("lock poisoned").clone()
+    }
+
+    /// Starts periodically downgrading the since, if not already running.
+    pub fn start(&mut self) {
+        let Some(retainer) = self.stopped.take() else {
+            return;
+        };
+        let (tx, rx) = oneshot::channel();
+        let task = mz_ore::task::spawn(
+            || format!("persist::retention::{}", self.shard_id),
+            retainer.run(rx),
+        );
+        self.running = Some((tx, task.abort_on_drop()));
+    }
+
+    /// Stops downgrading the since, if running. The since stays where it was
+    /// last downgraded until [Self::start] is called again.
+    pub async fn stop(&mut self) {
+        let Some((tx, task)) = self.running.take() else {
+            return;
+        };
+        // The task might have already exited if it was fenced.
+        let _ = tx.send(());
+        let retainer = task.await.expect("retention task panicked");
+        self.stopped = Some(retainer);
+    }
+
+    /// Stops this handle and releases its since, so that the shard no longer
+    /// retains any history on its behalf.
+    ///
+    /// This is a no-op if the handle has been fenced, because the since then
+    /// belongs to whoever took it over.
+    pub async fn expire(mut self) {
+        self.stop().await;
+        if self.status().fenced {
+            return;
+        }
+        let retainer = self.stopped.take().expect("handle was just stopped");
+        retainer.since.expire().await;
+    }
+}
+
+/// The state owned by the task of a running [RetentionHandle].
+#[derive(Debug)]
+struct Retainer<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + TotalOrder + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    since: SinceHandle<K, V, T, D, u64>,
+    window: RetentionWindow<T>,
+    token: u64,
+    now: NowFn,
+    interval: Duration,
+    status: Arc<Mutex<RetentionStatus<T>>>,
+}
+
+impl<K, V, T, D> Retainer<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + TotalOrder + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    async fn run(mut self, mut shutdown: oneshot::Receiver<()>) -> Self {
+        loop {
+            if !self.downgrade().await {
+                return self;
+            }
+            tokio::select! {
+                _ = &mut shutdown => return self,
+                _ = tokio::time::sleep(self.interval) => {}
+            }
+        }
+    }
+
+    /// Downgrades the since to retain the window as of now, returning false if
+    /// this handle has been fenced out.
+    async fn downgrade(&mut self) -> bool {
+        let new_since = self.window.since_at((self.now)());
+        if !PartialOrder::less_than(self.since.since(), &new_since) {
+            return true;
+        }
+        let res = self
+            .since
+            .compare_and_downgrade_since(&self.token, (&self.token, &new_since))
+            .await;
+        let mut status = self.status.lock().expect("lock poisoned");
+        status.retained_lower = self.since.since().clone();
+        match res {
+            Ok(_) => true,
+            Err(actual) => {
+                warn!(
+                    "retention for shard {} fenced by opaque {} (expected {})",
+                    self.since.shard_id(),
+                    actual,
+                    self.token
+                );
+                status.fenced = true;
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use mz_ore::metrics::MetricsRegistry;
+
+    use crate::cache::PersistClientCache;
+    use crate::cfg::PersistConfig;
+    use crate::critical::CriticalReaderId;
+    use crate::rpc::PubSubClientConnection;
+    use crate::{Diagnostics, PersistLocation};
+
+    use super::*;
+
+    async fn wait_for_lower<K, V, D>(handle: &RetentionHandle<K, V, u64, D>, expected: u64)
+    where
+        K: Debug + Codec,
+        V: Debug + Codec,
+        D: Semigroup + Codec64 + Send + Sync,
+    {
+        while handle.status().retained_lower != Antichain::from_elem(expected) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn retention_tracks_window() {
+        let now = Arc::new(AtomicU64::new(10_000));
+        let mut cfg = PersistConfig::new_for_tests();
+        cfg.now = NowFn::from({
+            let now = Arc::clone(&now);
+            move || now.load(Ordering::SeqCst)
+        });
+        cfg.critical_downgrade_interval = Duration::from_millis(1);
+        let cache = PersistClientCache::new(cfg, &MetricsRegistry::new(), |_, _| {
+            PubSubClientConnection::noop()
+        });
+        let client = cache
+            .open(PersistLocation::new_in_mem())
+            .await
+            .expect("client construction failed");
+        let shard_id = ShardId::new();
+        let reader_id = CriticalReaderId::new();
+        let window = RetentionWindow::millis(Duration::from_secs(1));
+
+        let mut handle = client
+            .apply_retention::<String, String, u64, i64>(
+                shard_id,
+                reader_id.clone(),
+                window.clone(),
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("codec mismatch");
+        assert!(handle.is_running());
+        wait_for_lower(&handle, 9_000).await;
+
+        // The since follows the wall clock.
+        now.store(20_000, Ordering::SeqCst);
+        wait_for_lower(&handle, 19_000).await;
+
+        // A stopped handle holds the since where it is.
+        handle.stop().await;
+        assert!(!handle.is_running());
+        now.store(30_000, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(handle.status().retained_lower, Antichain::from_elem(19_000));
+
+        // And picks back up once restarted.
+        handle.start();
+        wait_for_lower(&handle, 29_000).await;
+
+        // Taking over the reader (e.g. after a restart) fences out the
+        // previous handle.
+        now.store(40_000, Ordering::SeqCst);
+        let takeover = client
+            .apply_retention::<String, String, u64, i64>(
+                shard_id,
+                reader_id,
+                window,
+                Diagnostics::for_tests(),
+            )
+            .await
+            .expect("codec mismatch");
+        wait_for_lower(&takeover, 39_000).await;
+        now.store(50_000, Ordering::SeqCst);
+        while !handle.status().fenced {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        wait_for_lower(&takeover, 49_000).await;
+
+        handle.stop().await;
+        takeover.expire().await;
+    }
+}
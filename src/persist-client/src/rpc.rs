@@ -33,6 +33,7 @@ use mz_proto::{ProtoType, RustType};
 use prost::Message;
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
 use tokio_stream::StreamExt;
@@ -144,6 +145,41 @@ impl<T> PubSubReceiver for T where
 pub struct ShardSubscriptionToken {
     pub(crate) shard_id: ShardId,
     sender: Arc<dyn PubSubSenderInternal>,
+    /// Changed whenever the subscription is re-established with the PubSub
+    /// server, after which diffs pushed while it wasn't might be missing.
+    ///
+    /// This is a watch channel (rather than e.g. a [tokio::sync::Notify]) so
+    /// that a re-subscription isn't lost on anyone who only starts waiting for
+    /// it after it happened.
+    resubscribed: watch::Sender<()>,
+}
+
+impl ShardSubscriptionToken {
+    fn new(shard_id: ShardId, sender: Arc<dyn PubSubSenderInternal>) -> Self {
+        ShardSubscriptionToken {
+            shard_id,
+            sender,
+            resubscribed: watch::channel(()).0,
+        }
+    }
+
+    /// Returns a receiver that sees every time the subscription is
+    /// re-established with the PubSub server after this call, e.g. after the
+    /// connection to it was lost.
+    ///
+    /// Diffs pushed while the subscription wasn't established are never
+    /// delivered, so anyone waiting on diffs should refresh their state from
+    /// consensus when the receiver sees a change.
+    pub(crate) fn resubscribes(&self) -> watch::Receiver<()> {
+        self.resubscribed.subscribe()
+    }
+
+    /// Records that the subscription was re-established with the PubSub
+    /// server.
+    pub(crate) fn notify_resubscribed(&self) {
+        // Unlike `send`, this doesn't fail if there are no receivers.
+        self.resubscribed.send_replace(());
+    }
 }
 
 impl Debug for ShardSubscriptionToken {
@@ -151,6 +187,7 @@ impl Debug for ShardSubscriptionToken {
         let ShardSubscriptionToken {
             shard_id,
             sender: _sender,
+            resubscribed: _resubscribed,
         } = self;
         write!(f, "ShardSubscriptionToken({})", shard_id)
     }
@@ -194,6 +231,7 @@ impl GrpcPubSubClient {
         metrics: Arc<Metrics>,
     ) {
         let mut is_first_connection_attempt = true;
+        let mut reconnect_backoff = config.persist_cfg.pubsub_reconnect_backoff;
         loop {
             metrics.pubsub_client.grpc_connection.connected.set(0);
 
@@ -202,11 +240,17 @@ impl GrpcPubSubClient {
                 continue;
             }
 
-            // add a bit of backoff when reconnecting after some network/server failure
+            // add a bit of backoff when reconnecting after some network/server failure,
+            // growing exponentially while the server keeps rejecting our streams
             if is_first_connection_attempt {
                 is_first_connection_attempt = false;
             } else {
-                tokio::time::sleep(config.persist_cfg.pubsub_reconnect_backoff).await;
+                tokio::time::sleep(reconnect_backoff).await;
+                reconnect_backoff = std::cmp::min(
+                    reconnect_backoff.saturating_mul(2),
+                    config.persist_cfg.pubsub_connect_max_backoff,
+                );
+                metrics.pubsub_client.grpc_connection.reconnect_count.inc();
             }
 
             info!("Connecting to Persist PubSub: {}", config.url);
@@ -278,10 +322,13 @@ impl GrpcPubSubClient {
                     continue;
                 }
             };
+            reconnect_backoff = config.persist_cfg.pubsub_reconnect_backoff;
 
             // shard subscriptions are tracked by connection on the server, so if our
             // gRPC stream is ever swapped out, we must inform the server which shards
-            // our client intended to be subscribed to.
+            // our client intended to be subscribed to. this also wakes up anyone
+            // waiting on diffs for those shards, so they can refresh their state in
+            // case they missed some.
             sender.reconnect();
 
             let stream_completed = GrpcPubSubClient::consume_grpc_stream(
@@ -481,12 +528,14 @@ impl SubscriptionTrackingSender {
 
     fn reconnect(&self) {
         let mut subscribes = self.subscribes.lock().expect("lock");
-        subscribes.retain(|shard_id, token| {
-            if token.upgrade().is_none() {
-                false
-            } else {
+        subscribes.retain(|shard_id, token| match token.upgrade() {
+            None => false,
+            Some(token) => {
                 debug!("reconnecting to: {}", shard_id);
                 self.delegate.subscribe(shard_id);
+                // We might have missed diffs while we weren't subscribed, let
+                // anyone waiting on them know.
+                token.notify_resubscribed();
                 true
             }
         })
@@ -510,10 +559,7 @@ impl PubSubSender for SubscriptionTrackingSender {
         }
 
         let pubsub_sender = Arc::clone(&self.delegate);
-        let token = Arc::new(ShardSubscriptionToken {
-            shard_id: *shard_id,
-            sender: pubsub_sender,
-        });
+        let token = Arc::new(ShardSubscriptionToken::new(*shard_id, pubsub_sender));
 
         assert!(subscribes
             .insert(*shard_id, Arc::downgrade(&token))
@@ -556,10 +602,10 @@ impl PubSubSender for MetricsSameProcessPubSubSender {
         // For clients running in the same process as the server, this is
         // safe because the StateCached is shared between them, and the
         // server necessarily always receives and applies all diffs.
-        Arc::new(ShardSubscriptionToken {
-            shard_id: *shard_id,
-            sender: Arc::new(NoopPubSubSender),
-        })
+        Arc::new(ShardSubscriptionToken::new(
+            *shard_id,
+            Arc::new(NoopPubSubSender),
+        ))
     }
 }
 
@@ -576,10 +622,7 @@ impl PubSubSender for NoopPubSubSender {
     fn push_diff(&self, _shard_id: &ShardId, _diff: &VersionedData) {}
 
     fn subscribe(self: Arc<Self>, shard_id: &ShardId) -> Arc<ShardSubscriptionToken> {
-        Arc::new(ShardSubscriptionToken {
-            shard_id: *shard_id,
-            sender: self,
-        })
+        Arc::new(ShardSubscriptionToken::new(*shard_id, self))
     }
 }
 
@@ -1263,6 +1306,42 @@ mod pubsub_state {
         );
     }
 
+    #[mz_ore::test]
+    fn test_full_channel_drops_diffs() {
+        let state = Arc::new(PubSubState::new_for_test());
+
+        let (tx1, mut rx1) = tokio::sync::mpsc::channel(1);
+        let conn1 = Arc::clone(&state).new_connection(tx1);
+
+        let (tx2, _rx2) = tokio::sync::mpsc::channel(100);
+        let conn2 = Arc::clone(&state).new_connection(tx2);
+
+        conn1.subscribe(&SHARD_ID_0);
+
+        // the first diff fits in the subscriber's channel
+        conn2.push_diff(&SHARD_ID_0, &VERSIONED_DATA_0);
+        assert_eq!(state.metrics.push_call_count.get(), 1);
+        assert_eq!(state.metrics.broadcasted_diff_count.get(), 1);
+        assert_eq!(state.metrics.broadcasted_diff_dropped_channel_full.get(), 0);
+
+        // the channel is now full, so the next diff is dropped rather than
+        // blocking the publisher
+        conn2.push_diff(&SHARD_ID_0, &VERSIONED_DATA_1);
+        assert_eq!(state.metrics.push_call_count.get(), 2);
+        assert_eq!(state.metrics.broadcasted_diff_count.get(), 1);
+        assert_eq!(state.metrics.broadcasted_diff_dropped_channel_full.get(), 1);
+
+        // only the first diff was delivered
+        assert_push(&mut rx1, &SHARD_ID_0, &VERSIONED_DATA_0);
+        assert!(matches!(rx1.try_recv(), Err(TryRecvError::Empty)));
+
+        // once the subscriber catches up, diffs are delivered again
+        conn2.push_diff(&SHARD_ID_0, &VERSIONED_DATA_1);
+        assert_push(&mut rx1, &SHARD_ID_0, &VERSIONED_DATA_1);
+        assert_eq!(state.metrics.broadcasted_diff_count.get(), 2);
+        assert_eq!(state.metrics.broadcasted_diff_dropped_channel_full.get(), 1);
+    }
+
     fn assert_push(
         rx: &mut Receiver<Result<ProtoPubSubMessage, Status>>,
         shard: &ShardId,
@@ -1297,19 +1376,24 @@ mod grpc {
     use mz_ore::metrics::MetricsRegistry;
     use mz_persist::location::{SeqNo, VersionedData};
     use mz_proto::RustType;
+    use timely::progress::Antichain;
     use tokio::net::TcpListener;
     use tokio_stream::wrappers::TcpListenerStream;
     use tokio_stream::StreamExt;
 
-    use crate::cfg::{PersistConfig, PersistParameters};
+    use crate::async_runtime::IsolatedRuntime;
+    use crate::cache::StateCache;
+    use crate::cfg::{PersistConfig, PersistParameters, RetryParameters};
     use crate::internal::service::proto_pub_sub_message::Message;
     use crate::internal::service::ProtoPubSubMessage;
     use crate::metrics::Metrics;
+    use crate::read::ListenEvent;
     use crate::rpc::{
-        GrpcPubSubClient, PersistGrpcPubSubServer, PersistPubSubClient, PersistPubSubClientConfig,
-        PubSubState,
+        subscribe_state_cache_to_pubsub, GrpcPubSubClient, PersistGrpcPubSubServer,
+        PersistPubSubClient, PersistPubSubClientConfig, PubSubState,
     };
-    use crate::ShardId;
+    use crate::tests::new_test_client;
+    use crate::{PersistClient, ShardId};
 
     const SHARD_ID_0: ShardId = ShardId([0u8; 16]);
     const SHARD_ID_1: ShardId = ShardId([1u8; 16]);
@@ -1325,6 +1409,7 @@ mod grpc {
     const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
     const SUBSCRIPTIONS_TIMEOUT: Duration = Duration::from_secs(3);
     const SERVER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+    const PROGRESS_TIMEOUT: Duration = Duration::from_secs(30);
 
     // NB: we use separate runtimes for client and server throughout these tests to cleanly drop
     // ALL tasks (including spawned child tasks) associated with one end of a connection, to most
@@ -1658,6 +1743,152 @@ mod grpc {
         assert!(client_2.receiver.next().now_or_never().is_none());
     }
 
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // error: unsupported operation: can't call foreign function `socket` on OS `linux`
+    fn grpc_client_listen_across_server_restart() {
+        let server_runtime = tokio::runtime::Runtime::new().expect("server runtime");
+        let client_runtime = tokio::runtime::Runtime::new().expect("client runtime");
+        let (addr, tcp_listener_stream) = server_runtime.block_on(new_tcp_listener());
+        let server_state = server_runtime.block_on(spawn_server(tcp_listener_stream));
+
+        // make sure listens only learn about new data through pubsub, instead of
+        // falling back to polling consensus.
+        let cfg = test_persist_config();
+        let mut params = PersistParameters::default();
+        params.next_listen_batch_retryer = Some(RetryParameters {
+            initial_backoff: Duration::from_secs(60 * 60),
+            multiplier: 1,
+            clamp: Duration::from_secs(60 * 60),
+        });
+        params.apply(&cfg);
+
+        // a writer and a reader that share nothing but the location and the
+        // pubsub server, as if they were in different processes.
+        let shard_id = ShardId::new();
+        let (mut write, reader, mut progress) = client_runtime.block_on(async {
+            let client = new_test_client().await;
+            let writer = new_pubsub_client(&client, &cfg, addr, "writer");
+            let reader = new_pubsub_client(&client, &cfg, addr, "reader");
+            let (write, _) = writer
+                .expect_open::<String, String, u64, i64>(shard_id)
+                .await;
+            let (_, read) = reader
+                .expect_open::<String, String, u64, i64>(shard_id)
+                .await;
+            let mut listen = read.expect_listen(0).await;
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            mz_ore::task::spawn(|| "listen".to_string(), async move {
+                loop {
+                    for event in listen.fetch_next().await {
+                        if let ListenEvent::Progress(frontier) = event {
+                            if tx.send(frontier).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            });
+            (write, reader, rx)
+        });
+        let data = |ts: u64| [(("k".to_owned(), "v".to_owned()), ts, 1)];
+
+        server_runtime.block_on(poll_until_true(SUBSCRIPTIONS_TIMEOUT, || {
+            server_state.shard_subscription_counts() == HashMap::from([(shard_id, 2)])
+        }));
+        client_runtime.block_on(async {
+            write.expect_compare_and_append(&data(0), 0, 1).await;
+            wait_for_progress(&mut progress, 1).await;
+        });
+
+        // kill the server and write while the reader can't hear about it
+        server_runtime.shutdown_timeout(SERVER_SHUTDOWN_TIMEOUT);
+        client_runtime.block_on(write.expect_compare_and_append(&data(1), 1, 2));
+
+        // create a new server
+        let server_runtime = tokio::runtime::Runtime::new().expect("server runtime");
+        let tcp_listener_stream = server_runtime.block_on(async {
+            TcpListenerStream::new(
+                TcpListener::bind(addr)
+                    .await
+                    .expect("can bind to previous addr"),
+            )
+        });
+        let server_state = server_runtime.block_on(spawn_server(tcp_listener_stream));
+        server_runtime.block_on(poll_until_true(SUBSCRIPTIONS_TIMEOUT, || {
+            server_state.shard_subscription_counts() == HashMap::from([(shard_id, 2)])
+        }));
+
+        // the listen learns about the diff it missed once the reader resubscribes,
+        // and keeps making progress from pushed diffs afterwards.
+        client_runtime.block_on(async {
+            wait_for_progress(&mut progress, 2).await;
+            write.expect_compare_and_append(&data(2), 2, 3).await;
+            wait_for_progress(&mut progress, 3).await;
+        });
+        assert!(reader.metrics.watch.listen_woken_via_resubscribe.get() > 0);
+        assert!(
+            reader
+                .metrics
+                .pubsub_client
+                .grpc_connection
+                .reconnect_count
+                .get()
+                > 0
+        );
+    }
+
+    /// Returns a client with its own state cache that is connected to the pubsub
+    /// server at `addr`, and otherwise shares the location of `client`.
+    fn new_pubsub_client(
+        client: &PersistClient,
+        cfg: &PersistConfig,
+        addr: SocketAddr,
+        caller_id: &str,
+    ) -> PersistClient {
+        let metrics = Arc::new(Metrics::new(cfg, &MetricsRegistry::new()));
+        let connection = GrpcPubSubClient::connect(
+            PersistPubSubClientConfig {
+                url: format!("http://{}", addr),
+                caller_id: caller_id.to_string(),
+                persist_cfg: cfg.clone(),
+            },
+            Arc::clone(&metrics),
+        );
+        let state_cache = Arc::new(StateCache::new(
+            cfg,
+            Arc::clone(&metrics),
+            Arc::clone(&connection.sender),
+        ));
+        let _receiver_task =
+            subscribe_state_cache_to_pubsub(Arc::clone(&state_cache), connection.receiver);
+        PersistClient::new(
+            cfg.clone(),
+            Arc::clone(&client.blob),
+            Arc::clone(&client.consensus),
+            metrics,
+            Arc::new(IsolatedRuntime::new()),
+            state_cache,
+            connection.sender,
+        )
+        .expect("client construction failed")
+    }
+
+    async fn wait_for_progress(
+        progress: &mut tokio::sync::mpsc::UnboundedReceiver<Antichain<u64>>,
+        ts: u64,
+    ) {
+        tokio::time::timeout(PROGRESS_TIMEOUT, async {
+            loop {
+                let frontier = progress.recv().await.expect("listen is running");
+                if !frontier.less_equal(&(ts - 1)) {
+                    return;
+                }
+            }
+        })
+        .await
+        .expect("listen made progress")
+    }
+
     async fn new_tcp_listener() -> (SocketAddr, TcpListenerStream) {
         let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
         let tcp_listener = TcpListener::bind(addr).await.expect("tcp listener");
@@ -18,6 +18,7 @@ use proptest_derive::Arbitrary;
 use timely::progress::Antichain;
 
 use crate::internal::encoding::Schemas;
+use crate::internal::state::HollowBatch;
 use crate::ShardId;
 
 /// Aggregate statistics about data contained in a [Part].
@@ -89,4 +90,48 @@ pub struct SnapshotStats<T> {
     /// compaction never results in more updates than the sum of the inputs, it
     /// can only go down.
     pub num_updates: usize,
+    /// The total encoded size of the parts in the shard.
+    pub encoded_size_bytes: usize,
+    /// The number of parts in the shard.
+    pub num_parts: usize,
+    /// Whether `num_updates` and `encoded_size_bytes` are only lower bounds.
+    ///
+    /// This is the case if some of the batches or parts in the snapshot were
+    /// written before persist started recording their counts and sizes.
+    pub is_lower_bound: bool,
+}
+
+impl<T> SnapshotStats<T> {
+    /// Computes the stats for a snapshot made up of `batches`, using only the
+    /// metadata in state.
+    pub(crate) fn from_batches(
+        shard_id: ShardId,
+        as_of: Antichain<T>,
+        batches: &[HollowBatch<T>],
+    ) -> Self {
+        let mut stats = SnapshotStats {
+            shard_id,
+            as_of,
+            num_updates: 0,
+            encoded_size_bytes: 0,
+            num_parts: 0,
+            is_lower_bound: false,
+        };
+        for batch in batches {
+            // A batch with parts can't be empty, so a zero len means the count
+            // wasn't recorded.
+            if batch.len == 0 && !batch.parts.is_empty() {
+                stats.is_lower_bound = true;
+            }
+            stats.num_updates += batch.len;
+            for part in batch.parts.iter() {
+                if part.encoded_size_bytes == 0 {
+                    stats.is_lower_bound = true;
+                }
+                stats.encoded_size_bytes += part.encoded_size_bytes;
+                stats.num_parts += 1;
+            }
+        }
+        stats
+    }
 }
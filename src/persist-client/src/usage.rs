@@ -10,12 +10,13 @@
 //! Introspection of storage utilization by persist
 
 use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use futures::stream::{FuturesUnordered, StreamExt};
 use mz_ore::cast::CastFrom;
-use mz_persist::location::Blob;
+use mz_persist::location::{Blob, SeqNo};
 use tokio::sync::Semaphore;
 use tracing::{error, info};
 
@@ -127,6 +128,163 @@ pub struct ShardsUsageAudit {
     pub unattributable_bytes: u64,
 }
 
+/// The width of each bucket of a [ShardActivityWindow].
+const ACTIVITY_BUCKET_SECS: u64 = 5 * 60;
+/// The number of buckets of a [ShardActivityWindow], which together span the
+/// window.
+const ACTIVITY_NUM_BUCKETS: usize = 12;
+
+/// An operation against a shard that contributes to its cost beyond the bytes
+/// it stores in Blob.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ShardOp {
+    /// A batch was appended to the shard.
+    Append,
+    /// A compare_and_set of the shard's state was issued to Consensus.
+    ConsensusCas,
+    /// The shard's state was scanned from Consensus, returning `bytes` of
+    /// diffs.
+    ConsensusScan { bytes: u64 },
+    /// The shard's diffs in Consensus were truncated.
+    ConsensusTruncate,
+    /// A diff of the shard's state was pushed to PubSub subscribers.
+    PubSubPush,
+}
+
+/// Counts of the operations against a shard by this process over a trailing
+/// window.
+///
+/// See [StorageUsageClient::shards_activity_report].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShardActivity {
+    /// The number of batches appended.
+    pub appends: u64,
+    /// The number of compare_and_set calls issued to Consensus.
+    pub consensus_cas: u64,
+    /// The size of the diffs scanned from Consensus.
+    pub consensus_scan_bytes: u64,
+    /// The number of truncations issued to Consensus.
+    pub consensus_truncations: u64,
+    /// The number of diffs pushed to PubSub.
+    pub pubsub_pushes: u64,
+}
+
+/// The referenced blob usage of a shard, along with its recent activity.
+#[derive(Clone, Debug)]
+pub struct ShardActivityReport {
+    /// The blob usage referenced by live states of the shard.
+    pub usage: ShardUsageReferenced,
+    /// The operations against the shard by this process in the window.
+    pub activity: ShardActivity,
+}
+
+/// The activity reports for a set of shards.
+#[derive(Debug)]
+pub struct ShardsActivityReport {
+    /// The length of the trailing window the activity counts cover.
+    pub window_secs: u64,
+    /// The data for each shard.
+    pub by_shard: BTreeMap<ShardId, ShardActivityReport>,
+}
+
+/// Sliding window counts of the [ShardOp]s against a single shard.
+///
+/// This follows the same scheme as [crate::health::HealthWindow]: a fixed ring
+/// of time buckets of atomic counters, each lazily reset by the first
+/// recording that finds it stale, so the memory used per shard is constant no
+/// matter how busy the shard is.
+#[derive(Debug)]
+pub(crate) struct ShardActivityWindow {
+    start: Instant,
+    buckets: [ActivityBucket; ACTIVITY_NUM_BUCKETS],
+}
+
+#[derive(Debug, Default)]
+struct ActivityBucket {
+    /// The epoch (see [ShardActivityWindow::epoch]) this bucket holds counts
+    /// for, or 0 if it was never used.
+    epoch: AtomicU64,
+    appends: AtomicU64,
+    consensus_cas: AtomicU64,
+    consensus_scan_bytes: AtomicU64,
+    consensus_truncations: AtomicU64,
+    pubsub_pushes: AtomicU64,
+}
+
+impl ShardActivityWindow {
+    pub(crate) fn new() -> Self {
+        ShardActivityWindow {
+            start: Instant::now(),
+            buckets: Default::default(),
+        }
+    }
+
+    /// The length of the window covered by [Self::snapshot].
+    pub(crate) fn window_secs() -> u64 {
+        ACTIVITY_BUCKET_SECS * u64::cast_from(ACTIVITY_NUM_BUCKETS)
+    }
+
+    /// The index of the time bucket `now` falls into, starting at 1.
+    fn epoch(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.start).as_secs() / ACTIVITY_BUCKET_SECS + 1
+    }
+
+    /// Records an operation against the shard.
+    pub(crate) fn record(&self, op: ShardOp) {
+        self.record_at(Instant::now(), op)
+    }
+
+    fn record_at(&self, now: Instant, op: ShardOp) {
+        let epoch = self.epoch(now);
+        let bucket = &self.buckets[usize::cast_from(epoch) % ACTIVITY_NUM_BUCKETS];
+        let bucket_epoch = bucket.epoch.load(Ordering::Relaxed);
+        if bucket_epoch != epoch
+            && bucket
+                .epoch
+                .compare_exchange(bucket_epoch, epoch, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            bucket.appends.store(0, Ordering::Relaxed);
+            bucket.consensus_cas.store(0, Ordering::Relaxed);
+            bucket.consensus_scan_bytes.store(0, Ordering::Relaxed);
+            bucket.consensus_truncations.store(0, Ordering::Relaxed);
+            bucket.pubsub_pushes.store(0, Ordering::Relaxed);
+        }
+
+        let (counter, delta) = match op {
+            ShardOp::Append => (&bucket.appends, 1),
+            ShardOp::ConsensusCas => (&bucket.consensus_cas, 1),
+            ShardOp::ConsensusScan { bytes } => (&bucket.consensus_scan_bytes, bytes),
+            ShardOp::ConsensusTruncate => (&bucket.consensus_truncations, 1),
+            ShardOp::PubSubPush => (&bucket.pubsub_pushes, 1),
+        };
+        counter.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    /// Returns the counts of the window ending now.
+    pub(crate) fn snapshot(&self) -> ShardActivity {
+        self.snapshot_at(Instant::now())
+    }
+
+    fn snapshot_at(&self, now: Instant) -> ShardActivity {
+        let epoch = self.epoch(now);
+        let oldest_epoch = epoch.saturating_sub(u64::cast_from(ACTIVITY_NUM_BUCKETS) - 1);
+        let mut activity = ShardActivity::default();
+        for bucket in self.buckets.iter() {
+            let bucket_epoch = bucket.epoch.load(Ordering::Relaxed);
+            if bucket_epoch < oldest_epoch || bucket_epoch > epoch || bucket_epoch == 0 {
+                continue;
+            }
+            activity.appends += bucket.appends.load(Ordering::Relaxed);
+            activity.consensus_cas += bucket.consensus_cas.load(Ordering::Relaxed);
+            activity.consensus_scan_bytes += bucket.consensus_scan_bytes.load(Ordering::Relaxed);
+            activity.consensus_truncations += bucket.consensus_truncations.load(Ordering::Relaxed);
+            activity.pubsub_pushes += bucket.pubsub_pushes.load(Ordering::Relaxed);
+        }
+        activity
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 struct BlobUsage {
     by_shard: BTreeMap<ShardId, ShardBlobUsage>,
@@ -151,6 +309,16 @@ impl ShardBlobUsage {
     }
 }
 
+/// A [ShardUsageReferenced] computed by a previous run, along with what it was
+/// computed from.
+#[derive(Clone, Debug)]
+struct CachedShardUsage {
+    /// The seqnos of the earliest and latest live states the usage was
+    /// computed from.
+    live_seqnos: (SeqNo, SeqNo),
+    usage: ShardUsageReferenced,
+}
+
 /// Provides access to storage usage metrics for a specific Blob
 #[derive(Clone, Debug)]
 pub struct StorageUsageClient {
@@ -158,27 +326,72 @@ pub struct StorageUsageClient {
     blob: Arc<dyn Blob + Send + Sync>,
     metrics: Arc<Metrics>,
     state_versions: Arc<StateVersions>,
+    /// The referenced usage of each shard as of the last time it was computed,
+    /// which is reused as long as the shard's live states don't change.
+    ///
+    /// This only lives in memory, for as long as the client does: environmentd
+    /// keeps one client for its lifetime, so its periodic runs after the first
+    /// are incremental, but every restart starts over with a full recompute.
+    /// It's not written out because the keys in blob and consensus all belong
+    /// to shards.
+    usage_cache: Arc<Mutex<BTreeMap<ShardId, CachedShardUsage>>>,
 }
 
 impl StorageUsageClient {
     /// Creates a new StorageUsageClient.
     pub fn open(client: PersistClient) -> Self {
-        let state_versions = Arc::new(StateVersions::new(
-            client.cfg.clone(),
-            Arc::clone(&client.consensus),
-            Arc::clone(&client.blob),
-            Arc::clone(&client.metrics),
-        ));
+        let state_versions = Arc::new(client.state_versions());
         StorageUsageClient {
             cfg: client.cfg,
             blob: client.blob,
             metrics: client.metrics,
             state_versions,
+            usage_cache: Arc::new(Mutex::new(BTreeMap::new())),
         }
     }
 
     /// Computes [ShardUsageReferenced] for a single shard. Suitable for customer billing.
+    ///
+    /// The usage is only recomputed if the shard's live states changed since
+    /// the last time it was computed by this client. Otherwise, the previous
+    /// result is reused without fetching the states.
     pub async fn shard_usage_referenced(&self, shard_id: ShardId) -> ShardUsageReferenced {
+        let live_seqnos = self.state_versions.fetch_live_seqnos(&shard_id).await;
+        if let Some(live_seqnos) = live_seqnos {
+            let cache = self.usage_cache.lock().expect("mutex poisoned");
+            if let Some(cached) = cache.get(&shard_id) {
+                if cached.live_seqnos == live_seqnos {
+                    self.metrics.audit.shards_reused.inc();
+                    return cached.usage.clone();
+                }
+            }
+        }
+
+        self.metrics.audit.shards_recomputed.inc();
+        let (usage, live_seqnos) = self.compute_shard_usage_referenced(shard_id).await;
+        let mut cache = self.usage_cache.lock().expect("mutex poisoned");
+        match live_seqnos {
+            Some(live_seqnos) => {
+                let cached = CachedShardUsage {
+                    live_seqnos,
+                    usage: usage.clone(),
+                };
+                cache.insert(shard_id, cached);
+            }
+            None => {
+                cache.remove(&shard_id);
+            }
+        }
+        usage
+    }
+
+    /// Computes [ShardUsageReferenced] for a single shard from all of its live
+    /// states, returning it along with the seqnos of the earliest and latest of
+    /// those states (or None if the shard is uninitialized).
+    async fn compute_shard_usage_referenced(
+        &self,
+        shard_id: ShardId,
+    ) -> (ShardUsageReferenced, Option<(SeqNo, SeqNo)>) {
         let mut start = Instant::now();
         let states_iter = self
             .state_versions
@@ -187,15 +400,17 @@ impl StorageUsageClient {
         let states_iter = match states_iter {
             Some(x) => x,
             None => {
-                return ShardUsageReferenced {
+                let referenced = ShardUsageReferenced {
                     batches_bytes: 0,
                     rollup_bytes: 0,
-                }
+                };
+                return (referenced, None);
             }
         };
         let mut states_iter = states_iter
             .check_ts_codec()
             .expect("ts should be a u64 in all prod shards");
+        let earliest_seqno = states_iter.state().seqno;
 
         let shard_metrics = &self.metrics.shards.shard(&shard_id, "unknown");
         shard_metrics
@@ -249,10 +464,15 @@ impl StorageUsageClient {
             .step_math
             .inc_by(now.duration_since(start).as_secs_f64());
 
-        referenced
+        let live_seqnos = (earliest_seqno, states_iter.state().seqno);
+        (referenced, Some(live_seqnos))
     }
 
     /// Computes [ShardUsageReferenced] for a given set of shards. Suitable for customer billing.
+    ///
+    /// See [Self::shard_usage_referenced] for when a shard's usage is reused
+    /// from a previous call. Previous results for shards not in `shard_ids`
+    /// are forgotten.
     pub async fn shards_usage_referenced<I>(&self, shard_ids: I) -> ShardsUsageReferenced
     where
         I: IntoIterator<Item = ShardId>,
@@ -273,10 +493,46 @@ impl StorageUsageClient {
             };
             by_shard_futures.push(shard_usage_fut);
         }
-        let by_shard = by_shard_futures.collect().await;
+        let by_shard: BTreeMap<_, _> = by_shard_futures.collect().await;
+        self.usage_cache
+            .lock()
+            .expect("mutex poisoned")
+            .retain(|shard_id, _| by_shard.contains_key(shard_id));
         ShardsUsageReferenced { by_shard }
     }
 
+    /// Computes a [ShardActivityReport] for each of the given shards, pairing
+    /// its [ShardUsageReferenced] with the operations this process ran against
+    /// it over the trailing [ShardsActivityReport::window_secs].
+    ///
+    /// Together these attribute the cost of a shard beyond the bytes it
+    /// stores: Consensus writes and scans and PubSub fan-out scale with how
+    /// active the shard is. Activity is only tracked while a shard is open in
+    /// this process; shards that aren't report no activity.
+    pub async fn shards_activity_report<I>(&self, shard_ids: I) -> ShardsActivityReport
+    where
+        I: IntoIterator<Item = ShardId>,
+    {
+        let usage = self.shards_usage_referenced(shard_ids).await;
+        let by_shard = usage
+            .by_shard
+            .into_iter()
+            .map(|(shard_id, usage)| {
+                let activity = self
+                    .metrics
+                    .shards
+                    .get(&shard_id)
+                    .map(|shard_metrics| shard_metrics.activity.snapshot())
+                    .unwrap_or_default();
+                (shard_id, ShardActivityReport { usage, activity })
+            })
+            .collect();
+        ShardsActivityReport {
+            window_secs: ShardActivityWindow::window_secs(),
+            by_shard,
+        }
+    }
+
     /// Computes [ShardUsageAudit] for a single shard.
     ///
     /// Performs a full scan of [Blob] and [mz_persist::location::Consensus] to compute a full audit
@@ -728,14 +984,20 @@ impl std::fmt::Display for HumanBytes {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::cfg::PersistParameters;
+    use async_trait::async_trait;
     use bytes::Bytes;
-    use mz_persist::location::{Atomicity, SeqNo};
+    use mz_persist::location::{
+        Atomicity, CaSResult, Consensus, ExternalError, ResultStream, SeqNo, VersionedData,
+        SCAN_ALL,
+    };
     use semver::Version;
     use timely::progress::Antichain;
 
     use crate::internal::paths::{PartialRollupKey, RollupId};
-    use crate::tests::new_test_client;
+    use crate::tests::{new_test_client, test_data};
     use crate::ShardId;
 
     use super::*;
@@ -965,6 +1227,128 @@ mod tests {
         assert_eq!(shard_usage_referenced.batches_bytes, batches_size);
     }
 
+    /// A [Consensus] that counts, per shard, the scans of all of its live
+    /// diffs, which is what [StateVersions] does to compute a shard's usage.
+    #[derive(Debug)]
+    struct CountingConsensus {
+        consensus: Arc<dyn Consensus + Send + Sync>,
+        full_scans: Mutex<BTreeMap<String, usize>>,
+    }
+
+    impl CountingConsensus {
+        fn full_scans(&self, shard_id: &ShardId) -> usize {
+            let full_scans = self.full_scans.lock().expect("mutex poisoned");
+            full_scans.get(&shard_id.to_string()).copied().unwrap_or(0)
+        }
+    }
+
+    #[async_trait]
+    impl Consensus for CountingConsensus {
+        fn list_keys(&self) -> ResultStream<String> {
+            self.consensus.list_keys()
+        }
+
+        async fn head(&self, key: &str) -> Result<Option<VersionedData>, ExternalError> {
+            self.consensus.head(key).await
+        }
+
+        async fn compare_and_set(
+            &self,
+            key: &str,
+            expected: Option<SeqNo>,
+            new: VersionedData,
+        ) -> Result<CaSResult, ExternalError> {
+            self.consensus.compare_and_set(key, expected, new).await
+        }
+
+        async fn scan(
+            &self,
+            key: &str,
+            from: SeqNo,
+            limit: usize,
+        ) -> Result<Vec<VersionedData>, ExternalError> {
+            if limit == SCAN_ALL {
+                let mut full_scans = self.full_scans.lock().expect("mutex poisoned");
+                *full_scans.entry(key.to_owned()).or_default() += 1;
+            }
+            self.consensus.scan(key, from, limit).await
+        }
+
+        async fn truncate(&self, key: &str, seqno: SeqNo) -> Result<usize, ExternalError> {
+            self.consensus.truncate(key, seqno).await
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn usage_referenced_incremental() {
+        let data = test_data(2);
+
+        let mut client = new_test_client().await;
+        // Compaction would change the shards' states out from under the test.
+        client.cfg.compaction_enabled = false;
+        let (shard_one, shard_two) = (ShardId::new(), ShardId::new());
+        let (mut write_one, _read_one) = client
+            .expect_open::<String, String, u64, i64>(shard_one)
+            .await;
+        write_one.expect_append(&data[..1], vec![0], vec![2]).await;
+        let (mut write_two, _read_two) = client
+            .expect_open::<String, String, u64, i64>(shard_two)
+            .await;
+        write_two.expect_append(&data[..1], vec![0], vec![2]).await;
+
+        let consensus = Arc::new(CountingConsensus {
+            consensus: Arc::clone(&client.consensus),
+            full_scans: Mutex::new(BTreeMap::new()),
+        });
+        let mut usage = StorageUsageClient::open(client);
+        usage.state_versions = Arc::new(StateVersions::new(
+            usage.cfg.clone(),
+            Arc::<CountingConsensus>::clone(&consensus),
+            Arc::clone(&usage.blob),
+            Arc::clone(&usage.metrics),
+        ));
+        let audit = &usage.metrics.audit;
+        let shard_three = ShardId::new();
+        let shards = [shard_one, shard_two, shard_three];
+
+        // The first run computes every initialized shard.
+        let first = usage.shards_usage_referenced(shards).await;
+        assert_eq!(consensus.full_scans(&shard_one), 1);
+        assert_eq!(consensus.full_scans(&shard_two), 1);
+        assert_eq!(audit.shards_recomputed.get(), 3);
+        assert_eq!(audit.shards_reused.get(), 0);
+
+        // Nothing changed, so the second run reuses the initialized shards.
+        // The uninitialized one is cheap to check and never cached.
+        let second = usage.shards_usage_referenced(shards).await;
+        assert_eq!(consensus.full_scans(&shard_one), 1);
+        assert_eq!(consensus.full_scans(&shard_two), 1);
+        assert_eq!(audit.shards_recomputed.get(), 4);
+        assert_eq!(audit.shards_reused.get(), 2);
+        for shard_id in &shards {
+            assert_eq!(
+                first.by_shard[shard_id].size_bytes(),
+                second.by_shard[shard_id].size_bytes()
+            );
+        }
+
+        // Writing to one of the shards recomputes only that one.
+        write_two.expect_append(&data[1..], vec![2], vec![3]).await;
+        let third = usage.shards_usage_referenced(shards).await;
+        assert_eq!(consensus.full_scans(&shard_one), 1);
+        assert_eq!(consensus.full_scans(&shard_two), 2);
+        assert_eq!(audit.shards_recomputed.get(), 6);
+        assert_eq!(audit.shards_reused.get(), 3);
+        assert_eq!(
+            third.by_shard[&shard_one].size_bytes(),
+            first.by_shard[&shard_one].size_bytes()
+        );
+        assert!(
+            third.by_shard[&shard_two].batches_bytes > first.by_shard[&shard_two].batches_bytes
+        );
+    }
+
     fn writer_id(x: char) -> WriterId {
         let x = [x, x, x, x].iter().collect::<String>();
         let s = format!("w{x}{x}-{x}-{x}-{x}-{x}{x}{x}");
@@ -1251,4 +1635,91 @@ mod tests {
             3
         );
     }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn activity_report() {
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+        let other_shard_id = ShardId::new();
+        let (mut write, _read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let usage = StorageUsageClient::open(client.clone());
+
+        let report = usage.shards_activity_report([shard_id]).await;
+        let before = report.by_shard[&shard_id].activity.clone();
+        assert_eq!(before.appends, 0);
+
+        const APPENDS: u64 = 5;
+        for ts in 0..APPENDS {
+            let data = [((ts.to_string(), "v".to_owned()), ts, 1)];
+            write.expect_append(&data, vec![ts], vec![ts + 1]).await;
+        }
+
+        let report = usage
+            .shards_activity_report([shard_id, other_shard_id])
+            .await;
+        assert_eq!(
+            report.window_secs,
+            ACTIVITY_BUCKET_SECS * u64::cast_from(ACTIVITY_NUM_BUCKETS)
+        );
+        let shard = &report.by_shard[&shard_id];
+        assert!(shard.usage.batches_bytes > 0);
+        assert_eq!(shard.activity.appends, APPENDS);
+        // Each append is at least one compare_and_set, which is then pushed to
+        // PubSub. Maintenance may have added more of both.
+        assert!(shard.activity.consensus_cas - before.consensus_cas >= APPENDS);
+        assert!(shard.activity.pubsub_pushes - before.pubsub_pushes >= APPENDS);
+        // Computing the usage scanned the shard's diffs.
+        assert!(shard.activity.consensus_scan_bytes > before.consensus_scan_bytes);
+
+        // A shard that's not open in this process reports no activity.
+        let other = &report.by_shard[&other_shard_id];
+        assert_eq!(other.activity, ShardActivity::default());
+    }
+
+    #[mz_ore::test]
+    fn activity_window() {
+        let window = ShardActivityWindow::new();
+        let start = window.start;
+        assert_eq!(window.snapshot_at(start), ShardActivity::default());
+
+        window.record_at(start, ShardOp::Append);
+        window.record_at(start, ShardOp::ConsensusCas);
+        window.record_at(start, ShardOp::ConsensusScan { bytes: 10 });
+        let later = start + Duration::from_secs(ACTIVITY_BUCKET_SECS);
+        window.record_at(later, ShardOp::ConsensusScan { bytes: 5 });
+        window.record_at(later, ShardOp::ConsensusTruncate);
+        window.record_at(later, ShardOp::PubSubPush);
+        let expected = ShardActivity {
+            appends: 1,
+            consensus_cas: 1,
+            consensus_scan_bytes: 15,
+            consensus_truncations: 1,
+            pubsub_pushes: 1,
+        };
+        assert_eq!(window.snapshot_at(later), expected);
+
+        // Counts age out of the window one bucket at a time.
+        let window_end = start + Duration::from_secs(ShardActivityWindow::window_secs());
+        let expected = ShardActivity {
+            appends: 0,
+            consensus_cas: 0,
+            consensus_scan_bytes: 5,
+            consensus_truncations: 1,
+            pubsub_pushes: 1,
+        };
+        assert_eq!(window.snapshot_at(window_end), expected);
+        let after_window = window_end + Duration::from_secs(ACTIVITY_BUCKET_SECS);
+        assert_eq!(window.snapshot_at(after_window), ShardActivity::default());
+
+        // Reusing a bucket discards its stale counts.
+        window.record_at(after_window, ShardOp::Append);
+        let expected = ShardActivity {
+            appends: 1,
+            ..Default::default()
+        };
+        assert_eq!(window.snapshot_at(after_window), expected);
+    }
 }
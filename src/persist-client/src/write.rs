@@ -11,13 +11,17 @@
 
 use std::borrow::Borrow;
 use std::fmt::Debug;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
 use differential_dataflow::trace::Description;
+use mz_ore::cast::CastFrom;
 use mz_ore::task::RuntimeExt;
 use mz_persist::location::Blob;
+use mz_persist_types::columnar::Schema;
 use mz_persist_types::{Codec, Codec64};
 use mz_proto::{IntoRustIfSome, ProtoType};
 use proptest_derive::Arbitrary;
@@ -26,6 +30,7 @@ use serde::{Deserialize, Serialize};
 use timely::progress::{Antichain, Timestamp};
 use timely::PartialOrder;
 use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{debug_span, info, instrument, warn, Instrument};
 use uuid::Uuid;
 
@@ -33,14 +38,24 @@ use crate::batch::{
     validate_truncate_batch, Added, Batch, BatchBuilder, BatchBuilderConfig, BatchBuilderInternal,
     ProtoBatch, BATCH_DELETE_ENABLED,
 };
+use crate::dyn_cfg::Config;
 use crate::error::{InvalidUsage, UpperMismatch};
 use crate::internal::compact::Compactor;
 use crate::internal::encoding::{check_data_version, Schemas};
 use crate::internal::machine::Machine;
 use crate::internal::metrics::Metrics;
 use crate::internal::state::{HandleDebugState, HollowBatch, Upper};
+use crate::internal::watch::StateWatch;
 use crate::read::ReadHandle;
-use crate::{parse_id, GarbageCollector, IsolatedRuntime, PersistConfig, ShardId};
+use crate::{parse_id, GarbageCollector, IsolatedRuntime, PersistConfig, SchemaId, ShardId};
+
+pub(crate) const WRITER_COALESCE_WINDOW_MS: Config<usize> = Config::new(
+    "persist_writer_coalesce_window_ms",
+    0,
+    "How long (in milliseconds) WriteHandle::append_coalesced lingers to merge \
+    contiguous appends into a single compare_and_append, or 0 to disable \
+    coalescing.",
+);
 
 /// An opaque identifier for a writer of a persist durable TVC (aka shard).
 #[derive(Arbitrary, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -123,7 +138,11 @@ where
     pub(crate) schemas: Schemas<K, V>,
 
     pub(crate) upper: Antichain<T>,
+    /// Notifies [Self::refresh_upper] of newer states of the shard, or None if
+    /// this handle opted out of refreshing its cached upper from them.
+    upper_watch: Option<StateWatch<K, V, T, D>>,
     explicitly_expired: bool,
+    coalescer: Option<mpsc::UnboundedSender<CoalesceReq<K, V, T, D>>>,
 }
 
 impl<K, V, T, D> WriteHandle<K, V, T, D>
@@ -158,6 +177,9 @@ where
             hostname: cfg.hostname.to_owned(),
             purpose: purpose.to_owned(),
         };
+        // NB: Subscribe before reading the upper, so that no newer state can
+        // slip in between the two unnoticed.
+        let upper_watch = Some(machine.applier.watch());
         let upper = machine.applier.clone_upper();
         WriteHandle {
             cfg,
@@ -171,7 +193,9 @@ where
             debug_state,
             schemas,
             upper,
+            upper_watch,
             explicitly_expired: false,
+            coalescer: None,
         }
     }
 
@@ -201,10 +225,62 @@ where
     /// potentially more stale than [Self::shared_upper] but is lock-free and
     /// allocation-free. This will always be less or equal to the shard-global
     /// `upper`.
+    ///
+    /// The cache is updated by this handle's own appends and fetches and, see
+    /// [Self::refresh_upper], from states of the shard that this process
+    /// learned about otherwise.
     pub fn upper(&self) -> &Antichain<T> {
         &self.upper
     }
 
+    /// Refreshes the cached [Self::upper] from the newest state of the shard
+    /// known to this process and returns it.
+    ///
+    /// This picks up states delivered by PubSub or fetched by any other handle
+    /// for this shard in this process, so other writers' progress shows up
+    /// without this handle fetching from consensus. It never contacts
+    /// consensus itself and is cheap when nothing has changed.
+    ///
+    /// The freshness guarantee is best-effort: the returned upper is
+    /// monotonically non-decreasing and never past the shard-global `upper`,
+    /// but it may lag it arbitrarily (e.g. while PubSub is disconnected) and
+    /// is not linearized with writes. Use [Self::fetch_recent_upper] when
+    /// that's needed.
+    ///
+    /// This is a no-op if refreshing was disabled with
+    /// [Self::set_refresh_upper].
+    pub fn refresh_upper(&mut self) -> &Antichain<T> {
+        if let Some(watch) = &mut self.upper_watch {
+            if watch.try_advance() {
+                let upper = self.machine.applier.clone_upper();
+                if PartialOrder::less_than(&self.upper, &upper) {
+                    self.upper = upper;
+                }
+            }
+        }
+        &self.upper
+    }
+
+    /// Sets whether [Self::refresh_upper] refreshes the cached upper of this
+    /// handle from newer states of the shard (the default).
+    ///
+    /// Disable this for callers that rely on the cached upper only changing as
+    /// a result of this handle's own operations.
+    pub fn set_refresh_upper(&mut self, enabled: bool) {
+        match (enabled, self.upper_watch.is_some()) {
+            (true, false) => {
+                self.upper_watch = Some(self.machine.applier.watch());
+                // States that arrived while disabled won't be notified.
+                let upper = self.machine.applier.clone_upper();
+                if PartialOrder::less_than(&self.upper, &upper) {
+                    self.upper = upper;
+                }
+            }
+            (false, true) => self.upper_watch = None,
+            _ => {}
+        }
+    }
+
     /// A less-stale cached version of the shard-global `upper` frontier.
     ///
     /// This is the most recently known upper for this shard process-wide, but
@@ -303,7 +379,9 @@ where
     /// This uses a bounded amount of memory, even when `updates` is very large.
     /// Individual records, however, should be small enough that we can
     /// reasonably chunk them up: O(KB) is definitely fine, O(MB) come talk to
-    /// us.
+    /// us. Updates are encoded as `updates` yields them, without being
+    /// collected or cloned first, so passing borrowed updates doesn't copy
+    /// them.
     ///
     /// The clunky multi-level Result is to enable more obvious error handling
     /// in the caller. See <http://sled.rs/errors.html> for details.
@@ -342,6 +420,104 @@ where
         }
     }
 
+    /// Like [Self::compare_and_append], but the compare_and_append may be
+    /// merged with those of other appends made through this handle.
+    ///
+    /// The updates are written to blob before this returns, but the append
+    /// itself lingers for up to `persist_writer_coalesce_window_ms`. Any
+    /// appends made in that window whose bounds are contiguous (each one's
+    /// `expected_upper` is the previous one's `new_upper`) are merged into a
+    /// single compare_and_append covering all of them, which means one write
+    /// to Consensus instead of one per append. The returned future resolves
+    /// once that merged compare_and_append has been applied. If the window is
+    /// 0 (the default), this is equivalent to [Self::compare_and_append].
+    ///
+    /// A merged append succeeds or fails as a unit: if the shard's upper is
+    /// not the `expected_upper` of the first append in the group, every
+    /// append in the group resolves to an [UpperMismatch] with the shard's
+    /// current upper. If the merged append is rejected as invalid, the appends
+    /// in the group are instead applied one at a time, so that each caller
+    /// gets its own outcome.
+    ///
+    /// Coalesced appends don't update the cached [Self::upper] of this handle.
+    /// Callers should await all the returned futures before mixing in other
+    /// appends or expiring this handle.
+    #[instrument(level = "trace", skip_all, fields(shard = %self.machine.shard_id()))]
+    pub async fn append_coalesced<SB, KB, VB, TB, DB, I>(
+        &mut self,
+        updates: I,
+        expected_upper: Antichain<T>,
+        new_upper: Antichain<T>,
+    ) -> Result<
+        impl Future<Output = Result<Result<(), UpperMismatch<T>>, InvalidUsage<T>>>,
+        InvalidUsage<T>,
+    >
+    where
+        SB: Borrow<((KB, VB), TB, DB)>,
+        KB: Borrow<K>,
+        VB: Borrow<V>,
+        TB: Borrow<T>,
+        DB: Borrow<D>,
+        I: IntoIterator<Item = SB>,
+        D: Send + Sync,
+    {
+        self.metrics.coalesce.appends.inc();
+        let (tx, rx) = oneshot::channel();
+        let writer_id = self.writer_id.clone();
+        let result = async move {
+            match rx.await {
+                Ok(res) => res,
+                Err(_) => Err(InvalidUsage::CoalescerExited { writer_id }),
+            }
+        };
+        if WRITER_COALESCE_WINDOW_MS.get(&self.cfg.configs) == 0 {
+            let res = self
+                .compare_and_append(updates, expected_upper, new_upper)
+                .await?;
+            let _ = tx.send(Ok(res));
+            return Ok(result);
+        }
+
+        let batch = self
+            .batch(updates, expected_upper.clone(), new_upper.clone())
+            .await?;
+        if self.coalescer.is_none() {
+            let (coalescer_tx, coalescer_rx) = mpsc::unbounded_channel();
+            let coalescer = Coalescer {
+                cfg: self.cfg.clone(),
+                metrics: Arc::clone(&self.metrics),
+                machine: self.machine.clone(),
+                gc: self.gc.clone(),
+                compact: self.compact.clone(),
+                writer_id: self.writer_id.clone(),
+                debug_state: self.debug_state.clone(),
+            };
+            let _ = mz_ore::task::spawn(
+                || format!("persist::write::coalesce ({})", self.writer_id),
+                coalescer.run(coalescer_rx),
+            );
+            self.coalescer = Some(coalescer_tx);
+        }
+        let coalescer = self.coalescer.as_ref().expect("coalescer was just started");
+        let req = CoalesceReq {
+            batch,
+            lower: expected_upper,
+            upper: new_upper,
+            tx,
+        };
+        if let Err(mpsc::error::SendError(req)) = coalescer.send(req) {
+            // The coalescer task went away, so the append was never applied.
+            // Don't leak the batch, and start a new coalescer for the next
+            // append.
+            self.coalescer = None;
+            req.batch.delete().await;
+            let _ = req.tx.send(Err(InvalidUsage::CoalescerExited {
+                writer_id: self.writer_id.clone(),
+            }));
+        }
+        Ok(result)
+    }
+
     /// Appends the batch of updates to the shard and downgrades this handle's
     /// upper to `upper`.
     ///
@@ -418,6 +594,23 @@ where
         }
     }
 
+    /// Like [Self::append_batch], but uses the batch's own lower and upper as
+    /// the bounds of the append.
+    ///
+    /// This saves callers from having to extract and clone the bounds of the
+    /// batch themselves, and from accidentally passing mismatched ones.
+    pub async fn append_batch_owned(
+        &mut self,
+        batch: Batch<K, V, T, D>,
+    ) -> Result<Result<(), UpperMismatch<T>>, InvalidUsage<T>>
+    where
+        D: Send + Sync,
+    {
+        let lower = batch.lower().clone();
+        let upper = batch.upper().clone();
+        self.append_batch(batch, lower, upper).await
+    }
+
     /// Appends the batch of updates to the shard and downgrades this handle's
     /// upper to `new_upper` iff the current global upper of this shard is
     /// `expected_upper`.
@@ -534,8 +727,12 @@ where
 
     /// Turns the given [`ProtoBatch`] back into a [`Batch`] which can be used
     /// to append it to this shard.
+    ///
+    /// The `ProtoBatch` may have been built by any writer of this shard,
+    /// including one in another process. A batch from a different shard is
+    /// rejected with [InvalidUsage::BatchNotFromThisShard] when it is appended.
     pub fn batch_from_transmittable_batch(&self, batch: ProtoBatch) -> Batch<K, V, T, D> {
-        let ret = Batch {
+        Batch {
             batch_delete_enabled: BATCH_DELETE_ENABLED.get(&self.cfg.configs),
             metrics: Arc::clone(&self.metrics),
             shard_id: batch
@@ -549,9 +746,7 @@ where
                 .expect("valid transmittable batch"),
             blob: Arc::clone(&self.blob),
             _phantom: std::marker::PhantomData,
-        };
-        assert_eq!(ret.shard_id, self.machine.shard_id());
-        ret
+        }
     }
 
     /// Returns a [BatchBuilder] that can be used to write a batch of updates to
@@ -568,7 +763,8 @@ where
     /// O(MB) come talk to us.
     pub fn builder(&mut self, lower: Antichain<T>) -> BatchBuilder<K, V, T, D> {
         let builder = BatchBuilderInternal::new(
-            BatchBuilderConfig::new(&self.cfg, &self.writer_id),
+            BatchBuilderConfig::new(&self.cfg, &self.writer_id)
+                .with_tuning(&self.machine.applier.tuning()),
             Arc::clone(&self.metrics),
             Arc::clone(&self.machine.applier.shard_metrics),
             self.schemas.clone(),
@@ -588,6 +784,56 @@ where
         }
     }
 
+    /// Like [Self::builder], but the batch's values are written with
+    /// `val_schema` instead of the schema this handle was opened with.
+    ///
+    /// The given `schema_id` is recorded with every part of the batch, so that
+    /// a future reader can decode it with a compatible schema. This returns an
+    /// error if `val_schema` is not a compatible evolution of the handle's
+    /// schema.
+    ///
+    /// WIP! This is a prototype for schema evolution: persist doesn't yet keep
+    /// track of the schemas of a shard, so the id is assigned by the caller.
+    pub fn builder_with_schema_override(
+        &mut self,
+        lower: Antichain<T>,
+        schema_id: SchemaId,
+        val_schema: Arc<V::Schema>,
+    ) -> Result<BatchBuilder<K, V, T, D>, InvalidUsage<T>> {
+        if !val_schema
+            .columns()
+            .is_evolution_of(&self.schemas.val.columns())
+        {
+            return Err(InvalidUsage::IncompatibleSchema { schema_id });
+        }
+        let schemas = Schemas {
+            key: Arc::clone(&self.schemas.key),
+            val: val_schema,
+        };
+        let mut cfg = BatchBuilderConfig::new(&self.cfg, &self.writer_id)
+            .with_tuning(&self.machine.applier.tuning());
+        cfg.schema_id = Some(schema_id);
+        let builder = BatchBuilderInternal::new(
+            cfg,
+            Arc::clone(&self.metrics),
+            Arc::clone(&self.machine.applier.shard_metrics),
+            schemas.clone(),
+            self.metrics.user.clone(),
+            lower,
+            Arc::clone(&self.blob),
+            Arc::clone(&self.isolated_runtime),
+            self.machine.shard_id().clone(),
+            self.cfg.build_version.clone(),
+            Antichain::from_elem(T::minimum()),
+            None,
+            false,
+        );
+        Ok(BatchBuilder {
+            builder,
+            stats_schemas: schemas,
+        })
+    }
+
     /// Uploads the given `updates` as one `Batch` to the blob store and returns
     /// a handle to the batch.
     #[instrument(level = "trace", skip_all, fields(shard = %self.machine.shard_id()))]
@@ -641,7 +887,13 @@ where
     /// still preferred because the Drop one is best effort and is dependant on
     /// a tokio [Handle] being available in the TLC at the time of drop (which
     /// is a bit subtle). Also, explicit expiry allows for control over when it
-    /// happens.
+    /// happens. The Drop impl spawns a detached task rather than waiting on
+    /// Consensus; if the process exits before that task completes, the writer
+    /// is cleaned up by lease expiry as normal. Callers that need the writer
+    /// to be expired before moving on should call this instead.
+    ///
+    /// Expiry requires a write to Consensus, and so this may take arbitrarily
+    /// long if Consensus is slow. See [Self::expire_with_timeout] to bound it.
     #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
     pub async fn expire(mut self) {
         let (_, maintenance) = self.machine.expire_writer(&self.writer_id).await;
@@ -649,6 +901,31 @@ where
         self.explicitly_expired = true;
     }
 
+    /// Like [Self::expire], but gives up after `timeout`.
+    ///
+    /// Returns true if the writer was expired within the timeout. Otherwise,
+    /// the writer is left to be cleaned up when its lease expires, exactly as
+    /// if this process had crashed, and no expiry is attempted on drop.
+    #[instrument(level = "debug", skip_all, fields(shard = %self.machine.shard_id()))]
+    pub async fn expire_with_timeout(mut self, timeout: Duration) -> bool {
+        // Whether or not we finish in time, don't try again in Drop.
+        self.explicitly_expired = true;
+        let expire = self.machine.expire_writer(&self.writer_id);
+        match tokio::time::timeout(timeout, expire).await {
+            Ok((_, maintenance)) => {
+                maintenance.start_performing(&self.machine, &self.gc);
+                true
+            }
+            Err(_) => {
+                warn!(
+                    "WriteHandle {} timed out after {:?} expiring, falling back to lease timeout",
+                    self.writer_id, timeout
+                );
+                false
+            }
+        }
+    }
+
     /// Test helper for an [Self::append] call that is expected to succeed.
     #[cfg(test)]
     #[track_caller]
@@ -725,6 +1002,165 @@ where
     }
 }
 
+/// An append waiting to be merged by a [Coalescer].
+struct CoalesceReq<K, V, T, D>
+where
+    T: Timestamp + Lattice + Codec64,
+{
+    batch: Batch<K, V, T, D>,
+    lower: Antichain<T>,
+    upper: Antichain<T>,
+    tx: oneshot::Sender<Result<Result<(), UpperMismatch<T>>, InvalidUsage<T>>>,
+}
+
+/// The task backing [WriteHandle::append_coalesced], which merges contiguous
+/// appends made within the linger window into a single compare_and_append.
+struct Coalescer<K, V, T, D> {
+    cfg: PersistConfig,
+    metrics: Arc<Metrics>,
+    machine: Machine<K, V, T, D>,
+    gc: GarbageCollector<K, V, T, D>,
+    compact: Option<Compactor<K, V, T, D>>,
+    writer_id: WriterId,
+    debug_state: HandleDebugState,
+}
+
+impl<K, V, T, D> Coalescer<K, V, T, D>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64 + Send + Sync,
+{
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<CoalesceReq<K, V, T, D>>) {
+        let mut next = None;
+        loop {
+            let first = match next.take() {
+                Some(req) => req,
+                None => match rx.recv().await {
+                    Some(req) => req,
+                    // The WriteHandle has been dropped and everything it sent
+                    // us has been flushed.
+                    None => return,
+                },
+            };
+            let window = Duration::from_millis(u64::cast_from(
+                WRITER_COALESCE_WINDOW_MS.get(&self.cfg.configs),
+            ));
+            let deadline = tokio::time::Instant::now() + window;
+            let mut group = vec![first];
+            loop {
+                match tokio::time::timeout_at(deadline, rx.recv()).await {
+                    Ok(Some(req)) if group.last().map_or(false, |x| x.upper == req.lower) => {
+                        group.push(req)
+                    }
+                    // Not contiguous with the current group, so it starts the
+                    // next one.
+                    Ok(Some(req)) => {
+                        next = Some(req);
+                        break;
+                    }
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            self.flush(group).await;
+        }
+    }
+
+    async fn flush(&mut self, group: Vec<CoalesceReq<K, V, T, D>>) {
+        if let Err(group) = self.try_flush(group).await {
+            for req in group {
+                let res = self.try_flush(vec![req]).await;
+                assert!(res.is_ok(), "single appends are never handed back");
+            }
+        }
+    }
+
+    /// Applies `group` as a single compare_and_append and reports the outcome
+    /// to each of its appends, except that a group of more than one append
+    /// that is rejected as invalid is handed back unapplied.
+    async fn try_flush(
+        &mut self,
+        group: Vec<CoalesceReq<K, V, T, D>>,
+    ) -> Result<(), Vec<CoalesceReq<K, V, T, D>>> {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            return Ok(());
+        };
+        let since = Antichain::from_elem(T::minimum());
+        let desc = Description::new(first.lower.clone(), last.upper.clone(), since);
+
+        let (mut parts, mut num_updates, mut runs) = (vec![], 0, vec![]);
+        for req in group.iter() {
+            for run in req.batch.batch.runs() {
+                // Mark the boundary if this is not the first run in the batch.
+                let start_index = parts.len();
+                if start_index != 0 {
+                    runs.push(start_index);
+                }
+                parts.extend_from_slice(run);
+            }
+            num_updates += req.batch.batch.len;
+        }
+        self.metrics.coalesce.flushes.inc();
+        self.metrics
+            .coalesce
+            .coalesced
+            .inc_by(u64::cast_from(group.len() - 1));
+
+        let heartbeat_timestamp = (self.cfg.now)();
+        let res = self
+            .machine
+            .compare_and_append(
+                &HollowBatch {
+                    desc,
+                    parts,
+                    len: num_updates,
+                    runs,
+                },
+                &self.writer_id,
+                &self.debug_state,
+                heartbeat_timestamp,
+            )
+            .await;
+
+        match res {
+            Ok(Ok((_seqno, maintenance))) => {
+                for mut req in group {
+                    req.batch.mark_consumed();
+                    let _ = req.tx.send(Ok(Ok(())));
+                }
+                maintenance.start_performing(&self.machine, &self.gc, self.compact.as_ref());
+            }
+            // Each batch was validated against its own bounds when it was
+            // written and the bounds are contiguous, so the merged batch should
+            // be valid too. If it isn't, apply the appends one at a time, so
+            // that each caller gets its own outcome.
+            Ok(Err(invalid_usage)) if group.len() > 1 => {
+                warn!(
+                    "invalid usage in merged coalesced append, applying individually: {:?}",
+                    invalid_usage
+                );
+                return Err(group);
+            }
+            Ok(Err(invalid_usage)) => {
+                let req = group.into_iter().next().expect("group is not empty");
+                req.batch.delete().await;
+                let _ = req.tx.send(Err(invalid_usage));
+            }
+            Err(Upper(current)) => {
+                for req in group {
+                    req.batch.delete().await;
+                    let _ = req.tx.send(Ok(Err(UpperMismatch {
+                        current: current.clone(),
+                        expected: req.lower,
+                    })));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<K, V, T, D> Drop for WriteHandle<K, V, T, D>
 where
     T: Timestamp + Lattice + Codec64,
@@ -746,9 +1182,10 @@ where
         let mut machine = self.machine.clone();
         let gc = self.gc.clone();
         let writer_id = self.writer_id.clone();
-        // Spawn a best-effort task to expire this write handle. It's fine if
-        // this doesn't run to completion, we'd just have to wait out the lease
-        // before the shard-global since is unblocked.
+        // Spawn a detached, best-effort task to expire this write handle so
+        // that Drop never waits on Consensus. It's fine if this doesn't run to
+        // completion (e.g. the process exits first), we'd just have to wait
+        // out the lease before the writer is cleaned up.
         //
         // Intentionally create the span outside the task to set the parent.
         let expire_span = debug_span!("drop::expire");
@@ -769,14 +1206,28 @@ mod tests {
     use std::sync::mpsc;
 
     use crate::cache::PersistClientCache;
+    use bytes::BufMut;
     use differential_dataflow::consolidation::consolidate_updates;
     use futures_util::FutureExt;
     use mz_ore::collections::CollectionExt;
+    use mz_ore::metrics::MetricsRegistry;
     use mz_ore::task;
+    use mz_persist::unreliable::{Op, UnreliableHandle};
+    use mz_persist_types::codec_impls::{SimpleDecoder, SimpleEncoder, SimpleSchema};
+    use mz_persist_types::columnar::{ColumnFormat, ColumnPush, DataType};
+    use mz_persist_types::dyn_struct::{ColumnsMut, ColumnsRef, DynStructCfg};
+    use mz_persist_types::stats::StatsFn;
+    use prost::Message;
     use serde_json::json;
 
-    use crate::tests::{all_ok, new_test_client};
-    use crate::{PersistLocation, ShardId};
+    use crate::async_runtime::IsolatedRuntime;
+    use crate::cache::StateCache;
+    use crate::internal::compact::{CompactConfig, CompactReq};
+    use crate::rpc::{
+        subscribe_state_cache_to_pubsub, PersistGrpcPubSubServer, PubSubClientConnection,
+    };
+    use crate::tests::{all_ok, new_fault_injected_test_client, new_test_client, test_data};
+    use crate::{PersistClient, PersistLocation, ShardId};
 
     use super::*;
 
@@ -933,6 +1384,173 @@ mod tests {
         assert_eq!(actual, all_ok(&expected, 3));
     }
 
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn append_batch_owned() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("4".to_owned(), "four".to_owned()), 4, 1),
+        ];
+
+        let (mut write, mut read) = new_test_client()
+            .await
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+
+        // The batch's own bounds are used for the append.
+        let batch = write.expect_batch(&data[..2], 0, 3).await;
+        write
+            .append_batch_owned(batch)
+            .await
+            .expect("invalid usage")
+            .expect("unexpected upper");
+        assert_eq!(write.upper(), &Antichain::from_elem(3));
+        assert_eq!(
+            read.expect_snapshot_and_fetch(2).await,
+            all_ok(&data[..2], 2)
+        );
+
+        // A batch that doesn't line up with the shard's upper is rejected.
+        let batch = write.expect_batch(&data[2..], 4, 5).await;
+        let res = write
+            .append_batch_owned(batch)
+            .await
+            .expect("invalid usage");
+        assert_eq!(
+            res,
+            Err(UpperMismatch {
+                expected: Antichain::from_elem(4),
+                current: Antichain::from_elem(3),
+            })
+        );
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn transmittable_batch_across_clients() {
+        let data = test_data(2);
+
+        // The batch is built with one client and appended with another, which
+        // shares nothing but the location, as if they were in different
+        // processes.
+        let client = new_test_client().await;
+        let other_client = PersistClient::new(
+            client.cfg.clone(),
+            Arc::clone(&client.blob),
+            Arc::clone(&client.consensus),
+            Arc::clone(&client.metrics),
+            Arc::new(IsolatedRuntime::new()),
+            Arc::new(StateCache::new_no_metrics()),
+            PubSubClientConnection::noop().sender,
+        )
+        .expect("client construction failed");
+
+        let shard_id = ShardId::new();
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let (mut other_write, _) = other_client
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+
+        let batch = write.expect_batch(&data, 0, 3).await;
+        let batch = batch.into_transmittable_batch().encode_to_vec();
+        let batch = ProtoBatch::decode(batch.as_slice()).expect("valid encoded batch");
+        let mut batch = other_write.batch_from_transmittable_batch(batch);
+        other_write
+            .expect_compare_and_append_batch(&mut [&mut batch], 0, 3)
+            .await;
+        assert_eq!(read.expect_snapshot_and_fetch(2).await, all_ok(&data, 2));
+
+        // A batch from another shard is rejected.
+        let (mut wrong_write, _) = other_client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+        let batch = write.expect_batch(&data, 3, 4).await;
+        let mut batch =
+            wrong_write.batch_from_transmittable_batch(batch.into_transmittable_batch());
+        let res = wrong_write
+            .compare_and_append_batch(
+                &mut [&mut batch],
+                Antichain::from_elem(3),
+                Antichain::from_elem(4),
+            )
+            .await;
+        assert_eq!(
+            res.map(|_| ()),
+            Err(InvalidUsage::BatchNotFromThisShard {
+                batch_shard: shard_id,
+                handle_shard: wrong_write.shard_id(),
+            })
+        );
+        batch.delete().await;
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn refresh_upper_from_pubsub() {
+        let client = new_test_client().await;
+        let server = PersistGrpcPubSubServer::new(&client.cfg, &MetricsRegistry::new());
+        // Two clients that share nothing but the location and an in-process
+        // pubsub server.
+        let new_client = || {
+            let connection = server.new_same_process_connection();
+            let state_cache = Arc::new(StateCache::new(
+                &client.cfg,
+                Arc::clone(&client.metrics),
+                Arc::clone(&connection.sender),
+            ));
+            let _receiver_task =
+                subscribe_state_cache_to_pubsub(Arc::clone(&state_cache), connection.receiver);
+            PersistClient::new(
+                client.cfg.clone(),
+                Arc::clone(&client.blob),
+                Arc::clone(&client.consensus),
+                Arc::clone(&client.metrics),
+                Arc::new(IsolatedRuntime::new()),
+                state_cache,
+                connection.sender,
+            )
+            .expect("client construction failed")
+        };
+        let (client_a, client_b) = (new_client(), new_client());
+
+        let shard_id = ShardId::new();
+        let (mut write_a, _) = client_a
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let (mut write_b, _) = client_b
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        let (mut write_b_legacy, _) = client_b
+            .expect_open::<String, String, u64, i64>(shard_id)
+            .await;
+        write_b_legacy.set_refresh_upper(false);
+
+        // Nothing changed yet, so refreshing is a no-op.
+        assert_eq!(write_b.refresh_upper(), &Antichain::from_elem(0));
+
+        // Writer A's append reaches writer B's state via pubsub, and B picks
+        // it up without fetching from consensus.
+        write_a.expect_compare_and_append(&[], 0, 5).await;
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while write_b.refresh_upper() != &Antichain::from_elem(5) {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("pubsub delivered the new upper");
+        assert_eq!(write_b.upper(), &Antichain::from_elem(5));
+
+        // A handle that opted out keeps its cached upper until it learns of a
+        // newer one through its own operations, and catches up when it opts
+        // back in.
+        assert_eq!(write_b_legacy.refresh_upper(), &Antichain::from_elem(0));
+        write_b_legacy.set_refresh_upper(true);
+        assert_eq!(write_b_legacy.upper(), &Antichain::from_elem(5));
+    }
+
     #[mz_ore::test(tokio::test)]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn wait_for_upper_past() {
@@ -967,6 +1585,173 @@ mod tests {
         assert_eq!(write.upper(), &Antichain::from_elem(7));
     }
 
+    async fn num_writers(client: &PersistClient, shard_id: &ShardId) -> usize {
+        let state = client
+            .inspect_shard::<u64>(shard_id)
+            .await
+            .expect("shard exists");
+        let state = serde_json::to_value(state).expect("state is serializable");
+        state["writers"]
+            .as_object()
+            .expect("writers is a map")
+            .len()
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn expire_with_timeout() {
+        let faults = UnreliableHandle::new(0, 1.0, 0.0);
+        let client = new_fault_injected_test_client(&faults);
+        let shard_id = ShardId::new();
+
+        let (mut write, _) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+        write
+            .expect_compare_and_append(&[(((), ()), 0, 1)], 0, 1)
+            .await;
+        assert_eq!(num_writers(&client, &shard_id).await, 1);
+        assert!(write.expire_with_timeout(Duration::from_secs(60)).await);
+        assert_eq!(num_writers(&client, &shard_id).await, 0);
+
+        // If Consensus is too slow, we give up and leave the writer registered
+        // for lease expiry to clean up.
+        let (mut write, _) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+        write
+            .expect_compare_and_append(&[(((), ()), 1, 1)], 1, 2)
+            .await;
+        faults.set_op_latency(Op::ConsensusCompareAndSet, Duration::from_secs(60 * 60));
+        assert!(!write.expire_with_timeout(Duration::from_millis(10)).await);
+        faults.clear_op_faults();
+        assert_eq!(num_writers(&client, &shard_id).await, 1);
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn expire_on_drop() {
+        let client = new_test_client().await;
+        let shard_id = ShardId::new();
+
+        // Drop expires the writer in a detached task.
+        let (mut write, _) = client.expect_open::<(), (), u64, i64>(shard_id).await;
+        write
+            .expect_compare_and_append(&[(((), ()), 0, 1)], 0, 1)
+            .await;
+        drop(write);
+        while num_writers(&client, &shard_id).await > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn append_coalesced() {
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 0, 1),
+            (("2".to_owned(), "two".to_owned()), 1, 1),
+            (("3".to_owned(), "three".to_owned()), 2, 1),
+            (("4".to_owned(), "four".to_owned()), 3, 1),
+            (("5".to_owned(), "five".to_owned()), 4, 1),
+        ];
+
+        let client = new_test_client().await;
+        client.cfg.set_config(&WRITER_COALESCE_WINDOW_MS, 1000);
+        let (mut write, mut read) = client
+            .expect_open::<String, String, u64, i64>(ShardId::new())
+            .await;
+
+        // Five rapid, contiguous appends all land with a single write to
+        // consensus.
+        let seqno_before = write.machine.seqno();
+        let mut appends = Vec::new();
+        for update in data.iter() {
+            let append = write
+                .append_coalesced(
+                    std::slice::from_ref(update),
+                    Antichain::from_elem(update.1),
+                    Antichain::from_elem(update.1 + 1),
+                )
+                .await
+                .expect("invalid usage");
+            appends.push(append);
+        }
+        for append in appends {
+            append
+                .await
+                .expect("invalid usage")
+                .expect("upper mismatch");
+        }
+        assert_eq!(write.machine.seqno(), seqno_before.next());
+        assert_eq!(client.metrics.coalesce.flushes.get(), 1);
+        assert_eq!(client.metrics.coalesce.coalesced.get(), 4);
+        assert_eq!(read.expect_snapshot_and_fetch(4).await, all_ok(&data, 4));
+
+        // If the merged append hits an upper mismatch, every caller sees it.
+        let later = vec![
+            (("6".to_owned(), "six".to_owned()), 10, 1),
+            (("7".to_owned(), "seven".to_owned()), 11, 1),
+        ];
+        let first = write
+            .append_coalesced(
+                &later[..1],
+                Antichain::from_elem(10),
+                Antichain::from_elem(11),
+            )
+            .await
+            .expect("invalid usage");
+        let second = write
+            .append_coalesced(
+                &later[1..],
+                Antichain::from_elem(11),
+                Antichain::from_elem(12),
+            )
+            .await
+            .expect("invalid usage");
+        assert_eq!(
+            first.await,
+            Ok(Err(UpperMismatch {
+                current: Antichain::from_elem(5),
+                expected: Antichain::from_elem(10),
+            }))
+        );
+        assert_eq!(
+            second.await,
+            Ok(Err(UpperMismatch {
+                current: Antichain::from_elem(5),
+                expected: Antichain::from_elem(11),
+            }))
+        );
+        assert_eq!(client.metrics.coalesce.flushes.get(), 2);
+
+        // If the coalescer went away, the append fails instead of panicking,
+        // and the next one starts a new coalescer.
+        let (coalescer_tx, coalescer_rx) = tokio::sync::mpsc::unbounded_channel();
+        drop(coalescer_rx);
+        write.coalescer = Some(coalescer_tx);
+        let append = write
+            .append_coalesced(
+                &later[..1],
+                Antichain::from_elem(5),
+                Antichain::from_elem(11),
+            )
+            .await
+            .expect("invalid usage");
+        assert_eq!(
+            append.await,
+            Err(InvalidUsage::CoalescerExited {
+                writer_id: write.writer_id.clone(),
+            })
+        );
+        assert!(write.coalescer.is_none());
+        let append = write
+            .append_coalesced(
+                &later[..1],
+                Antichain::from_elem(5),
+                Antichain::from_elem(11),
+            )
+            .await
+            .expect("invalid usage");
+        assert_eq!(append.await, Ok(Ok(())));
+    }
+
     #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
     #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
     async fn fetch_recent_upper_linearized() {
@@ -1019,4 +1804,156 @@ mod tests {
 
         task.await.expect("await failed");
     }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct Evolving(String);
+
+    impl Codec for Evolving {
+        type Schema = EvolvingSchema;
+
+        fn codec_name() -> String {
+            "Evolving".into()
+        }
+
+        fn encode<B: BufMut>(&self, buf: &mut B) {
+            buf.put(self.0.as_bytes())
+        }
+
+        fn decode<'a>(buf: &'a [u8]) -> Result<Self, String> {
+            let val = String::from_utf8(buf.to_owned()).map_err(|err| err.to_string())?;
+            Ok(Evolving(val))
+        }
+    }
+
+    /// A schema for [Evolving] that can declare an additional (unused) column.
+    #[derive(Debug, Default)]
+    struct EvolvingSchema {
+        extra: Option<DataType>,
+    }
+
+    impl Schema<Evolving> for EvolvingSchema {
+        type Encoder<'a> = SimpleEncoder<'a, Evolving, String>;
+
+        type Decoder<'a> = SimpleDecoder<'a, Evolving, String>;
+
+        fn columns(&self) -> DynStructCfg {
+            let typ = DataType {
+                optional: false,
+                format: ColumnFormat::String,
+            };
+            let mut cols = vec![("".to_owned(), typ, StatsFn::Default)];
+            if let Some(extra) = self.extra.as_ref() {
+                cols.push(("extra".to_owned(), extra.clone(), StatsFn::Default));
+            }
+            DynStructCfg::from(cols)
+        }
+
+        fn decoder<'a>(&self, cols: ColumnsRef<'a>) -> Result<Self::Decoder<'a>, String> {
+            SimpleSchema::<Evolving, String>::decoder(cols, |val, ret| {
+                ret.0.clear();
+                ret.0.push_str(val);
+            })
+        }
+
+        fn encoder<'a>(&self, cols: ColumnsMut<'a>) -> Result<Self::Encoder<'a>, String> {
+            SimpleSchema::<Evolving, String>::push_encoder(cols, |col, val| {
+                ColumnPush::<String>::push(col, &val.0)
+            })
+        }
+    }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn builder_with_schema_override() {
+        let data = vec![
+            (("1".to_owned(), Evolving("one".to_owned())), 1, 1),
+            (("2".to_owned(), Evolving("two".to_owned())), 2, 1),
+        ];
+
+        let client = new_test_client().await;
+        let (mut write, mut read) = client
+            .expect_open::<String, Evolving, u64, i64>(ShardId::new())
+            .await;
+
+        // A schema that adds a required column is rejected.
+        let required = EvolvingSchema {
+            extra: Some(DataType {
+                optional: false,
+                format: ColumnFormat::U64,
+            }),
+        };
+        let res = write.builder_with_schema_override(
+            Antichain::from_elem(0),
+            SchemaId(1),
+            Arc::new(required),
+        );
+        assert_eq!(
+            res.err(),
+            Some(InvalidUsage::IncompatibleSchema {
+                schema_id: SchemaId(1)
+            })
+        );
+
+        // A compatible schema is recorded with every part of the batch.
+        let mut builder = write
+            .builder_with_schema_override(
+                Antichain::from_elem(0),
+                SchemaId(2),
+                Arc::new(EvolvingSchema::default()),
+            )
+            .expect("compatible schema");
+        for ((k, v), t, d) in data.iter() {
+            builder.add(k, v, t, d).await.expect("invalid usage");
+        }
+        let mut batch = builder
+            .finish(Antichain::from_elem(3))
+            .await
+            .expect("invalid usage");
+        assert!(!batch.batch.parts.is_empty());
+        for part in batch.batch.parts.iter() {
+            assert_eq!(part.schema_id, Some(SchemaId(2)));
+        }
+
+        // Compacting the batch carries the schema over to its outputs.
+        let req = CompactReq {
+            shard_id: write.machine.shard_id(),
+            desc: Description::new(
+                Antichain::from_elem(0),
+                Antichain::from_elem(3),
+                Antichain::from_elem(2),
+            ),
+            inputs: vec![batch.batch.clone()],
+        };
+        let res = Compactor::<String, Evolving, u64, i64>::compact(
+            CompactConfig::new(&write.cfg, &write.writer_id),
+            Arc::clone(&write.blob),
+            Arc::clone(&write.metrics),
+            write.metrics.shards.shard(&write.machine.shard_id(), ""),
+            Arc::new(IsolatedRuntime::new()),
+            req,
+            write.schemas.clone(),
+            None,
+        )
+        .await
+        .expect("compaction failed");
+        assert!(!res.output.parts.is_empty());
+        for part in res.output.parts.iter() {
+            assert_eq!(part.schema_id, Some(SchemaId(2)));
+        }
+
+        // The batch round-trips through state and can be read back.
+        write
+            .expect_compare_and_append_batch(&mut [&mut batch], 0, 3)
+            .await;
+        assert_eq!(read.expect_snapshot_and_fetch(2).await, all_ok(&data, 2));
+
+        // Batches from the regular builder don't record a schema.
+        let mut batch = write.expect_batch(&data[..1], 3, 4).await;
+        for part in batch.batch.parts.iter() {
+            assert_eq!(part.schema_id, None);
+        }
+        write
+            .expect_compare_and_append_batch(&mut [&mut batch], 3, 4)
+            .await;
+    }
 }
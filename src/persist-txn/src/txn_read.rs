@@ -20,6 +20,8 @@ use differential_dataflow::lattice::Lattice;
 use futures::Stream;
 use mz_ore::task::AbortOnDropHandle;
 use mz_persist_client::critical::SinceHandle;
+use mz_persist_client::error::SnapshotError;
+use mz_persist_client::fetch::DecodeResult;
 use mz_persist_client::read::{Cursor, LazyPartStats, ListenEvent, ReadHandle, Since, Subscribe};
 use mz_persist_client::stats::SnapshotStats;
 use mz_persist_client::write::WriteHandle;
@@ -141,7 +143,7 @@ impl<T: Timestamp + Lattice + TotalOrder + Codec64> DataSnapshot<T> {
     pub async fn snapshot_and_fetch<K, V, D>(
         &self,
         data_read: &mut ReadHandle<K, V, T, D>,
-    ) -> Result<Vec<((Result<K, String>, Result<V, String>), T, D)>, Since<T>>
+    ) -> Result<Vec<((DecodeResult<K>, DecodeResult<V>), T, D)>, SnapshotError<T>>
     where
         K: Debug + Codec + Ord,
         V: Debug + Codec + Ord,
@@ -176,7 +178,7 @@ impl<T: Timestamp + Lattice + TotalOrder + Codec64> DataSnapshot<T> {
     pub async fn snapshot_and_stream<K, V, D>(
         &self,
         data_read: &mut ReadHandle<K, V, T, D>,
-    ) -> Result<impl Stream<Item = ((Result<K, String>, Result<V, String>), T, D)>, Since<T>>
+    ) -> Result<impl Stream<Item = ((DecodeResult<K>, DecodeResult<V>), T, D)>, Since<T>>
     where
         K: Debug + Codec + Ord,
         V: Debug + Codec + Ord,
@@ -291,6 +291,52 @@ impl Opaque for i64 {
     }
 }
 
+/// Encodes a slice of [Codec64] values, e.g. the elements of a frontier, into
+/// `buf`.
+///
+/// The encoding is the number of values as a little-endian u64, followed by
+/// each value's [Codec64::encode]. It round-trips through
+/// [decode_codec64_slice], including for an empty slice.
+pub fn encode_codec64_slice<T: Codec64, B: BufMut>(vals: &[T], buf: &mut B) {
+    let len = u64::try_from(vals.len()).expect("slice length fits in u64");
+    buf.put_u64_le(len);
+    for val in vals {
+        buf.put_slice(&val.encode());
+    }
+}
+
+/// Decodes a slice of [Codec64] values previously encoded with
+/// [encode_codec64_slice].
+///
+/// Returns an error if `buf` is not exactly one such encoding.
+pub fn decode_codec64_slice<T: Codec64>(buf: &[u8]) -> Result<Vec<T>, String> {
+    if buf.len() < 8 {
+        return Err(format!(
+            "expected at least 8 bytes for the length prefix, got {}",
+            buf.len()
+        ));
+    }
+    let (len, vals) = buf.split_at(8);
+    let len = u64::from_le_bytes(len.try_into().expect("prefix is 8 bytes"));
+    let expected_bytes = usize::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_mul(8))
+        .ok_or_else(|| format!("invalid length prefix {}", len))?;
+    if vals.len() != expected_bytes {
+        return Err(format!(
+            "expected {} bytes for {} values, got {}",
+            expected_bytes,
+            len,
+            vals.len()
+        ));
+    }
+    let vals = vals
+        .chunks_exact(8)
+        .map(|val| T::decode(val.try_into().expect("chunk is 8 bytes")))
+        .collect();
+    Ok(vals)
+}
+
 impl Data for bool {
     type Cfg = ();
     type Ref<'a> = bool;
@@ -784,3 +830,51 @@ impl<T: Debug + Send + Sync> Schema<T> for TodoSchema<T> {
         panic!("TODO")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn round_trip<T: Codec64 + Debug + PartialEq>(vals: &[T]) {
+        let mut buf = Vec::new();
+        encode_codec64_slice(vals, &mut buf);
+        assert_eq!(buf.len(), 8 + 8 * vals.len());
+        assert_eq!(decode_codec64_slice::<T>(&buf).as_deref(), Ok(vals));
+    }
+
+    #[mz_ore::test]
+    fn codec64_slice_round_trip() {
+        // The empty slice, e.g. the empty antichain, is still length-prefixed.
+        round_trip::<u64>(&[]);
+        round_trip(&[0u64]);
+        round_trip(&[u64::MAX, 0, 7]);
+        round_trip(&[i64::MIN, -1, 0, i64::MAX]);
+    }
+
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn codec64_slice_round_trip_proptest() {
+        proptest!(|(vals in any::<Vec<u64>>())| {
+            round_trip(&vals);
+        });
+    }
+
+    #[mz_ore::test]
+    fn codec64_slice_invalid() {
+        let mut buf = Vec::new();
+        encode_codec64_slice(&[1u64, 2], &mut buf);
+
+        // Too short for the length prefix.
+        assert!(decode_codec64_slice::<u64>(&buf[..7]).is_err());
+        // Truncated and trailing values.
+        assert!(decode_codec64_slice::<u64>(&buf[..buf.len() - 1]).is_err());
+        buf.push(0);
+        assert!(decode_codec64_slice::<u64>(&buf).is_err());
+        // A length prefix that doesn't fit in memory.
+        let mut buf = Vec::new();
+        buf.put_u64_le(u64::MAX);
+        assert!(decode_codec64_slice::<u64>(&buf).is_err());
+    }
+}
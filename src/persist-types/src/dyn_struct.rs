@@ -19,7 +19,7 @@ use arrow2::datatypes::{DataType as ArrowLogicalType, Field};
 use arrow2::io::parquet::write::Encoding;
 
 use crate::columnar::sealed::{ColumnMut, ColumnRef};
-use crate::columnar::{ColumnGet, ColumnPush, Data, DataType};
+use crate::columnar::{ColumnFormat, ColumnGet, ColumnPush, Data, DataType};
 use crate::dyn_col::{DynColumnMut, DynColumnRef};
 use crate::stats::{OptionStats, StatsFn, StructStats};
 
@@ -45,6 +45,49 @@ impl From<Vec<(String, DataType, StatsFn)>> for DynStructCfg {
     }
 }
 
+impl DynStructCfg {
+    /// Returns whether this is a compatible evolution of the `old` schema.
+    ///
+    /// This is the case if every column in `old` is present (by name) with the
+    /// same type, and any columns that are new in this schema are optional, so
+    /// that data written with either schema can be read with the other.
+    pub fn is_evolution_of(&self, old: &DynStructCfg) -> bool {
+        let old_cols = old
+            .cols
+            .iter()
+            .map(|(name, typ, _)| (name.as_str(), typ))
+            .collect::<BTreeMap<_, _>>();
+        let mut matched = 0;
+        for (name, typ, _) in self.cols.iter() {
+            match old_cols.get(name.as_str()) {
+                Some(old_typ) => {
+                    if !data_type_is_evolution_of(typ, old_typ) {
+                        return false;
+                    }
+                    matched += 1;
+                }
+                None => {
+                    if !typ.optional {
+                        return false;
+                    }
+                }
+            }
+        }
+        // Dropping a column isn't (yet) a compatible change.
+        matched == old_cols.len()
+    }
+}
+
+fn data_type_is_evolution_of(new: &DataType, old: &DataType) -> bool {
+    if new.optional != old.optional {
+        return false;
+    }
+    match (&new.format, &old.format) {
+        (ColumnFormat::Struct(new), ColumnFormat::Struct(old)) => new.is_evolution_of(old),
+        (new, old) => std::mem::discriminant(new) == std::mem::discriminant(old),
+    }
+}
+
 /// A "dynamic" columnar struct.
 ///
 /// See [DynStructCfg].
@@ -575,3 +618,66 @@ impl<'a, V> ColumnsMut<'a, V> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[mz_ore::test]
+    fn is_evolution_of() {
+        fn col(name: &str, optional: bool, format: ColumnFormat) -> (String, DataType, StatsFn) {
+            (
+                name.to_owned(),
+                DataType { optional, format },
+                StatsFn::Default,
+            )
+        }
+        let old = DynStructCfg::from(vec![
+            col("a", false, ColumnFormat::I64),
+            col("b", true, ColumnFormat::String),
+        ]);
+
+        // The same schema, possibly with reordered columns.
+        assert!(old.is_evolution_of(&old));
+        let reordered = DynStructCfg::from(vec![
+            col("b", true, ColumnFormat::String),
+            col("a", false, ColumnFormat::I64),
+        ]);
+        assert!(reordered.is_evolution_of(&old));
+
+        // Adding an optional column is compatible, but a required one isn't.
+        let added = DynStructCfg::from(vec![
+            col("a", false, ColumnFormat::I64),
+            col("b", true, ColumnFormat::String),
+            col("c", true, ColumnFormat::Bool),
+        ]);
+        assert!(added.is_evolution_of(&old));
+        assert!(!old.is_evolution_of(&added));
+        let added_required = DynStructCfg::from(vec![
+            col("a", false, ColumnFormat::I64),
+            col("b", true, ColumnFormat::String),
+            col("c", false, ColumnFormat::Bool),
+        ]);
+        assert!(!added_required.is_evolution_of(&old));
+
+        // Changing the type or nullability of a column is incompatible.
+        let retyped = DynStructCfg::from(vec![
+            col("a", false, ColumnFormat::U64),
+            col("b", true, ColumnFormat::String),
+        ]);
+        assert!(!retyped.is_evolution_of(&old));
+        let nullable = DynStructCfg::from(vec![
+            col("a", true, ColumnFormat::I64),
+            col("b", true, ColumnFormat::String),
+        ]);
+        assert!(!nullable.is_evolution_of(&old));
+
+        // Nested structs follow the same rules.
+        let nested_old = DynStructCfg::from(vec![col("s", false, ColumnFormat::Struct(old))]);
+        let nested_added = DynStructCfg::from(vec![col("s", false, ColumnFormat::Struct(added))]);
+        let nested_retyped =
+            DynStructCfg::from(vec![col("s", false, ColumnFormat::Struct(retyped))]);
+        assert!(nested_added.is_evolution_of(&nested_old));
+        assert!(!nested_retyped.is_evolution_of(&nested_old));
+    }
+}
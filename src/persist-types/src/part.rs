@@ -9,7 +9,7 @@
 
 //! A columnar representation of one blob's worth of data
 
-use arrow2::array::{Array, PrimitiveArray};
+use arrow2::array::{Array, PrimitiveArray, StructArray};
 use arrow2::buffer::Buffer;
 use arrow2::chunk::Chunk;
 use arrow2::datatypes::{DataType as ArrowLogicalType, Field};
@@ -50,6 +50,24 @@ impl Part {
         self.val.as_ref()
     }
 
+    /// Returns the key columns as an arrow struct array, along with the
+    /// parquet encodings of its fields.
+    ///
+    /// Returns None if the key schema has no columns, because arrow2 doesn't
+    /// allow empty struct arrays.
+    pub fn key_arrow(&self) -> Option<(StructArray, Vec<Encoding>)> {
+        self.key.to_arrow_struct()
+    }
+
+    /// Returns the val columns as an arrow struct array, along with the
+    /// parquet encodings of its fields.
+    ///
+    /// Returns None if the val schema has no columns, because arrow2 doesn't
+    /// allow empty struct arrays.
+    pub fn val_arrow(&self) -> Option<(StructArray, Vec<Encoding>)> {
+        self.val.to_arrow_struct()
+    }
+
     /// Computes a [StructStats] for the key columns.
     pub fn key_stats(&self) -> Result<StructStats, String> {
         let stats = self.key.stats(ValidityRef(None))?;
@@ -25,7 +25,7 @@ use crate::location::{Blob, Consensus, Determinate, ExternalError};
 use crate::mem::{MemBlob, MemBlobConfig, MemConsensus};
 use crate::metrics::S3BlobMetrics;
 use crate::postgres::{PostgresConsensus, PostgresConsensusConfig};
-use crate::s3::{S3Blob, S3BlobConfig};
+use crate::s3::{S3Blob, S3BlobConfig, S3WriteOptions};
 
 /// Config for an implementation of [Blob].
 #[derive(Debug, Clone)]
@@ -94,6 +94,27 @@ impl BlobConfig {
                 let role_arn = query_params.remove("role_arn").map(|x| x.into_owned());
                 let endpoint = query_params.remove("endpoint").map(|x| x.into_owned());
                 let region = query_params.remove("region").map(|x| x.into_owned());
+                // Objects are encrypted with SSE-KMS if `sse_kms_key_id` is
+                // given, and tagged with every `tag.<key>=<value>` param.
+                let sse_kms_key_id = query_params
+                    .remove("sse_kms_key_id")
+                    .map(|x| x.into_owned());
+                let (tag_params, other_params) =
+                    std::mem::take(&mut query_params)
+                        .into_iter()
+                        .partition::<BTreeMap<_, _>, _>(|(key, _)| key.starts_with("tag."));
+                query_params = other_params;
+                let tags = tag_params
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let key = key.strip_prefix("tag.").expect("tag param").to_owned();
+                        (key, value.into_owned())
+                    })
+                    .collect();
+                let write_options = S3WriteOptions {
+                    sse_kms_key_id,
+                    tags,
+                };
 
                 let credentials = match url.password() {
                     None => None,
@@ -107,6 +128,7 @@ impl BlobConfig {
                     endpoint,
                     region,
                     credentials,
+                    write_options,
                     knobs,
                     metrics,
                 )
@@ -34,6 +34,12 @@ pub enum Error {
     /// An error returned when a command is sent to a persistence runtime that
     /// was previously stopped.
     RuntimeShutdown,
+    /// A batch part uses an essential feature, identified by its bit in the
+    /// part's feature bitmap, that this version doesn't know how to decode.
+    UnsupportedPartFeature {
+        /// The bit of the unsupported feature.
+        bit: u32,
+    },
 }
 
 impl error::Error for Error {}
@@ -47,6 +53,9 @@ impl fmt::Display for Error {
             Error::UnknownRegistration(id) => write!(f, "unknown registration: {}", id),
             Error::Noop(_, e) => f.write_str(e),
             Error::RuntimeShutdown => f.write_str("runtime shutdown"),
+            Error::UnsupportedPartFeature { bit } => {
+                write!(f, "batch part uses unsupported feature: bit {}", bit)
+            }
         }
     }
 }
@@ -59,6 +68,10 @@ impl PartialEq for Error {
             (Error::OutOfQuota(s), Error::OutOfQuota(o)) => s == o,
             (Error::UnknownRegistration(s), Error::UnknownRegistration(o)) => s == o,
             (Error::RuntimeShutdown, Error::RuntimeShutdown) => true,
+            (
+                Error::UnsupportedPartFeature { bit: s },
+                Error::UnsupportedPartFeature { bit: o },
+            ) => s == o,
             _ => false,
         }
     }
@@ -14,6 +14,7 @@ use std::io::{Read, Seek, Write};
 use arrow2::io::parquet::read::{infer_schema, read_metadata, FileReader};
 use arrow2::io::parquet::write::{
     CompressionOptions, Encoding, FileWriter, KeyValue, RowGroupIterator, Version, WriteOptions,
+    ZstdLevel,
 };
 use differential_dataflow::trace::Description;
 use mz_persist_types::Codec64;
@@ -26,7 +27,7 @@ use crate::indexed::columnar::arrow::{
 };
 use crate::indexed::columnar::ColumnarRecords;
 use crate::indexed::encoding::{
-    decode_trace_inline_meta, encode_trace_inline_meta, BlobTraceBatchPart,
+    decode_trace_inline_meta, encode_trace_inline_meta, BlobCompression, BlobTraceBatchPart,
 };
 
 const INLINE_METADATA_KEY: &str = "MZ:inline";
@@ -35,6 +36,7 @@ const INLINE_METADATA_KEY: &str = "MZ:inline";
 pub fn encode_trace_parquet<W: Write, T: Timestamp + Codec64>(
     w: &mut W,
     batch: &BlobTraceBatchPart<T>,
+    compression: BlobCompression,
 ) -> Result<(), Error> {
     // Better to error now than write out an invalid batch.
     batch.validate()?;
@@ -42,6 +44,7 @@ pub fn encode_trace_parquet<W: Write, T: Timestamp + Codec64>(
         w,
         encode_trace_inline_meta(batch, ProtoBatchFormat::ParquetKvtd),
         &batch.updates,
+        compression,
     )
 }
 
@@ -86,12 +89,23 @@ fn encode_parquet_kvtd<W: Write>(
     w: &mut W,
     inline_base64: String,
     iter: &[ColumnarRecords],
+    compression: BlobCompression,
 ) -> Result<(), Error> {
     let iter = iter.into_iter().map(|x| Ok(encode_arrow_batch_kvtd(x)));
 
+    // The codec is recorded in each column chunk's metadata, which is what the
+    // reader uses to decompress it, so no extra bookkeeping is needed to read
+    // back parts written with any of these.
+    let compression = match compression {
+        BlobCompression::None => CompressionOptions::Uncompressed,
+        BlobCompression::Zstd { level } => {
+            let level = ZstdLevel::try_new(level).map_err(|err| err.to_string())?;
+            CompressionOptions::Zstd(Some(level))
+        }
+    };
     let options = WriteOptions {
         write_statistics: false,
-        compression: CompressionOptions::Uncompressed,
+        compression,
         version: Version::V2,
         data_pagesize_limit: None, // use default limit
     };
@@ -141,3 +155,67 @@ fn decode_parquet_file_kvtd<R: Read + Seek>(r: &mut R) -> Result<Vec<ColumnarRec
     }
     Ok(ret)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use prost::Message;
+
+    use crate::gen::persist::ProtoBatchPartInline;
+    use crate::indexed::columnar::ColumnarRecordsBuilder;
+
+    use super::*;
+
+    /// Encodes a part with the given feature bitmap, as a newer writer might.
+    fn encode_with_features(desc: &Description<u64>, features: u64) -> Vec<u8> {
+        let inline = ProtoBatchPartInline {
+            format: ProtoBatchFormat::ParquetKvtd.into(),
+            desc: Some(desc.into()),
+            index: 0,
+            features,
+        };
+        let mut updates = ColumnarRecordsBuilder::default();
+        assert!(updates.push((
+            (b"k".as_slice(), b"v".as_slice()),
+            u64::encode(&0),
+            i64::encode(&1)
+        )));
+        let mut buf = Vec::new();
+        encode_parquet_kvtd(
+            &mut buf,
+            base64::encode(inline.encode_to_vec()),
+            &[updates.finish()],
+        )
+        .expect("valid part");
+        buf
+    }
+
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn part_features() {
+        let desc = Description::new(
+            Antichain::from_elem(0u64),
+            Antichain::from_elem(1),
+            Antichain::from_elem(0),
+        );
+        let decode = |features| {
+            let buf = encode_with_features(&desc, features);
+            decode_trace_parquet::<_, u64>(&mut Cursor::new(buf)).map(|part| part.desc)
+        };
+
+        assert_eq!(decode(0), Ok(desc.clone()));
+        // Unknown ignorable features are skipped.
+        assert_eq!(decode(1 << 40), Ok(desc.clone()));
+        assert_eq!(decode((1 << 63) | (1 << 32)), Ok(desc.clone()));
+        // Unknown essential features make the part undecodable.
+        assert_eq!(
+            decode((1 << 3) | (1 << 40)),
+            Err(Error::UnsupportedPartFeature { bit: 3 })
+        );
+        assert_eq!(
+            decode(1 << 31),
+            Err(Error::UnsupportedPartFeature { bit: 31 })
+        );
+    }
+}
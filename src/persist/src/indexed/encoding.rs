@@ -15,6 +15,7 @@
 
 use std::fmt::{self, Debug};
 use std::marker::PhantomData;
+use std::str::FromStr;
 
 use bytes::BufMut;
 use differential_dataflow::trace::Description;
@@ -219,7 +220,20 @@ impl<T: Timestamp + Codec64> BlobTraceBatchPart<T> {
     where
         B: BufMut,
     {
-        encode_trace_parquet(&mut buf.writer(), self).expect("batch was invalid");
+        self.encode_with_compression(buf, BlobCompression::None)
+    }
+
+    /// Encodes an BlobTraceBatchPart into the Parquet format, compressing its
+    /// columns with the given codec.
+    ///
+    /// The codec is recorded in the Parquet column metadata, so
+    /// [Self::decode] reads the part back regardless of the codec it was
+    /// written with.
+    pub fn encode_with_compression<B>(&self, buf: &mut B, compression: BlobCompression)
+    where
+        B: BufMut,
+    {
+        encode_trace_parquet(&mut buf.writer(), self, compression).expect("batch was invalid");
     }
 
     /// Decodes a BlobTraceBatchPart from the Parquet format.
@@ -228,6 +242,72 @@ impl<T: Timestamp + Codec64> BlobTraceBatchPart<T> {
     }
 }
 
+/// The compression codec used for the columns of a [BlobTraceBatchPart].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlobCompression {
+    /// No compression.
+    #[default]
+    None,
+    /// Zstandard compression at the given level.
+    Zstd {
+        /// The compression level, between [Self::ZSTD_MIN_LEVEL] and
+        /// [Self::ZSTD_MAX_LEVEL] (inclusive).
+        level: i32,
+    },
+}
+
+impl BlobCompression {
+    /// The lowest (fastest) supported zstd level.
+    pub const ZSTD_MIN_LEVEL: i32 = 1;
+    /// The highest (smallest output) supported zstd level.
+    pub const ZSTD_MAX_LEVEL: i32 = 22;
+    /// The zstd level used when none is specified.
+    pub const ZSTD_DEFAULT_LEVEL: i32 = 3;
+}
+
+impl fmt::Display for BlobCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlobCompression::None => f.write_str("none"),
+            BlobCompression::Zstd { level } => write!(f, "zstd:{}", level),
+        }
+    }
+}
+
+impl FromStr for BlobCompression {
+    type Err = String;
+
+    /// Parses one of `none`, `zstd`, or `zstd:<level>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (codec, level) = match s.split_once(':') {
+            Some((codec, level)) => (codec, Some(level)),
+            None => (s.as_str(), None),
+        };
+        match (codec, level) {
+            ("none", None) => Ok(BlobCompression::None),
+            ("zstd", None) => Ok(BlobCompression::Zstd {
+                level: Self::ZSTD_DEFAULT_LEVEL,
+            }),
+            ("zstd", Some(level)) => {
+                let level = level
+                    .parse::<i32>()
+                    .map_err(|err| format!("invalid zstd level {:?}: {}", level, err))?;
+                if !(Self::ZSTD_MIN_LEVEL..=Self::ZSTD_MAX_LEVEL).contains(&level) {
+                    return Err(format!(
+                        "zstd level {} must be between {} and {}",
+                        level,
+                        Self::ZSTD_MIN_LEVEL,
+                        Self::ZSTD_MAX_LEVEL
+                    ));
+                }
+                Ok(BlobCompression::Zstd { level })
+            }
+            _ => Err(format!("unknown blob compression: {:?}", s)),
+        }
+    }
+}
+
 #[derive(PartialOrd, Ord, PartialEq, Eq)]
 struct PrettyBytes<'a>(&'a [u8]);
 
@@ -309,6 +389,41 @@ impl<T: Timestamp + Codec64> From<&Description<T>> for ProtoU64Description {
     }
 }
 
+/// The bits of the optional part features this version knows how to decode.
+///
+/// This is the registry of part features: each optional columnar extension
+/// (e.g. a new stats encoding or dictionary encoding) gets a bit in the
+/// `features` bitmap of [ProtoBatchPartInline], which writers set when a part
+/// uses the extension. Bits below [IGNORABLE_PART_FEATURES_START] are
+/// essential: a reader that doesn't know about one of them can't decode the
+/// part correctly. The remaining bits are ignorable (e.g. extra metadata) and
+/// may be skipped by readers that don't know about them. A new feature must
+/// pick its bit according to this classification, which can't be changed
+/// once the bit is in use.
+///
+/// No features have been defined yet.
+pub const KNOWN_PART_FEATURES: u64 = 0;
+
+/// The first bit of the part feature bitmap that denotes an ignorable feature.
+///
+/// See [KNOWN_PART_FEATURES].
+pub const IGNORABLE_PART_FEATURES_START: u32 = 32;
+
+/// Returns an error if the given part feature bitmap includes an essential
+/// feature this version doesn't know how to decode.
+///
+/// Unknown ignorable features are skipped. See [KNOWN_PART_FEATURES].
+pub fn check_part_features(features: u64) -> Result<(), Error> {
+    let unknown = features & !KNOWN_PART_FEATURES;
+    // Essential features have the lower bits, so if any of the unknown
+    // features is essential, the lowest one is.
+    let bit = unknown.trailing_zeros();
+    if bit < IGNORABLE_PART_FEATURES_START {
+        return Err(Error::UnsupportedPartFeature { bit });
+    }
+    Ok(())
+}
+
 /// Encodes the inline metadata for a trace batch into a base64 string.
 pub fn encode_trace_inline_meta<T: Timestamp + Codec64>(
     batch: &BlobTraceBatchPart<T>,
@@ -318,6 +433,8 @@ pub fn encode_trace_inline_meta<T: Timestamp + Codec64>(
         format: format.into(),
         desc: Some((&batch.desc).into()),
         index: batch.index,
+        // Parts don't use any optional features yet.
+        features: 0,
     };
     let inline_encoded = inline.encode_to_vec();
     base64::encode(inline_encoded)
@@ -332,6 +449,7 @@ pub fn decode_trace_inline_meta(
     let inline = ProtoBatchPartInline::decode(&*inline_encoded).map_err(|err| err.to_string())?;
     let format = ProtoBatchFormat::from_i32(inline.format)
         .ok_or_else(|| Error::from(format!("unknown format: {}", inline.format)))?;
+    check_part_features(inline.features)?;
     Ok((format, inline))
 }
 
@@ -598,6 +716,79 @@ mod tests {
         Ok(())
     }
 
+    #[mz_ore::test]
+    fn blob_compression_parse() {
+        let cases = [
+            ("none", Ok(BlobCompression::None)),
+            ("NONE", Ok(BlobCompression::None)),
+            ("zstd", Ok(BlobCompression::Zstd { level: 3 })),
+            (" zstd:22 ", Ok(BlobCompression::Zstd { level: 22 })),
+            ("zstd:0", Err(())),
+            ("zstd:23", Err(())),
+            ("zstd:fast", Err(())),
+            ("none:1", Err(())),
+            ("lz4", Err(())),
+            ("", Err(())),
+        ];
+        for (input, expected) in cases {
+            let actual = input.parse::<BlobCompression>().map_err(|_| ());
+            assert_eq!(actual, expected, "{:?}", input);
+            if let Ok(compression) = actual {
+                // Display round-trips through FromStr.
+                assert_eq!(compression.to_string().parse(), Ok(compression));
+            }
+        }
+    }
+
+    #[mz_ore::test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn blob_compression_roundtrip() {
+        let updates = (0..1_000u64)
+            .map(|ts| {
+                let key = format!("key-{}", ts % 10).into_bytes();
+                ((key, b"a fairly compressible value".to_vec()), ts, 1)
+            })
+            .collect();
+        let part = BlobTraceBatchPart {
+            desc: u64_desc(0, 1_000),
+            index: 0,
+            updates: columnar_records(updates),
+        };
+
+        let mut sizes = Vec::new();
+        for compression in [
+            BlobCompression::None,
+            BlobCompression::Zstd { level: 1 },
+            BlobCompression::Zstd { level: 3 },
+            BlobCompression::Zstd { level: 22 },
+        ] {
+            let mut buf = Vec::new();
+            part.encode_with_compression(&mut buf, compression);
+            sizes.push(buf.len());
+            // Decoding doesn't need to be told the codec.
+            let decoded = BlobTraceBatchPart::<u64>::decode(&SegmentedBytes::from(buf))
+                .expect("decodable part");
+            assert_eq!(decoded.desc, part.desc);
+            let records = |x: &BlobTraceBatchPart<u64>| {
+                x.updates
+                    .iter()
+                    .flat_map(|x| x.iter())
+                    .map(|((k, v), t, d)| ((k.to_vec(), v.to_vec()), t, d))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(records(&decoded), records(&part), "{}", compression);
+        }
+
+        // Plain encode is uncompressed.
+        let mut buf = Vec::new();
+        part.encode(&mut buf);
+        assert_eq!(buf.len(), sizes[0]);
+        // And every zstd level beats it on this data.
+        for size in &sizes[1..] {
+            assert!(*size < sizes[0], "{:?}", sizes);
+        }
+    }
+
     #[mz_ore::test]
     #[cfg_attr(miri, ignore)] // too slow
     fn encoded_batch_sizes() {
@@ -264,6 +264,12 @@ impl From<deadpool_postgres::tokio_postgres::Error> for ExternalError {
                     inner: anyhow::Error::new(e),
                 })
             }
+            // A statement canceled by `statement_timeout` is rolled back.
+            &deadpool_postgres::tokio_postgres::error::SqlState::QUERY_CANCELED => {
+                ExternalError::Determinate(Determinate {
+                    inner: anyhow::Error::new(e),
+                })
+            }
             _ => ExternalError::Indeterminate(Indeterminate {
                 inner: anyhow::Error::new(e),
             }),
@@ -403,6 +409,16 @@ pub trait Consensus: std::fmt::Debug {
     /// `seqno` is greater than the current sequence number, or if there is no
     /// data at this key.
     async fn truncate(&self, key: &str, seqno: SeqNo) -> Result<usize, ExternalError>;
+
+    /// Deletes all versions of the data stored at `key`, iff the current
+    /// sequence number is exactly `expected`.
+    ///
+    /// Afterward, `key` is indistinguishable from one that was never written:
+    /// it is no longer returned by [Consensus::list_keys] and the next call to
+    /// [Consensus::compare_and_set] needs to happen with None as the expected
+    /// value. Returns [CaSResult::ExpectationMismatch] if the current sequence
+    /// number is not `expected`, including if there is no data at this key.
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError>;
 }
 
 #[async_trait]
@@ -466,6 +482,16 @@ impl<A: Consensus + Sync + Send + 'static> Consensus for Tasked<A> {
         )
         .await?
     }
+
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        let backing = self.clone_backing();
+        let key = key.to_owned();
+        mz_ore::task::spawn(
+            || "persist::task::delete",
+            async move { backing.delete(&key, expected).await }.instrument(Span::current()),
+        )
+        .await?
+    }
 }
 
 /// Metadata about a particular blob stored by persist
@@ -480,6 +506,10 @@ pub struct BlobMetadata<'a> {
 /// A key usable for liveness checks via [Blob::get].
 pub const BLOB_GET_LIVENESS_KEY: &str = "LIVENESS";
 
+/// A prefix for the keys of probes written by blob health checks, which
+/// delete them again once read back.
+pub const BLOB_HEALTH_CHECK_KEY_PREFIX: &str = "HEALTH_CHECK_";
+
 /// An abstraction over read-write access to a `bytes key`->`bytes value` store.
 ///
 /// Implementations are required to be _linearizable_.
@@ -1067,6 +1097,39 @@ pub mod tests {
         );
         assert_eq!(consensus.truncate(&key, SeqNo(12)).await, Ok(2));
 
+        // Can only delete all the data at a key at its current sequence number.
+        assert_eq!(
+            consensus.delete(&key, SeqNo(11)).await,
+            Ok(CaSResult::ExpectationMismatch),
+        );
+        assert_eq!(
+            consensus.delete(&key, SeqNo(12)).await,
+            Ok(CaSResult::Committed),
+        );
+        assert_eq!(consensus.head(&key).await, Ok(None));
+        assert_eq!(consensus.scan(&key, SeqNo(0), 10).await, Ok(vec![]));
+        assert!(!consensus
+            .list_keys()
+            .try_collect::<Vec<_>>()
+            .await?
+            .contains(&key));
+
+        // Deleting a key without any data is an expectation mismatch.
+        assert_eq!(
+            consensus.delete(&key, SeqNo(12)).await,
+            Ok(CaSResult::ExpectationMismatch),
+        );
+
+        // A deleted key can be written again as if it were new.
+        let v0 = VersionedData {
+            seqno: SeqNo(0),
+            data: Bytes::new(),
+        };
+        assert_eq!(
+            consensus.compare_and_set(&key, None, v0).await,
+            Ok(CaSResult::Committed),
+        );
+
         // Sequence numbers used within Consensus have to be within [0, i64::MAX].
 
         assert_eq!(
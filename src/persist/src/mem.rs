@@ -358,6 +358,23 @@ impl Consensus for MemConsensus {
 
         Ok(deleted)
     }
+
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        // Yield to maximize our chances for getting interesting orderings.
+        let () = yield_now().await;
+        let mut store = self.data.lock().map_err(Error::from)?;
+
+        let seqno = store
+            .get(key)
+            .and_then(|values| values.last())
+            .map(|data| data.seqno);
+        if seqno != Some(expected) {
+            return Ok(CaSResult::ExpectationMismatch);
+        }
+
+        store.remove(key);
+        Ok(CaSResult::Committed)
+    }
 }
 
 #[cfg(test)]
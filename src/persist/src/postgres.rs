@@ -9,7 +9,9 @@
 
 //! Implementation of [Consensus] backed by Postgres.
 
+use std::collections::BTreeMap;
 use std::fmt::Formatter;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -89,6 +91,118 @@ pub struct PostgresConsensusConfig {
     metrics: PostgresClientMetrics,
 }
 
+/// [PostgresClientKnobs] overridden by query params of the consensus URI.
+///
+/// Overrides take precedence over the wrapped knobs, including any dynamic
+/// updates to them.
+#[derive(Debug)]
+struct UriKnobs {
+    knobs: Arc<dyn PostgresClientKnobs>,
+    connection_pool_max_size: Option<usize>,
+    connection_pool_max_wait: Option<Duration>,
+    statement_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+}
+
+impl UriKnobs {
+    /// The query params of the consensus URI that are handled here, instead of
+    /// being passed on to Postgres.
+    const PARAMS: &'static [&'static str] = &[
+        "connection_pool_max_size",
+        "connection_pool_max_wait_ms",
+        "statement_timeout_ms",
+        "tcp_keepalive_ms",
+    ];
+
+    fn new(
+        knobs: Arc<dyn PostgresClientKnobs>,
+        params: &BTreeMap<&str, &str>,
+    ) -> Result<Self, Error> {
+        fn parse<T: FromStr>(params: &BTreeMap<&str, &str>, key: &str) -> Result<Option<T>, Error> {
+            params
+                .get(key)
+                .map(|val| {
+                    val.parse()
+                        .map_err(|_| Error::from(format!("invalid {} param value: {}", key, val)))
+                })
+                .transpose()
+        }
+        let millis = |key| -> Result<Option<Duration>, Error> {
+            Ok(parse::<u64>(params, key)?.map(Duration::from_millis))
+        };
+        Ok(UriKnobs {
+            knobs,
+            connection_pool_max_size: parse(params, "connection_pool_max_size")?,
+            connection_pool_max_wait: millis("connection_pool_max_wait_ms")?,
+            statement_timeout: millis("statement_timeout_ms")?,
+            tcp_keepalive: millis("tcp_keepalive_ms")?,
+        })
+    }
+}
+
+impl PostgresClientKnobs for UriKnobs {
+    fn connection_pool_max_size(&self) -> usize {
+        self.connection_pool_max_size
+            .unwrap_or_else(|| self.knobs.connection_pool_max_size())
+    }
+
+    fn connection_pool_max_wait(&self) -> Option<Duration> {
+        self.connection_pool_max_wait
+            .or_else(|| self.knobs.connection_pool_max_wait())
+    }
+
+    fn connection_pool_ttl(&self) -> Duration {
+        self.knobs.connection_pool_ttl()
+    }
+
+    fn connection_pool_ttl_stagger(&self) -> Duration {
+        self.knobs.connection_pool_ttl_stagger()
+    }
+
+    fn connect_timeout(&self) -> Duration {
+        self.knobs.connect_timeout()
+    }
+
+    fn tcp_user_timeout(&self) -> Duration {
+        self.knobs.tcp_user_timeout()
+    }
+
+    fn statement_timeout(&self) -> Duration {
+        self.statement_timeout
+            .unwrap_or_else(|| self.knobs.statement_timeout())
+    }
+
+    fn tcp_keepalive(&self) -> Option<Duration> {
+        self.tcp_keepalive.or_else(|| self.knobs.tcp_keepalive())
+    }
+}
+
+/// Splits the query params in `names` off of the given URL.
+///
+/// The remaining query params are left verbatim, as Postgres has its own
+/// rules for decoding them.
+fn split_uri_params<'a>(url: &'a str, names: &[&str]) -> (String, BTreeMap<&'a str, &'a str>) {
+    let Some((base, query)) = url.split_once('?') else {
+        return (url.to_owned(), BTreeMap::new());
+    };
+    let mut params = BTreeMap::new();
+    let mut rest = Vec::new();
+    for pair in query.split('&') {
+        let (key, val) = pair.split_once('=').unwrap_or((pair, ""));
+        if names.contains(&key) {
+            params.insert(key, val);
+        } else {
+            rest.push(pair);
+        }
+    }
+    let url = if rest.is_empty() {
+        base.to_owned()
+    } else {
+        format!("{}?{}", base, rest.join("&"))
+    };
+    (url, params)
+}
+
 impl From<PostgresConsensusConfig> for PostgresClientConfig {
     fn from(config: PostgresConsensusConfig) -> Self {
         PostgresClientConfig::new(config.url, config.knobs, config.metrics)
@@ -100,14 +214,28 @@ impl PostgresConsensusConfig {
         "MZ_PERSIST_EXTERNAL_STORAGE_TEST_POSTGRES_URL";
 
     /// Returns a new [PostgresConsensusConfig] for use in production.
+    ///
+    /// The `connection_pool_max_size`, `connection_pool_max_wait_ms`,
+    /// `statement_timeout_ms`, and `tcp_keepalive_ms` query params of `url`
+    /// override the corresponding `knobs`.
     pub fn new(
         url: &str,
         knobs: Box<dyn PostgresClientKnobs>,
         metrics: PostgresClientMetrics,
     ) -> Result<Self, Error> {
+        Self::new_with_knobs(url, Arc::from(knobs), metrics)
+    }
+
+    fn new_with_knobs(
+        url: &str,
+        knobs: Arc<dyn PostgresClientKnobs>,
+        metrics: PostgresClientMetrics,
+    ) -> Result<Self, Error> {
+        let (url, params) = split_uri_params(url, UriKnobs::PARAMS);
+        let knobs = UriKnobs::new(knobs, &params)?;
         Ok(PostgresConsensusConfig {
-            url: url.to_string(),
-            knobs: Arc::from(knobs),
+            url,
+            knobs: Arc::new(knobs),
             metrics,
         })
     }
@@ -159,6 +287,12 @@ impl PostgresConsensusConfig {
             fn tcp_user_timeout(&self) -> Duration {
                 Duration::ZERO
             }
+            fn statement_timeout(&self) -> Duration {
+                Duration::ZERO
+            }
+            fn tcp_keepalive(&self) -> Option<Duration> {
+                None
+            }
         }
 
         let config = PostgresConsensusConfig::new(
@@ -395,10 +529,31 @@ impl Consensus for PostgresConsensus {
 
         Ok(usize::cast_from(result))
     }
+
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        let q = "DELETE FROM consensus
+                WHERE shard = $1 AND
+                (SELECT sequence_number FROM consensus
+                 WHERE shard = $1
+                 ORDER BY sequence_number DESC LIMIT 1) = $2";
+
+        let result = {
+            let client = self.get_connection().await?;
+            let statement = client.prepare_cached(q).await?;
+            client.execute(&statement, &[&key, &expected]).await?
+        };
+        if result > 0 {
+            Ok(CaSResult::Committed)
+        } else {
+            Ok(CaSResult::ExpectationMismatch)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Instant;
+
     use tracing::info;
     use uuid::Uuid;
 
@@ -470,4 +625,70 @@ mod tests {
 
         Ok(())
     }
+
+    #[mz_ore::test]
+    fn uri_params() {
+        let params = UriKnobs::PARAMS;
+        assert_eq!(
+            split_uri_params("postgres://root@localhost:26257", params),
+            (
+                "postgres://root@localhost:26257".to_owned(),
+                BTreeMap::new()
+            )
+        );
+        let (url, params) = split_uri_params(
+            "postgres://root@localhost:26257?options=--search_path%3Dconsensus&statement_timeout_ms=100&tcp_keepalive_ms=5000",
+            params,
+        );
+        assert_eq!(
+            url,
+            "postgres://root@localhost:26257?options=--search_path%3Dconsensus"
+        );
+        assert_eq!(
+            params,
+            BTreeMap::from([
+                ("statement_timeout_ms", "100"),
+                ("tcp_keepalive_ms", "5000")
+            ])
+        );
+    }
+
+    #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
+    #[cfg_attr(miri, ignore)] // error: unsupported operation: can't call foreign function `TLS_client_method` on OS `linux`
+    async fn postgres_consensus_statement_timeout() -> Result<(), ExternalError> {
+        let config = match PostgresConsensusConfig::new_for_test()? {
+            Some(config) => config,
+            None => {
+                info!(
+                    "{} env not set: skipping test that uses external service",
+                    PostgresConsensusConfig::EXTERNAL_TESTS_POSTGRES_URL
+                );
+                return Ok(());
+            }
+        };
+
+        let sep = if config.url.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}statement_timeout_ms=1", config.url, sep);
+        let config = PostgresConsensusConfig::new_with_knobs(
+            &url,
+            Arc::clone(&config.knobs),
+            config.metrics.clone(),
+        )?;
+        assert_eq!(config.knobs.statement_timeout(), Duration::from_millis(1));
+
+        // Go through the client directly, as even opening a consensus might
+        // hit the timeout.
+        let client = PostgresClient::open(config.into())?;
+        let conn = client.get_connection().await?;
+        let start = Instant::now();
+        let err = conn
+            .execute("SELECT pg_sleep(10)", &[])
+            .await
+            .expect_err("statement should have timed out");
+        let err = ExternalError::from(err);
+        assert!(matches!(err, ExternalError::Determinate(_)), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        Ok(())
+    }
 }
@@ -10,6 +10,7 @@
 //! An S3 implementation of [Blob] storage.
 
 use std::cmp;
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter};
 use std::ops::Range;
 use std::sync::atomic::{self, AtomicU64};
@@ -22,9 +23,11 @@ use aws_config::sts::AssumeRoleProvider;
 use aws_config::timeout::TimeoutConfig;
 use aws_credential_types::Credentials;
 use aws_sdk_s3::config::{AsyncSleep, Sleep};
-use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::create_multipart_upload::builders::CreateMultipartUploadFluentBuilder;
+use aws_sdk_s3::operation::put_object::builders::PutObjectFluentBuilder;
 use aws_sdk_s3::primitives::ByteStream;
-use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, ServerSideEncryption};
 use aws_sdk_s3::Client as S3Client;
 use aws_types::region::Region;
 use bytes::Bytes;
@@ -50,6 +53,55 @@ pub struct S3BlobConfig {
     client: S3Client,
     bucket: String,
     prefix: String,
+    write_options: S3WriteOptions,
+}
+
+/// Server-side encryption and tagging applied to every object written by an
+/// [S3Blob].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct S3WriteOptions {
+    /// The KMS key (an ARN, key id, or alias) to encrypt objects with using
+    /// `aws:kms` server-side encryption. If unset, the bucket's default
+    /// encryption applies.
+    pub sse_kms_key_id: Option<String>,
+    /// Tags attached to every object, e.g. for cost allocation.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl S3WriteOptions {
+    /// Returns the tags as the URL-encoded query string S3 expects, or None if
+    /// there are no tags.
+    fn tagging(&self) -> Option<String> {
+        if self.tags.is_empty() {
+            return None;
+        }
+        let mut tagging = url::form_urlencoded::Serializer::new(String::new());
+        tagging.extend_pairs(&self.tags);
+        Some(tagging.finish())
+    }
+
+    fn sse(&self) -> Option<ServerSideEncryption> {
+        self.sse_kms_key_id
+            .as_ref()
+            .map(|_| ServerSideEncryption::AwsKms)
+    }
+
+    fn apply_to_put(&self, req: PutObjectFluentBuilder) -> PutObjectFluentBuilder {
+        req.set_server_side_encryption(self.sse())
+            .set_ssekms_key_id(self.sse_kms_key_id.clone())
+            .set_tagging(self.tagging())
+    }
+
+    // NB: The encryption and tags of a multipart upload are fixed when it's
+    // created, the individual parts don't need them.
+    fn apply_to_create_multipart(
+        &self,
+        req: CreateMultipartUploadFluentBuilder,
+    ) -> CreateMultipartUploadFluentBuilder {
+        req.set_server_side_encryption(self.sse())
+            .set_ssekms_key_id(self.sse_kms_key_id.clone())
+            .set_tagging(self.tagging())
+    }
 }
 
 // There is no simple way to hook into the S3 client to capture when its various timeouts
@@ -104,6 +156,9 @@ impl AsyncSleep for MetricsSleep {
 
 impl S3BlobConfig {
     const EXTERNAL_TESTS_S3_BUCKET: &'static str = "MZ_PERSIST_EXTERNAL_STORAGE_TEST_S3_BUCKET";
+    #[cfg(test)]
+    const EXTERNAL_TESTS_S3_KMS_KEY_ID: &'static str =
+        "MZ_PERSIST_EXTERNAL_STORAGE_TEST_S3_KMS_KEY_ID";
 
     /// Returns a new [S3BlobConfig] for use in production.
     ///
@@ -117,6 +172,7 @@ impl S3BlobConfig {
         endpoint: Option<String>,
         region: Option<String>,
         credentials: Option<(String, String)>,
+        write_options: S3WriteOptions,
         knobs: Box<dyn BlobKnobs>,
         metrics: S3BlobMetrics,
     ) -> Result<Self, Error> {
@@ -172,6 +228,7 @@ impl S3BlobConfig {
             client,
             bucket,
             prefix,
+            write_options,
         })
     }
 
@@ -259,6 +316,7 @@ impl S3BlobConfig {
             None,
             None,
             None,
+            S3WriteOptions::default(),
             Box::new(TestBlobKnobs),
             metrics,
         )
@@ -286,6 +344,7 @@ pub struct S3Blob {
     // Defaults to 1000 which is the current AWS max.
     max_keys: i32,
     multipart_config: MultipartConfig,
+    write_options: S3WriteOptions,
 }
 
 impl S3Blob {
@@ -298,6 +357,7 @@ impl S3Blob {
             prefix: config.prefix,
             max_keys: 1_000,
             multipart_config: MultipartConfig::default(),
+            write_options: config.write_options,
         };
         // Connect before returning success. We don't particularly care about
         // what's stored in this blob (nothing writes to it, so presumably it's
@@ -656,15 +716,18 @@ impl S3Blob {
         let value_len = value.len();
         let part_span = trace_span!("s3set_single", payload_len = value_len);
         self.metrics.set_single.inc();
-        self.client
+        let req = self
+            .client
             .put_object()
             .bucket(&self.bucket)
             .key(path)
-            .body(ByteStream::from(value))
+            .body(ByteStream::from(value));
+        self.write_options
+            .apply_to_put(req)
             .send()
             .instrument(part_span)
             .await
-            .map_err(|err| Error::from(err.to_string()))?;
+            .map_err(|err| write_err("put_object", err))?;
         debug!(
             "s3 PutObject single done {}b / {:?}",
             value_len,
@@ -683,15 +746,18 @@ impl S3Blob {
         // Start the multi part request and get an upload id.
         trace!("s3 PutObject multi start {}b", value.len());
         self.metrics.set_multi_create.inc();
-        let upload_res = self
+        let req = self
             .client
             .create_multipart_upload()
             .bucket(&self.bucket)
-            .key(&path)
+            .key(&path);
+        let upload_res = self
+            .write_options
+            .apply_to_create_multipart(req)
             .send()
             .instrument(debug_span!("s3set_multi_start"))
             .await
-            .map_err(|err| Error::from(format!("create_multipart_upload err: {}", err)))?;
+            .map_err(|err| write_err("create_multipart_upload", err))?;
         let upload_id = upload_res.upload_id().ok_or_else(|| {
             Error::from("create_multipart_upload response missing upload_id".to_string())
         })?;
@@ -809,6 +875,35 @@ impl S3Blob {
     }
 }
 
+/// Converts an error from an S3 write into an [ExternalError].
+///
+/// Errors caused by the encryption or tagging config of the blob, e.g. a KMS
+/// key the role isn't allowed to use, are surfaced as determinate errors with
+/// the message from AWS. Everything else is indeterminate.
+fn write_err<E, R>(op: &str, err: SdkError<E, R>) -> ExternalError
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    R: Debug + Send + Sync + 'static,
+{
+    let determinate = match err.as_service_error() {
+        // Feel free to add more things to this allowlist as we encounter them
+        // as long as you're certain they're determinate.
+        Some(service_err) => match (service_err.code(), service_err.message()) {
+            (Some(code), _) if code.starts_with("KMS.") => true,
+            (Some("InvalidTag" | "InvalidArgument"), _) => true,
+            (Some("AccessDenied"), Some(message)) => message.to_lowercase().contains("kms"),
+            _ => false,
+        },
+        None => false,
+    };
+    let err = anyhow!("{} err: {}", op, DisplayErrorContext(&err));
+    if determinate {
+        Determinate::new(err).into()
+    } else {
+        err.into()
+    }
+}
+
 #[derive(Clone, Debug)]
 struct MultipartConfig {
     multipart_threshold: usize,
@@ -1001,6 +1096,7 @@ mod tests {
                     client: config.client.clone(),
                     bucket: config.bucket.clone(),
                     prefix: format!("{}/s3_blob_impl_test/{}", config.prefix, path),
+                    write_options: config.write_options.clone(),
                 };
                 let mut blob = S3Blob::open(config).await?;
                 blob.max_keys = 2;
@@ -1024,6 +1120,130 @@ mod tests {
         Ok(())
     }
 
+    #[mz_ore::test(tokio::test(flavor = "multi_thread"))]
+    #[cfg_attr(coverage, ignore)] // https://github.com/MaterializeInc/materialize/issues/18898
+    #[cfg_attr(miri, ignore)] // error: unsupported operation: can't call foreign function `TLS_method` on OS `linux`
+    async fn s3_blob_write_options() -> Result<(), ExternalError> {
+        let mut config = match S3BlobConfig::new_for_test().await? {
+            Some(client) => client,
+            None => {
+                info!(
+                    "{} env not set: skipping test that uses external service",
+                    S3BlobConfig::EXTERNAL_TESTS_S3_BUCKET
+                );
+                return Ok(());
+            }
+        };
+        // Not every test bucket has a KMS key set up for it, so this one is
+        // opt-in even on CI.
+        let sse_kms_key_id = match std::env::var(S3BlobConfig::EXTERNAL_TESTS_S3_KMS_KEY_ID) {
+            Ok(key_id) => key_id,
+            Err(_) => {
+                info!(
+                    "{} env not set: skipping test that uses external service",
+                    S3BlobConfig::EXTERNAL_TESTS_S3_KMS_KEY_ID
+                );
+                return Ok(());
+            }
+        };
+        config.write_options = S3WriteOptions {
+            sse_kms_key_id: Some(sse_kms_key_id),
+            tags: BTreeMap::from([("mz-test".to_owned(), "s3_blob_write_options".to_owned())]),
+        };
+
+        let blob = S3Blob::open(config.clone()).await?;
+        blob.set("single", "foo".into(), Atomicity::RequireAtomic)
+            .await?;
+        blob.set_multi_part("multi", "bar".into()).await?;
+        for key in ["single", "multi"] {
+            let path = blob.get_path(key);
+            let head = blob
+                .client
+                .head_object()
+                .bucket(&blob.bucket)
+                .key(&path)
+                .send()
+                .await
+                .map_err(|err| anyhow!("head_object err: {}", DisplayErrorContext(err)))?;
+            assert_eq!(
+                head.server_side_encryption(),
+                Some(&ServerSideEncryption::AwsKms)
+            );
+            assert!(head.ssekms_key_id().is_some());
+            let tagging = blob
+                .client
+                .get_object_tagging()
+                .bucket(&blob.bucket)
+                .key(&path)
+                .send()
+                .await
+                .map_err(|err| anyhow!("get_object_tagging err: {}", DisplayErrorContext(err)))?;
+            let tags = tagging
+                .tag_set()
+                .iter()
+                .map(|tag| (tag.key().to_owned(), tag.value().to_owned()))
+                .collect::<BTreeMap<_, _>>();
+            assert_eq!(tags, config.write_options.tags);
+        }
+
+        // Writing with a key that can't be used fails determinately.
+        config.write_options.sse_kms_key_id = Some("alias/mz-persist-test-nonexistent".to_owned());
+        let blob = S3Blob::open(config).await?;
+        match blob
+            .set("bad_key", "baz".into(), Atomicity::RequireAtomic)
+            .await
+        {
+            Err(ExternalError::Determinate(_)) => {}
+            res => panic!("expected determinate error: {:?}", res),
+        }
+
+        Ok(())
+    }
+
+    #[mz_ore::test]
+    fn write_options_request_headers() {
+        let client = S3Client::from_conf(
+            aws_sdk_s3::Config::builder()
+                .behavior_version(aws_sdk_s3::config::BehaviorVersion::latest())
+                .region(Region::new("us-east-1"))
+                .build(),
+        );
+        let key_id = "arn:aws:kms:us-east-1:123456789012:key/1234abcd";
+        let options = S3WriteOptions {
+            sse_kms_key_id: Some(key_id.to_owned()),
+            tags: BTreeMap::from([
+                ("cost-center".to_owned(), "persist".to_owned()),
+                ("team".to_owned(), "db & storage".to_owned()),
+            ]),
+        };
+        let tagging = "cost-center=persist&team=db+%26+storage";
+
+        let put = options.apply_to_put(client.put_object());
+        let put = put.as_input();
+        assert_eq!(
+            put.get_server_side_encryption(),
+            &Some(ServerSideEncryption::AwsKms)
+        );
+        assert_eq!(put.get_ssekms_key_id().as_deref(), Some(key_id));
+        assert_eq!(put.get_tagging().as_deref(), Some(tagging));
+
+        let create = options.apply_to_create_multipart(client.create_multipart_upload());
+        let create = create.as_input();
+        assert_eq!(
+            create.get_server_side_encryption(),
+            &Some(ServerSideEncryption::AwsKms)
+        );
+        assert_eq!(create.get_ssekms_key_id().as_deref(), Some(key_id));
+        assert_eq!(create.get_tagging().as_deref(), Some(tagging));
+
+        // By default, we leave encryption up to the bucket and don't tag.
+        let put = S3WriteOptions::default().apply_to_put(client.put_object());
+        let put = put.as_input();
+        assert_eq!(put.get_server_side_encryption(), &None);
+        assert_eq!(put.get_ssekms_key_id(), &None);
+        assert_eq!(put.get_tagging(), &None);
+    }
+
     #[mz_ore::test]
     fn should_multipart() {
         let config = MultipartConfig::default();
@@ -9,9 +9,10 @@
 
 //! Test utilities for injecting latency and errors.
 
+use std::collections::{BTreeMap, VecDeque};
 use std::future::Future;
 use std::sync::{Arc, Mutex};
-use std::time::{Instant, UNIX_EPOCH};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -26,12 +27,85 @@ use crate::location::{
     SeqNo, VersionedData,
 };
 
+/// An operation of [Blob] or [Consensus] into which faults may be injected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Op {
+    /// [Blob::get].
+    BlobGet,
+    /// [Blob::list_keys_and_metadata].
+    BlobListKeys,
+    /// [Blob::set].
+    BlobSet,
+    /// [Blob::delete].
+    BlobDelete,
+    /// [Blob::restore].
+    BlobRestore,
+    /// [Consensus::head].
+    ConsensusHead,
+    /// [Consensus::compare_and_set].
+    ConsensusCompareAndSet,
+    /// [Consensus::scan].
+    ConsensusScan,
+    /// [Consensus::truncate].
+    ConsensusTruncate,
+    /// [Consensus::delete].
+    ConsensusDelete,
+}
+
+impl Op {
+    /// All [Blob] operations.
+    pub const BLOB: &'static [Op] = &[
+        Op::BlobGet,
+        Op::BlobListKeys,
+        Op::BlobSet,
+        Op::BlobDelete,
+        Op::BlobRestore,
+    ];
+
+    /// All [Consensus] operations.
+    pub const CONSENSUS: &'static [Op] = &[
+        Op::ConsensusHead,
+        Op::ConsensusCompareAndSet,
+        Op::ConsensusScan,
+        Op::ConsensusTruncate,
+        Op::ConsensusDelete,
+    ];
+}
+
+/// A fault to inject into a single operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Fail with a [Determinate] error without running the operation.
+    Determinate,
+    /// Fail with an [Indeterminate] error without running the operation.
+    Indeterminate,
+    /// Run the operation, but then fail with an [Indeterminate] error, as if
+    /// the response had been lost.
+    IndeterminateAfterRun,
+}
+
 #[derive(Debug)]
 struct UnreliableCore {
     rng: SmallRng,
     should_happen: f64,
     should_timeout: f64,
-    // TODO: Delays, what else?
+    latency: Duration,
+    op_probabilities: BTreeMap<Op, (f64, Fault)>,
+    op_latencies: BTreeMap<Op, Duration>,
+    op_scripted: BTreeMap<Op, VecDeque<Fault>>,
+    injected: BTreeMap<Op, u64>,
+}
+
+impl UnreliableCore {
+    /// Returns the fault configured specifically for the next call of `op`,
+    /// if any.
+    fn next_op_fault(&mut self, op: Op) -> Option<Fault> {
+        if let Some(fault) = self.op_scripted.get_mut(&op).and_then(|x| x.pop_front()) {
+            return Some(fault);
+        }
+        let (probability, fault) = *self.op_probabilities.get(&op)?;
+        self.rng.gen_bool(probability).then_some(fault)
+    }
 }
 
 /// A handle for controlling the behavior of an unreliable delegate.
@@ -60,6 +134,11 @@ impl UnreliableHandle {
             rng: SmallRng::seed_from_u64(seed),
             should_happen,
             should_timeout,
+            latency: Duration::ZERO,
+            op_probabilities: BTreeMap::new(),
+            op_latencies: BTreeMap::new(),
+            op_scripted: BTreeMap::new(),
+            injected: BTreeMap::new(),
         };
         UnreliableHandle {
             core: Arc::new(Mutex::new(core)),
@@ -87,6 +166,64 @@ impl UnreliableHandle {
         self.partially_available(1.0, 0.0);
     }
 
+    /// Cause all later calls to be delayed by `latency` before they run.
+    pub fn set_latency(&self, latency: Duration) {
+        let mut core = self.core.lock().expect("mutex poisoned");
+        core.latency = latency;
+    }
+
+    /// Cause later calls of `op` to fail with `fault` with the given
+    /// probability, instead of as configured for all calls.
+    ///
+    /// Scripted failures, from [Self::fail_next], take precedence.
+    pub fn fail_randomly(&self, op: Op, probability: f64, fault: Fault) {
+        assert!(probability >= 0.0);
+        assert!(probability <= 1.0);
+        let mut core = self.core.lock().expect("mutex poisoned");
+        core.op_probabilities.insert(op, (probability, fault));
+    }
+
+    /// Cause the next `n` calls of `op` to fail with `fault`, after any
+    /// previously scripted failures.
+    pub fn fail_next(&self, op: Op, n: usize, fault: Fault) {
+        let mut core = self.core.lock().expect("mutex poisoned");
+        core.op_scripted
+            .entry(op)
+            .or_default()
+            .extend(std::iter::repeat(fault).take(n));
+    }
+
+    /// Cause later calls of `op` to be delayed by `latency` before they run,
+    /// instead of by the latency configured for all calls.
+    pub fn set_op_latency(&self, op: Op, latency: Duration) {
+        let mut core = self.core.lock().expect("mutex poisoned");
+        core.op_latencies.insert(op, latency);
+    }
+
+    /// Stop injecting any faults or latency configured for individual
+    /// operations, including scripted failures that haven't happened yet.
+    ///
+    /// The behavior configured for all calls and the counts of injected faults
+    /// are unaffected.
+    pub fn clear_op_faults(&self) {
+        let mut core = self.core.lock().expect("mutex poisoned");
+        core.op_probabilities.clear();
+        core.op_latencies.clear();
+        core.op_scripted.clear();
+    }
+
+    /// Returns the number of errors injected into calls of `op`.
+    pub fn injected(&self, op: Op) -> u64 {
+        let core = self.core.lock().expect("mutex poisoned");
+        core.injected.get(&op).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of errors injected into calls of any operation.
+    pub fn injected_total(&self) -> u64 {
+        let core = self.core.lock().expect("mutex poisoned");
+        core.injected.values().sum()
+    }
+
     fn should_happen(&self) -> bool {
         let mut core = self.core.lock().expect("mutex poisoned");
         let should_happen = core.should_happen;
@@ -99,26 +236,49 @@ impl UnreliableHandle {
         core.rng.gen_bool(should_timeout)
     }
 
-    async fn run_op<R, F, WorkFn>(&self, name: &str, work_fn: WorkFn) -> Result<R, ExternalError>
+    async fn run_op<R, F, WorkFn>(&self, op: Op, work_fn: WorkFn) -> Result<R, ExternalError>
     where
         F: Future<Output = Result<R, ExternalError>>,
         WorkFn: FnOnce() -> F,
     {
-        let (should_happen, should_timeout) = (self.should_happen(), self.should_timeout());
-        trace!(
-            "unreliable {} should_happen={} should_timeout={}",
-            name,
-            should_happen,
-            should_timeout,
-        );
-        match (should_happen, should_timeout) {
-            (true, true) => {
+        let (op_fault, latency) = {
+            let mut core = self.core.lock().expect("mutex poisoned");
+            let latency = core.op_latencies.get(&op).copied().unwrap_or(core.latency);
+            (core.next_op_fault(op), latency)
+        };
+        let fault = match op_fault {
+            Some(fault) => Some(fault),
+            None => {
+                let (should_happen, should_timeout) = (self.should_happen(), self.should_timeout());
+                trace!(
+                    "unreliable {:?} should_happen={} should_timeout={}",
+                    op,
+                    should_happen,
+                    should_timeout,
+                );
+                match (should_happen, should_timeout) {
+                    (true, true) => Some(Fault::IndeterminateAfterRun),
+                    (true, false) => None,
+                    (false, true) => Some(Fault::Indeterminate),
+                    (false, false) => Some(Fault::Determinate),
+                }
+            }
+        };
+        if fault.is_some() {
+            let mut core = self.core.lock().expect("mutex poisoned");
+            *core.injected.entry(op).or_default() += 1;
+        }
+        if latency > Duration::ZERO {
+            tokio::time::sleep(latency).await;
+        }
+        match fault {
+            None => work_fn().await,
+            Some(Fault::IndeterminateAfterRun) => {
                 let _res = work_fn().await;
                 Err(ExternalError::new_timeout(Instant::now()))
             }
-            (true, false) => work_fn().await,
-            (false, true) => Err(ExternalError::new_timeout(Instant::now())),
-            (false, false) => Err(ExternalError::Determinate(Determinate::new(anyhow!(
+            Some(Fault::Indeterminate) => Err(ExternalError::new_timeout(Instant::now())),
+            Some(Fault::Determinate) => Err(ExternalError::Determinate(Determinate::new(anyhow!(
                 "unreliable"
             )))),
         }
@@ -142,7 +302,7 @@ impl UnreliableBlob {
 #[async_trait]
 impl Blob for UnreliableBlob {
     async fn get(&self, key: &str) -> Result<Option<SegmentedBytes>, ExternalError> {
-        self.handle.run_op("get", || self.blob.get(key)).await
+        self.handle.run_op(Op::BlobGet, || self.blob.get(key)).await
     }
 
     async fn list_keys_and_metadata(
@@ -151,7 +311,7 @@ impl Blob for UnreliableBlob {
         f: &mut (dyn FnMut(BlobMetadata) + Send + Sync),
     ) -> Result<(), ExternalError> {
         self.handle
-            .run_op("list_keys", || {
+            .run_op(Op::BlobListKeys, || {
                 self.blob.list_keys_and_metadata(key_prefix, f)
             })
             .await
@@ -159,17 +319,19 @@ impl Blob for UnreliableBlob {
 
     async fn set(&self, key: &str, value: Bytes, atomic: Atomicity) -> Result<(), ExternalError> {
         self.handle
-            .run_op("set", || self.blob.set(key, value, atomic))
+            .run_op(Op::BlobSet, || self.blob.set(key, value, atomic))
             .await
     }
 
     async fn delete(&self, key: &str) -> Result<Option<usize>, ExternalError> {
-        self.handle.run_op("delete", || self.blob.delete(key)).await
+        self.handle
+            .run_op(Op::BlobDelete, || self.blob.delete(key))
+            .await
     }
 
     async fn restore(&self, key: &str) -> Result<(), ExternalError> {
         self.handle
-            .run_op("restore", || self.blob.restore(key))
+            .run_op(Op::BlobRestore, || self.blob.restore(key))
             .await
     }
 }
@@ -197,7 +359,7 @@ impl Consensus for UnreliableConsensus {
 
     async fn head(&self, key: &str) -> Result<Option<VersionedData>, ExternalError> {
         self.handle
-            .run_op("head", || self.consensus.head(key))
+            .run_op(Op::ConsensusHead, || self.consensus.head(key))
             .await
     }
 
@@ -208,7 +370,7 @@ impl Consensus for UnreliableConsensus {
         new: VersionedData,
     ) -> Result<CaSResult, ExternalError> {
         self.handle
-            .run_op("compare_and_set", || {
+            .run_op(Op::ConsensusCompareAndSet, || {
                 self.consensus.compare_and_set(key, expected, new)
             })
             .await
@@ -221,13 +383,21 @@ impl Consensus for UnreliableConsensus {
         limit: usize,
     ) -> Result<Vec<VersionedData>, ExternalError> {
         self.handle
-            .run_op("scan", || self.consensus.scan(key, from, limit))
+            .run_op(Op::ConsensusScan, || self.consensus.scan(key, from, limit))
             .await
     }
 
     async fn truncate(&self, key: &str, seqno: SeqNo) -> Result<usize, ExternalError> {
         self.handle
-            .run_op("truncate", || self.consensus.truncate(key, seqno))
+            .run_op(Op::ConsensusTruncate, || {
+                self.consensus.truncate(key, seqno)
+            })
+            .await
+    }
+
+    async fn delete(&self, key: &str, expected: SeqNo) -> Result<CaSResult, ExternalError> {
+        self.handle
+            .run_op(Op::ConsensusDelete, || self.consensus.delete(key, expected))
             .await
     }
 }
@@ -301,4 +471,72 @@ mod tests {
         handle.totally_unavailable();
         assert!(consensus.head("key").await.is_err());
     }
+
+    #[mz_ore::test(tokio::test)]
+    #[cfg_attr(miri, ignore)] // unsupported operation: returning ready events from epoll_wait is not yet implemented
+    async fn unreliable_op_faults() {
+        let handle = UnreliableHandle::new(0, 1.0, 0.0);
+        let blob = UnreliableBlob::new(
+            Arc::new(MemBlob::open(MemBlobConfig::default())),
+            handle.clone(),
+        );
+        let consensus = UnreliableConsensus::new(Arc::new(MemConsensus::default()), handle.clone());
+        let data = |seqno| VersionedData {
+            seqno: SeqNo(seqno),
+            data: Bytes::from("data"),
+        };
+
+        // Random failures only apply to the configured op.
+        handle.fail_randomly(Op::BlobGet, 0.5, Fault::Determinate);
+        let mut succeeded = 0;
+        for _ in 0..100 {
+            if blob.get("a").await.is_ok() {
+                succeeded += 1;
+            }
+            assert!(blob.delete("a").await.is_ok());
+        }
+        // Intentionally have pretty loose bounds so this assertion doesn't
+        // become a maintenance burden if the rng impl changes.
+        assert!(succeeded > 10 && succeeded < 90, "succeeded={}", succeeded);
+        assert_eq!(handle.injected(Op::BlobGet), 100 - succeeded);
+        assert_eq!(handle.injected(Op::BlobDelete), 0);
+
+        // A failure that happens after the op ran has the op's side effects.
+        handle.clear_op_faults();
+        handle.fail_next(Op::BlobSet, 1, Fault::IndeterminateAfterRun);
+        let res = blob
+            .set("a", Bytes::from("1"), Atomicity::RequireAtomic)
+            .await;
+        assert!(matches!(res, Err(ExternalError::Indeterminate(_))));
+        let value = blob.get("a").await.expect("blob available");
+        assert_eq!(value.map(|x| x.into_contiguous()), Some(b"1".to_vec()));
+
+        // Scripted failures happen in order and take precedence over random
+        // ones.
+        handle.fail_randomly(Op::ConsensusCompareAndSet, 1.0, Fault::Determinate);
+        handle.fail_next(Op::ConsensusCompareAndSet, 2, Fault::Indeterminate);
+        handle.fail_next(Op::ConsensusCompareAndSet, 1, Fault::IndeterminateAfterRun);
+        for _ in 0..2 {
+            let res = consensus.compare_and_set("key", None, data(1)).await;
+            assert!(matches!(res, Err(ExternalError::Indeterminate(_))));
+            assert_eq!(consensus.head("key").await.expect("head"), None);
+        }
+        let res = consensus.compare_and_set("key", None, data(1)).await;
+        assert!(matches!(res, Err(ExternalError::Indeterminate(_))));
+        assert_eq!(consensus.head("key").await.expect("head"), Some(data(1)));
+        let res = consensus
+            .compare_and_set("key", Some(SeqNo(1)), data(2))
+            .await;
+        assert!(matches!(res, Err(ExternalError::Determinate(_))));
+        assert_eq!(handle.injected(Op::ConsensusCompareAndSet), 4);
+        assert_eq!(handle.injected(Op::ConsensusHead), 0);
+
+        // Once cleared, everything succeeds again.
+        handle.clear_op_faults();
+        let res = consensus
+            .compare_and_set("key", Some(SeqNo(1)), data(2))
+            .await;
+        assert_eq!(res.expect("cas"), CaSResult::Committed);
+        assert_eq!(handle.injected_total(), 100 - succeeded + 5);
+    }
 }
@@ -460,39 +460,25 @@ where
                     State::Drain => self.advance_drain().await?,
                     State::Done => return Ok(()),
                 };
-                self.adapter_client
-                    .add_idle_in_transaction_session_timeout();
             }
         }
     }
 
     #[instrument(level = "debug", skip_all)]
     async fn advance_ready(&mut self) -> Result<State, io::Error> {
-        // Handle timeouts first so we don't execute any statements when there's a pending timeout.
-        let message = select! {
-            biased;
-
-            // `recv_timeout()` is cancel-safe as per it's docs.
-            Some(timeout) = self.adapter_client.recv_timeout() => {
-                let err: AdapterError = timeout.into();
-                let conn_id = self.adapter_client.session().conn_id();
-                tracing::warn!("session timed out, conn_id {}", conn_id);
-
-                // Process the error, doing any state cleanup.
-                let error_response = err.into_response(Severity::Fatal);
-                let error_state = self.error(error_response).await;
-
-                // Terminate __after__ we do any cleanup.
-                self.adapter_client.terminate().await;
-
-                // We must wait for the client to send a request before we can send the error response.
-                // Due to the PG wire protocol, we can't send an ErrorResponse unless it is in response
-                // to a client message.
-                let _ = self.conn.recv().await?;
-                return error_state;
-            },
-            // `recv()` is cancel-safe as per it's docs.
-            message = self.conn.recv() => message?,
+        // Forward notices that arrive while the session is idle, like the one
+        // about its transaction being rolled back by the idle in transaction
+        // timeout, without waiting for the client's next message.
+        let message = loop {
+            select! {
+                // `recv()` is cancel-safe as per it's docs.
+                message = self.conn.recv() => break message?,
+                // `recv_notice()` is cancel-safe as per it's docs.
+                notice = self.adapter_client.session().recv_notice() => {
+                    self.send(notice.into_response()).await?;
+                    self.conn.flush().await?;
+                }
+            }
         };
 
         self.adapter_client
@@ -594,10 +580,6 @@ where
 
     async fn advance_drain(&mut self) -> Result<State, io::Error> {
         let message = self.conn.recv().await?;
-        if message.is_some() {
-            self.adapter_client
-                .remove_idle_in_transaction_session_timeout();
-        }
         match message {
             Some(FrontendMessage::Sync) => self.sync().await,
             None => Ok(State::Done),
@@ -1346,7 +1328,11 @@ where
     async fn ready(&mut self) -> Result<State, io::Error> {
         let txn_state = self.adapter_client.session().transaction().into();
         self.send(BackendMessage::ReadyForQuery(txn_state)).await?;
-        self.flush().await
+        let state = self.flush().await?;
+        // The session is idle from here until the client's next message.
+        self.adapter_client
+            .add_idle_in_transaction_session_timeout();
+        Ok(state)
     }
 
     // Converts a RowsFuture to a stream while also checking for connection close.
@@ -1539,7 +1525,7 @@ where
                         row_desc,
                         portal_name,
                         InProgressRows::new(RecordFirstRowStream::new(
-                            Box::new(UnboundedReceiverStream::new(rx)),
+                            Box::new(rx),
                             execute_started,
                             &self.adapter_client,
                         )),
@@ -1583,7 +1569,7 @@ where
                                 format,
                                 row_desc,
                                 RecordFirstRowStream::new(
-                                    Box::new(UnboundedReceiverStream::new(rx)),
+                                    Box::new(rx),
                                     execute_started,
                                     &self.adapter_client,
                                 ),
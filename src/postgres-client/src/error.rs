@@ -63,6 +63,10 @@ impl From<deadpool_postgres::tokio_postgres::Error> for PostgresError {
             &deadpool_postgres::tokio_postgres::error::SqlState::T_R_SERIALIZATION_FAILURE => {
                 PostgresError::Determinate(anyhow::Error::new(e))
             }
+            // A statement canceled by `statement_timeout` is rolled back.
+            &deadpool_postgres::tokio_postgres::error::SqlState::QUERY_CANCELED => {
+                PostgresError::Determinate(anyhow::Error::new(e))
+            }
             _ => PostgresError::Indeterminate(anyhow::Error::new(e)),
         }
     }
@@ -28,7 +28,7 @@ use std::time::{Duration, Instant};
 use deadpool_postgres::tokio_postgres::Config;
 use deadpool_postgres::{
     Hook, HookError, HookErrorCause, Manager, ManagerConfig, Object, Pool, PoolError,
-    RecyclingMethod, Runtime, Status,
+    RecyclingMethod, Runtime, Status, Timeouts,
 };
 use mz_ore::cast::{CastFrom, CastLossy};
 use mz_ore::now::SYSTEM_TIME;
@@ -53,6 +53,12 @@ pub trait PostgresClientKnobs: std::fmt::Debug + Send + Sync {
     fn connect_timeout(&self) -> Duration;
     /// TCP user timeout for connection attempts.
     fn tcp_user_timeout(&self) -> Duration;
+    /// Maximum time a statement may run before Postgres cancels it, applied to
+    /// connections as they are created. Zero disables the timeout.
+    fn statement_timeout(&self) -> Duration;
+    /// Idle time before TCP keepalives are sent on a connection, if any. If
+    /// unset, the driver's default is used.
+    fn tcp_keepalive(&self) -> Option<Duration>;
 }
 
 /// Configuration for creating a [PostgresClient].
@@ -81,6 +87,7 @@ impl PostgresClientConfig {
 /// A Postgres client wrapper that uses deadpool as a connection pool.
 pub struct PostgresClient {
     pool: Pool,
+    knobs: Arc<dyn PostgresClientKnobs>,
     metrics: PostgresClientMetrics,
 }
 
@@ -96,6 +103,10 @@ impl PostgresClient {
         let mut pg_config: Config = config.url.parse()?;
         pg_config.connect_timeout(config.knobs.connect_timeout());
         pg_config.tcp_user_timeout(config.knobs.tcp_user_timeout());
+        if let Some(keepalive) = config.knobs.tcp_keepalive() {
+            pg_config.keepalives(true);
+            pg_config.keepalives_idle(keepalive);
+        }
 
         let tls = mz_tls_util::make_tls(&pg_config).map_err(|tls_err| match tls_err {
             mz_tls_util::TlsError::Generic(e) => PostgresError::Indeterminate(e),
@@ -113,20 +124,27 @@ impl PostgresClient {
         let last_ttl_connection = AtomicU64::new(0);
         let connections_created = config.metrics.connpool_connections_created.clone();
         let ttl_reconnections = config.metrics.connpool_ttl_reconnections.clone();
-        let builder = Pool::builder(manager);
-        let builder = match config.knobs.connection_pool_max_wait() {
-            None => builder,
-            Some(wait) => builder.wait_timeout(Some(wait)).runtime(Runtime::Tokio1),
-        };
-        let pool = builder
+        let knobs = Arc::clone(&config.knobs);
+        let hook_knobs = Arc::clone(&config.knobs);
+        // The wait timeout is passed on every acquisition instead of being set
+        // here, so that changes to it take effect, but the runtime is needed
+        // for any timeouts to work at all.
+        let pool = Pool::builder(manager)
+            .runtime(Runtime::Tokio1)
             .max_size(config.knobs.connection_pool_max_size())
             .post_create(Hook::async_fn(move |client, _| {
                 connections_created.inc();
+                let statement_timeout = hook_knobs.statement_timeout();
                 Box::pin(async move {
                     debug!("opened new consensus postgres connection");
-                    client.batch_execute(
-                        "SET SESSION CHARACTERISTICS AS TRANSACTION ISOLATION LEVEL SERIALIZABLE",
-                    ).await.map_err(|e| HookError::Abort(HookErrorCause::Backend(e)))
+                    client
+                        .batch_execute(&format!(
+                            "SET SESSION CHARACTERISTICS AS TRANSACTION ISOLATION LEVEL SERIALIZABLE; \
+                             SET statement_timeout = {}",
+                            statement_timeout.as_millis()
+                        ))
+                        .await
+                        .map_err(|e| HookError::Abort(HookErrorCause::Backend(e)))
                 })
             }))
             .pre_recycle(Hook::sync_fn(move |_client, conn_metrics| {
@@ -163,6 +181,7 @@ impl PostgresClient {
 
         Ok(PostgresClient {
             pool,
+            knobs,
             metrics: config.metrics,
         })
     }
@@ -172,22 +191,50 @@ impl PostgresClient {
             .connpool_available
             .set(f64::cast_lossy(status.available));
         self.metrics.connpool_size.set(u64::cast_from(status.size));
-        // Don't bother reporting the maximum size of the pool... we know that from config.
+        self.metrics
+            .connpool_max_size
+            .set(u64::cast_from(status.max_size));
+        // A negative number of available connections is the number of callers
+        // waiting for one.
+        let available = usize::try_from(status.available).unwrap_or(0);
+        let waiting = usize::try_from(status.available.saturating_neg()).unwrap_or(0);
+        self.metrics
+            .connpool_in_use
+            .set(u64::cast_from(status.size.saturating_sub(available)));
+        self.metrics.connpool_waiting.set(u64::cast_from(waiting));
     }
 
     /// Gets connection from the pool or waits for one to become available.
     pub async fn get_connection(&self) -> Result<Object, PoolError> {
         let start = Instant::now();
+        // Pick up any change to the maximum size of the pool.
+        let max_size = self.knobs.connection_pool_max_size();
         // note that getting the pool size here requires briefly locking the pool
+        let status = self.pool.status();
+        if status.max_size != max_size {
+            debug!(
+                "resizing consensus postgres connection pool from {} to {}",
+                status.max_size, max_size
+            );
+            self.pool.resize(max_size);
+        }
         self.status_metrics(self.pool.status());
-        let res = self.pool.get().await;
-        if let Err(PoolError::Backend(err)) = &res {
-            debug!("error establishing connection: {}", err);
-            self.metrics.connpool_connection_errors.inc();
+        let timeouts = Timeouts {
+            wait: self.knobs.connection_pool_max_wait(),
+            ..self.pool.timeouts()
+        };
+        let res = self.pool.timeout_get(&timeouts).await;
+        match &res {
+            Err(PoolError::Backend(err)) => {
+                debug!("error establishing connection: {}", err);
+                self.metrics.connpool_connection_errors.inc();
+            }
+            Err(PoolError::Timeout(_)) => self.metrics.connpool_acquire_timeouts.inc(),
+            _ => {}
         }
-        self.metrics
-            .connpool_acquire_seconds
-            .inc_by(start.elapsed().as_secs_f64());
+        let elapsed = start.elapsed().as_secs_f64();
+        self.metrics.connpool_acquire_seconds.inc_by(elapsed);
+        self.metrics.connpool_acquire_wait_seconds.observe(elapsed);
         self.metrics.connpool_acquires.inc();
         self.status_metrics(self.pool.status());
         res
@@ -11,14 +11,21 @@
 
 use mz_ore::metric;
 use mz_ore::metrics::{Counter, IntCounter, MetricsRegistry, UIntGauge};
+use mz_ore::stats::histogram_seconds_buckets;
+use prometheus::Histogram;
 
 /// Metrics specific to [PostgresClient](crate::PostgresClient)'s internal
 /// workings.
 #[derive(Debug, Clone)]
 pub struct PostgresClientMetrics {
     pub(crate) connpool_size: UIntGauge,
+    pub(crate) connpool_max_size: UIntGauge,
+    pub(crate) connpool_in_use: UIntGauge,
+    pub(crate) connpool_waiting: UIntGauge,
     pub(crate) connpool_acquires: IntCounter,
     pub(crate) connpool_acquire_seconds: Counter,
+    pub(crate) connpool_acquire_wait_seconds: Histogram,
+    pub(crate) connpool_acquire_timeouts: IntCounter,
     pub(crate) connpool_available: prometheus::Gauge,
     pub(crate) connpool_connections_created: Counter,
     pub(crate) connpool_connection_errors: Counter,
@@ -33,6 +40,18 @@ impl PostgresClientMetrics {
                 name: format!("{}_postgres_connpool_size", prefix),
                 help: "number of connections currently in pool",
             )),
+            connpool_max_size: registry.register(metric!(
+                name: format!("{}_postgres_connpool_max_size", prefix),
+                help: "maximum number of connections allowed in pool",
+            )),
+            connpool_in_use: registry.register(metric!(
+                name: format!("{}_postgres_connpool_in_use", prefix),
+                help: "number of connections currently acquired from pool",
+            )),
+            connpool_waiting: registry.register(metric!(
+                name: format!("{}_postgres_connpool_waiting", prefix),
+                help: "number of callers waiting to acquire a connection from pool",
+            )),
             connpool_acquires: registry.register(metric!(
                 name: format!("{}_postgres_connpool_acquires", prefix),
                 help: "times a connection has been acquired from pool",
@@ -41,6 +60,15 @@ impl PostgresClientMetrics {
                 name: format!("{}_postgres_connpool_acquire_seconds", prefix),
                 help: "time spent acquiring connections from pool",
             )),
+            connpool_acquire_wait_seconds: registry.register(metric!(
+                name: format!("{}_postgres_connpool_acquire_wait_seconds", prefix),
+                help: "histogram of time spent acquiring a connection from pool",
+                buckets: histogram_seconds_buckets(0.000_128, 32.0),
+            )),
+            connpool_acquire_timeouts: registry.register(metric!(
+                name: format!("{}_postgres_connpool_acquire_timeouts", prefix),
+                help: "times acquiring a connection from pool timed out",
+            )),
             connpool_available: registry.register(metric!(
                 name: format!("{}_postgres_connpool_available", prefix),
                 help: "available connections in the pool",
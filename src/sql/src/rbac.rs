@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::iter;
 
 use itertools::Itertools;
@@ -152,6 +153,100 @@ impl UnauthorizedError {
     }
 }
 
+/// The trail of checks performed while authorizing a statement.
+///
+/// A trail is only collected when the `rbac_debug` session variable is on. Objects and roles are
+/// identified by name only, so a trail never contains the contents of secrets or any part of the
+/// statement being authorized.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RbacTrace {
+    /// The checks performed, in the order that they were performed. Authorization stops at the
+    /// first failed privilege check, while all required role memberships and ownerships are
+    /// checked before any of them is reported as failed.
+    pub checks: Vec<RbacCheck>,
+}
+
+impl RbacTrace {
+    fn push(
+        &mut self,
+        catalog: &impl SessionCatalog,
+        requirement: RbacRequirement,
+        role_id: &RoleId,
+        passed: bool,
+    ) {
+        self.checks.push(RbacCheck {
+            requirement,
+            role_name: role_name(catalog, role_id),
+            passed,
+        });
+    }
+}
+
+impl fmt::Display for RbacTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.checks.iter().join("\n"))
+    }
+}
+
+/// A single check performed while authorizing a statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RbacCheck {
+    /// What the check required.
+    pub requirement: RbacRequirement,
+    /// The name of the role that the check was evaluated for.
+    pub role_name: String,
+    /// Whether the role satisfied the requirement.
+    pub passed: bool,
+}
+
+impl fmt::Display for RbacCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = if self.passed { "passed" } else { "denied" };
+        write!(
+            f,
+            "{result}: {} for role {}",
+            self.requirement,
+            self.role_name.quoted()
+        )
+    }
+}
+
+/// A requirement checked while authorizing a statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RbacRequirement {
+    /// Membership in a role.
+    RoleMembership { role_name: String },
+    /// Ownership of an object.
+    Ownership {
+        object_description: ErrorMessageObjectDescription,
+    },
+    /// One or more privileges on an object.
+    Privilege {
+        acl_mode: AclMode,
+        object_description: ErrorMessageObjectDescription,
+    },
+    /// Superuser attributes.
+    Superuser { action: String },
+}
+
+impl fmt::Display for RbacRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RbacRequirement::RoleMembership { role_name } => {
+                write!(f, "membership in role {}", role_name.quoted())
+            }
+            RbacRequirement::Ownership { object_description } => {
+                write!(f, "ownership of {object_description}")
+            }
+            RbacRequirement::Privilege {
+                acl_mode,
+                object_description,
+            } => write!(f, "{} on {object_description}", acl_mode.to_error_string()),
+            RbacRequirement::Superuser { action } => write!(f, "superuser to {action}"),
+        }
+    }
+}
+
 /// RBAC requirements for executing a given plan.
 #[derive(Debug)]
 struct RbacRequirements {
@@ -178,6 +273,7 @@ impl RbacRequirements {
         role_metadata: &RoleMetadata,
         session_vars: &SessionVars,
         resolved_ids: &ResolvedIds,
+        mut trace: Option<&mut RbacTrace>,
     ) -> Result<(), UnauthorizedError> {
         // Obtain all roles that the current session is a member of.
         let role_membership = catalog.collect_role_membership(&role_metadata.current_role);
@@ -188,21 +284,25 @@ impl RbacRequirements {
             session_vars,
             resolved_ids,
             self.item_usage,
+            trace.as_deref_mut(),
         )?;
 
         // Validate that the current session has the required role membership to execute the provided
         // plan.
+        if let Some(trace) = trace.as_deref_mut() {
+            for role_id in &self.role_membership {
+                let requirement = RbacRequirement::RoleMembership {
+                    role_name: role_name(catalog, role_id),
+                };
+                let passed = role_membership.contains(role_id);
+                trace.push(catalog, requirement, &role_metadata.current_role, passed);
+            }
+        }
         let unheld_membership: Vec<_> = self.role_membership.difference(&role_membership).collect();
         if !unheld_membership.is_empty() {
             let role_names = unheld_membership
                 .into_iter()
-                .map(|role_id| {
-                    // Some role references may no longer exist due to concurrent drops.
-                    catalog
-                        .try_get_role(role_id)
-                        .map(|role| role.name().to_string())
-                        .unwrap_or(role_id.to_string())
-                })
+                .map(|role_id| role_name(catalog, role_id))
                 .collect();
             return Err(UnauthorizedError::RoleMembership { role_names });
         }
@@ -212,7 +312,18 @@ impl RbacRequirements {
         let unheld_ownership = self
             .ownership
             .into_iter()
-            .filter(|ownership| !check_owner_roles(ownership, &role_membership, catalog))
+            .filter(|ownership| {
+                let passed = check_owner_roles(ownership, &role_membership, catalog);
+                if let Some(trace) = trace.as_deref_mut() {
+                    let requirement = RbacRequirement::Ownership {
+                        object_description: ErrorMessageObjectDescription::from_id(
+                            ownership, catalog,
+                        ),
+                    };
+                    trace.push(catalog, requirement, &role_metadata.current_role, passed);
+                }
+                !passed
+            })
             .collect();
         ownership_err(unheld_ownership, catalog)?;
 
@@ -221,9 +332,16 @@ impl RbacRequirements {
             self.privileges,
             role_membership,
             role_metadata.current_role,
+            trace.as_deref_mut(),
         )?;
 
         if let Some(action) = self.superuser_action {
+            if let Some(trace) = trace {
+                let requirement = RbacRequirement::Superuser {
+                    action: action.clone(),
+                };
+                trace.push(catalog, requirement, &role_metadata.current_role, false);
+            }
             return Err(UnauthorizedError::Superuser { action });
         }
 
@@ -244,12 +362,15 @@ impl Default for RbacRequirements {
 }
 
 /// Checks if a `session` is authorized to use `resolved_ids`. If not, an error is returned.
+///
+/// If `trace` is provided, every check performed is recorded in it.
 pub fn check_usage(
     catalog: &impl SessionCatalog,
     role_metadata: &RoleMetadata,
     session_vars: &SessionVars,
     resolved_ids: &ResolvedIds,
     item_types: &BTreeSet<CatalogItemType>,
+    trace: Option<&mut RbacTrace>,
 ) -> Result<(), UnauthorizedError> {
     rbac_preamble!(catalog, role_metadata, session_vars);
 
@@ -279,12 +400,39 @@ pub fn check_usage(
         required_privileges,
         role_membership,
         role_metadata.current_role,
+        trace,
     )?;
 
     Ok(())
 }
 
+/// Checks if a session is authorized to read from the relations in `ids`, as if it were
+/// selecting from them. If not, an error is returned.
+///
+/// If `trace` is provided, every check performed is recorded in it.
+pub fn check_read(
+    catalog: &impl SessionCatalog,
+    role_metadata: &RoleMetadata,
+    session_vars: &SessionVars,
+    ids: impl Iterator<Item = GlobalId>,
+    trace: Option<&mut RbacTrace>,
+) -> Result<(), UnauthorizedError> {
+    rbac_preamble!(catalog, role_metadata, session_vars);
+
+    let role_membership = catalog.collect_role_membership(&role_metadata.current_role);
+    let required_privileges = generate_read_privileges(catalog, ids, role_metadata.current_role);
+    check_object_privileges(
+        catalog,
+        required_privileges,
+        role_membership,
+        role_metadata.current_role,
+        trace,
+    )
+}
+
 /// Checks if a session is authorized to execute a plan. If not, an error is returned.
+///
+/// If `trace` is provided, every check performed is recorded in it.
 pub fn check_plan(
     catalog: &impl SessionCatalog,
     // Map from connection IDs to authenticated roles. The roles may have been dropped concurrently.
@@ -294,6 +442,7 @@ pub fn check_plan(
     plan: &Plan,
     target_cluster_id: Option<ClusterId>,
     resolved_ids: &ResolvedIds,
+    trace: Option<&mut RbacTrace>,
 ) -> Result<(), UnauthorizedError> {
     rbac_preamble!(catalog, role_metadata, session_vars);
 
@@ -305,7 +454,7 @@ pub fn check_plan(
         role_metadata.current_role,
     );
     debug!("rbac requirements {rbac_requirements:?} for plan {plan:?}");
-    rbac_requirements.validate(catalog, role_metadata, session_vars, resolved_ids)
+    rbac_requirements.validate(catalog, role_metadata, session_vars, resolved_ids, trace)
 }
 
 /// Returns true if RBAC is turned on for a session, false otherwise.
@@ -1365,6 +1514,15 @@ fn generate_rbac_requirements(
     }
 }
 
+/// Returns the name of a role, or its ID if the role no longer exists.
+fn role_name(catalog: &impl SessionCatalog, role_id: &RoleId) -> String {
+    // Some role references may no longer exist due to concurrent drops.
+    catalog
+        .try_get_role(role_id)
+        .map(|role| role.name().to_string())
+        .unwrap_or(role_id.to_string())
+}
+
 /// Reports whether any role has ownership over an object.
 fn check_owner_roles(
     object_id: &ObjectId,
@@ -1557,6 +1715,7 @@ fn check_object_privileges(
     privileges: Vec<(SystemObjectId, AclMode, RoleId)>,
     role_membership: BTreeSet<RoleId>,
     current_role_id: RoleId,
+    mut trace: Option<&mut RbacTrace>,
 ) -> Result<(), UnauthorizedError> {
     let mut role_memberships: BTreeMap<RoleId, BTreeSet<RoleId>> = BTreeMap::new();
     role_memberships.insert(current_role_id, role_membership);
@@ -1572,7 +1731,15 @@ fn check_object_privileges(
             .flat_map(|role_id| object_privileges.get_acl_items_for_grantee(role_id))
             .map(|mz_acl_item| mz_acl_item.acl_mode)
             .fold(AclMode::empty(), |accum, acl_mode| accum.union(acl_mode));
-        if !role_privileges.contains(acl_mode) {
+        let passed = role_privileges.contains(acl_mode);
+        if let Some(trace) = trace.as_deref_mut() {
+            let requirement = RbacRequirement::Privilege {
+                acl_mode,
+                object_description: ErrorMessageObjectDescription::from_sys_id(&object_id, catalog),
+            };
+            trace.push(catalog, requirement, &role_id, passed);
+        }
+        if !passed {
             return Err(UnauthorizedError::Privilege {
                 object_description: ErrorMessageObjectDescription::from_sys_id(&object_id, catalog),
             });
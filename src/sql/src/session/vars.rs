@@ -360,7 +360,7 @@ const IDLE_IN_TRANSACTION_SESSION_TIMEOUT: ServerVar<Duration> = ServerVar {
     value: Duration::from_secs(60 * 2),
     description:
         "Sets the maximum allowed duration that a session can sit idle in a transaction before \
-         being terminated. If this value is specified without units, it is taken as milliseconds. \
+         it is rolled back. If this value is specified without units, it is taken as milliseconds. \
          A value of zero disables the timeout (PostgreSQL).",
     internal: false,
 };
@@ -1233,6 +1233,79 @@ const COMPUTE_DATAFLOW_MAX_INFLIGHT_BYTES: ServerVar<Option<usize>> = ServerVar
     internal: true,
 };
 
+const COMPUTE_REPLICA_RESTART_BACKOFF_BASE: ServerVar<Duration> = ServerVar {
+    name: UncasedStr::new("compute_replica_restart_backoff_base"),
+    value: Duration::from_secs(1),
+    description: "The initial delay before the compute controller restarts a failed replica. \
+                  Doubles with each consecutive failure (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_REPLICA_RESTART_BACKOFF_CAP: ServerVar<Duration> = ServerVar {
+    name: UncasedStr::new("compute_replica_restart_backoff_cap"),
+    value: Duration::from_secs(60),
+    description: "The maximum delay before the compute controller restarts a failed replica \
+                  (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_SUBSCRIBE_MAX_BUFFERED_BYTES: ServerVar<Option<usize>> = ServerVar {
+    name: UncasedStr::new("compute_subscribe_max_buffered_bytes"),
+    value: None,
+    description: "The maximum number of bytes of SUBSCRIBE output that may be buffered without \
+                  being drained before the SUBSCRIBE is terminated (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_SUBSCRIBE_MAX_LAG: ServerVar<Option<Duration>> = ServerVar {
+    name: UncasedStr::new("compute_subscribe_max_lag"),
+    value: None,
+    description: "The maximum duration the frontier of a SUBSCRIBE may lag behind the frontier \
+                  of its inputs before the SUBSCRIBE is terminated (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_SUBSCRIBE_MAX_BATCH_ROWS: ServerVar<Option<usize>> = ServerVar {
+    name: UncasedStr::new("compute_subscribe_max_batch_rows"),
+    value: None,
+    description: "The maximum number of updates in a batch of SUBSCRIBE output passed on by the \
+                  compute controller. Larger batches are split (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_SUBSCRIBE_MAX_BATCH_BYTES: ServerVar<Option<usize>> = ServerVar {
+    name: UncasedStr::new("compute_subscribe_max_batch_bytes"),
+    value: None,
+    description: "The maximum size in bytes of a batch of SUBSCRIBE output passed on by the \
+                  compute controller. Larger batches are split (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_INTROSPECTION_SHEDDING_BACKLOG: ServerVar<Option<usize>> = ServerVar {
+    name: UncasedStr::new("compute_introspection_shedding_backlog"),
+    value: None,
+    description: "The number of pending introspection updates above which the compute controller \
+                  sheds updates of low-priority introspection collections (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_INTROSPECTION_SHEDDING_DROP: ServerVar<bool> = ServerVar {
+    name: UncasedStr::new("compute_introspection_shedding_drop"),
+    value: false,
+    description: "Whether the compute controller drops shed introspection updates, rather than \
+                  deferring them until the backlog has cleared (Materialize).",
+    internal: true,
+};
+
+const COMPUTE_HISTORY_REDUCE_THRESHOLD: ServerVar<Option<usize>> = ServerVar {
+    name: UncasedStr::new("compute_history_reduce_threshold"),
+    value: None,
+    description: "The number of commands the compute controller's command history may grow by \
+                  before it is reduced, in addition to reducing it whenever it doubles \
+                  (Materialize).",
+    internal: true,
+};
+
 /// The maximum number of in-flight bytes emitted by persist_sources feeding _storage
 /// dataflows_.
 /// Currently defaults to 256MiB = 268435456 bytes
@@ -1475,6 +1548,14 @@ pub const ENABLE_SESSION_RBAC_CHECKS: ServerVar<bool> = ServerVar {
     internal: false,
 };
 
+static RBAC_DEBUG: ServerVar<bool> = ServerVar {
+    name: UncasedStr::new("rbac_debug"),
+    value: false,
+    description: "Boolean flag indicating whether to report the RBAC checks performed for \
+    statements that are denied (Materialize).",
+    internal: false,
+};
+
 pub const EMIT_INTROSPECTION_QUERY_NOTICE: ServerVar<bool> = ServerVar {
     name: UncasedStr::new("emit_introspection_query_notice"),
     value: true,
@@ -1606,6 +1687,23 @@ pub const MAX_CONNECTIONS: ServerVar<u32> = ServerVar {
     internal: false,
 };
 
+pub const MAX_PREPARED_STATEMENTS_PER_SESSION: ServerVar<usize> = ServerVar {
+    name: UncasedStr::new("max_prepared_statements_per_session"),
+    value: 10_000,
+    description: "The maximum number of named prepared statements, and separately of named \
+    portals, a session may hold (Materialize).",
+    internal: false,
+};
+
+pub const ENABLE_PREPARED_STATEMENT_EVICTION: ServerVar<bool> = ServerVar {
+    name: UncasedStr::new("enable_prepared_statement_eviction"),
+    value: false,
+    description:
+        "Whether a session that reaches max_prepared_statements_per_session evicts its \
+    least recently used prepared statement or portal, rather than returning an error (Materialize).",
+    internal: true,
+};
+
 /// Controls [`mz_storage_types::parameters::StorageParameters::keep_n_source_status_history_entries`].
 const KEEP_N_SOURCE_STATUS_HISTORY_ENTRIES: ServerVar<usize> = ServerVar {
     name: UncasedStr::new("keep_n_source_status_history_entries"),
@@ -2413,6 +2511,7 @@ impl SessionVars {
             .with_var(&EMIT_TRACE_ID_NOTICE)
             .with_var(&AUTO_ROUTE_INTROSPECTION_QUERIES)
             .with_var(&ENABLE_SESSION_RBAC_CHECKS)
+            .with_var(&RBAC_DEBUG)
             .with_feature_gated_var(
                 &ENABLE_SESSION_CARDINALITY_ESTIMATES,
                 &ENABLE_CARDINALITY_ESTIMATES,
@@ -2819,6 +2918,11 @@ impl SessionVars {
         *self.expect_value(&ENABLE_SESSION_RBAC_CHECKS)
     }
 
+    /// Returns the value of `rbac_debug` configuration parameter.
+    pub fn rbac_debug(&self) -> bool {
+        *self.expect_value(&RBAC_DEBUG)
+    }
+
     /// Returns the value of `enable_session_cardinality_estimates` configuration parameter.
     pub fn enable_session_cardinality_estimates(&self) -> bool {
         *self.expect_value(&ENABLE_SESSION_CARDINALITY_ESTIMATES)
@@ -3055,6 +3159,15 @@ impl SystemVars {
             .with_var(&CRDB_CONNECT_TIMEOUT)
             .with_var(&CRDB_TCP_USER_TIMEOUT)
             .with_var(&COMPUTE_DATAFLOW_MAX_INFLIGHT_BYTES)
+            .with_var(&COMPUTE_REPLICA_RESTART_BACKOFF_BASE)
+            .with_var(&COMPUTE_REPLICA_RESTART_BACKOFF_CAP)
+            .with_var(&COMPUTE_SUBSCRIBE_MAX_BUFFERED_BYTES)
+            .with_var(&COMPUTE_SUBSCRIBE_MAX_LAG)
+            .with_var(&COMPUTE_SUBSCRIBE_MAX_BATCH_ROWS)
+            .with_var(&COMPUTE_SUBSCRIBE_MAX_BATCH_BYTES)
+            .with_var(&COMPUTE_INTROSPECTION_SHEDDING_BACKLOG)
+            .with_var(&COMPUTE_INTROSPECTION_SHEDDING_DROP)
+            .with_var(&COMPUTE_HISTORY_REDUCE_THRESHOLD)
             .with_var(&STORAGE_DATAFLOW_MAX_INFLIGHT_BYTES)
             .with_var(&STORAGE_DATAFLOW_MAX_INFLIGHT_BYTES_TO_CLUSTER_SIZE_FRACTION)
             .with_var(&STORAGE_DATAFLOW_MAX_INFLIGHT_BYTES_DISK_ONLY)
@@ -3103,6 +3216,11 @@ impl SystemVars {
             .with_var(&KAFKA_PROGRESS_RECORD_FETCH_TIMEOUT)
             .with_var(&ENABLE_LAUNCHDARKLY)
             .with_var(&MAX_CONNECTIONS)
+            .with_value_constrained_var(
+                &MAX_PREPARED_STATEMENTS_PER_SESSION,
+                ValueConstraint::Domain(&UsizeInRange(1..)),
+            )
+            .with_var(&ENABLE_PREPARED_STATEMENT_EVICTION)
             .with_var(&KEEP_N_SOURCE_STATUS_HISTORY_ENTRIES)
             .with_var(&KEEP_N_SINK_STATUS_HISTORY_ENTRIES)
             .with_var(&KEEP_N_PRIVATELINK_STATUS_HISTORY_ENTRIES)
@@ -3329,6 +3447,30 @@ impl SystemVars {
             .and_then(|v| v.is_default(input))
     }
 
+    /// Checks whether [`SystemVars::set`] would accept setting the
+    /// configuration parameter named `name` to the value represented by
+    /// `input`, without modifying it.
+    ///
+    /// Note that this function does not check that the access variable should
+    /// be visible because of other settings or users. Before or after accessing
+    /// this method, you should call `Var::visible`.
+    ///
+    /// # Errors
+    ///
+    /// The call will return an error:
+    /// 1. If `name` does not refer to a valid [`SystemVars`] field.
+    /// 2. If `input` does not represent a valid [`SystemVars`] value for
+    ///    `name`.
+    pub fn validate(&self, name: &str, input: VarInput) -> Result<(), VarError> {
+        let mut var = self
+            .vars
+            .get(UncasedStr::new(name))
+            .ok_or_else(|| VarError::UnknownParameter(name.into()))?
+            .clone_var();
+        var.set(input)?;
+        Ok(())
+    }
+
     /// Sets the configuration parameter named `name` to the value represented
     /// by `value`.
     ///
@@ -3764,6 +3906,51 @@ impl SystemVars {
         *self.expect_value(&COMPUTE_DATAFLOW_MAX_INFLIGHT_BYTES)
     }
 
+    /// Returns the `compute_replica_restart_backoff_base` configuration parameter.
+    pub fn compute_replica_restart_backoff_base(&self) -> Duration {
+        *self.expect_value(&COMPUTE_REPLICA_RESTART_BACKOFF_BASE)
+    }
+
+    /// Returns the `compute_replica_restart_backoff_cap` configuration parameter.
+    pub fn compute_replica_restart_backoff_cap(&self) -> Duration {
+        *self.expect_value(&COMPUTE_REPLICA_RESTART_BACKOFF_CAP)
+    }
+
+    /// Returns the `compute_subscribe_max_buffered_bytes` configuration parameter.
+    pub fn compute_subscribe_max_buffered_bytes(&self) -> Option<usize> {
+        *self.expect_value(&COMPUTE_SUBSCRIBE_MAX_BUFFERED_BYTES)
+    }
+
+    /// Returns the `compute_subscribe_max_lag` configuration parameter.
+    pub fn compute_subscribe_max_lag(&self) -> Option<Duration> {
+        *self.expect_value(&COMPUTE_SUBSCRIBE_MAX_LAG)
+    }
+
+    /// Returns the `compute_subscribe_max_batch_rows` configuration parameter.
+    pub fn compute_subscribe_max_batch_rows(&self) -> Option<usize> {
+        *self.expect_value(&COMPUTE_SUBSCRIBE_MAX_BATCH_ROWS)
+    }
+
+    /// Returns the `compute_subscribe_max_batch_bytes` configuration parameter.
+    pub fn compute_subscribe_max_batch_bytes(&self) -> Option<usize> {
+        *self.expect_value(&COMPUTE_SUBSCRIBE_MAX_BATCH_BYTES)
+    }
+
+    /// Returns the `compute_introspection_shedding_backlog` configuration parameter.
+    pub fn compute_introspection_shedding_backlog(&self) -> Option<usize> {
+        *self.expect_value(&COMPUTE_INTROSPECTION_SHEDDING_BACKLOG)
+    }
+
+    /// Returns the `compute_introspection_shedding_drop` configuration parameter.
+    pub fn compute_introspection_shedding_drop(&self) -> bool {
+        *self.expect_value(&COMPUTE_INTROSPECTION_SHEDDING_DROP)
+    }
+
+    /// Returns the `compute_history_reduce_threshold` configuration parameter.
+    pub fn compute_history_reduce_threshold(&self) -> Option<usize> {
+        *self.expect_value(&COMPUTE_HISTORY_REDUCE_THRESHOLD)
+    }
+
     /// Returns the `storage_dataflow_max_inflight_bytes` configuration parameter.
     pub fn storage_dataflow_max_inflight_bytes(&self) -> Option<usize> {
         *self.expect_value(&STORAGE_DATAFLOW_MAX_INFLIGHT_BYTES)
@@ -4077,6 +4264,16 @@ impl SystemVars {
         *self.expect_value(&OPTIMIZER_ONESHOT_STATS_TIMEOUT)
     }
 
+    /// Returns the `max_prepared_statements_per_session` configuration parameter.
+    pub fn max_prepared_statements_per_session(&self) -> usize {
+        *self.expect_value(&MAX_PREPARED_STATEMENTS_PER_SESSION)
+    }
+
+    /// Returns the `enable_prepared_statement_eviction` configuration parameter.
+    pub fn enable_prepared_statement_eviction(&self) -> bool {
+        *self.expect_value(&ENABLE_PREPARED_STATEMENT_EVICTION)
+    }
+
     /// Returns the `webhook_concurrent_request_limit` configuration parameter.
     pub fn webhook_concurrent_request_limit(&self) -> usize {
         *self.expect_value(&WEBHOOK_CONCURRENT_REQUEST_LIMIT)
@@ -4939,6 +5136,26 @@ where
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct UsizeInRange<R>(R);
+
+impl<R> DomainConstraint<usize> for UsizeInRange<R>
+where
+    R: RangeBounds<usize> + std::fmt::Debug + Send + Sync,
+{
+    fn check(&self, var: &(dyn Var + Send + Sync), n: &usize) -> Result<(), VarError> {
+        if !self.0.contains(n) {
+            Err(VarError::InvalidParameterValue {
+                parameter: var.into(),
+                values: vec![n.to_string()],
+                reason: format!("only supports values in range {:?}", self.0),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
 impl Value for Numeric {
     fn type_name() -> String {
         "numeric".to_string()
@@ -5714,6 +5931,15 @@ impl SystemVars {
     pub fn is_compute_config_var(&self, name: &str) -> bool {
         name == MAX_RESULT_SIZE.name()
             || name == COMPUTE_DATAFLOW_MAX_INFLIGHT_BYTES.name()
+            || name == COMPUTE_REPLICA_RESTART_BACKOFF_BASE.name()
+            || name == COMPUTE_REPLICA_RESTART_BACKOFF_CAP.name()
+            || name == COMPUTE_SUBSCRIBE_MAX_BUFFERED_BYTES.name()
+            || name == COMPUTE_SUBSCRIBE_MAX_LAG.name()
+            || name == COMPUTE_SUBSCRIBE_MAX_BATCH_ROWS.name()
+            || name == COMPUTE_SUBSCRIBE_MAX_BATCH_BYTES.name()
+            || name == COMPUTE_INTROSPECTION_SHEDDING_BACKLOG.name()
+            || name == COMPUTE_INTROSPECTION_SHEDDING_DROP.name()
+            || name == COMPUTE_HISTORY_REDUCE_THRESHOLD.name()
             || name == LINEAR_JOIN_YIELDING.name()
             || name == ENABLE_MZ_JOIN_CORE.name()
             || name == ENABLE_JEMALLOC_PROFILING.name()
@@ -6105,6 +6331,27 @@ mod tests {
         }
     }
 
+    #[mz_ore::test]
+    fn test_system_vars_validate() {
+        let vars = SystemVars::default();
+        let name = MAX_TABLES.name();
+
+        assert_eq!(vars.validate(name, VarInput::Flat("7")), Ok(()));
+        // Validation doesn't apply the value.
+        assert_eq!(vars.max_tables(), 25);
+
+        assert!(vars.validate(name, VarInput::Flat("seven")).is_err());
+        assert_eq!(
+            vars.validate("no_such_var", VarInput::Flat("7")),
+            Err(VarError::UnknownParameter("no_such_var".into()))
+        );
+
+        // A session must be able to hold at least one named prepared statement and portal.
+        let name = MAX_PREPARED_STATEMENTS_PER_SESSION.name();
+        assert_eq!(vars.validate(name, VarInput::Flat("1")), Ok(()));
+        assert!(vars.validate(name, VarInput::Flat("0")).is_err());
+    }
+
     proptest! {
         #[mz_ore::test]
         #[cfg_attr(miri, ignore)] // slow
@@ -25,6 +25,7 @@ use async_trait::async_trait;
 use differential_dataflow::lattice::Lattice;
 use mz_cluster_client::client::ClusterReplicaLocation;
 use mz_cluster_client::ReplicaId;
+use mz_persist_client::fetch::DecodeResult;
 use mz_persist_client::read::{Cursor, ReadHandle};
 use mz_persist_client::stats::SnapshotStats;
 use mz_persist_types::Codec64;
@@ -72,6 +73,8 @@ pub enum IntrospectionType {
     ComputeDependencies,
     ComputeReplicaHeartbeats,
     ComputeHydrationStatus,
+    ComputeSubscribeLag,
+    ComputeIntrospectionShedding,
 
     // Written by the Adapter for tracking AWS PrivateLink Connection Status History
     PrivatelinkConnectionStatusHistory,
@@ -193,7 +196,7 @@ impl<T: Codec64 + Timestamp + Lattice> SnapshotCursor<T> {
     pub async fn next(
         &mut self,
     ) -> Option<
-        impl Iterator<Item = ((Result<SourceData, String>, Result<(), String>), T, Diff)> + Sized + '_,
+        impl Iterator<Item = ((DecodeResult<SourceData>, DecodeResult<()>), T, Diff)> + Sized + '_,
     > {
         self.cursor.next().await
     }
@@ -29,10 +29,11 @@ use mz_ore::metrics::MetricsRegistry;
 use mz_ore::now::{EpochMillis, NowFn};
 use mz_persist_client::cache::PersistClientCache;
 use mz_persist_client::critical::SinceHandle;
+use mz_persist_client::error::SnapshotError;
 use mz_persist_client::read::ReadHandle;
 use mz_persist_client::stats::SnapshotStats;
 use mz_persist_client::write::WriteHandle;
-use mz_persist_client::{Diagnostics, PersistClient, PersistLocation, ShardId};
+use mz_persist_client::{Diagnostics, FinalizeFrontiers, PersistClient, PersistLocation, ShardId};
 use mz_persist_txn::metrics::Metrics as TxnMetrics;
 use mz_persist_txn::txn_read::TxnsRead;
 use mz_persist_txn::txns::TxnsHandle;
@@ -769,7 +770,9 @@ where
                         // Truncate compute-maintained collections.
                         IntrospectionType::ComputeDependencies
                         | IntrospectionType::ComputeReplicaHeartbeats
-                        | IntrospectionType::ComputeHydrationStatus => {
+                        | IntrospectionType::ComputeHydrationStatus
+                        | IntrospectionType::ComputeSubscribeLag
+                        | IntrospectionType::ComputeIntrospectionShedding => {
                             self.reconcile_managed_collection(id, vec![]).await;
                         }
 
@@ -1190,7 +1193,15 @@ where
                 }
                 Ok(snapshot)
             }
-            Err(_) => Err(StorageError::ReadBeforeSince(id)),
+            Err(SnapshotError::Since(_) | SnapshotError::SinceAdvanced(_)) => {
+                Err(StorageError::ReadBeforeSince(id))
+            }
+            Err(SnapshotError::InternalPanic(err)) => Err(StorageError::Generic(err.into())),
+            Err(SnapshotError::Fetch(err)) => Err(StorageError::Generic(err.into())),
+            // Only returned by read-only handles.
+            Err(SnapshotError::Compacted) => Err(StorageError::Generic(anyhow::anyhow!(
+                "snapshot data compacted away during read"
+            ))),
         }
     }
 
@@ -2833,6 +2844,7 @@ where
                                 .finalize_shard::<SourceData, (), T, Diff>(
                                     shard_id,
                                     Diagnostics::from_purpose("finalizing shards"),
+                                    FinalizeFrontiers::RequireEmpty,
                                 )
                                 .await
                         };
@@ -562,8 +562,8 @@ impl PendingWork {
                     *work += 1;
                 }
                 // TODO(petrosagg): error handling
-                (Err(_), Ok(_)) | (Ok(_), Err(_)) | (Err(_), Err(_)) => {
-                    panic!("decoding failed")
+                (Err(err), _) | (Ok(_), Err(err)) => {
+                    panic!("decoding failed: {}", err)
                 }
             }
             if yield_fn(start_time, *work) {
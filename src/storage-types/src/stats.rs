@@ -13,6 +13,7 @@ use crate::controller::TxnsCodecRow;
 use crate::errors::DataflowError;
 use crate::sources::SourceData;
 use mz_expr::ResultSpec;
+use mz_persist_client::fetch::DecodeResult;
 use mz_persist_client::metrics::Metrics;
 use mz_persist_client::read::{Cursor, LazyPartStats, ReadHandle, Since};
 use mz_persist_client::stats::PartStats;
@@ -95,7 +96,7 @@ impl StatsCursor {
         fn expect_decode(
             raw: impl Iterator<
                 Item = (
-                    (Result<SourceData, String>, Result<(), String>),
+                    (DecodeResult<SourceData>, DecodeResult<()>),
                     Timestamp,
                     Diff,
                 ),
@@ -22,6 +22,7 @@ use futures::StreamExt;
 use mz_ore::vec::VecExt;
 use mz_persist_client::cache::PersistClientCache;
 use mz_persist_client::error::UpperMismatch;
+use mz_persist_client::fetch::DecodeResult;
 use mz_persist_client::read::ListenEvent;
 use mz_persist_client::write::WriteHandle;
 use mz_persist_client::Diagnostics;
@@ -39,14 +40,7 @@ use timely::progress::Timestamp;
 pub struct PersistHandle<FromTime: SourceTimestamp, IntoTime: Timestamp + Lattice + Codec64> {
     events: LocalBoxStream<
         'static,
-        ListenEvent<
-            IntoTime,
-            (
-                (Result<SourceData, String>, Result<(), String>),
-                IntoTime,
-                Diff,
-            ),
-        >,
+        ListenEvent<IntoTime, ((DecodeResult<SourceData>, DecodeResult<()>), IntoTime, Diff)>,
     >,
     write_handle: WriteHandle<SourceData, (), IntoTime, Diff>,
     pending_batch: Vec<(FromTime, IntoTime, Diff)>,
@@ -136,7 +130,7 @@ where
             let updates = read_handle
                 .snapshot_and_fetch(as_of.clone())
                 .await
-                .expect("since <= as_of asserted");
+                .unwrap_or_else(|err| panic!("unable to snapshot remap shard: {err}"));
             let snapshot = stream::once(std::future::ready(ListenEvent::Updates(updates)));
 
             let listener = read_handle
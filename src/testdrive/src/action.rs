@@ -7,10 +7,11 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::future::Future;
 use std::net::ToSocketAddrs;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{env, fs};
 
@@ -42,6 +43,7 @@ use rand::Rng;
 use rdkafka::producer::Producer;
 use rdkafka::ClientConfig;
 use regex::{Captures, Regex};
+use tokio_postgres::AsyncMessage;
 use tracing::info;
 use url::Url;
 
@@ -51,7 +53,9 @@ use crate::parser::{
 };
 use crate::util;
 use crate::util::postgres::postgres_client;
+use crate::util::redact::Redactor;
 
+mod consistency;
 mod file;
 mod http;
 mod kafka;
@@ -66,6 +70,7 @@ mod set;
 mod skip_if;
 mod sleep;
 mod sql;
+mod sql_parallel;
 mod sql_server;
 mod version_check;
 mod webhook;
@@ -79,6 +84,12 @@ pub struct Config {
     /// The value of each entry will be made available to the script in a
     /// variable named `arg.KEY`.
     pub arg_vars: BTreeMap<String, String>,
+    /// Case-insensitive glob patterns for the names of variables whose values
+    /// are redacted from testdrive's output.
+    ///
+    /// The AWS secret access key and session token, and the TLS certificate
+    /// and schema registry passwords, are always redacted.
+    pub redact_var_patterns: Vec<String>,
     /// A random number to distinguish each run of a testdrive script.
     pub seed: Option<u32>,
     /// Whether to reset Materialize state before executing each script and
@@ -176,8 +187,10 @@ pub struct State {
     initial_backoff: Duration,
     backoff_factor: f64,
     no_consistency_checks: bool,
+    consistency_scopes: Option<BTreeSet<consistency::ConsistencyScope>>,
     regex: Option<Regex>,
     regex_replacement: String,
+    redactor: Redactor,
     postgres_factory: StashFactory,
 
     // === Materialize state. ===
@@ -188,7 +201,12 @@ pub struct State {
     materialize_internal_sql_addr: String,
     materialize_internal_http_addr: String,
     materialize_user: String,
+    materialize_pgconfig: tokio_postgres::Config,
+    materialize_params: Vec<(String, String)>,
     pgclient: tokio_postgres::Client,
+    /// Messages of the notices received on `pgclient`'s connection, in
+    /// arrival order.
+    pgclient_notices: Arc<Mutex<Vec<String>>>,
     environment_id: EnvironmentId,
 
     // === Persist state. ===
@@ -299,8 +317,17 @@ impl State {
                 .insert(format!("arg.{}", key), value.to_string());
         }
 
+        for (key, value) in &self.cmd_vars {
+            self.redactor.track(key, value);
+        }
+
         Ok(())
     }
+
+    /// Returns the redactor that scrubs sensitive values from output.
+    pub(crate) fn redactor(&self) -> &Redactor {
+        &self.redactor
+    }
     /// Makes of copy of the durable catalog and runs a function on its
     /// state. Returns `None` if there's no catalog information in the State.
     pub async fn with_catalog_copy<F, T>(&self, f: F) -> Result<Option<T>, anyhow::Error>
@@ -431,7 +458,7 @@ impl State {
                 continue;
             }
             let query = format!("DROP DATABASE {}", db_name);
-            sql::print_query(&query, None);
+            sql::print_query(self, &query, None);
             inner_client.batch_execute(&query).await.context(format!(
                 "resetting materialize state: DROP DATABASE {}",
                 db_name,
@@ -485,7 +512,7 @@ impl State {
                 continue;
             }
             let query = format!("DROP CLUSTER {}", cluster_name);
-            sql::print_query(&query, None);
+            sql::print_query(self, &query, None);
             inner_client.batch_execute(&query).await.context(format!(
                 "resetting materialize state: DROP CLUSTER {}",
                 cluster_name,
@@ -507,7 +534,7 @@ impl State {
                     continue;
                 }
                 let query = format!("DROP ROLE {}", role_name);
-                sql::print_query(&query, None);
+                sql::print_query(self, &query, None);
                 inner_client.batch_execute(&query).await.context(format!(
                     "resetting materialize state: DROP ROLE {}",
                     role_name,
@@ -753,12 +780,17 @@ impl Run for PosCommand {
                     "schema-registry-verify" => schema_registry::run_verify(builtin, state).await,
                     "schema-registry-wait" => schema_registry::run_wait(builtin, state).await,
                     "skip-if" => skip_if::run_skip_if(builtin, state).await,
+                    "sql-parallel" => sql_parallel::run_sql_parallel(builtin, state).await,
                     "sql-server-connect" => sql_server::run_connect(builtin, state).await,
                     "sql-server-execute" => sql_server::run_execute(builtin, state).await,
                     "persist-force-compaction" => {
                         persist::run_force_compaction(builtin, state).await
                     }
+                    "persist-wait-upper" => persist::run_wait_upper(builtin, state).await,
                     "random-sleep" => sleep::run_random_sleep(builtin),
+                    "set-consistency-scope" => {
+                        consistency::run_set_consistency_scope(builtin, state)
+                    }
                     "set-regex" => set::run_regex_set(builtin, state),
                     "unset-regex" => set::run_regex_unset(builtin, state),
                     "set-sql-timeout" => set::run_sql_timeout(builtin, state),
@@ -769,6 +801,9 @@ impl Run for PosCommand {
                     "set" => set::set_vars(builtin, state),
                     "set-from-sql" => set::run_set_from_sql(builtin, state).await,
                     "set-from-file" => set::run_set_from_file(builtin, state).await,
+                    "verify-consistency" => {
+                        consistency::run_verify_consistency(builtin, state).await
+                    }
                     "webhook-append" => webhook::run_append(builtin, state).await,
                     // "verify-timestamp-compaction" => Box::new(
                     //     verify_timestamp_compaction::run_verify_timestamp_compaction_action(
@@ -787,6 +822,11 @@ impl Run for PosCommand {
             Command::Sql(mut sql, version_constraint) => {
                 handle_version!(version_constraint);
                 sql.query = subst(&sql.query, &state.cmd_vars)?;
+                if let Some(expected_notices) = &mut sql.expected_notices {
+                    for pattern in &mut expected_notices.patterns {
+                        *pattern = subst_re(pattern, &state.cmd_vars)?;
+                    }
+                }
                 if let SqlOutput::Full { expected_rows, .. } = &mut sql.expected_output {
                     for row in expected_rows {
                         for col in row {
@@ -861,6 +901,19 @@ fn substitute_vars(
 /// should be `await`ed only *after* dropping the `State` to check whether any
 /// errors occured while dropping the `State`. This awkward API is a workaround
 /// for the lack of `AsyncDrop` support in Rust.
+/// Creates the redactor for the sensitive values known from the configuration
+/// alone, before any script variables have been set.
+pub(crate) fn create_redactor(config: &Config) -> Result<Redactor, anyhow::Error> {
+    let mut redactor = Redactor::new(&config.redact_var_patterns)?;
+    if let Some(cert_password) = &config.cert_password {
+        redactor.track_sensitive("testdrive.cert-password", cert_password);
+    }
+    if let Some(ccsr_password) = &config.ccsr_password {
+        redactor.track_sensitive("testdrive.ccsr-password", ccsr_password);
+    }
+    Ok(redactor)
+}
+
 pub async fn create_state(
     config: &Config,
 ) -> Result<(State, impl Future<Output = Result<(), anyhow::Error>>), anyhow::Error> {
@@ -889,6 +942,7 @@ pub async fn create_state(
         materialize_internal_http_addr,
         materialize_user,
         pgclient,
+        pgclient_notices,
         pgconn_task,
     ) = {
         let materialize_url = util::postgres::config_url(&config.materialize_pgconfig)?;
@@ -905,6 +959,20 @@ pub async fn create_state(
                 pgconfig.connect(tls).await.map_err(|e| anyhow!(e))
             })
             .await?;
+        let pgclient_notices = Arc::new(Mutex::new(Vec::new()));
+        let notices = Arc::clone(&pgclient_notices);
+        let mut pgconn = pgconn;
+        let pgconn = async move {
+            while let Some(message) = std::future::poll_fn(|cx| pgconn.poll_message(cx)).await {
+                if let AsyncMessage::Notice(notice) = message? {
+                    notices
+                        .lock()
+                        .expect("lock poisoned")
+                        .push(notice.message().to_string());
+                }
+            }
+            Ok::<_, tokio_postgres::Error>(())
+        };
         let pgconn_task = task::spawn(|| "pgconn_task", pgconn).map(|join| {
             join.expect("pgconn_task unexpectedly canceled")
                 .context("running SQL connection")
@@ -949,6 +1017,7 @@ pub async fn create_state(
             materialize_internal_http_addr,
             materialize_user,
             pgclient,
+            pgclient_notices,
             pgconn_task,
         )
     };
@@ -1024,6 +1093,8 @@ pub async fn create_state(
         )
     };
 
+    let redactor = create_redactor(config)?;
+
     let mut state = State {
         // === Testdrive state. ===
         arg_vars: config.arg_vars.clone(),
@@ -1037,8 +1108,10 @@ pub async fn create_state(
         initial_backoff: config.initial_backoff,
         backoff_factor: config.backoff_factor,
         no_consistency_checks: config.no_consistency_checks,
+        consistency_scopes: None,
         regex: None,
         regex_replacement: set::DEFAULT_REGEX_REPLACEMENT.into(),
+        redactor,
         postgres_factory: StashFactory::new(&MetricsRegistry::new()),
 
         // === Materialize state. ===
@@ -1048,7 +1121,10 @@ pub async fn create_state(
         materialize_internal_sql_addr,
         materialize_internal_http_addr,
         materialize_user,
+        materialize_pgconfig: config.materialize_pgconfig.clone(),
+        materialize_params: config.materialize_params.clone(),
         pgclient,
+        pgclient_notices,
         environment_id,
 
         // === Persist state. ===
@@ -0,0 +1,252 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use http::StatusCode;
+use itertools::Itertools;
+use mz_ore::retry::Retry;
+
+use crate::action::{ControlFlow, State};
+use crate::parser::BuiltinCommand;
+
+/// A subset of the consistency checks that a script may opt in to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConsistencyScope {
+    /// The catalog, including whether its in-memory and on-disk states match.
+    Catalog,
+    /// The compute controller and its dataflows.
+    Compute,
+    /// The storage controller and its collections.
+    Storage,
+}
+
+impl ConsistencyScope {
+    const ALL: [ConsistencyScope; 3] = [
+        ConsistencyScope::Catalog,
+        ConsistencyScope::Compute,
+        ConsistencyScope::Storage,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ConsistencyScope::Catalog => "catalog",
+            ConsistencyScope::Compute => "compute",
+            ConsistencyScope::Storage => "storage",
+        }
+    }
+}
+
+impl fmt::Display for ConsistencyScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConsistencyScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ConsistencyScope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown consistency scope {:?}, valid scopes are: {}",
+                    s,
+                    ConsistencyScope::ALL.iter().join(", ")
+                )
+            })
+    }
+}
+
+pub fn run_set_consistency_scope(
+    mut cmd: BuiltinCommand,
+    state: &mut State,
+) -> Result<ControlFlow, anyhow::Error> {
+    let scopes = cmd.args.string("scopes")?;
+    cmd.args.done()?;
+
+    state.consistency_scopes = Some(parse_consistency_scopes(&scopes)?);
+    Ok(ControlFlow::Continue)
+}
+
+/// Parses a comma-separated list of consistency scopes.
+///
+/// Whitespace around scopes and empty entries are ignored, so an empty list
+/// selects no scopes at all.
+fn parse_consistency_scopes(scopes: &str) -> Result<BTreeSet<ConsistencyScope>, anyhow::Error> {
+    scopes
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(ConsistencyScope::from_str)
+        .collect()
+}
+
+pub async fn run_verify_consistency(
+    cmd: BuiltinCommand,
+    state: &State,
+) -> Result<ControlFlow, anyhow::Error> {
+    cmd.args.done()?;
+    if !state.no_consistency_checks {
+        run_consistency_checks(state).await?;
+    }
+    Ok(ControlFlow::Continue)
+}
+
+/// Returns the URL of the coordinator's consistency check, restricted to the
+/// scopes selected by `set-consistency-scope`, if any.
+fn coordinator_check_url(
+    internal_http_addr: &str,
+    scopes: Option<&BTreeSet<ConsistencyScope>>,
+) -> String {
+    let url = format!("http://{}/api/coordinator/check", internal_http_addr);
+    match scopes {
+        None => url,
+        Some(scopes) => format!("{}?scopes={}", url, scopes.iter().join(",")),
+    }
+}
+
+/// Runs the coordinator and catalog consistency checks selected by the
+/// script, or all of them if it hasn't selected any.
+pub async fn run_consistency_checks(state: &State) -> Result<(), anyhow::Error> {
+    let scopes = state.consistency_scopes.as_ref();
+    if scopes.map_or(false, |scopes| scopes.is_empty()) {
+        return Ok(());
+    }
+
+    let url = coordinator_check_url(&state.materialize_internal_http_addr, scopes);
+    let response = Retry::default()
+        .max_duration(Duration::from_secs(3))
+        .clamp_backoff(Duration::from_millis(500))
+        .retry_async(|_| async {
+            reqwest::get(&url)
+                .await
+                .context("while getting response from coordinator check")
+        })
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        tracing::info!("not performing coordinator check because the endpoint doesn't exist");
+    } else {
+        // 404 can happen if we're testing an older version of environmentd
+        let inconsistencies = response
+            .error_for_status()
+            .context("response from coordinator check returned an error")?
+            .text()
+            .await
+            .context("while getting text from coordinator check")?;
+        let inconsistencies: serde_json::Value = serde_json::from_str(&inconsistencies)
+            .with_context(|| {
+                format!(
+                    "while parsing result from consistency check: {:?}",
+                    inconsistencies
+                )
+            })?;
+        if inconsistencies != serde_json::json!("") {
+            bail!("Internal catalog inconsistencies {inconsistencies:#?}");
+        }
+    }
+
+    if scopes.map_or(true, |scopes| scopes.contains(&ConsistencyScope::Catalog)) {
+        check_catalog_state(state).await?;
+    }
+    Ok(())
+}
+
+/// Checks that the on-disk state of the catalog matches its in-memory state.
+async fn check_catalog_state(state: &State) -> Result<(), anyhow::Error> {
+    let catalog_state = state
+        .with_catalog_copy(|catalog| catalog.state().clone())
+        .await
+        .map_err(|e| anyhow!("failed to read on-disk catalog state: {e}"))?;
+
+    let disk_state = catalog_state.map(|state| state.dump().expect("state must be dumpable"));
+    if let Some(disk_state) = disk_state {
+        let mem_state = reqwest::get(&format!(
+            "http://{}/api/catalog/dump",
+            state.materialize_internal_http_addr,
+        ))
+        .await?
+        .text()
+        .await?;
+        if disk_state != mem_state {
+            // The state objects here are around 100k lines pretty printed, so find the
+            // first lines that differs and show context around it.
+            let diff = similar::TextDiff::from_lines(&mem_state, &disk_state)
+                .unified_diff()
+                .context_radius(50)
+                .to_string()
+                .lines()
+                .take(200)
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            bail!("the in-memory state of the catalog does not match its on-disk state:\n{diff}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[mz_ore::test]
+    fn consistency_scope_parsing() {
+        use ConsistencyScope::*;
+
+        for scope in ConsistencyScope::ALL {
+            assert_eq!(
+                scope.to_string().parse::<ConsistencyScope>().unwrap(),
+                scope
+            );
+        }
+
+        let parse = |s: &str| {
+            parse_consistency_scopes(s)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parse("catalog"), [Catalog]);
+        assert_eq!(parse("storage,catalog"), [Catalog, Storage]);
+        assert_eq!(parse(" compute , catalog,compute "), [Catalog, Compute]);
+        assert_eq!(parse("catalog,,storage,"), [Catalog, Storage]);
+        assert!(parse("").is_empty());
+        assert!(parse(" , ").is_empty());
+
+        let err = parse_consistency_scopes("catalog,Compute")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            r#"unknown consistency scope "Compute", valid scopes are: catalog, compute, storage"#
+        );
+    }
+
+    #[mz_ore::test]
+    fn coordinator_check_url_scopes() {
+        let addr = "localhost:6878";
+        assert_eq!(
+            coordinator_check_url(addr, None),
+            "http://localhost:6878/api/coordinator/check"
+        );
+
+        let scopes = parse_consistency_scopes("storage,compute").unwrap();
+        assert_eq!(
+            coordinator_check_url(addr, Some(&scopes)),
+            "http://localhost:6878/api/coordinator/check?scopes=compute,storage"
+        );
+    }
+}
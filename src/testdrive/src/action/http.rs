@@ -17,7 +17,7 @@ use crate::parser::BuiltinCommand;
 
 pub async fn run_request(
     mut cmd: BuiltinCommand,
-    _: &mut State,
+    state: &mut State,
 ) -> Result<ControlFlow, anyhow::Error> {
     let url = cmd.args.string("url")?;
     let method: Method = cmd.args.parse("method")?;
@@ -32,7 +32,8 @@ pub async fn run_request(
     };
     let body = cmd.input.join("\n");
 
-    println!("$ http-request {} {}\n{}", method, url, body);
+    let echo = format!("$ http-request {} {}\n{}", method, url, body);
+    println!("{}", state.redactor.redact(&echo));
 
     let client = reqwest::Client::new();
 
@@ -45,7 +46,8 @@ pub async fn run_request(
     let response = request.send().await?;
     let status = response.status();
 
-    println!("{}\n{}", status, response.text().await?);
+    let text = response.text().await?;
+    println!("{}\n{}", status, state.redactor.redact(&text));
 
     if status.is_success() || further_accepted_status_codes.contains(&status.as_u16()) {
         Ok(ControlFlow::Continue)
@@ -26,7 +26,7 @@ pub async fn run_execute(
         .ok_or_else(|| anyhow!("MySQL connection '{}' not found", &name))?;
 
     for query in cmd.input {
-        println!(">> {}", query);
+        println!(">> {}", state.redactor.redact(&query));
         query
             .run(&mut *conn)
             .await
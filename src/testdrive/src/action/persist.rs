@@ -10,10 +10,12 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
+use anyhow::anyhow;
 use mz_ore::metrics::MetricsRegistry;
 use mz_ore::now::SYSTEM_TIME;
+use mz_ore::retry::Retry;
 use mz_persist_client::cfg::PersistConfig;
-use mz_persist_client::ShardId;
+use mz_persist_client::{Diagnostics, PersistLocation, ShardId};
 use mz_persist_types::codec_impls::UnitSchema;
 use mz_repr::{Diff, RelationDesc, ScalarType, Timestamp};
 use mz_storage_types::sources::SourceData;
@@ -26,6 +28,8 @@ pub async fn run_force_compaction(
     state: &State,
 ) -> Result<ControlFlow, anyhow::Error> {
     let shard_id = cmd.args.string("shard-id")?;
+    let target_size = cmd.args.opt_parse::<usize>("target-size")?;
+    let max_parts = cmd.args.opt_parse::<usize>("max-parts")?;
     cmd.args.done()?;
 
     let shard_id = ShardId::from_str(&shard_id).expect("invalid shard id");
@@ -45,17 +49,86 @@ pub async fn run_force_compaction(
         .with_column("f1", ScalarType::String.nullable(true))
         .with_column("f2", ScalarType::Int64.nullable(true));
 
-    mz_persist_client::cli::admin::force_compaction::<SourceData, (), Timestamp, Diff>(
-        cfg,
-        &metrics_registry,
-        shard_id,
-        consensus_url,
-        blob_url,
-        Arc::new(relation_desc),
-        Arc::new(UnitSchema),
-        true,
-    )
-    .await?;
+    let part_count =
+        mz_persist_client::cli::admin::force_compaction::<SourceData, (), Timestamp, Diff>(
+            cfg,
+            &metrics_registry,
+            shard_id,
+            consensus_url,
+            blob_url,
+            Arc::new(relation_desc),
+            Arc::new(UnitSchema),
+            target_size,
+            max_parts,
+            true,
+        )
+        .await?;
+    println!("compacted shard {} into {} parts", shard_id, part_count);
+
+    Ok(ControlFlow::Continue)
+}
+
+pub async fn run_wait_upper(
+    mut cmd: BuiltinCommand,
+    state: &State,
+) -> Result<ControlFlow, anyhow::Error> {
+    let shard_id = cmd.args.string("shard-id")?;
+    let ts = Timestamp::from(cmd.args.parse::<u64>("timestamp")?);
+    cmd.args.done()?;
+
+    let shard_id = ShardId::from_str(&shard_id).expect("invalid shard id");
+
+    let Some(consensus_url) = state.persist_consensus_url.as_ref() else {
+        anyhow::bail!("Missing persist consensus URL");
+    };
+    let Some(blob_url) = state.persist_blob_url.as_ref() else {
+        anyhow::bail!("Missing persist blob URL");
+    };
+
+    let client = state
+        .persist_clients
+        .open(PersistLocation {
+            blob_uri: blob_url.clone(),
+            consensus_uri: consensus_url.clone(),
+        })
+        .await?;
+    // The schemas are only used when writing data, which we never do here.
+    let write = client
+        .open_writer::<SourceData, (), Timestamp, Diff>(
+            shard_id,
+            Arc::new(RelationDesc::empty()),
+            Arc::new(UnitSchema),
+            Diagnostics::from_purpose("testdrive wait upper"),
+        )
+        .await?;
+
+    println!(
+        "waiting for upper of shard {} to advance past {}",
+        shard_id, ts
+    );
+    let (write, res) = Retry::default()
+        .initial_backoff(state.initial_backoff)
+        .factor(state.backoff_factor)
+        .max_duration(state.timeout)
+        .max_tries(state.max_tries)
+        .retry_async_with_state(write, |_, mut write| async move {
+            let upper = write.fetch_recent_upper().await.clone();
+            let res = if upper.less_equal(&ts) {
+                Err(anyhow!(
+                    "upper of shard {} is {:?}, not past {}",
+                    shard_id,
+                    upper.elements(),
+                    ts
+                ))
+            } else {
+                Ok(upper)
+            };
+            (write, res)
+        })
+        .await;
+    write.expire().await;
+    let upper = res?;
+    println!("upper of shard {} is {:?}", shard_id, upper.elements());
 
     Ok(ControlFlow::Continue)
 }
@@ -33,7 +33,7 @@ pub async fn run_execute(
     };
 
     for query in cmd.input {
-        println!(">> {}", query);
+        println!(">> {}", state.redactor.redact(&query));
         client
             .batch_execute(&query)
             .await
@@ -77,11 +77,13 @@ pub fn run_max_tries(
 
 pub fn set_vars(cmd: BuiltinCommand, state: &mut State) -> Result<ControlFlow, anyhow::Error> {
     for (key, val) in cmd.args {
-        if val.is_empty() {
-            state.cmd_vars.insert(key, cmd.input.join("\n"));
+        let val = if val.is_empty() {
+            cmd.input.join("\n")
         } else {
-            state.cmd_vars.insert(key, val);
-        }
+            val
+        };
+        state.redactor.track(&key, &val);
+        state.cmd_vars.insert(key, val);
     }
 
     Ok(ControlFlow::Continue)
@@ -107,6 +109,7 @@ pub async fn run_set_from_sql(
     }
     let value: String = row.try_get(0).context("deserializing value as string")?;
 
+    state.redactor.track(&var, &value);
     state.cmd_vars.insert(var, value);
 
     Ok(ControlFlow::Continue)
@@ -121,6 +124,7 @@ pub async fn run_set_from_file(
         let contents = fs::read_to_string(&path)
             .await
             .with_context(|| format!("reading {path}"))?;
+        state.redactor.track(&key, &contents);
         state.cmd_vars.insert(key, contents);
     }
     Ok(ControlFlow::Continue)
@@ -11,10 +11,9 @@ use std::ascii;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter, Write as _};
 use std::io::{self, Write};
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::{anyhow, bail, Context};
-use http::StatusCode;
+use anyhow::{bail, Context};
 use md5::{Digest, Md5};
 use mz_ore::collections::CollectionExt;
 use mz_ore::retry::Retry;
@@ -28,8 +27,12 @@ use tokio_postgres::error::DbError;
 use tokio_postgres::row::Row;
 use tokio_postgres::types::{FromSql, Type};
 
-use crate::action::{ControlFlow, State};
-use crate::parser::{FailSqlCommand, SqlCommand, SqlExpectedError, SqlOutput};
+use crate::action::{consistency, ControlFlow, State};
+use crate::parser::{FailSqlCommand, SqlCommand, SqlExpectedError, SqlExpectedNotices, SqlOutput};
+
+/// How long to wait for expected notices that have not yet arrived once a
+/// query's rows match.
+const NOTICE_WAIT: Duration = Duration::from_secs(1);
 
 pub async fn run_sql(mut cmd: SqlCommand, state: &State) -> Result<ControlFlow, anyhow::Error> {
     use Statement::*;
@@ -44,6 +47,11 @@ pub async fn run_sql(mut cmd: SqlCommand, state: &State) -> Result<ControlFlow,
         // TODO(benesch): one day we'll support SQL queries where order matters.
         expected_rows.sort();
     }
+    let notice_matcher = cmd
+        .expected_notices
+        .as_ref()
+        .map(NoticeMatcher::new)
+        .transpose()?;
 
     let should_retry = match &stmt {
         // Do not retry FETCH statements as subsequent executions are likely
@@ -75,10 +83,11 @@ pub async fn run_sql(mut cmd: SqlCommand, state: &State) -> Result<ControlFlow,
     };
 
     let query = &cmd.query;
-    print_query(query, Some(&stmt));
+    print_query(state, query, Some(&stmt));
 
     let state = &state;
     let expected_output = &cmd.expected_output;
+    let notice_matcher = notice_matcher.as_ref();
     let res = match should_retry {
         true => Retry::default()
             .initial_backoff(state.initial_backoff)
@@ -88,7 +97,7 @@ pub async fn run_sql(mut cmd: SqlCommand, state: &State) -> Result<ControlFlow,
         false => Retry::default().max_duration(state.timeout).max_tries(1),
     }
     .retry_async_canceling(|retry_state| async move {
-        match try_run_sql(state, query, expected_output).await {
+        match try_run_sql(state, query, expected_output, notice_matcher).await {
             Ok(()) => {
                 if retry_state.i != 0 {
                     println!();
@@ -144,75 +153,7 @@ async fn run_extra_checks(state: &State, stmt: &Statement<Raw>) -> Result<(), an
         | Statement::GrantPrivileges { .. }
         | Statement::GrantRole { .. }
         | Statement::RevokePrivileges { .. }
-        | Statement::RevokeRole { .. } => {
-            let response = Retry::default()
-                .max_duration(Duration::from_secs(3))
-                .clamp_backoff(Duration::from_millis(500))
-                .retry_async(|_| async {
-                    reqwest::get(&format!(
-                        "http://{}/api/coordinator/check",
-                        state.materialize_internal_http_addr,
-                    ))
-                    .await
-                    .context("while getting response from coordinator check")
-                })
-                .await?;
-            if response.status() == StatusCode::NOT_FOUND {
-                tracing::info!(
-                    "not performing coordinator check because the endpoint doesn't exist"
-                );
-            } else {
-                // 404 can happen if we're testing an older version of environmentd
-                let inconsistencies = response
-                    .error_for_status()
-                    .context("response from coordinator check returned an error")?
-                    .text()
-                    .await
-                    .context("while getting text from coordinator check")?;
-                let inconsistencies: serde_json::Value = serde_json::from_str(&inconsistencies)
-                    .with_context(|| {
-                        format!(
-                            "while parsing result from consistency check: {:?}",
-                            inconsistencies
-                        )
-                    })?;
-                if inconsistencies != serde_json::json!("") {
-                    bail!("Internal catalog inconsistencies {inconsistencies:#?}");
-                }
-            }
-
-            let catalog_state = state
-                .with_catalog_copy(|catalog| catalog.state().clone())
-                .await
-                .map_err(|e| anyhow!("failed to read on-disk catalog state: {e}"))?;
-
-            // Check that our on-disk state matches the in-memory state.
-            let disk_state =
-                catalog_state.map(|state| state.dump().expect("state must be dumpable"));
-            if let Some(disk_state) = disk_state {
-                let mem_state = reqwest::get(&format!(
-                    "http://{}/api/catalog/dump",
-                    state.materialize_internal_http_addr,
-                ))
-                .await?
-                .text()
-                .await?;
-                if disk_state != mem_state {
-                    // The state objects here are around 100k lines pretty printed, so find the
-                    // first lines that differs and show context around it.
-                    let diff = similar::TextDiff::from_lines(&mem_state, &disk_state)
-                        .unified_diff()
-                        .context_radius(50)
-                        .to_string()
-                        .lines()
-                        .take(200)
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    bail!("the in-memory state of the catalog does not match its on-disk state:\n{diff}");
-                }
-            }
-        }
+        | Statement::RevokeRole { .. } => consistency::run_consistency_checks(state).await?,
         _ => {}
     }
     Ok(())
@@ -222,7 +163,13 @@ async fn try_run_sql(
     state: &State,
     query: &str,
     expected_output: &SqlOutput,
+    notice_matcher: Option<&NoticeMatcher>,
 ) -> Result<(), anyhow::Error> {
+    state
+        .pgclient_notices
+        .lock()
+        .expect("lock poisoned")
+        .clear();
     let stmt = state
         .pgclient
         .prepare(query)
@@ -258,6 +205,19 @@ async fn try_run_sql(
 
     actual.sort();
 
+    check_rows(&stmt, expected_output, actual, raw_actual)?;
+    match notice_matcher {
+        Some(notice_matcher) => check_notices(state, notice_matcher).await,
+        None => Ok(()),
+    }
+}
+
+fn check_rows(
+    stmt: &tokio_postgres::Statement,
+    expected_output: &SqlOutput,
+    actual: Vec<Vec<String>>,
+    raw_actual: Option<Vec<Vec<String>>>,
+) -> Result<(), anyhow::Error> {
     match expected_output {
         SqlOutput::Full {
             expected_rows,
@@ -345,7 +305,126 @@ async fn try_run_sql(
     }
 }
 
-enum ErrorMatcher {
+/// Waits for the notices received while running a query to satisfy
+/// `notice_matcher`, giving notices that arrive after the query completes up
+/// to [`NOTICE_WAIT`] to show up.
+async fn check_notices(state: &State, notice_matcher: &NoticeMatcher) -> Result<(), anyhow::Error> {
+    let deadline = Instant::now() + NOTICE_WAIT;
+    loop {
+        let notices: Vec<_> = state
+            .pgclient_notices
+            .lock()
+            .expect("lock poisoned")
+            .iter()
+            .map(|notice| match &state.regex {
+                Some(regex) => regex
+                    .replace_all(notice, state.regex_replacement.as_str())
+                    .to_string(),
+                None => notice.clone(),
+            })
+            .collect();
+        let res = notice_matcher.check(&notices);
+        // In strict mode, keep waiting out the full window so that late
+        // unexpected notices are caught too.
+        let done = res.is_ok() && notice_matcher.allow_unexpected;
+        if done || Instant::now() >= deadline {
+            return res;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Matches received notice messages against the patterns of an
+/// `expect-notice` section.
+struct NoticeMatcher {
+    patterns: Vec<Regex>,
+    allow_unexpected: bool,
+}
+
+impl NoticeMatcher {
+    fn new(expected_notices: &SqlExpectedNotices) -> Result<Self, anyhow::Error> {
+        let patterns = expected_notices
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("invalid expect-notice regex: {}", pattern))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(NoticeMatcher {
+            patterns,
+            allow_unexpected: expected_notices.allow_unexpected,
+        })
+    }
+
+    /// Checks that every pattern matches a distinct notice, in any order, and,
+    /// unless unexpected notices are allowed, that every notice is matched by
+    /// some pattern.
+    fn check(&self, notices: &[String]) -> Result<(), anyhow::Error> {
+        // Assign patterns to notices via augmenting paths, so that a pattern
+        // matching several notices never steals the only notice another
+        // pattern could match.
+        fn assign(
+            pattern: usize,
+            patterns: &[Regex],
+            notices: &[String],
+            visited: &mut [bool],
+            owner: &mut [Option<usize>],
+        ) -> bool {
+            for (notice, text) in notices.iter().enumerate() {
+                if visited[notice] || !patterns[pattern].is_match(text) {
+                    continue;
+                }
+                visited[notice] = true;
+                let free = match owner[notice] {
+                    None => true,
+                    Some(other) => assign(other, patterns, notices, visited, owner),
+                };
+                if free {
+                    owner[notice] = Some(pattern);
+                    return true;
+                }
+            }
+            false
+        }
+
+        let mut owner = vec![None; notices.len()];
+        let mut missing = Vec::new();
+        for pattern in 0..self.patterns.len() {
+            let mut visited = vec![false; notices.len()];
+            if !assign(pattern, &self.patterns, notices, &mut visited, &mut owner) {
+                missing.push(&self.patterns[pattern]);
+            }
+        }
+        let unexpected: Vec<_> = notices
+            .iter()
+            .zip(&owner)
+            .filter(|(_, owner)| owner.is_none())
+            .map(|(notice, _)| notice)
+            .collect();
+
+        if missing.is_empty() && (self.allow_unexpected || unexpected.is_empty()) {
+            return Ok(());
+        }
+        let mut buf = String::new();
+        for pattern in missing {
+            writeln!(buf, "- notice matching regex {}", pattern.as_str().quoted()).unwrap();
+        }
+        if !self.allow_unexpected {
+            for notice in unexpected {
+                writeln!(buf, "+ notice {}", notice.quoted()).unwrap();
+            }
+        }
+        bail!(
+            "non-matching notices: expected:\n{:?}\ngot:\n{:?}\nPoor diff:\n{}",
+            self.patterns.iter().map(|p| p.as_str()).collect::<Vec<_>>(),
+            notices,
+            buf
+        )
+    }
+}
+
+pub(crate) enum ErrorMatcher {
     Contains(String),
     Exact(String),
     Regex(Regex),
@@ -353,7 +432,16 @@ enum ErrorMatcher {
 }
 
 impl ErrorMatcher {
-    fn is_match(&self, err: &String) -> bool {
+    pub(crate) fn new(expected_error: SqlExpectedError) -> Result<Self, anyhow::Error> {
+        Ok(match expected_error {
+            SqlExpectedError::Contains(s) => ErrorMatcher::Contains(s),
+            SqlExpectedError::Exact(s) => ErrorMatcher::Exact(s),
+            SqlExpectedError::Regex(s) => ErrorMatcher::Regex(s.parse()?),
+            SqlExpectedError::Timeout => ErrorMatcher::Timeout,
+        })
+    }
+
+    pub(crate) fn is_match(&self, err: &String) -> bool {
         match self {
             ErrorMatcher::Contains(s) => err.contains(s),
             ErrorMatcher::Exact(s) => err == s,
@@ -409,17 +497,12 @@ pub async fn run_fail_sql(
         Err(_) => None,
     };
 
-    let expected_error = match cmd.expected_error {
-        SqlExpectedError::Contains(s) => ErrorMatcher::Contains(s),
-        SqlExpectedError::Exact(s) => ErrorMatcher::Exact(s),
-        SqlExpectedError::Regex(s) => ErrorMatcher::Regex(s.parse()?),
-        SqlExpectedError::Timeout => ErrorMatcher::Timeout,
-    };
+    let expected_error = ErrorMatcher::new(cmd.expected_error)?;
     let expected_detail = cmd.expected_detail.map(ErrorMatcher::Contains);
     let expected_hint = cmd.expected_hint.map(ErrorMatcher::Contains);
 
     let query = &cmd.query;
-    print_query(query, stmt.as_ref());
+    print_query(state, query, stmt.as_ref());
 
     let should_retry = match &stmt {
         // Do not retry statements that could not be parsed
@@ -553,12 +636,12 @@ async fn try_run_fail_sql(
     }
 }
 
-pub fn print_query(query: &str, stmt: Option<&Statement<Raw>>) {
+pub fn print_query(state: &State, query: &str, stmt: Option<&Statement<Raw>>) {
     use Statement::*;
     if let Some(CreateSecret(_)) = stmt {
         println!("> CREATE SECRET [query truncated on purpose so as to not reveal the secret in the log]");
     } else {
-        println!("> {}", query)
+        println!("> {}", state.redactor.redact(query))
     }
 }
 
@@ -836,3 +919,52 @@ impl Display for TestdriveRow<'_> {
         write!(f, "{}", cols.join(" "))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(patterns: &[&str], allow_unexpected: bool) -> NoticeMatcher {
+        NoticeMatcher::new(&SqlExpectedNotices {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            allow_unexpected,
+        })
+        .unwrap()
+    }
+
+    fn notices(notices: &[&str]) -> Vec<String> {
+        notices.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[mz_ore::test]
+    fn notice_matching() {
+        // Order doesn't matter, and extra notices are allowed by default.
+        let m = matcher(&["^b$", "^a$"], true);
+        assert!(m.check(&notices(&["a", "c", "b"])).is_ok());
+
+        // Patterns are matched as a multiset.
+        let m = matcher(&["^a$", "^a$"], true);
+        assert!(m.check(&notices(&["a", "a"])).is_ok());
+        let err = m.check(&notices(&["a"])).unwrap_err().to_string();
+        assert!(err.contains(r#"- notice matching regex "^a$""#), "{}", err);
+
+        // A broad pattern doesn't steal the only notice a narrower one
+        // matches.
+        let m = matcher(&["a", "^ab$"], true);
+        assert!(m.check(&notices(&["ab", "abc"])).is_ok());
+    }
+
+    #[mz_ore::test]
+    fn notice_matching_strict() {
+        let m = matcher(&["^a$"], false);
+        assert!(m.check(&notices(&["a"])).is_ok());
+        let err = m.check(&notices(&["b", "a"])).unwrap_err().to_string();
+        assert!(err.contains(r#"+ notice "b""#), "{}", err);
+        assert!(!err.contains("- notice"), "{}", err);
+
+        // With no patterns, strict mode asserts that no notices arrive.
+        let m = matcher(&[], false);
+        assert!(m.check(&notices(&[])).is_ok());
+        assert!(m.check(&notices(&["a"])).is_err());
+    }
+}
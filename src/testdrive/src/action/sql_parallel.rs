@@ -0,0 +1,197 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::error::Error;
+use std::fmt::{self, Write as _};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use itertools::Itertools;
+use mz_ore::str::StrExt;
+use mz_ore::task;
+use mz_tls_util::make_tls;
+use tokio_postgres::error::DbError;
+use tokio_postgres::Client;
+
+use crate::action::sql::{decode_row, ErrorMatcher};
+use crate::action::{ControlFlow, State};
+use crate::parser::{self, BuiltinCommand, SqlParallelOutcome};
+
+/// An expected outcome of a statement in a `sql-parallel` block.
+enum OutcomeMatcher {
+    Ok,
+    Error(ErrorMatcher),
+    Rows(Vec<Vec<String>>),
+}
+
+impl OutcomeMatcher {
+    fn is_match(&self, actual: &Result<Vec<Vec<String>>, String>) -> bool {
+        match (self, actual) {
+            (OutcomeMatcher::Ok, Ok(_)) => true,
+            (OutcomeMatcher::Error(expected), Err(err)) => expected.is_match(err),
+            (OutcomeMatcher::Rows(expected), Ok(rows)) => expected == rows,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for OutcomeMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutcomeMatcher::Ok => f.write_str("ok"),
+            OutcomeMatcher::Error(expected) => write!(f, "{}", expected),
+            OutcomeMatcher::Rows(rows) => write!(f, "rows {:?}", rows),
+        }
+    }
+}
+
+pub async fn run_sql_parallel(
+    mut cmd: BuiltinCommand,
+    state: &State,
+) -> Result<ControlFlow, anyhow::Error> {
+    let deadline = cmd
+        .args
+        .opt_parse::<humantime::Duration>("deadline")?
+        .map_or(state.timeout, Duration::from);
+    cmd.args.done()?;
+
+    let block = parser::parse_sql_parallel(&cmd.input)?;
+    let mut queries = Vec::new();
+    let mut expected_outcomes = Vec::new();
+    for stmt in block.statements {
+        let mut matchers = Vec::new();
+        for outcome in stmt.expected_outcomes {
+            matchers.push(match outcome {
+                SqlParallelOutcome::Ok => OutcomeMatcher::Ok,
+                SqlParallelOutcome::Error(expected) => {
+                    OutcomeMatcher::Error(ErrorMatcher::new(expected)?)
+                }
+                SqlParallelOutcome::Rows(mut rows) => {
+                    // Like `>` commands, the order of the rows doesn't matter.
+                    rows.sort();
+                    OutcomeMatcher::Rows(rows)
+                }
+            });
+        }
+        queries.push(stmt.query);
+        expected_outcomes.push(matchers);
+    }
+
+    // Open all the sessions before running any statement, so that connection
+    // setup doesn't make some statements lag behind the others.
+    let mut clients = Vec::new();
+    let mut connections = Vec::new();
+    for _ in &queries {
+        let (client, connection) = connect(state).await?;
+        clients.push(client);
+        connections.push(connection);
+    }
+
+    for (i, query) in queries.iter().enumerate() {
+        println!("> [{}] {}", i + 1, state.redactor.redact(query));
+    }
+    let statements = clients
+        .into_iter()
+        .zip(queries.iter().cloned())
+        .enumerate()
+        .map(|(i, (client, query))| {
+            task::spawn(|| format!("sql_parallel_statement:{}", i + 1), async move {
+                client.query(&query, &[]).await
+            })
+            .abort_on_drop()
+        });
+    // If the deadline passes, dropping the remaining statement tasks aborts
+    // them, which in turn drops their clients and closes the sessions.
+    let results = tokio::time::timeout(deadline, futures::future::join_all(statements))
+        .await
+        .map_err(|_| anyhow!("sql-parallel block did not complete within {:?}", deadline))?;
+
+    let mut failures = String::new();
+    for (i, ((query, expected), result)) in queries
+        .iter()
+        .zip(expected_outcomes.iter())
+        .zip(results)
+        .enumerate()
+    {
+        let actual = match result.context("sql-parallel statement task failed")? {
+            Ok(rows) => {
+                let mut decoded = Vec::new();
+                for row in rows {
+                    decoded.push(decode_row(state, row)?.0);
+                }
+                decoded.sort();
+                Ok(decoded)
+            }
+            Err(err) => match err.source().and_then(|err| err.downcast_ref::<DbError>()) {
+                Some(err) => {
+                    let mut err_string = err.message().to_string();
+                    if let Some(regex) = &state.regex {
+                        err_string = regex
+                            .replace_all(&err_string, state.regex_replacement.as_str())
+                            .to_string();
+                    }
+                    Err(err_string)
+                }
+                None => return Err(err).with_context(|| format!("executing statement {}", i + 1)),
+            },
+        };
+        if !expected.iter().any(|outcome| outcome.is_match(&actual)) {
+            let actual = match &actual {
+                Ok(rows) => format!("rows {:?}", rows),
+                Err(err) => format!("error {}", err.quoted()),
+            };
+            writeln!(
+                failures,
+                "statement {} ({}): expected one of [{}], got {}",
+                i + 1,
+                query,
+                expected.iter().join(", "),
+                actual
+            )
+            .expect("writing to a string cannot fail");
+        }
+    }
+
+    // Wait for the sessions to be torn down, so that they don't leak into
+    // subsequent commands.
+    for connection in connections {
+        connection
+            .await
+            .context("sql-parallel connection task failed")?
+            .context("running sql-parallel connection")?;
+    }
+
+    if !failures.is_empty() {
+        bail!("sql-parallel outcomes did not match:\n{}", failures);
+    }
+    println!("all sql-parallel outcomes match; continuing");
+    Ok(ControlFlow::Continue)
+}
+
+/// Opens a new session to Materialize with the same configuration as the main
+/// testdrive session.
+async fn connect(
+    state: &State,
+) -> Result<(Client, task::JoinHandle<Result<(), tokio_postgres::Error>>), anyhow::Error> {
+    let mut pgconfig = state.materialize_pgconfig.clone();
+    pgconfig.connect_timeout(state.default_timeout);
+    let tls = make_tls(&pgconfig)?;
+    let (client, connection) = pgconfig
+        .connect(tls)
+        .await
+        .context("connecting to materialize")?;
+    let connection = task::spawn(|| "sql_parallel_connection", connection);
+    for (key, value) in &state.materialize_params {
+        client
+            .batch_execute(&format!("SET {key} = {value}"))
+            .await
+            .context("setting session parameter")?;
+    }
+    Ok((client, connection))
+}
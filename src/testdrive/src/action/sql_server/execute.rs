@@ -26,7 +26,7 @@ pub async fn run_execute(
         .ok_or_else(|| anyhow!("connection {} not found", name.quoted()))?;
 
     for query in cmd.input {
-        println!(">> {}", query);
+        println!(">> {}", state.redactor.redact(&query));
         client
             .execute(query, &[])
             .await
@@ -32,7 +32,8 @@ pub async fn run_append(
 
     let body = cmd.input.join("\n");
 
-    println!("$ webhook-append {database}.{schema}.{name}\n{body}\n{headers:?}");
+    let echo = format!("$ webhook-append {database}.{schema}.{name}\n{body}\n{headers:?}");
+    println!("{}", state.redactor.redact(&echo));
 
     let client = reqwest::Client::new();
     let url = format!(
@@ -49,7 +50,8 @@ pub async fn run_append(
     let response = builder.send().await?;
     let status = response.status();
 
-    println!("{}\n{}", status, response.text().await?);
+    let text = response.text().await?;
+    println!("{}\n{}", status, state.redactor.redact(&text));
 
     let expected_status = status_code.unwrap_or(200);
     if status.as_u16() == expected_status {
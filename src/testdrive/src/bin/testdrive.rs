@@ -51,6 +51,18 @@ struct Args {
     /// value `bar`. Can be specified multiple times to set multiple variables.
     #[clap(long, env = "VAR", use_delimiter = true, value_name = "NAME=VALUE")]
     var: Vec<String>,
+    /// Case-insensitive glob patterns for the names of variables whose values
+    /// are redacted from testdrive's output.
+    ///
+    /// Occurrences of redacted values in echoed commands and error messages
+    /// are replaced with `[REDACTED:<var-name>]`.
+    #[clap(
+        long,
+        use_delimiter = true,
+        value_name = "PATTERN",
+        default_values = &["*PASSWORD*", "*SECRET*"]
+    )]
+    redact_var_pattern: Vec<String>,
     /// A random number to distinguish each testdrive run.
     #[clap(long, value_name = "N")]
     seed: Option<u32>,
@@ -368,6 +380,7 @@ async fn main() {
     let config = Config {
         // === Testdrive options. ===
         arg_vars,
+        redact_var_patterns: args.redact_var_pattern,
         seed: args.seed,
         reset: !args.no_reset,
         temp_dir: args.temp_dir,
@@ -16,14 +16,18 @@
 //! that is not human-readable, so `PosError`s are upgraded to `Error`s before
 //! they are returned externally.
 
+use std::borrow::Cow;
 use std::fmt::{self, Write as _};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use anyhow::anyhow;
 use atty::Stream;
 use mz_ore::error::ErrorExt;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
+use crate::util::redact::Redactor;
+
 /// An error produced when parsing or executing a testdrive script.
 ///
 /// Errors are optionally associated with a location in a testdrive script. When
@@ -159,6 +163,18 @@ impl PosError {
             pos: Some(pos),
         }
     }
+
+    /// Scrubs sensitive values from the error message.
+    pub(crate) fn redact(self, redactor: &Redactor) -> PosError {
+        let message = self.source.display_with_causes().to_string();
+        match redactor.redact(&message) {
+            Cow::Borrowed(_) => self,
+            Cow::Owned(message) => PosError {
+                source: anyhow!(message),
+                pos: self.pos,
+            },
+        }
+    }
 }
 
 impl From<anyhow::Error> for PosError {
@@ -100,7 +100,16 @@ pub(crate) async fn run_line_reader(
         )
     });
 
-    let (mut state, state_cleanup) = action::create_state(config).await?;
+    // Errors frequently echo the substituted command or the configuration, so
+    // scrub any secrets from them before they're printed. The state's redactor
+    // supersedes this one once it exists, as it also knows about the values
+    // of variables set by the script.
+    let redactor = action::create_redactor(config)?;
+    let (mut state, state_cleanup) = action::create_state(config)
+        .await
+        .map_err(|e| PosError::from(e).redact(&redactor))?;
+
+    let mut errors = Vec::new();
 
     if config.reset {
         // Delete any existing Materialize and Kafka state *before* the test
@@ -108,30 +117,36 @@ pub(crate) async fn run_line_reader(
         // end of the script because it's useful to leave the state around,
         // e.g., for debugging, or when using a testdrive script to set up
         // Materialize for further tinkering.
-
-        state.reset_materialize().await?;
-
-        // Only try to clean up Kafka state if the test script uses a Kafka
-        // action. Tests that don't use Kafka likely don't have a Kafka
-        // broker available.
-        if has_kafka_cmd {
-            state.reset_kafka().await?;
+        let reset = async {
+            state.reset_materialize().await?;
+
+            // Only try to clean up Kafka state if the test script uses a Kafka
+            // action. Tests that don't use Kafka likely don't have a Kafka
+            // broker available.
+            if has_kafka_cmd {
+                state.reset_kafka().await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+        if let Err(e) = reset.await {
+            errors.push(e.into());
         }
     }
 
-    let mut errors = Vec::new();
-
-    for cmd in cmds {
-        match cmd.run(&mut state).await {
-            Ok(ControlFlow::Continue) => (),
-            Ok(ControlFlow::Break) => break,
-            Err(e) => {
-                errors.push(e);
-                break;
+    if errors.is_empty() {
+        for cmd in cmds {
+            match cmd.run(&mut state).await {
+                Ok(ControlFlow::Continue) => (),
+                Ok(ControlFlow::Break) => break,
+                Err(e) => {
+                    errors.push(e);
+                    break;
+                }
             }
         }
     }
 
+    let redactor = state.redactor().clone();
     if config.reset {
         drop(state);
         if let Err(e) = state_cleanup.await {
@@ -143,6 +158,6 @@ pub(crate) async fn run_line_reader(
         Ok(())
     } else {
         // Only surface the first error encountered for sake of simplicity
-        Err(errors.remove(0))
+        Err(errors.remove(0).redact(&redactor))
     }
 }
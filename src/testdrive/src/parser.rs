@@ -70,6 +70,17 @@ pub enum SqlOutput {
 pub struct SqlCommand {
     pub query: String,
     pub expected_output: SqlOutput,
+    pub expected_notices: Option<SqlExpectedNotices>,
+}
+
+/// The notices a SQL command is expected to emit, as declared by the
+/// `expect-notice:` lines that follow the query.
+#[derive(Debug, Clone)]
+pub struct SqlExpectedNotices {
+    /// Regular expressions that must each match a distinct notice message.
+    pub patterns: Vec<String>,
+    /// Whether notices not matched by any pattern are tolerated.
+    pub allow_unexpected: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -88,6 +99,30 @@ pub enum SqlExpectedError {
     Timeout,
 }
 
+/// A block of SQL statements to execute concurrently, along with the outcomes
+/// accepted for each of them.
+#[derive(Debug, Clone)]
+pub struct SqlParallelBlock {
+    pub statements: Vec<SqlParallelStatement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqlParallelStatement {
+    pub query: String,
+    /// The statement passes if its outcome matches any of these.
+    pub expected_outcomes: Vec<SqlParallelOutcome>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SqlParallelOutcome {
+    /// The statement succeeded, regardless of the rows it returned.
+    Ok,
+    /// The statement failed with a matching error.
+    Error(SqlExpectedError),
+    /// The statement succeeded and returned exactly these rows, in any order.
+    Rows(Vec<Vec<String>>),
+}
+
 pub(crate) fn parse(line_reader: &mut LineReader) -> Result<Vec<PosCommand>, PosError> {
     let mut out = Vec::new();
     while let Some((pos, line)) = line_reader.peek() {
@@ -270,6 +305,7 @@ fn parse_version_constraint(
 fn parse_sql(line_reader: &mut LineReader) -> Result<SqlCommand, PosError> {
     let (_, line1) = line_reader.next().unwrap();
     let query = line1[1..].trim().to_owned();
+    let expected_notices = parse_expected_notices(line_reader)?;
     let line2 = slurp_one(line_reader);
     let line3 = slurp_one(line_reader);
     let mut column_names = None;
@@ -294,6 +330,7 @@ fn parse_sql(line_reader: &mut LineReader) -> Result<SqlCommand, PosError> {
                             num_values,
                             md5: captures[2].to_owned(),
                         },
+                        expected_notices,
                     })
                 }
                 Err(err) => {
@@ -316,9 +353,48 @@ fn parse_sql(line_reader: &mut LineReader) -> Result<SqlCommand, PosError> {
             column_names,
             expected_rows,
         },
+        expected_notices,
     })
 }
 
+/// Parses the optional `expect-notice:` and `allow-unexpected-notices=` lines
+/// that may directly follow the query of a SQL command.
+fn parse_expected_notices(
+    line_reader: &mut LineReader,
+) -> Result<Option<SqlExpectedNotices>, PosError> {
+    let mut patterns = Vec::new();
+    let mut allow_unexpected = None;
+    while let Some((pos, line)) = line_reader.peek() {
+        let pos = *pos;
+        if let Some(pattern) = line.strip_prefix("expect-notice:") {
+            patterns.push(pattern.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("allow-unexpected-notices=") {
+            match value.trim().parse::<bool>() {
+                Ok(value) => allow_unexpected = Some(value),
+                Err(_) => {
+                    return Err(PosError {
+                        source: anyhow!(
+                            "allow-unexpected-notices must be `true` or `false`, got: {}",
+                            value.trim()
+                        ),
+                        pos: Some(pos),
+                    })
+                }
+            }
+        } else {
+            break;
+        }
+        let _ = line_reader.next();
+    }
+    if patterns.is_empty() && allow_unexpected.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(SqlExpectedNotices {
+        patterns,
+        allow_unexpected: allow_unexpected.unwrap_or(true),
+    }))
+}
+
 fn parse_explain_sql(line_reader: &mut LineReader) -> Result<SqlCommand, PosError> {
     let (_, line1) = line_reader.next().unwrap();
     // This is a bit of a hack to extract the next chunk of the file with
@@ -342,6 +418,7 @@ fn parse_explain_sql(line_reader: &mut LineReader) -> Result<SqlCommand, PosErro
             column_names: None,
             expected_rows: vec![vec![expected_output]],
         },
+        expected_notices: None,
     })
 }
 
@@ -359,21 +436,16 @@ fn parse_fail_sql(line_reader: &mut LineReader) -> Result<FailSqlCommand, PosErr
     };
     let query = line1[1..].trim().to_string();
 
-    let expected_error = if let Some(e) = expected_error.strip_prefix("regex:") {
-        SqlExpectedError::Regex(e.trim().into())
-    } else if let Some(e) = expected_error.strip_prefix("contains:") {
-        SqlExpectedError::Contains(e.trim().into())
-    } else if let Some(e) = expected_error.strip_prefix("exact:") {
-        SqlExpectedError::Exact(e.trim().into())
-    } else if expected_error == "timeout" {
-        SqlExpectedError::Timeout
-    } else {
-        return Err(PosError {
+    let expected_error = match parse_expected_error(&expected_error) {
+        Some(expected_error) => expected_error,
+        None => {
+            return Err(PosError {
                 pos: Some(err_pos),
                 source: anyhow!(
                     "Query error must start with match specifier (`regex:`|`contains:`|`exact:`|`timeout`)"
                 ),
             });
+        }
     };
 
     let extra_error = |line_reader: &mut LineReader, prefix| {
@@ -406,6 +478,109 @@ fn parse_fail_sql(line_reader: &mut LineReader) -> Result<FailSqlCommand, PosErr
     })
 }
 
+fn parse_expected_error(expected_error: &str) -> Option<SqlExpectedError> {
+    if let Some(e) = expected_error.strip_prefix("regex:") {
+        Some(SqlExpectedError::Regex(e.trim().into()))
+    } else if let Some(e) = expected_error.strip_prefix("contains:") {
+        Some(SqlExpectedError::Contains(e.trim().into()))
+    } else if let Some(e) = expected_error.strip_prefix("exact:") {
+        Some(SqlExpectedError::Exact(e.trim().into()))
+    } else if expected_error == "timeout" {
+        Some(SqlExpectedError::Timeout)
+    } else {
+        None
+    }
+}
+
+/// Parses the input of a `sql-parallel` block.
+///
+/// The input consists of one SQL statement per line, followed by a `----`
+/// separator and the expected outcomes. Each expectation line has the form
+/// `N: OUTCOME`, where `N` is the 1-based index of the statement and `OUTCOME`
+/// is one of `ok`, `error <match specifier>` or `rows`. A `rows` outcome is
+/// followed by its expected rows, one per line, in the same format as the
+/// expected rows of a `>` command. A statement may have multiple expectation
+/// lines, in which case any of them is accepted.
+pub fn parse_sql_parallel(input: &[String]) -> Result<SqlParallelBlock, anyhow::Error> {
+    static OUTCOME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+):\s*(.*)$").unwrap());
+
+    let Some(separator) = input.iter().position(|line| line.trim() == "----") else {
+        bail!("sql-parallel block is missing the `----` separator before its expected outcomes");
+    };
+    let mut statements: Vec<_> = input[..separator]
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|query| SqlParallelStatement {
+            query: query.to_owned(),
+            expected_outcomes: Vec::new(),
+        })
+        .collect();
+    if statements.is_empty() {
+        bail!("sql-parallel block has no statements");
+    }
+
+    // The statement and outcome that subsequent row lines are appended to.
+    let mut current_rows: Option<(usize, usize)> = None;
+    for line in &input[separator + 1..] {
+        let Some(captures) = OUTCOME_REGEX.captures(line) else {
+            let Some((idx, outcome)) = current_rows else {
+                bail!("unexpected line in sql-parallel expectations: {}", line);
+            };
+            let row = split_line(0, line).map_err(|e| e.source)?;
+            match &mut statements[idx].expected_outcomes[outcome] {
+                SqlParallelOutcome::Rows(rows) => rows.push(row),
+                _ => unreachable!("current_rows always points at a rows outcome"),
+            }
+            continue;
+        };
+        let n: usize = captures[1].parse()?;
+        if n == 0 || n > statements.len() {
+            bail!(
+                "sql-parallel expectation refers to statement {}, but there are only {} statements",
+                n,
+                statements.len()
+            );
+        }
+        let spec = captures[2].trim();
+        let outcome = if spec == "ok" {
+            SqlParallelOutcome::Ok
+        } else if spec == "rows" {
+            SqlParallelOutcome::Rows(Vec::new())
+        } else if let Some(expected_error) = spec.strip_prefix("error ") {
+            match parse_expected_error(expected_error.trim()) {
+                Some(SqlExpectedError::Timeout) => {
+                    bail!("sql-parallel blocks do not support `timeout` expectations, use the `deadline` argument instead")
+                }
+                Some(expected_error) => SqlParallelOutcome::Error(expected_error),
+                None => bail!(
+                    "expected error must start with match specifier (`regex:`|`contains:`|`exact:`): {}",
+                    expected_error
+                ),
+            }
+        } else {
+            bail!(
+                "unknown sql-parallel outcome {:?}, expected `ok`, `error` or `rows`",
+                spec
+            );
+        };
+        let expected_outcomes = &mut statements[n - 1].expected_outcomes;
+        current_rows = match outcome {
+            SqlParallelOutcome::Rows(_) => Some((n - 1, expected_outcomes.len())),
+            _ => None,
+        };
+        expected_outcomes.push(outcome);
+    }
+
+    if let Some(n) = statements
+        .iter()
+        .position(|stmt| stmt.expected_outcomes.is_empty())
+    {
+        bail!("sql-parallel statement {} has no expected outcome", n + 1);
+    }
+    Ok(SqlParallelBlock { statements })
+}
+
 fn split_line(pos: usize, line: &str) -> Result<Vec<String>, PosError> {
     let mut out = Vec::new();
     let mut field = String::new();
@@ -8,4 +8,5 @@
 // by the Apache License, Version 2.0.
 
 pub mod postgres;
+pub mod redact;
 pub mod text;
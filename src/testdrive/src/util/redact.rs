@@ -0,0 +1,150 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Redaction of sensitive values from testdrive output.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use regex::Regex;
+
+/// Variables whose values are redacted regardless of the configured patterns.
+const SENSITIVE_VARS: &[&str] = &["testdrive.aws-secret-access-key", "testdrive.aws-token"];
+
+/// Values shorter than this are not redacted, as scrubbing every occurrence
+/// of them would mangle unrelated output.
+const MIN_REDACTED_LEN: usize = 4;
+
+/// Scrubs the values of sensitive variables from testdrive output, replacing
+/// each occurrence with `[REDACTED:<var-name>]`.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    /// Patterns matching the names of sensitive variables.
+    patterns: Vec<Regex>,
+    /// The sensitive values to redact, by the name of their variable.
+    values: BTreeMap<String, String>,
+}
+
+impl Redactor {
+    /// Creates a redactor for variables whose names match any of the given
+    /// case-insensitive glob patterns, where `*` matches any sequence of
+    /// characters.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, anyhow::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                let re = format!("(?i)^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+                Regex::new(&re).with_context(|| format!("invalid redaction pattern {pattern}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Redactor {
+            patterns,
+            values: BTreeMap::new(),
+        })
+    }
+
+    /// Reports whether the variable with the given name holds a sensitive
+    /// value.
+    pub fn is_sensitive(&self, name: &str) -> bool {
+        SENSITIVE_VARS.contains(&name) || self.patterns.iter().any(|re| re.is_match(name))
+    }
+
+    /// Starts redacting the value of the named variable, if it's sensitive.
+    pub fn track(&mut self, name: &str, value: &str) {
+        if self.is_sensitive(name) {
+            self.track_sensitive(name, value);
+        }
+    }
+
+    /// Starts redacting the value of the named variable, regardless of its
+    /// name.
+    pub fn track_sensitive(&mut self, name: &str, value: &str) {
+        if value.len() >= MIN_REDACTED_LEN {
+            self.values.insert(name.to_owned(), value.to_owned());
+        } else {
+            self.values.remove(name);
+        }
+    }
+
+    /// Replaces all occurrences of sensitive values in `s`.
+    pub fn redact<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        // Replace longer values first, so that values containing other values
+        // are redacted as a whole.
+        let mut values: Vec<_> = self.values.iter().collect();
+        values.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+
+        let mut s = Cow::Borrowed(s);
+        for (name, value) in values {
+            if s.contains(value.as_str()) {
+                s = Cow::Owned(s.replace(value.as_str(), &format!("[REDACTED:{name}]")));
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use mz_ore::error::ErrorExt;
+
+    use crate::error::PosError;
+
+    use super::*;
+
+    #[mz_ore::test]
+    fn redact_sensitive_vars() {
+        let mut redactor = Redactor::new(&["*PASSWORD*", "*SECRET*"]).unwrap();
+        redactor.track("env.FAKE_SECRET", "hunter2hunter2");
+        redactor.track("env.DB_Password", "swordfish");
+        redactor.track("testdrive.aws-secret-access-key", "AKIAFAKEFAKE");
+        redactor.track("testdrive.aws-token", "");
+        redactor.track("env.PATH", "/usr/bin");
+        redactor.track("env.SHORT_SECRET", "abc");
+
+        let msg = "CREATE SECRET s AS 'hunter2hunter2' -- swordfish AKIAFAKEFAKE /usr/bin abc";
+        assert_eq!(
+            redactor.redact(msg),
+            "CREATE SECRET s AS '[REDACTED:env.FAKE_SECRET]' -- [REDACTED:env.DB_Password] \
+             [REDACTED:testdrive.aws-secret-access-key] /usr/bin abc"
+        );
+        assert!(matches!(
+            redactor.redact("nothing to see"),
+            Cow::Borrowed(_)
+        ));
+
+        // Longer values are redacted before the values they contain.
+        redactor.track_sensitive("testdrive.cert-password", "swordfish-and-chips");
+        assert_eq!(
+            redactor.redact("swordfish-and-chips swordfish"),
+            "[REDACTED:testdrive.cert-password] [REDACTED:env.DB_Password]"
+        );
+    }
+
+    #[mz_ore::test]
+    fn redact_pos_error() {
+        let mut redactor = Redactor::new(&["*TOKEN*"]).unwrap();
+        redactor.track("env.FAKE_TOKEN", "not-a-real-token");
+
+        // An error echoing the substituted command, as e.g. a failed query
+        // does.
+        let err = PosError::new(
+            anyhow!("executing query failed").context("> SELECT 'not-a-real-token'"),
+            42,
+        );
+        let err = err.redact(&redactor);
+        let rendered = err.source.display_with_causes().to_string();
+        assert!(rendered.contains("[REDACTED:env.FAKE_TOKEN]"), "{rendered}");
+        assert!(!rendered.contains("not-a-real-token"), "{rendered}");
+        assert!(rendered.contains("executing query failed"), "{rendered}");
+        assert_eq!(err.pos, Some(42));
+    }
+}
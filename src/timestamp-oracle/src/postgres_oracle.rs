@@ -267,6 +267,14 @@ impl PostgresClientKnobs for PostgresTimestampOracleConfig {
     fn tcp_user_timeout(&self) -> Duration {
         self.dynamic.tcp_user_timeout()
     }
+
+    fn statement_timeout(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn tcp_keepalive(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Updates to values in [`PostgresTimestampOracleConfig`].